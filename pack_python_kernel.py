@@ -150,9 +150,13 @@ def build_executable(python_exe):
         "simulation.power_calculation.interface",
         "simulation.power_calculation.implementations",
         "simulation.power_calculation.implementations.pandapower_impl",
+        "simulation.power_calculation.implementations.pandapower_3ph_impl",
+        "simulation.power_calculation.implementations.simplified_impl",
         "simulation.power_calculation.implementations.pypsa_impl",
         "simulation.power_calculation.implementations.gridcal_impl",
         "simulation.historical_data",
+        "transport",
+        "shared_results",
         "ai",
         "ai.factory",
         "ai.interface",
@@ -190,6 +194,7 @@ def build_executable(python_exe):
         "scipy.optimize",
         "scipy.linalg",
         "networkx",
+        "msgpack",
     ]
     
     for module in hidden_imports:
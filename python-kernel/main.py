@@ -2,14 +2,25 @@
 # -*- coding: utf-8 -*-
 """
 Python 内核服务入口
-提供 JSON-RPC over stdio 接口，供 Rust 调用
+提供 JSON-RPC over stdio 接口，供 Rust 调用；
+以 --listen tcp://主机:端口 或 ws://主机:端口/路径 启动时改为在该端点监听（须以 --token 或 PYTHON_KERNEL_TOKEN 配置共享令牌，见 transport.py）
+日志输出到 stderr（由 Rust 端写入 python-kernel.log）：logging 记录带 [级别] 前缀，
+级别由环境变量 PYTHON_KERNEL_LOG_LEVEL 指定，运行中可经 kernel.set_log_level 调整；
+应用退出时 Rust 端发送 kernel.shutdown：停止仿真后结束服务，进程正常退出；
+以 --check-environment 启动时只输出运行环境与依赖版本（JSON）后退出，供 Rust 端启动前自检；
+simulation.perform_calculation 带 result_file 参数时，较大的结果写入共享文件、只返回描述符（见 shared_results.py）
 
 注意：为了与 PyInstaller 兼容，所有导入都在顶部完成
 """
 
 import sys
-import json
 import os
+import json
+import logging
+import platform
+import re
+import importlib
+import importlib.metadata
 
 # 确保 PyInstaller 打包后能找到模块
 if getattr(sys, 'frozen', False):
@@ -23,11 +34,25 @@ else:
 
 
 # 在顶部导入所有需要的模块（PyInstaller 兼容）
-from typing import Dict, Any
+from typing import Dict, Any, Optional
+import transport
+import shared_results
 
 # 延迟导入重型模块的标志
 _simulation_engine = None
-_power_calculator = None
+_power_calculators: Dict[str, Any] = {}
+# 大结果共享文件的写入端（首次使用时打开映射）
+_result_writer = shared_results.SharedResultWriter()
+
+LOG_LEVELS = ("DEBUG", "INFO", "WARNING", "ERROR")
+LOG_FORMAT = "[%(levelname)s] %(name)s: %(message)s"
+# 依赖的最低版本（与 requirements.txt 一致）；可选依赖缺失只影响部分功能
+REQUIRED_PACKAGES = (
+    ("pandapower", "3.1.2", False),
+    ("numpy", "1.24.0", False),
+    ("pandas", "2.0.0", False),
+    ("msgpack", "1.0.0", True),
+)
 
 
 def get_simulation_engine():
@@ -39,13 +64,16 @@ def get_simulation_engine():
     return _simulation_engine
 
 
-def get_power_calculator():
-    """获取功率计算器实例（延迟初始化）"""
-    global _power_calculator
-    if _power_calculator is None:
-        from simulation.power_calculation.factory import PowerKernelFactory
-        _power_calculator = PowerKernelFactory.create("pandapower")
-    return _power_calculator
+def get_power_calculator(kernel_type: Optional[str] = None):
+    """获取功率计算器实例（按内核类型延迟初始化并缓存；None 为默认内核）"""
+    from simulation.power_calculation.factory import PowerKernelFactory, DEFAULT_KERNEL
+    kernel_type = kernel_type or DEFAULT_KERNEL
+    if kernel_type not in _power_calculators:
+        calculator = PowerKernelFactory.create(kernel_type)
+        if calculator is None:
+            return None
+        _power_calculators[kernel_type] = calculator
+    return _power_calculators[kernel_type]
 
 
 def handle_request(request: Dict[str, Any]) -> Dict[str, Any]:
@@ -57,6 +85,12 @@ def handle_request(request: Dict[str, Any]) -> Dict[str, Any]:
     try:
         if method == "ping":
             result = {"status": "ok"}
+        elif method == "kernel.set_log_level":
+            result = {"status": "ok", "level": set_log_level(params.get("level", "INFO"))}
+        elif method == "kernel.shutdown":
+            result = shutdown()
+        elif method == "kernel.environment":
+            result = environment_report()
         elif method.startswith("simulation."):
             result = handle_simulation(method, params)
         elif method.startswith("power."):
@@ -88,13 +122,20 @@ def handle_simulation(method: str, params: Dict[str, Any]) -> Dict[str, Any]:
     
     if method == "simulation.start":
         calculation_interval_ms = params.get("calculation_interval_ms", 1000)
+        time_scale = params.get("time_scale", 1.0)
         try:
-            engine.start(calculation_interval_ms=calculation_interval_ms)
+            engine.start(
+                calculation_interval_ms=calculation_interval_ms,
+                time_scale=time_scale,
+                solver_options=params.get("solver_options"),
+            )
             return {"status": "started"}
         except Exception as e:
             return {"status": "error", "message": str(e)}
     elif method == "simulation.stop":
         try:
+            if "keep_warm" in params:
+                engine.keep_warm = bool(params.get("keep_warm"))
             engine.stop()
             return {"status": "stopped"}
         except Exception as e:
@@ -116,8 +157,18 @@ def handle_simulation(method: str, params: Dict[str, Any]) -> Dict[str, Any]:
         if not topology_data:
             return {"status": "error", "message": "拓扑数据未提供"}
         try:
-            engine.set_topology(topology_data)
-            return {"status": "ok"}
+            engine.set_topology(topology_data, params.get("kernel_type"))
+            return {"status": "ok", "kernel_type": engine.kernel_type}
+        except Exception as e:
+            return {"status": "error", "message": str(e)}
+    elif method == "simulation.preload_topology":
+        topology_data = params.get("topology_data")
+        if not topology_data:
+            return {"status": "error", "message": "拓扑数据未提供"}
+        try:
+            return engine.preload_topology(
+                topology_data, warm_up=params.get("warm_up", True), kernel_type=params.get("kernel_type")
+            )
         except Exception as e:
             return {"status": "error", "message": str(e)}
     elif method == "simulation.set_device_mode":
@@ -132,8 +183,16 @@ def handle_simulation(method: str, params: Dict[str, Any]) -> Dict[str, Any]:
         device_id = params.get("device_id")
         min_power = params.get("min_power")
         max_power = params.get("max_power")
+        external = bool(params.get("external", False))
+        seed = params.get("seed")
+        try:
+            engine.set_device_random_config(device_id, min_power, max_power, external, seed)
+            return {"status": "ok"}
+        except Exception as e:
+            return {"status": "error", "message": str(e)}
+    elif method == "simulation.set_device_random_value":
         try:
-            engine.set_device_random_config(device_id, min_power, max_power)
+            engine.set_device_random_value(params.get("device_id"), params.get("p_kw", 0.0))
             return {"status": "ok"}
         except Exception as e:
             return {"status": "error", "message": str(e)}
@@ -154,6 +213,32 @@ def handle_simulation(method: str, params: Dict[str, Any]) -> Dict[str, Any]:
             return {"status": "ok"}
         except Exception as e:
             return {"status": "error", "message": str(e)}
+    elif method == "simulation.set_device_solar_value":
+        try:
+            engine.set_device_solar_value(params.get("device_id"), params.get("p_kw", 0.0))
+            return {"status": "ok"}
+        except Exception as e:
+            return {"status": "error", "message": str(e)}
+    elif method == "simulation.set_device_weather_value":
+        try:
+            engine.set_device_weather_value(params.get("device_id"), params.get("p_kw", 0.0))
+            return {"status": "ok"}
+        except Exception as e:
+            return {"status": "error", "message": str(e)}
+    elif method == "simulation.set_device_ev_value":
+        try:
+            engine.set_device_ev_value(params.get("device_id"), params.get("p_kw", 0.0))
+            return {"status": "ok"}
+        except Exception as e:
+            return {"status": "error", "message": str(e)}
+    elif method == "simulation.set_device_historical_value":
+        try:
+            engine.set_device_historical_value(
+                params.get("device_id"), params.get("p_kw", 0.0), params.get("q_kvar", 0.0)
+            )
+            return {"status": "ok"}
+        except Exception as e:
+            return {"status": "error", "message": str(e)}
     elif method == "simulation.set_device_sim_params":
         device_id = params.get("device_id")
         sim_params = params.get("params") or {}
@@ -162,6 +247,63 @@ def handle_simulation(method: str, params: Dict[str, Any]) -> Dict[str, Any]:
             return {"status": "ok"}
         except Exception as e:
             return {"status": "error", "message": str(e)}
+    elif method == "simulation.set_device_reactive_control":
+        device_id = params.get("device_id")
+        config = params.get("config") or {}
+        try:
+            engine.set_device_reactive_control(device_id, config)
+            return {"status": "ok"}
+        except Exception as e:
+            return {"status": "error", "message": str(e)}
+    elif method == "simulation.set_grid_frequency":
+        try:
+            engine.set_grid_frequency(
+                params.get("frequency_hz", 50.0),
+                params.get("islanded", False),
+                params.get("nominal_hz"),
+            )
+            return {"status": "ok"}
+        except Exception as e:
+            return {"status": "error", "message": str(e)}
+    elif method == "simulation.get_grid_boundary":
+        try:
+            return {"status": "ok", **engine.get_grid_boundary()}
+        except Exception as e:
+            return {"status": "error", "message": str(e)}
+    elif method == "simulation.set_solver_options":
+        try:
+            engine.set_solver_options(params.get("options"))
+            return {"status": "ok"}
+        except Exception as e:
+            return {"status": "error", "message": str(e)}
+    elif method == "simulation.set_ext_grid_voltage":
+        try:
+            engine.set_ext_grid_voltage(params.get("vm_pu"))
+            return {"status": "ok"}
+        except Exception as e:
+            return {"status": "error", "message": str(e)}
+    elif method == "simulation.inject_fault":
+        try:
+            return engine.inject_fault(
+                params.get("device_id"),
+                params.get("fault_type"),
+                params.get("fault_resistance_ohm", 5.0),
+            )
+        except Exception as e:
+            return {"status": "error", "message": str(e)}
+    elif method == "simulation.clear_fault":
+        try:
+            return {"status": "ok", "cleared": engine.clear_fault(params.get("device_id"))}
+        except Exception as e:
+            return {"status": "error", "message": str(e)}
+    elif method == "simulation.set_device_pf_response":
+        device_id = params.get("device_id")
+        config = params.get("config") or {}
+        try:
+            engine.set_device_pf_response(device_id, config)
+            return {"status": "ok"}
+        except Exception as e:
+            return {"status": "error", "message": str(e)}
     elif method == "simulation.update_switch_state":
         device_id = params.get("device_id")
         is_closed = params.get("is_closed", True)
@@ -170,6 +312,24 @@ def handle_simulation(method: str, params: Dict[str, Any]) -> Dict[str, Any]:
             return {"status": "ok"}
         except Exception as e:
             return {"status": "error", "message": str(e)}
+    elif method == "simulation.set_grid_support_setpoint":
+        try:
+            engine.set_grid_support_setpoint(params.get("device_id"), params.get("q_kvar"), params.get("p_limit_kw"))
+            return {"status": "ok"}
+        except Exception as e:
+            return {"status": "error", "message": str(e)}
+    elif method == "simulation.set_droop_setpoint":
+        try:
+            engine.set_droop_setpoint(params.get("device_id"), params.get("droop_kw"))
+            return {"status": "ok"}
+        except Exception as e:
+            return {"status": "error", "message": str(e)}
+    elif method == "simulation.set_transformer_tap":
+        try:
+            engine.set_transformer_tap(params.get("device_id"), params.get("tap_pos", 0))
+            return {"status": "ok"}
+        except Exception as e:
+            return {"status": "error", "message": str(e)}
     elif method == "simulation.update_device_properties":
         device_id = params.get("device_id")
         properties = params.get("properties") or {}
@@ -185,6 +345,22 @@ def handle_simulation(method: str, params: Dict[str, Any]) -> Dict[str, Any]:
             return data
         except Exception as e:
             return {"status": "error", "message": str(e)}
+    elif method == "simulation.get_element_tables":
+        try:
+            return engine.get_element_tables()
+        except Exception as e:
+            return {"status": "error", "message": str(e)}
+    elif method == "simulation.export_state":
+        try:
+            return {"state": engine.export_state()}
+        except Exception as e:
+            return {"status": "error", "message": str(e)}
+    elif method == "simulation.import_state":
+        try:
+            engine.import_state(params.get("state") or {})
+            return {"status": "ok"}
+        except Exception as e:
+            return {"status": "error", "message": str(e)}
     elif method == "simulation.get_calculation_status":
         try:
             status = engine.get_calculation_status()
@@ -205,7 +381,17 @@ def handle_simulation(method: str, params: Dict[str, Any]) -> Dict[str, Any]:
             return {"status": "error", "message": str(e)}
     elif method == "simulation.perform_calculation":
         try:
-            result = engine.perform_calculation()
+            result = engine.perform_calculation(
+                force=bool(params.get("force", False)),
+                sim_time=params.get("sim_time"),
+            )
+            result_file = params.get("result_file")
+            if result_file:
+                descriptor = _result_writer.write(
+                    result, result_file["path"], int(result_file.get("min_bytes", 0))
+                )
+                if descriptor is not None:
+                    return {"result_ref": descriptor}
             return {"result": result}
         except Exception as e:
             return {"status": "error", "message": str(e)}
@@ -215,7 +401,12 @@ def handle_simulation(method: str, params: Dict[str, Any]) -> Dict[str, Any]:
 
 def handle_power_calculation(method: str, params: Dict[str, Any]) -> Dict[str, Any]:
     """处理功率计算相关请求"""
-    calculator = get_power_calculator()
+    if method == "power.list_kernels":
+        # 能力发现不依赖默认内核可用（pandapower 未安装时也能报告原因）
+        from simulation.power_calculation.factory import PowerKernelFactory
+        return {"kernels": PowerKernelFactory.list_kernels(), "current": get_simulation_engine().kernel_type}
+    # 快照类请求（含内核池任务）按请求指定的内核求解，与仿真实例选用的内核一致
+    calculator = get_power_calculator(params.get("kernel_type"))
     if not calculator:
         return {"error": "Power calculation kernel not available"}
     
@@ -223,12 +414,77 @@ def handle_power_calculation(method: str, params: Dict[str, Any]) -> Dict[str, A
         topology_data = params.get("topology_data", {})
         result = calculator.calculate_power_flow(topology_data)
         return result
+    elif method == "power.snapshot":
+        from simulation.snapshot import run_snapshot_powerflow
+        topology_data = params.get("topology_data")
+        if not topology_data:
+            return {"status": "error", "message": "拓扑数据未提供"}
+        return run_snapshot_powerflow(calculator, topology_data, params.get("assumptions"), params.get("out_of_service"))
     else:
         return {"status": "not_implemented"}
 
 
+def set_log_level(level: str) -> str:
+    """设置根 logger 级别（含 pandapower 等依赖库的日志），返回生效的级别"""
+    level = str(level).upper()
+    if level not in LOG_LEVELS:
+        raise ValueError(f"不支持的日志级别: {level}")
+    logging.getLogger().setLevel(level)
+    return level
+
+
+def shutdown() -> Dict[str, Any]:
+    """停止仿真（等待计算线程结束）；响应发出后由 transport 结束服务"""
+    if _simulation_engine is not None:
+        try:
+            _simulation_engine.stop()
+        except Exception as e:
+            print(f"关闭时停止仿真失败: {e}", file=sys.stderr)
+    print("Python kernel shutting down", file=sys.stderr)
+    sys.stderr.flush()
+    return {"status": "ok"}
+
+
+def environment_report() -> Dict[str, Any]:
+    """运行环境与依赖版本：逐个导入依赖并与最低版本比较"""
+    packages = []
+    for name, minimum, optional in REQUIRED_PACKAGES:
+        entry = {"name": name, "minimum": minimum, "optional": optional, "version": None, "ok": False, "error": None}
+        try:
+            module = importlib.import_module(name)
+            version = getattr(module, "__version__", None) or importlib.metadata.version(name)
+            entry["version"] = str(version)
+            entry["ok"] = _version_tuple(str(version)) >= _version_tuple(minimum)
+        except Exception as e:
+            entry["error"] = f"{type(e).__name__}: {e}"
+        packages.append(entry)
+    return {
+        "python_version": platform.python_version(),
+        "executable": sys.executable,
+        "frozen": bool(getattr(sys, "frozen", False)),
+        "packages": packages,
+    }
+
+
+def _version_tuple(version: str) -> tuple:
+    """取前三段的数字部分（3.1.2rc1 -> (3, 1, 2)）"""
+    parts = []
+    for part in version.split(".")[:3]:
+        match = re.match(r"\d+", part)
+        parts.append(int(match.group()) if match else 0)
+    return tuple(parts + [0] * (3 - len(parts)))
+
+
 def main():
     """主函数"""
+    if "--check-environment" in sys.argv[1:]:
+        print(json.dumps(environment_report(), ensure_ascii=False))
+        return
+    logging.basicConfig(stream=sys.stderr, format=LOG_FORMAT, level=logging.INFO)
+    try:
+        set_log_level(os.environ.get("PYTHON_KERNEL_LOG_LEVEL", "INFO"))
+    except ValueError as e:
+        print(f"{e}，使用 INFO", file=sys.stderr)
     # 输出启动信息到 stderr（会写入日志文件）
     print(f"Python kernel starting...", file=sys.stderr)
     print(f"Python version: {sys.version}", file=sys.stderr)
@@ -236,45 +492,32 @@ def main():
     print(f"Frozen: {getattr(sys, 'frozen', False)}", file=sys.stderr)
     sys.stderr.flush()
     
-    # 从 stdin 读取 JSON-RPC 请求
-    for line in sys.stdin:
-        line = line.strip()
-        if not line:
-            continue
-        
-        try:
-            request = json.loads(line)
-            method = request.get("method", "unknown")
-            req_id = request.get("id")
-            response = handle_request(request)
-            # 输出响应到 stdout（使用 ensure_ascii=False 避免 \uXXXX 转义产生 lone surrogate）
-            resp_json = json.dumps(response, ensure_ascii=False)
-            print(resp_json)
-            sys.stdout.flush()
-        except json.JSONDecodeError as e:
-            error_response = {
-                "jsonrpc": "2.0",
-                "id": None,
-                "error": {
-                    "code": -32700,
-                    "message": f"Parse error: {e}"
-                }
-            }
-            print(json.dumps(error_response, ensure_ascii=False))
-            sys.stdout.flush()
-        except Exception as e:
-            import traceback
-            traceback.print_exc(file=sys.stderr)
-            error_response = {
-                "jsonrpc": "2.0",
-                "id": None,
-                "error": {
-                    "code": -32000,
-                    "message": str(e)
-                }
-            }
-            print(json.dumps(error_response, ensure_ascii=False))
-            sys.stdout.flush()
+    listen_url = _listen_url()
+    if listen_url:
+        transport.serve(listen_url, handle_request, _listen_token())
+    else:
+        # 从 stdin 读取 JSON-RPC 请求，响应输出到 stdout
+        transport.serve_stdio(handle_request)
+
+
+def _listen_url() -> Optional[str]:
+    """命令行 --listen <地址> 或环境变量 PYTHON_KERNEL_LISTEN"""
+    args = sys.argv[1:]
+    if "--listen" in args:
+        index = args.index("--listen")
+        if index + 1 < len(args):
+            return args[index + 1]
+    return os.environ.get("PYTHON_KERNEL_LISTEN") or None
+
+
+def _listen_token() -> Optional[str]:
+    """远程端点的共享令牌：命令行 --token <令牌> 或环境变量 PYTHON_KERNEL_TOKEN"""
+    args = sys.argv[1:]
+    if "--token" in args:
+        index = args.index("--token")
+        if index + 1 < len(args):
+            return args[index + 1]
+    return os.environ.get("PYTHON_KERNEL_TOKEN") or None
 
 
 if __name__ == "__main__":
@@ -0,0 +1,80 @@
+#!/usr/bin/env python3
+# -*- coding: utf-8 -*-
+"""
+大结果共享文件传输：本地内核的单步计算结果较大时，写入 Rust 端指定的内存映射文件，
+JSON-RPC 只返回描述符（路径、序号、长度、编码），省去每步数 MB 消息经管道传输与逐字节解析。
+文件布局：头部 20 字节（小端：魔数 b"PVSR"、序号 u64、数据长度 u64）+ 结果数据；
+数据为 MessagePack（未安装 msgpack 时为 JSON）。文件只增不缩，由 Rust 端创建与删除。
+"""
+
+import json
+import mmap
+import struct
+from typing import Any, Dict, Optional
+
+try:
+    import msgpack
+except ImportError:  # 可选依赖
+    msgpack = None
+
+MAGIC = b"PVSR"
+HEADER = struct.Struct("<4sQQ")
+# 映射区最小容量，避免小幅增长时反复重映射
+MIN_CAPACITY = 1 << 20
+
+
+class SharedResultWriter:
+    """按 Rust 端下发的路径写入结果；路径变化（Rust 端重建文件）时重新映射"""
+
+    def __init__(self):
+        self._path: Optional[str] = None
+        self._file = None
+        self._map: Optional[mmap.mmap] = None
+        self._sequence = 0
+
+    def write(self, result: Any, path: str, min_bytes: int = 0) -> Optional[Dict[str, Any]]:
+        """写入结果并返回描述符；编码后不足 min_bytes 时不写入、返回 None（由调用方内联返回）"""
+        if msgpack is not None:
+            encoding = "msgpack"
+            payload = msgpack.packb(result, use_bin_type=True)
+        else:
+            encoding = "json"
+            payload = json.dumps(result, ensure_ascii=False).encode("utf-8")
+        if len(payload) < min_bytes:
+            return None
+        self._ensure_mapped(path, HEADER.size + len(payload))
+        self._sequence += 1
+        # 先写数据再写头部：Rust 端按头部的序号与长度校验，读到的不会是半写入的数据
+        self._map[HEADER.size:HEADER.size + len(payload)] = payload
+        self._map[:HEADER.size] = HEADER.pack(MAGIC, self._sequence, len(payload))
+        return {
+            "path": path,
+            "sequence": self._sequence,
+            "length": len(payload),
+            "encoding": encoding,
+        }
+
+    def close(self) -> None:
+        if self._map is not None:
+            self._map.close()
+            self._map = None
+        if self._file is not None:
+            self._file.close()
+            self._file = None
+        self._path = None
+
+    def _ensure_mapped(self, path: str, size: int) -> None:
+        if path != self._path:
+            self.close()
+            self._file = open(path, "r+b")
+            self._path = path
+        capacity = len(self._map) if self._map is not None else 0
+        if capacity >= size:
+            return
+        capacity = max(size, capacity * 2, MIN_CAPACITY)
+        # Windows 上存在映射时不能改变文件大小，先解除映射
+        if self._map is not None:
+            self._map.close()
+            self._map = None
+        self._file.truncate(capacity)
+        self._map = mmap.mmap(self._file.fileno(), capacity)
@@ -484,6 +484,7 @@ class PandapowerTopologyAdapter(TopologyAdapter):
                 name=device.get("name", device_id)
             )
             device_map["transformers"][device_id] = trafo_idx
+            self._apply_tap_parameters(net, trafo_idx, properties)
         except Exception as e:
             # 如果标准类型不存在，尝试使用默认类型
             try:
@@ -496,6 +497,7 @@ class PandapowerTopologyAdapter(TopologyAdapter):
                     name=device.get("name", device_id)
                 )
                 device_map["transformers"][device_id] = trafo_idx
+                self._apply_tap_parameters(net, trafo_idx, properties)
                 warnings.append(AdapterError(
                     error_type="adapter",
                     severity="warning",
@@ -511,6 +513,21 @@ class PandapowerTopologyAdapter(TopologyAdapter):
                     details={"sn_mva": sn_mva, "vn_hv_kv": vn_hv_kv, "vn_lv_kv": vn_lv_kv}
                 ))
     
+    # 变压器分接头参数：properties 中配置时覆盖标准类型的对应值（tap_pos 为当前档位，有载调压运行中由 Rust 端更新）
+    _TAP_PARAMETERS = (("tap_min", int), ("tap_max", int), ("tap_neutral", int), ("tap_pos", int),
+                       ("tap_step_percent", float), ("tap_side", str))
+
+    def _apply_tap_parameters(self, net, trafo_idx: int, properties: Dict[str, Any]) -> None:
+        for key, cast in self._TAP_PARAMETERS:
+            value = properties.get(key)
+            if value is None or value == "":
+                continue
+            try:
+                value = str(value).strip().lower() if cast is str else cast(float(value))
+            except (TypeError, ValueError):
+                continue
+            net.trafo.at[trafo_idx, key] = value
+
     def _create_switch(self, net, device_id: str, device: Dict[str, Any],
                      bus: int, element: int, et: str,
                      device_map: Dict[str, Dict[str, int]],
@@ -714,11 +731,17 @@ class PandapowerTopologyAdapter(TopologyAdapter):
             except ValueError:
                 vn_kv = 10.0
         
+        # 平衡节点电压（pu），局部仿真的边界等值可指定
+        try:
+            vm_pu = float(properties.get("vm_pu") or 1.0)
+        except (TypeError, ValueError):
+            vm_pu = 1.0
+        
         try:
             self.pp.create_ext_grid(
                 net,
                 bus=bus,
-                vm_pu=1.0,
+                vm_pu=vm_pu,
                 name=device.get("name", device_id)
             )
         except Exception as e:
@@ -8,7 +8,7 @@ import time
 import hashlib
 import json
 from typing import Dict, Any, List, Optional
-from .power_calculation.factory import PowerKernelFactory
+from .power_calculation.factory import DEFAULT_KERNEL, PowerKernelFactory
 from .adapters.pandapower_adapter import PandapowerTopologyAdapter
 from .adapters.topology_adapter import AdapterError
 
@@ -27,6 +27,7 @@ class SimulationEngine:
         # 计算内核和适配器
         self.power_calculator = None
         self.topology_adapter = None
+        self.kernel_type = DEFAULT_KERNEL
         
         # 网络对象缓存
         self.cached_network = None  # 缓存的pandapower网络对象
@@ -36,6 +37,8 @@ class SimulationEngine:
         # 周期性计算相关
         self.calculation_thread: Optional[threading.Thread] = None
         self.calculation_interval_ms = 1000  # 默认1秒
+        # 时间倍率：每步仿真时间 = 计算步长 × time_scale（加速仿真时历史回放、响应延迟按仿真时间推进）
+        self.time_scale = 1.0
         self.calculation_count = 0
         self.last_calculation_time = 0.0
         self.calculation_errors: List[Dict[str, Any]] = []
@@ -43,9 +46,14 @@ class SimulationEngine:
         # 计算结果缓存
         self.last_calculation_result: Optional[Dict[str, Any]] = None
 
+        # 潮流求解参数（如 algorithm / max_iteration / tolerance_mva），由计算预设下发，空表示内核默认
+        self.solver_options: Dict[str, Any] = {}
+
         # 随机模式设备配置：device_id -> {"min_power": float, "max_power": float}（单位 kW）
         self.device_random_config: Dict[str, Dict[str, float]] = {}
-        # 设备模式（manual / random_data / historical_data），用于统一后端更新时按模式写 properties
+        # 随机模式设备独立的随机数发生器（配置了种子时可复现）：device_id -> random.Random
+        self.device_random_rngs: Dict[str, random.Random] = {}
+        # 设备模式（manual / random_data / historical_data / solar_model / weather_model / ev_session），用于统一后端更新时按模式写 properties
         self.device_modes: Dict[str, str] = {}
         # 手动模式当前设定：device_id -> {"p_kw": float, "q_kvar": float}（单位 kW/kVar）
         self.device_manual_setpoint: Dict[str, Dict[str, float]] = {}
@@ -64,23 +72,54 @@ class SimulationEngine:
         self.device_sim_params: Dict[str, Dict[str, float]] = {}
         # 设备响应延迟 pending 队列：device_id -> [{target_props, apply_at}]
         self.device_pending_commands: Dict[str, List[Dict[str, Any]]] = {}
-        # 仿真累计时间（秒），每步累加
+        # 仿真累计时间（秒），每步按传入仿真时间的增量累加
         self.sim_elapsed_seconds: float = 0.0
+        # 仿真时间（Unix 秒）：由 Rust 端每步随 perform_calculation 传入，结果时间戳取该值；未传入时取墙钟
+        self.sim_time: Optional[float] = None
+        # 本步仿真时间增量（秒），由相邻两步传入的 sim_time 求得；None 表示无可用增量，按步长 × 倍速估算
+        self.sim_step_seconds: Optional[float] = None
+
+        # 光伏电网支撑设定（Rust 端按 volt-var / volt-watt 曲线计算）：device_id -> {"q_kvar", "p_limit_kw"}
+        self.device_grid_support_setpoint: Dict[str, Dict[str, float]] = {}
+        # 储能下垂一次调频出力（Rust 端孤岛频率模型计算，kW，正=增加出力）：device_id -> droop_kw
+        self.device_droop_kw: Dict[str, float] = {}
+        # 孤岛频率模型：激活时按当前频率对光伏/储能应用 P(f) 响应曲线
+        self.frequency_model_active: bool = False
+        self.nominal_frequency_hz: float = 50.0
+        self.grid_frequency_hz: float = 50.0
+        # P(f) 有意延时：device_id -> 频率越出死区的仿真时刻（秒）
+        self.device_pf_excursion_start: Dict[str, float] = {}
+        # 外部电网电压边界条件（pu），None 表示使用拓扑中各外部电网自身的 vm_pu；用于符合性测试驱动电压跌落
+        self.ext_grid_vm_pu: Optional[float] = None
+        # 建网时各外部电网的 vm_pu（拓扑属性），清除电压边界条件时据此恢复
+        self.ext_grid_base_vm_pu = None
+        # 停止后是否保留已构建的网络（内核保温），关闭时停止即释放网络缓存
+        self.keep_warm: bool = True
+        # 注入的故障：device_id -> {"fault_type": "outage"|"short_circuit", "fault_resistance_ohm", "bus", "shunt_idx", "net_id"}
+        self.active_faults: Dict[str, Dict[str, Any]] = {}
     
-    def set_topology(self, topology_data: Dict[str, Any], kernel_type: str = "pandapower"):
+    def set_topology(self, topology_data: Dict[str, Any], kernel_type: Optional[str] = None):
         """
         设置拓扑数据
         
         Args:
             topology_data: 标准拓扑数据格式
-            kernel_type: 计算内核类型（默认pandapower）
+            kernel_type: 计算内核类型（见 PowerKernelFactory.list_kernels）；None 保持当前内核（初始为pandapower）
         """
+        kernel_type = kernel_type or self.kernel_type
+        kernel_changed = kernel_type != self.kernel_type
+        # 切换到不可用的内核时直接报错，保持原内核与拓扑不变
+        if kernel_changed:
+            reason = PowerKernelFactory.unavailable_reason(kernel_type)
+            if reason:
+                raise RuntimeError(f"计算内核不可用: {reason}")
+
         # 计算拓扑数据哈希值（只考虑结构，不考虑功率值等变化的数据）
         topology_structure = self._extract_topology_structure(topology_data)
         new_hash = self._calculate_topology_hash(topology_structure)
         
-        # 如果拓扑结构发生变化，清除缓存并重新创建网络
-        if new_hash != self.topology_hash:
+        # 如果拓扑结构或计算内核发生变化，清除缓存并重新创建网络（各内核会在网络上补充参数与结果表）
+        if new_hash != self.topology_hash or kernel_changed:
             self.topology_hash = new_hash
             self.cached_network = None
             self.cached_bus_map = {}
@@ -88,6 +127,7 @@ class SimulationEngine:
         
         self.topology_data = topology_data
         self.device_random_config.clear()
+        self.device_random_rngs.clear()
         self.device_modes.clear()
         self.device_manual_setpoint.clear()
         self.device_remote_setpoint.clear()
@@ -96,8 +136,12 @@ class SimulationEngine:
         self.device_historical_index.clear()
         self.device_historical_last_update.clear()
         self.device_sim_params.clear()
+        self.device_grid_support_setpoint.clear()
+        self.device_droop_kw.clear()
         self.device_pending_commands.clear()
         self.sim_elapsed_seconds = 0.0
+        self.sim_time = None
+        self.sim_step_seconds = None
 
         # 使用工厂同时创建计算内核和适配器（如果可用）
         try:
@@ -129,6 +173,7 @@ class SimulationEngine:
                 self.power_calculator = PowerKernelFactory.create(kernel_type)
                 if not self.power_calculator:
                     raise RuntimeError(f"无法创建功率计算内核: {kernel_type}")
+        self.kernel_type = kernel_type
     
     def _extract_topology_structure(self, topology_data: Dict[str, Any]) -> Dict[str, Any]:
         """
@@ -197,12 +242,15 @@ class SimulationEngine:
         """
         设置历史模式设备配置并创建 Provider 实例。
         config 中 sourceType='csv'|'sqlite'，filePath 等字段传给 Provider。
+        config.external=True 时曲线由 Rust 端插值后逐步下发（set_device_historical_value），内核不再回放。
         """
         from .historical_data import create_provider
         self.device_historical_config[device_id] = dict(config) if config else {}
         self.device_historical_index[device_id] = 0
         self.device_historical_last_update[device_id] = 0.0
-        if config:
+        if config and config.get("external"):
+            self.device_historical_providers.pop(device_id, None)
+        elif config:
             provider = create_provider(config)
             if provider:
                 self.device_historical_providers[device_id] = provider
@@ -222,15 +270,268 @@ class SimulationEngine:
             "measurementErrorPct": float(params.get("measurementErrorPct", 0)),
         }
 
-    def set_device_random_config(self, device_id: str, min_power: float, max_power: float) -> None:
+    def set_device_reactive_control(self, device_id: str, config: Dict[str, Any]) -> None:
+        """
+        设置光伏无功控制模式（控制器设定），写入设备 properties，下一拍计算生效。
+        config: {"mode": "none"|"fixed_pf"|"q_u"|"fixed_q", "power_factor": float,
+                 "q_kvar": float, "q_u_curve": [{"u_pu": float, "q_pct": float}, ...]}
+        """
+        if not self.topology_data:
+            return
+        devices = self.topology_data.get("devices", {})
+        devices_dict = {d.get("id", ""): d for d in devices if d.get("id")} if isinstance(devices, list) else devices
+        device = devices_dict.get(device_id)
+        if not device:
+            return
+        props = device.setdefault("properties", {})
+        props["reactive_mode"] = config.get("mode", "none")
+        for src_key, prop_key in (("power_factor", "reactive_pf"), ("q_kvar", "reactive_q_kvar"), ("q_u_curve", "q_u_curve")):
+            if config.get(src_key) is not None:
+                props[prop_key] = config[src_key]
+
+    def set_grid_frequency(self, frequency_hz: float, islanded: bool, nominal_hz: Optional[float] = None) -> None:
+        """
+        设置孤岛频率模型状态。islanded=True 时激活频率模型，P(f) 曲线按 frequency_hz 生效；
+        退出孤岛时恢复额定频率并清除延时计时。
+        """
+        if nominal_hz:
+            self.nominal_frequency_hz = float(nominal_hz)
+        self.frequency_model_active = bool(islanded)
+        self.grid_frequency_hz = float(frequency_hz) if islanded else self.nominal_frequency_hz
+        if not islanded:
+            self.device_pf_excursion_start.clear()
+
+    def get_grid_boundary(self) -> Dict[str, Any]:
+        """当前电网边界条件：外部电网电压设定（None 表示使用拓扑自身 vm_pu）与频率模型状态，供场景结束后恢复。"""
+        return {
+            "vm_pu": self.ext_grid_vm_pu,
+            "frequency_hz": self.grid_frequency_hz,
+            "islanded": self.frequency_model_active,
+            "nominal_hz": self.nominal_frequency_hz,
+        }
+
+    def set_solver_options(self, options: Optional[Dict[str, Any]]) -> None:
+        """设置潮流求解参数，仅保留已知键；None 或空字典恢复内核默认。下一拍计算生效。"""
+        allowed = ("algorithm", "max_iteration", "tolerance_mva", "init")
+        self.solver_options = {k: v for k, v in (options or {}).items() if k in allowed and v is not None}
+
+    def set_ext_grid_voltage(self, vm_pu: Optional[float]) -> None:
+        """设置外部电网电压边界条件（pu），None 恢复拓扑中各外部电网自身的 vm_pu；下一拍计算生效。"""
+        self.ext_grid_vm_pu = float(vm_pu) if vm_pu is not None else None
+
+    # 短路故障接地电阻下限（Ω），避免金属性短路导致潮流无法收敛
+    MIN_FAULT_RESISTANCE_OHM = 0.1
+
+    def _fault_element(self, device_id: str) -> Optional[tuple]:
+        """线路/变压器设备对应的 (pandapower 表名, 索引)，其他设备返回 None。"""
+        if device_id in self.cached_device_map.get("lines", {}):
+            return ("line", self.cached_device_map["lines"][device_id])
+        if device_id in self.cached_device_map.get("transformers", {}):
+            return ("trafo", self.cached_device_map["transformers"][device_id])
+        return None
+
+    def _fault_bus(self, device_id: str) -> Optional[int]:
+        """短路位置母线：母线设备取自身，线路取首端母线，变压器取高压侧母线。"""
+        if device_id in self.cached_bus_map:
+            return int(self.cached_bus_map[device_id])
+        element = self._fault_element(device_id)
+        if element is None:
+            return None
+        table, idx = element
+        column = "from_bus" if table == "line" else "hv_bus"
+        return int(self.cached_network[table].at[idx, column])
+
+    def _calc_short_circuit(self, bus: int, fault_resistance_ohm: float) -> Dict[str, Any]:
+        """按 IEC 60909 计算故障母线三相短路电流（在网络副本上计算，外部电网未配置短路容量时取 1000 MVA）。"""
+        try:
+            import copy
+            import pandapower.shortcircuit as sc
+            net = copy.deepcopy(self.cached_network)
+            if "s_sc_max_mva" not in net.ext_grid.columns:
+                net.ext_grid["s_sc_max_mva"] = 1000.0
+            net.ext_grid["s_sc_max_mva"] = net.ext_grid["s_sc_max_mva"].fillna(1000.0)
+            if "rx_max" not in net.ext_grid.columns:
+                net.ext_grid["rx_max"] = 0.1
+            net.ext_grid["rx_max"] = net.ext_grid["rx_max"].fillna(0.1)
+            sc.calc_sc(net, bus=bus, case="max", r_fault_ohm=fault_resistance_ohm)
+            row = net.res_bus_sc.loc[bus]
+            return {
+                "ikss_ka": float(row.get("ikss_ka", 0.0)),
+                "skss_mw": float(row.get("skss_mw", 0.0)) if "skss_mw" in row else None,
+            }
+        except Exception as e:
+            return {"error": str(e)}
+
+    def _apply_faults(self) -> None:
+        """将故障施加到网络：停运元件置 in_service=False，短路母线接入故障接地电阻（网络重建后重新接入）。"""
+        net = self.cached_network
+        if net is None:
+            return
+        for device_id, fault in self.active_faults.items():
+            if fault["fault_type"] == "outage":
+                element = self._fault_element(device_id)
+                if element is not None:
+                    table, idx = element
+                    net[table].at[idx, "in_service"] = False
+            elif fault.get("net_id") != id(net):
+                import pandapower as pp
+                bus = self._fault_bus(device_id)
+                if bus is None:
+                    continue
+                vn_kv = float(net.bus.at[bus, "vn_kv"])
+                r = max(float(fault["fault_resistance_ohm"]), self.MIN_FAULT_RESISTANCE_OHM)
+                # 接地电阻在 1 pu 电压下吸收的有功 = U² / R
+                fault["shunt_idx"] = int(pp.create_shunt(net, bus, q_mvar=0.0, p_mw=vn_kv ** 2 / r, name=f"fault:{device_id}"))
+                fault["net_id"] = id(net)
+
+    def inject_fault(self, device_id: str, fault_type: str, fault_resistance_ohm: float = 5.0) -> Dict[str, Any]:
+        """
+        故障注入，下一拍计算生效：
+        - outage：线路/变压器退出运行
+        - short_circuit：在母线（线路取首端母线、变压器取高压侧母线）经接地电阻短路，并计算短路电流
+        """
+        if self.cached_network is None:
+            raise ValueError("网络未构建，请先启动仿真")
+        if fault_type not in ("outage", "short_circuit"):
+            raise ValueError(f"不支持的故障类型: {fault_type}")
+        if device_id in self.active_faults:
+            raise ValueError(f"设备 {device_id} 已存在故障")
+        resistance = max(float(fault_resistance_ohm), self.MIN_FAULT_RESISTANCE_OHM)
+        info: Dict[str, Any] = {"device_id": device_id, "fault_type": fault_type}
+        if fault_type == "outage":
+            if self._fault_element(device_id) is None:
+                raise ValueError("停运故障设备须为线路或变压器")
+        else:
+            bus = self._fault_bus(device_id)
+            if bus is None:
+                raise ValueError("短路故障位置须为母线、线路或变压器")
+            info["fault_resistance_ohm"] = resistance
+            info["short_circuit"] = self._calc_short_circuit(bus, resistance)
+        self.active_faults[device_id] = {"fault_type": fault_type, "fault_resistance_ohm": resistance}
+        self._apply_faults()
+        return info
+
+    def clear_fault(self, device_id: str) -> bool:
+        """清除故障并恢复网络，返回是否存在该故障。"""
+        fault = self.active_faults.pop(device_id, None)
+        if fault is None:
+            return False
+        net = self.cached_network
+        if net is None:
+            return True
+        if fault["fault_type"] == "outage":
+            element = self._fault_element(device_id)
+            if element is not None:
+                table, idx = element
+                net[table].at[idx, "in_service"] = True
+        elif fault.get("net_id") == id(net) and fault.get("shunt_idx") in net.shunt.index:
+            net.shunt.drop(fault["shunt_idx"], inplace=True)
+        return True
+
+    def clear_all_faults(self) -> None:
+        for device_id in list(self.active_faults.keys()):
+            self.clear_fault(device_id)
+
+    def set_device_pf_response(self, device_id: str, config: Dict[str, Any]) -> None:
+        """
+        设置设备 P(f) 响应配置，写入 properties.pf_response，下一拍计算生效。
+        config: {"enabled": bool, "curve": [{"f_hz": float, "p_pct": float}, ...],
+                 "deadband_hz": float, "delay_ms": float}
+        """
+        if not self.topology_data:
+            return
+        devices = self.topology_data.get("devices", {})
+        devices_dict = {d.get("id", ""): d for d in devices if d.get("id")} if isinstance(devices, list) else devices
+        device = devices_dict.get(device_id)
+        if not device:
+            return
+        device.setdefault("properties", {})["pf_response"] = dict(config)
+        self.device_pf_excursion_start.pop(device_id, None)
+
+    def set_device_random_config(
+        self, device_id: str, min_power: float, max_power: float, external: bool = False, seed: Optional[int] = None
+    ) -> None:
         """
         设置随机模式设备的功率范围（单位 kW）。
         每步计算前会在此范围内生成新的有功功率并写入设备 properties。
+        external=True 时功率由 Rust 端生成模型逐步下发（set_device_random_value），内核不再生成。
+        seed 为 Rust 端派生的设备种子，设置后该设备的随机序列可复现；None 时使用系统熵。
         """
+        if external:
+            self.device_random_config.pop(device_id, None)
+            self.device_random_rngs.pop(device_id, None)
+            return
         self.device_random_config[device_id] = {
             "min_power": float(min_power),
             "max_power": float(max_power),
         }
+        if seed is not None:
+            self.device_random_config[device_id]["seed"] = int(seed)
+        self.device_random_rngs[device_id] = random.Random(seed)
+
+    def set_device_random_value(self, device_id: str, p_kw: float) -> None:
+        """Rust 端生成的随机数据：直接写入随机模式设备的 properties（数据源，不经过响应延迟）"""
+        if not self.topology_data or self.device_modes.get(device_id) != "random_data":
+            return
+        devices = self.topology_data.get("devices", {})
+        devices_dict = {d.get("id", ""): d for d in devices if d.get("id")} if isinstance(devices, list) else devices
+        device = devices_dict.get(device_id)
+        if not device:
+            return
+        props = device.setdefault("properties", {})
+        props["p_kw"] = float(p_kw)
+        props["q_kvar"] = 0.0
+
+    def set_device_historical_value(self, device_id: str, p_kw: float, q_kvar: float = 0.0) -> None:
+        """Rust 端回放的历史曲线值：直接写入历史模式设备的 properties（数据源，不经过响应延迟）"""
+        if not self.topology_data or self.device_modes.get(device_id) != "historical_data":
+            return
+        devices = self.topology_data.get("devices", {})
+        devices_dict = {d.get("id", ""): d for d in devices if d.get("id")} if isinstance(devices, list) else devices
+        device = devices_dict.get(device_id)
+        if not device:
+            return
+        props = device.setdefault("properties", {})
+        props["p_kw"] = float(p_kw)
+        props["q_kvar"] = float(q_kvar)
+
+    def set_device_solar_value(self, device_id: str, p_kw: float) -> None:
+        """Rust 端晴空辐照模型计算的光伏出力：直接写入 solar_model 模式设备的 properties（数据源，不经过响应延迟）"""
+        if not self.topology_data or self.device_modes.get(device_id) != "solar_model":
+            return
+        devices = self.topology_data.get("devices", {})
+        devices_dict = {d.get("id", ""): d for d in devices if d.get("id")} if isinstance(devices, list) else devices
+        device = devices_dict.get(device_id)
+        if not device:
+            return
+        props = device.setdefault("properties", {})
+        props["p_kw"] = float(p_kw)
+        props["q_kvar"] = 0.0
+
+    def set_device_weather_value(self, device_id: str, p_kw: float) -> None:
+        """Rust 端气象模型计算的出力（风机 / 温度相关负荷 / 光伏）：直接写入 weather_model 模式设备的 properties"""
+        if not self.topology_data or self.device_modes.get(device_id) != "weather_model":
+            return
+        devices = self.topology_data.get("devices", {})
+        devices_dict = {d.get("id", ""): d for d in devices if d.get("id")} if isinstance(devices, list) else devices
+        device = devices_dict.get(device_id)
+        if not device:
+            return
+        props = device.setdefault("properties", {})
+        props["p_kw"] = float(p_kw)
+
+    def set_device_ev_value(self, device_id: str, p_kw: float) -> None:
+        """Rust 端充电会话模型汇总的充电需求功率：直接写入 ev_session 模式充电桩的 properties"""
+        if not self.topology_data or self.device_modes.get(device_id) != "ev_session":
+            return
+        devices = self.topology_data.get("devices", {})
+        devices_dict = {d.get("id", ""): d for d in devices if d.get("id")} if isinstance(devices, list) else devices
+        device = devices_dict.get(device_id)
+        if not device:
+            return
+        props = device.setdefault("properties", {})
+        props["p_kw"] = float(p_kw)
+        props["q_kvar"] = 0.0
 
     def update_switch_state(self, device_id: str, is_closed: bool) -> None:
         """
@@ -264,6 +565,31 @@ class SimulationEngine:
                 except Exception as e:
                     print(f"更新开关 {device_id} 状态失败: {e}")
 
+    def _apply_switch_command(self, device_id: str, device: Dict[str, Any], properties: Dict[str, Any]) -> None:
+        """开关远程合/分闸：properties 含 closed 时切换开关状态（Modbus 线圈写入）"""
+        if device.get("device_type") != "Switch" or "closed" not in properties:
+            return
+        closed = properties["closed"]
+        if isinstance(closed, str):
+            closed = closed.strip().lower() == "true"
+        self.update_switch_state(device_id, bool(closed))
+
+    def set_transformer_tap(self, device_id: str, tap_pos: int) -> None:
+        """
+        设置变压器分接头档位（有载调压），同时写入 properties.tap_pos（网络重建后保持）与 pandapower 网络，下一拍计算生效。
+        """
+        if not self.topology_data:
+            raise ValueError("拓扑数据未设置")
+        devices = self.topology_data.get("devices", {})
+        devices_dict = {d.get("id", ""): d for d in devices if d.get("id")} if isinstance(devices, list) else devices
+        device = devices_dict.get(device_id)
+        if not device:
+            raise ValueError(f"设备不存在: {device_id}")
+        device.setdefault("properties", {})["tap_pos"] = int(tap_pos)
+        trafo_idx = self.cached_device_map.get("transformers", {}).get(device_id)
+        if self.cached_network is not None and trafo_idx is not None:
+            self.cached_network.trafo.at[trafo_idx, "tap_pos"] = int(tap_pos)
+
     def _parse_power_from_properties(self, properties: Dict[str, Any]) -> Optional[tuple]:
         """从 properties 解析 (p_kw, q_kvar)，无功率字段时返回 None。"""
         if "rated_power" in properties:
@@ -288,6 +614,7 @@ class SimulationEngine:
         2. 合并 properties 到拓扑对应设备的 props。
         3. 手动模式：仅在“用户设定功率”时同步 device_manual_setpoint；Modbus 关机（on_off=0）不覆盖，保证 5005 先 0 再 1 后功率可恢复。
         4. 储能：set_power（HR4）写入 device_remote_setpoint；光伏/充电桩开机（on_off=1）时清除 device_remote_setpoint 残留。
+        5. 开关：Modbus 线圈合/分闸下发 closed，按 update_switch_state 切换网络中的开关。
         """
         if not self.topology_data:
             return
@@ -319,6 +646,7 @@ class SimulationEngine:
         if "reactive_comp_pct" in properties:
             props.pop("power_factor", None)
         props.update(properties)
+        self._apply_switch_command(device_id, device, properties)
 
         power_tuple = self._parse_power_from_properties(properties)
         device_type = device.get("device_type", "")
@@ -436,7 +764,7 @@ class SimulationEngine:
                         result_data["q_mvar"] = storage_data.get("q_mvar", 0.0)
             
             if result_data:
-                result_data["timestamp"] = time.time()
+                result_data["timestamp"] = self._result_timestamp()
                 result_data["device_id"] = device_id
                 result_data["device_name"] = device_name
                 result_data["device_type"] = device_type
@@ -447,8 +775,12 @@ class SimulationEngine:
             "voltage": 0.0,
             "current": 0.0,
             "power": 0.0,
-            "timestamp": time.time(),
+            "timestamp": self._result_timestamp(),
         }
+
+    def _result_timestamp(self) -> float:
+        """设备结果时间戳：有仿真时间时取仿真时间，否则取墙钟"""
+        return self.sim_time if self.sim_time is not None else time.time()
     
     def _calculation_loop(self):
         """
@@ -461,9 +793,10 @@ class SimulationEngine:
         # 保留方法定义以保持接口兼容性，但不再执行任何操作
         pass
     
-    def perform_calculation(self) -> Dict[str, Any]:
+    def perform_calculation(self, force: bool = False, sim_time: Optional[float] = None) -> Dict[str, Any]:
         """
-        执行一次仿真计算 - 四阶段数据流
+        执行一次仿真计算 - 四阶段数据流（force=True 时暂停中也计算一步，用于单步调试；
+        sim_time 为本步对应的仿真时间，暂停或加速时与墙钟不同）
         
         ┌──────────────────────────────────────────────────────────────────┐
         │ 第1阶段：应用三类原始数据源（优先级递增，后者覆盖前者）          │
@@ -518,7 +851,7 @@ class SimulationEngine:
                 "devices": {}
             }
         
-        if self.is_paused:
+        if self.is_paused and not force:
             # 暂停时返回上次结果
             return self.last_calculation_result or {
                 "converged": False,
@@ -526,9 +859,17 @@ class SimulationEngine:
                 "devices": {}
             }
         
+        if sim_time is not None:
+            sim_time = float(sim_time)
+            self.sim_step_seconds = max(0.0, sim_time - self.sim_time) if self.sim_time is not None else None
+            self.sim_time = sim_time
+        else:
+            self.sim_step_seconds = None
+
         try:
             # 执行一次计算
             result = self._perform_calculation()
+            result["sim_time"] = self.sim_time
             self.last_calculation_result = result
             self.calculation_count += 1
             self.last_calculation_time = time.time()
@@ -559,6 +900,99 @@ class SimulationEngine:
                 "auto_paused": True
             }
     
+    def _build_network(self, errors: List[Dict[str, Any]]) -> bool:
+        """使用适配器转换拓扑数据并缓存网络对象（只在拓扑结构变化时执行），返回是否成功"""
+        adapter_result = self.topology_adapter.convert(self.topology_data)
+        
+        # 收集适配器错误
+        for err in adapter_result.errors:
+            errors.append({
+                "type": err.error_type,
+                "severity": err.severity,
+                "message": err.message,
+                "device_id": err.device_id,
+                "details": err.details or {},
+                "timestamp": time.time()
+            })
+        
+        for warn in adapter_result.warnings:
+            errors.append({
+                "type": warn.error_type,
+                "severity": warn.severity,
+                "message": warn.message,
+                "device_id": warn.device_id,
+                "details": warn.details or {},
+                "timestamp": time.time()
+            })
+        
+        if not adapter_result.success:
+            return False
+        
+        # 缓存网络对象
+        self.cached_network = adapter_result.data
+        ext_grid_df = getattr(self.cached_network, "ext_grid", None)
+        self.ext_grid_base_vm_pu = ext_grid_df["vm_pu"].copy() if ext_grid_df is not None else None
+        
+        # 从适配器获取映射信息（如果适配器支持）
+        if hasattr(self.topology_adapter, 'get_bus_map'):
+            self.cached_bus_map = self.topology_adapter.get_bus_map()
+        if hasattr(self.topology_adapter, 'get_device_map'):
+            self.cached_device_map = self.topology_adapter.get_device_map()
+        return True
+
+    def preload_topology(self, topology_data: Dict[str, Any], warm_up: bool = True,
+                         kernel_type: Optional[str] = None) -> Dict[str, Any]:
+        """
+        预加载拓扑（项目打开时调用）：设置拓扑并构建网络缓存，可选试算一次潮流，
+        提前完成 pandapower 导入与首次求解的编译开销，使随后的 start 可立即步进
+        
+        Args:
+            topology_data: 标准拓扑数据格式
+            warm_up: 是否试算一次潮流
+            kernel_type: 计算内核类型；None 保持当前内核
+        """
+        if self.is_running:
+            raise ValueError("仿真运行中，无法预加载拓扑")
+        started = time.time()
+        new_hash = self._calculate_topology_hash(self._extract_topology_structure(topology_data))
+        already_warm = (
+            self.cached_network is not None
+            and new_hash == self.topology_hash
+            and (kernel_type or self.kernel_type) == self.kernel_type
+        )
+        self.set_topology(topology_data, kernel_type)
+        errors: List[Dict[str, Any]] = []
+        if self.cached_network is None and not self._build_network(errors):
+            return {
+                "status": "error",
+                "message": errors[0]["message"] if errors else "网络构建失败",
+                "errors": errors,
+            }
+        warmed_up = False
+        if warm_up and not already_warm:
+            try:
+                if hasattr(self.power_calculator, "solver_options"):
+                    self.power_calculator.solver_options = self.solver_options
+                self._update_network_power_values()
+                result = self.power_calculator.calculate_power_flow(self.cached_network)
+                warmed_up = bool(result.get("converged", False))
+            except Exception as e:
+                # 试算失败不影响预加载结果，正式计算时会再次报告
+                errors.append({
+                    "type": "calculation",
+                    "severity": "warning",
+                    "message": f"预加载试算失败: {str(e)}",
+                    "details": {"exception": str(e), "type": type(e).__name__},
+                    "timestamp": time.time()
+                })
+        return {
+            "status": "ok",
+            "already_warm": already_warm,
+            "warmed_up": warmed_up,
+            "elapsed_ms": int((time.time() - started) * 1000),
+            "errors": errors,
+        }
+
     def _perform_calculation(self) -> Dict[str, Any]:
         """执行一次潮流计算"""
         if not self.topology_data:
@@ -572,53 +1006,22 @@ class SimulationEngine:
         
         errors: List[Dict[str, Any]] = []
         
-        # 如果网络对象已缓存，直接使用；否则重新创建
-        if self.cached_network is None:
-            # 使用适配器转换拓扑数据（只在拓扑结构变化时执行）
-            adapter_result = self.topology_adapter.convert(self.topology_data)
-            
-            # 收集适配器错误
-            for err in adapter_result.errors:
-                errors.append({
-                    "type": err.error_type,
-                    "severity": err.severity,
-                    "message": err.message,
-                    "device_id": err.device_id,
-                    "details": err.details or {},
-                    "timestamp": time.time()
-                })
-            
-            for warn in adapter_result.warnings:
-                errors.append({
-                    "type": warn.error_type,
-                    "severity": warn.severity,
-                    "message": warn.message,
-                    "device_id": warn.device_id,
-                    "details": warn.details or {},
-                    "timestamp": time.time()
-                })
-            
-            # 如果适配器转换失败，返回错误并标记需自动停止
-            if not adapter_result.success:
-                self.is_paused = True
-                return {
-                    "converged": False,
-                    "errors": errors,
-                    "devices": {},
-                    "auto_paused": True
-                }
-            
-            # 缓存网络对象
-            self.cached_network = adapter_result.data
-            
-            # 从适配器获取映射信息（如果适配器支持）
-            if hasattr(self.topology_adapter, 'get_bus_map'):
-                self.cached_bus_map = self.topology_adapter.get_bus_map()
-            if hasattr(self.topology_adapter, 'get_device_map'):
-                self.cached_device_map = self.topology_adapter.get_device_map()
+        # 如果网络对象已缓存（含预加载），直接使用；否则重新创建
+        if self.cached_network is None and not self._build_network(errors):
+            self.is_paused = True
+            return {
+                "converged": False,
+                "errors": errors,
+                "devices": {},
+                "auto_paused": True
+            }
         
-        # 仿真时间累加（秒），用于历史回放和响应延迟
-        dt_sec = self.calculation_interval_ms / 1000.0
+        # 仿真时间累加（秒），用于历史回放和响应延迟；按 Rust 端仿真时钟的增量推进，
+        # 与 SimClock 保持一致（单步、倍速变化均不漂移），无增量时（首步或未传入 sim_time）按步长 × 倍速估算
+        if self.sim_step_seconds is not None:
+            dt_sec = self.sim_step_seconds
+        else:
+            dt_sec = self.calculation_interval_ms / 1000.0 * self.time_scale
         self.sim_elapsed_seconds += dt_sec
         # 历史回放采样控制：按 playbackIntervalMs 间隔更新数据索引（见 _apply_historical_power_values）
         # 处理响应延迟 pending 队列：到时间的命令写入 properties
@@ -627,10 +1030,20 @@ class SimulationEngine:
         self._apply_device_power_sources()
         # 第2阶段：应用 Modbus 远程控制指令（set_power + 限制过滤）
         self._apply_modbus_instructions()
+        # 第2.4阶段：孤岛频率模型激活时应用 P(f) 响应（光伏/储能）
+        self._apply_frequency_response()
+        # 第2.45阶段：储能下垂一次调频出力（Rust 端频率模型计算）
+        self._apply_droop_setpoints()
+        # 第2.5阶段：光伏本地无功控制（固定功率因数 / Q(U) / 固定无功），Modbus 无功指令优先
+        self._apply_reactive_control()
+        # 第2.6阶段：光伏电网支撑设定（volt-var 无功、volt-watt 有功上限），覆盖本地无功控制
+        self._apply_grid_support_setpoints()
         # 第3阶段：更新网络功率值（读 properties，光伏 power_limit_pct 精确计算，写网络）
         self._update_network_power_values()
         # 第4阶段：执行潮流计算（使用缓存的网络对象）
         try:
+            if hasattr(self.power_calculator, "solver_options"):
+                self.power_calculator.solver_options = self.solver_options
             calculation_result = self.power_calculator.calculate_power_flow(self.cached_network)
             
             # 合并错误信息
@@ -657,6 +1070,9 @@ class SimulationEngine:
                 unique_errors.append(err)
             errors = unique_errors
 
+            # 在清空映射缓存前，把错误中的元素索引映射回设备ID
+            self._attribute_errors_to_devices(errors)
+
             # 检查是否存在严重错误（severity == "error"），并判断是否需要自动暂停
             has_critical_error = any(
                 err.get("severity") == "error"
@@ -688,6 +1104,14 @@ class SimulationEngine:
                 "converged": converged,
                 "errors": errors,
                 "devices": calculation_result.get("devices", {}),
+                "frequency": {
+                    "frequency_hz": self.grid_frequency_hz,
+                    "islanded": self.frequency_model_active,
+                    "nominal_hz": self.nominal_frequency_hz,
+                },
+                "device_setpoints": self._device_active_setpoints(),
+                # 求解诊断（算法、初值方式、迭代次数、求解耗时），供性能调优
+                "solver": calculation_result.get("solver"),
                 "auto_paused": should_auto_pause  # 标记是否自动暂停
             }
             
@@ -716,6 +1140,54 @@ class SimulationEngine:
                 "auto_paused": True  # 标记已自动暂停
             }
     
+    # 错误 details 中的元素索引字段 -> pandapower 表名（兼容结果检查中的 *_index 字段）
+    _ERROR_INDEX_FIELDS = {"bus_index": "bus", "line_index": "line", "trafo_index": "trafo"}
+    # pandapower 表名 -> cached_device_map 中的分类键
+    _ELEMENT_TABLE_TO_MAP_KEY = {
+        "line": "lines",
+        "trafo": "transformers",
+        "switch": "switches",
+        "sgen": "generators",
+        "load": "loads",
+        "storage": "storages",
+    }
+
+    def _attribute_errors_to_devices(self, errors: List[Dict[str, Any]]) -> None:
+        """
+        将错误归属到具体设备：根据 details 中的元素索引（elements / *_index）
+        反查设备ID，写入 device_id（首个设备）与 details.device_ids（全部设备）。
+        已带 device_id 的错误（如适配器错误）保持不变。
+        """
+        reverse: Dict[str, Dict[int, str]] = {
+            "bus": {idx: dev_id for dev_id, idx in self.cached_bus_map.items()}
+        }
+        for table, map_key in self._ELEMENT_TABLE_TO_MAP_KEY.items():
+            reverse[table] = {idx: dev_id for dev_id, idx in self.cached_device_map.get(map_key, {}).items()}
+
+        for err in errors:
+            if err.get("device_id"):
+                continue
+            details = err.get("details")
+            if not isinstance(details, dict):
+                continue
+            elements: Dict[str, List[int]] = {}
+            for table, indices in (details.get("elements") or {}).items():
+                elements.setdefault(table, []).extend(indices)
+            for field, table in self._ERROR_INDEX_FIELDS.items():
+                if field in details:
+                    elements.setdefault(table, []).append(details[field])
+
+            device_ids: List[str] = []
+            for table, indices in elements.items():
+                table_map = reverse.get(table, {})
+                for idx in indices:
+                    dev_id = table_map.get(idx)
+                    if dev_id and dev_id not in device_ids:
+                        device_ids.append(dev_id)
+            if device_ids:
+                err["device_id"] = device_ids[0]
+                details["device_ids"] = device_ids
+
     def _apply_device_power_sources(self) -> None:
         """
         第1阶段：应用三类原始数据源。顺序：手动 -> 随机 -> 历史（后写覆盖先写）。
@@ -807,6 +1279,195 @@ class SimulationEngine:
                 q_kvar = nominal_kw * pct / 100.0
         return p_kw, q_kvar
 
+    def _apply_frequency_response(self) -> None:
+        """
+        P(f) 响应：频率偏差超出死区且持续超过有意延时后，按曲线插值得到 ΔP（额定功率百分比，正=增加出力），
+        叠加到当前有功上。光伏出力限制在 [0, 当前可用功率]（只能降出力）；储能按 pandapower 约定 p 正=充电，
+        出力增加即 p_kw 减小，限制在 ±额定功率内。
+        """
+        if not self.frequency_model_active or not self.topology_data:
+            return
+        devices = self.topology_data.get("devices", {})
+        devices_dict = {d.get("id", ""): d for d in devices if d.get("id")} if isinstance(devices, list) else devices
+        deviation = self.grid_frequency_hz - self.nominal_frequency_hz
+        for device_id, device in devices_dict.items():
+            device_type = device.get("device_type")
+            if device_type not in ("Pv", "Storage"):
+                continue
+            props = device.get("properties", {})
+            cfg = props.get("pf_response") or {}
+            if not cfg.get("enabled", False):
+                continue
+            deadband = abs(float(cfg.get("deadband_hz", 0.0) or 0.0))
+            if abs(deviation) <= deadband:
+                self.device_pf_excursion_start.pop(device_id, None)
+                continue
+            started = self.device_pf_excursion_start.setdefault(device_id, self.sim_elapsed_seconds)
+            delay_s = float(cfg.get("delay_ms", 0.0) or 0.0) / 1000.0
+            if self.sim_elapsed_seconds - started < delay_s:
+                continue
+            curve = [
+                {"u_pu": p.get("f_hz"), "q_pct": p.get("p_pct")}
+                for p in (cfg.get("curve") or [])
+                if isinstance(p, dict)
+            ]
+            delta_pct = self._interpolate_q_u_curve(curve, self.grid_frequency_hz)
+            nominal_kw = float(props.get("rated_power_kw") or props.get("max_power_kw") or props.get("rated_power") or 0)
+            if delta_pct is None or nominal_kw <= 0:
+                continue
+            delta_kw = nominal_kw * delta_pct / 100.0
+            p_kw = float(props.get("p_kw", 0.0))
+            if device_type == "Pv":
+                props["p_kw"] = min(p_kw, max(0.0, p_kw + delta_kw))
+            else:
+                props["p_kw"] = max(-nominal_kw, min(nominal_kw, p_kw - delta_kw))
+
+    def set_droop_setpoint(self, device_id: str, droop_kw: Optional[float]) -> None:
+        """设置储能下垂一次调频出力（kW，正=增加出力，Rust 端孤岛频率模型计算），None 时撤销；下一拍计算生效。"""
+        if droop_kw is None:
+            self.device_droop_kw.pop(device_id, None)
+        else:
+            self.device_droop_kw[device_id] = float(droop_kw)
+
+    def _apply_droop_setpoints(self) -> None:
+        """储能下垂出力叠加到当前有功上：pandapower 约定 p 正=充电，出力增加即 p_kw 减小，限制在 ±额定功率内。"""
+        if not self.device_droop_kw or not self.topology_data:
+            return
+        devices = self.topology_data.get("devices", {})
+        devices_dict = {d.get("id", ""): d for d in devices if d.get("id")} if isinstance(devices, list) else devices
+        for device_id, droop_kw in self.device_droop_kw.items():
+            device = devices_dict.get(device_id)
+            if not device or device.get("device_type") != "Storage":
+                continue
+            props = device.setdefault("properties", {})
+            nominal_kw = float(props.get("rated_power_kw") or props.get("max_power_kw") or props.get("rated_power") or 0)
+            p_kw = float(props.get("p_kw", 0.0)) - droop_kw
+            props["p_kw"] = max(-nominal_kw, min(nominal_kw, p_kw)) if nominal_kw > 0 else p_kw
+
+    def _device_active_setpoints(self) -> Dict[str, float]:
+        """
+        本拍功率设备的有功设定（kW，已叠加 Modbus 指令与 P(f) 响应），供 Rust 端孤岛频率模型计算功率不平衡：
+        孤岛内设备不参与潮流、无结果行，只能按设定计算。符号同 properties：光伏为发电、负载/充电桩为用电、
+        储能为充电（pandapower 约定），不含下垂出力（频率模型按下垂增益单独计入）；离网储能（grid_mode != 0）不计入。
+        """
+        if not self.topology_data:
+            return {}
+        devices = self.topology_data.get("devices", {})
+        devices_dict = {d.get("id", ""): d for d in devices if d.get("id")} if isinstance(devices, list) else devices
+        setpoints: Dict[str, float] = {}
+        for device_id, device in devices_dict.items():
+            if device.get("device_type") not in ("Pv", "Storage", "Load", "Charger"):
+                continue
+            props = device.get("properties", {})
+            if device.get("device_type") == "Storage" and int(props.get("grid_mode", 0) or 0) != 0:
+                continue
+            try:
+                setpoints[device_id] = float(props.get("p_kw", 0.0) or 0.0) + self.device_droop_kw.get(device_id, 0.0)
+            except (TypeError, ValueError):
+                continue
+        return setpoints
+
+    def _apply_reactive_control(self) -> None:
+        """
+        光伏无功控制模式（properties.reactive_mode）：
+        - fixed_pf：按 reactive_pf 由当前有功计算无功（正=发出无功，负=吸收）
+        - q_u：按 q_u_curve 以上一拍并网点电压插值，Q = 额定功率 × q_pct / 100
+        - fixed_q：Q = reactive_q_kvar
+        存在 Modbus 无功指令（power_factor / reactive_comp_pct）时以远程指令为准，不覆盖。
+        """
+        if not self.topology_data:
+            return
+        devices = self.topology_data.get("devices", {})
+        devices_dict = {d.get("id", ""): d for d in devices if d.get("id")} if isinstance(devices, list) else devices
+        for device_id, device in devices_dict.items():
+            if device.get("device_type") != "Pv":
+                continue
+            props = device.get("properties", {})
+            mode = props.get("reactive_mode") or "none"
+            if mode == "none" or "power_factor" in props or "reactive_comp_pct" in props:
+                continue
+            p_kw = float(props.get("p_kw", 0.0))
+            q_kvar = None
+            if mode == "fixed_pf":
+                pf = float(props.get("reactive_pf", 1.0))
+                if 0 < abs(pf) <= 1.0:
+                    q_mag = abs(p_kw) * ((1 - pf ** 2) ** 0.5) / abs(pf)
+                    q_kvar = q_mag if pf > 0 else -q_mag
+            elif mode == "fixed_q":
+                q_kvar = float(props.get("reactive_q_kvar", 0.0))
+            elif mode == "q_u":
+                nominal_kw = float(props.get("rated_power_kw") or props.get("max_power_kw") or props.get("rated_power") or 0)
+                u_pu = self._get_device_bus_voltage(device_id, "generators")
+                q_pct = self._interpolate_q_u_curve(props.get("q_u_curve") or [], u_pu)
+                if nominal_kw > 0 and q_pct is not None:
+                    q_kvar = nominal_kw * q_pct / 100.0
+            if q_kvar is not None:
+                props["q_kvar"] = q_kvar
+
+    def set_grid_support_setpoint(self, device_id: str, q_kvar: Optional[float], p_limit_kw: Optional[float]) -> None:
+        """设置光伏电网支撑设定（Rust 端计算），两项均为 None 时撤销；下一拍计算生效。"""
+        setpoint = {}
+        if q_kvar is not None:
+            setpoint["q_kvar"] = float(q_kvar)
+        if p_limit_kw is not None:
+            setpoint["p_limit_kw"] = float(p_limit_kw)
+        if setpoint:
+            self.device_grid_support_setpoint[device_id] = setpoint
+        else:
+            self.device_grid_support_setpoint.pop(device_id, None)
+
+    def _apply_grid_support_setpoints(self) -> None:
+        """
+        光伏电网支撑：volt-var 无功设定覆盖 q_kvar（Modbus 无功指令优先，与本地无功控制一致）；
+        volt-watt 有功上限只降低出力。
+        """
+        if not self.device_grid_support_setpoint or not self.topology_data:
+            return
+        devices = self.topology_data.get("devices", {})
+        devices_dict = {d.get("id", ""): d for d in devices if d.get("id")} if isinstance(devices, list) else devices
+        for device_id, setpoint in self.device_grid_support_setpoint.items():
+            device = devices_dict.get(device_id)
+            if not device or device.get("device_type") != "Pv":
+                continue
+            props = device.setdefault("properties", {})
+            if "q_kvar" in setpoint and "power_factor" not in props and "reactive_comp_pct" not in props:
+                props["q_kvar"] = setpoint["q_kvar"]
+            if "p_limit_kw" in setpoint:
+                props["p_kw"] = min(float(props.get("p_kw", 0.0)), max(0.0, setpoint["p_limit_kw"]))
+
+    def _get_device_bus_voltage(self, device_id: str, map_key: str) -> float:
+        """上一拍潮流结果中设备所接母线的电压（pu），无结果时返回 1.0。"""
+        net = self.cached_network
+        idx = self.cached_device_map.get(map_key, {}).get(device_id)
+        if net is None or idx is None:
+            return 1.0
+        table = {"generators": "sgen", "loads": "load", "storages": "storage"}.get(map_key)
+        try:
+            bus = getattr(net, table).at[idx, "bus"]
+            vm_pu = net.res_bus.at[bus, "vm_pu"]
+            return float(vm_pu) if vm_pu == vm_pu else 1.0
+        except Exception:
+            return 1.0
+
+    @staticmethod
+    def _interpolate_q_u_curve(curve: List[Dict[str, Any]], u_pu: float) -> Optional[float]:
+        """Q(U) 曲线线性插值（P(f) 曲线复用，横轴为频率），曲线两端外保持端点值；曲线为空时返回 None。"""
+        points = sorted(
+            (float(p.get("u_pu")), float(p.get("q_pct")))
+            for p in curve
+            if isinstance(p, dict) and p.get("u_pu") is not None and p.get("q_pct") is not None
+        )
+        if not points:
+            return None
+        if u_pu <= points[0][0]:
+            return points[0][1]
+        if u_pu >= points[-1][0]:
+            return points[-1][1]
+        for (u0, q0), (u1, q1) in zip(points, points[1:]):
+            if u0 <= u_pu <= u1:
+                return q0 if u1 == u0 else q0 + (q1 - q0) * (u_pu - u0) / (u1 - u0)
+        return points[-1][1]
+
     def _apply_manual_power_values(self) -> None:
         """
         对手动模式设备，将当前设定写入 topology_data 的 properties。
@@ -852,7 +1513,8 @@ class SimulationEngine:
                 continue
             min_p = cfg.get("min_power", 0.0)
             max_p = cfg.get("max_power", 0.0)
-            p_kw = min_p + random.random() * (max_p - min_p) if max_p > min_p else min_p
+            rng = self.device_random_rngs.get(device_id, random)
+            p_kw = min_p + rng.random() * (max_p - min_p) if max_p > min_p else min_p
             props = device.setdefault("properties", {})
             props["p_kw"] = p_kw
             props["q_kvar"] = 0.0
@@ -932,6 +1594,7 @@ class SimulationEngine:
                         if "reactive_comp_pct" in target:
                             props.pop("power_factor", None)
                         props.update(target)
+                        self._apply_switch_command(device_id, device, target)
                 else:
                     remaining.append(item)
             if remaining:
@@ -1041,14 +1704,25 @@ class SimulationEngine:
                             self.cached_network.load.at[load_idx, "p_mw"] = p_mw
                             if "q_mvar" in self.cached_network.load.columns:
                                 self.cached_network.load.at[load_idx, "q_mvar"] = q_mvar
+
+            # 外部电网电压边界条件
+            ext_grid_df = getattr(self.cached_network, "ext_grid", None)
+            if ext_grid_df is not None and len(ext_grid_df) > 0:
+                if self.ext_grid_vm_pu is not None:
+                    ext_grid_df["vm_pu"] = self.ext_grid_vm_pu
+                elif self.ext_grid_base_vm_pu is not None:
+                    ext_grid_df["vm_pu"] = self.ext_grid_base_vm_pu
+            # 故障在网络重建后重新施加
+            self._apply_faults()
                             
         except Exception as e:
             # 更新功率值失败不影响计算，只记录警告
             pass
     
-    def start(self, calculation_interval_ms: int = 1000):
+    def start(self, calculation_interval_ms: int = 1000, time_scale: float = 1.0,
+              solver_options: Optional[Dict[str, Any]] = None):
         """
-        启动仿真
+        启动仿真（solver_options 为本轮潮流求解参数，见 set_solver_options）
         
         注意：为了与Rust端同步，Python端不再启动自己的循环
         Rust端会主动调用 perform_calculation 来触发计算
@@ -1057,6 +1731,8 @@ class SimulationEngine:
         # 每次 start 调用都重置计数与暂停状态，支持「暂停后再点启动」从 0 重新计时
         self.calculation_count = 0
         self.is_paused = False
+        self.time_scale = float(time_scale) if time_scale and time_scale > 0 else 1.0
+        self.set_solver_options(solver_options)
         if self.is_running:
             return
         if not self.topology_data:
@@ -1073,7 +1749,10 @@ class SimulationEngine:
         """停止仿真"""
         self.is_running = False
         self.is_paused = False
+        # 故障只在本轮仿真内有效，停止时恢复网络（保温时网络会被下次启动复用）
+        self.clear_all_faults()
         self.device_random_config.clear()
+        self.device_random_rngs.clear()
         self.device_modes.clear()
         self.device_manual_setpoint.clear()
         self.device_remote_setpoint.clear()
@@ -1082,8 +1761,12 @@ class SimulationEngine:
         self.device_historical_index.clear()
         self.device_historical_last_update.clear()
         self.device_sim_params.clear()
+        self.device_grid_support_setpoint.clear()
+        self.device_droop_kw.clear()
         self.device_pending_commands.clear()
         self.sim_elapsed_seconds = 0.0
+        self.sim_time = None
+        self.sim_step_seconds = None
 
         # 等待计算线程结束
         if self.calculation_thread and self.calculation_thread.is_alive():
@@ -1091,8 +1774,12 @@ class SimulationEngine:
         
         self.calculation_thread = None
         
-        # 注意：不清除网络缓存，以便下次启动时复用
-        # 只有在拓扑结构变化时才会清除缓存
+        # 保温时不清除网络缓存，以便下次启动时复用（只有在拓扑结构变化时才会清除缓存）；
+        # 关闭保温时释放网络缓存，下次启动重新构建
+        if not self.keep_warm:
+            self.cached_network = None
+            self.cached_bus_map = {}
+            self.cached_device_map = {}
     
     def pause(self):
         """暂停仿真"""
@@ -1109,10 +1796,126 @@ class SimulationEngine:
             "is_paused": self.is_paused,
             "calculation_count": self.calculation_count,
             "calculation_interval_ms": self.calculation_interval_ms,
+            "time_scale": self.time_scale,
             "last_calculation_time": self.last_calculation_time,
-            "error_count": len(self.calculation_errors)
+            "error_count": len(self.calculation_errors),
+            "network_cached": self.cached_network is not None,
+            "keep_warm": self.keep_warm
         }
     
+    def get_element_tables(self) -> Dict[str, Any]:
+        """
+        导出当前缓存网络的元件表（供 Rust 端与拓扑比对）：各表行的索引、名称、所连母线名称与开关状态，
+        以及设备 ID 到元件索引的映射。网络尚未构建时 network_built 为 False。
+        """
+        net = self.cached_network
+        if net is None:
+            return {"network_built": False, "tables": {}, "bus_map": {}, "device_map": {}}
+        bus_names = {int(idx): str(name) for idx, name in net.bus["name"].items()}
+        bus_columns = {
+            "bus": [],
+            "line": ["from_bus", "to_bus"],
+            "trafo": ["hv_bus", "lv_bus"],
+            "switch": ["bus"],
+            "sgen": ["bus"],
+            "load": ["bus"],
+            "storage": ["bus"],
+            "ext_grid": ["bus"],
+        }
+        tables: Dict[str, List[Dict[str, Any]]] = {}
+        for table, columns in bus_columns.items():
+            df = getattr(net, table, None)
+            if df is None:
+                continue
+            rows = []
+            for idx, row in df.iterrows():
+                name = row.get("name")
+                item: Dict[str, Any] = {
+                    "index": int(idx),
+                    "name": "" if name is None or name != name else str(name),
+                    "buses": [bus_names.get(int(row[c]), str(row[c])) for c in columns],
+                }
+                if "in_service" in row:
+                    item["in_service"] = bool(row["in_service"])
+                if table == "switch":
+                    item["closed"] = bool(row["closed"])
+                    if row.get("et") == "b":
+                        item["buses"].append(bus_names.get(int(row["element"]), str(row["element"])))
+                rows.append(item)
+            tables[table] = rows
+        return {
+            "network_built": True,
+            "tables": tables,
+            "bus_map": {k: int(v) for k, v in self.cached_bus_map.items()},
+            "device_map": {t: {k: int(v) for k, v in m.items()} for t, m in self.cached_device_map.items()},
+        }
+
+    def export_state(self) -> Dict[str, Any]:
+        """
+        导出运行时状态（供 Rust 端写入仿真检查点文件）：设备模式与各模式设定、历史回放进度、仿真累计时间、
+        频率/电压边界条件与运行中修改过的设备 properties。历史数据 Provider 不导出，导入时按配置重建。
+        """
+        devices = (self.topology_data or {}).get("devices", {})
+        devices_dict = {d.get("id", ""): d for d in devices if d.get("id")} if isinstance(devices, list) else devices
+        return {
+            "device_modes": dict(self.device_modes),
+            "device_random_config": dict(self.device_random_config),
+            "device_manual_setpoint": dict(self.device_manual_setpoint),
+            "device_remote_setpoint": dict(self.device_remote_setpoint),
+            "device_historical_config": dict(self.device_historical_config),
+            "device_historical_index": dict(self.device_historical_index),
+            "device_historical_last_update": dict(self.device_historical_last_update),
+            "device_sim_params": dict(self.device_sim_params),
+            "device_grid_support_setpoint": dict(self.device_grid_support_setpoint),
+            "device_droop_kw": dict(self.device_droop_kw),
+            "sim_elapsed_seconds": self.sim_elapsed_seconds,
+            "frequency_model_active": self.frequency_model_active,
+            "nominal_frequency_hz": self.nominal_frequency_hz,
+            "grid_frequency_hz": self.grid_frequency_hz,
+            "ext_grid_vm_pu": self.ext_grid_vm_pu,
+            "device_properties": {
+                device_id: device.get("properties", {}) for device_id, device in devices_dict.items()
+            },
+        }
+
+    def import_state(self, state: Dict[str, Any]) -> None:
+        """
+        导入 export_state 导出的运行时状态（在 set_topology 之后、start 之前调用）。
+        历史模式按配置重建 Provider 后再恢复回放进度；拓扑中已不存在的设备的 properties 忽略。
+        """
+        self.device_modes = dict(state.get("device_modes") or {})
+        self.device_random_config = dict(state.get("device_random_config") or {})
+        self.device_random_rngs = {
+            device_id: random.Random(cfg.get("seed")) for device_id, cfg in self.device_random_config.items()
+        }
+        self.device_manual_setpoint = dict(state.get("device_manual_setpoint") or {})
+        self.device_remote_setpoint = dict(state.get("device_remote_setpoint") or {})
+        self.device_sim_params = dict(state.get("device_sim_params") or {})
+        self.device_grid_support_setpoint = dict(state.get("device_grid_support_setpoint") or {})
+        self.device_droop_kw = {k: float(v) for k, v in (state.get("device_droop_kw") or {}).items()}
+        for device_id, config in (state.get("device_historical_config") or {}).items():
+            self.set_device_historical_config(device_id, config)
+        self.device_historical_index.update({k: int(v) for k, v in (state.get("device_historical_index") or {}).items()})
+        self.device_historical_last_update.update(
+            {k: float(v) for k, v in (state.get("device_historical_last_update") or {}).items()}
+        )
+        self.sim_elapsed_seconds = float(state.get("sim_elapsed_seconds", 0.0))
+        # 恢复后首步不与恢复前的仿真时间求增量
+        self.sim_time = None
+        self.sim_step_seconds = None
+        self.frequency_model_active = bool(state.get("frequency_model_active", False))
+        self.nominal_frequency_hz = float(state.get("nominal_frequency_hz", self.nominal_frequency_hz))
+        self.grid_frequency_hz = float(state.get("grid_frequency_hz", self.nominal_frequency_hz))
+        vm_pu = state.get("ext_grid_vm_pu")
+        self.ext_grid_vm_pu = float(vm_pu) if vm_pu is not None else None
+        if self.topology_data:
+            devices = self.topology_data.get("devices", {})
+            devices_dict = {d.get("id", ""): d for d in devices if d.get("id")} if isinstance(devices, list) else devices
+            for device_id, props in (state.get("device_properties") or {}).items():
+                device = devices_dict.get(device_id)
+                if device is not None and isinstance(props, dict):
+                    device.setdefault("properties", {}).update(props)
+
     def get_errors(self) -> List[Dict[str, Any]]:
         """获取错误列表"""
         return self.calculation_errors.copy()
@@ -1,68 +1,142 @@
 """
 计算内核工厂
-支持同时创建计算内核和对应的适配器
+支持同时创建计算内核和对应的适配器，并报告各内核的能力（供前端选择）
 """
 
-from typing import Optional, Tuple
+import importlib
+from typing import Any, Dict, List, Optional, Tuple
 from .interface import PowerCalculationKernel
 from ..adapters.topology_adapter import TopologyAdapter
 
+DEFAULT_KERNEL = "pandapower"
+
+# 内核标识 -> 实现与能力描述
+# unbalanced: 三相不平衡潮流；reactive_power: 求解无功与电压幅值；solver_options: 生效的求解参数；
+# adapter: 构建网络所用的适配器（None 表示尚无适配器，内核不可用）
+_KERNELS: Dict[str, Dict[str, Any]] = {
+    "pandapower": {
+        "impl": ("pandapower_impl", "PandapowerKernel"),
+        "adapter": "pandapower",
+        "name": "pandapower 平衡潮流",
+        "description": "正序交流潮流（默认内核），支持全部求解参数",
+        "unbalanced": False,
+        "reactive_power": True,
+        "solver_options": ["algorithm", "max_iteration", "tolerance_mva", "init"],
+    },
+    "pandapower_3ph": {
+        "impl": ("pandapower_3ph_impl", "Pandapower3phKernel"),
+        "adapter": "pandapower",
+        "name": "pandapower 三相不平衡潮流",
+        "description": "三相潮流（runpp_3ph），附各相电压电流；未设置的零序参数按典型值估算",
+        "unbalanced": True,
+        "reactive_power": True,
+        "solver_options": ["max_iteration", "tolerance_mva", "init"],
+    },
+    "simplified": {
+        "impl": ("simplified_impl", "SimplifiedKernel"),
+        "adapter": "pandapower",
+        "name": "简化直流潮流",
+        "description": "线性化求解有功分布，电压幅值恒为 1.0 pu、不计无功与损耗，速度快且总能求出结果",
+        "unbalanced": False,
+        "reactive_power": False,
+        "solver_options": [],
+    },
+    "pypsa": {
+        "impl": ("pypsa_impl", "PyPSAKernel"),
+        "adapter": None,
+        "name": "PyPSA",
+        "description": "尚未实现",
+        "unbalanced": False,
+        "reactive_power": True,
+        "solver_options": [],
+    },
+    "gridcal": {
+        "impl": ("gridcal_impl", "GridCalKernel"),
+        "adapter": None,
+        "name": "GridCal",
+        "description": "尚未实现",
+        "unbalanced": False,
+        "reactive_power": True,
+        "solver_options": [],
+    },
+}
+
+
+def _instantiate(kernel_type: str) -> PowerCalculationKernel:
+    """创建内核实例；未知内核抛出 ValueError，依赖缺失抛出 ImportError"""
+    spec = _KERNELS.get(kernel_type)
+    if spec is None:
+        raise ValueError(f"未知的计算内核: {kernel_type}")
+    module_name, class_name = spec["impl"]
+    module = importlib.import_module(f".implementations.{module_name}", __package__)
+    return getattr(module, class_name)()
+
+
+def _create_adapter(name: Optional[str]) -> Optional[TopologyAdapter]:
+    if name == "pandapower":
+        from ..adapters.pandapower_adapter import PandapowerTopologyAdapter
+        return PandapowerTopologyAdapter()
+    return None
+
 
 class PowerKernelFactory:
     """功率计算内核工厂"""
-    
+
     @staticmethod
     def create(kernel_type: str) -> Optional[PowerCalculationKernel]:
         """创建功率计算内核实例"""
-        if kernel_type == "pandapower":
-            try:
-                from .implementations.pandapower_impl import PandapowerKernel
-                return PandapowerKernel()
-            except ImportError:
-                return None
-        elif kernel_type == "pypsa":
-            try:
-                from .implementations.pypsa_impl import PyPSAKernel
-                return PyPSAKernel()
-            except ImportError:
-                return None
-        elif kernel_type == "gridcal":
-            try:
-                from .implementations.gridcal_impl import GridCalKernel
-                return GridCalKernel()
-            except ImportError:
-                return None
-        else:
+        try:
+            return _instantiate(kernel_type)
+        except (ImportError, ValueError):
             return None
-    
+
     @staticmethod
     def create_with_adapter(kernel_type: str) -> Optional[Tuple[PowerCalculationKernel, TopologyAdapter]]:
         """
         创建计算内核和对应的适配器
-        
+
         Returns:
-            (计算内核, 适配器) 元组，如果创建失败返回None
+            (计算内核, 适配器) 元组，如果创建失败返回None；尚无适配器的内核适配器为 None
         """
-        if kernel_type == "pandapower":
-            try:
-                from .implementations.pandapower_impl import PandapowerKernel
-                from ..adapters.pandapower_adapter import PandapowerTopologyAdapter
-                return PandapowerKernel(), PandapowerTopologyAdapter()
-            except ImportError:
-                return None
-        elif kernel_type == "pypsa":
-            try:
-                from .implementations.pypsa_impl import PyPSAKernel
-                # PyPSA适配器尚未实现
-                return PyPSAKernel(), None
-            except ImportError:
-                return None
-        elif kernel_type == "gridcal":
-            try:
-                from .implementations.gridcal_impl import GridCalKernel
-                # GridCal适配器尚未实现
-                return GridCalKernel(), None
-            except ImportError:
-                return None
-        else:
+        try:
+            kernel = _instantiate(kernel_type)
+            return kernel, _create_adapter(_KERNELS[kernel_type]["adapter"])
+        except (ImportError, ValueError):
             return None
+
+    @staticmethod
+    def unavailable_reason(kernel_type: str) -> Optional[str]:
+        """内核不可用的原因；可用时返回 None"""
+        spec = _KERNELS.get(kernel_type)
+        if spec is None:
+            return f"未知的计算内核: {kernel_type}"
+        if spec["adapter"] is None:
+            return f"{spec['name']} 内核尚未实现"
+        try:
+            _instantiate(kernel_type)
+            _create_adapter(spec["adapter"])
+        except ImportError as e:
+            return f"依赖未安装: {e}"
+        return None
+
+    @staticmethod
+    def list_kernels() -> List[Dict[str, Any]]:
+        """全部内核的能力描述：可用性、是否支持三相不平衡与无功、生效的求解参数与功能列表"""
+        kernels = []
+        for kernel_type, spec in _KERNELS.items():
+            reason = PowerKernelFactory.unavailable_reason(kernel_type)
+            features: List[str] = []
+            if reason is None:
+                features = _instantiate(kernel_type).get_supported_features()
+            kernels.append({
+                "id": kernel_type,
+                "name": spec["name"],
+                "description": spec["description"],
+                "available": reason is None,
+                "unavailable_reason": reason,
+                "unbalanced": spec["unbalanced"],
+                "reactive_power": spec["reactive_power"],
+                "solver_options": list(spec["solver_options"]),
+                "features": features,
+            })
+        return kernels
@@ -0,0 +1,124 @@
+"""
+pandapower 三相不平衡潮流内核（runpp_3ph）
+
+网络由同一适配器构建；平衡负载/光伏/储能按三相对称注入，asymmetric_load / asymmetric_sgen 表中的元件按相注入。
+求解后把 res_*_3ph 结果按相汇总写回 res_bus / res_line 等平衡结果表（母线电压取三相平均，
+功率为三相之和，电流取最大相），Rust 端与结果检查沿用同一套字段；各相原始结果保留在行内。
+"""
+
+from typing import Dict, Any, List
+import pandas as pd
+
+from .pandapower_impl import PandapowerKernel
+
+PHASES = ("a", "b", "c")
+
+# 零序参数缺省值（典型估算）：线路零序阻抗约为正序的 3~4 倍，外部电网按 1000 MVA 短路容量
+_LINE_ZERO_SEQUENCE = {"r0_ohm_per_km": ("r_ohm_per_km", 4.0), "x0_ohm_per_km": ("x_ohm_per_km", 3.0), "c0_nf_per_km": ("c_nf_per_km", 0.5)}
+_EXT_GRID_DEFAULTS = {"s_sc_max_mva": 1000.0, "rx_max": 0.1, "x0x_max": 1.0, "r0x0_max": 0.1}
+_TRAFO_DEFAULTS = {"vector_group": "Dyn", "mag0_percent": 100.0, "mag0_rx": 0.0, "si0_hv_partial": 0.9}
+
+
+def _fill(table: pd.DataFrame, column: str, values) -> None:
+    """补齐缺失列或 NaN（已有零序参数不覆盖）"""
+    if table is None or table.empty:
+        return
+    if column not in table.columns:
+        table[column] = values
+    else:
+        table[column] = table[column].where(table[column].notna(), values)
+
+
+def _phase_sum(df: pd.DataFrame, pattern: str) -> pd.Series:
+    columns = [pattern.format(p) for p in PHASES if pattern.format(p) in df.columns]
+    return df[columns].sum(axis=1) if columns else pd.Series(0.0, index=df.index)
+
+
+def _phase_max(df: pd.DataFrame, pattern: str) -> pd.Series:
+    columns = [pattern.format(p) for p in PHASES if pattern.format(p) in df.columns]
+    return df[columns].max(axis=1) if columns else pd.Series(float("nan"), index=df.index)
+
+
+class Pandapower3phKernel(PandapowerKernel):
+    """pandapower 三相不平衡潮流内核"""
+
+    def _run_power_flow(self, options: Dict[str, Any]) -> None:
+        self._fill_zero_sequence_parameters()
+        # runpp_3ph 只支持牛顿-拉夫逊，不支持以上一拍结果热启动
+        kwargs = {k: options[k] for k in ("max_iteration", "tolerance_mva") if k in options}
+        if options.get("init") in ("auto", "flat"):
+            kwargs["init"] = options["init"]
+        self.pp.runpp_3ph(self.net, check_connectivity=True, **kwargs)
+        self._write_balanced_results()
+
+    def _solver_diagnostics(self, options: Dict[str, Any]) -> Dict[str, Any]:
+        diagnostics = super()._solver_diagnostics(options)
+        diagnostics["algorithm"] = "nr_3ph"
+        diagnostics["init"] = options.get("init") if options.get("init") in ("auto", "flat") else "auto"
+        return diagnostics
+
+    def _fill_zero_sequence_parameters(self) -> None:
+        net = self.net
+        for column, (positive, factor) in _LINE_ZERO_SEQUENCE.items():
+            if not net.line.empty:
+                _fill(net.line, column, net.line[positive] * factor)
+        for column, value in _EXT_GRID_DEFAULTS.items():
+            _fill(net.ext_grid, column, value)
+        if not net.trafo.empty:
+            for column, value in _TRAFO_DEFAULTS.items():
+                _fill(net.trafo, column, value)
+            _fill(net.trafo, "vk0_percent", net.trafo["vk_percent"])
+            _fill(net.trafo, "vkr0_percent", net.trafo["vkr_percent"])
+
+    def _write_balanced_results(self) -> None:
+        """res_*_3ph -> 平衡结果表字段；切换回平衡内核时会被 runpp 覆盖"""
+        net = self.net
+        res = net.res_bus_3ph.copy()
+        vm = [f"vm_{p}_pu" for p in PHASES if f"vm_{p}_pu" in res.columns]
+        res["vm_pu"] = res[vm].mean(axis=1)
+        res["va_degree"] = res.get("va_a_degree", 0.0)
+        res["p_mw"] = _phase_sum(res, "p_{}_mw")
+        res["q_mvar"] = _phase_sum(res, "q_{}_mvar")
+        net.res_bus = res
+
+        if not net.line.empty:
+            res = net.res_line_3ph.copy()
+            for side in ("from", "to"):
+                res[f"p_{side}_mw"] = _phase_sum(res, "p_{}_" + side + "_mw")
+                res[f"q_{side}_mvar"] = _phase_sum(res, "q_{}_" + side + "_mvar")
+                res[f"i_{side}_ka"] = _phase_max(res, "i_{}_" + side + "_ka")
+            res["pl_mw"] = _phase_sum(res, "p_{}_l_mw")
+            res["ql_mvar"] = _phase_sum(res, "q_{}_l_mvar")
+            res["i_ka"] = res[["i_from_ka", "i_to_ka"]].max(axis=1)
+            net.res_line = res
+
+        if not net.trafo.empty:
+            res = net.res_trafo_3ph.copy()
+            for side in ("hv", "lv"):
+                res[f"p_{side}_mw"] = _phase_sum(res, "p_{}_" + side + "_mw")
+                res[f"q_{side}_mvar"] = _phase_sum(res, "q_{}_" + side + "_mvar")
+                res[f"i_{side}_ka"] = _phase_max(res, "i_{}_" + side + "_ka")
+            res["pl_mw"] = _phase_sum(res, "p_{}_l_mw")
+            res["ql_mvar"] = _phase_sum(res, "q_{}_l_mvar")
+            net.res_trafo = res
+
+        res = net.res_ext_grid_3ph.copy()
+        res["p_mw"] = _phase_sum(res, "p_{}_mw")
+        res["q_mvar"] = _phase_sum(res, "q_{}_mvar")
+        net.res_ext_grid = res
+
+        # 单端元件的三相结果已是总功率
+        for table in ("load", "sgen", "storage"):
+            res_3ph = getattr(net, f"res_{table}_3ph", None)
+            if res_3ph is not None and not getattr(net, table).empty:
+                setattr(net, f"res_{table}", res_3ph.copy())
+        # runpp_3ph 不计算开关结果，清空以免沿用上一次平衡潮流的数值
+        net.res_switch = net.res_switch.iloc[0:0]
+
+    def get_supported_features(self) -> List[str]:
+        """获取支持的功能列表"""
+        return [
+            "Unbalanced 3-phase power flow",
+            "Per-phase voltages and currents",
+            "Asymmetric loads and generators",
+        ]
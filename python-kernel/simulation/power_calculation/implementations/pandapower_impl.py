@@ -21,6 +21,48 @@ def _nan_to_none(obj: Any) -> Any:
     return obj
 
 
+# pandapower 诊断结果中可能出现的元素表名（单/复数形式）到标准表名的映射
+_DIAGNOSTIC_ELEMENT_TABLES = {
+    "bus": "bus", "buses": "bus",
+    "line": "line", "lines": "line",
+    "trafo": "trafo", "trafos": "trafo",
+    "switch": "switch", "switches": "switch",
+    "sgen": "sgen", "sgens": "sgen",
+    "load": "load", "loads": "load",
+    "storage": "storage", "storages": "storage",
+}
+
+
+def _collect_diagnostic_elements(value: Any, table: str = None, out: Dict[str, List[int]] = None) -> Dict[str, List[int]]:
+    """
+    递归提取 pandapower 诊断结果中涉及的元素索引。
+
+    诊断结果结构因检查项而异（dict/list/tuple 嵌套），这里按键名识别元素表，
+    收集其下的整数索引（tuple 取首元素，如 invalid_values 的 (idx, 参数, 值, 期望)）。
+    """
+    if out is None:
+        out = {}
+    if isinstance(value, dict):
+        for key, sub in value.items():
+            sub_table = _DIAGNOSTIC_ELEMENT_TABLES.get(str(key), table)
+            _collect_diagnostic_elements(sub, sub_table, out)
+    elif isinstance(value, (list, tuple, set)):
+        if table and isinstance(value, tuple) and value and not isinstance(value[0], (list, tuple, dict)):
+            _collect_diagnostic_elements(value[0], table, out)
+        else:
+            for item in value:
+                _collect_diagnostic_elements(item, table, out)
+    elif table and not isinstance(value, bool):
+        try:
+            idx = int(value)
+        except (TypeError, ValueError):
+            return out
+        indices = out.setdefault(table, [])
+        if idx not in indices:
+            indices.append(idx)
+    return out
+
+
 class PandapowerKernel(PowerCalculationKernel):
     """pandapower 计算内核实现"""
     
@@ -29,6 +71,8 @@ class PandapowerKernel(PowerCalculationKernel):
             import pandapower as pp
             self.pp = pp
             self.net = None
+            # 额外的 runpp 参数（algorithm / max_iteration / tolerance_mva / init），由仿真引擎按预设设置
+            self.solver_options: Dict[str, Any] = {}
         except ImportError:
             raise ImportError("pandapower is not installed. Please install it with: pip install pandapower")
     
@@ -66,8 +110,12 @@ class PandapowerKernel(PowerCalculationKernel):
             # 执行潮流计算（check_connectivity=True：自动检测并跳过断联孤岛母线，
             # 避免开关断开后产生的隔离区域导致整体计算不收敛）
             calculation_failed = False
+            options = dict(self.solver_options)
+            # init=results 热启动需上一拍的收敛结果，首拍或上一拍失败时退回 auto
+            if options.get("init") == "results" and not self._has_previous_results():
+                options["init"] = "auto"
             try:
-                self.pp.runpp(self.net, check_connectivity=True)
+                self._run_power_flow(options)
             except Exception as calc_error:
                 calculation_failed = True
                 errors.append({
@@ -97,11 +145,13 @@ class PandapowerKernel(PowerCalculationKernel):
                     diagnostic = self.pp.diagnostic(self.net)
                     if diagnostic:
                         for diag_item in diagnostic:
+                            # 提取诊断结果中涉及的元素索引，供引擎层映射回设备ID
+                            elements = _collect_diagnostic_elements(diagnostic[diag_item])
                             errors.append({
                                 "type": "calculation",
                                 "severity": "warning",
                                 "message": f"诊断信息: {diag_item}",
-                                "details": {"diagnostic": diag_item}
+                                "details": {"diagnostic": diag_item, "elements": elements}
                             })
                 except Exception:
                     # 诊断功能可能不可用，忽略
@@ -114,7 +164,8 @@ class PandapowerKernel(PowerCalculationKernel):
             results = {
                 "converged": converged,
                 "errors": errors,
-                "devices": {}
+                "devices": {},
+                "solver": self._solver_diagnostics(options),
             }
 
             def _res_to_row_dict(net, res_df, table_name: str) -> Dict[str, Dict[str, Any]]:
@@ -123,10 +174,14 @@ class PandapowerKernel(PowerCalculationKernel):
                 table = getattr(net, table_name, None)
                 name_series = table["name"] if table is not None and "name" in table.columns else None
                 out = {}
+                # 单端元件（sgen/load/storage）附带所接母线索引，供 Rust 端按并网点电压控制
+                bus_series = table["bus"] if table is not None and "bus" in table.columns else None
                 for idx in res_df.index:
                     row = res_df.loc[idx].to_dict()
                     if name_series is not None and idx in name_series.index:
                         row["name"] = name_series[idx]
+                    if bus_series is not None and idx in bus_series.index:
+                        row["bus"] = int(bus_series[idx])
                     out[str(idx)] = _nan_to_none(row)
                 return out
 
@@ -233,6 +288,27 @@ class PandapowerKernel(PowerCalculationKernel):
                 "devices": {}
             }
     
+    def _run_power_flow(self, options: Dict[str, Any]) -> None:
+        """求解当前网络，结果写入 net.res_* 表（子类替换为其他求解方式）"""
+        self.pp.runpp(self.net, check_connectivity=True, **options)
+
+    def _has_previous_results(self) -> bool:
+        """网络是否保留上一拍的收敛结果（可用于 init=results 热启动）"""
+        res_bus = getattr(self.net, "res_bus", None)
+        return bool(getattr(self.net, "converged", False)) and res_bus is not None and not res_bus.empty
+
+    def _solver_diagnostics(self, options: Dict[str, Any]) -> Dict[str, Any]:
+        """本拍求解诊断：实际使用的算法与初值方式、迭代次数与求解耗时（pandapower 内部 _ppc，缺失时为 None）"""
+        ppc = getattr(self.net, "_ppc", None) or {}
+        iterations = ppc.get("iterations") if isinstance(ppc, dict) else None
+        elapsed = ppc.get("et") if isinstance(ppc, dict) else None
+        return {
+            "algorithm": options.get("algorithm", "nr"),
+            "init": options.get("init", "auto"),
+            "iterations": int(iterations) if iterations is not None else None,
+            "elapsed_ms": float(elapsed) * 1000.0 if elapsed is not None else None,
+        }
+
     def _check_result_errors(self, net, errors: List[Dict[str, Any]]):
         """检查结果表中的错误标记"""
         try:
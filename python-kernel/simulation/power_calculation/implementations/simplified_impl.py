@@ -0,0 +1,30 @@
+"""
+简化求解内核：直流潮流（rundcpp）
+
+线性化求解，只计算有功分布与电压相角：电压幅值恒为 1.0 pu，不计无功与线路损耗。
+无需迭代、总能求出结果，适合大网络或长时间快速扫描时粗略评估线路/变压器负载率。
+"""
+
+from typing import Dict, Any, List
+
+from .pandapower_impl import PandapowerKernel
+
+
+class SimplifiedKernel(PandapowerKernel):
+    """直流潮流内核（忽略求解参数）"""
+
+    def _run_power_flow(self, options: Dict[str, Any]) -> None:
+        self.pp.rundcpp(self.net, check_connectivity=True)
+
+    def _solver_diagnostics(self, options: Dict[str, Any]) -> Dict[str, Any]:
+        diagnostics = super()._solver_diagnostics(options)
+        diagnostics["algorithm"] = "dc"
+        diagnostics["init"] = "flat"
+        return diagnostics
+
+    def get_supported_features(self) -> List[str]:
+        """获取支持的功能列表"""
+        return [
+            "DC power flow",
+            "Active power distribution and loading",
+        ]
@@ -0,0 +1,94 @@
+"""
+单次潮流快照：对给定拓扑按统一负荷假设执行一次潮流计算，不影响运行中的仿真引擎。
+用于多拓扑方案（如加固方案）的母线电压与支路负载率对比。
+"""
+from typing import Dict, Any, List, Optional
+
+from .adapters.pandapower_adapter import PandapowerTopologyAdapter
+
+
+# 各设备类型功率取额定功率的比例；储能正=充电（与 pandapower 一致）
+DEFAULT_ASSUMPTIONS = {
+    "load_factor": 1.0,
+    "charger_factor": 1.0,
+    "pv_factor": 0.0,
+    "storage_factor": 0.0,
+}
+
+
+def _rated_kw(properties: Dict[str, Any]) -> float:
+    for key in ("rated_power", "max_power_kw", "max_power", "p_kw"):
+        value = properties.get(key)
+        if value is None:
+            continue
+        try:
+            return float(value)
+        except (TypeError, ValueError):
+            continue
+    return 0.0
+
+
+def run_snapshot_powerflow(calculator, topology_data: Dict[str, Any],
+                           assumptions: Optional[Dict[str, Any]] = None,
+                           out_of_service: Optional[List[str]] = None) -> Dict[str, Any]:
+    """
+    转换拓扑并按负荷假设设置功率后执行一次潮流计算。
+
+    assumptions: {"load_factor", "charger_factor", "pv_factor", "storage_factor"}，缺省见 DEFAULT_ASSUMPTIONS
+    out_of_service: 计算前退出运行的线路/变压器设备 ID（N-1 预想故障）
+    返回 calculate_power_flow 的结果（converged / errors / devices），
+    另附 element_ids：各结果表索引到设备 ID 的映射（buses / lines / transformers）
+    """
+    factors = dict(DEFAULT_ASSUMPTIONS)
+    factors.update({k: float(v) for k, v in (assumptions or {}).items() if k in DEFAULT_ASSUMPTIONS and v is not None})
+
+    adapter = PandapowerTopologyAdapter()
+    converted = adapter.convert(topology_data)
+    if not converted.success:
+        return {
+            "converged": False,
+            "errors": [
+                {"type": e.error_type, "severity": e.severity, "message": e.message, "device_id": e.device_id}
+                for e in converted.errors
+            ],
+            "devices": {},
+        }
+    net = converted.data
+
+    devices = topology_data.get("devices", {})
+    devices_dict = {d.get("id", ""): d for d in devices if d.get("id")} if isinstance(devices, list) else devices
+    table_by_type = {
+        "Load": ("loads", "load", "load_factor"),
+        "Charger": ("loads", "load", "charger_factor"),
+        "Pv": ("generators", "sgen", "pv_factor"),
+        "Storage": ("storages", "storage", "storage_factor"),
+    }
+    for device_id, device in devices_dict.items():
+        entry = table_by_type.get(device.get("device_type", ""))
+        if not entry:
+            continue
+        map_key, table_name, factor_key = entry
+        idx = adapter.device_map.get(map_key, {}).get(device_id)
+        table = getattr(net, table_name, None)
+        if idx is None or table is None or idx not in table.index:
+            continue
+        table.at[idx, "p_mw"] = _rated_kw(device.get("properties", {})) * factors[factor_key] / 1000.0
+
+    for device_id in out_of_service or []:
+        if device_id in adapter.device_map["lines"]:
+            net.line.at[adapter.device_map["lines"][device_id], "in_service"] = False
+        elif device_id in adapter.device_map["transformers"]:
+            net.trafo.at[adapter.device_map["transformers"][device_id], "in_service"] = False
+        else:
+            return {"status": "error", "message": f"设备 {device_id} 不是可退出运行的线路或变压器"}
+
+    result = calculator.calculate_power_flow(net)
+    result["element_ids"] = {
+        "buses": {
+            str(idx): device_id for device_id, idx in adapter.bus_map.items()
+            if devices_dict.get(device_id, {}).get("device_type") == "Node"
+        },
+        "lines": {str(idx): device_id for device_id, idx in adapter.device_map["lines"].items()},
+        "transformers": {str(idx): device_id for device_id, idx in adapter.device_map["transformers"].items()},
+    }
+    return result
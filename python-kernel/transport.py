@@ -0,0 +1,298 @@
+# -*- coding: utf-8 -*-
+"""
+内核传输：stdio，或在 TCP / WebSocket 端点上监听（部署在容器或远程主机），
+Rust 端通过设置项 kernel_endpoint 或环境变量 PYTHON_KERNEL_ENDPOINT 连接。
+
+消息编码由 Rust 端启动时发送 kernel.negotiate 协商（协商本身为 JSON），响应发出后双方切换：
+- json：stdio / TCP 每行一个 JSON-RPC 对象，WebSocket 每个文本帧一个
+- msgpack：stdio / TCP 为 4 字节大端长度前缀 + MessagePack 消息体，WebSocket 每个二进制帧一个
+未安装 msgpack 时只支持 json。
+
+内核状态为单会话，远程端点同一时间只服务一个连接，断开后等待下一个连接。
+远程端点未指定主机时只监听 127.0.0.1；必须经 --token 或环境变量 PYTHON_KERNEL_TOKEN 配置共享令牌，
+客户端在 kernel.negotiate 的 params.token 中携带，认证通过前拒绝其他所有方法。
+kernel.shutdown（由 main.py 处理）的响应发出后结束服务：stdio 进程退出，远程端点关闭监听。
+"""
+
+import base64
+import hashlib
+import hmac
+import json
+import socket
+import struct
+import sys
+import traceback
+from typing import Any, Callable, Dict, Optional, Tuple
+from urllib.parse import urlparse
+
+try:
+    import msgpack
+except ImportError:
+    msgpack = None
+
+WS_GUID = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11"
+# 单条消息上限（拓扑数据与大型网络的结果）
+MAX_MESSAGE_BYTES = 256 * 1024 * 1024
+NEGOTIATE_METHOD = "kernel.negotiate"
+SHUTDOWN_METHOD = "kernel.shutdown"
+SUPPORTED_ENCODINGS = ("msgpack", "json") if msgpack is not None else ("json",)
+# 认证失败 / 未认证的错误码（Rust 端据此中止连接，而不是当作旧版内核回退）
+AUTH_ERROR_CODE = -32001
+
+RequestHandler = Callable[[Dict[str, Any]], Dict[str, Any]]
+
+
+class Session:
+    """一个连接（或 stdio）上的协议状态：协商前为 json，kernel.negotiate 的响应发出后切换为协商结果"""
+
+    def __init__(self, handle_request: RequestHandler, token: Optional[str] = None):
+        self.handle_request = handle_request
+        # 远程会话的共享令牌；None 表示无需认证（stdio）
+        self.token = token
+        self.authenticated = token is None
+        self.encoding = "json"
+        # 已应答 kernel.shutdown，发出响应后结束服务
+        self.closing = False
+
+    def handle(self, payload: bytes) -> Tuple[bytes, str]:
+        """处理一条请求，返回 (响应消息体, 消息体所用编码)"""
+        encoding = self.encoding
+        try:
+            request = self._decode(payload)
+            if not isinstance(request, dict):
+                raise ValueError("请求不是 JSON-RPC 对象")
+        except Exception as e:
+            return self._encode(_error_response(None, -32700, f"Parse error: {e}"), encoding), encoding
+        try:
+            if request.get("method") == NEGOTIATE_METHOD:
+                response = self._negotiate(request)
+            elif not self.authenticated:
+                response = _error_response(request.get("id"), AUTH_ERROR_CODE, "内核认证失败：未经 kernel.negotiate 认证")
+            else:
+                response = self.handle_request(request)
+            body = self._encode(response, encoding)
+        except Exception as e:
+            # 含结果无法序列化的情况
+            traceback.print_exc(file=sys.stderr)
+            response = _error_response(request.get("id"), -32000, str(e))
+            body = self._encode(response, encoding)
+        if request.get("method") == NEGOTIATE_METHOD and "result" in response:
+            self.encoding = response.get("result", {}).get("encoding", "json")
+            print(f"Negotiated encoding: {self.encoding}", file=sys.stderr)
+            sys.stderr.flush()
+        if request.get("method") == SHUTDOWN_METHOD and "result" in response:
+            self.closing = True
+        return body, encoding
+
+    def _negotiate(self, request: Dict[str, Any]) -> Dict[str, Any]:
+        params = request.get("params") or {}
+        if self.token is not None:
+            supplied = params.get("token")
+            if not isinstance(supplied, str) or not hmac.compare_digest(supplied.encode("utf-8"), self.token.encode("utf-8")):
+                return _error_response(request.get("id"), AUTH_ERROR_CODE, "内核认证失败：令牌无效")
+            self.authenticated = True
+        offered = params.get("encodings") or ["json"]
+        chosen = next((e for e in offered if e in SUPPORTED_ENCODINGS), "json")
+        return {"jsonrpc": "2.0", "id": request.get("id"), "result": {"encoding": chosen}}
+
+    def _decode(self, payload: bytes) -> Dict[str, Any]:
+        if self.encoding == "msgpack":
+            return msgpack.unpackb(payload, raw=False)
+        return json.loads(payload)
+
+    @staticmethod
+    def _encode(response: Dict[str, Any], encoding: str) -> bytes:
+        if encoding == "msgpack":
+            return msgpack.packb(response, use_bin_type=True)
+        # ensure_ascii=False 避免 \uXXXX 转义产生 lone surrogate
+        return json.dumps(response, ensure_ascii=False).encode("utf-8")
+
+
+def _error_response(request_id, code: int, message: str) -> Dict[str, Any]:
+    return {"jsonrpc": "2.0", "id": request_id, "error": {"code": code, "message": message}}
+
+
+def serve_stdio(handle_request: RequestHandler) -> None:
+    """经 stdin/stdout 服务（本地进程）；其他模块误写 stdout 会破坏分帧，因此将 sys.stdout 重定向到 stderr"""
+    reader = sys.stdin.buffer
+    out = sys.stdout.buffer
+    sys.stdout = sys.stderr
+
+    def write(data: bytes) -> None:
+        out.write(data)
+        out.flush()
+
+    _serve_stream(reader, write, Session(handle_request))
+
+
+def serve(url: str, handle_request: RequestHandler, token: Optional[str]) -> None:
+    """在 tcp://主机:端口 或 ws://主机:端口/路径 上监听并逐个服务连接（收到 kernel.shutdown 后返回）；token 为必需的共享令牌"""
+    parsed = urlparse(url)
+    scheme = parsed.scheme.lower()
+    if scheme not in ("tcp", "ws") or parsed.port is None:
+        raise ValueError(f"监听地址格式无效（应为 tcp://主机:端口 或 ws://主机:端口/路径）: {url}")
+    if not token:
+        raise ValueError("远程内核端点必须配置共享令牌（--token 或环境变量 PYTHON_KERNEL_TOKEN）")
+    host = parsed.hostname or "127.0.0.1"
+    server = socket.create_server((host, parsed.port))
+    print(f"Python kernel listening on {scheme}://{host}:{parsed.port}", file=sys.stderr)
+    sys.stderr.flush()
+    while True:
+        conn, addr = server.accept()
+        print(f"Client connected: {addr}", file=sys.stderr)
+        sys.stderr.flush()
+        conn.setsockopt(socket.IPPROTO_TCP, socket.TCP_NODELAY, 1)
+        # 每个连接重新协商编码
+        session = Session(handle_request, token)
+        try:
+            with conn:
+                if scheme == "tcp":
+                    _serve_stream(conn.makefile("rb"), conn.sendall, session)
+                else:
+                    _serve_websocket(conn, session)
+        except (OSError, ValueError) as e:
+            print(f"Connection error: {e}", file=sys.stderr)
+        print(f"Client disconnected: {addr}", file=sys.stderr)
+        sys.stderr.flush()
+        if session.closing:
+            server.close()
+            return
+
+
+def _serve_stream(reader, write: Callable[[bytes], Any], session: Session) -> None:
+    """stdio / TCP：json 按行分帧，msgpack 按长度前缀分帧"""
+    while True:
+        payload = _read_stream_message(reader, session.encoding)
+        if payload is None:
+            return
+        if session.encoding == "json" and not payload.strip():
+            continue
+        body, encoding = session.handle(payload)
+        if encoding == "msgpack":
+            write(struct.pack("!I", len(body)) + body)
+        else:
+            write(body + b"\n")
+        if session.closing:
+            return
+
+
+def _read_stream_message(reader, encoding: str) -> Optional[bytes]:
+    if encoding == "msgpack":
+        header = _read_exact(reader, 4)
+        if header is None:
+            return None
+        length = struct.unpack("!I", header)[0]
+        if length > MAX_MESSAGE_BYTES:
+            raise ValueError("消息过大")
+        return _read_exact(reader, length) if length else b""
+    line = reader.readline()
+    return line if line else None
+
+
+def _serve_websocket(conn: socket.socket, session: Session) -> None:
+    reader = conn.makefile("rb")
+    headers = {}
+    request_line = reader.readline()
+    if not request_line.startswith(b"GET "):
+        raise ValueError("不是 WebSocket 握手请求")
+    while True:
+        raw = reader.readline()
+        if not raw or raw in (b"\r\n", b"\n"):
+            break
+        name, _, value = raw.decode("latin-1").partition(":")
+        headers[name.strip().lower()] = value.strip()
+    key = headers.get("sec-websocket-key")
+    if not key:
+        conn.sendall(b"HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\n\r\n")
+        return
+    accept = base64.b64encode(hashlib.sha1((key + WS_GUID).encode("ascii")).digest()).decode("ascii")
+    conn.sendall(
+        (
+            "HTTP/1.1 101 Switching Protocols\r\n"
+            "Upgrade: websocket\r\n"
+            "Connection: Upgrade\r\n"
+            f"Sec-WebSocket-Accept: {accept}\r\n\r\n"
+        ).encode("ascii")
+    )
+
+    message = bytearray()
+    while True:
+        frame = _read_frame(reader)
+        if frame is None:
+            return
+        fin, opcode, payload = frame
+        if opcode == 0x8:
+            _send_frame(conn, 0x8, payload[:2])
+            return
+        if opcode == 0x9:
+            _send_frame(conn, 0xA, payload)
+            continue
+        if opcode == 0xA:
+            continue
+        message.extend(payload)
+        if len(message) > MAX_MESSAGE_BYTES:
+            raise ValueError("WebSocket 消息过大")
+        if not fin:
+            continue
+        payload = bytes(message)
+        message.clear()
+        if session.encoding == "json" and not payload.strip():
+            continue
+        body, encoding = session.handle(payload)
+        _send_frame(conn, 0x2 if encoding == "msgpack" else 0x1, body)
+        if session.closing:
+            _send_frame(conn, 0x8, struct.pack("!H", 1000))
+            return
+
+
+def _read_exact(reader, size: int) -> Optional[bytes]:
+    data = reader.read(size)
+    if data is None or len(data) < size:
+        return None
+    return data
+
+
+def _read_frame(reader):
+    """读取一帧，返回 (fin, opcode, 去掩码后的负载)；连接关闭时返回 None"""
+    header = _read_exact(reader, 2)
+    if header is None:
+        return None
+    fin = bool(header[0] & 0x80)
+    opcode = header[0] & 0x0F
+    masked = bool(header[1] & 0x80)
+    length = header[1] & 0x7F
+    if length == 126:
+        ext = _read_exact(reader, 2)
+        if ext is None:
+            return None
+        length = struct.unpack("!H", ext)[0]
+    elif length == 127:
+        ext = _read_exact(reader, 8)
+        if ext is None:
+            return None
+        length = struct.unpack("!Q", ext)[0]
+    if length > MAX_MESSAGE_BYTES:
+        raise ValueError("WebSocket 帧过大")
+    mask = _read_exact(reader, 4) if masked else None
+    if masked and mask is None:
+        return None
+    payload = _read_exact(reader, length) if length else b""
+    if payload is None:
+        return None
+    if mask and length:
+        # 整块异或去掩码（逐字节处理大消息过慢）
+        key = (mask * (length // 4 + 1))[:length]
+        payload = (int.from_bytes(payload, "big") ^ int.from_bytes(key, "big")).to_bytes(length, "big")
+    return fin, opcode, payload
+
+
+def _send_frame(conn: socket.socket, opcode: int, payload: bytes) -> None:
+    """服务端帧不加掩码"""
+    length = len(payload)
+    if length < 126:
+        header = struct.pack("!BB", 0x80 | opcode, length)
+    elif length < 65536:
+        header = struct.pack("!BBH", 0x80 | opcode, 126, length)
+    else:
+        header = struct.pack("!BBQ", 0x80 | opcode, 127, length)
+    conn.sendall(header + payload)
@@ -43,7 +43,7 @@ pub async fn predict_device_data(
     request: PredictionRequest,
     python_bridge: State<'_, TokioMutex<PythonBridge>>,
 ) -> Result<Vec<PredictionResult>, String> {
-    let mut bridge = python_bridge.lock().await;
+    let bridge = python_bridge.lock().await.client();
     let params = serde_json::to_value(&request)
         .map_err(|e| format!("Failed to serialize request: {}", e))?;
     
@@ -59,7 +59,7 @@ pub async fn optimize_operation(
     request: OptimizationRequest,
     python_bridge: State<'_, TokioMutex<PythonBridge>>,
 ) -> Result<OptimizationResult, String> {
-    let mut bridge = python_bridge.lock().await;
+    let bridge = python_bridge.lock().await.client();
     let params = serde_json::to_value(&request)
         .map_err(|e| format!("Failed to serialize request: {}", e))?;
     
@@ -75,7 +75,7 @@ pub async fn get_ai_recommendations(
     device_ids: Vec<String>,
     python_bridge: State<'_, TokioMutex<PythonBridge>>,
 ) -> Result<Vec<String>, String> {
-    let mut bridge = python_bridge.lock().await;
+    let bridge = python_bridge.lock().await.client();
     let params = serde_json::json!({
         "device_ids": device_ids
     });
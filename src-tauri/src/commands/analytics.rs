@@ -3,6 +3,21 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use crate::commands::dashboard;
 use crate::commands::dashboard::TimeSeriesPoint;
+use crate::domain::grid_schedule::GridSchedule;
+use crate::domain::metadata::DeviceMetadataStore;
+use crate::domain::sign_convention::{PowerSign, SignConvention};
+use crate::domain::topology::DeviceType;
+use crate::services::chart_renderer::{self, ChartSpec};
+use crate::services::csv_cache::CsvCache;
+use crate::services::database::Database;
+use crate::services::data_source::{open_data_source, DataSource, DataSourceContext, DataSourceSpec};
+use crate::services::energy_balance::{self, EnergyBalanceBaseline, EnergyBalanceReport, EnergyBalanceStore};
+use crate::services::modbus::ModbusService;
+use crate::services::settings::SettingsStore;
+use crate::services::simulation_engine::SimulationEngine;
+use crate::services::timezone::ImportTimezone;
+use std::sync::{Arc, Mutex};
+use tauri::State;
 
 /// 数据源类型
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,6 +42,30 @@ pub struct PriceConfig {
     pub capacity_charge_per_kva_month: Option<f64>,
 }
 
+impl PriceConfig {
+    /// 由外部电网分时计划生成单一制电价配置；计划未覆盖全部 24 小时电价时返回 None
+    pub fn from_grid_schedule(schedule: &GridSchedule) -> Option<Self> {
+        Some(Self {
+            tou_prices: schedule.tou_prices()?,
+            voltage_level: "under_1kv".to_string(),
+            tariff_type: "single".to_string(),
+            demand_charge_per_kw_month: None,
+            capacity_charge_per_kva_month: None,
+        })
+    }
+}
+
+/// 从当前拓扑中首个配置了完整电价计划的 ExternalGrid 读取电价
+fn price_config_from_topology(metadata_store: &Mutex<DeviceMetadataStore>) -> Option<PriceConfig> {
+    let store = metadata_store.lock().unwrap();
+    store
+        .get_all_devices()
+        .iter()
+        .filter(|d| d.device_type == DeviceType::ExternalGrid)
+        .filter_map(|d| GridSchedule::from_properties(&d.properties))
+        .find_map(|s| PriceConfig::from_grid_schedule(&s))
+}
+
 /// 性能分析：数据角色到 key 的映射
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PerformanceDataMapping {
@@ -66,6 +105,22 @@ pub struct AnalysisRequest {
     /// 性能分析：数据角色映射
     #[serde(default)]
     pub performance_data_mapping: Option<PerformanceDataMapping>,
+    /// 统一数据源（实时/DB/CSV/SSH 远程），提供时优先于 data_source + file_path
+    #[serde(default)]
+    pub source: Option<DataSourceSpec>,
+    /// 数据时区：CSV 按 file_path 读取时用于解析无时区时间戳，收益分析按该时区的本地小时分桶；
+    /// 未提供时取 source 中 CSV 的导入时区，否则为 UTC
+    #[serde(default)]
+    pub timezone: Option<ImportTimezone>,
+}
+
+impl AnalysisRequest {
+    /// 分析分桶使用的时区
+    fn analysis_timezone(&self) -> ImportTimezone {
+        self.timezone
+            .or_else(|| self.source.as_ref().and_then(|s| s.timezone()))
+            .unwrap_or_default()
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -101,11 +156,20 @@ pub struct ReportRequest {
     pub performance_standards: Option<Vec<String>>,
     #[serde(default)]
     pub performance_data_mapping: Option<PerformanceDataMapping>,
+    #[serde(default)]
+    pub source: Option<DataSourceSpec>,
+    #[serde(default)]
+    pub timezone: Option<ImportTimezone>,
+    /// 报告配图：按规格从仿真库渲染，未指定 output_path 时保存在报告旁（报告名_chart序号.svg/png）
+    #[serde(default)]
+    pub charts: Option<Vec<ChartSpec>>,
 }
 
-/// 根据请求解析得到各 key 的时间序列（仅 [start_time, end_time] 内）
+/// 根据请求解析得到各 key 的时间序列（仅 [start_time, end_time] 内）；仿真数据的功率按 convention 输出
 async fn resolve_series(
     request: &AnalysisRequest,
+    ctx: &DataSourceContext,
+    convention: &SignConvention,
 ) -> Result<HashMap<String, Vec<dashboard::TimeSeriesPoint>>, String> {
     let start = request.start_time;
     let end = request.end_time;
@@ -149,22 +213,26 @@ async fn resolve_series(
         }
         out
     } else {
-        match &request.data_source {
-            DataSourceKind::LocalFile => {
-                let path = request.file_path.as_ref().ok_or("本地文件数据源需提供 file_path")?;
-                dashboard::dashboard_fetch_series_batch(
-                    path.clone(),
-                    keys,
-                    Some(start),
-                    Some(end),
-                    Some(5000),
-                )
-                .await?
-            }
-            DataSourceKind::Csv => {
-                return Err("CSV 数据源需在前端传入 series_data".to_string());
-            }
+        // 统一经 DataSource 取数：显式 source 优先，否则按 data_source + file_path 推断（DB / 宽表 CSV）
+        let spec = match (&request.source, &request.data_source) {
+            (Some(spec), _) => spec.clone(),
+            (None, DataSourceKind::LocalFile) => DataSourceSpec::Db {
+                path: request.file_path.clone().ok_or("本地文件数据源需提供 file_path")?,
+            },
+            (None, DataSourceKind::Csv) => DataSourceSpec::WideCsv {
+                path: request.file_path.clone().ok_or("CSV 数据源需提供 file_path 或 series_data")?,
+                column_parser: Default::default(),
+                timezone: request.timezone.unwrap_or_default(),
+            },
+        };
+        let ds = open_data_source(&spec, ctx)?;
+        let mut out = HashMap::new();
+        for key in keys {
+            let pts = ds.fetch_series(&key, Some(start), Some(end), 5000)?;
+            out.insert(key, pts);
         }
+        normalize_power_signs(&spec, ctx, convention, &mut out).await?;
+        out
     };
 
     for (_k, v) in series.iter_mut() {
@@ -173,6 +241,137 @@ async fn resolve_series(
     Ok(series)
 }
 
+/// 储能放电方向的符号：项目约定下使放电为正的系数（发电机约定为 1，负荷约定为 -1）
+fn discharge_sign(convention: &SignConvention) -> f64 {
+    if convention.storage == PowerSign::Generator {
+        1.0
+    } else {
+        -1.0
+    }
+}
+
+/// 关口购电方向的符号：项目约定下使购电为正的系数（负荷约定为 1，发电机约定为 -1）
+fn import_sign(convention: &SignConvention) -> f64 {
+    if convention.ext_grid == PowerSign::Load {
+        1.0
+    } else {
+        -1.0
+    }
+}
+
+/// 把仿真数据（DB/实时）的 p_active/p_reactive 序列由数据自身的符号约定转换为分析约定 target（项目设置的约定）；
+/// 电表按其测量对象的设备类型转换。CSV/远程文件的约定未知，由前端数据项配置处理方向
+async fn normalize_power_signs(
+    spec: &DataSourceSpec,
+    ctx: &DataSourceContext,
+    target: &SignConvention,
+    series: &mut HashMap<String, Vec<dashboard::TimeSeriesPoint>>,
+) -> Result<(), String> {
+    let power_keys: Vec<(String, String)> = series
+        .keys()
+        .filter_map(|k| {
+            let (device_id, field) = k.rsplit_once(':')?;
+            (field == "p_active" || field == "p_reactive").then(|| (k.clone(), device_id.to_string()))
+        })
+        .collect();
+    if power_keys.is_empty() {
+        return Ok(());
+    }
+    let (source_convention, device_types): (SignConvention, HashMap<String, DeviceType>) = match spec {
+        DataSourceSpec::Db { path } => {
+            let path = path.clone();
+            let ids: Vec<String> = power_keys.iter().map(|(_, id)| id.clone()).collect();
+            tokio::task::spawn_blocking(move || db_sign_context(&path, &ids))
+                .await
+                .map_err(|e| e.to_string())??
+        }
+        DataSourceSpec::Live => {
+            let Some(topology) = (match ctx.engine.as_ref() {
+                Some(engine) => engine.get_topology().await,
+                None => None,
+            }) else {
+                return Ok(());
+            };
+            let mut types = HashMap::new();
+            for (_, device_id) in &power_keys {
+                let Some(device) = topology.devices.get(device_id) else {
+                    continue;
+                };
+                // 实时缓存中电表值与其测量对象一致，取连接另一端设备的类型
+                let device_type = if device.device_type == DeviceType::Meter {
+                    topology
+                        .connections
+                        .values()
+                        .find_map(|c| {
+                            let other = if &c.from_device_id == device_id {
+                                &c.to_device_id
+                            } else if &c.to_device_id == device_id {
+                                &c.from_device_id
+                            } else {
+                                return None;
+                            };
+                            topology.devices.get(other).map(|d| d.device_type.clone())
+                        })
+                } else {
+                    Some(device.device_type.clone())
+                };
+                if let Some(t) = device_type {
+                    types.insert(device_id.clone(), t);
+                }
+            }
+            (SignConvention::native(), types)
+        }
+        _ => return Ok(()),
+    };
+    for (key, device_id) in power_keys {
+        let Some(device_type) = device_types.get(&device_id) else {
+            continue;
+        };
+        if target.factor_from(&source_convention, device_type) < 0.0 {
+            if let Some(points) = series.get_mut(&key) {
+                for p in points.iter_mut() {
+                    p.value = -p.value;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 读取仿真库记录的符号约定（未记录视为原生约定）与各设备的类型；电表取其测量对象的类型（见 database::meter_target_types）
+fn db_sign_context(path: &str, device_ids: &[String]) -> Result<(SignConvention, HashMap<String, DeviceType>), String> {
+    let conn = rusqlite::Connection::open(path).map_err(|e| format!("打开数据库失败: {}", e))?;
+    let convention = conn
+        .query_row(
+            "SELECT value_text FROM simulation_meta WHERE key = 'sign_convention'",
+            [],
+            |row| row.get::<_, Option<String>>(0),
+        )
+        .ok()
+        .flatten()
+        .and_then(|s| serde_json::from_str::<SignConvention>(&s).ok())
+        .unwrap_or_else(SignConvention::native);
+    let meter_targets = crate::services::database::meter_target_types(&conn).map_err(|e| format!("读取电表测量对象失败: {}", e))?;
+    let mut types = HashMap::new();
+    for device_id in device_ids {
+        let own: Option<String> = conn
+            .query_row(
+                "SELECT device_type FROM device_data WHERE device_id = ?1 AND device_type IS NOT NULL LIMIT 1",
+                rusqlite::params![device_id],
+                |row| row.get(0),
+            )
+            .ok();
+        let resolved = match own.as_deref() {
+            Some("meter") => meter_targets.get(device_id).cloned(),
+            other => other.map(String::from),
+        };
+        if let Some(t) = resolved.and_then(|t| crate::commands::topology::parse_device_type(&t).ok()) {
+            types.insert(device_id.clone(), t);
+        }
+    }
+    Ok((convention, types))
+}
+
 /// 空值判定
 fn is_valid(v: f64) -> bool {
     v.is_finite()
@@ -256,13 +455,14 @@ fn align_series(
 }
 
 /// 性能分析：功率相关指标，标注国标/行标/国际标准
-/// selected_indicators: 用户选择的指标 id 列表，为空则返回全部
+/// selected_indicators: 用户选择的指标 id 列表，为空则返回全部；discharge_sign 使项目约定下的放电功率为正（见 discharge_sign）
 fn run_performance_analysis(
     series: HashMap<String, Vec<dashboard::TimeSeriesPoint>>,
     mapping: Option<&PerformanceDataMapping>,
     selected_indicators: Option<&[String]>,
     start_time: f64,
     end_time: f64,
+    discharge_sign: f64,
 ) -> AnalysisResult {
     let mut summary = serde_json::Map::new();
     let mut details = serde_json::Map::new();
@@ -335,7 +535,7 @@ fn run_performance_analysis(
         let (t0, p0, _) = valid_pts[i - 1];
         let (t1, p1, _) = valid_pts[i];
         let dt_h = (t1 - t0) / 3600.0;
-        let p_avg = (p0 + p1) * 0.5;
+        let p_avg = (p0 + p1) * 0.5 * discharge_sign;
         if p_avg < 0.0 {
             energy_charge_kwh += (-p_avg) * dt_h;
         } else if p_avg > 0.0 {
@@ -421,7 +621,7 @@ fn run_performance_analysis(
         let (t0, p0, _) = valid_pts[i - 1];
         let (t1, p1, _) = valid_pts[i];
         let dt_h = (t1 - t0) / 3600.0;
-        let p_avg = (p0 + p1) * 0.5;
+        let p_avg = (p0 + p1) * 0.5 * discharge_sign;
         if p_avg < 0.0 {
             acc += (-p_avg) * dt_h * eta.sqrt();
         } else if p_avg > 0.0 {
@@ -608,12 +808,15 @@ fn fixed_unit_price(voltage: &str, tariff_type: &str) -> f64 {
     }
 }
 
-/// 收益分析：关口有功积分得电量，分时+固定+两部制
+/// 收益分析：关口有功积分得电量，分时+固定+两部制；分时电量按 timezone 的本地小时分桶；
+/// import_sign 使项目约定下的购电功率为正（见 import_sign）
 fn run_revenue_analysis(
     series: HashMap<String, Vec<dashboard::TimeSeriesPoint>>,
     config: &PriceConfig,
     start_time: f64,
     end_time: f64,
+    timezone: &ImportTimezone,
+    import_sign: f64,
 ) -> AnalysisResult {
     let gateway_series = series
         .values()
@@ -650,14 +853,10 @@ fn run_revenue_analysis(
             continue;
         }
         let dt_h = (t1 - t0) / 3600.0;
-        let e = (p0 + p1) * 0.5 * dt_h;
+        let e = (p0 + p1) * 0.5 * dt_h * import_sign;
         if e.is_finite() {
             total_energy_kwh += e;
-            let hour_idx = ((t0 + t1) * 0.5 / 3600.0).floor() as i32 % 24;
-            let idx = (hour_idx.rem_euclid(24)) as usize;
-            if idx < 24 {
-                hourly_energy[idx] += e;
-            }
+            hourly_energy[timezone.local_hour((t0 + t1) * 0.5)] += e;
         }
     }
 
@@ -683,7 +882,8 @@ fn run_revenue_analysis(
         "two_part_cost_yuan": two_part_cost,
         "total_cost_yuan": total_cost,
         "voltage_level": config.voltage_level,
-        "tariff_type": config.tariff_type
+        "tariff_type": config.tariff_type,
+        "timezone": timezone.label()
     });
 
     let charts = vec![ChartData {
@@ -705,8 +905,21 @@ fn run_revenue_analysis(
 }
 
 #[tauri::command]
-pub async fn analyze_performance(request: AnalysisRequest) -> Result<AnalysisResult, String> {
-    let series = resolve_series(&request).await?;
+pub async fn analyze_performance(
+    request: AnalysisRequest,
+    metadata_store: State<'_, Mutex<DeviceMetadataStore>>,
+    engine: State<'_, Arc<SimulationEngine>>,
+    csv_cache: State<'_, Arc<CsvCache>>,
+    settings: State<'_, SettingsStore>,
+) -> Result<AnalysisResult, String> {
+    let ctx = DataSourceContext {
+        engine: Some(engine.inner().clone()),
+        csv_cache: Some(csv_cache.inner().clone()),
+    };
+    request.analysis_timezone().validate()?;
+    // 分析按项目设置的符号约定进行：仿真数据换算到该约定，储能充放电与关口购电方向随约定确定
+    let convention = settings.sign_convention();
+    let series = resolve_series(&request, &ctx, &convention).await?;
     let result = match request.analysis_type.as_str() {
         "performance" => run_performance_analysis(
             series,
@@ -714,17 +927,22 @@ pub async fn analyze_performance(request: AnalysisRequest) -> Result<AnalysisRes
             request.performance_standards.as_deref(),
             request.start_time,
             request.end_time,
+            discharge_sign(&convention),
         ),
         "revenue" => {
+            // 未显式提供电价时使用外部电网分时计划中的电价
             let config = request
                 .price_config
-                .as_ref()
-                .ok_or("收益分析需提供 price_config")?;
+                .clone()
+                .or_else(|| price_config_from_topology(&metadata_store))
+                .ok_or("收益分析需提供 price_config 或在外部电网配置分时电价")?;
             run_revenue_analysis(
                 series,
-                config,
+                &config,
                 request.start_time,
                 request.end_time,
+                &request.analysis_timezone(),
+                import_sign(&convention),
             )
         }
         _ => return Err(format!("未知分析类型: {}", request.analysis_type)),
@@ -733,7 +951,15 @@ pub async fn analyze_performance(request: AnalysisRequest) -> Result<AnalysisRes
 }
 
 #[tauri::command]
-pub async fn generate_report(request: ReportRequest) -> Result<String, String> {
+pub async fn generate_report(
+    request: ReportRequest,
+    metadata_store: State<'_, Mutex<DeviceMetadataStore>>,
+    engine: State<'_, Arc<SimulationEngine>>,
+    csv_cache: State<'_, Arc<CsvCache>>,
+    settings: State<'_, SettingsStore>,
+    db: State<'_, Arc<Mutex<Option<Database>>>>,
+) -> Result<String, String> {
+    let charts = request.charts.unwrap_or_default();
     let analysis_request = AnalysisRequest {
         data_source: request.data_source,
         file_path: request.file_path,
@@ -746,8 +972,10 @@ pub async fn generate_report(request: ReportRequest) -> Result<String, String> {
         series_data: request.series_data,
         performance_standards: request.performance_standards,
         performance_data_mapping: request.performance_data_mapping,
+        source: request.source,
+        timezone: request.timezone,
     };
-    let result = analyze_performance(analysis_request).await?;
+    let result = analyze_performance(analysis_request, metadata_store, engine, csv_cache, settings).await?;
     let report_path = request.report_path.unwrap_or_else(|| {
         format!(
             "analysis_report_{}_{}.json",
@@ -757,5 +985,65 @@ pub async fn generate_report(request: ReportRequest) -> Result<String, String> {
     });
     let content = serde_json::to_string_pretty(&result).map_err(|e| e.to_string())?;
     std::fs::write(&report_path, content).map_err(|e| format!("写入报告失败: {}", e))?;
+    if !charts.is_empty() {
+        let stem = report_path.strip_suffix(".json").unwrap_or(&report_path).to_string();
+        let db = db.inner().clone();
+        tokio::task::spawn_blocking(move || {
+            for (i, mut chart) in charts.into_iter().enumerate() {
+                if chart.output_path.is_none() {
+                    chart.output_path = Some(format!("{}_chart{}.{}", stem, i + 1, chart.format.extension()));
+                }
+                chart_renderer::render_with_fallback(&chart, &db)?;
+            }
+            Ok::<(), String>(())
+        })
+        .await
+        .map_err(|e| format!("渲染报告配图失败: {}", e))??;
+    }
     Ok(report_path)
 }
+
+/// 电量平衡核对：各电表 Modbus 累计电量（折算一次侧）与被测设备功率积分电量逐点比对，报告偏差与疑似符号/比例错误；
+/// 已记录基线时只核对基线之后的增量，tolerance_pct 默认 2%（另加电表准确度等级）
+#[tauri::command]
+pub async fn run_energy_balance(
+    tolerance_pct: Option<f64>,
+    engine: State<'_, Arc<SimulationEngine>>,
+    modbus_service: State<'_, ModbusService>,
+    store: State<'_, EnergyBalanceStore>,
+) -> Result<EnergyBalanceReport, String> {
+    let tolerance_pct = tolerance_pct.unwrap_or(energy_balance::DEFAULT_TOLERANCE_PCT);
+    if !tolerance_pct.is_finite() || tolerance_pct < 0.0 {
+        return Err(format!("允许偏差无效: {}", tolerance_pct));
+    }
+    let topology = engine.get_topology().await.ok_or("未加载拓扑")?;
+    let registers = modbus_service.energy_value_snapshot().await;
+    let device_energy = engine.get_all_device_energy();
+    Ok(energy_balance::reconcile(
+        &topology,
+        &registers,
+        &device_energy,
+        store.baseline().as_ref(),
+        tolerance_pct,
+    ))
+}
+
+/// 记录电量平衡核对基线（当前电表电量寄存器与设备电量计数），之后的核对只比较增量
+#[tauri::command]
+pub async fn mark_energy_balance_baseline(
+    engine: State<'_, Arc<SimulationEngine>>,
+    modbus_service: State<'_, ModbusService>,
+    store: State<'_, EnergyBalanceStore>,
+) -> Result<EnergyBalanceBaseline, String> {
+    let registers = modbus_service.energy_value_snapshot().await;
+    let baseline = energy_balance::baseline(&registers, engine.get_all_device_energy());
+    store.set_baseline(baseline.clone());
+    Ok(baseline)
+}
+
+/// 清除电量平衡核对基线（恢复按全部累计值核对）
+#[tauri::command]
+pub async fn clear_energy_balance_baseline(store: State<'_, EnergyBalanceStore>) -> Result<(), String> {
+    store.clear_baseline();
+    Ok(())
+}
@@ -0,0 +1,162 @@
+// 模型参数标定命令：读取现场 CSV，拟合光伏/储能模型参数并写回设备属性，返回标定前后的拟合报告
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use tauri::State;
+use crate::domain::metadata::DeviceMetadataStore;
+use crate::domain::topology::{prop_f64, DeviceType};
+use crate::services::calibration::{
+    fit_pv, fit_storage, read_csv_columns, storage_intervals, FitMetrics, PvModelParams, StorageModelParams,
+};
+use crate::services::simulation_engine::SimulationEngine;
+use crate::services::weather::DEFAULT_PV_TEMP_COEFF_PCT_PER_C;
+
+#[derive(Debug, Deserialize)]
+pub struct CalibrationRequest {
+    pub device_id: String,
+    /// 现场数据 CSV：光伏需含 p_kw、irradiance（组件面辐照，W/m²），可选 temperature（环境温度，°C）；
+    /// 储能需含 timestamp、p_kw（正=充电）、soc（%）
+    pub file_path: String,
+    /// false 时只返回报告，不写回设备属性
+    #[serde(default = "default_apply")]
+    pub apply: bool,
+}
+
+fn default_apply() -> bool {
+    true
+}
+
+#[derive(Debug, Serialize)]
+pub struct CalibrationReport {
+    pub device_id: String,
+    /// "pv" | "storage"
+    pub model: String,
+    pub parameters_before: serde_json::Value,
+    pub parameters_after: serde_json::Value,
+    pub fit_before: FitMetrics,
+    pub fit_after: FitMetrics,
+    pub applied: bool,
+}
+
+/// 用现场数据标定光伏（系统效率 performance_ratio、温度系数 pv_temp_coeff_pct_per_c，即辐照/气象出力模型读取的属性）
+/// 或储能（充/放电效率）模型参数
+#[tauri::command]
+pub async fn calibrate_device_model(
+    request: CalibrationRequest,
+    metadata_store: State<'_, Mutex<DeviceMetadataStore>>,
+    engine: State<'_, Arc<SimulationEngine>>,
+) -> Result<CalibrationReport, String> {
+    let device = metadata_store
+        .lock()
+        .unwrap()
+        .get_device(&request.device_id)
+        .ok_or_else(|| format!("Device {} not found", request.device_id))?;
+    let props = device.properties.clone();
+    let path = request.file_path.clone();
+
+    let (model, before, after, fit_before, fit_after, updates) = match device.device_type {
+        DeviceType::Pv => {
+            // 装机容量与出力模型一致：panel_kwp，缺省取额定功率
+            let kwp = prop_f64(&props, "panel_kwp")
+                .filter(|v| *v > 0.0)
+                .or_else(|| crate::domain::topology::rated_power_kw(&props))
+                .unwrap_or(0.0);
+            let rows = tokio::task::spawn_blocking(move || read_csv_columns(&path, &["p_kw", "irradiance"], &["temperature"]))
+                .await
+                .map_err(|e| e.to_string())??;
+            let samples: Vec<(f64, Option<f64>, f64)> = rows
+                .iter()
+                .map(|r| (r["irradiance"], r.get("temperature").copied(), r["p_kw"]))
+                .collect();
+            // 标定前参数取出力模型实际使用的值（含缺省值）
+            let before = PvModelParams {
+                performance_ratio: prop_f64(&props, "performance_ratio").filter(|v| *v > 0.0).unwrap_or(0.85),
+                pv_temp_coeff_pct_per_c: prop_f64(&props, "pv_temp_coeff_pct_per_c")
+                    .unwrap_or(DEFAULT_PV_TEMP_COEFF_PCT_PER_C),
+            };
+            let after = fit_pv(kwp, &samples, before.pv_temp_coeff_pct_per_c)?;
+            let observed: Vec<f64> = samples.iter().map(|s| s.2).collect();
+            let predict = |m: &PvModelParams| -> Vec<f64> {
+                samples.iter().map(|(g, t, _)| m.predict(kwp, *g, *t)).collect()
+            };
+            let fit_before = FitMetrics::compute(&observed, &predict(&before));
+            let fit_after = FitMetrics::compute(&observed, &predict(&after));
+            let updates = vec![
+                ("performance_ratio", serde_json::json!(after.performance_ratio)),
+                ("pv_temp_coeff_pct_per_c", serde_json::json!(after.pv_temp_coeff_pct_per_c)),
+            ];
+            (
+                "pv",
+                serde_json::to_value(&before).unwrap_or_default(),
+                serde_json::to_value(&after).unwrap_or_default(),
+                fit_before,
+                fit_after,
+                updates,
+            )
+        }
+        DeviceType::Storage => {
+            let capacity_kwh = prop_f64(&props, "capacity_kwh")
+                .or_else(|| prop_f64(&props, "capacity"))
+                .or_else(|| prop_f64(&props, "max_e_mwh").map(|v| v * 1000.0))
+                .ok_or("储能未配置额定容量，无法标定")?;
+            let rows = tokio::task::spawn_blocking(move || read_csv_columns(&path, &["timestamp", "p_kw", "soc"], &[]))
+                .await
+                .map_err(|e| e.to_string())??;
+            let mut samples: Vec<(f64, f64, f64)> = rows.iter().map(|r| (r["timestamp"], r["p_kw"], r["soc"])).collect();
+            samples.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+            let intervals = storage_intervals(capacity_kwh, &samples);
+            let before = StorageModelParams {
+                charge_efficiency: prop_f64(&props, "charge_efficiency").unwrap_or(1.0),
+                discharge_efficiency: prop_f64(&props, "discharge_efficiency").unwrap_or(1.0),
+            };
+            let after = fit_storage(&intervals)?;
+            let observed: Vec<f64> = intervals.iter().map(|i| i.2).collect();
+            let predict = |m: &StorageModelParams| -> Vec<f64> {
+                intervals.iter().map(|(c, d, _)| m.predict_delta_kwh(*c, *d)).collect()
+            };
+            let fit_before = FitMetrics::compute(&observed, &predict(&before));
+            let fit_after = FitMetrics::compute(&observed, &predict(&after));
+            let updates = vec![
+                ("charge_efficiency", serde_json::json!(after.charge_efficiency)),
+                ("discharge_efficiency", serde_json::json!(after.discharge_efficiency)),
+            ];
+            let mut after_value = serde_json::to_value(&after).unwrap_or_default();
+            after_value["round_trip_efficiency"] = serde_json::json!(after.round_trip());
+            (
+                "storage",
+                serde_json::to_value(&before).unwrap_or_default(),
+                after_value,
+                fit_before,
+                fit_after,
+                updates,
+            )
+        }
+        _ => return Err(format!("设备 {} 类型不支持标定（仅光伏与储能）", request.device_id)),
+    };
+
+    if request.apply {
+        {
+            let store = metadata_store.lock().unwrap();
+            let mut device = store
+                .get_device(&request.device_id)
+                .ok_or_else(|| format!("Device {} not found", request.device_id))?;
+            for (k, v) in &updates {
+                device.properties.insert(k.to_string(), v.clone());
+            }
+            store.update_device(device)?;
+        }
+        // 同步到运行中的仿真拓扑
+        for (k, v) in updates {
+            engine.set_device_property(&request.device_id, k, v, "command").await;
+        }
+    }
+
+    Ok(CalibrationReport {
+        device_id: request.device_id,
+        model: model.to_string(),
+        parameters_before: before,
+        parameters_after: after,
+        fit_before,
+        fit_after,
+        applied: request.apply,
+    })
+}
@@ -0,0 +1,43 @@
+// 并网规范符合性测试命令
+use crate::services::compliance::{self, ComplianceResult, ComplianceResultStore, ComplianceTestDef};
+use crate::services::simulation_engine::SimulationEngine;
+use std::sync::Arc;
+use tauri::{AppHandle, State};
+
+#[tauri::command]
+pub async fn list_compliance_tests() -> Result<Vec<ComplianceTestDef>, String> {
+    Ok(compliance::predefined_tests())
+}
+
+/// 运行预定义符合性测试（需仿真运行中），测试期间通过 compliance-test-progress 推送采样，完成后返回并保存结果
+#[tauri::command]
+pub async fn run_compliance_test(
+    app: AppHandle,
+    test_id: String,
+    device_id: String,
+    engine: State<'_, Arc<SimulationEngine>>,
+    store: State<'_, ComplianceResultStore>,
+) -> Result<ComplianceResult, String> {
+    let test = compliance::predefined_tests()
+        .into_iter()
+        .find(|t| t.id == test_id)
+        .ok_or_else(|| format!("未知的符合性测试: {}", test_id))?;
+    let result = compliance::run_test(&engine, &app, &test, &device_id).await?;
+    store.push(result.clone());
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn get_compliance_results(
+    store: State<'_, ComplianceResultStore>,
+) -> Result<Vec<ComplianceResult>, String> {
+    Ok(store.list())
+}
+
+#[tauri::command]
+pub async fn clear_compliance_results(
+    store: State<'_, ComplianceResultStore>,
+) -> Result<(), String> {
+    store.clear();
+    Ok(())
+}
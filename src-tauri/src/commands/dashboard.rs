@@ -4,6 +4,18 @@ use std::collections::HashMap;
 use std::fs::File;
 use std::io::BufReader;
 use crate::commands::monitoring::DeviceDataPoint;
+use crate::services::csv_cache::{CsvCache, CsvCacheStatus};
+use crate::services::column_parser::{self, parse_uppercase_sn_prefix, ColumnNameParser, ColumnParsePreview, ColumnParserTemplate};
+use crate::services::timezone::{self, parse_timestamp, ImportTimezone, TimezoneHint, TIMEZONE_SAMPLE_ROWS};
+use crate::services::series_codec::{encode_series_response, SeriesEncoding};
+use crate::services::series_tail::SeriesTailManager;
+use tauri::ipc::Response;
+use crate::services::data_source::{open_data_source, CsvFormat, DataSource, DataSourceContext, DataSourceSpec, DbDataSource};
+use crate::services::simulation_engine::SimulationEngine;
+use crate::domain::device_alias::AliasIndex;
+use crate::domain::metadata::DeviceMetadataStore;
+use std::sync::Arc;
+use tauri::State;
 
 #[derive(serde::Serialize)]
 pub struct DashboardListFromPathResponse {
@@ -129,13 +141,27 @@ pub async fn query_device_data_from_path(
 pub struct DashboardCsvData {
     pub device_ids: Vec<String>,
     pub points_by_device: HashMap<String, Vec<DeviceDataPoint>>,
+    /// 解析无时区后缀时间戳所用的时区
+    #[serde(default)]
+    pub timezone: ImportTimezone,
 }
 
 /// 解析长表 CSV，支持列名：device_id, timestamp 或 local_timestamp, p_active 或 p_mw, p_reactive 或 q_mvar, data_json（可选）。
 /// 与本地 device_data 表同构的 CSV 或 remote-tool 导出的长表格式。
+/// timezone 指定无时区后缀时间戳的时区，默认 UTC
 #[tauri::command]
-pub async fn dashboard_parse_csv(file_path: String) -> Result<DashboardCsvData, String> {
-    let file = File::open(&file_path).map_err(|e| format!("打开文件失败: {}", e))?;
+pub async fn dashboard_parse_csv(
+    file_path: String,
+    timezone: Option<ImportTimezone>,
+) -> Result<DashboardCsvData, String> {
+    let timezone = timezone.unwrap_or_default();
+    timezone.validate()?;
+    parse_long_csv_file(&file_path, &timezone)
+}
+
+/// 长表 CSV 解析实现（供看板命令与数据源共用）
+pub(crate) fn parse_long_csv_file(file_path: &str, timezone: &ImportTimezone) -> Result<DashboardCsvData, String> {
+    let file = File::open(file_path).map_err(|e| format!("打开文件失败: {}", e))?;
     let mut rdr = csv::Reader::from_reader(BufReader::new(file));
     let headers = rdr.headers().map_err(|e| format!("读取表头失败: {}", e))?;
     let headers: Vec<String> = headers.iter().map(|h| h.trim().to_string()).collect();
@@ -163,7 +189,7 @@ pub async fn dashboard_parse_csv(file_path: String) -> Result<DashboardCsvData,
             continue;
         }
         let ts_str = record.get(idx_timestamp).unwrap().trim();
-        let timestamp = parse_timestamp(ts_str).unwrap_or(0.0);
+        let timestamp = parse_timestamp(ts_str, timezone).unwrap_or(0.0);
         let p_active = idx_p_active
             .and_then(|i| record.get(i))
             .and_then(|s| s.trim().parse::<f64>().ok())
@@ -201,43 +227,10 @@ pub async fn dashboard_parse_csv(file_path: String) -> Result<DashboardCsvData,
     Ok(DashboardCsvData {
         device_ids,
         points_by_device,
+        timezone: *timezone,
     })
 }
 
-fn parse_timestamp(s: &str) -> Option<f64> {
-    // 去除前后空格，以及前导单引号（宽表 CSV 中常见）
-    let s = s.trim().trim_start_matches('\'').trim_start_matches('"');
-    if s.is_empty() {
-        return None;
-    }
-    if let Ok(t) = s.parse::<f64>() {
-        if t > 1e12 {
-            return Some(t / 1000.0);
-        }
-        return Some(t);
-    }
-    if let Ok(t) = s.parse::<i64>() {
-        let t = t as f64;
-        if t > 1e12 {
-            return Some(t / 1000.0);
-        }
-        return Some(t);
-    }
-    chrono::DateTime::parse_from_rfc3339(s)
-        .ok()
-        .map(|dt| dt.timestamp_millis() as f64 / 1000.0)
-        .or_else(|| {
-            chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.3f")
-                .ok()
-                .and_then(|dt| dt.and_local_timezone(chrono::Utc).single().map(|dtu| dtu.timestamp_millis() as f64 / 1000.0))
-        })
-        .or_else(|| {
-            chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S")
-                .ok()
-                .and_then(|dt| dt.and_local_timezone(chrono::Utc).single().map(|dtu| dtu.timestamp_millis() as f64 / 1000.0))
-        })
-}
-
 // ====== 宽表 CSV 解析 ======
 
 /// 宽表列元信息
@@ -251,6 +244,9 @@ pub struct ColumnMeta {
     pub data_item: String,
     /// 简化的图例标签
     pub short_label: String,
+    /// 按设备 ID、名称与别名自动匹配到的拓扑设备
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_id: Option<String>,
 }
 
 /// 时间序列数据点
@@ -267,34 +263,13 @@ pub struct WideTableData {
     pub columns: Vec<ColumnMeta>,
     /// 每列的时间序列数据（key = 原始列名）
     pub series: HashMap<String, Vec<TimeSeriesPoint>>,
-}
-
-/// 从宽表列名中解析设备 SN 和数据项
-/// 列名格式: {SN}_{dataItem}，其中 SN 为大写字母+数字
-fn parse_column_name(col: &str) -> (String, String) {
-    // 尝试匹配模式：SN 全为大写字母或数字，后跟 _ 和数据项
-    // 例：TESAR125261GT00CN251225002_activePowerLimit → (TESAR125261GT00CN251225002, activePowerLimit)
-    // 例：TMEAD35K050EI00CN251209001_active_power → (TMEAD35K050EI00CN251209001, active_power)
-    let bytes = col.as_bytes();
-    let mut split_pos = None;
-    for i in 0..bytes.len() {
-        if bytes[i] == b'_' && i > 0 {
-            // 检查 _ 之前是否全部为大写字母/数字
-            let prefix_valid = bytes[..i].iter().all(|b| b.is_ascii_uppercase() || b.is_ascii_digit());
-            if prefix_valid && i + 1 < bytes.len() {
-                split_pos = Some(i);
-                break;
-            }
-        }
-    }
-    match split_pos {
-        Some(pos) => (col[..pos].to_string(), col[pos + 1..].to_string()),
-        None => (String::new(), col.to_string()),
-    }
+    /// 解析无时区后缀时间戳所用的时区
+    #[serde(default)]
+    pub timezone: ImportTimezone,
 }
 
 /// 生成简化的图例标签：取 SN 尾部若干字符 + 字段名，格式为 sn尾部_字段名，便于与设备树对应
-fn make_short_label(device_sn: &str, data_item: &str) -> String {
+pub(crate) fn make_short_label(device_sn: &str, data_item: &str) -> String {
     if device_sn.is_empty() {
         return data_item.to_string();
     }
@@ -305,7 +280,7 @@ fn make_short_label(device_sn: &str, data_item: &str) -> String {
 }
 
 /// 均匀降采样：保留首尾点，中间均匀选取
-fn downsample(data: &mut Vec<TimeSeriesPoint>, max_points: usize) {
+pub(crate) fn downsample(data: &mut Vec<TimeSeriesPoint>, max_points: usize) {
     if data.len() <= max_points || max_points < 2 {
         return;
     }
@@ -320,14 +295,100 @@ fn downsample(data: &mut Vec<TimeSeriesPoint>, max_points: usize) {
 }
 
 /// 解析宽表 CSV 文件
-/// 列格式：local_timestamp, {SN}_{dataItem}, {SN}_{dataItem}, ...
+/// 列格式：local_timestamp, {SN}_{dataItem}, {SN}_{dataItem}, ...；其他厂商格式可用 column_parser 指定列名解析策略
 /// 数据稀疏，大部分单元格为空
-/// 每列最多保留 MAX_POINTS_PER_SERIES 个点（自动降采样）
+/// 每列最多保留 MAX_POINTS_PER_SERIES 个点（自动降采样）；timezone 指定无时区后缀时间戳的时区，默认 UTC
 #[tauri::command]
-pub async fn dashboard_parse_wide_csv(file_path: String) -> Result<WideTableData, String> {
+pub async fn dashboard_parse_wide_csv(
+    file_path: String,
+    column_parser: Option<ColumnNameParser>,
+    timezone: Option<ImportTimezone>,
+    csv_cache: State<'_, Arc<CsvCache>>,
+    metadata_store: State<'_, std::sync::Mutex<DeviceMetadataStore>>,
+) -> Result<WideTableData, String> {
     const MAX_POINTS_PER_SERIES: usize = 5000;
+    let timezone = timezone.unwrap_or_default();
+    timezone.validate()?;
+    // 经解析缓存读取（文件未变化时不重复解析），再按看板点数上限降采样
+    let cache = csv_cache.inner().clone();
+    let parsed = tokio::task::spawn_blocking(move || cache.get_or_parse(&file_path, CsvFormat::Wide, timezone))
+        .await
+        .map_err(|e| format!("解析任务失败: {}", e))??;
+    let mut series = parsed.series.clone();
+    for data in series.values_mut() {
+        downsample(data, MAX_POINTS_PER_SERIES);
+    }
+    let mut columns = parsed.columns.clone();
+    column_parser.unwrap_or_default().apply(&mut columns)?;
+    match_columns_to_devices(&mut columns, &alias_index(&metadata_store));
+    Ok(WideTableData {
+        columns,
+        series,
+        timezone: parsed.timezone,
+    })
+}
+
+/// 列名解析内置模板（按 SN 前缀、点号、斜杠、方括号等常见厂商导出格式）
+#[tauri::command]
+pub async fn dashboard_list_column_parsers() -> Result<Vec<ColumnParserTemplate>, String> {
+    Ok(ColumnNameParser::templates())
+}
+
+/// 预览列名解析策略：只读取表头，返回各列解析出的设备 SN / 数据项及匹配统计
+#[tauri::command]
+pub async fn dashboard_preview_column_parser(
+    file_path: String,
+    column_parser: ColumnNameParser,
+) -> Result<ColumnParsePreview, String> {
+    let (headers, ts_idx) = read_wide_csv_headers(&file_path)?;
+    let data_headers: Vec<String> = headers
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| *i != ts_idx)
+        .map(|(_, h)| h)
+        .collect();
+    column_parser::preview(&data_headers, &column_parser)
+}
+
+/// 检测 CSV 时间戳的时区：读取时间戳列前若干行，返回格式统计与建议时区（宽表与长表均以 timestamp / local_timestamp 列为准）
+#[tauri::command]
+pub async fn dashboard_detect_timezone(file_path: String) -> Result<TimezoneHint, String> {
+    tokio::task::spawn_blocking(move || {
+        let (headers, ts_idx) = read_wide_csv_headers(&file_path)?;
+        let file = File::open(&file_path).map_err(|e| format!("打开文件失败: {}", e))?;
+        let mut rdr = csv::Reader::from_reader(BufReader::new(file));
+        let samples: Vec<String> = rdr
+            .records()
+            .filter_map(|r| r.ok())
+            .filter_map(|r| r.get(ts_idx).map(|s| s.trim().to_string()))
+            .filter(|s| !s.is_empty())
+            .take(TIMEZONE_SAMPLE_ROWS)
+            .collect();
+        Ok(timezone::detect_hint(&headers[ts_idx], &samples))
+    })
+    .await
+    .map_err(|e| format!("检测任务失败: {}", e))?
+}
+
+/// 读取宽表表头，返回 (列名, 时间戳列索引)
+fn read_wide_csv_headers(file_path: &str) -> Result<(Vec<String>, usize), String> {
+    let file = File::open(file_path).map_err(|e| format!("打开文件失败: {}", e))?;
+    let mut rdr = csv::Reader::from_reader(BufReader::new(file));
+    let headers = rdr.headers().map_err(|e| format!("读取表头失败: {}", e))?;
+    let headers: Vec<String> = headers.iter().map(|h| h.trim().trim_matches('"').to_string()).collect();
+    let ts_idx = headers.iter().position(|h| {
+        h.eq_ignore_ascii_case("local_timestamp") || h.eq_ignore_ascii_case("timestamp")
+    }).ok_or("CSV 缺少 local_timestamp 或 timestamp 列")?;
+    Ok((headers, ts_idx))
+}
 
-    let file = File::open(&file_path).map_err(|e| format!("打开文件失败: {}", e))?;
+/// 宽表 CSV 解析实现（供看板命令与数据源共用），每列最多保留 max_points 个点
+pub(crate) fn parse_wide_csv_file(
+    file_path: &str,
+    max_points: usize,
+    timezone: &ImportTimezone,
+) -> Result<WideTableData, String> {
+    let file = File::open(file_path).map_err(|e| format!("打开文件失败: {}", e))?;
     let mut rdr = csv::Reader::from_reader(BufReader::new(file));
     let headers = rdr.headers().map_err(|e| format!("读取表头失败: {}", e))?;
     let headers: Vec<String> = headers.iter().map(|h| h.trim().trim_matches('"').to_string()).collect();
@@ -345,13 +406,14 @@ pub async fn dashboard_parse_wide_csv(file_path: String) -> Result<WideTableData
         if i == ts_idx {
             continue;
         }
-        let (device_sn, data_item) = parse_column_name(header);
+        let (device_sn, data_item) = parse_uppercase_sn_prefix(header);
         let short_label = make_short_label(&device_sn, &data_item);
         columns.push(ColumnMeta {
             key: header.clone(),
             device_sn,
             data_item,
             short_label,
+            device_id: None,
         });
         col_indices.push(i);
     }
@@ -366,7 +428,7 @@ pub async fn dashboard_parse_wide_csv(file_path: String) -> Result<WideTableData
     for result in rdr.records() {
         let record = result.map_err(|e| format!("解析行失败: {}", e))?;
         let ts_str = record.get(ts_idx).unwrap_or("").trim().to_string();
-        let timestamp = match parse_timestamp(&ts_str) {
+        let timestamp = match parse_timestamp(&ts_str, timezone) {
             Some(ts) => ts,
             None => continue,
         };
@@ -386,12 +448,13 @@ pub async fn dashboard_parse_wide_csv(file_path: String) -> Result<WideTableData
 
     // 对每列降采样
     for (_key, data) in series.iter_mut() {
-        downsample(data, MAX_POINTS_PER_SERIES);
+        downsample(data, max_points);
     }
 
     Ok(WideTableData {
         columns,
         series,
+        timezone: *timezone,
     })
 }
 
@@ -408,13 +471,29 @@ pub struct DbColumnMeta {
     pub field_name: String,
     /// 简化标签
     pub short_label: String,
+    /// 按设备 ID、名称与别名自动匹配到的拓扑设备
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matched_device_id: Option<String>,
 }
 
-/// 从本地 DB 列出所有可选的数据列
+/// 从本地 DB 列出所有可选的数据列，并标注自动匹配到的拓扑设备
 /// 返回每个设备的基本字段（p_active, p_reactive）以及 data_json 中的额外字段
 #[tauri::command]
-pub async fn dashboard_list_db_columns(db_path: String) -> Result<Vec<DbColumnMeta>, String> {
-    let conn = rusqlite::Connection::open(&db_path).map_err(|e| format!("打开数据库失败: {}", e))?;
+pub async fn dashboard_list_db_columns(
+    db_path: String,
+    metadata_store: State<'_, std::sync::Mutex<DeviceMetadataStore>>,
+) -> Result<Vec<DbColumnMeta>, String> {
+    let mut columns = list_db_columns(&db_path)?;
+    let index = alias_index(&metadata_store);
+    for col in columns.iter_mut() {
+        col.matched_device_id = index.resolve(&col.device_id).map(|m| m.device_id);
+    }
+    Ok(columns)
+}
+
+/// 本地 DB 数据列列举实现（供看板命令与数据源共用）
+pub(crate) fn list_db_columns(db_path: &str) -> Result<Vec<DbColumnMeta>, String> {
+    let conn = rusqlite::Connection::open(db_path).map_err(|e| format!("打开数据库失败: {}", e))?;
 
     // 获取所有设备 ID
     let mut stmt = conn
@@ -439,12 +518,14 @@ pub async fn dashboard_list_db_columns(db_path: String) -> Result<Vec<DbColumnMe
             device_id: device_id.clone(),
             field_name: "p_active".to_string(),
             short_label: format!("{}_p_active", sn_tail),
+            matched_device_id: None,
         });
         columns.push(DbColumnMeta {
             key: format!("{}:p_reactive", device_id),
             device_id: device_id.clone(),
             field_name: "p_reactive".to_string(),
             short_label: format!("{}_p_reactive", sn_tail),
+            matched_device_id: None,
         });
 
         // 尝试读取一行 data_json，解析出额外字段
@@ -463,6 +544,7 @@ pub async fn dashboard_list_db_columns(db_path: String) -> Result<Vec<DbColumnMe
                         device_id: device_id.clone(),
                         field_name: field_key.clone(),
                         short_label: format!("{}_{}", sn_tail, field_key),
+                        matched_device_id: None,
                     });
                 }
             }
@@ -472,7 +554,7 @@ pub async fn dashboard_list_db_columns(db_path: String) -> Result<Vec<DbColumnMe
     Ok(columns)
 }
 
-/// 从本地 DB 批量按 key（格式 device_id:field_name）拉取时间序列，用于分析
+/// 从本地 DB 批量按 key（格式 device_id:field_name）拉取时间序列，用于分析；encoding=binary 时返回紧凑二进制
 #[tauri::command]
 pub async fn dashboard_fetch_series_batch(
     db_path: String,
@@ -480,26 +562,21 @@ pub async fn dashboard_fetch_series_batch(
     start_time: Option<f64>,
     end_time: Option<f64>,
     max_points_per_series: Option<usize>,
-) -> Result<HashMap<String, Vec<TimeSeriesPoint>>, String> {
+    encoding: Option<SeriesEncoding>,
+) -> Result<Response, String> {
+    let source = DbDataSource::new(db_path);
     let mut out: HashMap<String, Vec<TimeSeriesPoint>> = HashMap::new();
     for key in keys {
-        if let Some((device_id, field_name)) = key.split_once(':') {
-            let pts = dashboard_query_db_series_impl(
-                &db_path,
-                device_id.to_string(),
-                field_name.to_string(),
-                start_time,
-                end_time,
-                max_points_per_series.unwrap_or(5000),
-            )?;
+        if key.contains(':') {
+            let pts = source.fetch_series(&key, start_time, end_time, max_points_per_series.unwrap_or(5000))?;
             out.insert(key, pts);
         }
     }
-    Ok(out)
+    encode_series_response(&out, encoding.unwrap_or_default())
 }
 
 /// 从本地 DB 查询指定设备指定字段的时间序列（内部实现，支持时间范围）
-fn dashboard_query_db_series_impl(
+pub(crate) fn dashboard_query_db_series_impl(
     db_path: &str,
     device_id: String,
     field_name: String,
@@ -583,12 +660,321 @@ pub async fn dashboard_query_db_series(
     field_name: String,
     max_points: Option<usize>,
 ) -> Result<Vec<TimeSeriesPoint>, String> {
-    dashboard_query_db_series_impl(
-        &db_path,
-        device_id,
-        field_name,
+    DbDataSource::new(db_path).fetch_series(
+        &format!("{}:{}", device_id, field_name),
         None,
         None,
         max_points.unwrap_or(5000),
     )
 }
+
+// ====== 看板导出/导入（自描述数据包） ======
+
+/// 数据包格式版本，导入时校验
+const DASHBOARD_PACKAGE_VERSION: u32 = 1;
+
+/// 导出时应用的降采样
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ResamplingInfo {
+    /// 当前仅 "uniform"（保留首尾，中间均匀选取）
+    pub method: String,
+    pub max_points_per_series: usize,
+    /// 各序列降采样前的点数
+    pub original_points: HashMap<String, usize>,
+}
+
+/// 自描述看板数据包：序列数据 + 列元信息 + 来源 + 降采样 + 时间范围，导入后可还原同一图表
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DashboardPackage {
+    pub format_version: u32,
+    /// 导出时间（RFC 3339）
+    pub exported_at: String,
+    pub source: DataSourceSpec,
+    pub start_time: Option<f64>,
+    pub end_time: Option<f64>,
+    pub resampling: ResamplingInfo,
+    /// 列元信息，顺序即图表中的序列顺序
+    pub columns: Vec<ColumnMeta>,
+    pub series: HashMap<String, Vec<TimeSeriesPoint>>,
+    /// 前端图表配置（坐标轴、颜色等），原样保存
+    #[serde(default)]
+    pub chart_options: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DashboardExportRequest {
+    pub source: DataSourceSpec,
+    pub keys: Vec<String>,
+    pub start_time: Option<f64>,
+    pub end_time: Option<f64>,
+    pub max_points_per_series: Option<usize>,
+    pub chart_options: Option<serde_json::Value>,
+    pub output_path: String,
+}
+
+/// 按看板当前选择经数据源重新取数并导出为单个 JSON 数据包，返回写入路径
+#[tauri::command]
+pub async fn dashboard_export_package(
+    request: DashboardExportRequest,
+    engine: State<'_, Arc<SimulationEngine>>,
+    csv_cache: State<'_, Arc<CsvCache>>,
+) -> Result<String, String> {
+    if request.keys.is_empty() {
+        return Err("未选择数据列".to_string());
+    }
+    let max_points = request.max_points_per_series.unwrap_or(5000);
+    let ds = open_data_source(&request.source, &data_source_context(&engine, &csv_cache))?;
+    let available = ds.list_keys()?;
+    let mut columns: Vec<ColumnMeta> = Vec::with_capacity(request.keys.len());
+    let mut series: HashMap<String, Vec<TimeSeriesPoint>> = HashMap::new();
+    let mut original_points: HashMap<String, usize> = HashMap::new();
+
+    for key in &request.keys {
+        let meta = available
+            .iter()
+            .find(|c| &c.key == key)
+            .cloned()
+            .ok_or_else(|| format!("数据源中不存在数据列: {}", key))?;
+        // 先取全量以记录原始点数，再按导出粒度降采样
+        let mut pts = ds.fetch_series(key, request.start_time, request.end_time, usize::MAX)?;
+        original_points.insert(key.clone(), pts.len());
+        downsample(&mut pts, max_points);
+        columns.push(meta);
+        series.insert(key.clone(), pts);
+    }
+
+    let package = DashboardPackage {
+        format_version: DASHBOARD_PACKAGE_VERSION,
+        exported_at: chrono::Local::now().to_rfc3339(),
+        source: request.source,
+        start_time: request.start_time,
+        end_time: request.end_time,
+        resampling: ResamplingInfo {
+            method: "uniform".to_string(),
+            max_points_per_series: max_points,
+            original_points,
+        },
+        columns,
+        series,
+        chart_options: request.chart_options,
+    };
+    let content = serde_json::to_string_pretty(&package).map_err(|e| e.to_string())?;
+    std::fs::write(&request.output_path, content).map_err(|e| format!("写入导出文件失败: {}", e))?;
+    Ok(request.output_path)
+}
+
+/// 导入看板数据包，校验版本与序列完整性后原样返回，供前端还原图表
+#[tauri::command]
+pub async fn dashboard_import_package(file_path: String) -> Result<DashboardPackage, String> {
+    let content = std::fs::read_to_string(&file_path).map_err(|e| format!("读取文件失败: {}", e))?;
+    let package: DashboardPackage =
+        serde_json::from_str(&content).map_err(|e| format!("数据包格式无效: {}", e))?;
+    if package.format_version > DASHBOARD_PACKAGE_VERSION {
+        return Err(format!(
+            "数据包版本 {} 高于当前支持的版本 {}",
+            package.format_version, DASHBOARD_PACKAGE_VERSION
+        ));
+    }
+    if let Some(missing) = package.columns.iter().find(|c| !package.series.contains_key(&c.key)) {
+        return Err(format!("数据包缺少序列: {}", missing.key));
+    }
+    Ok(package)
+}
+
+// ====== 统一数据源查询 ======
+
+fn data_source_context(engine: &State<'_, Arc<SimulationEngine>>, csv_cache: &State<'_, Arc<CsvCache>>) -> DataSourceContext {
+    DataSourceContext {
+        engine: Some(engine.inner().clone()),
+        csv_cache: Some(csv_cache.inner().clone()),
+    }
+}
+
+/// 当前拓扑的设备标识索引（设备 ID、名称与设备属性中的别名）
+fn alias_index(metadata_store: &State<'_, std::sync::Mutex<DeviceMetadataStore>>) -> AliasIndex {
+    let topology = metadata_store.lock().unwrap().get_topology();
+    AliasIndex::build(topology.as_ref())
+}
+
+/// 按设备 ID、名称与别名将数据列匹配到拓扑设备：先用解析出的设备 SN，再用原始列名
+pub(crate) fn match_columns_to_devices(columns: &mut [ColumnMeta], index: &AliasIndex) {
+    for col in columns.iter_mut() {
+        col.device_id = index
+            .resolve(&col.device_sn)
+            .or_else(|| index.resolve(&col.key))
+            .map(|m| m.device_id);
+    }
+}
+
+/// 列出任一数据源（实时/DB/CSV/SSH 远程）的可选数据列，并标注自动匹配到的拓扑设备
+#[tauri::command]
+pub async fn data_source_list_keys(
+    source: DataSourceSpec,
+    engine: State<'_, Arc<SimulationEngine>>,
+    csv_cache: State<'_, Arc<CsvCache>>,
+    metadata_store: State<'_, std::sync::Mutex<DeviceMetadataStore>>,
+) -> Result<Vec<ColumnMeta>, String> {
+    let mut columns = open_data_source(&source, &data_source_context(&engine, &csv_cache))?.list_keys()?;
+    match_columns_to_devices(&mut columns, &alias_index(&metadata_store));
+    Ok(columns)
+}
+
+/// 从任一数据源批量拉取时间序列；encoding=binary 时返回紧凑二进制（见 series_codec）
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn data_source_fetch_series(
+    source: DataSourceSpec,
+    keys: Vec<String>,
+    start_time: Option<f64>,
+    end_time: Option<f64>,
+    max_points_per_series: Option<usize>,
+    encoding: Option<SeriesEncoding>,
+    engine: State<'_, Arc<SimulationEngine>>,
+    csv_cache: State<'_, Arc<CsvCache>>,
+) -> Result<Response, String> {
+    let ds = open_data_source(&source, &data_source_context(&engine, &csv_cache))?;
+    let mut out = HashMap::new();
+    for key in keys {
+        let pts = ds.fetch_series(&key, start_time, end_time, max_points_per_series.unwrap_or(5000))?;
+        out.insert(key, pts);
+    }
+    encode_series_response(&out, encoding.unwrap_or_default())
+}
+
+/// 从任一数据源读取各数据列最新值，无数据的列不返回
+#[tauri::command]
+pub async fn data_source_fetch_latest(
+    source: DataSourceSpec,
+    keys: Vec<String>,
+    engine: State<'_, Arc<SimulationEngine>>,
+    csv_cache: State<'_, Arc<CsvCache>>,
+) -> Result<HashMap<String, TimeSeriesPoint>, String> {
+    let ds = open_data_source(&source, &data_source_context(&engine, &csv_cache))?;
+    let mut out = HashMap::new();
+    for key in keys {
+        if let Some(p) = ds.fetch_latest(&key)? {
+            out.insert(key, p);
+        }
+    }
+    Ok(out)
+}
+
+// ====== CSV 解析缓存 ======
+
+#[tauri::command]
+pub async fn csv_cache_status(csv_cache: State<'_, Arc<CsvCache>>) -> Result<Vec<CsvCacheStatus>, String> {
+    Ok(csv_cache.status())
+}
+
+/// 清除指定文件（或全部）的解析缓存，返回清除条数
+#[tauri::command]
+pub async fn csv_cache_invalidate(
+    file_path: Option<String>,
+    csv_cache: State<'_, Arc<CsvCache>>,
+) -> Result<usize, String> {
+    Ok(csv_cache.invalidate(file_path.as_deref()))
+}
+
+/// 后台预解析 CSV（选择文件后即可调用），完成后可通过 csv_cache_status 查看
+#[tauri::command]
+pub async fn csv_cache_prefetch(
+    file_path: String,
+    format: CsvFormat,
+    timezone: Option<ImportTimezone>,
+    csv_cache: State<'_, Arc<CsvCache>>,
+) -> Result<(), String> {
+    let timezone = timezone.unwrap_or_default();
+    timezone.validate()?;
+    csv_cache.prefetch(file_path, format, timezone);
+    Ok(())
+}
+
+// ====== 渐进加载（概览 + 按缩放窗口细化，游标分页） ======
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SeriesPageRequest {
+    pub source: DataSourceSpec,
+    pub keys: Vec<String>,
+    /// 窗口范围；均为 None 时为全量概览
+    pub start_time: Option<f64>,
+    pub end_time: Option<f64>,
+    /// 窗口内点数不超过该值时返回原始分辨率，否则降采样到该值（默认 2000）
+    pub max_points: Option<usize>,
+    /// 分页：仅返回 timestamp > cursor 的点；提供 page_size 时按原始分辨率分页
+    pub cursor: Option<f64>,
+    pub page_size: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SeriesPage {
+    pub points: Vec<TimeSeriesPoint>,
+    /// 窗口（游标之后）内原始点数
+    pub total_points: usize,
+    /// true 表示返回的是原始分辨率数据，前端无需再细化
+    pub full_resolution: bool,
+    /// 分页未取完时的下一游标（本页最后一点时间戳）
+    pub next_cursor: Option<f64>,
+}
+
+/// 渐进加载：首次以全量范围获取粗粒度概览，缩放后仅请求可见窗口；窗口足够小时返回原始分辨率，
+/// 或按 page_size 以游标分页拉取原始数据
+#[tauri::command]
+pub async fn data_source_fetch_series_page(
+    request: SeriesPageRequest,
+    engine: State<'_, Arc<SimulationEngine>>,
+    csv_cache: State<'_, Arc<CsvCache>>,
+) -> Result<HashMap<String, SeriesPage>, String> {
+    let ds = open_data_source(&request.source, &data_source_context(&engine, &csv_cache))?;
+    let max_points = request.max_points.unwrap_or(2000).max(2);
+    let mut out = HashMap::new();
+    for key in &request.keys {
+        let mut points = ds.fetch_series(key, request.start_time, request.end_time, usize::MAX)?;
+        if let Some(cursor) = request.cursor {
+            points.retain(|p| p.timestamp > cursor);
+        }
+        let total_points = points.len();
+        let page = match request.page_size {
+            Some(size) if size > 0 => {
+                let next_cursor = if total_points > size { points.get(size - 1).map(|p| p.timestamp) } else { None };
+                points.truncate(size);
+                SeriesPage { points, total_points, full_resolution: true, next_cursor }
+            }
+            _ => {
+                let full_resolution = total_points <= max_points;
+                downsample(&mut points, max_points);
+                SeriesPage { points, total_points, full_resolution, next_cursor: None }
+            }
+        };
+        out.insert(key.clone(), page);
+    }
+    Ok(out)
+}
+
+// ====== 实时尾随 ======
+
+/// 订阅当前仿真数据的增量推送：每 interval_ms 推送所选数据列自上次推送以来的新点（事件 series-tail-update），返回订阅 id；主仿真停止时自动结束
+#[tauri::command]
+pub async fn subscribe_series_tail(
+    app: tauri::AppHandle,
+    keys: Vec<String>,
+    interval_ms: Option<u64>,
+    since: Option<f64>,
+    tail_manager: State<'_, SeriesTailManager>,
+    current_db_path: State<'_, Arc<std::sync::Mutex<String>>>,
+) -> Result<String, String> {
+    if keys.is_empty() {
+        return Err("未选择数据列".to_string());
+    }
+    if let Some(bad) = keys.iter().find(|k| !k.contains(':')) {
+        return Err(format!("无效的数据列 key: {}", bad));
+    }
+    Ok(tail_manager.subscribe(app, current_db_path.inner().clone(), keys, interval_ms.unwrap_or(1000), since))
+}
+
+#[tauri::command]
+pub async fn unsubscribe_series_tail(
+    subscription_id: String,
+    tail_manager: State<'_, SeriesTailManager>,
+) -> Result<bool, String> {
+    Ok(tail_manager.unsubscribe(&subscription_id))
+}
@@ -3,8 +3,20 @@ use serde::{Deserialize, Serialize};
 use tauri::State;
 use crate::domain::metadata::DeviceMetadataStore;
 use crate::domain::device::DeviceMetadata;
+use crate::domain::grid_schedule::{GridLimitViolation, GridSchedule};
+use crate::domain::meter_dropout::MeterDropoutConfig;
+use crate::domain::storage_schedule::{ScheduleAdherence, ScheduleConflictRule, StorageSchedule};
+use crate::domain::forecast::{DeviceForecast, ForecastAccuracy};
+use crate::domain::device_alias::{topology_aliases, validate_aliases, AliasIndex, AliasMatch, DeviceAlias, ALIASES_PROPERTY};
+use crate::services::settings::SettingsStore;
+use crate::services::database::Database;
+use crate::services::forecast_accuracy;
+use crate::services::profile_playback::{PowerProfile, ProfilePlaybackOptions};
+use crate::services::meter_dropout::MeterDropoutReport;
+use crate::domain::topology::DeviceType;
 use crate::services::simulation_engine::SimulationEngine;
-use crate::services::modbus::ModbusService;
+use crate::services::modbus::{unit_id_from_properties, ModbusRtuConfig, ModbusService};
+use crate::services::modbus_schema::{RegisterDataType, WordOrder};
 use crate::commands::topology::device_type_to_string;
 use std::sync::{Arc, Mutex};
 use std::collections::HashMap;
@@ -34,11 +46,21 @@ pub struct ModbusDeviceInfo {
     pub device_type: String,
     pub ip: String,
     pub port: u16,
+    /// 从站 ID（unit_id / slave_id 属性，默认 1）：配置同一 ip/port 的设备按此区分
+    #[serde(default = "default_unit_id")]
+    pub unit_id: u8,
+    /// 配置了串口（serial_port）时同时提供 RTU 从站
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rtu: Option<ModbusRtuConfig>,
+}
+
+fn default_unit_id() -> u8 {
+    1
 }
 
 /// 单条寄存器配置（四类：coils / discrete_inputs / input_registers / holding_registers）
 /// key 为语义标识（如 active_power / on_off），用于在自定义地址下仍正确更新/解析命令
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ModbusRegisterEntry {
     pub address: u16,
     pub value: u16,
@@ -50,93 +72,111 @@ pub struct ModbusRegisterEntry {
     /// 语义键，参与仿真更新或 HR 命令的寄存器必填，用于可配置地址
     #[serde(skip_serializing_if = "Option::is_none")]
     pub key: Option<String>,
+    /// 数据类型；指定后仿真按该类型、倍率与字序编码工程值（32 位类型占 address 起两个寄存器），
+    /// 未指定时沿用该设备类型的默认编码
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_type: Option<RegisterDataType>,
+    /// 倍率：工程值 = 寄存器值 × scale，默认 1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scale: Option<f64>,
+    /// 字节/字序，默认 ABCD（大端、高字在前）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub word_order: Option<WordOrder>,
 }
 
 fn modbus_register_defaults_meter() -> Vec<ModbusRegisterEntry> {
     vec![
-        ModbusRegisterEntry { address: 0, value: 0, type_: "input_registers".into(), name: Some("当前有功功率".into()), key: Some("active_power".into()) },
-        ModbusRegisterEntry { address: 1, value: 220, type_: "input_registers".into(), name: Some("A相电压".into()), key: None },
-        ModbusRegisterEntry { address: 2, value: 220, type_: "input_registers".into(), name: Some("B相电压".into()), key: None },
-        ModbusRegisterEntry { address: 3, value: 220, type_: "input_registers".into(), name: Some("C相电压".into()), key: None },
-        ModbusRegisterEntry { address: 4, value: 0, type_: "input_registers".into(), name: Some("A相电流".into()), key: None },
-        ModbusRegisterEntry { address: 5, value: 0, type_: "input_registers".into(), name: Some("B相电流".into()), key: None },
-        ModbusRegisterEntry { address: 6, value: 0, type_: "input_registers".into(), name: Some("C相电流".into()), key: None },
-        ModbusRegisterEntry { address: 7, value: 0, type_: "input_registers".into(), name: Some("四象限-有功导出(上网)".into()), key: None },
-        ModbusRegisterEntry { address: 8, value: 0, type_: "input_registers".into(), name: Some("四象限-有功导入(下网)".into()), key: None },
-        ModbusRegisterEntry { address: 9, value: 0, type_: "input_registers".into(), name: Some("组合有功总电能".into()), key: None },
-        ModbusRegisterEntry { address: 10, value: 0, type_: "input_registers".into(), name: Some("四象限-无功导出".into()), key: None },
-        ModbusRegisterEntry { address: 11, value: 0, type_: "input_registers".into(), name: Some("四象限-无功导入".into()), key: None },
-        ModbusRegisterEntry { address: 20, value: 0, type_: "input_registers".into(), name: Some("无功功率".into()), key: Some("reactive_power".into()) },
+        ModbusRegisterEntry { address: 0, value: 0, type_: "input_registers".into(), name: Some("当前有功功率".into()), key: Some("active_power".into()), ..Default::default() },
+        ModbusRegisterEntry { address: 1, value: 220, type_: "input_registers".into(), name: Some("A相电压".into()), key: None, ..Default::default() },
+        ModbusRegisterEntry { address: 2, value: 220, type_: "input_registers".into(), name: Some("B相电压".into()), key: None, ..Default::default() },
+        ModbusRegisterEntry { address: 3, value: 220, type_: "input_registers".into(), name: Some("C相电压".into()), key: None, ..Default::default() },
+        ModbusRegisterEntry { address: 4, value: 0, type_: "input_registers".into(), name: Some("A相电流".into()), key: None, ..Default::default() },
+        ModbusRegisterEntry { address: 5, value: 0, type_: "input_registers".into(), name: Some("B相电流".into()), key: None, ..Default::default() },
+        ModbusRegisterEntry { address: 6, value: 0, type_: "input_registers".into(), name: Some("C相电流".into()), key: None, ..Default::default() },
+        ModbusRegisterEntry { address: 7, value: 0, type_: "input_registers".into(), name: Some("四象限-有功导出(上网)".into()), key: Some("energy_export_kwh".into()), ..Default::default() },
+        ModbusRegisterEntry { address: 8, value: 0, type_: "input_registers".into(), name: Some("四象限-有功导入(下网)".into()), key: Some("energy_import_kwh".into()), ..Default::default() },
+        ModbusRegisterEntry { address: 9, value: 0, type_: "input_registers".into(), name: Some("组合有功总电能".into()), key: Some("energy_total_kwh".into()), ..Default::default() },
+        ModbusRegisterEntry { address: 10, value: 0, type_: "input_registers".into(), name: Some("四象限-无功导出".into()), key: Some("energy_export_kvarh".into()), ..Default::default() },
+        ModbusRegisterEntry { address: 11, value: 0, type_: "input_registers".into(), name: Some("四象限-无功导入".into()), key: Some("energy_import_kvarh".into()), ..Default::default() },
+        ModbusRegisterEntry { address: 20, value: 0, type_: "input_registers".into(), name: Some("无功功率".into()), key: Some("reactive_power".into()), ..Default::default() },
     ]
 }
 
 fn modbus_register_defaults_static_generator() -> Vec<ModbusRegisterEntry> {
     vec![
-        ModbusRegisterEntry { address: 5005, value: 1, type_: "holding_registers".into(), name: Some("开关机".into()), key: Some("on_off".into()) },
-        ModbusRegisterEntry { address: 5007, value: 100, type_: "holding_registers".into(), name: Some("有功功率百分比限制".into()), key: Some("power_limit_pct".into()) },
-        ModbusRegisterEntry { address: 5038, value: 0x7FFF, type_: "holding_registers".into(), name: Some("有功功率限制".into()), key: Some("power_limit_raw".into()) },
-        ModbusRegisterEntry { address: 5040, value: 0, type_: "holding_registers".into(), name: Some("无功补偿百分比".into()), key: Some("reactive_comp_pct".into()) },
-        ModbusRegisterEntry { address: 5041, value: 0, type_: "holding_registers".into(), name: Some("功率因数".into()), key: Some("power_factor".into()) },
-        ModbusRegisterEntry { address: 5001, value: 0, type_: "input_registers".into(), name: Some("额定功率".into()), key: None },
-        ModbusRegisterEntry { address: 5003, value: 0, type_: "input_registers".into(), name: Some("今日发电量".into()), key: None },
-        ModbusRegisterEntry { address: 5004, value: 0, type_: "input_registers".into(), name: Some("总发电量".into()), key: None },
-        ModbusRegisterEntry { address: 5030, value: 0, type_: "input_registers".into(), name: Some("当前有功功率(低)".into()), key: Some("active_power_low".into()) },
-        ModbusRegisterEntry { address: 5031, value: 0, type_: "input_registers".into(), name: Some("当前有功功率(高)".into()), key: Some("active_power_high".into()) },
-        ModbusRegisterEntry { address: 5032, value: 0, type_: "input_registers".into(), name: Some("无功功率(低)".into()), key: Some("reactive_power_low".into()) },
-        ModbusRegisterEntry { address: 5033, value: 0, type_: "input_registers".into(), name: Some("无功功率(高)".into()), key: Some("reactive_power_high".into()) },
+        ModbusRegisterEntry { address: 5005, value: 1, type_: "holding_registers".into(), name: Some("开关机".into()), key: Some("on_off".into()), ..Default::default() },
+        ModbusRegisterEntry { address: 5007, value: 100, type_: "holding_registers".into(), name: Some("有功功率百分比限制".into()), key: Some("power_limit_pct".into()), ..Default::default() },
+        ModbusRegisterEntry { address: 5038, value: 0x7FFF, type_: "holding_registers".into(), name: Some("有功功率限制".into()), key: Some("power_limit_raw".into()), ..Default::default() },
+        ModbusRegisterEntry { address: 5040, value: 0, type_: "holding_registers".into(), name: Some("无功补偿百分比".into()), key: Some("reactive_comp_pct".into()), ..Default::default() },
+        ModbusRegisterEntry { address: 5041, value: 0, type_: "holding_registers".into(), name: Some("功率因数".into()), key: Some("power_factor".into()), ..Default::default() },
+        ModbusRegisterEntry { address: 5001, value: 0, type_: "input_registers".into(), name: Some("额定功率".into()), key: Some("rated_power_kw".into()), ..Default::default() },
+        ModbusRegisterEntry { address: 5003, value: 0, type_: "input_registers".into(), name: Some("今日发电量".into()), key: Some("daily_energy_kwh".into()), ..Default::default() },
+        ModbusRegisterEntry { address: 5004, value: 0, type_: "input_registers".into(), name: Some("总发电量".into()), key: Some("total_energy_kwh".into()), ..Default::default() },
+        ModbusRegisterEntry { address: 5030, value: 0, type_: "input_registers".into(), name: Some("当前有功功率(低)".into()), key: Some("active_power_low".into()), ..Default::default() },
+        ModbusRegisterEntry { address: 5031, value: 0, type_: "input_registers".into(), name: Some("当前有功功率(高)".into()), key: Some("active_power_high".into()), ..Default::default() },
+        ModbusRegisterEntry { address: 5032, value: 0, type_: "input_registers".into(), name: Some("无功功率(低)".into()), key: Some("reactive_power_low".into()), ..Default::default() },
+        ModbusRegisterEntry { address: 5033, value: 0, type_: "input_registers".into(), name: Some("无功功率(高)".into()), key: Some("reactive_power_high".into()), ..Default::default() },
+        ModbusRegisterEntry { address: 5042, value: 0, type_: "input_registers".into(), name: Some("无功控制模式(0-无,1-固定PF,2-Q(U),3-固定Q)".into()), key: None, ..Default::default() },
     ]
 }
 
 fn modbus_register_defaults_storage() -> Vec<ModbusRegisterEntry> {
     vec![
-        ModbusRegisterEntry { address: 4, value: 0, type_: "holding_registers".into(), name: Some("设置功率".into()), key: Some("set_power".into()) },
-        ModbusRegisterEntry { address: 55, value: 243, type_: "holding_registers".into(), name: Some("开关机(243默认开机)".into()), key: Some("on_off".into()) },
-        ModbusRegisterEntry { address: 5095, value: 0, type_: "holding_registers".into(), name: Some("并离网模式(0-并网,1-离网)".into()), key: Some("grid_mode".into()) },
-        ModbusRegisterEntry { address: 5033, value: 0, type_: "holding_registers".into(), name: Some("PCS充放电状态(1-放电,2-充电)".into()), key: Some("pcs_charge_discharge_state".into()) },
-        ModbusRegisterEntry { address: 0, value: 3, type_: "input_registers".into(), name: Some("state1".into()), key: None },
-        ModbusRegisterEntry { address: 2, value: 288, type_: "input_registers".into(), name: Some("SOC".into()), key: None },
-        ModbusRegisterEntry { address: 8, value: 10000, type_: "input_registers".into(), name: Some("最大充电功率".into()), key: None },
-        ModbusRegisterEntry { address: 9, value: 10000, type_: "input_registers".into(), name: Some("最大放电功率".into()), key: None },
-        ModbusRegisterEntry { address: 12, value: 862, type_: "input_registers".into(), name: Some("剩余可放电容量".into()), key: None },
-        ModbusRegisterEntry { address: 39, value: 100, type_: "input_registers".into(), name: Some("额定容量".into()), key: None },
-        ModbusRegisterEntry { address: 40, value: 0, type_: "input_registers".into(), name: Some("pcs_num".into()), key: None },
-        ModbusRegisterEntry { address: 41, value: 0, type_: "input_registers".into(), name: Some("battery_cluster_num".into()), key: None },
-        ModbusRegisterEntry { address: 42, value: 0, type_: "input_registers".into(), name: Some("battery_cluster_capacity".into()), key: None },
-        ModbusRegisterEntry { address: 43, value: 0, type_: "input_registers".into(), name: Some("battery_cluster_power".into()), key: None },
-        ModbusRegisterEntry { address: 400, value: 0, type_: "input_registers".into(), name: Some("state4".into()), key: None },
-        ModbusRegisterEntry { address: 408, value: 1, type_: "input_registers".into(), name: Some("state2".into()), key: None },
-        ModbusRegisterEntry { address: 409, value: 2200, type_: "input_registers".into(), name: Some("A相电压".into()), key: None },
-        ModbusRegisterEntry { address: 410, value: 2200, type_: "input_registers".into(), name: Some("B相电压".into()), key: None },
-        ModbusRegisterEntry { address: 411, value: 2200, type_: "input_registers".into(), name: Some("C相电压".into()), key: None },
-        ModbusRegisterEntry { address: 412, value: 0, type_: "input_registers".into(), name: Some("A相电流".into()), key: None },
-        ModbusRegisterEntry { address: 413, value: 0, type_: "input_registers".into(), name: Some("B相电流".into()), key: None },
-        ModbusRegisterEntry { address: 414, value: 0, type_: "input_registers".into(), name: Some("C相电流".into()), key: None },
-        ModbusRegisterEntry { address: 420, value: 0, type_: "input_registers".into(), name: Some("有功功率(低)".into()), key: Some("active_power_low".into()) },
-        ModbusRegisterEntry { address: 421, value: 0, type_: "input_registers".into(), name: Some("有功功率(高)".into()), key: Some("active_power_high".into()) },
-        ModbusRegisterEntry { address: 426, value: 0, type_: "input_registers".into(), name: Some("日充电量".into()), key: None },
-        ModbusRegisterEntry { address: 427, value: 0, type_: "input_registers".into(), name: Some("日放电量".into()), key: None },
-        ModbusRegisterEntry { address: 428, value: 0, type_: "input_registers".into(), name: Some("累计充电总量(低)".into()), key: None },
-        ModbusRegisterEntry { address: 429, value: 0, type_: "input_registers".into(), name: Some("累计充电总量(高)".into()), key: None },
-        ModbusRegisterEntry { address: 430, value: 0, type_: "input_registers".into(), name: Some("累计放电总量(低)".into()), key: None },
-        ModbusRegisterEntry { address: 431, value: 0, type_: "input_registers".into(), name: Some("累计放电总量(高)".into()), key: None },
-        ModbusRegisterEntry { address: 432, value: 0, type_: "input_registers".into(), name: Some("PCS工作模式(bit9-并网,bit10-离网)".into()), key: None },
-        ModbusRegisterEntry { address: 839, value: 240, type_: "input_registers".into(), name: Some("state3(240-停机,243/245-正常,242/246-故障)".into()), key: None },
-        ModbusRegisterEntry { address: 900, value: 0, type_: "input_registers".into(), name: Some("SN_900".into()), key: None },
+        ModbusRegisterEntry { address: 4, value: 0, type_: "holding_registers".into(), name: Some("设置功率".into()), key: Some("set_power".into()), ..Default::default() },
+        ModbusRegisterEntry { address: 55, value: 243, type_: "holding_registers".into(), name: Some("开关机(243默认开机)".into()), key: Some("on_off".into()), ..Default::default() },
+        ModbusRegisterEntry { address: 5095, value: 0, type_: "holding_registers".into(), name: Some("并离网模式(0-并网,1-离网)".into()), key: Some("grid_mode".into()), ..Default::default() },
+        ModbusRegisterEntry { address: 5033, value: 0, type_: "holding_registers".into(), name: Some("PCS充放电状态(1-放电,2-充电)".into()), key: Some("pcs_charge_discharge_state".into()), ..Default::default() },
+        ModbusRegisterEntry { address: 0, value: 3, type_: "input_registers".into(), name: Some("state1".into()), key: None, ..Default::default() },
+        ModbusRegisterEntry { address: 2, value: 288, type_: "input_registers".into(), name: Some("SOC".into()), key: Some("soc".into()), ..Default::default() },
+        ModbusRegisterEntry { address: 8, value: 10000, type_: "input_registers".into(), name: Some("最大充电功率".into()), key: None, ..Default::default() },
+        ModbusRegisterEntry { address: 9, value: 10000, type_: "input_registers".into(), name: Some("最大放电功率".into()), key: None, ..Default::default() },
+        ModbusRegisterEntry { address: 12, value: 862, type_: "input_registers".into(), name: Some("剩余可放电容量".into()), key: Some("remaining_energy_kwh".into()), ..Default::default() },
+        ModbusRegisterEntry { address: 39, value: 100, type_: "input_registers".into(), name: Some("额定容量".into()), key: Some("capacity_kwh".into()), ..Default::default() },
+        ModbusRegisterEntry { address: 40, value: 0, type_: "input_registers".into(), name: Some("pcs_num".into()), key: None, ..Default::default() },
+        ModbusRegisterEntry { address: 41, value: 0, type_: "input_registers".into(), name: Some("battery_cluster_num".into()), key: None, ..Default::default() },
+        ModbusRegisterEntry { address: 42, value: 0, type_: "input_registers".into(), name: Some("battery_cluster_capacity".into()), key: None, ..Default::default() },
+        ModbusRegisterEntry { address: 43, value: 0, type_: "input_registers".into(), name: Some("battery_cluster_power".into()), key: None, ..Default::default() },
+        ModbusRegisterEntry { address: 400, value: 0, type_: "input_registers".into(), name: Some("state4".into()), key: None, ..Default::default() },
+        ModbusRegisterEntry { address: 408, value: 1, type_: "input_registers".into(), name: Some("state2".into()), key: None, ..Default::default() },
+        ModbusRegisterEntry { address: 409, value: 2200, type_: "input_registers".into(), name: Some("A相电压".into()), key: None, ..Default::default() },
+        ModbusRegisterEntry { address: 410, value: 2200, type_: "input_registers".into(), name: Some("B相电压".into()), key: None, ..Default::default() },
+        ModbusRegisterEntry { address: 411, value: 2200, type_: "input_registers".into(), name: Some("C相电压".into()), key: None, ..Default::default() },
+        ModbusRegisterEntry { address: 412, value: 0, type_: "input_registers".into(), name: Some("A相电流".into()), key: None, ..Default::default() },
+        ModbusRegisterEntry { address: 413, value: 0, type_: "input_registers".into(), name: Some("B相电流".into()), key: None, ..Default::default() },
+        ModbusRegisterEntry { address: 414, value: 0, type_: "input_registers".into(), name: Some("C相电流".into()), key: None, ..Default::default() },
+        ModbusRegisterEntry { address: 420, value: 0, type_: "input_registers".into(), name: Some("有功功率(低)".into()), key: Some("active_power_low".into()), ..Default::default() },
+        ModbusRegisterEntry { address: 421, value: 0, type_: "input_registers".into(), name: Some("有功功率(高)".into()), key: Some("active_power_high".into()), ..Default::default() },
+        ModbusRegisterEntry { address: 426, value: 0, type_: "input_registers".into(), name: Some("日充电量".into()), key: Some("daily_charge_kwh".into()), ..Default::default() },
+        ModbusRegisterEntry { address: 427, value: 0, type_: "input_registers".into(), name: Some("日放电量".into()), key: Some("daily_discharge_kwh".into()), ..Default::default() },
+        ModbusRegisterEntry { address: 428, value: 0, type_: "input_registers".into(), name: Some("累计充电总量(低)".into()), key: Some("total_charge_kwh_low".into()), ..Default::default() },
+        ModbusRegisterEntry { address: 429, value: 0, type_: "input_registers".into(), name: Some("累计充电总量(高)".into()), key: Some("total_charge_kwh_high".into()), ..Default::default() },
+        ModbusRegisterEntry { address: 430, value: 0, type_: "input_registers".into(), name: Some("累计放电总量(低)".into()), key: Some("total_discharge_kwh_low".into()), ..Default::default() },
+        ModbusRegisterEntry { address: 431, value: 0, type_: "input_registers".into(), name: Some("累计放电总量(高)".into()), key: Some("total_discharge_kwh_high".into()), ..Default::default() },
+        ModbusRegisterEntry { address: 432, value: 0, type_: "input_registers".into(), name: Some("PCS工作模式(bit9-并网,bit10-离网)".into()), key: None, ..Default::default() },
+        ModbusRegisterEntry { address: 839, value: 240, type_: "input_registers".into(), name: Some("state3(240-停机,243/245-正常,242/246-故障)".into()), key: None, ..Default::default() },
+        ModbusRegisterEntry { address: 900, value: 0, type_: "input_registers".into(), name: Some("SN_900".into()), key: None, ..Default::default() },
     ]
 }
 
 fn modbus_register_defaults_charger() -> Vec<ModbusRegisterEntry> {
     vec![
-        ModbusRegisterEntry { address: 0, value: 0x7FFF, type_: "holding_registers".into(), name: Some("功率限制".into()), key: Some("power_limit_raw".into()) },
-        ModbusRegisterEntry { address: 0, value: 0, type_: "input_registers".into(), name: Some("有功功率".into()), key: Some("active_power".into()) },
-        ModbusRegisterEntry { address: 1, value: 1, type_: "input_registers".into(), name: Some("状态".into()), key: None },
-        ModbusRegisterEntry { address: 2, value: 0, type_: "input_registers".into(), name: Some("需求功率".into()), key: None },
-        ModbusRegisterEntry { address: 3, value: 0, type_: "input_registers".into(), name: Some("枪数量".into()), key: None },
-        ModbusRegisterEntry { address: 4, value: 0, type_: "input_registers".into(), name: Some("额定功率".into()), key: None },
-        ModbusRegisterEntry { address: 100, value: 1, type_: "input_registers".into(), name: Some("枪1状态".into()), key: None },
-        ModbusRegisterEntry { address: 101, value: 2, type_: "input_registers".into(), name: Some("枪2状态".into()), key: None },
-        ModbusRegisterEntry { address: 102, value: 3, type_: "input_registers".into(), name: Some("枪3状态".into()), key: None },
-        ModbusRegisterEntry { address: 103, value: 4, type_: "input_registers".into(), name: Some("枪4状态".into()), key: None },
+        ModbusRegisterEntry { address: 0, value: 0x7FFF, type_: "holding_registers".into(), name: Some("功率限制".into()), key: Some("power_limit_raw".into()), ..Default::default() },
+        ModbusRegisterEntry { address: 0, value: 0, type_: "input_registers".into(), name: Some("有功功率".into()), key: Some("active_power".into()), ..Default::default() },
+        ModbusRegisterEntry { address: 1, value: 1, type_: "input_registers".into(), name: Some("状态".into()), key: None, ..Default::default() },
+        ModbusRegisterEntry { address: 2, value: 0, type_: "input_registers".into(), name: Some("需求功率".into()), key: None, ..Default::default() },
+        ModbusRegisterEntry { address: 3, value: 0, type_: "input_registers".into(), name: Some("枪数量".into()), key: None, ..Default::default() },
+        ModbusRegisterEntry { address: 4, value: 0, type_: "input_registers".into(), name: Some("额定功率".into()), key: Some("rated_power_kw".into()), ..Default::default() },
+        ModbusRegisterEntry { address: 100, value: 1, type_: "input_registers".into(), name: Some("枪1状态".into()), key: None, ..Default::default() },
+        ModbusRegisterEntry { address: 101, value: 2, type_: "input_registers".into(), name: Some("枪2状态".into()), key: None, ..Default::default() },
+        ModbusRegisterEntry { address: 102, value: 3, type_: "input_registers".into(), name: Some("枪3状态".into()), key: None, ..Default::default() },
+        ModbusRegisterEntry { address: 103, value: 4, type_: "input_registers".into(), name: Some("枪4状态".into()), key: None, ..Default::default() },
+    ]
+}
+
+/// 开关：线圈 0 为合/分闸（1-合闸，0-分闸），SCADA 以 Write Single Coil 远程操作断路器
+fn modbus_register_defaults_switch() -> Vec<ModbusRegisterEntry> {
+    vec![
+        ModbusRegisterEntry { address: 0, value: 1, type_: "coils".into(), name: Some("合/分闸".into()), key: Some("closed".into()), ..Default::default() },
     ]
 }
 
@@ -148,6 +188,7 @@ pub fn get_modbus_register_defaults(device_type: String) -> Result<Vec<ModbusReg
         "static_generator" => modbus_register_defaults_static_generator(),
         "storage" => modbus_register_defaults_storage(),
         "charger" => modbus_register_defaults_charger(),
+        "switch" => modbus_register_defaults_switch(),
         _ => modbus_register_defaults_meter(),
     };
     Ok(list)
@@ -169,17 +210,20 @@ pub async fn get_all_devices(
 }
 
 /// 支持 Modbus 的设备类型（与 get_modbus_register_defaults 一致）；无 ip/port 时使用默认值，保证设备树不为空
-const MODBUS_CAPABLE_TYPES: &[crate::domain::topology::DeviceType] = &[
+pub(crate) const MODBUS_CAPABLE_TYPES: &[crate::domain::topology::DeviceType] = &[
     crate::domain::topology::DeviceType::Meter,
     crate::domain::topology::DeviceType::Storage,
     crate::domain::topology::DeviceType::Pv,
     crate::domain::topology::DeviceType::Charger,
+    crate::domain::topology::DeviceType::Switch,
 ];
 
 /// 返回拓扑中可配置 Modbus 的设备列表（供 Modbus 通信面板使用）。若设备未配置 ip/port 则使用默认值，保证设备树显示所有支持 Modbus 的设备。
+/// 启用网关模式时 ip/port/unit id 为网关端口与映射表分配的 unit id
 #[tauri::command]
 pub async fn get_modbus_devices(
     metadata_store: State<'_, Mutex<DeviceMetadataStore>>,
+    settings: State<'_, SettingsStore>,
 ) -> Result<Vec<ModbusDeviceInfo>, String> {
     let metadata_store = metadata_store.lock().unwrap();
     let mut out = Vec::new();
@@ -207,6 +251,7 @@ pub async fn get_modbus_devices(
                     crate::domain::topology::DeviceType::Storage => 502,
                     crate::domain::topology::DeviceType::Pv => 602,
                     crate::domain::topology::DeviceType::Charger => 702,
+                    crate::domain::topology::DeviceType::Switch => 802,
                     _ => continue,
                 };
                 let c = type_counters.entry(dt_str.clone()).or_insert(0);
@@ -221,8 +266,19 @@ pub async fn get_modbus_devices(
             device_type: dt_str,
             ip,
             port,
+            unit_id: unit_id_from_properties(&d.properties).unwrap_or_else(default_unit_id),
+            rtu: ModbusRtuConfig::from_properties(&d.properties),
         });
     }
+    let gateway = settings.modbus_gateway();
+    if gateway.enabled {
+        let units = crate::commands::modbus::gateway_unit_ids(&metadata_store, &gateway)?;
+        for d in out.iter_mut() {
+            d.ip = gateway.ip.clone();
+            d.port = gateway.port;
+            d.unit_id = units[&d.id];
+        }
+    }
     Ok(out)
 }
 
@@ -275,6 +331,7 @@ pub async fn update_device_config(
     config: DeviceConfig,
     metadata_store: State<'_, Mutex<DeviceMetadataStore>>,
     engine: State<'_, Arc<SimulationEngine>>,
+    settings: State<'_, SettingsStore>,
 ) -> Result<(), String> {
     // 验证设备存在
     {
@@ -287,6 +344,7 @@ pub async fn update_device_config(
     if let Some(work_mode_str) = &config.work_mode {
         // 设置工作模式
         engine.set_device_mode(config.device_id.clone(), work_mode_str.clone()).await?;
+        settings.update_device_control(&config.device_id, |c| c.mode = Some(work_mode_str.clone()))?;
     }
     
     // 更新设备元数据（响应延迟、测量误差等）
@@ -300,9 +358,316 @@ pub async fn batch_set_device_mode(
     device_ids: Vec<String>,
     mode: String,
     engine: State<'_, Arc<SimulationEngine>>,
+    settings: State<'_, SettingsStore>,
 ) -> Result<(), String> {
     for device_id in device_ids {
-        engine.set_device_mode(device_id, mode.clone()).await?;
+        engine.set_device_mode(device_id.clone(), mode.clone()).await?;
+        settings.update_device_control(&device_id, |c| c.mode = Some(mode.clone()))?;
     }
     Ok(())
 }
+
+/// 设置外部电网分时计划（购/售电功率限值与电价），写入设备属性 grid_schedule；空计划表示清除
+#[tauri::command]
+pub async fn set_grid_schedule(
+    device_id: String,
+    schedule: GridSchedule,
+    metadata_store: State<'_, Mutex<DeviceMetadataStore>>,
+    engine: State<'_, Arc<SimulationEngine>>,
+) -> Result<(), String> {
+    schedule.validate()?;
+    let value = serde_json::to_value(&schedule).map_err(|e| e.to_string())?;
+    {
+        let store = metadata_store.lock().unwrap();
+        let mut device = store
+            .get_device(&device_id)
+            .ok_or_else(|| format!("Device {} not found", device_id))?;
+        if device.device_type != DeviceType::ExternalGrid {
+            return Err(format!("设备 {} 不是外部电网", device_id));
+        }
+        if schedule.slots.is_empty() {
+            device.properties.remove("grid_schedule");
+        } else {
+            device.properties.insert("grid_schedule".to_string(), value.clone());
+        }
+        store.update_device(device)?;
+    }
+    // 同步到运行中的仿真拓扑，越限检查立即生效
+    engine.set_device_property(&device_id, "grid_schedule", value, "command").await;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_grid_schedule(
+    device_id: String,
+    metadata_store: State<'_, Mutex<DeviceMetadataStore>>,
+) -> Result<GridSchedule, String> {
+    let store = metadata_store.lock().unwrap();
+    let device = store
+        .get_device(&device_id)
+        .ok_or_else(|| format!("Device {} not found", device_id))?;
+    Ok(GridSchedule::from_properties(&device.properties).unwrap_or_default())
+}
+
+/// 本次仿真中的关口功率越限记录
+#[tauri::command]
+pub async fn get_grid_limit_violations(
+    engine: State<'_, Arc<SimulationEngine>>,
+) -> Result<Vec<GridLimitViolation>, String> {
+    Ok(engine.get_grid_limit_violations())
+}
+
+/// 设置电表通信中断模拟（随机缺口与计划中断），写入设备属性 meter_dropout；空配置表示清除
+#[tauri::command]
+pub async fn set_meter_dropout(
+    device_id: String,
+    config: MeterDropoutConfig,
+    metadata_store: State<'_, Mutex<DeviceMetadataStore>>,
+    engine: State<'_, Arc<SimulationEngine>>,
+) -> Result<(), String> {
+    config.validate()?;
+    let value = serde_json::to_value(&config).map_err(|e| e.to_string())?;
+    {
+        let store = metadata_store.lock().unwrap();
+        let mut device = store
+            .get_device(&device_id)
+            .ok_or_else(|| format!("Device {} not found", device_id))?;
+        if device.device_type != DeviceType::Meter {
+            return Err(format!("设备 {} 不是电表", device_id));
+        }
+        if config.is_empty() {
+            device.properties.remove("meter_dropout");
+        } else {
+            device.properties.insert("meter_dropout".to_string(), value.clone());
+        }
+        store.update_device(device)?;
+    }
+    // 同步到运行中的仿真拓扑，下一步即生效
+    engine.set_device_property(&device_id, "meter_dropout", value, "command").await;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_meter_dropout(
+    device_id: String,
+    metadata_store: State<'_, Mutex<DeviceMetadataStore>>,
+) -> Result<MeterDropoutConfig, String> {
+    let store = metadata_store.lock().unwrap();
+    let device = store
+        .get_device(&device_id)
+        .ok_or_else(|| format!("Device {} not found", device_id))?;
+    Ok(MeterDropoutConfig::from_properties(&device.properties).unwrap_or_default())
+}
+
+/// 本次仿真的电表数据缺口日志与各表丢数统计（数据清洗测试的真值参考）
+#[tauri::command]
+pub async fn get_meter_dropout_report(
+    engine: State<'_, Arc<SimulationEngine>>,
+) -> Result<MeterDropoutReport, String> {
+    Ok(engine.get_meter_dropout_report())
+}
+
+/// 导入储能日前充放电计划（CSV：timestamp/time + p_kw[, q_kvar]），仿真运行时由引擎按时段自动下发
+#[tauri::command]
+pub async fn import_storage_schedule(
+    device_id: String,
+    file_path: String,
+    conflict_rule: Option<ScheduleConflictRule>,
+    metadata_store: State<'_, Mutex<DeviceMetadataStore>>,
+    engine: State<'_, Arc<SimulationEngine>>,
+) -> Result<StorageSchedule, String> {
+    {
+        let store = metadata_store.lock().unwrap();
+        let device = store
+            .get_device(&device_id)
+            .ok_or_else(|| format!("Device {} not found", device_id))?;
+        if device.device_type != DeviceType::Storage {
+            return Err(format!("设备 {} 不是储能", device_id));
+        }
+    }
+    let schedule = StorageSchedule::from_csv(&device_id, &file_path, conflict_rule.unwrap_or_default())?;
+    engine.set_storage_schedule(schedule.clone());
+    Ok(schedule)
+}
+
+#[tauri::command]
+pub async fn get_storage_schedule(
+    device_id: String,
+    engine: State<'_, Arc<SimulationEngine>>,
+) -> Result<Option<StorageSchedule>, String> {
+    Ok(engine.get_storage_schedule(&device_id))
+}
+
+/// 清除储能计划；已下发的最后一个设定保持，直到新的远程指令或手动设定
+#[tauri::command]
+pub async fn clear_storage_schedule(
+    device_id: String,
+    engine: State<'_, Arc<SimulationEngine>>,
+) -> Result<bool, String> {
+    Ok(engine.remove_storage_schedule(&device_id))
+}
+
+/// 导入设备功率预测曲线（CSV：timestamp/time + p_kw），仿真运行时逐步与实际功率对比统计预测精度
+#[tauri::command]
+pub async fn import_device_forecast(
+    device_id: String,
+    file_path: String,
+    group: Option<String>,
+    metadata_store: State<'_, Mutex<DeviceMetadataStore>>,
+    engine: State<'_, Arc<SimulationEngine>>,
+) -> Result<DeviceForecast, String> {
+    {
+        let store = metadata_store.lock().unwrap();
+        let device = store
+            .get_device(&device_id)
+            .ok_or_else(|| format!("Device {} not found", device_id))?;
+        if !matches!(
+            device.device_type,
+            DeviceType::Pv | DeviceType::Storage | DeviceType::Load | DeviceType::Charger | DeviceType::ExternalGrid
+        ) {
+            return Err(format!("设备 {} 不是功率设备", device_id));
+        }
+    }
+    let forecast = DeviceForecast::from_csv(&device_id, &file_path, group)?;
+    engine.set_device_forecast(forecast.clone());
+    Ok(forecast)
+}
+
+#[tauri::command]
+pub async fn get_device_forecast(
+    device_id: String,
+    engine: State<'_, Arc<SimulationEngine>>,
+) -> Result<Option<DeviceForecast>, String> {
+    Ok(engine.get_device_forecast(&device_id))
+}
+
+#[tauri::command]
+pub async fn clear_device_forecast(
+    device_id: String,
+    engine: State<'_, Arc<SimulationEngine>>,
+) -> Result<bool, String> {
+    Ok(engine.remove_device_forecast(&device_id))
+}
+
+/// 导入历史数据模式的功率曲线（CSV：timestamp/time + p/p_kw，可选 q/q_kvar），仿真时由引擎按仿真时间插值回放；
+/// options 指定循环、时间偏移、循环周期与时区
+#[tauri::command]
+pub async fn import_device_profile(
+    device_id: String,
+    file_path: String,
+    options: Option<ProfilePlaybackOptions>,
+    metadata_store: State<'_, Mutex<DeviceMetadataStore>>,
+    engine: State<'_, Arc<SimulationEngine>>,
+) -> Result<PowerProfile, String> {
+    {
+        let store = metadata_store.lock().unwrap();
+        let device = store
+            .get_device(&device_id)
+            .ok_or_else(|| format!("Device {} not found", device_id))?;
+        if !matches!(device.device_type, DeviceType::Pv | DeviceType::Storage | DeviceType::Load | DeviceType::Charger) {
+            return Err(format!("设备 {} 不支持历史曲线回放", device_id));
+        }
+    }
+    let profile = PowerProfile::from_csv(&device_id, &file_path, options.unwrap_or_default())?;
+    engine.set_device_profile(profile.clone()).await?;
+    Ok(profile)
+}
+
+#[tauri::command]
+pub async fn get_device_profile(
+    device_id: String,
+    engine: State<'_, Arc<SimulationEngine>>,
+) -> Result<Option<PowerProfile>, String> {
+    Ok(engine.get_device_profile(&device_id))
+}
+
+/// 清除导入的功率曲线；设备保持历史数据模式时功率停留在最后下发的值
+#[tauri::command]
+pub async fn clear_device_profile(
+    device_id: String,
+    engine: State<'_, Arc<SimulationEngine>>,
+) -> Result<bool, String> {
+    Ok(engine.remove_device_profile(&device_id))
+}
+
+/// 预测精度（MAPE/RMSE/偏差，按 15 分钟、1 小时、24 小时滚动窗口及全程统计）
+/// key 为设备 ID、type:<设备类型> 或 group:<自定义组>，为空返回全部；指定 db_path 时读取该仿真库保存的结果
+#[tauri::command]
+pub async fn get_forecast_accuracy(
+    key: Option<String>,
+    db_path: Option<String>,
+    engine: State<'_, Arc<SimulationEngine>>,
+) -> Result<Vec<ForecastAccuracy>, String> {
+    match db_path {
+        Some(path) => {
+            if !std::path::Path::new(&path).exists() {
+                return Err(format!("仿真库不存在: {}", path));
+            }
+            let db = Database::new(Some(std::path::Path::new(&path)))
+                .map_err(|e| format!("打开仿真库失败: {}", e))?;
+            let mut report = forecast_accuracy::load_persisted(&db)?;
+            if let Some(key) = key {
+                report.retain(|r| r.key == key);
+            }
+            Ok(report)
+        }
+        None => Ok(engine.get_forecast_accuracy(key.as_deref())),
+    }
+}
+
+/// 本次仿真的储能计划执行情况（偏差能量、被远程接管步数等）
+#[tauri::command]
+pub async fn get_storage_schedule_adherence(
+    engine: State<'_, Arc<SimulationEngine>>,
+) -> Result<Vec<ScheduleAdherence>, String> {
+    Ok(engine.get_storage_schedule_adherence())
+}
+
+/// 设备别名（SCADA 点名、SN、协议名、多语言显示名，保存在设备属性 aliases 中）；device_id 为空返回全部设备
+#[tauri::command]
+pub async fn get_device_aliases(
+    device_id: Option<String>,
+    metadata_store: State<'_, Mutex<DeviceMetadataStore>>,
+) -> Result<HashMap<String, Vec<DeviceAlias>>, String> {
+    let topology = metadata_store.lock().unwrap().get_topology();
+    let mut all = topology.as_ref().map(topology_aliases).unwrap_or_default();
+    if let Some(id) = device_id {
+        all.retain(|k, _| *k == id);
+    }
+    Ok(all)
+}
+
+/// 替换设备的全部别名，写入设备属性 aliases（随拓扑文件保存）；别名不能与其他设备的 ID、名称或别名重复，传空列表删除
+#[tauri::command]
+pub async fn set_device_aliases(
+    device_id: String,
+    aliases: Vec<DeviceAlias>,
+    metadata_store: State<'_, Mutex<DeviceMetadataStore>>,
+) -> Result<(), String> {
+    let store = metadata_store.lock().unwrap();
+    let topology = store.get_topology().ok_or_else(|| "未加载拓扑".to_string())?;
+    let mut device = topology
+        .devices
+        .get(&device_id)
+        .cloned()
+        .ok_or_else(|| format!("Device {} not found", device_id))?;
+    validate_aliases(&device_id, &aliases, &topology)?;
+    // 删除时写入空列表而不移除属性，避免保存拓扑时被前端缓存的旧属性合并回来
+    let value = serde_json::to_value(&aliases).map_err(|e| e.to_string())?;
+    device.properties.insert(ALIASES_PROPERTY.to_string(), value);
+    store.update_device(device)
+}
+
+/// 将外部数据中的设备标识（CSV 列中的 SN、长表/数据库中的 device_id、SCADA 点名等）按 ID、名称与别名匹配到拓扑设备，未匹配的不返回
+#[tauri::command]
+pub async fn resolve_device_keys(
+    keys: Vec<String>,
+    metadata_store: State<'_, Mutex<DeviceMetadataStore>>,
+) -> Result<HashMap<String, AliasMatch>, String> {
+    let topology = metadata_store.lock().unwrap().get_topology();
+    let index = AliasIndex::build(topology.as_ref());
+    Ok(keys
+        .into_iter()
+        .filter_map(|k| index.resolve(&k).map(|m| (k, m)))
+        .collect())
+}
@@ -0,0 +1,71 @@
+// EMS 调度命令：查询内置策略、选择策略与参数、关闭及查询运行状态
+use crate::services::ems::{EmsConfig, EmsStatus};
+use crate::services::simulation_manager::SimulationManager;
+use serde::Serialize;
+use tauri::State;
+
+/// 内置调度策略说明
+#[derive(Debug, Clone, Serialize)]
+pub struct EmsStrategyInfo {
+    pub strategy: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    /// 策略参数（除通用的 storages / min_soc_percent / max_soc_percent / deadband_kw 外）
+    pub parameters: &'static [&'static str],
+}
+
+const BUILTIN_STRATEGIES: &[EmsStrategyInfo] = &[
+    EmsStrategyInfo {
+        strategy: "peak_shaving",
+        name: "削峰",
+        description: "关口购电超过限值时储能放电补足；recharge 为 true 时在限值余量内充电",
+        parameters: &["import_limit_kw", "recharge"],
+    },
+    EmsStrategyInfo {
+        strategy: "tou_arbitrage",
+        name: "分时套利",
+        description: "购电电价不高于充电价时充电、不低于放电价时放电（电价取外部电网分时电价）",
+        parameters: &["charge_below_price", "discharge_above_price", "power_kw"],
+    },
+    EmsStrategyInfo {
+        strategy: "self_consumption",
+        name: "自发自用",
+        description: "光伏余电充入储能，用电缺口由储能放电补足，使关口交换趋近于零",
+        parameters: &[],
+    },
+];
+
+#[tauri::command]
+pub fn list_ems_strategies() -> Vec<EmsStrategyInfo> {
+    BUILTIN_STRATEGIES.to_vec()
+}
+
+/// 启用或更换 EMS 策略，下一步起按上一步结果调度储能（执行日前计划的储能不受控）
+#[tauri::command]
+pub async fn set_ems_strategy(
+    config: EmsConfig,
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
+) -> Result<EmsStatus, String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
+    engine.set_ems_config(config).await
+}
+
+/// 关闭 EMS，受控储能设定恢复为 0
+#[tauri::command]
+pub async fn disable_ems(
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
+) -> Result<(), String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
+    engine.disable_ems().await
+}
+
+#[tauri::command]
+pub async fn get_ems_status(
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
+) -> Result<EmsStatus, String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
+    Ok(engine.get_ems_status())
+}
@@ -9,3 +9,8 @@ pub mod analytics;
 pub mod ai;
 pub mod dashboard;
 pub mod modbus;
+pub mod compliance;
+pub mod settings;
+pub mod calibration;
+pub mod scenario;
+pub mod ems;
@@ -1,20 +1,27 @@
 // Modbus 设备启停命令
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use tauri::State;
+use std::collections::HashMap;
 use std::sync::Mutex;
-use crate::commands::device::{get_modbus_register_defaults, ModbusRegisterEntry};
+use crate::commands::device::{get_modbus_register_defaults, ModbusRegisterEntry, MODBUS_CAPABLE_TYPES};
 use crate::commands::topology::device_type_to_string;
 use crate::domain::metadata::DeviceMetadataStore;
-use crate::services::modbus::ModbusService;
+use crate::domain::modbus_gateway::ModbusGatewayConfig;
+use crate::services::settings::SettingsStore;
+use crate::services::modbus::{unit_id_from_properties, ModbusRtuConfig, ModbusService};
+use crate::services::modbus_doc::{self, PointListFormat};
 
 #[derive(Debug, Deserialize)]
 pub struct StartModbusConfig {
     pub ip_address: String,
     pub port: u16,
-    /// 从站 ID（Unit ID）：当前后端未使用，每设备独立端口，客户端通常用 1；默认 1
+    /// 从站 ID（Unit ID）：多个设备配置同一 ip/port 时按此区分（端口上只有一个设备时应答任意 unit id）；默认 1
     #[serde(default = "default_slave_id")]
     pub slave_id: u8,
     pub registers: Option<Vec<ModbusRegisterEntry>>,
+    /// 同时经串口以 Modbus RTU 从站应答（可选）
+    #[serde(default)]
+    pub rtu: Option<ModbusRtuConfig>,
 }
 
 fn default_slave_id() -> u8 {
@@ -31,8 +38,16 @@ pub async fn start_device_modbus(
     let registers = config.registers.unwrap_or_default();
     // 单设备启动（非加载拓扑）不写入不可变寄存器，传 None
     modbus_service
-        .start_device_modbus(device_id, device_type, config.ip_address, config.port, registers, None, None)
-        .await
+        .start_device_modbus(device_id.clone(), device_type, config.ip_address, config.port, config.slave_id, registers, None, None)
+        .await?;
+    // 串口打开失败时一并停止 TCP 服务，不留下只启动了一半的设备
+    if let Some(rtu) = config.rtu {
+        if let Err(e) = modbus_service.start_device_rtu(&device_id, &rtu) {
+            modbus_service.stop_device_modbus(&device_id).await?;
+            return Err(e);
+        }
+    }
+    Ok(())
 }
 
 #[tauri::command]
@@ -43,11 +58,13 @@ pub async fn stop_device_modbus(
     modbus_service.stop_device_modbus(&device_id).await
 }
 
-/// 启动拓扑中所有配置了 ip/port 的设备的 Modbus TCP 服务器（运行仿真时自动调用；寄存器使用各类型默认列表）
+/// 启动拓扑中所有配置了 ip/port 的设备的 Modbus TCP 服务器（运行仿真时自动调用；寄存器使用各类型默认列表）。
+/// 启用网关模式时所有设备挂在网关端口上，按映射表分配的 unit id 区分
 #[tauri::command]
 pub async fn start_all_modbus_servers(
     metadata_store: State<'_, Mutex<DeviceMetadataStore>>,
     modbus_service: State<'_, ModbusService>,
+    settings: State<'_, SettingsStore>,
 ) -> Result<(), String> {
     // 先停止所有旧的 Modbus 服务器（避免上一轮仿真残留导致"已在运行"错误）
     modbus_service.stop_all_device_modbus().await;
@@ -56,7 +73,8 @@ pub async fn start_all_modbus_servers(
     // - 旧版逻辑仅启动 properties 中明确配置了 ip/port 的设备
     // - 但默认拓扑（例如 topology.json）通常未配置这些字段，导致前端"运行中"但实际没有 Modbus 端口监听
     // - 这里为常用设备类型提供默认端口分配（与 working_*_client.py 保持一致），让仿真开机即具备可连的 Modbus TCP 服务
-    let devices_to_start: Vec<(String, String, String, u16, Option<f64>, Option<f64>)> = {
+    #[allow(clippy::type_complexity)]
+    let devices_to_start: Vec<(String, String, String, u16, u8, Option<f64>, Option<f64>, Option<ModbusRtuConfig>)> = {
         let store = metadata_store.lock().map_err(|e| e.to_string())?;
         let mut devices = store.get_all_devices();
         // HashMap 的 values() 顺序不稳定，这里按 id 排序，保证默认端口分配稳定
@@ -81,12 +99,13 @@ pub async fn start_all_modbus_servers(
                     .get("port")
                     .and_then(|v| v.as_u64().map(|n| n as u16).or_else(|| v.as_str().and_then(|s| s.parse::<u16>().ok())));
 
-                // 2) 提供默认 ip/port（与 device_type_to_string 返回值一致：meter/storage/static_generator/charger）
+                // 2) 提供默认 ip/port（与 device_type_to_string 返回值一致：meter/storage/static_generator/charger/switch）
                 let default_base_port: Option<u16> = match device_type.as_str() {
                     "meter" => Some(403),
                     "storage" => Some(502),
                     "static_generator" => Some(602),
                     "charger" => Some(702),
+                    "switch" => Some(802),
                     _ => None,
                 };
 
@@ -106,11 +125,7 @@ pub async fn start_all_modbus_servers(
                 // 不可变数据：仅加载拓扑时写入，设备属性编辑时也会同步（光伏 IR 5001、储能 IR 39、充电桩 IR 4）
                 // 前端设备属性面板使用 rated_power_kw，拓扑/旧数据可能为 max_power_kw 或 rated_power
                 let rated_power_kw: Option<f64> = if device_type == "static_generator" || device_type == "charger" {
-                    d.properties
-                        .get("rated_power_kw")
-                        .or_else(|| d.properties.get("max_power_kw"))
-                        .or_else(|| d.properties.get("rated_power"))
-                        .and_then(|v| v.as_f64().or_else(|| v.as_u64().map(|u| u as f64)))
+                    crate::domain::topology::rated_power_kw(&d.properties)
                 } else {
                     None
                 };
@@ -126,24 +141,158 @@ pub async fn start_all_modbus_servers(
                     None
                 };
 
-                Some((d.id.clone(), device_type, ip, port, rated_power_kw, rated_capacity_kwh))
+                // 配置了相同 ip/port 的设备按 unit id 共享监听
+                let unit_id = unit_id_from_properties(&d.properties).unwrap_or(1);
+                // 配置了 serial_port 的设备同时经串口以 RTU 从站应答
+                let rtu = ModbusRtuConfig::from_properties(&d.properties);
+
+                Some((d.id.clone(), device_type, ip, port, unit_id, rated_power_kw, rated_capacity_kwh, rtu))
+            })
+            .collect()
+    };
+    let gateway = settings.modbus_gateway();
+    let devices_to_start = if gateway.enabled {
+        let units = {
+            let store = metadata_store.lock().map_err(|e| e.to_string())?;
+            gateway_unit_ids(&store, &gateway)?
+        };
+        // 只有网关设备集合中的设备挂到网关端口（与映射编辑器、设备列表一致）
+        devices_to_start
+            .into_iter()
+            .filter_map(|(id, device_type, _, _, _, rated_power_kw, rated_capacity_kwh, rtu)| {
+                let unit_id = *units.get(&id)?;
+                Some((id, device_type, gateway.ip.clone(), gateway.port, unit_id, rated_power_kw, rated_capacity_kwh, rtu))
             })
             .collect()
+    } else {
+        devices_to_start
     };
-    for (id, device_type, ip, port, rated_power_kw, rated_capacity_kwh) in devices_to_start {
+    for (id, device_type, ip, port, unit_id, rated_power_kw, rated_capacity_kwh, rtu) in devices_to_start {
         let registers = get_modbus_register_defaults(device_type.clone()).map_err(|e| e.to_string())?;
         if let Err(e) = modbus_service
-            .start_device_modbus(id.clone(), device_type, ip, port, registers, rated_power_kw, rated_capacity_kwh)
+            .start_device_modbus(id.clone(), device_type, ip, port, unit_id, registers, rated_power_kw, rated_capacity_kwh)
             .await
         {
             eprintln!("start_all_modbus_servers: {} 启动失败: {}", id, e);
+            continue;
+        }
+        if let Some(rtu) = rtu {
+            if let Err(e) = modbus_service.start_device_rtu(&id, &rtu) {
+                eprintln!("start_all_modbus_servers: {} RTU 启动失败: {}", id, e);
+            }
         }
     }
     Ok(())
 }
 
+/// 网关模式下的设备集合（支持 Modbus 的设备类型）及其 unit id；启动服务、映射编辑器与设备列表共用，保证三处分配一致
+pub(crate) fn gateway_unit_ids(
+    store: &DeviceMetadataStore,
+    gateway: &ModbusGatewayConfig,
+) -> Result<HashMap<String, u8>, String> {
+    let ids: Vec<String> = store
+        .get_all_devices()
+        .into_iter()
+        .filter(|d| MODBUS_CAPABLE_TYPES.contains(&d.device_type))
+        .map(|d| d.id)
+        .collect();
+    gateway.assign_unit_ids(&ids)
+}
+
+#[tauri::command]
+pub async fn get_modbus_gateway_config(settings: State<'_, SettingsStore>) -> Result<ModbusGatewayConfig, String> {
+    Ok(settings.modbus_gateway())
+}
+
+/// 保存网关配置与映射表；下次启动全部 Modbus 服务（运行仿真）时生效
+#[tauri::command]
+pub async fn set_modbus_gateway_config(
+    config: ModbusGatewayConfig,
+    settings: State<'_, SettingsStore>,
+) -> Result<(), String> {
+    settings.set_modbus_gateway(config)
+}
+
+/// 网关映射表中的一行（映射编辑器使用）
+#[derive(Debug, Serialize)]
+pub struct ModbusGatewayMapping {
+    pub device_id: String,
+    pub name: String,
+    pub device_type: String,
+    pub unit_id: u8,
+    /// true 表示来自映射表，false 表示自动分配
+    pub mapped: bool,
+}
+
+/// 返回拓扑中各 Modbus 设备在网关下的生效 unit id；传入 config 时按该（未保存的）配置预览
+#[tauri::command]
+pub async fn get_modbus_gateway_mapping(
+    config: Option<ModbusGatewayConfig>,
+    metadata_store: State<'_, Mutex<DeviceMetadataStore>>,
+    settings: State<'_, SettingsStore>,
+) -> Result<Vec<ModbusGatewayMapping>, String> {
+    let gateway = config.unwrap_or_else(|| settings.modbus_gateway());
+    gateway.validate()?;
+    let (mut devices, units): (Vec<_>, _) = {
+        let store = metadata_store.lock().map_err(|e| e.to_string())?;
+        let units = gateway_unit_ids(&store, &gateway)?;
+        let devices = store.get_all_devices().into_iter().filter(|d| units.contains_key(&d.id)).collect();
+        (devices, units)
+    };
+    devices.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(devices
+        .into_iter()
+        .map(|d| ModbusGatewayMapping {
+            unit_id: units[&d.id],
+            mapped: gateway.unit_ids.contains_key(&d.id),
+            device_type: device_type_to_string(&d.device_type),
+            name: d.name,
+            device_id: d.id,
+        })
+        .collect())
+}
+
 /// 返回当前正在运行的 Modbus 服务器对应的设备 id 列表（设备控制页用于显示开关状态）
 #[tauri::command]
 pub fn get_running_modbus_device_ids(modbus_service: State<'_, ModbusService>) -> Vec<String> {
     modbus_service.running_device_ids()
 }
+
+/// 生成设备的 Modbus 点表（生效寄存器表：运行中为其启动配置，否则为默认表），
+/// 指定 output_path 时写入文件并返回路径，否则返回文档文本
+#[tauri::command]
+pub fn export_modbus_register_doc(
+    device_id: String,
+    format: Option<PointListFormat>,
+    output_path: Option<String>,
+    metadata_store: State<'_, Mutex<DeviceMetadataStore>>,
+    modbus_service: State<'_, ModbusService>,
+) -> Result<String, String> {
+    let device = {
+        let store = metadata_store.lock().map_err(|e| e.to_string())?;
+        store.get_device(&device_id)
+    };
+    let running = modbus_service.running_registers(&device_id);
+    let (device_type, device_name) = match (&running, &device) {
+        (Some((device_type, _)), d) => (device_type.clone(), d.as_ref().map(|d| d.name.clone())),
+        (None, Some(d)) => (device_type_to_string(&d.device_type), Some(d.name.clone())),
+        (None, None) => return Err(format!("设备不存在: {}", device_id)),
+    };
+    if !["meter", "static_generator", "storage", "charger", "switch"].contains(&device_type.as_str()) {
+        return Err(format!("设备 {} 的类型不支持 Modbus 点表", device_id));
+    }
+    let list = modbus_doc::build_point_list(
+        &device_id,
+        device_name.as_deref().unwrap_or(&device_id),
+        &device_type,
+        running.as_ref().map(|(_, r)| r.as_slice()),
+    )?;
+    let text = modbus_doc::render(&list, format.unwrap_or_default())?;
+    match output_path.filter(|p| !p.trim().is_empty()) {
+        Some(path) => {
+            std::fs::write(&path, text).map_err(|e| format!("写入点表失败 {}: {}", path, e))?;
+            Ok(path)
+        }
+        None => Ok(text),
+    }
+}
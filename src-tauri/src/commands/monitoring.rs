@@ -4,10 +4,19 @@ use tauri::State;
 use crate::services::database::Database;
 use crate::services::simulation_engine::SimulationEngine;
 use crate::domain::metadata::DeviceMetadataStore;
-use crate::domain::simulation::SimulationState;
+use crate::domain::simulation::{DeviceEnergyCounters, EvSessionRecord, SimulationState, StorageState};
 use crate::domain::topology::DeviceType;
 use crate::commands::topology::device_type_to_string;
 use crate::services::modbus::ModbusService;
+use crate::services::chart_renderer::{self, ChartSpec, RenderedChart};
+use crate::services::results_pipeline::{self, BurstWindow};
+use crate::services::daily_rollover::{self, DailyEnergyArchive};
+use crate::services::setpoint_limits::SetpointClamp;
+use crate::services::oltc::TapOperation;
+use crate::services::protection::ProtectionTrip;
+use crate::services::weather::{self, WeatherBinding};
+use crate::services::run_summary::{self, RunSummary};
+use crate::services::limit_monitor::{LimitBand, LimitKpi, LimitLevel, QUANTITY_LOADING_PERCENT, QUANTITY_POWER_RATIO_PCT, QUANTITY_VOLTAGE_PU};
 use std::sync::{Arc, Mutex as StdMutex};
 use std::collections::HashMap;
 
@@ -51,16 +60,23 @@ pub struct DeviceStatus {
     /// 仅开关有值：开关闭合状态，true=闭合 false=断开
     #[serde(skip_serializing_if = "Option::is_none")]
     pub is_closed: Option<bool>,
+    /// 功率设备（光伏/储能/负荷/充电桩/外部电网）：引擎积分的日/累计输入输出电量
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub energy_counters: Option<DeviceEnergyCounters>,
+    /// 已配置的传感器上报延迟与执行器响应延迟（毫秒），未配置时省略
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sensor_delay_ms: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actuator_delay_ms: Option<f64>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-#[allow(dead_code)]
 pub struct Alert {
     pub id: String,
     pub device_id: String,
     pub alert_type: String,
     pub message: String,
-    pub severity: String, // "info", "warning", "error"
+    pub severity: String, // "info", "warning", "alarm", "error"
     pub timestamp: f64,
     pub acknowledged: bool,
 }
@@ -86,31 +102,145 @@ pub async fn record_device_data(
     Ok(())
 }
 
+/// 历史回看：游标时刻之前该窗口（秒）内有数据的设备视为在线
+const HISTORY_ONLINE_WINDOW_S: f64 = 30.0;
+
+/// 历史库时间范围（监控页回看模式的时间轴）
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HistoryTimeRange {
+    pub db_path: Option<String>,
+    pub simulation_start: Option<f64>,
+    pub first_timestamp: Option<f64>,
+    pub last_timestamp: Option<f64>,
+}
+
+/// 监控数据所用数据库：指定 db_path 时只读打开该历史库，否则使用当前仿真库（尚无时返回 None）
+fn with_monitor_db<T>(
+    db_path: Option<&str>,
+    db: &Arc<StdMutex<Option<Database>>>,
+    f: impl FnOnce(&Database) -> Result<T, String>,
+) -> Result<Option<T>, String> {
+    match db_path.filter(|p| !p.trim().is_empty()) {
+        Some(path) => {
+            let history = Database::open_readonly(std::path::Path::new(path))
+                .map_err(|e| format!("打开历史数据库失败: {}", e))?;
+            f(&history).map(Some)
+        }
+        None => {
+            let guard = db.lock().unwrap();
+            guard.as_ref().map(f).transpose()
+        }
+    }
+}
+
 #[tauri::command]
 pub async fn get_latest_simulation_start_time(
+    db_path: Option<String>,
     db: State<'_, Arc<StdMutex<Option<Database>>>>,
 ) -> Result<Option<f64>, String> {
-    let guard = db.lock().unwrap();
-    match guard.as_ref() {
-        Some(db) => db.get_latest_simulation_start().map_err(|e| format!("Failed to get latest simulation start: {}", e)),
-        None => Ok(None),
-    }
+    let start = with_monitor_db(db_path.as_deref(), &db, |db| {
+        db.get_latest_simulation_start().map_err(|e| format!("Failed to get latest simulation start: {}", e))
+    })?;
+    Ok(start.flatten())
+}
+
+/// 历史库的仿真起始时间与数据首末时间戳；不传 db_path 时查询当前仿真库
+#[tauri::command]
+pub async fn get_history_time_range(
+    db_path: Option<String>,
+    db: State<'_, Arc<StdMutex<Option<Database>>>>,
+) -> Result<HistoryTimeRange, String> {
+    let range = with_monitor_db(db_path.as_deref(), &db, |db| {
+        let first = db.query_earliest_timestamp().map_err(|e| format!("查询历史数据失败: {}", e))?;
+        let last = db.query_latest_timestamp().map_err(|e| format!("查询历史数据失败: {}", e))?;
+        // 旧库可能没有 simulation_meta 表，此时以首条数据时间为起点
+        let start = db.get_latest_simulation_start().ok().flatten().or(first);
+        Ok((start, first, last))
+    })?;
+    let (simulation_start, first_timestamp, last_timestamp) = range.unwrap_or((None, None, None));
+    Ok(HistoryTimeRange { db_path, simulation_start, first_timestamp, last_timestamp })
+}
+
+/// 仿真库中的突发落库窗口（事件前后逐步落库的时间段）；不传 db_path 时查询当前仿真库
+#[tauri::command]
+pub async fn get_burst_windows(
+    db_path: Option<String>,
+    db: State<'_, Arc<StdMutex<Option<Database>>>>,
+) -> Result<Vec<BurstWindow>, String> {
+    let windows = with_monitor_db(db_path.as_deref(), &db, |db| Ok(results_pipeline::load_burst_windows(db)))?;
+    Ok(windows.unwrap_or_default())
+}
+
+/// 仿真库中的日电量归档（每次日翻转一条）；不传 db_path 时查询当前仿真库
+#[tauri::command]
+pub async fn get_daily_energy_archive(
+    db_path: Option<String>,
+    db: State<'_, Arc<StdMutex<Option<Database>>>>,
+) -> Result<Vec<DailyEnergyArchive>, String> {
+    let archives = with_monitor_db(db_path.as_deref(), &db, |db| Ok(daily_rollover::load_archives(db)))?;
+    Ok(archives.unwrap_or_default())
+}
+
+/// 仿真库记录的本次运行气象数据源；不传 db_path 时查询当前仿真库
+#[tauri::command]
+pub async fn get_run_weather_source(
+    db_path: Option<String>,
+    db: State<'_, Arc<StdMutex<Option<Database>>>>,
+) -> Result<Option<WeatherBinding>, String> {
+    let binding = with_monitor_db(db_path.as_deref(), &db, |db| Ok(weather::load_binding(db)))?;
+    Ok(binding.flatten())
+}
+
+/// 仿真库记录的运行摘要（仿真停止时生成）；不传 db_path 时查询当前仿真库
+#[tauri::command]
+pub async fn get_run_summary(
+    db_path: Option<String>,
+    db: State<'_, Arc<StdMutex<Option<Database>>>>,
+) -> Result<Option<RunSummary>, String> {
+    let summary = with_monitor_db(db_path.as_deref(), &db, |db| Ok(run_summary::load_summary(db)))?;
+    Ok(summary.flatten())
 }
 
+/// 仿真库中的电动汽车充电会话（按到达时间排序）；device_id 为空时返回全部充电桩，不传 db_path 时查询当前仿真库
+#[tauri::command]
+pub async fn get_ev_sessions(
+    device_id: Option<String>,
+    db_path: Option<String>,
+    db: State<'_, Arc<StdMutex<Option<Database>>>>,
+) -> Result<Vec<EvSessionRecord>, String> {
+    let sessions = with_monitor_db(db_path.as_deref(), &db, |db| {
+        db.query_ev_sessions(device_id.as_deref()).map_err(|e| format!("查询充电会话失败: {}", e))
+    })?;
+    Ok(sessions.unwrap_or_default())
+}
+
+/// 渲染曲线图（SVG / PNG）；spec.db_path 指定时从历史库读取，否则读当前仿真库
+#[tauri::command]
+pub async fn render_chart(
+    spec: ChartSpec,
+    db: State<'_, Arc<StdMutex<Option<Database>>>>,
+) -> Result<RenderedChart, String> {
+    let db = db.inner().clone();
+    tokio::task::spawn_blocking(move || chart_renderer::render_with_fallback(&spec, &db))
+    .await
+    .map_err(|e| format!("渲染曲线图失败: {}", e))?
+}
+
+/// 设备历史曲线；db_path 指定时从历史库读取（回看模式），否则读当前仿真库
 #[tauri::command]
 pub async fn query_device_data(
     device_id: String,
     start_time: Option<f64>,
     end_time: Option<f64>,
     max_points: Option<usize>,
+    db_path: Option<String>,
     db: State<'_, Arc<StdMutex<Option<Database>>>>,
 ) -> Result<Vec<DeviceDataPoint>, String> {
-    let guard = db.lock().unwrap();
-    let rows = match guard.as_ref() {
-        Some(db) => db.query_device_data(&device_id, start_time, end_time, max_points)
-            .map_err(|e| format!("Failed to query device data: {}", e))?,
-        None => Vec::new(),
-    };
+    let rows = with_monitor_db(db_path.as_deref(), &db, |db| {
+        db.query_device_data(&device_id, start_time, end_time, max_points)
+            .map_err(|e| format!("Failed to query device data: {}", e))
+    })?
+    .unwrap_or_default();
     let points: Vec<DeviceDataPoint> = rows
         .into_iter()
         .map(|(ts, p_a, p_r, json_str)| {
@@ -152,8 +282,84 @@ fn build_meter_connections(
 /// 电表 Modbus 电量寄存器单位：1 寄存器 = 1 kWh / 1 kVarh（与 modbus_server 一致；前端显示时按 0.1 单位）
 const METER_ENERGY_UNIT: f64 = 1.0;
 
+/// 按游标时刻从库中解析设备状态（历史回看）：功率取该时刻及之前最后一行，
+/// 该行在 HISTORY_ONLINE_WINDOW_S 内视为在线；电量寄存器与引擎计数不落库，回看时不返回。
+/// 设备列表为当前拓扑设备，加上库中存在而拓扑中没有的设备（名称取 id）
+fn historical_devices_status(
+    db: &Database,
+    at: Option<f64>,
+    devices: Vec<crate::domain::topology::Device>,
+    meter_connections: &HashMap<String, String>,
+) -> Result<Vec<DeviceStatus>, String> {
+    let cursor = match at {
+        Some(t) => Some(t),
+        None => db.query_latest_timestamp().map_err(|e| format!("查询历史数据失败: {}", e))?,
+    };
+    let db_devices = db.query_device_ids_with_types().map_err(|e| format!("查询历史数据失败: {}", e))?;
+
+    let mut entries: Vec<(String, String, String, Option<bool>)> = devices
+        .iter()
+        .map(|d| {
+            let is_closed = (d.device_type == DeviceType::Switch)
+                .then(|| d.properties.get("is_closed").and_then(|v| v.as_bool()).unwrap_or(true));
+            (d.id.clone(), d.name.clone(), device_type_to_string(&d.device_type), is_closed)
+        })
+        .collect();
+    for (id, device_type) in db_devices {
+        if !devices.iter().any(|d| d.id == id) {
+            entries.push((id.clone(), id, device_type.unwrap_or_else(|| "unknown".to_string()), None));
+        }
+    }
+
+    let mut statuses = Vec::with_capacity(entries.len());
+    for (device_id, name, device_type, is_closed) in entries {
+        let target_device_id = if device_type == "meter" { meter_connections.get(&device_id).cloned() } else { None };
+        let row = match cursor {
+            Some(t) => {
+                let own = db.query_device_data_as_of(&device_id, t).map_err(|e| format!("查询历史数据失败: {}", e))?;
+                match (own, target_device_id.as_deref()) {
+                    (None, Some(target)) => {
+                        db.query_device_data_as_of(target, t).map_err(|e| format!("查询历史数据失败: {}", e))?
+                    }
+                    (own, _) => own,
+                }
+            }
+            None => None,
+        };
+        let (last_update, p_active, p_reactive) = match &row {
+            Some((t, p_a, p_r, _)) => (Some(*t), *p_a, *p_r),
+            None => (None, None, None),
+        };
+        let is_online = matches!((last_update, cursor), (Some(t), Some(c)) if t >= c - HISTORY_ONLINE_WINDOW_S);
+        statuses.push(DeviceStatus {
+            device_id,
+            name,
+            device_type,
+            is_online,
+            last_update,
+            current_p_active: p_active,
+            current_p_reactive: p_reactive,
+            target_device_id,
+            energy_export_kwh: None,
+            energy_import_kwh: None,
+            energy_total_kwh: None,
+            energy_reactive_export_kvarh: None,
+            energy_reactive_import_kvarh: None,
+            grid_mode: None,
+            is_closed,
+            energy_counters: None,
+            sensor_delay_ms: None,
+            actuator_delay_ms: None,
+        });
+    }
+    Ok(statuses)
+}
+
+/// 全部设备状态；传入 db_path 或 at 时进入历史回看模式，按 at（默认库中最后时刻）解析各设备状态
 #[tauri::command]
 pub async fn get_all_devices_status(
+    db_path: Option<String>,
+    at: Option<f64>,
     metadata_store: State<'_, StdMutex<DeviceMetadataStore>>,
     db: State<'_, Arc<StdMutex<Option<Database>>>>,
     engine: State<'_, Arc<SimulationEngine>>,
@@ -170,6 +376,13 @@ pub async fn get_all_devices_status(
     };
     let meter_connections = topology.as_ref().map(build_meter_connections).unwrap_or_default();
 
+    if db_path.is_some() || at.is_some() {
+        let statuses = with_monitor_db(db_path.as_deref(), &db, |db| {
+            historical_devices_status(db, at, devices, &meter_connections)
+        })?;
+        return Ok(statuses.unwrap_or_default());
+    }
+
     let sim_status = engine.get_status().await;
     let device_active = engine.get_device_active_status().await;
     let is_online_from_engine = |device_id: &str| -> bool {
@@ -251,6 +464,7 @@ pub async fn get_all_devices_status(
             None
         };
 
+        let delays = engine.get_device_delays(&device.id);
         statuses.push(DeviceStatus {
             device_id: device.id.clone(),
             name: device.name.clone(),
@@ -267,20 +481,42 @@ pub async fn get_all_devices_status(
             energy_reactive_export_kvarh,
             energy_reactive_import_kvarh,
             grid_mode,
+            energy_counters: engine.get_device_energy(&device.id),
+            sensor_delay_ms: delays.map(|d| d.sensor_delay_ms).filter(|v| *v > 0.0),
+            actuator_delay_ms: delays.map(|d| d.actuator_delay_ms).filter(|v| *v > 0.0),
         });
     }
 
     Ok(statuses)
 }
 
+/// 单个设备状态；db_path / at 含义同 get_all_devices_status
 #[tauri::command]
 pub async fn get_device_status(
     device_id: String,
+    db_path: Option<String>,
+    at: Option<f64>,
     metadata_store: State<'_, StdMutex<DeviceMetadataStore>>,
     db: State<'_, Arc<StdMutex<Option<Database>>>>,
     engine: State<'_, Arc<SimulationEngine>>,
     modbus: State<'_, ModbusService>,
 ) -> Result<DeviceStatus, String> {
+    if db_path.is_some() || at.is_some() {
+        let (device, meter_connections) = {
+            let store = metadata_store.lock().unwrap();
+            let connections = store.get_topology().as_ref().map(build_meter_connections).unwrap_or_default();
+            (store.get_device(&device_id), connections)
+        };
+        let statuses = with_monitor_db(db_path.as_deref(), &db, |db| {
+            historical_devices_status(db, at, device.into_iter().collect(), &meter_connections)
+        })?
+        .unwrap_or_default();
+        return statuses
+            .into_iter()
+            .find(|s| s.device_id == device_id)
+            .ok_or_else(|| format!("Device {} not found", device_id));
+    }
+
     let (name, device_type_str, device_type, is_closed) = {
         let store = metadata_store.lock().unwrap();
         let device = store.get_device(&device_id)
@@ -368,6 +604,8 @@ pub async fn get_device_status(
         None
     };
 
+    let energy_counters = engine.get_device_energy(&device_id);
+    let delays = engine.get_device_delays(&device_id);
     Ok(DeviceStatus {
         device_id,
         name,
@@ -384,5 +622,109 @@ pub async fn get_device_status(
         energy_reactive_import_kvarh,
         grid_mode,
         is_closed,
+        energy_counters,
+        sensor_delay_ms: delays.map(|d| d.sensor_delay_ms).filter(|v| *v > 0.0),
+        actuator_delay_ms: delays.map(|d| d.actuator_delay_ms).filter(|v| *v > 0.0),
     })
 }
+
+/// 当前软限值告警（预警 warning / 报警 alarm），按严重程度排序
+#[tauri::command]
+pub async fn get_active_alerts(
+    engine: State<'_, Arc<SimulationEngine>>,
+) -> Result<Vec<Alert>, String> {
+    let alerts = engine
+        .get_active_limit_alerts()
+        .into_iter()
+        .map(|a| {
+            let (label, unit) = match a.quantity.as_str() {
+                QUANTITY_VOLTAGE_PU => ("电压", " pu"),
+                QUANTITY_LOADING_PERCENT => ("负载率", "%"),
+                QUANTITY_POWER_RATIO_PCT => ("出力占额定功率", "%"),
+                other => (other, ""),
+            };
+            let level = if a.level == LimitLevel::Alarm { "报警" } else { "预警" };
+            Alert {
+                id: format!("{}:{}", a.device_id, a.quantity),
+                device_id: a.device_id,
+                alert_type: format!("limit_{}", a.quantity),
+                message: format!("{}{}: {:.3}{}", label, level, a.value, unit),
+                severity: a.level.as_str().to_string(),
+                timestamp: a.since,
+                acknowledged: false,
+            }
+        })
+        .collect();
+    Ok(alerts)
+}
+
+/// 各设备软限值 KPI（评估步数、预警步数、报警步数）
+#[tauri::command]
+pub async fn get_limit_kpis(
+    engine: State<'_, Arc<SimulationEngine>>,
+) -> Result<HashMap<String, LimitKpi>, String> {
+    Ok(engine.get_limit_kpis())
+}
+
+/// 储能状态（SOC、日/累计电量、等效循环次数与容量衰减）；储能尚未参与计算时为 None
+#[tauri::command]
+pub async fn get_storage_state(
+    device_id: String,
+    engine: State<'_, Arc<SimulationEngine>>,
+) -> Result<Option<StorageState>, String> {
+    Ok(engine.get_storage_state(&device_id))
+}
+
+/// 本次仿真中设定值超出设备额定功率被限幅的记录（请求值与实际下发值）
+#[tauri::command]
+pub async fn get_setpoint_clamps(
+    engine: State<'_, Arc<SimulationEngine>>,
+) -> Result<Vec<SetpointClamp>, String> {
+    Ok(engine.get_setpoint_clamps())
+}
+
+/// 本次仿真中有载调压变压器的分接头动作记录
+#[tauri::command]
+pub async fn get_tap_operations(
+    engine: State<'_, Arc<SimulationEngine>>,
+) -> Result<Vec<TapOperation>, String> {
+    Ok(engine.get_tap_operations())
+}
+
+/// 本次仿真中线路/变压器过流保护的跳闸记录
+#[tauri::command]
+pub async fn get_protection_events(
+    engine: State<'_, Arc<SimulationEngine>>,
+) -> Result<Vec<ProtectionTrip>, String> {
+    Ok(engine.get_protection_events())
+}
+
+/// 设置设备软限值带，quantity -> 限值带；传空表示恢复默认
+#[tauri::command]
+pub async fn set_device_limit_bands(
+    device_id: String,
+    bands: HashMap<String, LimitBand>,
+    engine: State<'_, Arc<SimulationEngine>>,
+) -> Result<(), String> {
+    engine.set_device_limit_bands(device_id, bands);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_device_limit_bands(
+    device_id: String,
+    engine: State<'_, Arc<SimulationEngine>>,
+) -> Result<HashMap<String, LimitBand>, String> {
+    Ok(engine.get_device_limit_bands(&device_id).await)
+}
+
+/// 导出前端事件负载的机器可读 schema（JSON Schema）；指定 output_path 时同时写入文件
+#[tauri::command]
+pub async fn export_event_schema(output_path: Option<String>) -> Result<serde_json::Value, String> {
+    let document = crate::domain::events::event_schema_document();
+    if let Some(path) = output_path {
+        let content = serde_json::to_string_pretty(&document).map_err(|e| e.to_string())?;
+        std::fs::write(&path, content).map_err(|e| format!("写入事件 schema 失败: {}", e))?;
+    }
+    Ok(document)
+}
@@ -0,0 +1,77 @@
+// 场景脚本命令：运行带断言的场景并返回通过/失败结果；加载内置演示项目
+use crate::domain::metadata::DeviceMetadataStore;
+use crate::domain::preset::RunOptions;
+use crate::domain::simulation::SimulationState;
+use crate::services::demo_project::{self, DemoProjectInfo};
+use crate::services::event_scheduler::{EventSchedule, EventScheduleStatus};
+use crate::services::scenario::{self, ScenarioResult, ScenarioScript};
+use crate::services::simulation_engine::SimulationEngine;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, State};
+
+/// 运行场景（需仿真运行中）：直接传入 script，或由 file_path 加载；进度通过 scenario-progress 推送
+#[tauri::command]
+pub async fn run_scenario(
+    app: AppHandle,
+    script: Option<ScenarioScript>,
+    file_path: Option<String>,
+    engine: State<'_, Arc<SimulationEngine>>,
+) -> Result<ScenarioResult, String> {
+    let script = match (script, file_path) {
+        (Some(s), _) => s,
+        (None, Some(path)) => ScenarioScript::load(&path)?,
+        (None, None) => return Err("需提供场景脚本或场景文件路径".to_string()),
+    };
+    scenario::run_scenario(&engine, &app, &script).await
+}
+
+/// 加载内置演示项目：安装示例拓扑、随机曲线、电价与场景到临时目录并启动一段脚本化运行（需仿真已停止）；
+/// 运行结束后自动停止仿真并推送 demo-project-finished
+#[tauri::command]
+pub async fn load_demo_project(
+    app: AppHandle,
+    engine: State<'_, Arc<SimulationEngine>>,
+    metadata_store: State<'_, Mutex<DeviceMetadataStore>>,
+) -> Result<DemoProjectInfo, String> {
+    if engine.get_status().await.state != SimulationState::Stopped {
+        return Err("仿真运行中，请先停止仿真再加载演示项目".to_string());
+    }
+    let workspace = demo_project::install_workspace()?;
+    let topology = crate::commands::topology::read_topology_file(&workspace.topology_path)?;
+    metadata_store.lock().unwrap().set_topology(topology.clone());
+    engine.set_topology(topology).await;
+    engine.set_remote_control_enabled(false);
+    engine.set_run_options(RunOptions::default());
+    demo_project::start_demo_run(engine.inner().clone(), app, workspace).await
+}
+
+/// 载入定时事件表：直接传入 schedule，或由 file_path 加载。事件在计算循环中按仿真时间施加，每轮仿真从头开始；
+/// 施加结果通过 scheduled-event-applied 推送。功率设定（p_kw/q_kvar）仅在设备处于手动模式时持续生效
+#[tauri::command]
+pub async fn load_event_schedule(
+    schedule: Option<EventSchedule>,
+    file_path: Option<String>,
+    engine: State<'_, Arc<SimulationEngine>>,
+) -> Result<EventScheduleStatus, String> {
+    let schedule = match (schedule, file_path) {
+        (Some(s), _) => s,
+        (None, Some(path)) => EventSchedule::load(&path)?,
+        (None, None) => return Err("需提供事件表或事件表文件路径".to_string()),
+    };
+    engine.load_event_schedule(schedule)?;
+    Ok(engine.event_schedule_status())
+}
+
+#[tauri::command]
+pub async fn clear_event_schedule(engine: State<'_, Arc<SimulationEngine>>) -> Result<(), String> {
+    engine.clear_event_schedule();
+    Ok(())
+}
+
+/// 定时事件表进度与本轮已施加事件记录
+#[tauri::command]
+pub async fn get_event_schedule_status(
+    engine: State<'_, Arc<SimulationEngine>>,
+) -> Result<EventScheduleStatus, String> {
+    Ok(engine.event_schedule_status())
+}
@@ -0,0 +1,468 @@
+// 应用设置命令：计算预设的查询、保存与删除；功率符号约定设置与旧仿真库迁移；内核保温开关、看门狗、内核日志（级别、滚动与末尾行读取）、内核请求超时表、并行求解内核池与远程内核端点；Webhook 通知配置；外部接口 API 令牌与审计日志
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tauri::State;
+use crate::domain::auth::{ApiScope, ApiTokenInfo, AuditEntry, CreatedApiToken, PeerScopeRule};
+use crate::domain::preset::CalculationPreset;
+use crate::domain::sign_convention::SignConvention;
+use crate::domain::simulation::{KernelLogConfig, KernelPoolConfig, KernelRpcConfig, KernelWatchdogConfig, ResultTransferConfig, SimulationState};
+use crate::domain::webhook::{WebhookConfig, WebhookDelivery};
+use crate::domain::topology::DeviceType;
+use crate::services::database::Database;
+use crate::services::kernel_log::{KernelLog, KernelLogTail};
+use crate::services::kernel_pool::KernelPool;
+use crate::services::kernel_transport::KernelEndpoint;
+use crate::services::api_auth::{self, ApiAuth};
+use crate::services::settings::SettingsStore;
+use crate::services::simulation_engine::SimulationEngine;
+use crate::services::webhook::WebhookDispatcher;
+
+#[tauri::command]
+pub async fn list_calculation_presets(
+    settings: State<'_, SettingsStore>,
+) -> Result<Vec<CalculationPreset>, String> {
+    Ok(settings.list_presets())
+}
+
+/// 保存用户预设（同名覆盖；与内置预设同名时覆盖内置值）
+#[tauri::command]
+pub async fn save_calculation_preset(
+    preset: CalculationPreset,
+    settings: State<'_, SettingsStore>,
+) -> Result<(), String> {
+    settings.save_preset(preset)
+}
+
+#[tauri::command]
+pub async fn delete_calculation_preset(
+    name: String,
+    settings: State<'_, SettingsStore>,
+) -> Result<(), String> {
+    settings.delete_preset(&name)
+}
+
+#[tauri::command]
+pub async fn get_sign_convention(
+    settings: State<'_, SettingsStore>,
+) -> Result<SignConvention, String> {
+    Ok(settings.sign_convention())
+}
+
+/// 设置项目级功率符号约定；仿真运行中不可修改（同一仿真库内约定须一致），下次启动仿真生效
+#[tauri::command]
+pub async fn set_sign_convention(
+    convention: SignConvention,
+    settings: State<'_, SettingsStore>,
+    engine: State<'_, Arc<SimulationEngine>>,
+) -> Result<(), String> {
+    if engine.get_status().await.state != SimulationState::Stopped {
+        return Err("仿真运行中不可修改符号约定，请先停止仿真".to_string());
+    }
+    settings.set_sign_convention(convention.clone())?;
+    engine.set_sign_convention(convention);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_keep_kernel_warm(
+    settings: State<'_, SettingsStore>,
+) -> Result<bool, String> {
+    Ok(settings.keep_kernel_warm())
+}
+
+/// 设置内核保温：开启时停止仿真后内核保留已构建的网络，下次停止时生效
+#[tauri::command]
+pub async fn set_keep_kernel_warm(
+    enabled: bool,
+    settings: State<'_, SettingsStore>,
+    engine: State<'_, Arc<SimulationEngine>>,
+) -> Result<(), String> {
+    settings.set_keep_kernel_warm(enabled)?;
+    engine.set_keep_kernel_warm(enabled);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_kernel_watchdog_config(
+    settings: State<'_, SettingsStore>,
+) -> Result<KernelWatchdogConfig, String> {
+    Ok(settings.kernel_watchdog())
+}
+
+/// 设置内核看门狗（下一次探测起生效）
+#[tauri::command]
+pub async fn set_kernel_watchdog_config(
+    config: KernelWatchdogConfig,
+    settings: State<'_, SettingsStore>,
+) -> Result<(), String> {
+    config.validate()?;
+    settings.set_kernel_watchdog(config)
+}
+
+#[tauri::command]
+pub async fn get_kernel_log_config(
+    settings: State<'_, SettingsStore>,
+) -> Result<KernelLogConfig, String> {
+    Ok(settings.kernel_log())
+}
+
+/// 设置内核日志级别与滚动上限，立即生效：过滤 python-kernel.log 的写入并调整运行中主内核的 logging 级别
+/// （并行求解的内核进程与未运行的内核在下次启动时生效）
+#[tauri::command]
+pub async fn set_kernel_log_config(
+    config: KernelLogConfig,
+    settings: State<'_, SettingsStore>,
+    kernel_log: State<'_, Arc<KernelLog>>,
+    engine: State<'_, Arc<SimulationEngine>>,
+) -> Result<(), String> {
+    config.validate()?;
+    settings.set_kernel_log(config.clone())?;
+    kernel_log.configure(config.clone());
+    let params = serde_json::json!({ "level": config.level.as_python() });
+    if let Err(e) = engine.kernel().call("kernel.set_log_level", params).await {
+        eprintln!("调整内核日志级别失败（下次启动内核时生效）: {}", e);
+    }
+    Ok(())
+}
+
+/// 内核日志末尾若干行（默认 200 行），供诊断面板显示
+#[tauri::command]
+pub async fn get_kernel_log_tail(
+    lines: Option<usize>,
+    kernel_log: State<'_, Arc<KernelLog>>,
+) -> Result<KernelLogTail, String> {
+    kernel_log.tail(lines.unwrap_or(200))
+}
+
+/// 立即滚动内核日志（当前文件改名为 .1，之后写入新文件）
+#[tauri::command]
+pub async fn rotate_kernel_log(
+    kernel_log: State<'_, Arc<KernelLog>>,
+) -> Result<(), String> {
+    kernel_log.rotate();
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_kernel_rpc_config(
+    settings: State<'_, SettingsStore>,
+) -> Result<KernelRpcConfig, String> {
+    Ok(settings.kernel_rpc())
+}
+
+/// 设置内核请求的超时表与重试策略，主内核立即生效（并行求解的内核进程在下次启动时生效）
+#[tauri::command]
+pub async fn set_kernel_rpc_config(
+    config: KernelRpcConfig,
+    settings: State<'_, SettingsStore>,
+    engine: State<'_, Arc<SimulationEngine>>,
+) -> Result<(), String> {
+    config.validate()?;
+    settings.set_kernel_rpc(config.clone())?;
+    engine.kernel().set_rpc_config(config);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_kernel_pool_config(
+    settings: State<'_, SettingsStore>,
+) -> Result<KernelPoolConfig, String> {
+    Ok(settings.kernel_pool())
+}
+
+/// 设置并行求解内核池的进程上限与默认并行度，立即生效（超过新上限的内核进程随即关闭）
+#[tauri::command]
+pub async fn set_kernel_pool_config(
+    config: KernelPoolConfig,
+    settings: State<'_, SettingsStore>,
+    kernel_pool: State<'_, KernelPool>,
+) -> Result<(), String> {
+    config.validate()?;
+    settings.set_kernel_pool(config.clone())?;
+    kernel_pool.configure(config).await;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_result_transfer_config(
+    settings: State<'_, SettingsStore>,
+) -> Result<ResultTransferConfig, String> {
+    Ok(settings.result_transfer())
+}
+
+/// 设置单步结果的共享文件传输，主内核从下一步起生效
+#[tauri::command]
+pub async fn set_result_transfer_config(
+    config: ResultTransferConfig,
+    settings: State<'_, SettingsStore>,
+    engine: State<'_, Arc<SimulationEngine>>,
+) -> Result<(), String> {
+    config.validate()?;
+    settings.set_result_transfer(config.clone())?;
+    engine.kernel().set_result_transfer(config);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_kernel_endpoint(
+    settings: State<'_, SettingsStore>,
+) -> Result<Option<String>, String> {
+    Ok(settings.kernel_endpoint())
+}
+
+/// 设置远程内核端点（tcp://主机:端口 或 ws://主机:端口/路径，空或 None 恢复本地内核进程），下次启动应用时生效
+#[tauri::command]
+pub async fn set_kernel_endpoint(
+    endpoint: Option<String>,
+    settings: State<'_, SettingsStore>,
+) -> Result<(), String> {
+    let endpoint = endpoint.map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+    if let Some(ref url) = endpoint {
+        KernelEndpoint::parse(url)?;
+    }
+    settings.set_kernel_endpoint(endpoint)
+}
+
+#[tauri::command]
+pub async fn get_random_seed(
+    settings: State<'_, SettingsStore>,
+) -> Result<Option<u64>, String> {
+    Ok(settings.random_seed())
+}
+
+/// 设置随机模式全局种子（None 取消）：之后配置的随机模式设备按此派生种子，相同种子的仿真可复现
+#[tauri::command]
+pub async fn set_random_seed(
+    seed: Option<u64>,
+    settings: State<'_, SettingsStore>,
+    engine: State<'_, Arc<SimulationEngine>>,
+) -> Result<(), String> {
+    settings.set_random_seed(seed)?;
+    engine.set_random_seed(seed);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn list_webhooks(
+    settings: State<'_, SettingsStore>,
+) -> Result<Vec<WebhookConfig>, String> {
+    Ok(settings.webhooks())
+}
+
+/// 保存 Webhook（同 ID 覆盖）并立即生效
+#[tauri::command]
+pub async fn save_webhook(
+    webhook: WebhookConfig,
+    settings: State<'_, SettingsStore>,
+    dispatcher: State<'_, WebhookDispatcher>,
+) -> Result<Vec<WebhookConfig>, String> {
+    let webhooks = settings.save_webhook(webhook)?;
+    dispatcher.set_configs(webhooks.clone());
+    Ok(webhooks)
+}
+
+#[tauri::command]
+pub async fn delete_webhook(
+    id: String,
+    settings: State<'_, SettingsStore>,
+    dispatcher: State<'_, WebhookDispatcher>,
+) -> Result<Vec<WebhookConfig>, String> {
+    let webhooks = settings.delete_webhook(&id)?;
+    dispatcher.set_configs(webhooks.clone());
+    Ok(webhooks)
+}
+
+/// 向指定 Webhook 发送测试消息并返回投递结果
+#[tauri::command]
+pub async fn test_webhook(
+    id: String,
+    settings: State<'_, SettingsStore>,
+    dispatcher: State<'_, WebhookDispatcher>,
+) -> Result<WebhookDelivery, String> {
+    let webhook = settings
+        .webhooks()
+        .into_iter()
+        .find(|w| w.id == id)
+        .ok_or_else(|| format!("Webhook 不存在: {}", id))?;
+    Ok(dispatcher.send_test(webhook).await)
+}
+
+/// 最近的投递记录（含重试次数与失败原因）
+#[tauri::command]
+pub async fn get_webhook_deliveries(
+    dispatcher: State<'_, WebhookDispatcher>,
+) -> Result<Vec<WebhookDelivery>, String> {
+    Ok(dispatcher.deliveries())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SignMigrationResult {
+    pub from: SignConvention,
+    pub to: SignConvention,
+    /// 被翻转符号的设备类型
+    pub flipped_device_types: Vec<String>,
+    pub rows_updated: usize,
+}
+
+/// 将仿真库转换到目标符号约定（默认当前项目约定）；未记录约定的旧库按内核原生约定处理
+#[tauri::command]
+pub async fn migrate_run_db_sign_convention(
+    db_path: String,
+    target: Option<SignConvention>,
+    settings: State<'_, SettingsStore>,
+    engine: State<'_, Arc<SimulationEngine>>,
+    current_db_path: State<'_, Arc<std::sync::Mutex<String>>>,
+) -> Result<SignMigrationResult, String> {
+    if engine.get_status().await.state != SimulationState::Stopped
+        && *current_db_path.lock().unwrap() == db_path
+    {
+        return Err("不能迁移正在写入的仿真库，请先停止仿真".to_string());
+    }
+    let to = target.unwrap_or_else(|| settings.sign_convention());
+    tokio::task::spawn_blocking(move || {
+        let mut db = Database::new(Some(std::path::Path::new(&db_path)))
+            .map_err(|e| format!("打开仿真库失败: {}", e))?;
+        let from = db
+            .get_meta_text("sign_convention")
+            .map_err(|e| format!("读取符号约定失败: {}", e))?
+            .and_then(|s| serde_json::from_str::<SignConvention>(&s).ok())
+            .unwrap_or_else(SignConvention::native);
+        let flipped: Vec<&str> = [
+            DeviceType::Load,
+            DeviceType::Charger,
+            DeviceType::Pv,
+            DeviceType::Storage,
+            DeviceType::ExternalGrid,
+        ]
+        .iter()
+        .filter(|t| to.factor_from(&from, t) < 0.0)
+        .map(|t| t.as_str())
+        .collect();
+        let rows_updated = db
+            .flip_power_sign(&flipped)
+            .map_err(|e| format!("迁移失败: {}", e))?;
+        let to_json = serde_json::to_string(&to).map_err(|e| e.to_string())?;
+        db.set_meta_text("sign_convention", &to_json)
+            .map_err(|e| format!("写入符号约定失败: {}", e))?;
+        Ok(SignMigrationResult {
+            from,
+            to,
+            flipped_device_types: flipped.into_iter().map(String::from).collect(),
+            rows_updated,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+// ====== 外部接口认证 ======
+
+/// 创建 API 令牌；返回的明文令牌只出现这一次，之后仅保存摘要
+#[tauri::command]
+pub async fn create_api_token(
+    name: String,
+    scope: ApiScope,
+    expires_in_days: Option<f64>,
+    settings: State<'_, SettingsStore>,
+    auth: State<'_, Arc<ApiAuth>>,
+) -> Result<CreatedApiToken, String> {
+    if name.trim().is_empty() {
+        return Err("令牌名称不能为空".to_string());
+    }
+    if expires_in_days.is_some_and(|d| d <= 0.0) {
+        return Err("有效期须大于 0 天".to_string());
+    }
+    let expires_at = expires_in_days.map(|d| {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs_f64()
+            + d * 86400.0
+    });
+    let (token, record) = api_auth::generate_token(name.trim(), scope, expires_at);
+    let id = record.id.clone();
+    auth.set_tokens(settings.add_api_token(record)?);
+    auth.record_local("create_token", Some(format!("{}（{}，{}）", id, name.trim(), scope.as_str())));
+    let info = auth
+        .token_infos()
+        .into_iter()
+        .find(|t| t.id == id)
+        .ok_or("令牌保存失败")?;
+    Ok(CreatedApiToken { token, info })
+}
+
+#[tauri::command]
+pub async fn list_api_tokens(
+    auth: State<'_, Arc<ApiAuth>>,
+) -> Result<Vec<ApiTokenInfo>, String> {
+    Ok(auth.token_infos())
+}
+
+/// 吊销令牌：立即对所有对外服务失效，记录保留供审计
+#[tauri::command]
+pub async fn revoke_api_token(
+    id: String,
+    settings: State<'_, SettingsStore>,
+    auth: State<'_, Arc<ApiAuth>>,
+) -> Result<Vec<ApiTokenInfo>, String> {
+    auth.set_tokens(settings.revoke_api_token(&id)?);
+    auth.record_local("revoke_token", Some(id));
+    Ok(auth.token_infos())
+}
+
+#[tauri::command]
+pub async fn get_external_anonymous_scope(
+    auth: State<'_, Arc<ApiAuth>>,
+) -> Result<Option<ApiScope>, String> {
+    Ok(auth.anonymous_scope())
+}
+
+/// 设置未携带令牌的外部连接（如 Modbus TCP 客户端）的权限；None 表示拒绝，立即生效
+#[tauri::command]
+pub async fn set_external_anonymous_scope(
+    scope: Option<ApiScope>,
+    settings: State<'_, SettingsStore>,
+    auth: State<'_, Arc<ApiAuth>>,
+) -> Result<(), String> {
+    settings.set_external_anonymous_scope(scope)?;
+    auth.set_anonymous_scope(scope);
+    auth.record_local(
+        "set_anonymous_scope",
+        Some(scope.map(|s| s.as_str()).unwrap_or("deny").to_string()),
+    );
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_external_peer_scopes(
+    auth: State<'_, Arc<ApiAuth>>,
+) -> Result<Vec<PeerScopeRule>, String> {
+    Ok(auth.peer_scopes())
+}
+
+/// 设置按客户端地址（IP / CIDR）授予的权限，先于匿名权限匹配，立即生效
+#[tauri::command]
+pub async fn set_external_peer_scopes(
+    rules: Vec<PeerScopeRule>,
+    settings: State<'_, SettingsStore>,
+    auth: State<'_, Arc<ApiAuth>>,
+) -> Result<(), String> {
+    settings.set_external_peer_scopes(rules.clone())?;
+    let detail = rules
+        .iter()
+        .map(|r| format!("{}={}", r.address, r.scope.as_str()))
+        .collect::<Vec<_>>()
+        .join(",");
+    auth.set_peer_scopes(rules);
+    auth.record_local("set_peer_scopes", Some(detail));
+    Ok(())
+}
+
+/// 认证审计日志（新的在前，默认 200 条）
+#[tauri::command]
+pub async fn get_auth_audit_log(
+    limit: Option<usize>,
+    auth: State<'_, Arc<ApiAuth>>,
+) -> Result<Vec<AuditEntry>, String> {
+    Ok(auth.audit_log(limit.unwrap_or(200)))
+}
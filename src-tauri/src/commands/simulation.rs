@@ -1,16 +1,61 @@
 // 仿真引擎命令
 use serde::{Deserialize, Serialize};
-use tauri::{AppHandle, State};
+use tauri::{AppHandle, Manager, State};
 use crate::services::simulation_engine::SimulationEngine;
-use crate::domain::simulation::{SimulationStatus, SimulationError};
+use crate::services::simulation_manager::{SimulationInstanceInfo, SimulationManager};
+use crate::services::partial_topology::{self, BoundaryConfig, BoundaryMode, PartialTopology};
+use crate::services::replay::{ReplayOptions, ReplayService, ReplayStatus};
+use crate::domain::simulation::{CounterGroup, CounterResetResult, SimulationStatus, SimulationError, SimulationState, DevicePropertyDrift, TopologyPreloadResult};
 use crate::domain::metadata::DeviceMetadataStore;
+use crate::domain::device::{GridSupportConfig, PfResponseConfig, ReactiveControlConfig, StoredDeviceControl, StoredManualSetpoint, StoredRandomConfig};
+use crate::domain::topology::DeviceType;
+use crate::services::modbus::ModbusService;
+use crate::services::api_auth::ApiAuth;
+use crate::services::settings::SettingsStore;
+use crate::services::database::Database;
+use crate::services::run_recovery::{self, CheckpointFileInfo, InterruptedRun, RunStatus};
+use crate::domain::preset::{AdaptiveInterval, ConsumerRates, DailyRollover, RunOptions};
+use crate::domain::random_profile::RandomProfile;
+use crate::services::kernel_sync::KernelSyncReport;
+use crate::services::bridge_metrics::BridgeMetrics;
+use crate::services::kernel_transport::KernelEndpoint;
+use crate::services::python_env::{self, EnvironmentReport};
+use crate::services::kernel_factory::{KernelFactory, KernelType, PowerKernelCatalog};
+use crate::services::weather::{WeatherBinding, WeatherSourceSpec};
+use crate::services::breakpoints::Breakpoint;
+use crate::services::sweep::{self, SweepIndex, SweepSettings, SweepSpec};
+use crate::services::fault_injector::{ActiveFault, FaultRecord, FaultType};
+use crate::services::window_hub::{SystemSnapshot, WindowEventHub, WindowSubscription};
+use crate::services::series_tail::SeriesTailManager;
 use std::sync::{Arc, Mutex};
+use std::collections::{HashMap, HashSet};
 use rusqlite::Connection;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SimulationConfig {
     pub calculation_interval_ms: u64,
     pub remote_control_enabled: bool,
+    /// 时间倍率（如 10 / 60 / 3600）：每步仿真时间 = 计算步长 × time_scale；未提供时为实时
+    #[serde(default)]
+    pub time_scale: Option<f64>,
+    /// 落库/前端事件/Modbus 同步各自的输出间隔；未提供时逐步输出
+    #[serde(default)]
+    pub consumer_rates: Option<ConsumerRates>,
+    /// 自适应计算间隔：按实测步耗时在范围内调整计算间隔；未提供时为固定步长
+    #[serde(default)]
+    pub adaptive_interval: Option<AdaptiveInterval>,
+    /// 仿真时钟起点（Unix 秒，如从某日 0 点开始仿真）；未提供时取启动时刻
+    #[serde(default)]
+    pub sim_start_epoch: Option<f64>,
+    /// 日电量翻转；未提供时每天本地零点翻转
+    #[serde(default)]
+    pub daily_rollover: Option<DailyRollover>,
+    /// 无人值守运行自动停止：仿真时钟经过该时长（秒，随时间倍率加速）后停止并生成运行摘要
+    #[serde(default)]
+    pub max_duration_secs: Option<f64>,
+    /// 自动停止：完成该步数后停止
+    #[serde(default)]
+    pub max_steps: Option<u64>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -24,57 +69,496 @@ pub struct DeviceModeRequest {
     pub mode: String,
 }
 
+/// 主仿真从界面元数据同步拓扑；独立实例使用创建时的拓扑快照
+async fn sync_topology(
+    engine: &SimulationEngine,
+    simulation_id: Option<&str>,
+    metadata_store: &Mutex<DeviceMetadataStore>,
+) -> Result<(), String> {
+    if !SimulationManager::is_default(simulation_id) {
+        return Ok(());
+    }
+    let topology = metadata_store.lock().unwrap().get_topology();
+    let topology = topology.ok_or("未找到拓扑数据，请先加载拓扑")?;
+    engine.set_topology(topology).await;
+    Ok(())
+}
+
+/// 创建独立仿真实例（专用内核进程与仿真库）；topology_path 为空时使用当前界面拓扑的快照。
+/// 之后各仿真命令传入 simulation_id 即作用于该实例
+#[tauri::command]
+pub async fn create_simulation_instance(
+    app: AppHandle,
+    simulation_id: String,
+    label: Option<String>,
+    topology_path: Option<String>,
+    simulations: State<'_, SimulationManager>,
+    metadata_store: State<'_, Mutex<DeviceMetadataStore>>,
+    settings: State<'_, SettingsStore>,
+) -> Result<SimulationInstanceInfo, String> {
+    let topology = match topology_path {
+        Some(path) => crate::commands::topology::read_topology_file(&path)?,
+        None => metadata_store
+            .lock()
+            .unwrap()
+            .get_topology()
+            .ok_or("未找到拓扑数据，请先加载拓扑或指定拓扑文件")?,
+    };
+    let info = simulations.create(&app, &simulation_id, label, topology).await?;
+    // 项目级设置与主仿真一致
+    let engine = simulations.get(Some(&simulation_id))?;
+    engine.set_sign_convention(settings.sign_convention());
+    engine.set_keep_kernel_warm(settings.keep_kernel_warm());
+    engine.set_random_seed(settings.random_seed());
+    Ok(info)
+}
+
+/// 全部仿真实例（含主仿真 default）及其运行状态
+#[tauri::command]
+pub async fn list_simulation_instances(
+    simulations: State<'_, SimulationManager>,
+) -> Result<Vec<SimulationInstanceInfo>, String> {
+    Ok(simulations.list().await)
+}
+
+/// 删除独立仿真实例：停止其仿真并关闭内核进程（仿真库文件保留）
+#[tauri::command]
+pub async fn remove_simulation_instance(
+    simulation_id: String,
+    simulations: State<'_, SimulationManager>,
+) -> Result<(), String> {
+    simulations.remove(&simulation_id).await
+}
+
+/// 参数扫描：按参数网格逐个组合运行无界面仿真（每次一个独立实例，到达步数/时长上限自动停止），
+/// 仿真库与 index.json 写入输出目录；进度通过 sweep-progress 推送
+#[tauri::command]
+pub async fn run_parameter_sweep(
+    app: AppHandle,
+    spec: SweepSpec,
+    simulations: State<'_, SimulationManager>,
+    metadata_store: State<'_, Mutex<DeviceMetadataStore>>,
+    settings: State<'_, SettingsStore>,
+) -> Result<SweepIndex, String> {
+    let topology = match &spec.topology_path {
+        Some(path) => crate::commands::topology::read_topology_file(path)?,
+        None => metadata_store
+            .lock()
+            .unwrap()
+            .get_topology()
+            .ok_or("未找到拓扑数据，请先加载拓扑或指定拓扑文件")?,
+    };
+    let sweep_settings = SweepSettings {
+        sign_convention: settings.sign_convention(),
+        random_seed: settings.random_seed(),
+    };
+    sweep::run_sweep(&app, &simulations, spec, topology, sweep_settings).await
+}
+
+/// 读取已完成参数扫描的结果索引
+#[tauri::command]
+pub async fn get_sweep_index(output_dir: String) -> Result<SweepIndex, String> {
+    sweep::load_index(&output_dir)
+}
+
 #[tauri::command]
 pub async fn start_simulation(
     app: AppHandle,
     config: SimulationConfig,
-    engine: State<'_, Arc<SimulationEngine>>,
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
     metadata_store: State<'_, Mutex<DeviceMetadataStore>>,
 ) -> Result<(), String> {
-    // 从元数据存储获取拓扑数据
-    let topology = {
-        let store = metadata_store.lock().unwrap();
-        store.get_topology()
-    };
+    let engine = simulations.get(simulation_id.as_deref())?;
+    // 从元数据存储获取拓扑数据并设置到仿真引擎
+    sync_topology(&engine, simulation_id.as_deref(), &metadata_store).await?;
+
+    apply_simulation_config(&engine, &config)?;
     
-    if let Some(topology) = topology {
-        // 设置拓扑数据到仿真引擎
+    // 启动仿真
+    engine.start(Some(app), config.calculation_interval_ms).await
+}
+
+/// 普通启动使用默认运行参数（逐步落库、内核默认求解参数），时间倍率按请求（默认实时）
+fn apply_simulation_config(engine: &SimulationEngine, config: &SimulationConfig) -> Result<(), String> {
+    engine.set_remote_control_enabled(config.remote_control_enabled);
+    let time_scale = config.time_scale.unwrap_or(1.0);
+    if !time_scale.is_finite() || time_scale <= 0.0 {
+        return Err("时间倍率必须大于 0".to_string());
+    }
+    let consumer_rates = config.consumer_rates.clone().unwrap_or_default();
+    consumer_rates.validate()?;
+    if let Some(adaptive) = &config.adaptive_interval {
+        adaptive.validate()?;
+    }
+    if config.sim_start_epoch.is_some_and(|t| !t.is_finite() || t < 0.0) {
+        return Err("仿真时钟起点无效".to_string());
+    }
+    let daily_rollover = config.daily_rollover.clone().unwrap_or_default();
+    daily_rollover.validate()?;
+    if config.max_duration_secs.is_some_and(|d| !d.is_finite() || d <= 0.0) {
+        return Err("最大仿真时长必须大于 0".to_string());
+    }
+    if config.max_steps == Some(0) {
+        return Err("最大步数必须大于 0".to_string());
+    }
+    engine.set_run_options(RunOptions {
+        time_scale,
+        consumer_rates,
+        adaptive_interval: config.adaptive_interval.clone(),
+        sim_start_epoch: config.sim_start_epoch,
+        daily_rollover,
+        max_duration_secs: config.max_duration_secs,
+        max_steps: config.max_steps,
+        ..RunOptions::default()
+    });
+    Ok(())
+}
+
+/// 局部仿真：只仿真分组（设备属性 group）对应的子网，切断处按 boundary_config 接入边界等值
+/// （默认第一个边界母线接 1.0 pu 平衡节点，其余边界母线零注入）。config 未提供时按 1 秒步长实时运行；
+/// 界面拓扑保持完整，下次普通启动即恢复全网仿真
+#[tauri::command]
+pub async fn start_partial_simulation(
+    app: AppHandle,
+    group_id: String,
+    boundary_config: Option<BoundaryConfig>,
+    config: Option<SimulationConfig>,
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
+    metadata_store: State<'_, Mutex<DeviceMetadataStore>>,
+) -> Result<PartialTopology, String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
+    let full = metadata_store.lock().unwrap().get_topology();
+    let full = full.ok_or("未找到拓扑数据，请先加载拓扑")?;
+    let mut partial = partial_topology::build(&full, &group_id, &boundary_config.unwrap_or_default())?;
+    let config = config.unwrap_or(SimulationConfig {
+        calculation_interval_ms: 1000,
+        remote_control_enabled: true,
+        time_scale: None,
+        consumer_rates: None,
+        adaptive_interval: None,
+        sim_start_epoch: None,
+        daily_rollover: None,
+        max_duration_secs: None,
+        max_steps: None,
+    });
+    apply_simulation_config(&engine, &config)?;
+    if let Some(topology) = partial.topology.take() {
         engine.set_topology(topology).await;
+    }
+    engine.start(Some(app), config.calculation_interval_ms).await?;
+    // 定功率等值按手动设定下发（内核在设置拓扑时清空设备模式，须在启动后设置）
+    for equivalent in partial.boundary.iter().filter(|e| e.mode == BoundaryMode::Injection) {
+        engine.set_device_mode(equivalent.device_id.clone(), "manual".to_string()).await?;
+        engine
+            .set_device_manual_setpoint(equivalent.device_id.clone(), equivalent.p_kw, equivalent.q_kvar)
+            .await?;
+    }
+    Ok(partial)
+}
+
+/// 按计算预设启动仿真：应用步长、时间倍率、求解参数与落库粒度，按需自动启动全部 Modbus 服务器
+#[tauri::command]
+pub async fn start_simulation_with_preset(
+    app: AppHandle,
+    preset_name: String,
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
+    metadata_store: State<'_, Mutex<DeviceMetadataStore>>,
+    settings: State<'_, SettingsStore>,
+    modbus_service: State<'_, ModbusService>,
+) -> Result<(), String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
+    let preset = settings
+        .get_preset(&preset_name)
+        .ok_or_else(|| format!("预设不存在: {}", preset_name))?;
+    preset.validate()?;
+    sync_topology(&engine, simulation_id.as_deref(), &metadata_store).await?;
+    engine.set_remote_control_enabled(preset.remote_control_enabled);
+    engine.set_run_options(preset.run_options());
+    engine.start(Some(app), preset.calculation_interval_ms).await?;
+    // Modbus 服务器只对接主仿真
+    if preset.auto_start_modbus && SimulationManager::is_default(simulation_id.as_deref()) {
+        crate::commands::modbus::start_all_modbus_servers(metadata_store, modbus_service, settings).await?;
+    }
+    Ok(())
+}
+
+/// 预加载拓扑（项目打开时调用）：在内核中提前构建网络并试算，使随后的启动可在 1 秒内开始步进
+#[tauri::command]
+pub async fn preload_topology(
+    warm_up: Option<bool>,
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
+    metadata_store: State<'_, Mutex<DeviceMetadataStore>>,
+) -> Result<TopologyPreloadResult, String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
+    sync_topology(&engine, simulation_id.as_deref(), &metadata_store).await?;
+    engine.preload_topology(warm_up.unwrap_or(true)).await
+}
+
+/// 可选的潮流求解内核及其能力（三相不平衡、无功、生效的求解参数等），以及该仿真实例当前选用的内核（降级内核生效时报告降级内核）
+#[tauri::command]
+pub async fn get_power_kernels(
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
+) -> Result<PowerKernelCatalog, String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
+    let kernels = KernelFactory::query_capabilities(engine.kernel()).await?;
+    let selected = if engine.kernel().is_fallback() { KernelType::BuiltinRadial } else { engine.power_kernel() };
+    Ok(PowerKernelCatalog { kernels, selected })
+}
+
+/// 为仿真实例选择潮流求解内核（须为内核进程报告可用的内核），下次启动仿真时生效；主仿真的选择写入设置
+#[tauri::command]
+pub async fn set_power_kernel(
+    kernel_type: KernelType,
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
+    settings: State<'_, SettingsStore>,
+) -> Result<(), String> {
+    if kernel_type == KernelType::BuiltinRadial {
+        return Err("内置辐射网潮流为 Python 内核不可用时的降级内核，自动启用，不能手动选择".to_string());
+    }
+    let engine = simulations.get(simulation_id.as_deref())?;
+    KernelFactory::ensure_available(engine.kernel(), kernel_type).await?;
+    if SimulationManager::is_default(simulation_id.as_deref()) {
+        settings.set_power_kernel(kernel_type)?;
+    }
+    engine.set_power_kernel(kernel_type);
+    Ok(())
+}
+
+/// 校验内核模型与界面拓扑是否一致（热编辑、内核重连或重启后使用），返回逐项差异
+#[tauri::command]
+pub async fn verify_kernel_sync(
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
+    metadata_store: State<'_, Mutex<DeviceMetadataStore>>,
+) -> Result<KernelSyncReport, String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
+    let ui_topology = metadata_store.lock().unwrap().get_topology();
+    let topology = if SimulationManager::is_default(simulation_id.as_deref()) {
+        ui_topology
     } else {
-        return Err("未找到拓扑数据，请先加载拓扑".to_string());
+        engine.get_topology().await
+    };
+    engine.verify_kernel_sync(topology).await
+}
+
+/// 热重载 Python 内核（开发时修改内核代码后使用，无需重启应用）：重启内核进程并重新下发拓扑与设备工作模式，
+/// 运行或暂停中的仿真随之恢复；返回恢复的设备模式数
+#[tauri::command]
+pub async fn reload_python_kernel(
+    app: AppHandle,
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
+) -> Result<usize, String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
+    engine.reload_kernel(&app).await
+}
+
+/// 内核请求的性能统计（各方法调用次数、耗时分位数与失败率，按累计耗时降序）；reset 为 true 时读取后清零
+#[tauri::command]
+pub async fn get_bridge_metrics(
+    simulation_id: Option<String>,
+    reset: Option<bool>,
+    simulations: State<'_, SimulationManager>,
+) -> Result<BridgeMetrics, String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
+    let metrics = engine.kernel().metrics();
+    if reset.unwrap_or(false) {
+        engine.kernel().reset_metrics();
     }
+    Ok(metrics)
+}
 
-    engine.set_remote_control_enabled(config.remote_control_enabled);
-    
-    // 启动仿真
-    engine.start(Some(app), config.calculation_interval_ms).await
+/// 启动仿真前的 Python 环境自检：定位内核（或远程端点）、检查解释器与 pandapower/numpy 等依赖版本，返回逐项诊断与修复建议
+#[tauri::command]
+pub async fn check_python_environment(
+    app: AppHandle,
+    settings: State<'_, SettingsStore>,
+    engine: State<'_, Arc<SimulationEngine>>,
+) -> Result<EnvironmentReport, String> {
+    let endpoint = KernelEndpoint::resolve(settings.kernel_endpoint().as_deref());
+    Ok(python_env::check(&app, endpoint, engine.kernel()).await)
+}
+
+/// 查找最近一次未正常结束（应用崩溃或被强制关闭）的仿真；仿真运行中不提示
+#[tauri::command]
+pub async fn get_interrupted_run(
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
+) -> Result<Option<InterruptedRun>, String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
+    if engine.get_status().await.state != SimulationState::Stopped {
+        return Ok(None);
+    }
+    let dir = std::env::current_dir().map_err(|e| format!("获取工作目录失败: {}", e))?;
+    Ok(run_recovery::find_interrupted_run(&dir, None))
+}
+
+/// 恢复中断的仿真：重新加载库中的拓扑快照，恢复储能 SOC 与电表电量，继续向同一数据库追加数据；未指定路径时恢复最近一次
+#[tauri::command]
+pub async fn resume_interrupted_run(
+    app: AppHandle,
+    db_path: Option<String>,
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
+    metadata_store: State<'_, Mutex<DeviceMetadataStore>>,
+) -> Result<(), String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
+    let db_path = match db_path {
+        Some(p) => p,
+        None => {
+            let dir = std::env::current_dir().map_err(|e| format!("获取工作目录失败: {}", e))?;
+            run_recovery::find_interrupted_run(&dir, None)
+                .ok_or("没有可恢复的中断仿真")?
+                .db_path
+        }
+    };
+    let is_default = SimulationManager::is_default(simulation_id.as_deref());
+    let topology = engine.resume_interrupted_run(Some(app), &db_path).await?;
+    if is_default {
+        metadata_store.lock().unwrap().set_topology(topology);
+    }
+    Ok(())
+}
+
+/// 将运行中的仿真保存为检查点文件（拓扑、设备模式、储能状态、电量计数与内核状态），关闭应用后可恢复
+#[tauri::command]
+pub async fn save_checkpoint(
+    app: AppHandle,
+    path: String,
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
+) -> Result<CheckpointFileInfo, String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
+    engine.save_checkpoint(Some(&app), &path).await
+}
+
+/// 读取检查点文件摘要（恢复前确认）
+#[tauri::command]
+pub async fn get_checkpoint_info(path: String) -> Result<CheckpointFileInfo, String> {
+    Ok(run_recovery::read_checkpoint_file(std::path::Path::new(&path))?.info(&path))
+}
+
+/// 从检查点文件恢复仿真并同步界面拓扑
+#[tauri::command]
+pub async fn restore_checkpoint(
+    app: AppHandle,
+    path: String,
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
+    metadata_store: State<'_, Mutex<DeviceMetadataStore>>,
+) -> Result<(), String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
+    let is_default = SimulationManager::is_default(simulation_id.as_deref());
+    let topology = engine.restore_checkpoint(Some(app), &path).await?;
+    if is_default {
+        metadata_store.lock().unwrap().set_topology(topology);
+    }
+    Ok(())
+}
+
+/// 放弃恢复：标记该仿真库不再提示
+#[tauri::command]
+pub async fn dismiss_interrupted_run(db_path: String) -> Result<(), String> {
+    let db = Database::new(Some(std::path::Path::new(&db_path)))
+        .map_err(|e| format!("打开仿真数据库失败: {}", e))?;
+    run_recovery::set_run_status(&db, RunStatus::Dismissed);
+    Ok(())
 }
 
 #[tauri::command]
 pub async fn stop_simulation(
-    engine: State<'_, Arc<SimulationEngine>>,
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
+    tail_manager: State<'_, SeriesTailManager>,
 ) -> Result<(), String> {
-    engine.stop().await
+    let engine = simulations.get(simulation_id.as_deref())?;
+    engine.stop().await?;
+    // 实时尾随只读取主仿真的数据库，主仿真停止后结束全部订阅
+    if SimulationManager::is_default(simulation_id.as_deref()) {
+        tail_manager.stop_all();
+    }
+    Ok(())
 }
 
 #[tauri::command]
 pub async fn pause_simulation(
-    engine: State<'_, Arc<SimulationEngine>>,
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
 ) -> Result<(), String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
     engine.pause().await
 }
 
 #[tauri::command]
 pub async fn resume_simulation(
-    engine: State<'_, Arc<SimulationEngine>>,
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
 ) -> Result<(), String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
     engine.resume().await
 }
 
+/// 单步调试（仿真暂停中）：执行一次 simulation.perform_calculation 并完整处理结果，完成后保持暂停，返回该步后的仿真状态
+#[tauri::command]
+pub async fn step_simulation(
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
+) -> Result<SimulationStatus, String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
+    engine.step_once().await?;
+    Ok(engine.get_status().await)
+}
+
+/// 登记或更新条件断点（id 为空时自动分配）：每步评估，条件由不满足变为满足时暂停仿真并推送 simulation-breakpoint-hit
+#[tauri::command]
+pub async fn set_breakpoint(
+    breakpoint: Breakpoint,
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
+) -> Result<Breakpoint, String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
+    engine.set_breakpoint(breakpoint).await
+}
+
+/// 删除条件断点；id 为空时清空全部
+#[tauri::command]
+pub async fn remove_breakpoint(
+    id: Option<String>,
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
+) -> Result<(), String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
+    match id {
+        Some(id) if !engine.remove_breakpoint(&id) => Err(format!("断点不存在: {}", id)),
+        Some(_) => Ok(()),
+        None => {
+            engine.clear_breakpoints();
+            Ok(())
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn list_breakpoints(
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
+) -> Result<Vec<Breakpoint>, String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
+    Ok(engine.list_breakpoints())
+}
+
 #[tauri::command]
 pub async fn get_simulation_status(
-    engine: State<'_, Arc<SimulationEngine>>,
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
 ) -> Result<SimulationStatus, String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
     Ok(engine.get_status().await)
 }
 
@@ -82,9 +566,17 @@ pub async fn get_simulation_status(
 pub async fn set_device_mode(
     device_id: String,
     mode: String,
-    engine: State<'_, Arc<SimulationEngine>>,
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
+    settings: State<'_, SettingsStore>,
 ) -> Result<(), String> {
-    engine.set_device_mode(device_id, mode).await
+    let engine = simulations.get(simulation_id.as_deref())?;
+    engine.set_device_mode(device_id.clone(), mode.clone()).await?;
+    // 主仿真的设定持久化，下次启动仿真时自动恢复
+    if SimulationManager::is_default(simulation_id.as_deref()) {
+        settings.update_device_control(&device_id, |c| c.mode = Some(mode))?;
+    }
+    Ok(())
 }
 
 #[tauri::command]
@@ -92,9 +584,30 @@ pub async fn set_device_random_config(
     device_id: String,
     min_power: f64,
     max_power: f64,
-    engine: State<'_, Arc<SimulationEngine>>,
+    profile: Option<RandomProfile>,
+    seed: Option<u64>,
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
+    settings: State<'_, SettingsStore>,
 ) -> Result<(), String> {
-    engine.set_device_random_config(device_id, min_power, max_power).await
+    let engine = simulations.get(simulation_id.as_deref())?;
+    engine.set_device_random_config(device_id.clone(), min_power, max_power, profile.clone(), seed).await?;
+    if SimulationManager::is_default(simulation_id.as_deref()) {
+        settings.update_device_control(&device_id, |c| {
+            c.random = Some(StoredRandomConfig { min_power, max_power, profile, seed });
+        })?;
+    }
+    Ok(())
+}
+
+/// 获取各设备当前的随机数据生成模型（仅含 Rust 端生成的设备）
+#[tauri::command]
+pub async fn get_device_random_profiles(
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
+) -> Result<HashMap<String, RandomProfile>, String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
+    Ok(engine.get_random_profiles())
 }
 
 #[tauri::command]
@@ -102,19 +615,45 @@ pub async fn set_device_manual_setpoint(
     device_id: String,
     active_power: f64,
     reactive_power: f64,
-    engine: State<'_, Arc<SimulationEngine>>,
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
+    settings: State<'_, SettingsStore>,
 ) -> Result<(), String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
     engine
-        .set_device_manual_setpoint(device_id, active_power, reactive_power)
-        .await
+        .set_device_manual_setpoint(device_id.clone(), active_power, reactive_power)
+        .await?;
+    if SimulationManager::is_default(simulation_id.as_deref()) {
+        settings.update_device_control(&device_id, |c| {
+            c.manual_setpoint = Some(StoredManualSetpoint { active_power, reactive_power });
+        })?;
+    }
+    Ok(())
+}
+
+/// 已保存的设备控制状态（工作模式、随机配置、手动设定），每次启动主仿真时自动重新下发
+#[tauri::command]
+pub fn get_stored_device_controls(settings: State<'_, SettingsStore>) -> HashMap<String, StoredDeviceControl> {
+    settings.device_controls()
+}
+
+/// 清除指定设备（未指定时为全部）已保存的控制状态，返回清除的设备数；不影响当前运行中的设定
+#[tauri::command]
+pub fn clear_stored_device_controls(
+    device_ids: Option<Vec<String>>,
+    settings: State<'_, SettingsStore>,
+) -> Result<usize, String> {
+    settings.clear_device_controls(device_ids.as_deref())
 }
 
 #[tauri::command]
 pub async fn set_device_historical_config(
     device_id: String,
     config: serde_json::Value,
-    engine: State<'_, Arc<SimulationEngine>>,
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
 ) -> Result<(), String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
     engine.set_device_historical_config(device_id, config).await
 }
 
@@ -122,23 +661,222 @@ pub async fn set_device_historical_config(
 pub async fn set_device_sim_params(
     device_id: String,
     params: serde_json::Value,
-    engine: State<'_, Arc<SimulationEngine>>,
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
 ) -> Result<(), String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
     engine.set_device_sim_params(device_id, params).await
 }
 
+/// 设置设备传感器上报延迟（测量值滞后显示与落库）与执行器响应延迟（指令滞后生效），单位毫秒
+#[tauri::command]
+pub async fn set_device_delays(
+    device_id: String,
+    sensor_delay_ms: f64,
+    actuator_delay_ms: f64,
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
+) -> Result<(), String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
+    engine.set_device_delays(device_id, sensor_delay_ms, actuator_delay_ms).await
+}
+
+/// 设置光伏无功控制模式（固定功率因数 / Q(U) 曲线 / 固定无功）：写入设备属性（随拓扑保存到工程文件），
+/// 推送到仿真内核，并同步 Modbus 无功控制模式寄存器
+#[tauri::command]
+pub async fn set_pv_reactive_control(
+    device_id: String,
+    config: ReactiveControlConfig,
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
+    metadata_store: State<'_, Mutex<DeviceMetadataStore>>,
+    modbus_service: State<'_, ModbusService>,
+) -> Result<(), String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
+    config.validate()?;
+    if !SimulationManager::is_default(simulation_id.as_deref()) {
+        // 独立实例：只推送到其内核，不修改界面拓扑与 Modbus 寄存器
+        return engine.set_device_reactive_control(device_id, config).await;
+    }
+    let properties = {
+        let store = metadata_store.lock().unwrap();
+        let mut device = store
+            .get_device(&device_id)
+            .ok_or_else(|| format!("Device {} not found", device_id))?;
+        if device.device_type != DeviceType::Pv {
+            return Err(format!("设备 {} 不是光伏，不支持无功控制模式", device_id));
+        }
+        device.properties.extend(config.to_properties());
+        let properties = device.properties.clone();
+        store.update_device(device)?;
+        properties
+    };
+    engine.set_device_reactive_control(device_id.clone(), config).await?;
+    modbus_service
+        .update_device_immutable_registers(&device_id, "static_generator", &properties)
+        .await;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_pv_reactive_control(
+    device_id: String,
+    metadata_store: State<'_, Mutex<DeviceMetadataStore>>,
+) -> Result<ReactiveControlConfig, String> {
+    let store = metadata_store.lock().unwrap();
+    let device = store
+        .get_device(&device_id)
+        .ok_or_else(|| format!("Device {} not found", device_id))?;
+    Ok(ReactiveControlConfig::from_properties(&device.properties))
+}
+
+/// 设置光伏电网支撑功能（volt-var 无功曲线 / volt-watt 有功上限曲线）：写入设备属性（随拓扑保存到工程文件）
+/// 并同步到仿真引擎，运行中下一步按并网点电压生效
+#[tauri::command]
+pub async fn set_pv_grid_support(
+    device_id: String,
+    config: GridSupportConfig,
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
+    metadata_store: State<'_, Mutex<DeviceMetadataStore>>,
+) -> Result<(), String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
+    config.validate()?;
+    if !SimulationManager::is_default(simulation_id.as_deref()) {
+        return engine.set_device_grid_support(device_id, config).await;
+    }
+    {
+        let store = metadata_store.lock().unwrap();
+        let mut device = store
+            .get_device(&device_id)
+            .ok_or_else(|| format!("Device {} not found", device_id))?;
+        if device.device_type != DeviceType::Pv {
+            return Err(format!("设备 {} 不是光伏，不支持电网支撑功能", device_id));
+        }
+        device.properties.insert(
+            "grid_support".to_string(),
+            serde_json::to_value(&config).map_err(|e| e.to_string())?,
+        );
+        store.update_device(device)?;
+    }
+    engine.set_device_grid_support(device_id, config).await
+}
+
+#[tauri::command]
+pub async fn get_pv_grid_support(
+    device_id: String,
+    metadata_store: State<'_, Mutex<DeviceMetadataStore>>,
+) -> Result<GridSupportConfig, String> {
+    let store = metadata_store.lock().unwrap();
+    let device = store
+        .get_device(&device_id)
+        .ok_or_else(|| format!("Device {} not found", device_id))?;
+    Ok(GridSupportConfig::from_properties(&device.properties))
+}
+
+/// 绑定气象数据源（本地 CSV 或 HTTP JSON 接口），驱动 solar_model / weather_model 模式设备；
+/// 运行中绑定的下一步生效，并记录到本次运行的仿真库
+#[tauri::command]
+pub async fn bind_weather_source(
+    source: WeatherSourceSpec,
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
+) -> Result<WeatherBinding, String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
+    engine.bind_weather_source(source).await
+}
+
+/// 解除气象数据源绑定：辐照模型回到晴空辐照，气象模型设备保持最后下发的功率
+#[tauri::command]
+pub async fn unbind_weather_source(
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
+) -> Result<bool, String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
+    Ok(engine.unbind_weather_source())
+}
+
+#[tauri::command]
+pub async fn get_weather_binding(
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
+) -> Result<Option<WeatherBinding>, String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
+    Ok(engine.weather_binding())
+}
+
+/// 设置光伏/储能 P(f) 响应曲线（死区、有意延时），写入设备属性并推送到仿真内核；仅孤岛频率模型激活时生效
+#[tauri::command]
+pub async fn set_device_pf_response(
+    device_id: String,
+    config: PfResponseConfig,
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
+    metadata_store: State<'_, Mutex<DeviceMetadataStore>>,
+) -> Result<(), String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
+    config.validate()?;
+    if !SimulationManager::is_default(simulation_id.as_deref()) {
+        return engine.set_device_pf_response(device_id, config).await;
+    }
+    {
+        let store = metadata_store.lock().unwrap();
+        let mut device = store
+            .get_device(&device_id)
+            .ok_or_else(|| format!("Device {} not found", device_id))?;
+        if !matches!(device.device_type, DeviceType::Pv | DeviceType::Storage) {
+            return Err(format!("设备 {} 不支持 P(f) 响应（仅光伏/储能）", device_id));
+        }
+        device.properties.insert(
+            "pf_response".to_string(),
+            serde_json::to_value(&config).map_err(|e| e.to_string())?,
+        );
+        store.update_device(device)?;
+    }
+    engine.set_device_pf_response(device_id, config).await
+}
+
+#[tauri::command]
+pub async fn get_device_pf_response(
+    device_id: String,
+    metadata_store: State<'_, Mutex<DeviceMetadataStore>>,
+) -> Result<PfResponseConfig, String> {
+    let store = metadata_store.lock().unwrap();
+    let device = store
+        .get_device(&device_id)
+        .ok_or_else(|| format!("Device {} not found", device_id))?;
+    Ok(PfResponseConfig::from_properties(&device.properties))
+}
+
+/// 设置孤岛频率模型：islanded=true 时激活并使用 frequency_hz，false 时恢复额定频率
+#[tauri::command]
+pub async fn set_grid_frequency(
+    frequency_hz: f64,
+    islanded: bool,
+    nominal_hz: Option<f64>,
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
+) -> Result<(), String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
+    engine.set_grid_frequency(frequency_hz, islanded, nominal_hz).await
+}
+
 #[tauri::command]
 pub async fn get_device_data(
     device_id: String,
-    engine: State<'_, Arc<SimulationEngine>>,
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
 ) -> Result<serde_json::Value, String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
     engine.get_device_data(&device_id).await
 }
 
 #[tauri::command]
 pub async fn get_simulation_errors(
-    engine: State<'_, Arc<SimulationEngine>>,
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
 ) -> Result<Vec<SimulationError>, String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
     let status = engine.get_status().await;
     Ok(status.errors)
 }
@@ -146,8 +884,10 @@ pub async fn get_simulation_errors(
 #[tauri::command]
 pub async fn set_remote_control_enabled(
     enabled: bool,
-    engine: State<'_, Arc<SimulationEngine>>,
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
 ) -> Result<(), String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
     engine.set_remote_control_enabled(enabled);
     Ok(())
 }
@@ -156,8 +896,10 @@ pub async fn set_remote_control_enabled(
 pub async fn set_device_remote_control_enabled(
     device_id: String,
     enabled: bool,
-    engine: State<'_, Arc<SimulationEngine>>,
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
 ) -> Result<(), String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
     engine.set_device_remote_control_enabled(device_id, enabled).await;
     Ok(())
 }
@@ -167,24 +909,108 @@ pub async fn set_device_remote_control_enabled(
 pub async fn update_device_properties_for_simulation(
     device_id: String,
     properties: serde_json::Value,
-    engine: State<'_, Arc<SimulationEngine>>,
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
     modbus_service: State<'_, crate::services::modbus::ModbusService>,
 ) -> Result<(), String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
     let _mapping = modbus_service.get_device_mapping(&device_id);
     engine
-        .update_device_properties_for_simulation(device_id, properties)
+        .update_device_properties_for_simulation(device_id, properties, "command")
         .await
 }
 
+/// 故障注入（需仿真运行中）：outage 使线路/变压器停运，short_circuit 使母线（或线路首端、变压器高压侧母线）经接地电阻短路，
+/// 默认接地电阻 5 Ω；duration_s 为持续仿真秒数，到期自动恢复。注入与恢复通过 fault-state-changed 推送
+#[tauri::command]
+pub async fn inject_fault(
+    app: AppHandle,
+    device_id: String,
+    fault_type: FaultType,
+    duration_s: Option<f64>,
+    fault_resistance_ohm: Option<f64>,
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
+) -> Result<ActiveFault, String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
+    engine
+        .inject_fault(Some(&app), &device_id, fault_type, duration_s, fault_resistance_ohm)
+        .await
+}
+
+/// 手动清除故障并恢复网络
+#[tauri::command]
+pub async fn clear_fault(
+    app: AppHandle,
+    device_id: String,
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
+) -> Result<FaultRecord, String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
+    engine.clear_fault(Some(&app), &device_id).await
+}
+
+/// 手动标记突发落库（需仿真运行中且运行参数配置了突发落库）：下一步起按事件前后窗口逐步落库
+#[tauri::command]
+pub async fn trigger_burst_logging(
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
+) -> Result<(), String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
+    if engine.get_status().await.state != SimulationState::Running {
+        return Err("仿真未运行".to_string());
+    }
+    if engine.get_run_options().consumer_rates.burst.is_none() {
+        return Err("当前运行参数未配置突发落库".to_string());
+    }
+    engine.request_burst("manual");
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_active_faults(
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
+) -> Result<Vec<ActiveFault>, String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
+    Ok(engine.active_faults())
+}
+
+/// 本轮仿真已结束的故障记录
+#[tauri::command]
+pub async fn get_fault_history(
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
+) -> Result<Vec<FaultRecord>, String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
+    Ok(engine.fault_history())
+}
+
+/// 运行中设备属性偏移：对比启动时加载的拓扑与当前生效属性，并给出每项修改来源（modbus/command/switch）
+#[tauri::command]
+pub async fn get_effective_property_drift(
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
+) -> Result<Vec<DevicePropertyDrift>, String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
+    Ok(engine.get_effective_property_drift().await)
+}
+
 /// 更新开关状态（同时更新 Python 仿真、Rust 元数据与拓扑，保证再次打开面板时显示实际状态）
 /// 即使 Python 侧调用失败（如仿真未启动），也会更新 Rust 元数据，确保设备树始终显示正确的开关状态。
 #[tauri::command]
 pub async fn update_switch_state(
     device_id: String,
     is_closed: bool,
-    engine: State<'_, Arc<SimulationEngine>>,
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
     metadata_store: State<'_, Mutex<DeviceMetadataStore>>,
 ) -> Result<(), String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
+    if !SimulationManager::is_default(simulation_id.as_deref()) {
+        // 独立实例没有界面设备树，直接切换其内核中的开关
+        return engine.update_switch_state(device_id, is_closed).await;
+    }
     // 先更新 Rust 元数据（无论 Python 侧是否成功，设备树都能正确显示开关状态）
     // 【修复】将第一次锁获取放入独立作用域，确保 MutexGuard 在第二次加锁前释放，
     // 避免 Rust 2021 edition 中 if-let 临时变量生命周期延伸导致的同线程死锁。
@@ -249,3 +1075,145 @@ pub async fn get_historical_time_range(
         }
     }
 }
+
+/// 清理已关闭窗口的订阅
+fn prune_closed_windows(app: &AppHandle, hub: &WindowEventHub) {
+    let alive: HashSet<String> = app.webview_windows().into_keys().collect();
+    hub.retain_windows(&alive);
+}
+
+/// 登记调用窗口的事件订阅（按事件名与设备过滤）；同一窗口再次调用覆盖原订阅
+#[tauri::command]
+pub async fn subscribe_window_events(
+    events: Option<Vec<String>>,
+    device_ids: Option<Vec<String>>,
+    window: tauri::Window,
+    app: AppHandle,
+    hub: State<'_, Arc<WindowEventHub>>,
+) -> Result<(), String> {
+    prune_closed_windows(&app, &hub);
+    hub.subscribe(
+        window.label(),
+        WindowSubscription {
+            events: events.unwrap_or_default(),
+            device_ids,
+        },
+    );
+    Ok(())
+}
+
+/// 取消调用窗口的订阅，恢复接收全部事件
+#[tauri::command]
+pub async fn unsubscribe_window_events(
+    window: tauri::Window,
+    hub: State<'_, Arc<WindowEventHub>>,
+) -> Result<bool, String> {
+    Ok(hub.unsubscribe(window.label()))
+}
+
+#[tauri::command]
+pub async fn list_window_subscriptions(
+    app: AppHandle,
+    hub: State<'_, Arc<WindowEventHub>>,
+) -> Result<HashMap<String, WindowSubscription>, String> {
+    prune_closed_windows(&app, &hub);
+    Ok(hub.subscriptions())
+}
+
+/// 一致系统快照：最近一次提交的计算步（结果、功率缓存与储能状态同属一步）及仿真状态、活动告警、设备模式；
+/// 新窗口据此初始化，之后只处理 seq 大于快照的 state-step-committed 之后的事件
+#[tauri::command]
+pub async fn get_system_snapshot(
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
+    hub: State<'_, Arc<WindowEventHub>>,
+) -> Result<SystemSnapshot, String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
+    Ok(SystemSnapshot {
+        status: engine.get_status().await,
+        // 计算步提交记录只来自主仿真
+        step: if SimulationManager::is_default(simulation_id.as_deref()) { hub.last_step() } else { None },
+        active_alerts: engine.get_active_limit_alerts(),
+        device_modes: engine.get_device_modes().await,
+    })
+}
+
+/// 运行中清零所选累计量（电表电量、储能日/累计电量、光伏发电量、设备电量、KPI），无需重启仿真；
+/// groups 为空表示全部分组，device_ids 为空表示全部设备；每组清零写入审计日志
+#[tauri::command]
+pub async fn reset_counters(
+    groups: Option<Vec<CounterGroup>>,
+    device_ids: Option<Vec<String>>,
+    simulation_id: Option<String>,
+    simulations: State<'_, SimulationManager>,
+    modbus_service: State<'_, ModbusService>,
+    auth: State<'_, Arc<ApiAuth>>,
+) -> Result<Vec<CounterResetResult>, String> {
+    let engine = simulations.get(simulation_id.as_deref())?;
+    let groups = match groups {
+        Some(g) if !g.is_empty() => g,
+        _ => CounterGroup::all(),
+    };
+    let device_ids: Option<HashSet<String>> = device_ids.filter(|ids| !ids.is_empty()).map(|ids| ids.into_iter().collect());
+    let is_default = SimulationManager::is_default(simulation_id.as_deref());
+    let mut results = Vec::new();
+    for group in groups {
+        let mut cleared = engine.reset_counters(group, device_ids.as_ref());
+        // Modbus 寄存器只对应主仿真
+        let register_ids = if is_default {
+            modbus_service.reset_counter_registers(group, device_ids.as_ref()).await
+        } else {
+            Vec::new()
+        };
+        for id in register_ids {
+            if !cleared.contains(&id) {
+                cleared.push(id);
+            }
+        }
+        cleared.sort();
+        let scope = match &device_ids {
+            Some(ids) => {
+                let mut ids: Vec<&str> = ids.iter().map(|s| s.as_str()).collect();
+                ids.sort();
+                ids.join(",")
+            }
+            None => "全部设备".to_string(),
+        };
+        auth.record_local(
+            "reset_counters",
+            Some(format!("{}：{}（清零 {} 台）", group.as_str(), scope, cleared.len())),
+        );
+        results.push(CounterResetResult { group, device_ids: cleared });
+    }
+    Ok(results)
+}
+
+// ====== 历史仿真库回放 ======
+
+/// 回放历史仿真库：按录制节奏（speed 倍速）推送设备数据并驱动运行中的 Modbus 服务器；主仿真运行中不可回放
+#[tauri::command]
+pub async fn start_replay(
+    app: AppHandle,
+    options: ReplayOptions,
+    replay: State<'_, ReplayService>,
+    engine: State<'_, Arc<SimulationEngine>>,
+    settings: State<'_, SettingsStore>,
+    metadata_store: State<'_, Mutex<DeviceMetadataStore>>,
+) -> Result<ReplayStatus, String> {
+    if engine.get_status().await.state != SimulationState::Stopped {
+        return Err("仿真运行中，请先停止仿真再回放".to_string());
+    }
+    let topology = metadata_store.lock().unwrap().get_topology();
+    replay.start(app, options, topology, settings.sign_convention())
+}
+
+/// 停止回放（Modbus 寄存器保持最后一帧的值）
+#[tauri::command]
+pub async fn stop_replay(app: AppHandle, replay: State<'_, ReplayService>) -> Result<ReplayStatus, String> {
+    Ok(replay.stop(&app))
+}
+
+#[tauri::command]
+pub async fn get_replay_status(replay: State<'_, ReplayService>) -> Result<ReplayStatus, String> {
+    Ok(replay.status())
+}
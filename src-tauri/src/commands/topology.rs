@@ -66,7 +66,7 @@ pub struct LoadAndValidateResult {
     pub validation: ValidationResult,
 }
 
-fn parse_device_type(s: &str) -> Result<DeviceType, String> {
+pub(crate) fn parse_device_type(s: &str) -> Result<DeviceType, String> {
     match s.to_lowercase().as_str() {
         // 支持前端使用的类型名称
         "bus" | "node" => Ok(DeviceType::Node),
@@ -1293,3 +1293,209 @@ pub async fn load_and_validate_topology(
         validation,
     })
 }
+
+/// 读取拓扑文件为内部 Topology（新格式优先，兼容旧格式），不修改元数据仓库与仿真引擎
+pub(crate) fn read_topology_file(path: &str) -> Result<Topology, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("读取拓扑文件失败 {}: {}", path, e))?;
+    if let Ok(topology) = serde_json::from_str::<Topology>(&content) {
+        return Ok(topology);
+    }
+    let data = try_convert_legacy_format(&content)
+        .ok_or_else(|| format!("无法解析拓扑文件：{}", path))?;
+    convert_topology_data(data)
+}
+
+/// 快照潮流负荷假设：各类设备功率取额定功率的比例，None 使用内核默认（负荷/充电桩 1.0，光伏/储能 0）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SnapshotLoadAssumptions {
+    pub load_factor: Option<f64>,
+    pub charger_factor: Option<f64>,
+    pub pv_factor: Option<f64>,
+    /// 储能功率比例，正=充电
+    pub storage_factor: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopologySnapshotSummary {
+    pub path: String,
+    pub converged: bool,
+    pub errors: Vec<crate::domain::simulation::SimulationError>,
+}
+
+/// 对比表的一行：按名称对齐，values 与 topologies 顺序一致，该方案中不存在该元件时为 None
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComparisonRow {
+    pub name: String,
+    /// "bus" | "line" | "transformer"
+    pub element_type: String,
+    pub values: Vec<Option<f64>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopologyComparisonResult {
+    pub topologies: Vec<TopologySnapshotSummary>,
+    /// 母线电压（pu）
+    pub bus_voltages: Vec<ComparisonRow>,
+    /// 线路/变压器负载率（%）
+    pub branch_loadings: Vec<ComparisonRow>,
+}
+
+/// 多拓扑方案对比：按同一负荷假设对每个拓扑文件执行一次潮流，返回并列的母线电压与支路负载率
+#[tauri::command]
+pub async fn compare_topology_powerflow(
+    paths: Vec<String>,
+    assumptions: Option<SnapshotLoadAssumptions>,
+    engine: State<'_, std::sync::Arc<crate::services::simulation_engine::SimulationEngine>>,
+) -> Result<TopologyComparisonResult, String> {
+    if paths.is_empty() {
+        return Err("请至少选择一个拓扑文件".to_string());
+    }
+    let assumptions = serde_json::to_value(assumptions.unwrap_or_default()).map_err(|e| e.to_string())?;
+    let n = paths.len();
+    let mut topologies = Vec::with_capacity(n);
+    // (element_type, name) -> 每个方案的取值
+    let mut buses: std::collections::BTreeMap<(String, String), Vec<Option<f64>>> = Default::default();
+    let mut branches: std::collections::BTreeMap<(String, String), Vec<Option<f64>>> = Default::default();
+
+    for (i, path) in paths.iter().enumerate() {
+        let topology = read_topology_file(path)?;
+        let result = engine.run_snapshot_powerflow(&topology, &assumptions).await?;
+        let errors = result
+            .get("errors")
+            .and_then(|v| v.as_array())
+            .map(|arr| arr.iter().filter_map(crate::domain::simulation::SimulationError::from_kernel_value).collect())
+            .unwrap_or_default();
+        topologies.push(TopologySnapshotSummary {
+            path: path.clone(),
+            converged: result.get("converged").and_then(|v| v.as_bool()).unwrap_or(false),
+            errors,
+        });
+        let devices = result.get("devices");
+        let tables: [(&str, &str, &str); 3] = [
+            ("buses", "bus", "vm_pu"),
+            ("lines", "line", "loading_percent"),
+            ("transformers", "transformer", "loading_percent"),
+        ];
+        for (table, element_type, field) in tables {
+            let Some(rows) = devices.and_then(|d| d.get(table)).and_then(|v| v.as_object()) else { continue };
+            let target = if element_type == "bus" { &mut buses } else { &mut branches };
+            for row in rows.values() {
+                let Some(name) = row.get("name").and_then(|v| v.as_str()) else { continue };
+                let values = target
+                    .entry((element_type.to_string(), name.to_string()))
+                    .or_insert_with(|| vec![None; n]);
+                values[i] = row.get(field).and_then(|v| v.as_f64());
+            }
+        }
+    }
+
+    let to_rows = |m: std::collections::BTreeMap<(String, String), Vec<Option<f64>>>| {
+        m.into_iter()
+            .map(|((element_type, name), values)| ComparisonRow { name, element_type, values })
+            .collect()
+    };
+    Ok(TopologyComparisonResult {
+        topologies,
+        bus_voltages: to_rows(buses),
+        branch_loadings: to_rows(branches),
+    })
+}
+
+/// 大规模网络快照：按电气孤岛拆分当前拓扑，在多个内核进程中并行求解后合并
+#[tauri::command]
+pub async fn run_partitioned_snapshot(
+    app: tauri::AppHandle,
+    assumptions: Option<SnapshotLoadAssumptions>,
+    parallelism: Option<usize>,
+    metadata_store: State<'_, Mutex<DeviceMetadataStore>>,
+    engine: State<'_, std::sync::Arc<crate::services::simulation_engine::SimulationEngine>>,
+    kernel_pool: State<'_, crate::services::kernel_pool::KernelPool>,
+) -> Result<crate::domain::simulation::PartitionedSnapshotResult, String> {
+    let topology = metadata_store.lock().unwrap().get_topology();
+    let topology = topology.ok_or("未找到拓扑数据，请先加载拓扑")?;
+    let assumptions = serde_json::to_value(assumptions.unwrap_or_default()).map_err(|e| e.to_string())?;
+    engine
+        .run_partitioned_snapshot(&app, &kernel_pool, &topology, &assumptions, kernel_pool.parallelism(parallelism))
+        .await
+}
+
+/// 多工况快照（蒙特卡洛抽样或预想工况）：每组负荷假设一个任务，在多个内核进程中并行求解并汇总
+#[tauri::command]
+pub async fn run_snapshot_batch(
+    app: tauri::AppHandle,
+    samples: Vec<SnapshotLoadAssumptions>,
+    parallelism: Option<usize>,
+    metadata_store: State<'_, Mutex<DeviceMetadataStore>>,
+    engine: State<'_, std::sync::Arc<crate::services::simulation_engine::SimulationEngine>>,
+    kernel_pool: State<'_, crate::services::kernel_pool::KernelPool>,
+) -> Result<crate::domain::simulation::SnapshotBatchResult, String> {
+    let topology = metadata_store.lock().unwrap().get_topology();
+    let topology = topology.ok_or("未找到拓扑数据，请先加载拓扑")?;
+    let samples = samples
+        .into_iter()
+        .map(|s| serde_json::to_value(s).map_err(|e| e.to_string()))
+        .collect::<Result<Vec<_>, String>>()?;
+    engine
+        .run_snapshot_batch(&app, &kernel_pool, &topology, &samples, kernel_pool.parallelism(parallelism))
+        .await
+}
+
+/// 潮流参数扫描：按参数网格（如光伏容量 × 负荷功率）修改当前拓扑的设备属性，每个组合一次快照潮流，
+/// 在多个内核进程中并行求解并汇总各组合的电压与负载率极值（时域批量仿真见 run_parameter_sweep）
+#[tauri::command]
+pub async fn run_snapshot_sweep(
+    app: tauri::AppHandle,
+    parameters: Vec<crate::services::sweep::SweepParameter>,
+    assumptions: Option<SnapshotLoadAssumptions>,
+    parallelism: Option<usize>,
+    metadata_store: State<'_, Mutex<DeviceMetadataStore>>,
+    engine: State<'_, std::sync::Arc<crate::services::simulation_engine::SimulationEngine>>,
+    kernel_pool: State<'_, crate::services::kernel_pool::KernelPool>,
+) -> Result<crate::domain::simulation::SnapshotSweepResult, String> {
+    let topology = metadata_store.lock().unwrap().get_topology();
+    let topology = topology.ok_or("未找到拓扑数据，请先加载拓扑")?;
+    let assumptions = serde_json::to_value(assumptions.unwrap_or_default()).map_err(|e| e.to_string())?;
+    engine
+        .run_snapshot_sweep(&app, &kernel_pool, &topology, &parameters, &assumptions, kernel_pool.parallelism(parallelism))
+        .await
+}
+
+/// N-1 预想故障分析：对当前拓扑逐一退出每条线路/变压器执行潮流，返回各预想故障的过载、电压越限与失电母线；
+/// 各预想故障分发到内核池并行求解（parallelism 为 1 时经主内核逐个计算）
+#[tauri::command]
+pub async fn run_contingency_analysis(
+    app: tauri::AppHandle,
+    assumptions: Option<SnapshotLoadAssumptions>,
+    limits: Option<crate::domain::simulation::ContingencyLimits>,
+    parallelism: Option<usize>,
+    metadata_store: State<'_, Mutex<DeviceMetadataStore>>,
+    engine: State<'_, std::sync::Arc<crate::services::simulation_engine::SimulationEngine>>,
+    kernel_pool: State<'_, crate::services::kernel_pool::KernelPool>,
+) -> Result<crate::domain::simulation::ContingencyReport, String> {
+    let topology = metadata_store.lock().unwrap().get_topology();
+    let topology = topology.ok_or("未找到拓扑数据，请先加载拓扑")?;
+    let limits = limits.unwrap_or_default();
+    if limits.min_vm_pu >= limits.max_vm_pu || limits.max_loading_percent <= 0.0 {
+        return Err("预想故障判定限值无效".to_string());
+    }
+    let assumptions = serde_json::to_value(assumptions.unwrap_or_default()).map_err(|e| e.to_string())?;
+    engine
+        .run_contingency_analysis(&app, &kernel_pool, &topology, &assumptions, limits, kernel_pool.parallelism(parallelism))
+        .await
+}
+
+#[tauri::command]
+pub async fn get_kernel_pool_status(
+    kernel_pool: State<'_, crate::services::kernel_pool::KernelPool>,
+) -> Result<crate::services::kernel_pool::KernelPoolStatus, String> {
+    Ok(kernel_pool.status().await)
+}
+
+/// 关闭内核池中的全部内核进程（释放内存），下次并行计算时按需重新拉起
+#[tauri::command]
+pub async fn shutdown_kernel_pool(
+    kernel_pool: State<'_, crate::services::kernel_pool::KernelPool>,
+) -> Result<usize, String> {
+    Ok(kernel_pool.shutdown().await)
+}
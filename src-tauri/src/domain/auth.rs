@@ -0,0 +1,138 @@
+// 外部接口认证：API 令牌（按权限范围授权）与审计日志条目
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+
+/// 令牌权限范围：admin ⊇ control ⊇ read_only
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiScope {
+    /// 只读：读取测量值、寄存器与状态
+    ReadOnly,
+    /// 控制：下发设定值、写寄存器、启停设备
+    Control,
+    /// 管理：令牌管理、配置修改
+    Admin,
+}
+
+impl ApiScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiScope::ReadOnly => "read_only",
+            ApiScope::Control => "control",
+            ApiScope::Admin => "admin",
+        }
+    }
+
+    /// 本权限是否满足 required
+    pub fn allows(&self, required: ApiScope) -> bool {
+        *self >= required
+    }
+}
+
+/// 按客户端地址授予的权限：用于 Modbus 等无法携带令牌的协议。address 为单个 IP 或 CIDR 网段（如 192.168.1.0/24）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PeerScopeRule {
+    pub address: String,
+    pub scope: ApiScope,
+}
+
+impl PeerScopeRule {
+    pub fn validate(&self) -> Result<(), String> {
+        parse_network(&self.address).map(|_| ())
+    }
+
+    /// 客户端 IP 是否落在本规则的网段内（IPv4 映射的 IPv6 地址按 IPv4 比较）
+    pub fn matches(&self, ip: IpAddr) -> bool {
+        let Ok((network, prefix)) = parse_network(&self.address) else { return false };
+        let ip = match ip {
+            IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(ip),
+            v4 => v4,
+        };
+        match (network, ip) {
+            (IpAddr::V4(n), IpAddr::V4(a)) => {
+                let mask = u32::MAX.checked_shl(32 - prefix as u32).unwrap_or(0);
+                u32::from(n) & mask == u32::from(a) & mask
+            }
+            (IpAddr::V6(n), IpAddr::V6(a)) => {
+                let mask = u128::MAX.checked_shl(128 - prefix as u32).unwrap_or(0);
+                u128::from(n) & mask == u128::from(a) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+/// 解析 "IP" 或 "IP/前缀长度"
+fn parse_network(address: &str) -> Result<(IpAddr, u8), String> {
+    let invalid = || format!("地址格式无效（应为 IP 或 CIDR 网段）: {}", address);
+    let (ip, prefix) = match address.trim().split_once('/') {
+        Some((ip, prefix)) => (ip, Some(prefix.parse::<u8>().map_err(|_| invalid())?)),
+        None => (address.trim(), None),
+    };
+    let ip: IpAddr = ip.parse().map_err(|_| invalid())?;
+    let max = if ip.is_ipv4() { 32 } else { 128 };
+    let prefix = prefix.unwrap_or(max);
+    if prefix > max {
+        return Err(invalid());
+    }
+    Ok((ip, prefix))
+}
+
+/// 默认仅本机回环地址享有控制权限（本机联调客户端），其他地址按匿名权限处理
+pub fn default_peer_scopes() -> Vec<PeerScopeRule> {
+    vec![
+        PeerScopeRule { address: "127.0.0.1/8".to_string(), scope: ApiScope::Control },
+        PeerScopeRule { address: "::1".to_string(), scope: ApiScope::Control },
+    ]
+}
+
+/// 持久化的令牌记录：只保存 SHA-256 摘要，明文仅在创建时返回一次
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ApiToken {
+    pub id: String,
+    pub name: String,
+    pub scope: ApiScope,
+    pub token_hash: String,
+    pub created_at: f64,
+    #[serde(default)]
+    pub expires_at: Option<f64>,
+    #[serde(default)]
+    pub revoked: bool,
+}
+
+/// 返回前端的令牌信息（不含摘要）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiTokenInfo {
+    pub id: String,
+    pub name: String,
+    pub scope: ApiScope,
+    pub created_at: f64,
+    pub expires_at: Option<f64>,
+    pub revoked: bool,
+    pub last_used_at: Option<f64>,
+}
+
+/// 创建令牌的结果：token 为明文，之后不可再查询
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatedApiToken {
+    pub token: String,
+    pub info: ApiTokenInfo,
+}
+
+/// 认证/授权审计记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: f64,
+    /// 对外接口：modbus / rest / grpc / mqtt，本地管理操作为 local
+    pub surface: String,
+    /// 发起方：令牌 ID；按地址规则授权的为 peer:<网段>；其余未携带令牌的外部连接为 anonymous
+    pub actor: String,
+    /// 客户端地址（如 192.168.1.10:50211）
+    #[serde(default)]
+    pub peer: Option<String>,
+    pub action: String,
+    pub required_scope: Option<ApiScope>,
+    pub allowed: bool,
+    #[serde(default)]
+    pub detail: Option<String>,
+}
@@ -9,6 +9,9 @@ pub enum WorkMode {
     Manual,        // 手动模式
     Remote,        // 远程模式
     HistoricalData, // 历史数据模式
+    SolarModel,    // 晴空辐照光伏出力模型
+    WeatherModel,  // 气象数据驱动（风机功率曲线、温度相关负荷）
+    EvSession,     // 充电桩电动汽车充电会话模型
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,6 +41,21 @@ impl DeviceMetadata {
     }
 }
 
+impl WorkMode {
+    /// 内核使用的模式名
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WorkMode::RandomData => "random_data",
+            WorkMode::Manual => "manual",
+            WorkMode::Remote => "remote",
+            WorkMode::HistoricalData => "historical_data",
+            WorkMode::SolarModel => "solar_model",
+            WorkMode::WeatherModel => "weather_model",
+            WorkMode::EvSession => "ev_session",
+        }
+    }
+}
+
 impl From<String> for WorkMode {
     fn from(s: String) -> Self {
         match s.as_str() {
@@ -45,7 +63,383 @@ impl From<String> for WorkMode {
             "manual" => WorkMode::Manual,
             "remote" => WorkMode::Remote,
             "historical_data" => WorkMode::HistoricalData,
+            "solar_model" => WorkMode::SolarModel,
+            "weather_model" => WorkMode::WeatherModel,
+            "ev_session" => WorkMode::EvSession,
             _ => WorkMode::RandomData,
         }
     }
 }
+
+/// 持久化的设备控制状态：启动仿真前设置的工作模式、随机数据源与手动设定，按设备 id 保存在设置文件中，
+/// 每次启动仿真（及内核重启）时自动重新下发
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StoredDeviceControl {
+    /// 内核模式名：random_data | manual | remote | historical_data | solar_model | weather_model | ev_session
+    #[serde(default)]
+    pub mode: Option<String>,
+    #[serde(default)]
+    pub random: Option<StoredRandomConfig>,
+    #[serde(default)]
+    pub manual_setpoint: Option<StoredManualSetpoint>,
+    /// 最近一次更新时间（Unix 秒）
+    #[serde(default)]
+    pub updated_at: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredRandomConfig {
+    pub min_power: f64,
+    pub max_power: f64,
+    #[serde(default)]
+    pub profile: Option<crate::domain::random_profile::RandomProfile>,
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct StoredManualSetpoint {
+    pub active_power: f64,
+    pub reactive_power: f64,
+}
+
+/// 光伏无功控制模式：无、固定功率因数、Q(U) 曲线、固定无功
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReactiveControlMode {
+    #[default]
+    None,
+    FixedPf,
+    #[serde(rename = "q_u")]
+    QU,
+    FixedQ,
+}
+
+impl ReactiveControlMode {
+    /// Modbus 输入寄存器中的模式编码：0=无 1=固定功率因数 2=Q(U) 3=固定无功
+    pub fn register_value(&self) -> u16 {
+        match self {
+            ReactiveControlMode::None => 0,
+            ReactiveControlMode::FixedPf => 1,
+            ReactiveControlMode::QU => 2,
+            ReactiveControlMode::FixedQ => 3,
+        }
+    }
+}
+
+/// Q(U) 曲线点：并网点电压（pu）-> 无功（额定功率百分比，正=发出，负=吸收）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct QuCurvePoint {
+    pub u_pu: f64,
+    pub q_pct: f64,
+}
+
+/// 光伏无功控制配置，保存在设备 properties（reactive_mode / reactive_pf / reactive_q_kvar / q_u_curve）中随工程文件持久化
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReactiveControlConfig {
+    pub mode: ReactiveControlMode,
+    #[serde(default)]
+    pub power_factor: Option<f64>,
+    #[serde(default)]
+    pub q_kvar: Option<f64>,
+    #[serde(default)]
+    pub q_u_curve: Vec<QuCurvePoint>,
+}
+
+impl ReactiveControlConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        match self.mode {
+            ReactiveControlMode::FixedPf => {
+                let pf = self.power_factor.ok_or("固定功率因数模式需要 power_factor")?;
+                if pf == 0.0 || pf.abs() > 1.0 {
+                    return Err(format!("功率因数超出范围: {}", pf));
+                }
+            }
+            ReactiveControlMode::FixedQ => {
+                self.q_kvar.ok_or("固定无功模式需要 q_kvar")?;
+            }
+            ReactiveControlMode::QU => {
+                if self.q_u_curve.len() < 2 {
+                    return Err("Q(U) 曲线至少需要 2 个点".to_string());
+                }
+                if self.q_u_curve.iter().any(|p| p.u_pu <= 0.0 || p.q_pct.abs() > 100.0) {
+                    return Err("Q(U) 曲线点无效：电压需大于 0，无功百分比需在 -100~100".to_string());
+                }
+            }
+            ReactiveControlMode::None => {}
+        }
+        Ok(())
+    }
+
+    /// 转换为写入设备 properties 的字段
+    pub fn to_properties(&self) -> serde_json::Map<String, serde_json::Value> {
+        let mut props = serde_json::Map::new();
+        props.insert("reactive_mode".to_string(), serde_json::to_value(self.mode).unwrap_or_default());
+        if let Some(pf) = self.power_factor {
+            props.insert("reactive_pf".to_string(), serde_json::json!(pf));
+        }
+        if let Some(q) = self.q_kvar {
+            props.insert("reactive_q_kvar".to_string(), serde_json::json!(q));
+        }
+        if !self.q_u_curve.is_empty() {
+            props.insert("q_u_curve".to_string(), serde_json::to_value(&self.q_u_curve).unwrap_or_default());
+        }
+        props
+    }
+
+    /// 从设备 properties 读取（缺省为无控制）
+    pub fn from_properties(properties: &HashMap<String, serde_json::Value>) -> Self {
+        Self {
+            mode: properties
+                .get("reactive_mode")
+                .and_then(|v| serde_json::from_value(v.clone()).ok())
+                .unwrap_or_default(),
+            power_factor: properties.get("reactive_pf").and_then(|v| v.as_f64()),
+            q_kvar: properties.get("reactive_q_kvar").and_then(|v| v.as_f64()),
+            q_u_curve: properties
+                .get("q_u_curve")
+                .and_then(|v| serde_json::from_value(v.clone()).ok())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// P(f) 曲线点：频率（Hz）-> 有功调整量（额定功率百分比，正=增加出力，负=降低出力）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PfCurvePoint {
+    pub f_hz: f64,
+    pub p_pct: f64,
+}
+
+/// 光伏/储能 P(f) 响应配置，保存在设备 properties.pf_response 中；仅孤岛频率模型激活时生效
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PfResponseConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub curve: Vec<PfCurvePoint>,
+    /// 死区（Hz）：|f - 额定频率| 不超过死区时不响应
+    #[serde(default)]
+    pub deadband_hz: f64,
+    /// 有意延时（ms）：频率持续越出死区超过该时长后才开始响应
+    #[serde(default)]
+    pub delay_ms: f64,
+}
+
+impl Default for PfResponseConfig {
+    /// 默认曲线：50.2 Hz 以上按 40%/Hz 降出力，49.8 Hz 以下按 40%/Hz 增出力，死区 ±0.2 Hz
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            curve: vec![
+                PfCurvePoint { f_hz: 47.5, p_pct: 92.0 },
+                PfCurvePoint { f_hz: 49.8, p_pct: 0.0 },
+                PfCurvePoint { f_hz: 50.2, p_pct: 0.0 },
+                PfCurvePoint { f_hz: 52.5, p_pct: -92.0 },
+            ],
+            deadband_hz: 0.2,
+            delay_ms: 0.0,
+        }
+    }
+}
+
+impl PfResponseConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.enabled && self.curve.len() < 2 {
+            return Err("P(f) 曲线至少需要 2 个点".to_string());
+        }
+        if self.curve.iter().any(|p| p.f_hz <= 0.0 || p.p_pct.abs() > 100.0) {
+            return Err("P(f) 曲线点无效：频率需大于 0，有功百分比需在 -100~100".to_string());
+        }
+        if self.deadband_hz < 0.0 || self.delay_ms < 0.0 {
+            return Err("死区与延时不能为负".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn from_properties(properties: &HashMap<String, serde_json::Value>) -> Self {
+        properties
+            .get("pf_response")
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or_default()
+    }
+}
+
+/// Volt-watt 曲线点：并网点电压（pu）-> 有功上限（额定功率百分比）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VoltWattCurvePoint {
+    pub u_pu: f64,
+    pub p_pct: f64,
+}
+
+/// 随机量的概率分布（电动汽车会话的停留时长、需求电量等），按 kind 区分
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Distribution {
+    Fixed { value: f64 },
+    Uniform { min: f64, max: f64 },
+    /// 正态分布，抽样结果截断到不小于 0
+    Normal { mean: f64, std_dev: f64 },
+    Exponential { mean: f64 },
+}
+
+impl Distribution {
+    pub fn validate(&self, name: &str) -> Result<(), String> {
+        let ok = match *self {
+            Distribution::Fixed { value } => value.is_finite() && value >= 0.0,
+            Distribution::Uniform { min, max } => min.is_finite() && max.is_finite() && 0.0 <= min && min <= max,
+            Distribution::Normal { mean, std_dev } => mean.is_finite() && std_dev.is_finite() && mean >= 0.0 && std_dev >= 0.0,
+            Distribution::Exponential { mean } => mean.is_finite() && mean > 0.0,
+        };
+        if ok {
+            Ok(())
+        } else {
+            Err(format!("{} 分布参数无效", name))
+        }
+    }
+}
+
+fn default_ev_arrivals_per_hour() -> f64 {
+    1.0
+}
+fn default_ev_dwell_minutes() -> Distribution {
+    Distribution::Exponential { mean: 120.0 }
+}
+fn default_ev_energy_kwh() -> Distribution {
+    Distribution::Normal { mean: 20.0, std_dev: 8.0 }
+}
+
+/// 充电桩电动汽车充电会话模型（ev_session 模式），保存在设备 properties.ev_sessions 中：
+/// 车辆按（可按小时加权的）泊松过程到达，随机停留时长与需求电量，空闲枪接入后以单枪最大功率充电至充满或离开
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EvSessionConfig {
+    /// 充电枪数量；缺省取属性 gun_count，再缺省为 1
+    #[serde(default)]
+    pub guns: Option<u32>,
+    /// 每小时平均到达车辆数
+    #[serde(default = "default_ev_arrivals_per_hour")]
+    pub arrivals_per_hour: f64,
+    /// 24 个小时权重（本地时间 0–23 时），到达率 = arrivals_per_hour × 权重；为空时全天均匀
+    #[serde(default)]
+    pub hourly_weights: Vec<f64>,
+    /// 停留时长（分钟）
+    #[serde(default = "default_ev_dwell_minutes")]
+    pub dwell_minutes: Distribution,
+    /// 需求电量（kWh）
+    #[serde(default = "default_ev_energy_kwh")]
+    pub energy_kwh: Distribution,
+    /// 单枪最大充电功率（kW）；缺省为额定功率按枪数均分
+    #[serde(default)]
+    pub max_gun_power_kw: Option<f64>,
+    /// 随机种子；未指定时由全局随机种子与设备 ID 派生
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+impl Default for EvSessionConfig {
+    fn default() -> Self {
+        Self {
+            guns: None,
+            arrivals_per_hour: default_ev_arrivals_per_hour(),
+            hourly_weights: Vec::new(),
+            dwell_minutes: default_ev_dwell_minutes(),
+            energy_kwh: default_ev_energy_kwh(),
+            max_gun_power_kw: None,
+            seed: None,
+        }
+    }
+}
+
+impl EvSessionConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.guns == Some(0) {
+            return Err("充电枪数量须大于 0".to_string());
+        }
+        if !(self.arrivals_per_hour.is_finite() && self.arrivals_per_hour >= 0.0) {
+            return Err("到达率须为非负数".to_string());
+        }
+        if !self.hourly_weights.is_empty()
+            && (self.hourly_weights.len() != 24 || self.hourly_weights.iter().any(|w| !w.is_finite() || *w < 0.0))
+        {
+            return Err("小时权重须为 24 个非负数".to_string());
+        }
+        self.dwell_minutes.validate("停留时长")?;
+        self.energy_kwh.validate("需求电量")?;
+        if self.max_gun_power_kw.is_some_and(|p| !(p.is_finite() && p > 0.0)) {
+            return Err("单枪最大功率须为正数".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn from_properties(properties: &HashMap<String, serde_json::Value>) -> Self {
+        properties
+            .get("ev_sessions")
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or_default()
+    }
+}
+
+/// 光伏逆变器电网支撑功能（volt-var / volt-watt），保存在设备 properties.grid_support 中；
+/// 由引擎按上一步并网点电压计算无功设定与有功上限，在下一步计算前下发，优先于本地无功控制模式
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GridSupportConfig {
+    #[serde(default)]
+    pub volt_var_enabled: bool,
+    /// 电压 -> 无功（额定功率百分比，正=发出，负=吸收）
+    #[serde(default)]
+    pub volt_var_curve: Vec<QuCurvePoint>,
+    #[serde(default)]
+    pub volt_watt_enabled: bool,
+    #[serde(default)]
+    pub volt_watt_curve: Vec<VoltWattCurvePoint>,
+}
+
+impl Default for GridSupportConfig {
+    /// 默认曲线（参照 IEEE 1547 类别 B）：volt-var 0.92/0.98/1.02/1.08 pu 对应 +44%/0/0/-44%，
+    /// volt-watt 1.06 pu 起线性降至 1.10 pu 时 20%
+    fn default() -> Self {
+        Self {
+            volt_var_enabled: false,
+            volt_var_curve: vec![
+                QuCurvePoint { u_pu: 0.92, q_pct: 44.0 },
+                QuCurvePoint { u_pu: 0.98, q_pct: 0.0 },
+                QuCurvePoint { u_pu: 1.02, q_pct: 0.0 },
+                QuCurvePoint { u_pu: 1.08, q_pct: -44.0 },
+            ],
+            volt_watt_enabled: false,
+            volt_watt_curve: vec![
+                VoltWattCurvePoint { u_pu: 1.06, p_pct: 100.0 },
+                VoltWattCurvePoint { u_pu: 1.10, p_pct: 20.0 },
+            ],
+        }
+    }
+}
+
+impl GridSupportConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.volt_var_enabled && self.volt_var_curve.len() < 2 {
+            return Err("volt-var 曲线至少需要 2 个点".to_string());
+        }
+        if self.volt_var_curve.iter().any(|p| p.u_pu <= 0.0 || p.q_pct.abs() > 100.0) {
+            return Err("volt-var 曲线点无效：电压需大于 0，无功百分比需在 -100~100".to_string());
+        }
+        if self.volt_watt_enabled && self.volt_watt_curve.len() < 2 {
+            return Err("volt-watt 曲线至少需要 2 个点".to_string());
+        }
+        if self.volt_watt_curve.iter().any(|p| p.u_pu <= 0.0 || !(0.0..=100.0).contains(&p.p_pct)) {
+            return Err("volt-watt 曲线点无效：电压需大于 0，有功上限百分比需在 0~100".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.volt_var_enabled || self.volt_watt_enabled
+    }
+
+    pub fn from_properties(properties: &HashMap<String, serde_json::Value>) -> Self {
+        properties
+            .get("grid_support")
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or_default()
+    }
+}
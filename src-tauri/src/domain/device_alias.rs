@@ -0,0 +1,143 @@
+// 设备别名：SCADA 点名、设备 SN、各协议中的名称及多语言显示名，用于将外部数据（SSH 远程/CSV/数据库）的设备标识自动匹配到拓扑设备；
+// 别名保存在设备属性 aliases 中，随拓扑文件（项目）保存
+use crate::domain::topology::{Device, Topology};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum AliasKind {
+    /// SCADA 点名
+    ScadaPoint,
+    /// 设备序列号
+    SerialNumber,
+    /// 特定协议中的设备名（protocol 指明协议，如 modbus / iec104）
+    Protocol,
+    /// 其他语言的显示名（locale 指明语言，如 en / zh-CN）
+    DisplayName,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DeviceAlias {
+    pub kind: AliasKind,
+    pub value: String,
+    #[serde(default)]
+    pub protocol: Option<String>,
+    #[serde(default)]
+    pub locale: Option<String>,
+}
+
+/// 外部数据标识匹配到的设备
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AliasMatch {
+    pub device_id: String,
+    /// 匹配方式："id" | "name" | 别名类型（scada_point / serial_number / protocol / display_name）
+    pub matched_by: String,
+    /// 命中的标识（原值）
+    pub matched_value: String,
+}
+
+/// 设备属性中保存别名列表的键
+pub const ALIASES_PROPERTY: &str = "aliases";
+
+/// 读取设备属性中的别名；未设置或格式无效时为空
+pub fn device_aliases(device: &Device) -> Vec<DeviceAlias> {
+    device
+        .properties
+        .get(ALIASES_PROPERTY)
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default()
+}
+
+/// 拓扑中全部设置了别名的设备：设备 ID -> 别名列表
+pub fn topology_aliases(topology: &Topology) -> HashMap<String, Vec<DeviceAlias>> {
+    topology
+        .devices
+        .values()
+        .map(|d| (d.id.clone(), device_aliases(d)))
+        .filter(|(_, list)| !list.is_empty())
+        .collect()
+}
+
+/// 匹配时忽略首尾空白与大小写
+pub fn normalize_alias(value: &str) -> String {
+    value.trim().to_lowercase()
+}
+
+fn kind_str(kind: AliasKind) -> &'static str {
+    match kind {
+        AliasKind::ScadaPoint => "scada_point",
+        AliasKind::SerialNumber => "serial_number",
+        AliasKind::Protocol => "protocol",
+        AliasKind::DisplayName => "display_name",
+    }
+}
+
+/// 校验一台设备的别名：不能为空，不能与拓扑中其他设备的 ID、名称或别名重复
+pub fn validate_aliases(device_id: &str, aliases: &[DeviceAlias], topology: &Topology) -> Result<(), String> {
+    let mut taken: HashMap<String, String> = HashMap::new();
+    for device in topology.devices.values().filter(|d| d.id != device_id) {
+        taken.insert(normalize_alias(&device.id), device.id.clone());
+        taken.insert(normalize_alias(&device.name), device.id.clone());
+        for alias in device_aliases(device) {
+            taken.insert(normalize_alias(&alias.value), device.id.clone());
+        }
+    }
+    for alias in aliases {
+        let key = normalize_alias(&alias.value);
+        if key.is_empty() {
+            return Err("别名不能为空".to_string());
+        }
+        if alias.kind == AliasKind::Protocol && alias.protocol.as_deref().is_none_or(|p| p.trim().is_empty()) {
+            return Err(format!("协议别名 {} 未指定协议", alias.value));
+        }
+        if let Some(owner) = taken.get(&key) {
+            return Err(format!("别名 {} 已被设备 {} 使用", alias.value, owner));
+        }
+    }
+    Ok(())
+}
+
+/// 标识索引：设备 ID、设备名称与全部别名 -> 设备
+pub struct AliasIndex {
+    entries: HashMap<String, AliasMatch>,
+}
+
+impl AliasIndex {
+    /// 由拓扑中的设备 ID、名称与设备属性中的别名建立；优先级：别名 < 名称 < 设备 ID（同一标识冲突时以后者为准）
+    pub fn build(topology: Option<&Topology>) -> Self {
+        let mut entries = HashMap::new();
+        let mut insert = |value: &str, device_id: &str, matched_by: &str| {
+            let key = normalize_alias(value);
+            if !key.is_empty() {
+                entries.insert(
+                    key,
+                    AliasMatch {
+                        device_id: device_id.to_string(),
+                        matched_by: matched_by.to_string(),
+                        matched_value: value.to_string(),
+                    },
+                );
+            }
+        };
+        if let Some(t) = topology {
+            for device in t.devices.values() {
+                for alias in device_aliases(device) {
+                    insert(&alias.value, &device.id, kind_str(alias.kind));
+                }
+            }
+            for device in t.devices.values() {
+                insert(&device.name, &device.id, "name");
+            }
+            for device in t.devices.values() {
+                insert(&device.id, &device.id, "id");
+            }
+        }
+        Self { entries }
+    }
+
+    /// 解析外部数据中的设备标识（CSV 列名解析出的 SN、长表 device_id、远程库中的设备 ID 等）
+    pub fn resolve(&self, key: &str) -> Option<AliasMatch> {
+        self.entries.get(&normalize_alias(key)).cloned()
+    }
+}
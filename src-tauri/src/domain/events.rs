@@ -0,0 +1,904 @@
+// 前端事件负载：带版本号的类型化结构，供前端与 WebSocket 镜像等外部消费方按 schema 对接
+// 字段只增不改；删除或改变字段含义时递增 EVENT_SCHEMA_VERSION
+use crate::domain::grid_schedule::GridLimitViolation;
+use crate::domain::simulation::{EvSessionRecord, SimulationError};
+use crate::services::breakpoints::BreakpointHit;
+use crate::services::limit_monitor::LimitAlert;
+use crate::services::event_scheduler::AppliedEventRecord;
+use crate::services::fault_injector::{ActiveFault, FaultRecord};
+use crate::services::frequency_model::FrequencySample;
+use crate::services::oltc::TapOperation;
+use crate::services::protection::ProtectionTrip;
+use crate::services::run_summary::RunSummary;
+use crate::services::setpoint_limits::SetpointClamp;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+pub const EVENT_SCHEMA_VERSION: u32 = 1;
+
+fn schema_version() -> u32 {
+    EVENT_SCHEMA_VERSION
+}
+
+/// 类型化事件负载：事件名与负载的 JSON Schema
+pub trait EventPayload: Serialize {
+    const EVENT: &'static str;
+    fn payload_schema() -> Value;
+}
+
+/// 设备测量值更新（功率为 kW/kvar，内核原生约定；data_json 为内核结果表中该元件的原始行）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceDataUpdate {
+    #[serde(default = "schema_version")]
+    pub schema_version: u32,
+    pub device_id: String,
+    pub data: DeviceDataSample,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceDataSample {
+    pub active_power: Option<f64>,
+    pub reactive_power: Option<f64>,
+    pub timestamp: f64,
+    pub data_json: Value,
+}
+
+impl DeviceDataUpdate {
+    pub fn new(device_id: &str, active_power: Option<f64>, reactive_power: Option<f64>, timestamp: f64, data_json: &Value) -> Self {
+        Self {
+            schema_version: EVENT_SCHEMA_VERSION,
+            device_id: device_id.to_string(),
+            data: DeviceDataSample {
+                active_power,
+                reactive_power,
+                timestamp,
+                data_json: data_json.clone(),
+            },
+        }
+    }
+}
+
+impl EventPayload for DeviceDataUpdate {
+    const EVENT: &'static str = "device-data-update";
+    fn payload_schema() -> Value {
+        object_schema(
+            json!({
+                "schema_version": { "type": "integer" },
+                "device_id": { "type": "string" },
+                "data": object_schema(
+                    json!({
+                        "active_power": nullable("number", "有功功率 kW"),
+                        "reactive_power": nullable("number", "无功功率 kvar"),
+                        "timestamp": { "type": "number", "description": "Unix 时间戳（秒）" },
+                        "data_json": { "type": "object", "description": "内核结果表原始行" }
+                    }),
+                    &["active_power", "reactive_power", "timestamp", "data_json"],
+                )
+            }),
+            &["schema_version", "device_id", "data"],
+        )
+    }
+}
+
+/// 运行中 Modbus 设备的寄存器快照（地址 -> 值）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModbusRegistersUpdated {
+    #[serde(default = "schema_version")]
+    pub schema_version: u32,
+    pub device_id: String,
+    pub input_registers: HashMap<String, u16>,
+    pub holding_registers: HashMap<String, u16>,
+}
+
+impl EventPayload for ModbusRegistersUpdated {
+    const EVENT: &'static str = "modbus-registers-updated";
+    fn payload_schema() -> Value {
+        let registers = json!({ "type": "object", "additionalProperties": { "type": "integer", "minimum": 0, "maximum": 65535 } });
+        object_schema(
+            json!({
+                "schema_version": { "type": "integer" },
+                "device_id": { "type": "string" },
+                "input_registers": registers,
+                "holding_registers": registers
+            }),
+            &["schema_version", "device_id", "input_registers", "holding_registers"],
+        )
+    }
+}
+
+/// Modbus 客户端写保持寄存器
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModbusHoldingRegisterWrite {
+    #[serde(default = "schema_version")]
+    pub schema_version: u32,
+    pub device_id: String,
+    pub address: u16,
+    pub value: u16,
+}
+
+impl EventPayload for ModbusHoldingRegisterWrite {
+    const EVENT: &'static str = "modbus-holding-register-write";
+    fn payload_schema() -> Value {
+        object_schema(
+            json!({
+                "schema_version": { "type": "integer" },
+                "device_id": { "type": "string" },
+                "address": { "type": "integer", "minimum": 0, "maximum": 65535 },
+                "value": { "type": "integer", "minimum": 0, "maximum": 65535 }
+            }),
+            &["schema_version", "device_id", "address", "value"],
+        )
+    }
+}
+
+/// Modbus 客户端写线圈（开关合/分闸）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModbusCoilWrite {
+    #[serde(default = "schema_version")]
+    pub schema_version: u32,
+    pub device_id: String,
+    pub address: u16,
+    pub value: bool,
+}
+
+impl EventPayload for ModbusCoilWrite {
+    const EVENT: &'static str = "modbus-coil-write";
+    fn payload_schema() -> Value {
+        object_schema(
+            json!({
+                "schema_version": { "type": "integer" },
+                "device_id": { "type": "string" },
+                "address": { "type": "integer", "minimum": 0, "maximum": 65535 },
+                "value": { "type": "boolean" }
+            }),
+            &["schema_version", "device_id", "address", "value"],
+        )
+    }
+}
+
+/// 仿真错误列表变化
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulationErrorsUpdate {
+    #[serde(default = "schema_version")]
+    pub schema_version: u32,
+    pub errors: Vec<SimulationError>,
+    /// 错误涉及的设备 ID
+    pub device_ids: Vec<String>,
+}
+
+impl SimulationErrorsUpdate {
+    pub fn new(errors: Vec<SimulationError>) -> Self {
+        let device_ids = crate::domain::simulation::error_device_ids(&errors);
+        Self { schema_version: EVENT_SCHEMA_VERSION, errors, device_ids }
+    }
+}
+
+impl EventPayload for SimulationErrorsUpdate {
+    const EVENT: &'static str = "simulation-errors-update";
+    fn payload_schema() -> Value {
+        object_schema(
+            json!({
+                "schema_version": { "type": "integer" },
+                "errors": {
+                    "type": "array",
+                    "items": object_schema(
+                        json!({
+                            "error_type": { "enum": ["adapter", "topology", "calculation", "runtime"] },
+                            "severity": { "enum": ["error", "warning", "info"] },
+                            "message": { "type": "string" },
+                            "device_id": { "type": "string" },
+                            "details": {},
+                            "timestamp": { "type": "integer" }
+                        }),
+                        &["error_type", "severity", "message", "details", "timestamp"],
+                    )
+                },
+                "device_ids": { "type": "array", "items": { "type": "string" } }
+            }),
+            &["schema_version", "errors", "device_ids"],
+        )
+    }
+}
+
+/// 仿真因严重错误自动停止
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulationAutoStopped {
+    #[serde(default = "schema_version")]
+    pub schema_version: u32,
+    pub reason: String,
+}
+
+impl EventPayload for SimulationAutoStopped {
+    const EVENT: &'static str = "simulation-auto-stopped";
+    fn payload_schema() -> Value {
+        object_schema(
+            json!({ "schema_version": { "type": "integer" }, "reason": { "type": "string" } }),
+            &["schema_version", "reason"],
+        )
+    }
+}
+
+/// 当前全部限值告警（告警集合变化时推送）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LimitAlertsUpdate {
+    #[serde(default = "schema_version")]
+    pub schema_version: u32,
+    pub alerts: Vec<LimitAlert>,
+}
+
+impl EventPayload for LimitAlertsUpdate {
+    const EVENT: &'static str = "limit-alerts-update";
+    fn payload_schema() -> Value {
+        let bound = nullable("number", "");
+        object_schema(
+            json!({
+                "schema_version": { "type": "integer" },
+                "alerts": {
+                    "type": "array",
+                    "items": object_schema(
+                        json!({
+                            "device_id": { "type": "string" },
+                            "quantity": { "type": "string" },
+                            "value": { "type": "number" },
+                            "level": { "enum": ["normal", "warning", "alarm"] },
+                            "band": {
+                                "type": "object",
+                                "properties": {
+                                    "warning_low": bound, "warning_high": bound,
+                                    "alarm_low": bound, "alarm_high": bound
+                                }
+                            },
+                            "since": { "type": "number" },
+                            "timestamp": { "type": "number" }
+                        }),
+                        &["device_id", "quantity", "value", "level", "band", "since", "timestamp"],
+                    )
+                }
+            }),
+            &["schema_version", "alerts"],
+        )
+    }
+}
+
+/// 外部电网分时功率越限
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GridLimitViolationUpdate {
+    #[serde(default = "schema_version")]
+    pub schema_version: u32,
+    pub violations: Vec<GridLimitViolation>,
+}
+
+impl EventPayload for GridLimitViolationUpdate {
+    const EVENT: &'static str = "grid-limit-violation";
+    fn payload_schema() -> Value {
+        object_schema(
+            json!({
+                "schema_version": { "type": "integer" },
+                "violations": {
+                    "type": "array",
+                    "items": object_schema(
+                        json!({
+                            "device_id": { "type": "string" },
+                            "timestamp": { "type": "number" },
+                            "hour": { "type": "integer", "minimum": 0, "maximum": 23 },
+                            "direction": { "enum": ["import", "export"] },
+                            "p_kw": { "type": "number" },
+                            "limit_kw": { "type": "number" }
+                        }),
+                        &["device_id", "timestamp", "hour", "direction", "p_kw", "limit_kw"],
+                    )
+                }
+            }),
+            &["schema_version", "violations"],
+        )
+    }
+}
+
+/// 设定值超出额定范围被限幅（字段与 SetpointClamp 相同）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetpointClamped {
+    #[serde(default = "schema_version")]
+    pub schema_version: u32,
+    #[serde(flatten)]
+    pub clamp: SetpointClamp,
+}
+
+impl SetpointClamped {
+    pub fn new(clamp: SetpointClamp) -> Self {
+        Self { schema_version: EVENT_SCHEMA_VERSION, clamp }
+    }
+}
+
+impl EventPayload for SetpointClamped {
+    const EVENT: &'static str = "setpoint-clamped";
+    fn payload_schema() -> Value {
+        object_schema(
+            json!({
+                "schema_version": { "type": "integer" },
+                "device_id": { "type": "string" },
+                "source": { "type": "string" },
+                "field": { "type": "string" },
+                "requested_kw": { "type": "number" },
+                "applied_kw": { "type": "number" },
+                "rating": object_schema(
+                    json!({ "min_kw": { "type": "number" }, "max_kw": { "type": "number" } }),
+                    &["min_kw", "max_kw"],
+                ),
+                "timestamp": { "type": "number" }
+            }),
+            &["schema_version", "device_id", "source", "field", "requested_kw", "applied_kw", "rating", "timestamp"],
+        )
+    }
+}
+
+/// 有载调压变压器分接头动作（字段与 TapOperation 相同）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransformerTapChanged {
+    #[serde(default = "schema_version")]
+    pub schema_version: u32,
+    #[serde(flatten)]
+    pub operation: TapOperation,
+}
+
+impl TransformerTapChanged {
+    pub fn new(operation: TapOperation) -> Self {
+        Self { schema_version: EVENT_SCHEMA_VERSION, operation }
+    }
+}
+
+impl EventPayload for TransformerTapChanged {
+    const EVENT: &'static str = "transformer-tap-changed";
+    fn payload_schema() -> Value {
+        object_schema(
+            json!({
+                "schema_version": { "type": "integer" },
+                "device_id": { "type": "string" },
+                "regulated_bus": { "type": "string", "description": "受控母线设备 ID" },
+                "from_tap": { "type": "integer" },
+                "to_tap": { "type": "integer" },
+                "vm_pu": { "type": "number", "description": "触发动作的受控母线电压" },
+                "target_vm_pu": { "type": "number" },
+                "timestamp": { "type": "number" }
+            }),
+            &["schema_version", "device_id", "regulated_bus", "from_tap", "to_tap", "vm_pu", "target_vm_pu", "timestamp"],
+        )
+    }
+}
+
+/// 过流保护动作（字段与 ProtectionTrip 相同），每次跳闸发送
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProtectionTripped {
+    #[serde(default = "schema_version")]
+    pub schema_version: u32,
+    #[serde(flatten)]
+    pub trip: ProtectionTrip,
+}
+
+impl ProtectionTripped {
+    pub fn new(trip: ProtectionTrip) -> Self {
+        Self { schema_version: EVENT_SCHEMA_VERSION, trip }
+    }
+}
+
+impl EventPayload for ProtectionTripped {
+    const EVENT: &'static str = "protection-tripped";
+    fn payload_schema() -> Value {
+        object_schema(
+            json!({
+                "schema_version": { "type": "integer" },
+                "device_id": { "type": "string", "description": "被保护的线路/变压器设备 ID" },
+                "switch_id": { "type": "string", "description": "跳闸开关设备 ID" },
+                "i_ka": nullable("number", "动作时电流（kA）"),
+                "loading_percent": nullable("number", "动作时负载率（%）"),
+                "pickup_ka": nullable("number", "电流定值"),
+                "pickup_loading_pct": nullable("number", "负载率定值"),
+                "delay_s": { "type": "number" },
+                "picked_up_at": { "type": "number", "description": "越限开始时间（仿真时间）" },
+                "timestamp": { "type": "number", "description": "动作时间（仿真时间）" },
+                "ok": { "type": "boolean", "description": "开关是否已断开" },
+                "error": nullable("string", "断开开关失败原因")
+            }),
+            &["schema_version", "device_id", "switch_id", "delay_s", "picked_up_at", "timestamp", "ok"],
+        )
+    }
+}
+
+/// 定时事件已施加（字段与 AppliedEventRecord 相同）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledEventApplied {
+    #[serde(default = "schema_version")]
+    pub schema_version: u32,
+    #[serde(flatten)]
+    pub record: AppliedEventRecord,
+}
+
+impl ScheduledEventApplied {
+    pub fn new(record: AppliedEventRecord) -> Self {
+        Self { schema_version: EVENT_SCHEMA_VERSION, record }
+    }
+}
+
+impl EventPayload for ScheduledEventApplied {
+    const EVENT: &'static str = "scheduled-event-applied";
+    fn payload_schema() -> Value {
+        object_schema(
+            json!({
+                "schema_version": { "type": "integer" },
+                "index": { "type": "integer", "minimum": 0 },
+                "at_s": { "type": "number" },
+                "sim_time_s": { "type": "number" },
+                "device_id": { "type": "string" },
+                "properties": { "type": "object" },
+                "action": nullable("object", "场景动作（kind: grid_voltage | grid_frequency | device_setpoint | device_mode），电网动作的 device_id 为空"),
+                "label": nullable("string", ""),
+                "ok": { "type": "boolean" },
+                "error": nullable("string", "")
+            }),
+            &["schema_version", "index", "at_s", "sim_time_s", "device_id", "properties", "ok"],
+        )
+    }
+}
+
+/// 故障注入/清除；phase 为 "injected" 或 "cleared"，清除时附带 cleared_by 等字段
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FaultStateChanged {
+    #[serde(default = "schema_version")]
+    pub schema_version: u32,
+    pub phase: String,
+    #[serde(flatten)]
+    pub fault: ActiveFault,
+    #[serde(default)]
+    pub cleared_at: Option<f64>,
+    #[serde(default)]
+    pub cleared_sim_s: Option<f64>,
+    #[serde(default)]
+    pub cleared_by: Option<String>,
+}
+
+impl FaultStateChanged {
+    pub fn injected(fault: ActiveFault) -> Self {
+        Self {
+            schema_version: EVENT_SCHEMA_VERSION,
+            phase: "injected".to_string(),
+            fault,
+            cleared_at: None,
+            cleared_sim_s: None,
+            cleared_by: None,
+        }
+    }
+
+    pub fn cleared(record: FaultRecord) -> Self {
+        Self {
+            schema_version: EVENT_SCHEMA_VERSION,
+            phase: "cleared".to_string(),
+            fault: record.fault,
+            cleared_at: Some(record.cleared_at),
+            cleared_sim_s: Some(record.cleared_sim_s),
+            cleared_by: Some(record.cleared_by),
+        }
+    }
+}
+
+impl EventPayload for FaultStateChanged {
+    const EVENT: &'static str = "fault-state-changed";
+    fn payload_schema() -> Value {
+        object_schema(
+            json!({
+                "schema_version": { "type": "integer" },
+                "phase": { "type": "string", "enum": ["injected", "cleared"] },
+                "fault_id": { "type": "integer" },
+                "device_id": { "type": "string" },
+                "fault_type": { "type": "string", "enum": ["outage", "short_circuit"] },
+                "fault_resistance_ohm": nullable("number", "短路接地电阻（Ω）"),
+                "injected_at": { "type": "number" },
+                "injected_sim_s": { "type": "number" },
+                "duration_s": nullable("number", "持续仿真秒数，null 表示持续到清除"),
+                "short_circuit": { "type": ["object", "null"] },
+                "cleared_at": nullable("number", ""),
+                "cleared_sim_s": nullable("number", ""),
+                "cleared_by": nullable("string", "duration | command | stop | error")
+            }),
+            &["schema_version", "phase", "fault_id", "device_id", "fault_type", "injected_at", "injected_sim_s"],
+        )
+    }
+}
+
+/// 计算步已提交（多窗口快照对齐）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateStepCommitted {
+    #[serde(default = "schema_version")]
+    pub schema_version: u32,
+    pub seq: u64,
+    pub timestamp: f64,
+}
+
+impl EventPayload for StateStepCommitted {
+    const EVENT: &'static str = "state-step-committed";
+    fn payload_schema() -> Value {
+        object_schema(
+            json!({
+                "schema_version": { "type": "integer" },
+                "seq": { "type": "integer", "minimum": 1 },
+                "timestamp": { "type": "number" }
+            }),
+            &["schema_version", "seq", "timestamp"],
+        )
+    }
+}
+
+/// 回放开始/结束；phase 为 "started" 或 "finished"，结束时附带 reason（finished | stopped | error: ...）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayStateChanged {
+    #[serde(default = "schema_version")]
+    pub schema_version: u32,
+    pub phase: String,
+    pub db_path: String,
+    pub speed: f64,
+    #[serde(default)]
+    pub timestamp: Option<f64>,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+impl EventPayload for ReplayStateChanged {
+    const EVENT: &'static str = "replay-state-changed";
+    fn payload_schema() -> Value {
+        object_schema(
+            json!({
+                "schema_version": { "type": "integer" },
+                "phase": { "type": "string", "enum": ["started", "finished"] },
+                "db_path": { "type": "string" },
+                "speed": { "type": "number", "description": "回放倍速" },
+                "timestamp": nullable("number", "最近推送的录制时刻"),
+                "reason": nullable("string", "finished | stopped | error: ...")
+            }),
+            &["schema_version", "phase", "db_path", "speed"],
+        )
+    }
+}
+
+/// 运行中 Python 内核进程意外退出后自动重启的结果；ok=false 时附带失败原因，连续失败达到上限后仿真自动停止
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PythonKernelRestarted {
+    #[serde(default = "schema_version")]
+    pub schema_version: u32,
+    /// 本轮仿真中的第几次重启
+    pub attempt: u32,
+    pub ok: bool,
+    /// 重新下发的设备工作模式数
+    #[serde(default)]
+    pub restored_modes: usize,
+    /// 重启时的仿真时间
+    pub timestamp: f64,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+impl EventPayload for PythonKernelRestarted {
+    const EVENT: &'static str = "python-kernel-restarted";
+    fn payload_schema() -> Value {
+        object_schema(
+            json!({
+                "schema_version": { "type": "integer" },
+                "attempt": { "type": "integer", "description": "本轮仿真中的第几次重启" },
+                "ok": { "type": "boolean" },
+                "restored_modes": { "type": "integer", "description": "重新下发的设备工作模式数" },
+                "timestamp": { "type": "number", "description": "重启时的仿真时间" },
+                "error": nullable("string", "重启失败原因")
+            }),
+            &["schema_version", "attempt", "ok", "timestamp"],
+        )
+    }
+}
+
+/// 内核看门狗连续探测失败（未响应 ping 或进程已退出）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PythonKernelUnhealthy {
+    #[serde(default = "schema_version")]
+    pub schema_version: u32,
+    pub consecutive_failures: u32,
+    pub error: String,
+    /// 是否随即尝试自动重启
+    pub restarting: bool,
+    /// 未重启时距下次重启尝试的毫秒数（退避中）
+    #[serde(default)]
+    pub next_restart_in_ms: Option<u64>,
+}
+
+impl EventPayload for PythonKernelUnhealthy {
+    const EVENT: &'static str = "python-kernel-unhealthy";
+    fn payload_schema() -> Value {
+        object_schema(
+            json!({
+                "schema_version": { "type": "integer" },
+                "consecutive_failures": { "type": "integer" },
+                "error": { "type": "string" },
+                "restarting": { "type": "boolean", "description": "是否随即尝试自动重启" },
+                "next_restart_in_ms": nullable("integer", "退避中距下次重启尝试的毫秒数")
+            }),
+            &["schema_version", "consecutive_failures", "error", "restarting"],
+        )
+    }
+}
+
+/// 仿真时间跨日时日电量已归档并清零（储能日充放电量、光伏今日发电量、设备日电量）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyCountersRolledOver {
+    #[serde(default = "schema_version")]
+    pub schema_version: u32,
+    /// 已归档的日（YYYY-MM-DD）
+    pub day: String,
+    /// 翻转时的仿真时间
+    pub timestamp: f64,
+    /// 清零的储能数
+    #[serde(default)]
+    pub storage_count: usize,
+    /// 清零今日发电量寄存器的光伏数
+    #[serde(default)]
+    pub pv_count: usize,
+}
+
+impl EventPayload for DailyCountersRolledOver {
+    const EVENT: &'static str = "daily-counters-rolled-over";
+    fn payload_schema() -> Value {
+        object_schema(
+            json!({
+                "schema_version": { "type": "integer" },
+                "day": { "type": "string", "description": "已归档的日（YYYY-MM-DD）" },
+                "timestamp": { "type": "number", "description": "翻转时的仿真时间" },
+                "storage_count": { "type": "integer", "description": "清零的储能数" },
+                "pv_count": { "type": "integer", "description": "清零今日发电量寄存器的光伏数" }
+            }),
+            &["schema_version", "day", "timestamp"],
+        )
+    }
+}
+
+/// 孤岛运行时的系统频率（字段与 FrequencySample 相同），按推送间隔发送
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FrequencyUpdate {
+    #[serde(default = "schema_version")]
+    pub schema_version: u32,
+    #[serde(flatten)]
+    pub sample: FrequencySample,
+}
+
+impl FrequencyUpdate {
+    pub fn new(sample: FrequencySample) -> Self {
+        Self { schema_version: EVENT_SCHEMA_VERSION, sample }
+    }
+}
+
+impl EventPayload for FrequencyUpdate {
+    const EVENT: &'static str = "frequency-update";
+    fn payload_schema() -> Value {
+        object_schema(
+            json!({
+                "schema_version": { "type": "integer" },
+                "frequency_hz": { "type": "number" },
+                "rocof_hz_per_s": { "type": "number", "description": "频率变化率" },
+                "islanded": { "type": "boolean" },
+                "imbalance_kw": { "type": "number", "description": "孤岛发用电不平衡，正=发电多于用电" },
+                "droop_kw": { "type": "number", "description": "储能下垂一次调频出力，正=增加出力" },
+                "timestamp": { "type": "number" }
+            }),
+            &["schema_version", "frequency_hz", "rocof_hz_per_s", "islanded", "imbalance_kw", "droop_kw", "timestamp"],
+        )
+    }
+}
+
+/// 电动汽车充电会话结束（离开、被拒绝或仿真停止；字段与 EvSessionRecord 相同），每次结束发送
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvSessionEnded {
+    #[serde(default = "schema_version")]
+    pub schema_version: u32,
+    #[serde(flatten)]
+    pub record: EvSessionRecord,
+}
+
+impl EvSessionEnded {
+    pub fn new(record: EvSessionRecord) -> Self {
+        Self { schema_version: EVENT_SCHEMA_VERSION, record }
+    }
+}
+
+impl EventPayload for EvSessionEnded {
+    const EVENT: &'static str = "ev-session-ended";
+    fn payload_schema() -> Value {
+        object_schema(
+            json!({
+                "schema_version": { "type": "integer" },
+                "session_id": { "type": "string" },
+                "device_id": { "type": "string" },
+                "gun": nullable("integer", "充电枪序号（从 1 开始），被拒绝时为 null"),
+                "arrival_at": { "type": "number" },
+                "departure_at": { "type": "number" },
+                "charged_at": nullable("number", "充满时间，未充满为 null"),
+                "energy_requested_kwh": { "type": "number" },
+                "energy_delivered_kwh": { "type": "number" },
+                "peak_power_kw": { "type": "number" },
+                "status": { "type": "string", "enum": ["completed", "unmet", "rejected", "interrupted"] }
+            }),
+            &["schema_version", "session_id", "device_id", "arrival_at", "departure_at", "energy_requested_kwh", "energy_delivered_kwh", "peak_power_kw", "status"],
+        )
+    }
+}
+
+/// 仿真停止时的运行摘要（字段与 RunSummary 相同，同时写入仿真库），每轮运行发送一次
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulationSummary {
+    #[serde(default = "schema_version")]
+    pub schema_version: u32,
+    #[serde(flatten)]
+    pub summary: RunSummary,
+}
+
+impl SimulationSummary {
+    pub fn new(summary: RunSummary) -> Self {
+        Self { schema_version: EVENT_SCHEMA_VERSION, summary }
+    }
+}
+
+impl EventPayload for SimulationSummary {
+    const EVENT: &'static str = "simulation-summary";
+    fn payload_schema() -> Value {
+        let voltage = json!({
+            "type": ["object", "null"],
+            "properties": {
+                "bus_id": nullable("string", "母线设备 ID"),
+                "bus_name": { "type": "string" },
+                "vm_pu": { "type": "number" },
+                "timestamp": { "type": "number", "description": "出现时刻（仿真时间）" }
+            }
+        });
+        object_schema(
+            json!({
+                "schema_version": { "type": "integer" },
+                "reason": { "type": "string", "enum": ["stop", "max_steps", "max_duration", "error", "kernel_failure"] },
+                "started_at": { "type": "number", "description": "启动墙钟时间" },
+                "stopped_at": { "type": "number", "description": "停止墙钟时间" },
+                "wall_duration_s": { "type": "number" },
+                "sim_start": nullable("number", "首步仿真时间"),
+                "sim_end": nullable("number", "末步仿真时间"),
+                "sim_duration_s": { "type": "number" },
+                "steps": { "type": "integer" },
+                "convergence_failures": { "type": "integer", "description": "潮流未收敛步数" },
+                "min_voltage": voltage.clone(),
+                "max_voltage": voltage,
+                "device_energy": {
+                    "type": "array",
+                    "description": "功率设备累计电量（输入 = 从电网流入设备，输出 = 设备送入电网）",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "device_id": { "type": "string" },
+                            "name": nullable("string", ""),
+                            "device_type": nullable("string", ""),
+                            "import_kwh": { "type": "number" },
+                            "export_kwh": { "type": "number" }
+                        }
+                    }
+                }
+            }),
+            &["schema_version", "reason", "started_at", "stopped_at", "wall_duration_s", "sim_duration_s", "steps", "convergence_failures", "device_energy"],
+        )
+    }
+}
+
+/// 条件断点命中，仿真已暂停（单步执行中命中时保持暂停）；hits 为本步新命中的全部断点
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulationBreakpointHit {
+    #[serde(default = "schema_version")]
+    pub schema_version: u32,
+    /// 命中步的仿真时间
+    pub timestamp: f64,
+    pub step: u64,
+    pub hits: Vec<BreakpointHit>,
+}
+
+impl EventPayload for SimulationBreakpointHit {
+    const EVENT: &'static str = "simulation-breakpoint-hit";
+    fn payload_schema() -> Value {
+        object_schema(
+            json!({
+                "schema_version": { "type": "integer" },
+                "timestamp": { "type": "number" },
+                "step": { "type": "integer" },
+                "hits": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "breakpoint": {
+                                "type": "object",
+                                "properties": {
+                                    "id": { "type": "string" },
+                                    "device_id": { "type": "string" },
+                                    "quantity": { "type": "string", "enum": ["voltage_pu", "loading_percent", "soc_percent", "p_kw", "q_kvar"] },
+                                    "op": { "type": "string", "enum": ["lt", "le", "gt", "ge"] },
+                                    "threshold": { "type": "number" },
+                                    "enabled": { "type": "boolean" }
+                                }
+                            },
+                            "value": { "type": "number", "description": "命中时的实际值" }
+                        }
+                    }
+                }
+            }),
+            &["schema_version", "timestamp", "step", "hits"],
+        )
+    }
+}
+
+/// 直接转发内核结果的事件：负载为内核结果表中的原始行，随内核版本变化，不做版本约束
+const PASSTHROUGH_EVENTS: &[(&str, &str)] = &[
+    ("calculation-result-update", "本步完整计算结果（devices/converged、solver 求解诊断含迭代次数等，已应用传感器延迟）"),
+    ("bus-voltage-update", "内核 res_bus 行"),
+    ("line-data-update", "内核 res_line 行"),
+    ("switch-data-update", "内核 res_switch 行"),
+    ("transformer-data-update", "内核 res_trafo 行"),
+    ("storage-data-update", "内核 res_storage 行"),
+    ("load-power-update", "负荷功率与内核 res_load 行"),
+    ("generator-power-update", "发电机功率与内核 res_sgen 行"),
+];
+
+fn object_schema(properties: Value, required: &[&str]) -> Value {
+    json!({ "type": "object", "properties": properties, "required": required })
+}
+
+fn nullable(ty: &str, description: &str) -> Value {
+    let mut schema = json!({ "type": [ty, "null"] });
+    if !description.is_empty() {
+        schema["description"] = json!(description);
+    }
+    schema
+}
+
+fn typed_entry<T: EventPayload>(events: &mut serde_json::Map<String, Value>) {
+    events.insert(
+        T::EVENT.to_string(),
+        json!({
+            "typed": true,
+            "payload": T::payload_schema()
+        }),
+    );
+}
+
+/// 机器可读的事件 schema 文档（各负载为 JSON Schema draft-07）
+pub fn event_schema_document() -> Value {
+    let mut events = serde_json::Map::new();
+    typed_entry::<DeviceDataUpdate>(&mut events);
+    typed_entry::<ModbusRegistersUpdated>(&mut events);
+    typed_entry::<ModbusHoldingRegisterWrite>(&mut events);
+    typed_entry::<ModbusCoilWrite>(&mut events);
+    typed_entry::<SimulationErrorsUpdate>(&mut events);
+    typed_entry::<SimulationAutoStopped>(&mut events);
+    typed_entry::<LimitAlertsUpdate>(&mut events);
+    typed_entry::<GridLimitViolationUpdate>(&mut events);
+    typed_entry::<SetpointClamped>(&mut events);
+    typed_entry::<TransformerTapChanged>(&mut events);
+    typed_entry::<ProtectionTripped>(&mut events);
+    typed_entry::<ScheduledEventApplied>(&mut events);
+    typed_entry::<FaultStateChanged>(&mut events);
+    typed_entry::<StateStepCommitted>(&mut events);
+    typed_entry::<ReplayStateChanged>(&mut events);
+    typed_entry::<PythonKernelRestarted>(&mut events);
+    typed_entry::<PythonKernelUnhealthy>(&mut events);
+    typed_entry::<DailyCountersRolledOver>(&mut events);
+    typed_entry::<FrequencyUpdate>(&mut events);
+    typed_entry::<EvSessionEnded>(&mut events);
+    typed_entry::<SimulationSummary>(&mut events);
+    typed_entry::<SimulationBreakpointHit>(&mut events);
+    for (event, description) in PASSTHROUGH_EVENTS {
+        events.insert(
+            event.to_string(),
+            json!({ "typed": false, "description": description, "payload": { "type": "object" } }),
+        );
+    }
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "schema_version": EVENT_SCHEMA_VERSION,
+        "events": events
+    })
+}
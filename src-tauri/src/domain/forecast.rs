@@ -0,0 +1,136 @@
+// 设备功率预测：导入的预测曲线（与储能计划相同的时间格式），用于评估预测与实际的偏差
+use crate::domain::storage_schedule::parse_schedule_time;
+use crate::domain::simulation::sim_seconds_of_day;
+use serde::{Deserialize, Serialize};
+
+/// 预测点：at 为 Unix 时间戳（秒）；daily 预测中为当天零点起的秒数
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ForecastPoint {
+    pub at: f64,
+    /// 预测有功（kW，与内核原生约定一致：光伏正=发电，负荷/储能/充电桩正=用电，外部电网正=购电）
+    pub p_kw: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DeviceForecast {
+    pub device_id: String,
+    /// true 时预测点为一天内的时刻，每天重复
+    pub daily: bool,
+    pub points: Vec<ForecastPoint>,
+    /// 自定义分组：同组设备的预测与实际求和后另行统计
+    #[serde(default)]
+    pub group: Option<String>,
+}
+
+impl DeviceForecast {
+    /// 从 CSV 导入：需含时间列（timestamp/time）与预测功率列（p_kw/forecast_kw）
+    pub fn from_csv(device_id: &str, path: &str, group: Option<String>) -> Result<Self, String> {
+        let mut reader = csv::Reader::from_path(path).map_err(|e| format!("打开预测文件失败: {}", e))?;
+        let headers: Vec<String> = reader
+            .headers()
+            .map_err(|e| format!("读取表头失败: {}", e))?
+            .iter()
+            .map(|h| h.trim().to_lowercase())
+            .collect();
+        let find = |names: &[&str]| headers.iter().position(|h| names.contains(&h.as_str()));
+        let time_col = find(&["timestamp", "time", "时间"]).ok_or("预测文件缺少时间列 timestamp/time")?;
+        let p_col = find(&["p_kw", "forecast_kw", "power_kw"]).ok_or("预测文件缺少功率列 p_kw")?;
+
+        let mut points = Vec::new();
+        let mut daily: Option<bool> = None;
+        for (i, record) in reader.records().enumerate() {
+            let record = record.map_err(|e| format!("第 {} 行读取失败: {}", i + 2, e))?;
+            let raw_time = record.get(time_col).unwrap_or("");
+            let (at, is_daily) = parse_schedule_time(raw_time)
+                .ok_or_else(|| format!("第 {} 行时间无法解析: {}", i + 2, raw_time))?;
+            if daily.is_some_and(|d| d != is_daily) {
+                return Err("预测文件不能混用日期时间与每日时刻".to_string());
+            }
+            daily = Some(is_daily);
+            let p_kw = record
+                .get(p_col)
+                .and_then(|v| v.trim().parse::<f64>().ok())
+                .ok_or_else(|| format!("第 {} 行功率无法解析", i + 2))?;
+            points.push(ForecastPoint { at, p_kw });
+        }
+        let forecast = Self {
+            device_id: device_id.to_string(),
+            daily: daily.unwrap_or(false),
+            points,
+            group: group.filter(|g| !g.trim().is_empty()),
+        };
+        forecast.validate()?;
+        Ok(forecast)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.points.is_empty() {
+            return Err("预测为空".to_string());
+        }
+        if self.points.windows(2).any(|w| w[1].at <= w[0].at) {
+            return Err("预测时间须严格递增".to_string());
+        }
+        if self.daily && self.points.iter().any(|p| !(0.0..86400.0).contains(&p.at)) {
+            return Err("每日预测时刻须在 00:00–24:00 之间".to_string());
+        }
+        Ok(())
+    }
+
+    /// 某时刻的预测值（相邻预测点线性插值）；绝对预测超出范围时为 None，每日预测跨零点首尾相接
+    pub fn value_at(&self, timestamp: f64) -> Option<f64> {
+        let first = self.points.first()?;
+        let last = self.points.last()?;
+        if !self.daily {
+            if timestamp < first.at || timestamp > last.at {
+                return None;
+            }
+            let i = self.points.iter().rposition(|p| p.at <= timestamp)?;
+            return Some(match self.points.get(i + 1) {
+                Some(next) => lerp(&self.points[i], next, timestamp),
+                None => self.points[i].p_kw,
+            });
+        }
+        let t = sim_seconds_of_day(timestamp);
+        match self.points.iter().rposition(|p| p.at <= t) {
+            Some(i) => match self.points.get(i + 1) {
+                Some(next) => Some(lerp(&self.points[i], next, t)),
+                None => Some(lerp(last, &ForecastPoint { at: first.at + 86400.0, p_kw: first.p_kw }, t)),
+            },
+            None => Some(lerp(&ForecastPoint { at: last.at - 86400.0, p_kw: last.p_kw }, first, t)),
+        }
+    }
+}
+
+fn lerp(a: &ForecastPoint, b: &ForecastPoint, t: f64) -> f64 {
+    if b.at <= a.at {
+        return a.p_kw;
+    }
+    a.p_kw + (b.p_kw - a.p_kw) * (t - a.at) / (b.at - a.at)
+}
+
+/// 某一滚动窗口内的预测精度
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccuracyWindow {
+    /// 窗口长度（秒）；None 表示本次仿真全程
+    pub window_s: Option<f64>,
+    pub samples: u64,
+    /// 平均绝对百分比误差（%）；实际功率接近 0 的样本不计入
+    pub mape_pct: Option<f64>,
+    pub rmse_kw: Option<f64>,
+    /// 平均偏差（预测 − 实际，kW），正值表示预测偏高
+    pub bias_kw: Option<f64>,
+}
+
+/// 单台设备或分组的预测精度（key 为设备 ID、type:<设备类型> 或 group:<自定义组>）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForecastAccuracy {
+    pub key: String,
+    pub device_ids: Vec<String>,
+    /// 预测来源：imported（导入的预测曲线）| clear_sky（内置晴空出力预测）| mixed（分组内两者皆有）
+    #[serde(default)]
+    pub source: String,
+    pub windows: Vec<AccuracyWindow>,
+    pub last_forecast_kw: Option<f64>,
+    pub last_actual_kw: Option<f64>,
+    pub updated_at: Option<f64>,
+}
@@ -0,0 +1,82 @@
+// 外部电网分时边界条件：购/售电功率限值与分时电价，配置在 ExternalGrid 设备 properties.grid_schedule 中
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 一个时段 [start_hour, end_hour)，end_hour 可为 24；start_hour > end_hour 表示跨零点
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GridScheduleSlot {
+    pub start_hour: u8,
+    pub end_hour: u8,
+    /// 最大购电功率（kW，从电网取电），None 不限制
+    #[serde(default)]
+    pub import_limit_kw: Option<f64>,
+    /// 最大上网功率（kW，向电网送电），None 不限制
+    #[serde(default)]
+    pub export_limit_kw: Option<f64>,
+    /// 购电电价（元/kWh）
+    #[serde(default)]
+    pub price: Option<f64>,
+    /// 上网电价（元/kWh）
+    #[serde(default)]
+    pub sell_price: Option<f64>,
+}
+
+impl GridScheduleSlot {
+    pub fn contains(&self, hour: u8) -> bool {
+        if self.start_hour <= self.end_hour {
+            hour >= self.start_hour && hour < self.end_hour
+        } else {
+            hour >= self.start_hour || hour < self.end_hour
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct GridSchedule {
+    #[serde(default)]
+    pub slots: Vec<GridScheduleSlot>,
+}
+
+impl GridSchedule {
+    pub fn from_properties(properties: &HashMap<String, serde_json::Value>) -> Option<Self> {
+        properties
+            .get("grid_schedule")
+            .and_then(|v| serde_json::from_value::<GridSchedule>(v.clone()).ok())
+            .filter(|s| !s.slots.is_empty())
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        for slot in &self.slots {
+            if slot.start_hour > 23 || slot.end_hour > 24 || slot.start_hour == slot.end_hour {
+                return Err(format!("时段无效: {}-{}", slot.start_hour, slot.end_hour));
+            }
+            if slot.import_limit_kw.is_some_and(|v| v < 0.0) || slot.export_limit_kw.is_some_and(|v| v < 0.0) {
+                return Err("功率限值不能为负".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// 某小时生效的时段（多个时段重叠时取第一个）
+    pub fn slot_at(&self, hour: u8) -> Option<&GridScheduleSlot> {
+        self.slots.iter().find(|s| s.contains(hour))
+    }
+
+    /// 24 小时购电电价；任一小时未配置电价时返回 None
+    pub fn tou_prices(&self) -> Option<Vec<f64>> {
+        (0..24u8).map(|h| self.slot_at(h).and_then(|s| s.price)).collect()
+    }
+}
+
+/// 关口功率越限记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GridLimitViolation {
+    pub device_id: String,
+    pub timestamp: f64,
+    pub hour: u8,
+    /// "import" 购电越限 | "export" 上网越限
+    pub direction: String,
+    /// 实际交换功率（kW，正=购电，负=上网）
+    pub p_kw: f64,
+    pub limit_kw: f64,
+}
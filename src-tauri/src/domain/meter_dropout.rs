@@ -0,0 +1,86 @@
+// 电表通信中断模拟配置：配置在 Meter 设备 properties.meter_dropout 中，仅影响落库与 Modbus 对外数据，内部真实序列不受影响
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 计划中断窗口：相对仿真开始的仿真时间 [start_s, end_s)；repeat_every_s 设置时按周期重复（如 86400 表示每天）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScheduledOutage {
+    pub start_s: f64,
+    pub end_s: f64,
+    #[serde(default)]
+    pub repeat_every_s: Option<f64>,
+}
+
+impl ScheduledOutage {
+    pub fn contains(&self, sim_elapsed_s: f64) -> bool {
+        let t = match self.repeat_every_s {
+            Some(period) if period > 0.0 => sim_elapsed_s.rem_euclid(period),
+            _ => sim_elapsed_s,
+        };
+        t >= self.start_s && t < self.end_s
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct MeterDropoutConfig {
+    /// 每步开始一次随机丢数的概率（0~1），0 表示不产生随机缺口
+    #[serde(default)]
+    pub gap_probability: f64,
+    /// 随机缺口持续时长范围（仿真秒）
+    #[serde(default)]
+    pub gap_min_s: f64,
+    #[serde(default)]
+    pub gap_max_s: f64,
+    #[serde(default)]
+    pub scheduled_outages: Vec<ScheduledOutage>,
+}
+
+impl MeterDropoutConfig {
+    pub fn from_properties(properties: &HashMap<String, serde_json::Value>) -> Option<Self> {
+        properties
+            .get("meter_dropout")
+            .and_then(|v| serde_json::from_value::<MeterDropoutConfig>(v.clone()).ok())
+            .filter(|c| !c.is_empty())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.gap_probability <= 0.0 && self.scheduled_outages.is_empty()
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !(0.0..=1.0).contains(&self.gap_probability) {
+            return Err(format!("缺口概率应在 0~1 之间: {}", self.gap_probability));
+        }
+        if self.gap_min_s < 0.0 || self.gap_max_s < self.gap_min_s {
+            return Err(format!("缺口时长范围无效: {}-{}", self.gap_min_s, self.gap_max_s));
+        }
+        for o in &self.scheduled_outages {
+            if o.start_s < 0.0 || o.end_s <= o.start_s {
+                return Err(format!("计划中断窗口无效: {}-{}", o.start_s, o.end_s));
+            }
+            if o.repeat_every_s.is_some_and(|p| p < o.end_s) {
+                return Err("重复周期不能小于中断窗口结束时间".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 一段已发生的数据缺口（ended_at 为 None 表示仍在中断中）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeterDropoutGap {
+    pub meter_id: String,
+    /// "random" | "scheduled"
+    pub reason: String,
+    pub started_at: f64,
+    pub ended_at: Option<f64>,
+    pub dropped_steps: u64,
+}
+
+/// 单表统计
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MeterDropoutStats {
+    pub total_steps: u64,
+    pub dropped_steps: u64,
+    pub gap_count: u64,
+}
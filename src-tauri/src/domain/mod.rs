@@ -4,3 +4,15 @@ pub mod topology;
 pub mod device;
 pub mod simulation;
 pub mod metadata;
+pub mod grid_schedule;
+pub mod preset;
+pub mod meter_dropout;
+pub mod storage_schedule;
+pub mod sign_convention;
+pub mod webhook;
+pub mod random_profile;
+pub mod forecast;
+pub mod device_alias;
+pub mod auth;
+pub mod events;
+pub mod modbus_gateway;
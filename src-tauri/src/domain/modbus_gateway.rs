@@ -0,0 +1,86 @@
+// Modbus 网关模式：与现场站端网关一样只开一个 TCP 端口，按 unit id 把请求转发到各设备的寄存器上下文
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Modbus 从站地址的有效范围（0 为广播，248-255 保留）
+pub const MAX_GATEWAY_UNIT_ID: u8 = 247;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ModbusGatewayConfig {
+    /// 启用后所有 Modbus 设备共用网关端口，忽略各设备自身的 ip/port/unit id
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_gateway_ip")]
+    pub ip: String,
+    #[serde(default = "default_gateway_port")]
+    pub port: u16,
+    /// 设备 id → unit id；未列出的设备按 id 顺序分配未占用的最小 unit id
+    #[serde(default)]
+    pub unit_ids: BTreeMap<String, u8>,
+}
+
+/// 默认只监听本机（与未配置 ip 的设备一致）；对外开放须显式配置监听地址
+fn default_gateway_ip() -> String {
+    "127.0.0.1".to_string()
+}
+
+fn default_gateway_port() -> u16 {
+    502
+}
+
+impl Default for ModbusGatewayConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ip: default_gateway_ip(),
+            port: default_gateway_port(),
+            unit_ids: BTreeMap::new(),
+        }
+    }
+}
+
+impl ModbusGatewayConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.ip.trim().is_empty() {
+            return Err("网关监听地址不能为空".to_string());
+        }
+        if self.port == 0 {
+            return Err("网关端口不能为 0".to_string());
+        }
+        let mut seen: HashMap<u8, &str> = HashMap::new();
+        for (device_id, &unit_id) in &self.unit_ids {
+            if unit_id == 0 || unit_id > MAX_GATEWAY_UNIT_ID {
+                return Err(format!("设备 {} 的 unit id {} 超出范围 1-{}", device_id, unit_id, MAX_GATEWAY_UNIT_ID));
+            }
+            if let Some(other) = seen.insert(unit_id, device_id) {
+                return Err(format!("unit id {} 同时分配给了设备 {} 和 {}", unit_id, other, device_id));
+            }
+        }
+        Ok(())
+    }
+
+    /// 为给定设备确定 unit id：映射表优先，其余设备按 id 排序依次取未占用的最小 unit id
+    /// （映射表中的 unit id 即使对应设备不在拓扑中也保留，避免拓扑变化时其他设备的地址漂移）
+    pub fn assign_unit_ids(&self, device_ids: &[String]) -> Result<HashMap<String, u8>, String> {
+        let mut used: HashSet<u8> = self.unit_ids.values().copied().collect();
+        let mut sorted: Vec<&String> = device_ids.iter().collect();
+        sorted.sort();
+        let mut next: u8 = 1;
+        let mut out = HashMap::new();
+        for device_id in sorted {
+            if let Some(&unit_id) = self.unit_ids.get(device_id) {
+                out.insert(device_id.clone(), unit_id);
+                continue;
+            }
+            while used.contains(&next) {
+                if next == MAX_GATEWAY_UNIT_ID {
+                    return Err(format!("网关 unit id 已用尽，无法为设备 {} 分配", device_id));
+                }
+                next += 1;
+            }
+            used.insert(next);
+            out.insert(device_id.clone(), next);
+        }
+        Ok(out)
+    }
+}
@@ -0,0 +1,344 @@
+// 计算预设：计算步长、时间倍率、求解参数、落库粒度与 Modbus 自动启动，按名称选择
+use crate::domain::simulation::sim_utc_offset_s;
+use serde::{Deserialize, Serialize};
+
+/// 潮流求解参数，None 表示使用内核默认值
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct SolverOptions {
+    /// pandapower 算法："nr" | "iwamoto_nr" | "bfsw" | "gs" | "fdbx" | "fdxb"
+    #[serde(default)]
+    pub algorithm: Option<String>,
+    #[serde(default)]
+    pub max_iteration: Option<u32>,
+    #[serde(default)]
+    pub tolerance_mva: Option<f64>,
+    /// 初值方式："auto" | "flat" | "dc" | "results"（以上一拍结果热启动，首拍或上一拍不收敛时退回 auto）
+    #[serde(default)]
+    pub init: Option<String>,
+}
+
+impl SolverOptions {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(ref a) = self.algorithm {
+            if !["nr", "iwamoto_nr", "bfsw", "gs", "fdbx", "fdxb"].contains(&a.as_str()) {
+                return Err(format!("不支持的求解算法: {}", a));
+            }
+        }
+        if let Some(ref init) = self.init {
+            if !["auto", "flat", "dc", "results"].contains(&init.as_str()) {
+                return Err(format!("不支持的初值方式: {}", init));
+            }
+        }
+        if self.max_iteration == Some(0) {
+            return Err("最大迭代次数必须大于 0".to_string());
+        }
+        if self.tolerance_mva.is_some_and(|t| t <= 0.0) {
+            return Err("收敛精度必须大于 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// 落库聚合方式：sample 取落库步的瞬时值，average 取落库间隔内各步的平均值
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PersistAggregation {
+    #[default]
+    Sample,
+    Average,
+}
+
+/// 各结果消费方的输出间隔（毫秒，None 表示每步），与求解步长解耦；间隔按求解步长取整为步数
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ConsumerRates {
+    /// 落库间隔；未设置时按 persist_every_n_steps
+    #[serde(default)]
+    pub persist_interval_ms: Option<u64>,
+    #[serde(default)]
+    pub persist_aggregation: PersistAggregation,
+    /// 前端事件（设备数据、计算结果更新）推送间隔；告警与错误事件不受限
+    #[serde(default)]
+    pub emit_interval_ms: Option<u64>,
+    /// Modbus 寄存器同步间隔（与设备级 samplingIntervalMs 叠加）
+    #[serde(default)]
+    pub modbus_interval_ms: Option<u64>,
+    /// 事件触发的突发落库；未设置时只按落库间隔输出
+    #[serde(default)]
+    pub burst: Option<BurstLogging>,
+}
+
+impl ConsumerRates {
+    pub fn validate(&self) -> Result<(), String> {
+        if [self.persist_interval_ms, self.emit_interval_ms, self.modbus_interval_ms].contains(&Some(0)) {
+            return Err("输出间隔必须大于 0".to_string());
+        }
+        if let Some(burst) = &self.burst {
+            burst.validate()?;
+        }
+        Ok(())
+    }
+}
+
+/// 突发落库：告警、故障、定时事件等触发时，事件前 pre_event_ms 内被抽稀的步补写入库，
+/// 事件后 post_event_ms 内逐步落库（瞬时值），窗口记录在 burst_windows 表中
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BurstLogging {
+    #[serde(default)]
+    pub pre_event_ms: u64,
+    pub post_event_ms: u64,
+}
+
+impl BurstLogging {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.post_event_ms == 0 {
+            return Err("突发落库的事件后窗口必须大于 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// 自适应计算间隔：按近期每步平均耗时调整计算间隔，使步耗时约占间隔的 target_utilization，
+/// 间隔限制在 [min_interval_ms, max_interval_ms]；每步仿真时间随当前间隔变化
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AdaptiveInterval {
+    pub min_interval_ms: u64,
+    pub max_interval_ms: u64,
+    #[serde(default = "default_target_utilization")]
+    pub target_utilization: f64,
+}
+
+fn default_target_utilization() -> f64 {
+    0.7
+}
+
+impl AdaptiveInterval {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.min_interval_ms == 0 || self.min_interval_ms > self.max_interval_ms {
+            return Err("自适应计算间隔的范围无效".to_string());
+        }
+        if !(self.target_utilization > 0.0 && self.target_utilization <= 1.0) {
+            return Err("目标占用率须在 (0, 1] 之间".to_string());
+        }
+        Ok(())
+    }
+
+    /// 按平均步耗时（毫秒）求目标间隔
+    pub fn target_interval_ms(&self, average_delay_ms: f64) -> u64 {
+        ((average_delay_ms / self.target_utilization).ceil() as u64).clamp(self.min_interval_ms, self.max_interval_ms)
+    }
+}
+
+/// 日电量翻转：仿真时间每跨过一天的翻转时刻，归档并清零日电量（储能日充放电量、光伏今日发电量、设备日电量），
+/// 累计电量不变；电表四象限电量为累计量，不参与翻转
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DailyRollover {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// 翻转时刻：当天第几分钟（0 = 零点）
+    #[serde(default)]
+    pub at_minute: u32,
+    /// 时区偏移（分钟）；未设置时按本机时区
+    #[serde(default)]
+    pub utc_offset_minutes: Option<i32>,
+}
+
+impl Default for DailyRollover {
+    fn default() -> Self {
+        Self { enabled: true, at_minute: 0, utc_offset_minutes: None }
+    }
+}
+
+impl DailyRollover {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.at_minute >= 24 * 60 {
+            return Err(format!("日翻转时刻须在 0~1439 分钟之间: {}", self.at_minute));
+        }
+        if self.utc_offset_minutes.is_some_and(|m| m.abs() > 14 * 60) {
+            return Err("日翻转时区偏移须在 ±14 小时之内".to_string());
+        }
+        Ok(())
+    }
+
+    /// 仿真时间（Unix 秒）所属的日（YYYY-MM-DD）：翻转时刻之前归前一天；未指定偏移时与 sim_seconds_of_day 的日界一致
+    pub fn day_of(&self, timestamp: f64) -> String {
+        let offset_s = match self.utc_offset_minutes {
+            Some(m) => m as f64 * 60.0,
+            None => sim_utc_offset_s(timestamp),
+        };
+        let local = (timestamp + offset_s - self.at_minute as f64 * 60.0).floor() as i64;
+        chrono::DateTime::from_timestamp(local, 0)
+            .map(|d| d.format("%Y-%m-%d").to_string())
+            .unwrap_or_default()
+    }
+}
+
+/// 仿真运行参数：由预设设置，普通启动时为默认值
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RunOptions {
+    /// 时间倍率：每步仿真时间 = 计算步长 × time_scale（影响 SOC/电量积分）
+    pub time_scale: f64,
+    /// 落库粒度：每 N 步写一次数据库（1 = 每步）
+    pub persist_every_n_steps: u32,
+    pub solver_options: SolverOptions,
+    /// 落库、前端事件与 Modbus 同步各自的输出间隔（按启动时的计算步长换算为步数）
+    #[serde(default)]
+    pub consumer_rates: ConsumerRates,
+    /// 自适应计算间隔；未设置时按固定步长
+    #[serde(default)]
+    pub adaptive_interval: Option<AdaptiveInterval>,
+    /// 仿真时钟起点（Unix 秒）；未设置时取启动时刻
+    #[serde(default)]
+    pub sim_start_epoch: Option<f64>,
+    /// 日电量翻转（按仿真时间）
+    #[serde(default)]
+    pub daily_rollover: DailyRollover,
+    /// 自动停止：仿真时钟经过该时长（秒）后停止；未设置时不限
+    #[serde(default)]
+    pub max_duration_secs: Option<f64>,
+    /// 自动停止：完成该步数后停止；未设置时不限
+    #[serde(default)]
+    pub max_steps: Option<u64>,
+}
+
+impl Default for RunOptions {
+    fn default() -> Self {
+        Self {
+            time_scale: 1.0,
+            persist_every_n_steps: 1,
+            solver_options: SolverOptions::default(),
+            consumer_rates: ConsumerRates::default(),
+            adaptive_interval: None,
+            sim_start_epoch: None,
+            daily_rollover: DailyRollover::default(),
+            max_duration_secs: None,
+            max_steps: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CalculationPreset {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub calculation_interval_ms: u64,
+    #[serde(default = "default_time_scale")]
+    pub time_scale: f64,
+    #[serde(default)]
+    pub solver_options: SolverOptions,
+    #[serde(default = "default_persist_every")]
+    pub persist_every_n_steps: u32,
+    #[serde(default)]
+    pub consumer_rates: ConsumerRates,
+    #[serde(default)]
+    pub adaptive_interval: Option<AdaptiveInterval>,
+    /// 启动仿真后自动启动全部设备 Modbus 服务器
+    #[serde(default)]
+    pub auto_start_modbus: bool,
+    #[serde(default = "default_true")]
+    pub remote_control_enabled: bool,
+    /// 内置预设不可删除，仅可被同名用户预设覆盖
+    #[serde(default)]
+    pub builtin: bool,
+}
+
+fn default_time_scale() -> f64 {
+    1.0
+}
+
+fn default_persist_every() -> u32 {
+    1
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl CalculationPreset {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("预设名称不能为空".to_string());
+        }
+        if self.calculation_interval_ms == 0 {
+            return Err("计算步长必须大于 0".to_string());
+        }
+        if self.time_scale <= 0.0 {
+            return Err("时间倍率必须大于 0".to_string());
+        }
+        if self.persist_every_n_steps == 0 {
+            return Err("落库粒度必须大于 0".to_string());
+        }
+        self.consumer_rates.validate()?;
+        if let Some(adaptive) = &self.adaptive_interval {
+            adaptive.validate()?;
+        }
+        self.solver_options.validate()
+    }
+
+    pub fn run_options(&self) -> RunOptions {
+        RunOptions {
+            time_scale: self.time_scale,
+            persist_every_n_steps: self.persist_every_n_steps,
+            solver_options: self.solver_options.clone(),
+            consumer_rates: self.consumer_rates.clone(),
+            adaptive_interval: self.adaptive_interval.clone(),
+            sim_start_epoch: None,
+            daily_rollover: DailyRollover::default(),
+            max_duration_secs: None,
+            max_steps: None,
+        }
+    }
+}
+
+/// 内置预设：快速演示、精确研究、长时间浸泡测试
+pub fn builtin_presets() -> Vec<CalculationPreset> {
+    vec![
+        CalculationPreset {
+            name: "fast demo".to_string(),
+            description: "快速演示：500ms 步长、10 倍时间倍率，自动启动 Modbus".to_string(),
+            calculation_interval_ms: 500,
+            time_scale: 10.0,
+            solver_options: SolverOptions {
+                max_iteration: Some(10),
+                ..Default::default()
+            },
+            persist_every_n_steps: 10,
+            consumer_rates: ConsumerRates::default(),
+            adaptive_interval: None,
+            auto_start_modbus: true,
+            remote_control_enabled: true,
+            builtin: true,
+        },
+        CalculationPreset {
+            name: "precision study".to_string(),
+            description: "精确研究：1s 步长、实时，严格收敛精度并逐步落库".to_string(),
+            calculation_interval_ms: 1000,
+            time_scale: 1.0,
+            solver_options: SolverOptions {
+                algorithm: Some("nr".to_string()),
+                max_iteration: Some(50),
+                tolerance_mva: Some(1e-9),
+                init: None,
+            },
+            persist_every_n_steps: 1,
+            consumer_rates: ConsumerRates::default(),
+            adaptive_interval: None,
+            auto_start_modbus: false,
+            remote_control_enabled: true,
+            builtin: true,
+        },
+        CalculationPreset {
+            name: "overnight soak".to_string(),
+            description: "长时间浸泡：5s 步长、实时，稀疏落库以控制数据库体积".to_string(),
+            calculation_interval_ms: 5000,
+            time_scale: 1.0,
+            solver_options: SolverOptions::default(),
+            persist_every_n_steps: 12,
+            consumer_rates: ConsumerRates::default(),
+            adaptive_interval: None,
+            auto_start_modbus: true,
+            remote_control_enabled: true,
+            builtin: true,
+        },
+    ]
+}
@@ -0,0 +1,135 @@
+// 随机数据源生成模型：random_data 模式下按设备类型选择更接近实际的功率曲线，功率范围仍由 min_power/max_power 给出
+use crate::domain::topology::DeviceType;
+use serde::{Deserialize, Serialize};
+
+fn default_theta_per_s() -> f64 {
+    0.01
+}
+fn default_volatility() -> f64 {
+    0.1
+}
+fn default_sunrise_hour() -> f64 {
+    6.0
+}
+fn default_sunset_hour() -> f64 {
+    18.0
+}
+fn default_clouds_per_hour() -> f64 {
+    2.0
+}
+fn default_cloud_depth() -> f64 {
+    0.6
+}
+fn default_cloud_duration_s() -> f64 {
+    300.0
+}
+fn default_sessions_per_hour() -> f64 {
+    1.0
+}
+fn default_session_minutes() -> f64 {
+    45.0
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RandomProfile {
+    /// 区间内均匀分布白噪声（由内核生成，原有行为）
+    Uniform,
+    /// Ornstein–Uhlenbeck 过程：围绕区间中点均值回归的有色噪声，适合负荷
+    OuNoise {
+        /// 均值回归速率（1/s）
+        #[serde(default = "default_theta_per_s")]
+        theta_per_s: f64,
+        /// 稳态标准差占功率区间的比例（0~1）
+        #[serde(default = "default_volatility")]
+        volatility: f64,
+    },
+    /// 晴空曲线叠加随机云遮：日出到日落按正弦出力，峰值为 max_power，夜间为 min_power；
+    /// 日出/日落为本地时刻（小时），按本步仿真时间取日内时段，同一种子与起始仿真时间的运行可复现
+    ClearSkyPv {
+        #[serde(default = "default_sunrise_hour")]
+        sunrise_hour: f64,
+        #[serde(default = "default_sunset_hour")]
+        sunset_hour: f64,
+        /// 每小时平均出现云遮次数
+        #[serde(default = "default_clouds_per_hour")]
+        clouds_per_hour: f64,
+        /// 云遮时出力削减比例（0~1）
+        #[serde(default = "default_cloud_depth")]
+        cloud_depth: f64,
+        /// 单次云遮平均持续时长（秒）
+        #[serde(default = "default_cloud_duration_s")]
+        cloud_duration_s: f64,
+    },
+    /// 充电块：车辆随机到达后以 max_power 连续充电一段时间，空闲时为 min_power
+    ChargingBlocks {
+        /// 每小时平均到达车辆数
+        #[serde(default = "default_sessions_per_hour")]
+        sessions_per_hour: f64,
+        /// 单次充电平均时长（分钟）
+        #[serde(default = "default_session_minutes")]
+        mean_session_minutes: f64,
+    },
+}
+
+impl RandomProfile {
+    /// 设备类型的默认生成模型（前端未指定时使用）
+    pub fn default_for(device_type: &DeviceType) -> Self {
+        match device_type {
+            DeviceType::Pv => RandomProfile::ClearSkyPv {
+                sunrise_hour: default_sunrise_hour(),
+                sunset_hour: default_sunset_hour(),
+                clouds_per_hour: default_clouds_per_hour(),
+                cloud_depth: default_cloud_depth(),
+                cloud_duration_s: default_cloud_duration_s(),
+            },
+            DeviceType::Charger => RandomProfile::ChargingBlocks {
+                sessions_per_hour: default_sessions_per_hour(),
+                mean_session_minutes: default_session_minutes(),
+            },
+            DeviceType::Load => RandomProfile::OuNoise {
+                theta_per_s: default_theta_per_s(),
+                volatility: default_volatility(),
+            },
+            _ => RandomProfile::Uniform,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            RandomProfile::Uniform => Ok(()),
+            RandomProfile::OuNoise { theta_per_s, volatility } => {
+                if *theta_per_s <= 0.0 {
+                    return Err(format!("均值回归速率必须大于 0: {}", theta_per_s));
+                }
+                if !(0.0..=1.0).contains(volatility) {
+                    return Err(format!("波动率应在 0~1 之间: {}", volatility));
+                }
+                Ok(())
+            }
+            RandomProfile::ClearSkyPv { sunrise_hour, sunset_hour, clouds_per_hour, cloud_depth, cloud_duration_s } => {
+                if !(0.0..24.0).contains(sunrise_hour) || !(0.0..=24.0).contains(sunset_hour) || sunset_hour <= sunrise_hour {
+                    return Err(format!("日出日落时间无效: {}-{}", sunrise_hour, sunset_hour));
+                }
+                if *clouds_per_hour < 0.0 || *cloud_duration_s <= 0.0 {
+                    return Err("云遮频率不能为负，持续时长必须大于 0".to_string());
+                }
+                if !(0.0..=1.0).contains(cloud_depth) {
+                    return Err(format!("云遮削减比例应在 0~1 之间: {}", cloud_depth));
+                }
+                Ok(())
+            }
+            RandomProfile::ChargingBlocks { sessions_per_hour, mean_session_minutes } => {
+                if *sessions_per_hour < 0.0 || *mean_session_minutes <= 0.0 {
+                    return Err("到达频率不能为负，充电时长必须大于 0".to_string());
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// 是否由 Rust 端生成并下发（Uniform 仍由内核生成）
+    pub fn is_engine_generated(&self) -> bool {
+        !matches!(self, RandomProfile::Uniform)
+    }
+}
@@ -0,0 +1,77 @@
+// 有功/无功符号约定：项目级设置，按设备类型选择发电机约定或负荷约定，统一用于落库、Modbus 编码与分析
+use crate::domain::topology::DeviceType;
+use serde::{Deserialize, Serialize};
+
+/// 符号约定
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerSign {
+    /// 负荷约定：正=吸收功率（用电、充电、从电网购电）
+    Load,
+    /// 发电机约定：正=发出功率（发电、放电、向电网送电）
+    Generator,
+}
+
+/// 各类功率设备的符号约定；默认值与计算内核（pandapower）原生约定一致，即不做任何翻转
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SignConvention {
+    pub load: PowerSign,
+    pub charger: PowerSign,
+    pub pv: PowerSign,
+    pub storage: PowerSign,
+    /// 外部电网按「微电网侧」理解：负荷约定下正=购电，发电机约定下正=上网
+    pub ext_grid: PowerSign,
+}
+
+impl Default for SignConvention {
+    fn default() -> Self {
+        Self {
+            load: PowerSign::Load,
+            charger: PowerSign::Load,
+            pv: PowerSign::Generator,
+            storage: PowerSign::Load,
+            ext_grid: PowerSign::Load,
+        }
+    }
+}
+
+impl SignConvention {
+    /// 计算内核结果的原生约定（pandapower：负荷/充电桩/储能正=吸收，光伏正=发出，外部电网 p_mw 正=向微电网供电即购电）
+    pub fn native() -> Self {
+        Self::default()
+    }
+
+    fn sign_for(&self, device_type: &DeviceType) -> Option<PowerSign> {
+        match device_type {
+            DeviceType::Load => Some(self.load),
+            DeviceType::Charger => Some(self.charger),
+            DeviceType::Pv => Some(self.pv),
+            DeviceType::Storage => Some(self.storage),
+            DeviceType::ExternalGrid => Some(self.ext_grid),
+            _ => None,
+        }
+    }
+
+    /// 原生值转换到本约定的系数（1 或 -1）；非功率设备（母线/线路/变压器/开关/电表）恒为 1
+    pub fn factor(&self, device_type: &DeviceType) -> f64 {
+        match (self.sign_for(device_type), Self::native().sign_for(device_type)) {
+            (Some(a), Some(b)) if a != b => -1.0,
+            _ => 1.0,
+        }
+    }
+
+    /// 转换 (P, Q)：无功与有功采用同一约定
+    pub fn apply(&self, device_type: &DeviceType, p: Option<f64>, q: Option<f64>) -> (Option<f64>, Option<f64>) {
+        let f = self.factor(device_type);
+        (p.map(|v| v * f), q.map(|v| v * f))
+    }
+
+    /// 从 from 约定转换到 self 约定的系数
+    pub fn factor_from(&self, from: &SignConvention, device_type: &DeviceType) -> f64 {
+        self.factor(device_type) * from.factor(device_type)
+    }
+
+    pub fn is_native(&self) -> bool {
+        *self == Self::native()
+    }
+}
@@ -1,7 +1,7 @@
 // 仿真状态和工作模式
 use crate::domain::device::WorkMode;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -37,6 +37,108 @@ impl PartialEq for SimulationError {
 
 impl Eq for SimulationError {}
 
+impl SimulationError {
+    /// 从 Python 内核返回的错误对象构造：字段 "type" 重命名为 "error_type"，
+    /// 浮点秒时间戳转换为 u64，缺失时间戳时取当前时间
+    pub fn from_kernel_value(value: &serde_json::Value) -> Option<Self> {
+        let mut error_obj = value.clone();
+        if let serde_json::Value::Object(ref mut map) = error_obj {
+            if let Some(type_value) = map.remove("type") {
+                map.insert("error_type".to_string(), type_value);
+            }
+            let timestamp = map
+                .get("timestamp")
+                .and_then(|v| v.as_f64())
+                .map(|t| t as u64)
+                .unwrap_or_else(|| {
+                    SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .unwrap()
+                        .as_secs()
+                });
+            map.insert("timestamp".to_string(), serde_json::json!(timestamp));
+            if !map.contains_key("details") {
+                map.insert("details".to_string(), serde_json::json!({}));
+            }
+        }
+        serde_json::from_value::<SimulationError>(error_obj)
+            .map_err(|err| {
+                eprintln!("解析错误对象失败: {} - 原始数据: {}", err, serde_json::to_string(value).unwrap_or_default());
+            })
+            .ok()
+    }
+
+    /// 错误涉及的全部设备ID：优先 details.device_ids（内核诊断归属），否则为 device_id
+    pub fn related_device_ids(&self) -> Vec<String> {
+        let from_details: Vec<String> = self
+            .details
+            .get("device_ids")
+            .and_then(|v| v.as_array())
+            .map(|arr| arr.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+            .unwrap_or_default();
+        if !from_details.is_empty() {
+            return from_details;
+        }
+        self.device_id.iter().cloned().collect()
+    }
+}
+
+/// 汇总一组错误涉及的设备ID（去重、保持顺序），随 simulation-errors-update 一并下发供前端高亮
+pub fn error_device_ids(errors: &[SimulationError]) -> Vec<String> {
+    let mut ids: Vec<String> = Vec::new();
+    for err in errors {
+        for id in err.related_device_ids() {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+    }
+    ids
+}
+
+/// 仿真时钟：起点（Unix 秒）+ 累计仿真时长。每步按 计算步长 × 时间倍率 前进，暂停时不前进，
+/// 落库时间戳与内核结果时间戳均取仿真时间
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct SimClock {
+    pub start_epoch: f64,
+    pub elapsed_s: f64,
+}
+
+impl SimClock {
+    pub fn new(start_epoch: f64) -> Self {
+        Self { start_epoch, elapsed_s: 0.0 }
+    }
+
+    /// 当前仿真时间（Unix 秒）
+    pub fn now(&self) -> f64 {
+        self.start_epoch + self.elapsed_s
+    }
+
+    pub fn advance(&mut self, dt_s: f64) {
+        self.elapsed_s += dt_s;
+    }
+}
+
+/// 仿真时间（Unix 秒）在本机时区下的当日秒数（0..86400）；分时电价、日内曲线等时段逻辑均按仿真时间取时段，不读墙钟
+pub fn sim_seconds_of_day(unix: f64) -> f64 {
+    (unix + sim_utc_offset_s(unix)).rem_euclid(86_400.0)
+}
+
+/// 仿真时间（Unix 秒）时刻的本机时区 UTC 偏移（秒），时段与日界划分共用
+pub fn sim_utc_offset_s(unix: f64) -> f64 {
+    use chrono::{Offset, TimeZone};
+    chrono::Local
+        .timestamp_opt(unix.floor() as i64, 0)
+        .single()
+        .map(|d| d.offset().fix().local_minus_utc() as f64)
+        .unwrap_or(0.0)
+}
+
+/// 仿真时间在本机时区下的小时（0..24）
+pub fn sim_hour_of_day(unix: f64) -> u8 {
+    ((sim_seconds_of_day(unix) / 3600.0) as u8).min(23)
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SimulationStatus {
     pub state: SimulationState,
@@ -45,6 +147,23 @@ pub struct SimulationStatus {
     pub calculation_count: u64,
     /// 每步平均耗时（毫秒）：一次仿真步（get_status + get_errors + perform_calculation + 结果处理）的耗时均值，用于判断是否跟得上计算间隔
     pub average_delay: f64,
+    /// 当前计算间隔（毫秒）：固定步长时为启动步长，自适应时随步耗时调整
+    #[serde(default)]
+    pub current_interval_ms: u64,
+    /// 近 100 步实际步间隔相对计算间隔的偏差（毫秒）：平均值与最大值
+    #[serde(default)]
+    pub jitter_mean_ms: f64,
+    #[serde(default)]
+    pub jitter_max_ms: f64,
+    /// 步耗时超过计算间隔的累计次数
+    #[serde(default)]
+    pub overrun_count: u64,
+    /// 超时步期间跳过的定时节拍累计数（跳过的节拍不补算，用于判断计算间隔是否过小）
+    #[serde(default)]
+    pub missed_steps: u64,
+    /// 当前仿真时间（Unix 秒）：最近一步的落库时间戳，未开始计算时为空
+    #[serde(default)]
+    pub sim_time: Option<f64>,
     pub errors: Vec<SimulationError>,
     /// 暂停开始时刻（Unix 秒），用于累计暂停时长
     #[serde(skip)]
@@ -62,6 +181,12 @@ impl SimulationStatus {
             elapsed_time: 0,
             calculation_count: 0,
             average_delay: 0.0,
+            current_interval_ms: 0,
+            jitter_mean_ms: 0.0,
+            jitter_max_ms: 0.0,
+            overrun_count: 0,
+            missed_steps: 0,
+            sim_time: None,
             errors: Vec::new(),
             pause_started_at: None,
             total_paused_secs: 0,
@@ -78,6 +203,11 @@ impl SimulationStatus {
         );
         self.elapsed_time = 0;
         self.calculation_count = 0;
+        self.jitter_mean_ms = 0.0;
+        self.jitter_max_ms = 0.0;
+        self.overrun_count = 0;
+        self.missed_steps = 0;
+        self.sim_time = None;
         self.pause_started_at = None;
         self.total_paused_secs = 0;
     }
@@ -121,15 +251,15 @@ impl Default for SimulationStatus {
 pub type DeviceWorkModes = HashMap<String, WorkMode>;
 
 /// 储能设备独立维护的状态（pandapower 仅返回有功/无功功率）
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct StorageState {
     /// 额定容量 kWh（从拓扑 properties.capacity / max_e_mwh 解析，仅首次初始化）
     pub capacity_kwh: f64,
-    /// 当前能量 kWh（积分功率得到，用于计算 SOC）
+    /// 当前能量 kWh（积分功率得到，计入充放电效率与自放电，用于计算 SOC）
     pub energy_kwh: f64,
-    /// SOC 百分比 0–100（由 energy_kwh / capacity_kwh 计算）
+    /// SOC 百分比 0–100（由 energy_kwh / effective_capacity_kwh 计算）
     pub soc_percent: f64,
-    /// 日充电量 kWh（仿真步内积分，日重置可后续扩展）
+    /// 日充电量 kWh（仿真步内积分，日电量翻转时清零并归档）
     pub daily_charge_kwh: f64,
     /// 日放电量 kWh
     pub daily_discharge_kwh: f64,
@@ -137,4 +267,641 @@ pub struct StorageState {
     pub total_charge_kwh: f64,
     /// 累计放电总量 kWh
     pub total_discharge_kwh: f64,
+    /// 等效满充放循环次数（并网侧充放电吞吐量 / 2 倍额定容量）
+    #[serde(default)]
+    pub equivalent_full_cycles: f64,
+    /// 运行时长 h（仿真时间，用于日历衰减）
+    #[serde(default)]
+    pub operating_hours: f64,
+    /// 容量衰减百分比（循环衰减 + 日历衰减）
+    #[serde(default)]
+    pub capacity_fade_percent: f64,
+    /// 衰减后的有效容量 kWh（SOC 以此为满充基准，同步到 Modbus 额定容量 IR 39）
+    #[serde(default)]
+    pub effective_capacity_kwh: f64,
+}
+
+/// 功率设备电量计数（引擎按每步功率积分，不依赖电表布置）：
+/// 输入 = 从电网流入设备（负荷/充电桩用电、储能充电、外部电网购电、光伏倒吸），输出 = 设备送入电网
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeviceEnergyCounters {
+    /// 日电量所属日期（按日翻转配置划分的仿真日 YYYY-MM-DD，关闭翻转时为空），跨日时日电量清零
+    pub day: String,
+    pub daily_import_kwh: f64,
+    pub daily_export_kwh: f64,
+    pub total_import_kwh: f64,
+    pub total_export_kwh: f64,
+}
+
+impl DeviceEnergyCounters {
+    /// 累加一步电量；consumed_kw 为正表示从电网流入设备
+    pub fn accumulate(&mut self, day: &str, consumed_kw: f64, dt_h: f64) {
+        if self.day != day {
+            self.day = day.to_string();
+            self.daily_import_kwh = 0.0;
+            self.daily_export_kwh = 0.0;
+        }
+        let energy = consumed_kw * dt_h;
+        if energy > 0.0 {
+            self.daily_import_kwh += energy;
+            self.total_import_kwh += energy;
+        } else if energy < 0.0 {
+            self.daily_export_kwh += -energy;
+            self.total_export_kwh += -energy;
+        }
+    }
+}
+
+/// 仿真运行中设备属性的修改来源："modbus" 远程指令 | "command" 前端/接口命令 | "switch" 开关操作 | "schedule" 定时事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PropertyChangeRecord {
+    pub source: String,
+    pub changed_at: f64,
+}
+
+/// 单个属性相对于启动时拓扑的偏移
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PropertyDrift {
+    pub key: String,
+    /// 启动时加载的值，None 表示原拓扑中无此属性
+    pub original: Option<serde_json::Value>,
+    pub current: Option<serde_json::Value>,
+    pub source: Option<String>,
+    pub changed_at: Option<f64>,
+}
+
+/// 设备属性偏移汇总（仅包含存在差异的设备）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DevicePropertyDrift {
+    pub device_id: String,
+    pub device_name: String,
+    pub changes: Vec<PropertyDrift>,
+}
+
+/// 拓扑预加载结果：内核已构建网络缓存，随后启动可立即步进
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopologyPreloadResult {
+    /// 内核已缓存相同结构的网络，本次无需重建
+    pub already_warm: bool,
+    /// 是否完成一次收敛的试算潮流
+    pub warmed_up: bool,
+    pub elapsed_ms: u64,
+    /// 适配器/试算产生的警告
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
+
+/// 孤岛拆分求解中单个孤岛的结果摘要
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IslandSnapshotSummary {
+    pub index: usize,
+    pub device_count: usize,
+    pub converged: bool,
+    pub errors: Vec<SimulationError>,
+}
+
+/// 按孤岛拆分、多内核并行求解后的合并结果；devices 中各表的键为 "孤岛序号:原索引"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartitionedSnapshotResult {
+    pub converged: bool,
+    pub kernels_used: usize,
+    pub elapsed_ms: u64,
+    pub islands: Vec<IslandSnapshotSummary>,
+    pub devices: serde_json::Value,
+}
+
+/// 蒙特卡洛/多工况快照中单个样本的关键指标
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotSampleSummary {
+    pub index: usize,
+    pub converged: bool,
+    pub min_vm_pu: Option<f64>,
+    pub max_vm_pu: Option<f64>,
+    /// 线路与变压器中的最大负载率（%）
+    pub max_loading_percent: Option<f64>,
+    pub error: Option<String>,
+}
+
+/// 多工况快照汇总：各样本指标与全体样本的极值
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotBatchResult {
+    pub kernels_used: usize,
+    pub elapsed_ms: u64,
+    pub converged_count: usize,
+    pub min_vm_pu: Option<f64>,
+    pub max_vm_pu: Option<f64>,
+    pub max_loading_percent: Option<f64>,
+    pub samples: Vec<SnapshotSampleSummary>,
+}
+
+/// 潮流参数扫描结果：combinations[i] 为第 i 个样本的参数取值（"设备 ID.属性" -> 取值）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotSweepResult {
+    pub combinations: Vec<BTreeMap<String, f64>>,
+    #[serde(flatten)]
+    pub batch: SnapshotBatchResult,
+}
+
+/// N-1 预想故障判定限值
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ContingencyLimits {
+    /// 线路/变压器负载率上限（%）
+    #[serde(default = "default_max_loading_percent")]
+    pub max_loading_percent: f64,
+    #[serde(default = "default_min_vm_pu")]
+    pub min_vm_pu: f64,
+    #[serde(default = "default_max_vm_pu")]
+    pub max_vm_pu: f64,
+}
+
+fn default_max_loading_percent() -> f64 {
+    100.0
+}
+
+fn default_min_vm_pu() -> f64 {
+    0.95
+}
+
+fn default_max_vm_pu() -> f64 {
+    1.05
+}
+
+impl Default for ContingencyLimits {
+    fn default() -> Self {
+        Self {
+            max_loading_percent: default_max_loading_percent(),
+            min_vm_pu: default_min_vm_pu(),
+            max_vm_pu: default_max_vm_pu(),
+        }
+    }
+}
+
+/// 支路过载
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BranchOverload {
+    pub device_id: String,
+    pub name: String,
+    /// "line" | "transformer"
+    pub element_type: String,
+    pub loading_percent: f64,
+}
+
+/// 母线电压越限
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BusVoltageViolation {
+    pub device_id: String,
+    pub name: String,
+    pub vm_pu: f64,
+    /// "low" | "high"
+    pub kind: String,
+}
+
+/// 单个预想故障（或基态）的潮流结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContingencyCase {
+    /// 退出运行的线路/变压器；基态为 None
+    pub outage_device_id: Option<String>,
+    pub outage_name: Option<String>,
+    pub outage_type: Option<String>,
+    pub converged: bool,
+    pub overloads: Vec<BranchOverload>,
+    pub voltage_violations: Vec<BusVoltageViolation>,
+    /// 失电（无电压结果）的母线，通常为辐射网末端被切除
+    pub isolated_buses: Vec<String>,
+    pub min_vm_pu: Option<f64>,
+    pub max_vm_pu: Option<f64>,
+    pub max_loading_percent: Option<f64>,
+    pub errors: Vec<String>,
+}
+
+impl ContingencyCase {
+    /// 是否存在越限、失电或不收敛
+    pub fn is_critical(&self) -> bool {
+        !self.converged
+            || !self.overloads.is_empty()
+            || !self.voltage_violations.is_empty()
+            || !self.isolated_buses.is_empty()
+    }
+}
+
+/// N-1 预想故障分析报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContingencyReport {
+    pub limits: ContingencyLimits,
+    /// 并行求解使用的内核数（1 表示经主内核逐个计算）
+    #[serde(default)]
+    pub kernels_used: usize,
+    pub elapsed_ms: u64,
+    pub base_case: ContingencyCase,
+    /// 逐一退出各线路/变压器的结果（按设备 ID 排序）
+    pub contingencies: Vec<ContingencyCase>,
+    /// 存在越限、失电或不收敛的预想故障数
+    pub critical_count: usize,
+}
+
+/// 可单独清零的累计量分组（运行中清零，无需重启仿真）
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum CounterGroup {
+    /// 电表四象限电量与组合有功总电能（Modbus IR 7–11）
+    MeterEnergy,
+    /// 储能日充电量/日放电量
+    StorageDaily,
+    /// 储能累计充电/放电总量
+    StorageTotal,
+    /// 光伏今日发电量（Modbus IR 5003）
+    PvDailyGeneration,
+    /// 光伏总发电量（Modbus IR 5004）
+    PvTotalGeneration,
+    /// 功率设备日/累计输入输出电量
+    DeviceEnergy,
+    /// 限值 KPI 与设定值限幅计数；未指定设备时同时清空预测精度统计
+    KpiWindows,
+}
+
+impl CounterGroup {
+    pub fn all() -> Vec<CounterGroup> {
+        vec![
+            CounterGroup::MeterEnergy,
+            CounterGroup::StorageDaily,
+            CounterGroup::StorageTotal,
+            CounterGroup::PvDailyGeneration,
+            CounterGroup::PvTotalGeneration,
+            CounterGroup::DeviceEnergy,
+            CounterGroup::KpiWindows,
+        ]
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CounterGroup::MeterEnergy => "meter_energy",
+            CounterGroup::StorageDaily => "storage_daily",
+            CounterGroup::StorageTotal => "storage_total",
+            CounterGroup::PvDailyGeneration => "pv_daily_generation",
+            CounterGroup::PvTotalGeneration => "pv_total_generation",
+            CounterGroup::DeviceEnergy => "device_energy",
+            CounterGroup::KpiWindows => "kpi_windows",
+        }
+    }
+}
+
+/// 一组累计量的清零结果：实际被清零的设备
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CounterResetResult {
+    pub group: CounterGroup,
+    pub device_ids: Vec<String>,
+}
+
+/// 电动汽车充电会话的结束状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EvSessionStatus {
+    /// 离开前已充满需求电量
+    Completed,
+    /// 到离开时间仍未充满
+    Unmet,
+    /// 到达时所有充电枪均被占用，未接入
+    Rejected,
+    /// 仿真停止时仍在接入中
+    Interrupted,
+}
+
+impl EvSessionStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EvSessionStatus::Completed => "completed",
+            EvSessionStatus::Unmet => "unmet",
+            EvSessionStatus::Rejected => "rejected",
+            EvSessionStatus::Interrupted => "interrupted",
+        }
+    }
+}
+
+/// 一次电动汽车充电会话（仿真库 ev_sessions 表一行）；时间均为仿真时间（Unix 秒）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvSessionRecord {
+    pub session_id: String,
+    pub device_id: String,
+    /// 充电枪序号（从 1 开始）；被拒绝的会话为 None
+    pub gun: Option<u32>,
+    pub arrival_at: f64,
+    /// 实际离开时间（被拒绝时等于到达时间）
+    pub departure_at: f64,
+    /// 充满时间（未充满为 None）
+    pub charged_at: Option<f64>,
+    pub energy_requested_kwh: f64,
+    pub energy_delivered_kwh: f64,
+    pub peak_power_kw: f64,
+    pub status: EvSessionStatus,
+}
+
+/// Python 内核看门狗：空闲时定期 ping 内核，连续失败达到阈值推送 python-kernel-unhealthy，并可按退避间隔自动重启进程
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct KernelWatchdogConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// 探测间隔（毫秒）
+    #[serde(default = "default_watchdog_interval_ms")]
+    pub interval_ms: u64,
+    /// 单次 ping 超时（毫秒）
+    #[serde(default = "default_watchdog_ping_timeout_ms")]
+    pub ping_timeout_ms: u64,
+    /// 连续失败多少次判定为不健康
+    #[serde(default = "default_watchdog_failure_threshold")]
+    pub failure_threshold: u32,
+    #[serde(default = "default_true")]
+    pub auto_restart: bool,
+    /// 重启失败后的退避：首次间隔，此后逐次翻倍至上限（毫秒）
+    #[serde(default = "default_watchdog_backoff_initial_ms")]
+    pub backoff_initial_ms: u64,
+    #[serde(default = "default_watchdog_backoff_max_ms")]
+    pub backoff_max_ms: u64,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_watchdog_interval_ms() -> u64 {
+    5000
+}
+
+fn default_watchdog_ping_timeout_ms() -> u64 {
+    3000
+}
+
+fn default_watchdog_failure_threshold() -> u32 {
+    3
+}
+
+fn default_watchdog_backoff_initial_ms() -> u64 {
+    2000
+}
+
+fn default_watchdog_backoff_max_ms() -> u64 {
+    60_000
+}
+
+impl Default for KernelWatchdogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_ms: default_watchdog_interval_ms(),
+            ping_timeout_ms: default_watchdog_ping_timeout_ms(),
+            failure_threshold: default_watchdog_failure_threshold(),
+            auto_restart: true,
+            backoff_initial_ms: default_watchdog_backoff_initial_ms(),
+            backoff_max_ms: default_watchdog_backoff_max_ms(),
+        }
+    }
+}
+
+impl KernelWatchdogConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.interval_ms < 500 {
+            return Err("探测间隔不能小于 500 ms".to_string());
+        }
+        if self.ping_timeout_ms == 0 || self.failure_threshold == 0 {
+            return Err("ping 超时与失败阈值必须大于 0".to_string());
+        }
+        if self.backoff_initial_ms == 0 || self.backoff_max_ms < self.backoff_initial_ms {
+            return Err("退避间隔无效：首次间隔须大于 0 且不大于上限".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Python 内核日志级别（低于设定级别的行不写入日志文件）
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum KernelLogLevel {
+    Debug,
+    #[default]
+    Info,
+    Warning,
+    Error,
+}
+
+impl KernelLogLevel {
+    /// Python logging 级别名
+    pub fn as_python(&self) -> &'static str {
+        match self {
+            KernelLogLevel::Debug => "DEBUG",
+            KernelLogLevel::Info => "INFO",
+            KernelLogLevel::Warning => "WARNING",
+            KernelLogLevel::Error => "ERROR",
+        }
+    }
+}
+
+/// Python 内核日志：记录级别与 python-kernel.log 的滚动（超过上限时改名为 .1、.2…，只保留指定个数的旧文件）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct KernelLogConfig {
+    #[serde(default)]
+    pub level: KernelLogLevel,
+    /// 单个日志文件上限（KB）
+    #[serde(default = "default_kernel_log_max_size_kb")]
+    pub max_size_kb: u64,
+    /// 保留的旧日志文件个数（0 表示超限时直接清空）
+    #[serde(default = "default_kernel_log_max_backups")]
+    pub max_backups: u32,
+}
+
+fn default_kernel_log_max_size_kb() -> u64 {
+    5 * 1024
+}
+
+fn default_kernel_log_max_backups() -> u32 {
+    3
+}
+
+impl Default for KernelLogConfig {
+    fn default() -> Self {
+        Self {
+            level: KernelLogLevel::default(),
+            max_size_kb: default_kernel_log_max_size_kb(),
+            max_backups: default_kernel_log_max_backups(),
+        }
+    }
+}
+
+impl KernelLogConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_size_kb < 64 {
+            return Err("日志文件上限不能小于 64 KB".to_string());
+        }
+        if self.max_backups > 20 {
+            return Err("保留的旧日志文件不能超过 20 个".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// 单个内核方法的调用策略：超时与失败重试。只对超时、写入失败与进程重启导致的失败重试，
+/// 内核返回的 JSON-RPC 错误不重试；重试会重新执行请求，只应为可重复执行的方法开启
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct KernelRpcPolicy {
+    /// 超时（毫秒）；None 时使用默认超时
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+    #[serde(default)]
+    pub retries: u32,
+    /// 两次尝试之间的等待（毫秒）
+    #[serde(default = "default_rpc_retry_delay_ms")]
+    pub retry_delay_ms: u64,
+}
+
+impl KernelRpcPolicy {
+    fn with_timeout(timeout_ms: u64) -> Self {
+        Self { timeout_ms: Some(timeout_ms), retries: 0, retry_delay_ms: default_rpc_retry_delay_ms() }
+    }
+}
+
+/// 内核 JSON-RPC 超时表：方法名 -> 调用策略，未列出的方法使用默认超时且不重试
+/// （看门狗探测等显式指定超时的调用不受此表影响）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct KernelRpcConfig {
+    /// 默认超时（毫秒）
+    #[serde(default = "default_rpc_timeout_ms")]
+    pub default_timeout_ms: u64,
+    /// 用户配置按方法名覆盖默认策略，未列出的方法保留默认超时
+    #[serde(default = "default_rpc_methods", deserialize_with = "deserialize_rpc_methods")]
+    pub methods: HashMap<String, KernelRpcPolicy>,
+}
+
+fn default_rpc_timeout_ms() -> u64 {
+    10_000
+}
+
+fn default_rpc_retry_delay_ms() -> u64 {
+    500
+}
+
+fn default_rpc_methods() -> HashMap<String, KernelRpcPolicy> {
+    // 设置拓扑首次加载 pandapower 较慢；大电网的单步潮流计算也可能超过默认超时
+    HashMap::from([
+        ("simulation.set_topology".to_string(), KernelRpcPolicy::with_timeout(60_000)),
+        ("simulation.perform_calculation".to_string(), KernelRpcPolicy::with_timeout(60_000)),
+        ("power.calculate".to_string(), KernelRpcPolicy::with_timeout(60_000)),
+    ])
+}
+
+fn deserialize_rpc_methods<'de, D>(deserializer: D) -> Result<HashMap<String, KernelRpcPolicy>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let user = HashMap::<String, KernelRpcPolicy>::deserialize(deserializer)?;
+    let mut methods = default_rpc_methods();
+    methods.extend(user);
+    Ok(methods)
+}
+
+impl Default for KernelRpcConfig {
+    fn default() -> Self {
+        Self { default_timeout_ms: default_rpc_timeout_ms(), methods: default_rpc_methods() }
+    }
+}
+
+impl KernelRpcConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        let valid_timeout = |ms: u64| (100..=3_600_000).contains(&ms);
+        if !valid_timeout(self.default_timeout_ms) {
+            return Err("默认超时须在 100 ms 至 1 小时之间".to_string());
+        }
+        for (method, policy) in &self.methods {
+            if method.trim().is_empty() {
+                return Err("方法名不能为空".to_string());
+            }
+            if policy.timeout_ms.is_some_and(|ms| !valid_timeout(ms)) {
+                return Err(format!("{} 的超时须在 100 ms 至 1 小时之间", method));
+            }
+            if policy.retries > 10 {
+                return Err(format!("{} 的重试次数不能超过 10 次", method));
+            }
+        }
+        Ok(())
+    }
+
+    /// 方法的超时（毫秒）、重试次数与重试间隔（毫秒）
+    pub fn resolve(&self, method: &str) -> (u64, u32, u64) {
+        match self.methods.get(method) {
+            Some(policy) => (
+                policy.timeout_ms.unwrap_or(self.default_timeout_ms),
+                policy.retries,
+                policy.retry_delay_ms,
+            ),
+            None => (self.default_timeout_ms, 0, 0),
+        }
+    }
+}
+
+/// 单步结果的传输方式：开启共享文件后，本地内核把编码后不小于阈值的每步结果写入内存映射文件，
+/// JSON-RPC 只返回描述符，避免大电网每步数 MB 消息经管道传输与解析（远程内核与降级内核始终内联返回）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ResultTransferConfig {
+    #[serde(default)]
+    pub shared_file: bool,
+    /// 结果编码后达到该大小（字节）才经共享文件传输，较小的结果仍内联返回
+    #[serde(default = "default_shared_min_bytes")]
+    pub min_bytes: u64,
+}
+
+fn default_shared_min_bytes() -> u64 {
+    256 * 1024
+}
+
+impl Default for ResultTransferConfig {
+    fn default() -> Self {
+        Self { shared_file: false, min_bytes: default_shared_min_bytes() }
+    }
+}
+
+impl ResultTransferConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.min_bytes > crate::services::shared_results::MAX_SHARED_RESULT_BYTES {
+            return Err("共享文件传输阈值不能超过单步结果上限".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// 并行求解内核池：批量潮流任务（孤岛拆分、多工况快照、N-1 预想故障、潮流参数扫描）可同时使用的内核进程数
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct KernelPoolConfig {
+    /// 池中内核进程上限（每个进程独立加载 pandapower，内存占用较大）
+    #[serde(default = "default_pool_max_kernels")]
+    pub max_kernels: usize,
+    /// 命令未指定并行度时使用的内核数；None 时取 CPU 核数（均不超过上限）
+    #[serde(default)]
+    pub default_parallelism: Option<usize>,
+}
+
+fn default_pool_max_kernels() -> usize {
+    4
+}
+
+impl Default for KernelPoolConfig {
+    fn default() -> Self {
+        Self { max_kernels: default_pool_max_kernels(), default_parallelism: None }
+    }
+}
+
+impl KernelPoolConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        let cap = crate::services::kernel_pool::MAX_POOL_KERNELS;
+        if self.max_kernels == 0 || self.max_kernels > cap {
+            return Err(format!("内核进程上限须在 1 至 {} 之间", cap));
+        }
+        if self.default_parallelism == Some(0) {
+            return Err("默认并行度必须大于 0".to_string());
+        }
+        Ok(())
+    }
+
+    /// 未指定并行度时使用的内核数
+    pub fn parallelism(&self) -> usize {
+        self.default_parallelism
+            .unwrap_or_else(|| std::thread::available_parallelism().map(|n| n.get()).unwrap_or(2))
+            .clamp(1, self.max_kernels)
+    }
 }
@@ -0,0 +1,149 @@
+// 储能日前充放电计划：按时间点的功率设定（阶梯保持），由引擎每步自动下发，并统计计划执行偏差
+use chrono::{Local, NaiveDateTime, NaiveTime, TimeZone, Timelike};
+use serde::{Deserialize, Serialize};
+
+/// 计划设定点：at 为 Unix 时间戳（秒）；daily 计划中为当天零点起的秒数
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StorageSchedulePoint {
+    pub at: f64,
+    /// 有功设定（kW，与 pandapower 一致：正=充电，负=放电）
+    pub p_kw: f64,
+    #[serde(default)]
+    pub q_kvar: f64,
+}
+
+/// 计划与远程指令（Modbus set_power）的冲突规则
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduleConflictRule {
+    /// 计划优先：远程指令仅在下一步前生效，随后重新下发计划值
+    #[default]
+    SchedulePriority,
+    /// 远程指令生效至下一个计划时段开始
+    RemoteUntilNextSlot,
+    /// 远程指令优先：收到远程指令后本次仿真内暂停执行计划
+    RemotePriority,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StorageSchedule {
+    pub device_id: String,
+    /// true 时设定点为一天内的时刻（HH:MM），每天重复
+    pub daily: bool,
+    pub points: Vec<StorageSchedulePoint>,
+    #[serde(default)]
+    pub conflict_rule: ScheduleConflictRule,
+}
+
+/// 计划执行情况（偏差能量 = ∑|实际功率 − 计划功率|·dt）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScheduleAdherence {
+    pub device_id: String,
+    pub steps: u64,
+    /// 被远程指令接管的步数
+    pub overridden_steps: u64,
+    pub scheduled_energy_kwh: f64,
+    pub actual_energy_kwh: f64,
+    pub deviation_energy_kwh: f64,
+    /// 偏差率（%）= 偏差能量 / 计划能量绝对值之和
+    pub deviation_percent: f64,
+    pub max_deviation_kw: f64,
+    /// 当前生效的计划设定（kW）
+    pub current_setpoint_kw: Option<f64>,
+}
+
+/// 解析时间：Unix 秒、"YYYY-MM-DD HH:MM[:SS]"（本地时间）或 "HH:MM[:SS]"（每日时刻）；返回 (值, 是否为每日时刻)
+pub(crate) fn parse_schedule_time(s: &str) -> Option<(f64, bool)> {
+    let s = s.trim();
+    if let Ok(v) = s.parse::<f64>() {
+        return Some((v, false));
+    }
+    for fmt in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M:%S", "%Y/%m/%d %H:%M:%S", "%Y/%m/%d %H:%M"] {
+        if let Ok(dt) = NaiveDateTime::parse_from_str(s, fmt) {
+            return Local.from_local_datetime(&dt).single().map(|t| (t.timestamp() as f64, false));
+        }
+    }
+    for fmt in ["%H:%M:%S", "%H:%M"] {
+        if let Ok(t) = NaiveTime::parse_from_str(s, fmt) {
+            return Some((t.num_seconds_from_midnight() as f64, true));
+        }
+    }
+    None
+}
+
+impl StorageSchedule {
+    /// 从 CSV 导入：需含时间列（timestamp/time）与有功列（p_kw/setpoint_kw），可选无功列 q_kvar
+    pub fn from_csv(device_id: &str, path: &str, conflict_rule: ScheduleConflictRule) -> Result<Self, String> {
+        let mut reader = csv::Reader::from_path(path).map_err(|e| format!("打开计划文件失败: {}", e))?;
+        let headers: Vec<String> = reader
+            .headers()
+            .map_err(|e| format!("读取表头失败: {}", e))?
+            .iter()
+            .map(|h| h.trim().to_lowercase())
+            .collect();
+        let find = |names: &[&str]| headers.iter().position(|h| names.contains(&h.as_str()));
+        let time_col = find(&["timestamp", "time", "时间"]).ok_or("计划文件缺少时间列 timestamp/time")?;
+        let p_col = find(&["p_kw", "setpoint_kw", "power_kw"]).ok_or("计划文件缺少功率列 p_kw")?;
+        let q_col = find(&["q_kvar"]);
+
+        let mut points = Vec::new();
+        let mut daily: Option<bool> = None;
+        for (i, record) in reader.records().enumerate() {
+            let record = record.map_err(|e| format!("第 {} 行读取失败: {}", i + 2, e))?;
+            let raw_time = record.get(time_col).unwrap_or("");
+            let (at, is_daily) = parse_schedule_time(raw_time)
+                .ok_or_else(|| format!("第 {} 行时间无法解析: {}", i + 2, raw_time))?;
+            if daily.is_some_and(|d| d != is_daily) {
+                return Err("计划文件不能混用日期时间与每日时刻".to_string());
+            }
+            daily = Some(is_daily);
+            let p_kw = record
+                .get(p_col)
+                .and_then(|v| v.trim().parse::<f64>().ok())
+                .ok_or_else(|| format!("第 {} 行功率无法解析", i + 2))?;
+            let q_kvar = q_col
+                .and_then(|c| record.get(c))
+                .and_then(|v| v.trim().parse::<f64>().ok())
+                .unwrap_or(0.0);
+            points.push(StorageSchedulePoint { at, p_kw, q_kvar });
+        }
+        let schedule = Self {
+            device_id: device_id.to_string(),
+            daily: daily.unwrap_or(false),
+            points,
+            conflict_rule,
+        };
+        schedule.validate()?;
+        Ok(schedule)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.points.is_empty() {
+            return Err("计划为空".to_string());
+        }
+        if self.points.windows(2).any(|w| w[1].at <= w[0].at) {
+            return Err("计划时间须严格递增".to_string());
+        }
+        if self.daily && self.points.iter().any(|p| !(0.0..86400.0).contains(&p.at)) {
+            return Err("每日计划时刻须在 00:00–24:00 之间".to_string());
+        }
+        Ok(())
+    }
+
+    /// 某时刻生效的时段序号（阶梯保持）；绝对计划在首个设定点之前不生效，每日计划在首个时刻之前沿用前一天最后时段
+    pub fn slot_index_at(&self, timestamp: f64) -> Option<usize> {
+        let t = if self.daily {
+            Local
+                .timestamp_opt(timestamp as i64, 0)
+                .single()
+                .map(|dt| dt.num_seconds_from_midnight() as f64)?
+        } else {
+            timestamp
+        };
+        match self.points.iter().rposition(|p| p.at <= t) {
+            Some(i) => Some(i),
+            None if self.daily => Some(self.points.len() - 1),
+            None => None,
+        }
+    }
+}
@@ -59,6 +59,23 @@ pub struct Device {
     pub location: Option<Location>,
 }
 
+/// 读取数值型设备属性：兼容数字与数字字符串（前端表单输入），非有限值视为未设置
+pub(crate) fn prop_f64(properties: &HashMap<String, serde_json::Value>, key: &str) -> Option<f64> {
+    properties
+        .get(key)
+        .and_then(|v| v.as_f64().or_else(|| v.as_str().and_then(|s| s.trim().parse().ok())))
+        .filter(|v| v.is_finite())
+}
+
+/// 设备额定功率（kW）：前端属性面板使用 rated_power_kw，拓扑/旧数据可能为 max_power_kw 或 rated_power；
+/// Modbus 铭牌寄存器、气象出力模型、模型标定与越限监视统一按此顺序解析
+pub fn rated_power_kw(properties: &HashMap<String, serde_json::Value>) -> Option<f64> {
+    ["rated_power_kw", "max_power_kw", "rated_power"]
+        .iter()
+        .find(|k| properties.contains_key(**k))
+        .and_then(|k| prop_f64(properties, k))
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Connection {
     pub id: String,
@@ -136,6 +153,131 @@ impl Topology {
         Ok(())
     }
 
+    /// 按连接关系拆分为相互独立的电气孤岛（断开的开关不连通两侧），仅保留含母线的孤岛；
+    /// 各孤岛可分别求解潮流后合并结果
+    pub fn split_islands(&self) -> Vec<Topology> {
+        fn find(parent: &mut HashMap<String, String>, id: &str) -> String {
+            let mut root = id.to_string();
+            while let Some(p) = parent.get(&root) {
+                if *p == root {
+                    break;
+                }
+                root = p.clone();
+            }
+            parent.insert(id.to_string(), root.clone());
+            root
+        }
+        let is_open_switch = |id: &str| {
+            self.devices.get(id).is_some_and(|d| {
+                d.device_type == DeviceType::Switch
+                    && d.properties.get("is_closed").and_then(|v| v.as_bool()) == Some(false)
+            })
+        };
+        let mut parent: HashMap<String, String> =
+            self.devices.keys().map(|id| (id.clone(), id.clone())).collect();
+        for conn in self.connections.values() {
+            if !self.devices.contains_key(&conn.from_device_id)
+                || !self.devices.contains_key(&conn.to_device_id)
+                || is_open_switch(&conn.from_device_id)
+                || is_open_switch(&conn.to_device_id)
+            {
+                continue;
+            }
+            let a = find(&mut parent, &conn.from_device_id);
+            let b = find(&mut parent, &conn.to_device_id);
+            if a != b {
+                parent.insert(a, b);
+            }
+        }
+        let mut groups: HashMap<String, Vec<String>> = HashMap::new();
+        let mut ids: Vec<&String> = self.devices.keys().collect();
+        ids.sort();
+        for id in ids {
+            let root = find(&mut parent, id);
+            groups.entry(root).or_default().push(id.clone());
+        }
+        let mut groups: Vec<Vec<String>> = groups
+            .into_values()
+            .filter(|members| {
+                members
+                    .iter()
+                    .any(|id| self.devices[id].device_type == DeviceType::Node)
+            })
+            .collect();
+        // 设备多的孤岛在前，便于优先分配；同规模按首个设备 ID 保证顺序稳定
+        groups.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a[0].cmp(&b[0])));
+        groups
+            .into_iter()
+            .map(|members| {
+                let mut island = Topology::new(self.id.clone(), self.name.clone(), self.description.clone());
+                for id in &members {
+                    island.devices.insert(id.clone(), self.devices[id].clone());
+                }
+                island.connections = self
+                    .connections
+                    .iter()
+                    .filter(|(_, c)| {
+                        island.devices.contains_key(&c.from_device_id)
+                            && island.devices.contains_key(&c.to_device_id)
+                    })
+                    .map(|(k, c)| (k.clone(), c.clone()))
+                    .collect();
+                island
+            })
+            .collect()
+    }
+
+    /// 提取分组（设备属性 group）对应的子网：组内设备及两端均在子网内的连接；
+    /// 组内设备连到的组外母线一并纳入（只保留与组内设备的连接）。返回 (子网, 边界母线 id)，
+    /// 边界母线为在完整拓扑中仍与组外设备相连的子网母线，按 id 排序
+    pub fn extract_group(&self, group_id: &str) -> Result<(Topology, Vec<String>), String> {
+        let members: std::collections::HashSet<&String> = self
+            .devices
+            .values()
+            .filter(|d| d.properties.get("group").and_then(|v| v.as_str()) == Some(group_id))
+            .map(|d| &d.id)
+            .collect();
+        if members.is_empty() {
+            return Err(format!("分组 {} 中没有设备（设备属性 group）", group_id));
+        }
+        let is_node = |id: &str| self.devices.get(id).is_some_and(|d| d.device_type == DeviceType::Node);
+        let mut boundary: std::collections::BTreeSet<String> = std::collections::BTreeSet::new();
+        let mut pulled_in: std::collections::HashSet<String> = std::collections::HashSet::new();
+        for conn in self.connections.values() {
+            let (inside, outside) = match (members.contains(&conn.from_device_id), members.contains(&conn.to_device_id)) {
+                (true, false) => (&conn.from_device_id, &conn.to_device_id),
+                (false, true) => (&conn.to_device_id, &conn.from_device_id),
+                _ => continue,
+            };
+            if is_node(inside) {
+                boundary.insert(inside.clone());
+            } else if is_node(outside) {
+                pulled_in.insert(outside.clone());
+                boundary.insert(outside.clone());
+            }
+        }
+        let mut sub = Topology::new(self.id.clone(), self.name.clone(), self.description.clone());
+        for id in members.iter().map(|id| id.as_str()).chain(pulled_in.iter().map(|id| id.as_str())) {
+            if let Some(device) = self.devices.get(id) {
+                sub.devices.insert(id.to_string(), device.clone());
+            }
+        }
+        if !sub.devices.values().any(|d| d.device_type == DeviceType::Node) {
+            return Err(format!("分组 {} 不含母线，无法单独仿真", group_id));
+        }
+        sub.connections = self
+            .connections
+            .iter()
+            .filter(|(_, c)| {
+                sub.devices.contains_key(&c.from_device_id)
+                    && sub.devices.contains_key(&c.to_device_id)
+                    && (members.contains(&c.from_device_id) || members.contains(&c.to_device_id))
+            })
+            .map(|(k, c)| (k.clone(), c.clone()))
+            .collect();
+        Ok((sub, boundary.into_iter().collect()))
+    }
+
     fn validate_connection(&self, connection: &Connection) -> Result<(), String> {
         // 检查设备是否存在
         if !self.devices.contains_key(&connection.from_device_id) {
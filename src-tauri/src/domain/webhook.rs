@@ -0,0 +1,85 @@
+// Webhook 通知配置：选定事件发生时以 HTTP POST 推送 JSON，可选 HMAC-SHA256 签名
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    /// 仿真因严重错误自动停止
+    SimulationAutoStopped,
+    /// 出现新的限值预警/报警
+    AlertRaised,
+    /// 场景运行结束（无论是否通过）
+    ScenarioCompleted,
+    /// 场景中有断言未通过
+    AssertionFailed,
+}
+
+impl WebhookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::SimulationAutoStopped => "simulation_auto_stopped",
+            WebhookEvent::AlertRaised => "alert_raised",
+            WebhookEvent::ScenarioCompleted => "scenario_completed",
+            WebhookEvent::AssertionFailed => "assertion_failed",
+        }
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_max_retries() -> u32 {
+    3
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WebhookConfig {
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    /// 订阅的事件
+    pub events: Vec<WebhookEvent>,
+    /// 签名密钥：设置后请求头带 X-PVSC-Signature: sha256=HMAC(secret, "{timestamp}.{body}")
+    #[serde(default)]
+    pub secret: Option<String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// 失败重试次数（指数退避 1s、2s、4s…）
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+}
+
+impl WebhookConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.id.trim().is_empty() {
+            return Err("Webhook ID 不能为空".to_string());
+        }
+        if !(self.url.starts_with("http://") || self.url.starts_with("https://")) {
+            return Err(format!("Webhook 地址须以 http:// 或 https:// 开头: {}", self.url));
+        }
+        if self.events.is_empty() {
+            return Err(format!("Webhook {} 未订阅任何事件", self.name));
+        }
+        if self.max_retries > 10 {
+            return Err("重试次数不能超过 10".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn subscribes(&self, event: WebhookEvent) -> bool {
+        self.enabled && self.events.contains(&event)
+    }
+}
+
+/// 一次投递记录（含重试结果），供前端查看
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    pub webhook_id: String,
+    pub event: WebhookEvent,
+    pub timestamp: f64,
+    pub attempts: u32,
+    pub success: bool,
+    pub status_code: Option<u16>,
+    pub error: Option<String>,
+}
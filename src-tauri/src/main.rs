@@ -15,7 +15,16 @@ use domain::metadata::DeviceMetadataStore;
 use std::sync::{Arc, Mutex as StdMutex};
 use tokio::sync::{mpsc, Mutex as TokioMutex};
 
+/// 应用退出时关闭全部内核进程的总时限
+const EXIT_SHUTDOWN_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
 fn main() {
+    // 命令行导出模式：export 子命令不启动界面与内核，导出后直接退出
+    let cli_args: Vec<String> = std::env::args().collect();
+    if cli_args.get(1).map(String::as_str) == Some(services::cli_export::EXPORT_SUBCOMMAND) {
+        std::process::exit(services::cli_export::run_cli(&cli_args[2..]));
+    }
+
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_dialog::init())
@@ -27,8 +36,17 @@ fn main() {
                 window.open_devtools();
             }
 
-            // 初始化 Python 桥接（在应用启动时立即启动）
-            let python_bridge = PythonBridge::new();
+            // 项目设置（远程内核端点在创建桥接时读取）
+            let settings_store = services::settings::SettingsStore::load();
+            // 内核日志须在启动内核进程前注册（进程的 stderr 读取线程从 AppHandle 获取）
+            app.manage(Arc::new(services::kernel_log::KernelLog::new(settings_store.kernel_log())));
+
+            // 初始化 Python 桥接（在应用启动时立即启动）；配置了远程端点时连接远程内核，否则拉起本地进程
+            let kernel_endpoint = services::kernel_transport::KernelEndpoint::resolve(settings_store.kernel_endpoint().as_deref());
+            let python_bridge = PythonBridge::with_endpoint(kernel_endpoint);
+            let kernel_client = python_bridge.client();
+            kernel_client.set_rpc_config(settings_store.kernel_rpc());
+            kernel_client.set_result_transfer(settings_store.result_transfer());
             let python_bridge_arc = Arc::new(TokioMutex::new(python_bridge));
 
             // 数据库仅在开始仿真时创建（data_<timestamp>.db），不仿真不生成空文件
@@ -41,6 +59,7 @@ fn main() {
             // 初始化仿真引擎
             let simulation_engine = Arc::new(SimulationEngine::new(
                 python_bridge_arc.clone(),
+                kernel_client,
                 db_arc.clone(),
                 current_db_path.clone(),
             ));
@@ -55,7 +74,7 @@ fn main() {
                 let mut bridge = python_bridge_clone.lock().await;
                 
                 // 启动 Python 进程（传入 app_handle 以便从 bundle resources 加载内核）
-                match bridge.start(Some(&app_handle)).await {
+                let ready = match bridge.start(Some(&app_handle)).await {
                     Ok(_) => {
                         eprintln!("Python 进程已启动，等待就绪...");
                         // 等待 Python 进程初始化
@@ -72,6 +91,14 @@ fn main() {
                                     ready = true;
                                     // 发送就绪事件到前端
                                     let _ = app_handle.emit("python-kernel-ready", ());
+                                    // 检测上次未正常结束的仿真，提示前端可恢复
+                                    if let Some(run) = std::env::current_dir()
+                                        .ok()
+                                        .and_then(|dir| services::run_recovery::find_interrupted_run(&dir, None))
+                                    {
+                                        eprintln!("检测到未正常结束的仿真: {}", run.db_path);
+                                        let _ = app_handle.emit("interrupted-run-detected", run);
+                                    }
                                 }
                                 Err(e) => {
                                     eprintln!("Python 内核尚未就绪 (剩余重试: {}): {}", retries - 1, e);
@@ -84,30 +111,38 @@ fn main() {
                         }
                         
                         if !ready {
-                            let log_path = std::env::current_exe()
-                                .ok()
-                                .and_then(|p| p.parent().map(|p| p.join("python-kernel.log")))
-                                .map(|p| p.display().to_string())
-                                .unwrap_or_else(|| "python-kernel.log".to_string());
+                            let log_path = services::kernel_log::log_path().display().to_string();
                             eprintln!("警告: Python 内核在多次重试后仍未就绪，日志: {}", log_path);
                             let _ = app_handle.emit("python-kernel-error", format!("Python 内核启动失败，请查看日志: {}", log_path));
                         }
+                        ready
                     }
                     Err(e) => {
-                        let log_path = std::env::current_exe()
-                            .ok()
-                            .and_then(|p| p.parent().map(|p| p.join("python-kernel.log")))
-                            .map(|p| p.display().to_string())
-                            .unwrap_or_else(|| "python-kernel.log".to_string());
+                        let log_path = services::kernel_log::log_path().display().to_string();
                         eprintln!("启动 Python 内核失败: {}，日志: {}", e, log_path);
                         let _ = app_handle.emit("python-kernel-error", format!("启动失败: {}，日志: {}", e, log_path));
+                        false
+                    }
+                };
+                // 内核不可用时切换到内置辐射网求解器，小型辐射状网络仍可仿真
+                if !ready {
+                    match bridge.enable_fallback().await {
+                        Ok(()) => {
+                            eprintln!("已切换到内置潮流求解器（降级模式）");
+                            let _ = app_handle.emit(
+                                "python-kernel-fallback",
+                                "Python 内核不可用，已切换到内置潮流求解器（仅支持辐射状网络，部分功能不可用）",
+                            );
+                        }
+                        Err(e) => eprintln!("切换到内置潮流求解器失败: {}", e),
                     }
                 }
             });
 
             // 初始化 Modbus 服务：HR 写入通过 channel 发出事件；若设备开启远程控制则经 Modbus 过滤后推送到 Python 内核
             let (modbus_hr_tx, mut modbus_hr_rx) = mpsc::channel::<services::modbus::HoldingRegisterWriteEvent>(64);
-            let modbus_service = ModbusService::new(modbus_hr_tx);
+            let (modbus_coil_tx, mut modbus_coil_rx) = mpsc::channel::<services::modbus::CoilWriteEvent>(64);
+            let modbus_service = ModbusService::new(modbus_hr_tx, modbus_coil_tx);
             let app_handle_modbus = app.handle().clone();
             tauri::async_runtime::spawn(async move {
                 while let Some((device_id, address, value)) = modbus_hr_rx.recv().await {
@@ -115,32 +150,128 @@ fn main() {
                     if let (Some(engine), Some(modbus)) = (
                         app_handle_modbus.try_state::<Arc<SimulationEngine>>(),
                         app_handle_modbus.try_state::<ModbusService>(),
+                    ) {
+                        let engine = engine.inner().clone();
+                        // 写入的功率设定与上送寄存器同为项目符号约定，按设备类型的约定系数换回内核原生约定
+                        let device_type: Option<(String, f64)> = engine.get_topology().await.and_then(|t| {
+                            t.devices.get(&device_id).map(|d| {
+                                (d.device_type.as_str().to_string(), engine.get_sign_convention().factor(&d.device_type))
+                            })
+                        });
+                        if let Some((ref dt, power_sign)) = device_type {
+                            if let Some(props) =
+                                modbus.apply_hr_write_and_effective_properties(&device_id, dt, address, value, power_sign)
+                            {
+                                let _ = engine.update_device_properties_for_simulation(device_id.clone(), props, "modbus").await;
+                            }
+                        }
+                    }
+                    services::window_hub::publish_typed(
+                        &app_handle_modbus,
+                        None,
+                        domain::events::ModbusHoldingRegisterWrite {
+                            schema_version: domain::events::EVENT_SCHEMA_VERSION,
+                            device_id,
+                            address,
+                            value,
+                        },
+                    );
+                }
+            });
+            // 线圈写入：开关设备的合/分闸线圈经 Modbus 过滤后推送到仿真（closed=true/false），下一拍潮流按新的开关状态计算
+            let app_handle_coil = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                while let Some((device_id, address, value)) = modbus_coil_rx.recv().await {
+                    if let (Some(engine), Some(modbus)) = (
+                        app_handle_coil.try_state::<Arc<SimulationEngine>>(),
+                        app_handle_coil.try_state::<ModbusService>(),
                     ) {
                         let engine = engine.inner().clone();
                         let device_type: Option<String> = engine
                             .get_topology()
                             .await
                             .and_then(|t| t.devices.get(&device_id).map(|d| d.device_type.as_str().to_string()));
-                        if let Some(ref dt) = device_type {
-                            if let Some(props) = modbus.apply_hr_write_and_effective_properties(&device_id, dt, address, value) {
-                                let _ = engine.update_device_properties_for_simulation(device_id.clone(), props).await;
+                        let props = device_type
+                            .as_deref()
+                            .and_then(|dt| modbus.apply_coil_write_and_effective_properties(&device_id, dt, address, value));
+                        if let Some(props) = props {
+                            if engine.device_remote_control_allowed(&device_id).await
+                                && engine.update_device_properties_for_simulation(device_id.clone(), props, "modbus").await.is_ok()
+                            {
+                                // 设备树同步开关状态（与手动操作开关一致）
+                                if let Some(store) = app_handle_coil.try_state::<StdMutex<DeviceMetadataStore>>() {
+                                    let device = store.lock().unwrap().get_device(&device_id);
+                                    if let Some(mut device) = device {
+                                        device.properties.insert("is_closed".to_string(), serde_json::json!(value));
+                                        let _ = store.lock().unwrap().update_device(device);
+                                    }
+                                }
                             }
                         }
                     }
-                    let _ = app_handle_modbus.emit("modbus-holding-register-write", serde_json::json!({
-                        "device_id": device_id,
-                        "address": address,
-                        "value": value,
-                    }));
+                    services::window_hub::publish_typed(
+                        &app_handle_coil,
+                        None,
+                        domain::events::ModbusCoilWrite {
+                            schema_version: domain::events::EVENT_SCHEMA_VERSION,
+                            device_id,
+                            address,
+                            value,
+                        },
+                    );
                 }
             });
+            // 项目设置：符号约定在启动时同步到仿真引擎
+            simulation_engine.set_sign_convention(settings_store.sign_convention());
+            simulation_engine.set_keep_kernel_warm(settings_store.keep_kernel_warm());
+            simulation_engine.set_random_seed(settings_store.random_seed());
+            simulation_engine.set_power_kernel(settings_store.power_kernel());
+            // 外部接口认证：令牌与匿名权限来自设置，Modbus 服务与令牌管理命令共享同一实例
+            let api_auth = modbus_service.api_auth();
+            api_auth.set_tokens(settings_store.api_tokens());
+            api_auth.set_anonymous_scope(settings_store.external_anonymous_scope());
+            api_auth.set_peer_scopes(settings_store.external_peer_scopes());
+
             // 将服务存储到应用状态
+            // 多实例管理：主仿真即 default 实例，其余命令仍直接使用 Arc<SimulationEngine>
+            let simulation_manager = services::simulation_manager::SimulationManager::new(
+                simulation_engine.clone(),
+                python_bridge_arc.clone(),
+            );
+            // 内核看门狗：空闲时定期探测主内核，无响应时推送事件并按设置自动重启
+            PythonBridge::spawn_watchdog(python_bridge_arc.clone(), app.handle().clone());
             app.manage(python_bridge_arc);
             app.manage(db_arc);
             app.manage(current_db_path);
             app.manage(StdMutex::new(metadata_store));
             app.manage(simulation_engine);
+            app.manage(simulation_manager);
+            app.manage(services::replay::ReplayService::new());
             app.manage(modbus_service);
+            app.manage(services::compliance::ComplianceResultStore::new());
+            app.manage(services::energy_balance::EnergyBalanceStore::new());
+            app.manage(services::webhook::WebhookDispatcher::new(settings_store.webhooks()));
+            app.manage(services::kernel_pool::KernelPool::new(settings_store.kernel_pool()));
+            app.manage(settings_store);
+            app.manage(api_auth);
+            app.manage(Arc::new(services::window_hub::WindowEventHub::new()));
+            app.manage(Arc::new(services::csv_cache::CsvCache::new()));
+            app.manage(services::series_tail::SeriesTailManager::new());
+
+            // 命令行模式：--scenario <文件> 运行场景脚本，输出结果并以是否通过作为进程退出码
+            let args: Vec<String> = std::env::args().collect();
+            if let Some(path) = args
+                .iter()
+                .position(|a| a == "--scenario")
+                .and_then(|i| args.get(i + 1))
+                .cloned()
+            {
+                let handle = app.handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    let code = services::scenario::run_cli(&handle, &path).await;
+                    handle.exit(code);
+                });
+            }
 
             Ok(())
         })
@@ -150,29 +281,155 @@ fn main() {
             commands::topology::load_topology,
             commands::topology::validate_topology,
             commands::topology::load_and_validate_topology,
+            commands::topology::compare_topology_powerflow,
+            commands::topology::run_partitioned_snapshot,
+            commands::topology::run_snapshot_batch,
+            commands::topology::run_snapshot_sweep,
+            commands::topology::run_contingency_analysis,
+            commands::topology::get_kernel_pool_status,
+            commands::topology::shutdown_kernel_pool,
+            commands::simulation::create_simulation_instance,
+            commands::simulation::list_simulation_instances,
+            commands::simulation::remove_simulation_instance,
+            commands::simulation::run_parameter_sweep,
+            commands::simulation::get_sweep_index,
             commands::simulation::start_simulation,
+            commands::simulation::start_partial_simulation,
+            commands::simulation::start_replay,
+            commands::simulation::stop_replay,
+            commands::simulation::get_replay_status,
+            commands::simulation::start_simulation_with_preset,
             commands::simulation::stop_simulation,
             commands::simulation::pause_simulation,
             commands::simulation::resume_simulation,
+            commands::simulation::step_simulation,
+            commands::simulation::set_breakpoint,
+            commands::simulation::remove_breakpoint,
+            commands::simulation::list_breakpoints,
+            commands::ems::list_ems_strategies,
+            commands::ems::set_ems_strategy,
+            commands::ems::disable_ems,
+            commands::ems::get_ems_status,
             commands::simulation::get_simulation_status,
             commands::simulation::get_simulation_errors,
             commands::simulation::set_remote_control_enabled,
             commands::simulation::set_device_remote_control_enabled,
             commands::simulation::update_device_properties_for_simulation,
             commands::simulation::update_switch_state,
+            commands::simulation::get_effective_property_drift,
             commands::simulation::set_device_mode,
             commands::simulation::set_device_random_config,
+            commands::simulation::get_device_random_profiles,
+            commands::simulation::inject_fault,
+            commands::simulation::clear_fault,
+            commands::simulation::trigger_burst_logging,
+            commands::simulation::get_active_faults,
+            commands::simulation::get_fault_history,
             commands::simulation::set_device_manual_setpoint,
+            commands::simulation::get_stored_device_controls,
+            commands::simulation::clear_stored_device_controls,
             commands::simulation::set_device_historical_config,
             commands::simulation::set_device_sim_params,
+            commands::simulation::set_device_delays,
+            commands::simulation::set_pv_reactive_control,
+            commands::simulation::get_pv_reactive_control,
+            commands::simulation::set_pv_grid_support,
+            commands::simulation::get_pv_grid_support,
+            commands::simulation::bind_weather_source,
+            commands::simulation::unbind_weather_source,
+            commands::simulation::get_weather_binding,
+            commands::simulation::set_device_pf_response,
+            commands::simulation::get_device_pf_response,
+            commands::simulation::set_grid_frequency,
+            commands::compliance::list_compliance_tests,
+            commands::compliance::run_compliance_test,
+            commands::compliance::get_compliance_results,
+            commands::compliance::clear_compliance_results,
+            commands::settings::list_calculation_presets,
+            commands::settings::save_calculation_preset,
+            commands::settings::delete_calculation_preset,
+            commands::settings::get_sign_convention,
+            commands::settings::set_sign_convention,
+            commands::settings::migrate_run_db_sign_convention,
+            commands::settings::get_keep_kernel_warm,
+            commands::settings::set_keep_kernel_warm,
+            commands::settings::get_kernel_watchdog_config,
+            commands::settings::set_kernel_watchdog_config,
+            commands::settings::get_kernel_log_config,
+            commands::settings::set_kernel_log_config,
+            commands::settings::get_kernel_log_tail,
+            commands::settings::rotate_kernel_log,
+            commands::settings::get_kernel_rpc_config,
+            commands::settings::set_kernel_rpc_config,
+            commands::settings::get_kernel_pool_config,
+            commands::settings::set_kernel_pool_config,
+            commands::settings::get_result_transfer_config,
+            commands::settings::set_result_transfer_config,
+            commands::settings::get_kernel_endpoint,
+            commands::settings::set_kernel_endpoint,
+            commands::settings::get_random_seed,
+            commands::settings::set_random_seed,
+            commands::settings::list_webhooks,
+            commands::settings::save_webhook,
+            commands::settings::delete_webhook,
+            commands::settings::test_webhook,
+            commands::settings::get_webhook_deliveries,
+            commands::settings::create_api_token,
+            commands::settings::list_api_tokens,
+            commands::settings::revoke_api_token,
+            commands::settings::get_external_anonymous_scope,
+            commands::settings::set_external_anonymous_scope,
+            commands::settings::get_external_peer_scopes,
+            commands::settings::set_external_peer_scopes,
+            commands::settings::get_auth_audit_log,
+            commands::simulation::preload_topology,
+            commands::simulation::get_power_kernels,
+            commands::simulation::set_power_kernel,
+            commands::simulation::verify_kernel_sync,
+            commands::simulation::get_bridge_metrics,
+            commands::simulation::reload_python_kernel,
+            commands::simulation::check_python_environment,
+            commands::simulation::subscribe_window_events,
+            commands::simulation::unsubscribe_window_events,
+            commands::simulation::list_window_subscriptions,
+            commands::simulation::get_system_snapshot,
+            commands::simulation::reset_counters,
+            commands::simulation::get_interrupted_run,
+            commands::simulation::resume_interrupted_run,
+            commands::simulation::dismiss_interrupted_run,
+            commands::simulation::save_checkpoint,
+            commands::simulation::get_checkpoint_info,
+            commands::simulation::restore_checkpoint,
+            commands::calibration::calibrate_device_model,
+            commands::scenario::run_scenario,
+            commands::scenario::load_demo_project,
+            commands::scenario::load_event_schedule,
+            commands::scenario::clear_event_schedule,
+            commands::scenario::get_event_schedule_status,
             commands::simulation::get_device_data,
             commands::simulation::list_sqlite_devices,
             commands::simulation::get_historical_time_range,
             commands::monitoring::record_device_data,
             commands::monitoring::get_latest_simulation_start_time,
             commands::monitoring::query_device_data,
+            commands::monitoring::get_history_time_range,
+            commands::monitoring::get_burst_windows,
+            commands::monitoring::get_daily_energy_archive,
+            commands::monitoring::get_run_weather_source,
+            commands::monitoring::get_ev_sessions,
+            commands::monitoring::get_run_summary,
+            commands::monitoring::render_chart,
             commands::monitoring::get_all_devices_status,
             commands::monitoring::get_device_status,
+            commands::monitoring::get_active_alerts,
+            commands::monitoring::get_limit_kpis,
+            commands::monitoring::get_storage_state,
+            commands::monitoring::get_setpoint_clamps,
+            commands::monitoring::get_tap_operations,
+            commands::monitoring::get_protection_events,
+            commands::monitoring::export_event_schema,
+            commands::monitoring::set_device_limit_bands,
+            commands::monitoring::get_device_limit_bands,
             commands::device::get_all_devices,
             commands::device::get_modbus_devices,
             commands::device::get_modbus_register_defaults,
@@ -181,22 +438,82 @@ fn main() {
             commands::modbus::stop_device_modbus,
             commands::modbus::start_all_modbus_servers,
             commands::modbus::get_running_modbus_device_ids,
+            commands::modbus::export_modbus_register_doc,
+            commands::modbus::get_modbus_gateway_config,
+            commands::modbus::set_modbus_gateway_config,
+            commands::modbus::get_modbus_gateway_mapping,
             commands::device::update_device_config,
             commands::device::update_device_metadata,
             commands::device::batch_set_device_mode,
+            commands::device::set_grid_schedule,
+            commands::device::get_grid_schedule,
+            commands::device::get_grid_limit_violations,
+            commands::device::set_meter_dropout,
+            commands::device::get_meter_dropout,
+            commands::device::get_meter_dropout_report,
+            commands::device::import_storage_schedule,
+            commands::device::get_storage_schedule,
+            commands::device::clear_storage_schedule,
+            commands::device::import_device_forecast,
+            commands::device::get_device_forecast,
+            commands::device::clear_device_forecast,
+            commands::device::import_device_profile,
+            commands::device::get_device_profile,
+            commands::device::clear_device_profile,
+            commands::device::get_forecast_accuracy,
+            commands::device::get_storage_schedule_adherence,
+            commands::device::get_device_aliases,
+            commands::device::set_device_aliases,
+            commands::device::resolve_device_keys,
             commands::ai::predict_device_data,
             commands::ai::optimize_operation,
             commands::ai::get_ai_recommendations,
             commands::analytics::analyze_performance,
             commands::analytics::generate_report,
+            commands::analytics::run_energy_balance,
+            commands::analytics::mark_energy_balance_baseline,
+            commands::analytics::clear_energy_balance_baseline,
             commands::dashboard::dashboard_parse_csv,
             commands::dashboard::dashboard_list_devices_from_path,
             commands::dashboard::query_device_data_from_path,
             commands::dashboard::dashboard_parse_wide_csv,
+            commands::dashboard::dashboard_list_column_parsers,
+            commands::dashboard::dashboard_preview_column_parser,
+            commands::dashboard::dashboard_detect_timezone,
             commands::dashboard::dashboard_list_db_columns,
             commands::dashboard::dashboard_query_db_series,
             commands::dashboard::dashboard_fetch_series_batch,
+            commands::dashboard::dashboard_export_package,
+            commands::dashboard::dashboard_import_package,
+            commands::dashboard::data_source_list_keys,
+            commands::dashboard::data_source_fetch_series,
+            commands::dashboard::data_source_fetch_latest,
+            commands::dashboard::data_source_fetch_series_page,
+            commands::dashboard::subscribe_series_tail,
+            commands::dashboard::unsubscribe_series_tail,
+            commands::dashboard::csv_cache_status,
+            commands::dashboard::csv_cache_invalidate,
+            commands::dashboard::csv_cache_prefetch,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                // 退出前关闭全部内核进程（主仿真、独立实例与并行求解池），避免残留 python-kernel 进程；
+                // 内核被占用（如正在重启）时最多等待 EXIT_SHUTDOWN_TIMEOUT
+                tauri::async_runtime::block_on(async {
+                    let shutdown = async {
+                        if let Some(manager) = app.try_state::<services::simulation_manager::SimulationManager>() {
+                            manager.shutdown_kernels().await;
+                        }
+                        if let Some(pool) = app.try_state::<services::kernel_pool::KernelPool>() {
+                            pool.shutdown().await;
+                        }
+                    };
+                    if tokio::time::timeout(EXIT_SHUTDOWN_TIMEOUT, shutdown).await.is_err() {
+                        eprintln!("关闭内核进程超时，直接退出");
+                    }
+                });
+            }
+        });
 }
@@ -0,0 +1,203 @@
+// 外部接口统一认证：所有对外服务（Modbus TCP 及后续 REST/gRPC/MQTT）经此按令牌权限范围授权，并写入审计日志
+use crate::domain::auth::{ApiScope, ApiToken, ApiTokenInfo, AuditEntry, PeerScopeRule};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 审计日志保留上限
+const MAX_AUDIT_ENTRIES: usize = 1000;
+/// 明文令牌前缀，便于在配置文件/日志中识别
+const TOKEN_PREFIX: &str = "pvsc_";
+
+fn now_secs() -> f64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs_f64()
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// 令牌摘要：SHA-256(明文)，十六进制小写
+pub fn hash_token(token: &str) -> String {
+    to_hex(&Sha256::digest(token.as_bytes()))
+}
+
+/// 生成新令牌，返回 (明文, 记录)
+pub fn generate_token(name: &str, scope: ApiScope, expires_at: Option<f64>) -> (String, ApiToken) {
+    let mut secret = [0u8; 24];
+    rand::thread_rng().fill_bytes(&mut secret);
+    let mut id = [0u8; 6];
+    rand::thread_rng().fill_bytes(&mut id);
+    let token = format!("{}{}", TOKEN_PREFIX, to_hex(&secret));
+    let record = ApiToken {
+        id: to_hex(&id),
+        name: name.to_string(),
+        scope,
+        token_hash: hash_token(&token),
+        created_at: now_secs(),
+        expires_at,
+        revoked: false,
+    };
+    (token, record)
+}
+
+pub struct ApiAuth {
+    tokens: Mutex<Vec<ApiToken>>,
+    /// 未携带令牌的外部连接（如 Modbus TCP 客户端）享有的权限；None 表示拒绝
+    anonymous_scope: Mutex<Option<ApiScope>>,
+    /// 未携带令牌时按客户端地址授权，先于匿名权限匹配（按顺序取第一条匹配的规则）
+    peer_scopes: Mutex<Vec<PeerScopeRule>>,
+    last_used: Mutex<HashMap<String, f64>>,
+    audit: Mutex<VecDeque<AuditEntry>>,
+}
+
+impl ApiAuth {
+    pub fn new(tokens: Vec<ApiToken>, anonymous_scope: Option<ApiScope>) -> Self {
+        Self {
+            tokens: Mutex::new(tokens),
+            anonymous_scope: Mutex::new(anonymous_scope),
+            peer_scopes: Mutex::new(Vec::new()),
+            last_used: Mutex::new(HashMap::new()),
+            audit: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    pub fn set_tokens(&self, tokens: Vec<ApiToken>) {
+        *self.tokens.lock().unwrap() = tokens;
+    }
+
+    pub fn set_anonymous_scope(&self, scope: Option<ApiScope>) {
+        *self.anonymous_scope.lock().unwrap() = scope;
+    }
+
+    pub fn anonymous_scope(&self) -> Option<ApiScope> {
+        *self.anonymous_scope.lock().unwrap()
+    }
+
+    pub fn set_peer_scopes(&self, rules: Vec<PeerScopeRule>) {
+        *self.peer_scopes.lock().unwrap() = rules;
+    }
+
+    pub fn peer_scopes(&self) -> Vec<PeerScopeRule> {
+        self.peer_scopes.lock().unwrap().clone()
+    }
+
+    /// 按客户端地址（"IP:端口" 或 "IP"）匹配地址规则；串口等非 IP 来源不匹配
+    fn peer_scope(&self, peer: Option<&str>) -> Option<(String, ApiScope)> {
+        let peer = peer?;
+        let ip = peer
+            .parse::<std::net::SocketAddr>()
+            .map(|a| a.ip())
+            .or_else(|_| peer.parse::<std::net::IpAddr>())
+            .ok()?;
+        self.peer_scopes
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|r| r.matches(ip))
+            .map(|r| (format!("peer:{}", r.address), r.scope))
+    }
+
+    pub fn token_infos(&self) -> Vec<ApiTokenInfo> {
+        let last_used = self.last_used.lock().unwrap();
+        self.tokens
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|t| ApiTokenInfo {
+                id: t.id.clone(),
+                name: t.name.clone(),
+                scope: t.scope,
+                created_at: t.created_at,
+                expires_at: t.expires_at,
+                revoked: t.revoked,
+                last_used_at: last_used.get(&t.id).copied(),
+            })
+            .collect()
+    }
+
+    /// 校验明文令牌，返回 (令牌 ID, 权限)
+    fn authenticate(&self, token: &str) -> Result<(String, ApiScope), String> {
+        let hash = hash_token(token.trim());
+        let tokens = self.tokens.lock().unwrap();
+        let record = tokens.iter().find(|t| t.token_hash == hash).ok_or("令牌无效")?;
+        if record.revoked {
+            return Err(format!("令牌 {} 已吊销", record.id));
+        }
+        if record.expires_at.is_some_and(|e| now_secs() > e) {
+            return Err(format!("令牌 {} 已过期", record.id));
+        }
+        Ok((record.id.clone(), record.scope))
+    }
+
+    /// 对外接口请求授权：token 为 None 时先按客户端地址规则、再按匿名权限处理；拒绝的请求与需要控制/管理权限的请求写入审计日志
+    pub fn authorize(
+        &self,
+        surface: &str,
+        token: Option<&str>,
+        peer: Option<&str>,
+        action: &str,
+        required: ApiScope,
+    ) -> Result<String, String> {
+        let outcome = match token {
+            Some(t) => self.authenticate(t),
+            None => self
+                .peer_scope(peer)
+                .or_else(|| self.anonymous_scope().map(|s| ("anonymous".to_string(), s)))
+                .ok_or_else(|| "未提供令牌".to_string()),
+        };
+        let (actor, result) = match outcome {
+            Ok((actor, scope)) if scope.allows(required) => (actor.clone(), Ok(actor)),
+            Ok((actor, scope)) => (
+                actor,
+                Err(format!("权限不足：需要 {}，当前 {}", required.as_str(), scope.as_str())),
+            ),
+            Err(e) => ("unknown".to_string(), Err(e)),
+        };
+        if result.is_ok() && token.is_some() {
+            self.last_used.lock().unwrap().insert(actor.clone(), now_secs());
+        }
+        if result.is_err() || required > ApiScope::ReadOnly {
+            self.record(AuditEntry {
+                timestamp: now_secs(),
+                surface: surface.to_string(),
+                actor,
+                peer: peer.map(|p| p.to_string()),
+                action: action.to_string(),
+                required_scope: Some(required),
+                allowed: result.is_ok(),
+                detail: result.as_ref().err().cloned(),
+            });
+        }
+        result
+    }
+
+    pub fn record(&self, entry: AuditEntry) {
+        let mut audit = self.audit.lock().unwrap();
+        audit.push_back(entry);
+        while audit.len() > MAX_AUDIT_ENTRIES {
+            audit.pop_front();
+        }
+    }
+
+    /// 本地管理操作（令牌创建/吊销、匿名权限修改）写入审计日志
+    pub fn record_local(&self, action: &str, detail: Option<String>) {
+        self.record(AuditEntry {
+            timestamp: now_secs(),
+            surface: "local".to_string(),
+            actor: "local".to_string(),
+            peer: None,
+            action: action.to_string(),
+            required_scope: Some(ApiScope::Admin),
+            allowed: true,
+            detail,
+        });
+    }
+
+    /// 最近的审计记录（新的在前）
+    pub fn audit_log(&self, limit: usize) -> Vec<AuditEntry> {
+        self.audit.lock().unwrap().iter().rev().take(limit).cloned().collect()
+    }
+}
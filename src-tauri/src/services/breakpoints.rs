@@ -0,0 +1,189 @@
+// 条件断点：用户登记“设备量 比较 阈值”条件（如母线电压 < 0.95 pu、储能 SOC < 10%），计算循环每步评估，
+// 条件由不满足变为满足时命中，引擎暂停仿真并推送 simulation-breakpoint-hit（附命中时的实际值）
+use crate::domain::simulation::StorageState;
+use crate::domain::topology::{DeviceType, Topology};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// 断点监视的量
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakpointQuantity {
+    /// 母线电压（pu）
+    VoltagePu,
+    /// 线路/变压器负载率（%）
+    LoadingPercent,
+    /// 储能 SOC（%）
+    SocPercent,
+    /// 设备有功（kW，与功率缓存及前端显示一致）
+    PKw,
+    /// 设备无功（kvar）
+    QKvar,
+}
+
+impl BreakpointQuantity {
+    fn applies_to(&self, device_type: &DeviceType) -> bool {
+        match self {
+            BreakpointQuantity::VoltagePu => *device_type == DeviceType::Node,
+            BreakpointQuantity::LoadingPercent => matches!(device_type, DeviceType::Line | DeviceType::Transformer),
+            BreakpointQuantity::SocPercent => *device_type == DeviceType::Storage,
+            BreakpointQuantity::PKw | BreakpointQuantity::QKvar => !matches!(
+                device_type,
+                DeviceType::Node | DeviceType::Line | DeviceType::Transformer
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CompareOp {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl CompareOp {
+    pub fn holds(&self, value: f64, threshold: f64) -> bool {
+        match self {
+            CompareOp::Lt => value < threshold,
+            CompareOp::Le => value <= threshold,
+            CompareOp::Gt => value > threshold,
+            CompareOp::Ge => value >= threshold,
+        }
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Breakpoint {
+    /// 断点 ID；登记时为空则自动分配
+    #[serde(default)]
+    pub id: String,
+    pub device_id: String,
+    pub quantity: BreakpointQuantity,
+    pub op: CompareOp,
+    pub threshold: f64,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+impl Breakpoint {
+    pub fn validate(&self, topology: Option<&Topology>) -> Result<(), String> {
+        if !self.threshold.is_finite() {
+            return Err("断点阈值无效".to_string());
+        }
+        if let Some(t) = topology {
+            let device = t.devices.get(&self.device_id).ok_or_else(|| format!("设备不存在: {}", self.device_id))?;
+            if !self.quantity.applies_to(&device.device_type) {
+                return Err(format!("设备 {} 不支持该断点量", device.name));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 一次断点命中
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BreakpointHit {
+    pub breakpoint: Breakpoint,
+    /// 命中时的实际值
+    pub value: f64,
+}
+
+#[derive(Debug, Default)]
+pub struct BreakpointSet {
+    breakpoints: Vec<Breakpoint>,
+    /// 当前条件已满足的断点（满足期间不重复命中，恢复运行后需先回到不满足再次越过）
+    triggered: HashSet<String>,
+    next_id: u64,
+}
+
+impl BreakpointSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 新一轮仿真开始时清空命中状态（保留断点配置）
+    pub fn reset(&mut self) {
+        self.triggered.clear();
+    }
+
+    pub fn list(&self) -> Vec<Breakpoint> {
+        self.breakpoints.clone()
+    }
+
+    /// 登记或更新（按 ID）断点，返回生效的断点
+    pub fn upsert(&mut self, mut breakpoint: Breakpoint) -> Breakpoint {
+        if breakpoint.id.is_empty() {
+            self.next_id += 1;
+            breakpoint.id = format!("bp-{}", self.next_id);
+        }
+        self.triggered.remove(&breakpoint.id);
+        match self.breakpoints.iter_mut().find(|b| b.id == breakpoint.id) {
+            Some(existing) => *existing = breakpoint.clone(),
+            None => self.breakpoints.push(breakpoint.clone()),
+        }
+        breakpoint
+    }
+
+    pub fn remove(&mut self, id: &str) -> bool {
+        self.triggered.remove(id);
+        let before = self.breakpoints.len();
+        self.breakpoints.retain(|b| b.id != id);
+        self.breakpoints.len() != before
+    }
+
+    pub fn clear(&mut self) {
+        self.breakpoints.clear();
+        self.triggered.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.breakpoints.is_empty()
+    }
+
+    /// 评估一步结果：电压与负载率取真实潮流，SOC 取储能状态，功率取功率缓存；返回本步新命中的断点
+    pub fn evaluate(
+        &mut self,
+        results: &serde_json::Value,
+        topology: &Topology,
+        storage_state: &HashMap<String, StorageState>,
+        device_power: &HashMap<String, (f64, Option<f64>, Option<f64>)>,
+    ) -> Vec<BreakpointHit> {
+        let mut hits = Vec::new();
+        for breakpoint in self.breakpoints.iter().filter(|b| b.enabled) {
+            let Some(device) = topology.devices.get(&breakpoint.device_id) else { continue };
+            let row_value = |tables: &[&str], field: &str| {
+                tables
+                    .iter()
+                    .filter_map(|table| results.get(*table).and_then(|v| v.as_object()))
+                    .flat_map(|rows| rows.values())
+                    .find(|row| row.get("name").and_then(|v| v.as_str()) == Some(device.name.as_str()))
+                    .and_then(|row| row.get(field))
+                    .and_then(|v| v.as_f64())
+            };
+            let value = match breakpoint.quantity {
+                BreakpointQuantity::VoltagePu => row_value(&["buses"], "vm_pu"),
+                BreakpointQuantity::LoadingPercent => match device.device_type {
+                    DeviceType::Transformer => row_value(&["transformers"], "loading_percent"),
+                    _ => row_value(&["lines"], "loading_percent"),
+                },
+                BreakpointQuantity::SocPercent => storage_state.get(&breakpoint.device_id).map(|s| s.soc_percent),
+                BreakpointQuantity::PKw => device_power.get(&breakpoint.device_id).and_then(|(_, p, _)| *p),
+                BreakpointQuantity::QKvar => device_power.get(&breakpoint.device_id).and_then(|(_, _, q)| *q),
+            };
+            let Some(value) = value.filter(|v| v.is_finite()) else { continue };
+            if !breakpoint.op.holds(value, breakpoint.threshold) {
+                self.triggered.remove(&breakpoint.id);
+            } else if self.triggered.insert(breakpoint.id.clone()) {
+                hits.push(BreakpointHit { breakpoint: breakpoint.clone(), value });
+            }
+        }
+        hits
+    }
+}
@@ -0,0 +1,140 @@
+// 内核桥接性能统计：按 JSON-RPC 方法记录调用次数、失败次数与耗时分布（含降级内核的应答），
+// 用于判断单步耗时主要花在 set_topology、perform_calculation 还是其他请求上。
+// 耗时分位数按每个方法最近 LATENCY_WINDOW 次调用计算，次数与累计耗时自上次清零起累计
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex as StdMutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// 每个方法保留的最近耗时样本数
+const LATENCY_WINDOW: usize = 1024;
+
+#[derive(Default)]
+struct MethodStats {
+    calls: u64,
+    failures: u64,
+    kernel_errors: u64,
+    total_ms: f64,
+    max_ms: f64,
+    recent_ms: VecDeque<f64>,
+}
+
+/// 单个方法的统计
+#[derive(Debug, Clone, Serialize)]
+pub struct MethodMetrics {
+    pub method: String,
+    pub calls: u64,
+    /// 失败次数（含超时、连接失败与内核返回的错误）
+    pub failures: u64,
+    /// 其中内核返回的 JSON-RPC 错误
+    pub kernel_errors: u64,
+    pub failure_rate: f64,
+    pub avg_ms: f64,
+    pub p50_ms: f64,
+    pub p90_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+    /// 累计耗时及其占全部请求累计耗时的比例
+    pub total_ms: f64,
+    pub time_share: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BridgeMetrics {
+    /// 统计起点（Unix 秒）
+    pub since: f64,
+    pub total_calls: u64,
+    pub total_failures: u64,
+    /// 按累计耗时降序
+    pub methods: Vec<MethodMetrics>,
+}
+
+pub struct BridgeMetricsRecorder {
+    since: StdMutex<f64>,
+    methods: StdMutex<HashMap<String, MethodStats>>,
+}
+
+impl BridgeMetricsRecorder {
+    pub fn new() -> Self {
+        Self { since: StdMutex::new(now_secs()), methods: StdMutex::new(HashMap::new()) }
+    }
+
+    /// 记录一次调用；ok 为 false 时 kernel_error 区分内核返回的错误
+    pub fn record(&self, method: &str, elapsed: Duration, ok: bool, kernel_error: bool) {
+        let ms = elapsed.as_secs_f64() * 1000.0;
+        let mut methods = self.methods.lock().unwrap();
+        let stats = methods.entry(method.to_string()).or_default();
+        stats.calls += 1;
+        if !ok {
+            stats.failures += 1;
+            if kernel_error {
+                stats.kernel_errors += 1;
+            }
+        }
+        stats.total_ms += ms;
+        stats.max_ms = stats.max_ms.max(ms);
+        if stats.recent_ms.len() == LATENCY_WINDOW {
+            stats.recent_ms.pop_front();
+        }
+        stats.recent_ms.push_back(ms);
+    }
+
+    pub fn snapshot(&self) -> BridgeMetrics {
+        let methods = self.methods.lock().unwrap();
+        let grand_total_ms: f64 = methods.values().map(|s| s.total_ms).sum();
+        let mut rows: Vec<MethodMetrics> = methods
+            .iter()
+            .map(|(method, stats)| {
+                let mut sorted: Vec<f64> = stats.recent_ms.iter().copied().collect();
+                sorted.sort_by(|a, b| a.total_cmp(b));
+                MethodMetrics {
+                    method: method.clone(),
+                    calls: stats.calls,
+                    failures: stats.failures,
+                    kernel_errors: stats.kernel_errors,
+                    failure_rate: ratio(stats.failures as f64, stats.calls as f64),
+                    avg_ms: ratio(stats.total_ms, stats.calls as f64),
+                    p50_ms: percentile(&sorted, 0.50),
+                    p90_ms: percentile(&sorted, 0.90),
+                    p99_ms: percentile(&sorted, 0.99),
+                    max_ms: stats.max_ms,
+                    total_ms: stats.total_ms,
+                    time_share: ratio(stats.total_ms, grand_total_ms),
+                }
+            })
+            .collect();
+        rows.sort_by(|a, b| b.total_ms.total_cmp(&a.total_ms));
+        BridgeMetrics {
+            since: *self.since.lock().unwrap(),
+            total_calls: rows.iter().map(|r| r.calls).sum(),
+            total_failures: rows.iter().map(|r| r.failures).sum(),
+            methods: rows,
+        }
+    }
+
+    pub fn reset(&self) {
+        self.methods.lock().unwrap().clear();
+        *self.since.lock().unwrap() = now_secs();
+    }
+}
+
+fn ratio(numerator: f64, denominator: f64) -> f64 {
+    if denominator > 0.0 {
+        numerator / denominator
+    } else {
+        0.0
+    }
+}
+
+/// 最近邻秩分位数（样本已升序）
+fn percentile(sorted: &[f64], q: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (q * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+fn now_secs() -> f64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs_f64()).unwrap_or(0.0)
+}
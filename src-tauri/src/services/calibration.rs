@@ -0,0 +1,208 @@
+// 模型参数标定：基于现场数据以最小二乘拟合光伏系统效率/温度系数与储能充放电效率
+use crate::services::weather::{cell_temperature_c, temperature_derate};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 标准测试条件辐照度（W/m²）与电池温度（°C）
+const STC_IRRADIANCE: f64 = 1000.0;
+const STC_TEMPERATURE: f64 = 25.0;
+
+/// 拟合优度
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FitMetrics {
+    pub samples: usize,
+    pub rmse: f64,
+    pub mae: f64,
+    /// 决定系数 R²（样本方差为 0 时为 0）
+    pub r_squared: f64,
+}
+
+impl FitMetrics {
+    pub fn compute(observed: &[f64], predicted: &[f64]) -> Self {
+        let n = observed.len().min(predicted.len());
+        if n == 0 {
+            return Self::default();
+        }
+        let mean = observed[..n].iter().sum::<f64>() / n as f64;
+        let (mut sse, mut sae, mut sst) = (0.0, 0.0, 0.0);
+        for (o, p) in observed.iter().zip(predicted).take(n) {
+            let e = o - p;
+            sse += e * e;
+            sae += e.abs();
+            sst += (o - mean).powi(2);
+        }
+        Self {
+            samples: n,
+            rmse: (sse / n as f64).sqrt(),
+            mae: sae / n as f64,
+            r_squared: if sst > 0.0 { 1.0 - sse / sst } else { 0.0 },
+        }
+    }
+}
+
+/// 无截距线性最小二乘：y ≈ Σ β_j·x_j，解正规方程（仅支持 1~2 个自变量）
+fn least_squares(rows: &[Vec<f64>], y: &[f64]) -> Result<Vec<f64>, String> {
+    let k = rows.first().map(|r| r.len()).unwrap_or(0);
+    let mut ata = vec![vec![0.0; k]; k];
+    let mut aty = vec![0.0; k];
+    for (row, &yi) in rows.iter().zip(y) {
+        for (i, &xi) in row.iter().enumerate() {
+            aty[i] += xi * yi;
+            for (j, &xj) in row.iter().enumerate() {
+                ata[i][j] += xi * xj;
+            }
+        }
+    }
+    match k {
+        1 if ata[0][0].abs() > 1e-12 => Ok(vec![aty[0] / ata[0][0]]),
+        2 => {
+            let det = ata[0][0] * ata[1][1] - ata[0][1] * ata[1][0];
+            if det.abs() < 1e-12 {
+                return Err("数据不足以区分待拟合参数（自变量线性相关）".to_string());
+            }
+            Ok(vec![
+                (aty[0] * ata[1][1] - ata[0][1] * aty[1]) / det,
+                (ata[0][0] * aty[1] - ata[1][0] * aty[0]) / det,
+            ])
+        }
+        _ => Err("有效样本不足，无法拟合".to_string()),
+    }
+}
+
+/// 光伏模型（与 weather_model / solar_model 出力模型一致）：P = P_kwp × G/1000 × PR × (1 + γ/100·(T_cell − 25))，
+/// G 为组件面辐照，T_cell 由环境温度按 NOCT 估算；字段名即写回的设备属性名
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PvModelParams {
+    /// 系统效率（降额系数）
+    pub performance_ratio: f64,
+    /// 功率温度系数（%/°C，通常为负）
+    pub pv_temp_coeff_pct_per_c: f64,
+}
+
+impl PvModelParams {
+    pub fn predict(&self, kwp: f64, irradiance: f64, ambient_c: Option<f64>) -> f64 {
+        let temp_term = ambient_c
+            .map(|t| temperature_derate(self.pv_temp_coeff_pct_per_c, cell_temperature_c(t, irradiance)))
+            .unwrap_or(1.0);
+        kwp * irradiance / STC_IRRADIANCE * self.performance_ratio * temp_term
+    }
+}
+
+/// 光伏样本：(组件面辐照度 W/m², 环境温度 °C, 实测功率 kW)；样本不含温度时只拟合系统效率，温度系数沿用 prior_coeff_pct_per_c
+pub fn fit_pv(kwp: f64, samples: &[(f64, Option<f64>, f64)], prior_coeff_pct_per_c: f64) -> Result<PvModelParams, String> {
+    if kwp <= 0.0 {
+        return Err("光伏未配置装机容量或额定功率，无法标定".to_string());
+    }
+    // 辐照度过低时逆变器损耗占比大，不参与拟合
+    let used: Vec<&(f64, Option<f64>, f64)> = samples.iter().filter(|s| s.0 > 50.0).collect();
+    let with_temp = used.iter().all(|s| s.1.is_some()) && !used.is_empty();
+    let rows: Vec<Vec<f64>> = used
+        .iter()
+        .map(|(g, t, _)| {
+            let x = kwp * g / STC_IRRADIANCE;
+            match t.filter(|_| with_temp) {
+                Some(t) => vec![x, x * (cell_temperature_c(t, *g) - STC_TEMPERATURE)],
+                None => vec![x],
+            }
+        })
+        .collect();
+    let y: Vec<f64> = used.iter().map(|s| s.2).collect();
+    let beta = least_squares(&rows, &y)?;
+    let performance_ratio = beta[0];
+    if performance_ratio <= 0.0 {
+        return Err("拟合得到的系统效率非正，请检查功率与辐照度数据".to_string());
+    }
+    Ok(PvModelParams {
+        performance_ratio,
+        pv_temp_coeff_pct_per_c: if with_temp { beta[1] / performance_ratio * 100.0 } else { prior_coeff_pct_per_c },
+    })
+}
+
+/// 储能模型：ΔE = η_c·E_charge − E_discharge/η_d
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StorageModelParams {
+    pub charge_efficiency: f64,
+    pub discharge_efficiency: f64,
+}
+
+impl StorageModelParams {
+    pub fn predict_delta_kwh(&self, charge_kwh: f64, discharge_kwh: f64) -> f64 {
+        self.charge_efficiency * charge_kwh - discharge_kwh / self.discharge_efficiency.max(1e-6)
+    }
+
+    pub fn round_trip(&self) -> f64 {
+        self.charge_efficiency * self.discharge_efficiency
+    }
+}
+
+/// 储能样本：(时间戳 s, 功率 kW 正=充电, SOC %)；按相邻样本的 SOC 变化拟合充放电效率
+pub fn storage_intervals(capacity_kwh: f64, samples: &[(f64, f64, f64)]) -> Vec<(f64, f64, f64)> {
+    samples
+        .windows(2)
+        .filter_map(|w| {
+            let dt_h = (w[1].0 - w[0].0) / 3600.0;
+            if dt_h <= 0.0 {
+                return None;
+            }
+            let p = (w[0].1 + w[1].1) * 0.5;
+            let delta = (w[1].2 - w[0].2) / 100.0 * capacity_kwh;
+            Some((p.max(0.0) * dt_h, (-p).max(0.0) * dt_h, delta))
+        })
+        .collect()
+}
+
+pub fn fit_storage(intervals: &[(f64, f64, f64)]) -> Result<StorageModelParams, String> {
+    let rows: Vec<Vec<f64>> = intervals.iter().map(|(c, d, _)| vec![*c, -*d]).collect();
+    let y: Vec<f64> = intervals.iter().map(|i| i.2).collect();
+    if !intervals.iter().any(|i| i.0 > 0.0) || !intervals.iter().any(|i| i.1 > 0.0) {
+        return Err("数据需同时包含充电与放电时段".to_string());
+    }
+    let beta = least_squares(&rows, &y)?;
+    let (eta_c, inv_eta_d) = (beta[0], beta[1]);
+    if eta_c <= 0.0 || inv_eta_d <= 0.0 {
+        return Err("拟合得到的效率非正，请检查功率符号与 SOC 数据".to_string());
+    }
+    Ok(StorageModelParams {
+        charge_efficiency: eta_c.min(1.0),
+        discharge_efficiency: (1.0 / inv_eta_d).min(1.0),
+    })
+}
+
+/// 从 CSV 读取指定列（列名不区分大小写）；返回每行各列的数值（无法解析的行跳过）
+pub fn read_csv_columns(path: &str, columns: &[&str], optional: &[&str]) -> Result<Vec<HashMap<String, f64>>, String> {
+    let mut reader = csv::Reader::from_path(path).map_err(|e| format!("打开现场数据文件失败: {}", e))?;
+    let headers: Vec<String> = reader
+        .headers()
+        .map_err(|e| format!("读取表头失败: {}", e))?
+        .iter()
+        .map(|h| h.trim().to_lowercase())
+        .collect();
+    let index = |name: &str| headers.iter().position(|h| h == &name.to_lowercase());
+    let mut idx = Vec::new();
+    for c in columns {
+        idx.push((c.to_string(), index(c).ok_or_else(|| format!("现场数据缺少列: {}", c))?, true));
+    }
+    for c in optional {
+        if let Some(i) = index(c) {
+            idx.push((c.to_string(), i, false));
+        }
+    }
+    let mut rows = Vec::new();
+    for record in reader.records().filter_map(|r| r.ok()) {
+        let mut row = HashMap::new();
+        let mut complete = true;
+        for (name, i, required) in &idx {
+            match record.get(*i).and_then(|v| v.trim().parse::<f64>().ok()).filter(|v| v.is_finite()) {
+                Some(v) => {
+                    row.insert(name.clone(), v);
+                }
+                None if *required => complete = false,
+                None => {}
+            }
+        }
+        if complete {
+            rows.push(row);
+        }
+    }
+    Ok(rows)
+}
@@ -0,0 +1,301 @@
+// 曲线图渲染：按规格从仿真库读取若干设备的功率 / 数据项曲线，用 plotters 输出 SVG 或 PNG，
+// 供 render_chart 命令、分析报告与命令行场景运行共用（无界面时生成报告配图）
+use crate::services::database::Database;
+use plotters::coord::Shift;
+use std::sync::Mutex;
+use plotters::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 单条曲线默认最大点数（超出时由库查询降采样）
+const DEFAULT_MAX_POINTS: usize = 2000;
+const MIN_SIZE: u32 = 200;
+const MAX_SIZE: u32 = 4000;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChartFormat {
+    #[default]
+    Svg,
+    Png,
+}
+
+impl ChartFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Svg => "svg",
+            Self::Png => "png",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChartSeriesSpec {
+    pub device_id: String,
+    /// p_active / p_reactive，或 data_json 中的数值字段（如 soc、vm_pu）
+    #[serde(default = "default_field")]
+    pub field: String,
+    /// 图例名称，默认 设备 id.字段
+    #[serde(default)]
+    pub label: Option<String>,
+    /// 数值倍率（如 W -> kW 取 0.001）
+    #[serde(default)]
+    pub scale: Option<f64>,
+}
+
+fn default_field() -> String {
+    "p_active".to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChartSpec {
+    #[serde(default)]
+    pub title: String,
+    pub series: Vec<ChartSeriesSpec>,
+    /// 数据来源库；不传时为当前仿真库
+    #[serde(default)]
+    pub db_path: Option<String>,
+    #[serde(default)]
+    pub start_time: Option<f64>,
+    #[serde(default)]
+    pub end_time: Option<f64>,
+    #[serde(default)]
+    pub format: ChartFormat,
+    #[serde(default = "default_width")]
+    pub width: u32,
+    #[serde(default = "default_height")]
+    pub height: u32,
+    /// 纵轴说明（如 kW）
+    #[serde(default)]
+    pub y_label: Option<String>,
+    #[serde(default)]
+    pub max_points: Option<usize>,
+    /// 图例附带各曲线的均值 / 最小 / 最大（KPI 摘要）
+    #[serde(default)]
+    pub show_stats: bool,
+    /// 输出文件；PNG 必须指定，SVG 不指定时以文本返回
+    #[serde(default)]
+    pub output_path: Option<String>,
+}
+
+fn default_width() -> u32 {
+    1200
+}
+
+fn default_height() -> u32 {
+    600
+}
+
+impl ChartSpec {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.series.is_empty() {
+            return Err("曲线图至少需要一条曲线".to_string());
+        }
+        if !(MIN_SIZE..=MAX_SIZE).contains(&self.width) || !(MIN_SIZE..=MAX_SIZE).contains(&self.height) {
+            return Err(format!("图片尺寸须在 {}~{} 像素之间", MIN_SIZE, MAX_SIZE));
+        }
+        if let (Some(start), Some(end)) = (self.start_time, self.end_time) {
+            if end <= start {
+                return Err("结束时间须晚于开始时间".to_string());
+            }
+        }
+        if self.format == ChartFormat::Png && self.output_path.as_deref().is_none_or(|p| p.trim().is_empty()) {
+            return Err("PNG 格式须指定输出文件".to_string());
+        }
+        for s in &self.series {
+            if s.scale.is_some_and(|k| !k.is_finite() || k == 0.0) {
+                return Err(format!("曲线 {} 的倍率无效", s.device_id));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 曲线摘要（均值 / 最小 / 最大），随渲染结果返回
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChartSeriesStats {
+    pub label: String,
+    pub points: usize,
+    pub mean: Option<f64>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenderedChart {
+    pub format: ChartFormat,
+    /// 写入的文件（指定 output_path 时）
+    #[serde(default)]
+    pub output_path: Option<String>,
+    /// SVG 文本（未指定 output_path 时）
+    #[serde(default)]
+    pub svg: Option<String>,
+    pub series: Vec<ChartSeriesStats>,
+}
+
+struct LoadedSeries {
+    label: String,
+    /// (相对起点秒, 值)
+    points: Vec<(f64, f64)>,
+}
+
+fn series_value(field: &str, p_active: Option<f64>, p_reactive: Option<f64>, data_json: Option<&serde_json::Value>) -> Option<f64> {
+    match field {
+        "p_active" => p_active,
+        "p_reactive" => p_reactive,
+        _ => data_json.and_then(|d| d.get(field)).and_then(|v| v.as_f64()),
+    }
+}
+
+fn load_series(db: &Database, spec: &ChartSpec) -> Result<(f64, Vec<LoadedSeries>), String> {
+    let max_points = spec.max_points.unwrap_or(DEFAULT_MAX_POINTS).max(2);
+    let mut raw = Vec::with_capacity(spec.series.len());
+    for s in &spec.series {
+        let rows = db
+            .query_device_data(&s.device_id, spec.start_time, spec.end_time, Some(max_points))
+            .map_err(|e| format!("查询设备 {} 数据失败: {}", s.device_id, e))?;
+        let scale = s.scale.unwrap_or(1.0);
+        let points: Vec<(f64, f64)> = rows
+            .into_iter()
+            .filter_map(|(ts, p_a, p_r, json)| {
+                let data = json.as_deref().and_then(|j| serde_json::from_str::<serde_json::Value>(j).ok());
+                series_value(&s.field, p_a, p_r, data.as_ref()).filter(|v| v.is_finite()).map(|v| (ts, v * scale))
+            })
+            .collect();
+        let label = s.label.clone().unwrap_or_else(|| format!("{}.{}", s.device_id, s.field));
+        raw.push(LoadedSeries { label, points });
+    }
+    let origin = spec
+        .start_time
+        .or_else(|| raw.iter().filter_map(|s| s.points.first().map(|p| p.0)).reduce(f64::min))
+        .unwrap_or(0.0);
+    for s in &mut raw {
+        for p in &mut s.points {
+            p.0 -= origin;
+        }
+    }
+    Ok((origin, raw))
+}
+
+fn stats(series: &LoadedSeries) -> ChartSeriesStats {
+    let values = series.points.iter().map(|p| p.1);
+    let n = series.points.len();
+    ChartSeriesStats {
+        label: series.label.clone(),
+        points: n,
+        mean: (n > 0).then(|| values.clone().sum::<f64>() / n as f64),
+        min: values.clone().reduce(f64::min),
+        max: values.reduce(f64::max),
+    }
+}
+
+/// 横轴刻度：相对起点秒换算为本地时刻（跨天时带日期）
+fn time_label(origin: f64, span_s: f64, x: f64) -> String {
+    let fmt = if span_s > 86400.0 { "%m-%d %H:%M" } else { "%H:%M:%S" };
+    chrono::DateTime::from_timestamp_millis(((origin + x) * 1000.0) as i64)
+        .map(|t| t.with_timezone(&chrono::Local).format(fmt).to_string())
+        .unwrap_or_default()
+}
+
+fn draw<DB: DrawingBackend>(
+    root: DrawingArea<DB, Shift>,
+    spec: &ChartSpec,
+    origin: f64,
+    series: &[LoadedSeries],
+    stats: &[ChartSeriesStats],
+) -> Result<(), String>
+where
+    DB::ErrorType: 'static,
+{
+    let err = |e: DrawingAreaErrorKind<DB::ErrorType>| format!("绘制曲线图失败: {}", e);
+    let all = series.iter().flat_map(|s| s.points.iter());
+    let x_max = spec
+        .end_time
+        .map(|t| t - origin)
+        .or_else(|| all.clone().map(|p| p.0).reduce(f64::max))
+        .unwrap_or(1.0)
+        .max(1.0);
+    let (mut y_min, mut y_max) = all.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| (lo.min(p.1), hi.max(p.1)));
+    if !y_min.is_finite() {
+        (y_min, y_max) = (0.0, 1.0);
+    }
+    let pad = ((y_max - y_min) * 0.05).max(1e-3);
+
+    root.fill(&WHITE).map_err(err)?;
+    let mut chart = ChartBuilder::on(&root)
+        .caption(&spec.title, ("sans-serif", 22))
+        .margin(12)
+        .x_label_area_size(36)
+        .y_label_area_size(64)
+        .build_cartesian_2d(0.0..x_max, (y_min - pad)..(y_max + pad))
+        .map_err(err)?;
+    let x_formatter = |x: &f64| time_label(origin, x_max, *x);
+    chart
+        .configure_mesh()
+        .x_labels(8)
+        .x_label_formatter(&x_formatter)
+        .y_desc(spec.y_label.clone().unwrap_or_default())
+        .draw()
+        .map_err(err)?;
+    for (i, (s, st)) in series.iter().zip(stats).enumerate() {
+        let color = Palette99::pick(i).to_rgba();
+        let label = match (spec.show_stats, st.mean, st.min, st.max) {
+            (true, Some(mean), Some(min), Some(max)) => {
+                format!("{}（均值 {:.2} / 最小 {:.2} / 最大 {:.2}）", s.label, mean, min, max)
+            }
+            _ => s.label.clone(),
+        };
+        chart
+            .draw_series(LineSeries::new(s.points.iter().copied(), color.stroke_width(2)))
+            .map_err(err)?
+            .label(label)
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color.stroke_width(2)));
+    }
+    chart
+        .configure_series_labels()
+        .position(SeriesLabelPosition::UpperRight)
+        .background_style(WHITE.mix(0.85))
+        .border_style(BLACK)
+        .draw()
+        .map_err(err)?;
+    root.present().map_err(err)
+}
+
+/// 按规格与已读出的曲线渲染曲线图（不访问数据库）
+fn render_loaded(spec: &ChartSpec, origin: f64, series: &[LoadedSeries]) -> Result<RenderedChart, String> {
+    let series_stats: Vec<ChartSeriesStats> = series.iter().map(stats).collect();
+    let size = (spec.width, spec.height);
+    let output_path = spec.output_path.clone().filter(|p| !p.trim().is_empty());
+    let svg = match (spec.format, output_path.as_deref()) {
+        (ChartFormat::Png, Some(path)) => {
+            draw(BitMapBackend::new(path, size).into_drawing_area(), spec, origin, series, &series_stats)?;
+            None
+        }
+        (ChartFormat::Svg, Some(path)) => {
+            draw(SVGBackend::new(path, size).into_drawing_area(), spec, origin, series, &series_stats)?;
+            None
+        }
+        (ChartFormat::Svg, None) => {
+            let mut buf = String::new();
+            draw(SVGBackend::with_string(&mut buf, size).into_drawing_area(), spec, origin, series, &series_stats)?;
+            Some(buf)
+        }
+        (ChartFormat::Png, None) => return Err("PNG 格式须指定输出文件".to_string()),
+    };
+    Ok(RenderedChart { format: spec.format, output_path, svg, series: series_stats })
+}
+
+/// 打开规格指定的历史库渲染；未指定 db_path 时读取当前库，只在读取曲线期间持有库锁，绘制前释放（不阻塞落库）
+pub fn render_with_fallback(spec: &ChartSpec, current: &Mutex<Option<Database>>) -> Result<RenderedChart, String> {
+    spec.validate()?;
+    let (origin, series) = match spec.db_path.as_deref().filter(|p| !p.trim().is_empty()) {
+        Some(path) => {
+            let db = Database::open_readonly(std::path::Path::new(path)).map_err(|e| format!("打开历史数据库失败: {}", e))?;
+            load_series(&db, spec)?
+        }
+        None => {
+            let guard = current.lock().unwrap();
+            load_series(guard.as_ref().ok_or("当前没有仿真数据库")?, spec)?
+        }
+    };
+    render_loaded(spec, origin, &series)
+}
@@ -0,0 +1,224 @@
+// 命令行数据导出：`<程序> export --db run.db --devices pv1,storage1 --fields p_mw,soc --from ... --to ... --format csv`，
+// 不启动界面与 Python 内核，经统一 DataSource 读取后按时间戳对齐成宽表，输出到 stdout 或 --out 文件
+use crate::commands::dashboard::ColumnMeta;
+use crate::services::column_parser::ColumnNameParser;
+use crate::services::data_source::{open_data_source, DataSourceContext, DataSourceSpec};
+use crate::services::timezone::{parse_timestamp, ImportTimezone};
+use std::collections::BTreeMap;
+use std::io::Write;
+
+/// 命令行子命令名
+pub const EXPORT_SUBCOMMAND: &str = "export";
+
+const USAGE: &str = "用法: export (--db <仿真库> | --wide-csv <文件> | --long-csv <文件>) \
+[--devices id1,id2] [--fields p_active,p_mw,soc] [--from 时间] [--to 时间] [--tz utc|local|+08:00] \
+[--format csv|json] [--out <文件>]";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    /// JSON 数组，每个时间戳一条记录
+    Json,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportArgs {
+    pub source: DataSourceSpec,
+    /// 为空时导出全部设备
+    pub devices: Vec<String>,
+    /// 为空时导出全部字段
+    pub fields: Vec<String>,
+    pub from: Option<f64>,
+    pub to: Option<f64>,
+    pub format: ExportFormat,
+    pub output: Option<String>,
+}
+
+/// 导出字段别名：p_mw / q_mvar 由库中 kW / kVar 换算
+fn resolve_field(field: &str) -> (&str, f64) {
+    match field {
+        "p_mw" => ("p_active", 0.001),
+        "q_mvar" => ("p_reactive", 0.001),
+        "p_kw" => ("p_active", 1.0),
+        "q_kvar" => ("p_reactive", 1.0),
+        other => (other, 1.0),
+    }
+}
+
+fn parse_timezone(s: &str) -> Result<ImportTimezone, String> {
+    let tz = match s.to_ascii_lowercase().as_str() {
+        "utc" => ImportTimezone::Utc,
+        "local" => ImportTimezone::Local,
+        other => {
+            let (sign, rest) = match other.as_bytes().first() {
+                Some(b'-') => (-1, &other[1..]),
+                Some(b'+') => (1, &other[1..]),
+                _ => return Err(format!("无效的时区: {}", s)),
+            };
+            let (h, m) = rest.split_once(':').unwrap_or((rest, "0"));
+            let h: i32 = h.parse().map_err(|_| format!("无效的时区: {}", s))?;
+            let m: i32 = m.parse().map_err(|_| format!("无效的时区: {}", s))?;
+            ImportTimezone::FixedOffset { offset_minutes: sign * (h * 60 + m) }
+        }
+    };
+    tz.validate()?;
+    Ok(tz)
+}
+
+fn split_list(s: &str) -> Vec<String> {
+    s.split(',').map(|x| x.trim().to_string()).filter(|x| !x.is_empty()).collect()
+}
+
+/// 解析 export 子命令参数（args 为子命令之后的参数）
+pub fn parse_args(args: &[String]) -> Result<ExportArgs, String> {
+    let mut values: Vec<(String, String)> = Vec::new();
+    let mut it = args.iter();
+    while let Some(flag) = it.next() {
+        if !flag.starts_with("--") {
+            return Err(format!("无法识别的参数: {}\n{}", flag, USAGE));
+        }
+        let value = it.next().ok_or_else(|| format!("参数 {} 缺少取值\n{}", flag, USAGE))?;
+        values.push((flag.trim_start_matches("--").to_string(), value.clone()));
+    }
+    let get = |name: &str| values.iter().rev().find(|(k, _)| k == name).map(|(_, v)| v.as_str());
+    if let Some((k, _)) = values.iter().find(|(k, _)| {
+        !["db", "wide-csv", "long-csv", "devices", "fields", "from", "to", "tz", "format", "out"].contains(&k.as_str())
+    }) {
+        return Err(format!("未知参数: --{}\n{}", k, USAGE));
+    }
+
+    let timezone = get("tz").map(parse_timezone).transpose()?.unwrap_or_default();
+    let source = match (get("db"), get("wide-csv"), get("long-csv")) {
+        (Some(path), None, None) => DataSourceSpec::Db { path: path.to_string() },
+        (None, Some(path), None) => DataSourceSpec::WideCsv {
+            path: path.to_string(),
+            column_parser: ColumnNameParser::default(),
+            timezone,
+        },
+        (None, None, Some(path)) => DataSourceSpec::LongCsv { path: path.to_string(), timezone },
+        _ => return Err(format!("须且只能指定一个数据源（--db / --wide-csv / --long-csv）\n{}", USAGE)),
+    };
+    let parse_time = |name: &str| -> Result<Option<f64>, String> {
+        get(name)
+            .map(|s| parse_timestamp(s, &timezone).ok_or_else(|| format!("无法解析时间 --{}: {}", name, s)))
+            .transpose()
+    };
+    let (from, to) = (parse_time("from")?, parse_time("to")?);
+    if let (Some(f), Some(t)) = (from, to) {
+        if t <= f {
+            return Err("--to 须晚于 --from".to_string());
+        }
+    }
+    let format = match get("format").unwrap_or("csv") {
+        "csv" => ExportFormat::Csv,
+        "json" => ExportFormat::Json,
+        other => return Err(format!("不支持的导出格式: {}（csv / json）", other)),
+    };
+    Ok(ExportArgs {
+        source,
+        devices: get("devices").map(split_list).unwrap_or_default(),
+        fields: get("fields").map(split_list).unwrap_or_default(),
+        from,
+        to,
+        format,
+        output: get("out").map(str::to_string),
+    })
+}
+
+/// 按设备与字段筛选数据列：(输出列名, 数据列 key, 倍率)
+fn select_columns(columns: &[ColumnMeta], devices: &[String], fields: &[String]) -> Result<Vec<(String, String, f64)>, String> {
+    let device_match = |c: &ColumnMeta| devices.is_empty() || devices.iter().any(|d| *d == c.device_sn);
+    let mut selected = Vec::new();
+    if fields.is_empty() {
+        for c in columns.iter().filter(|c| device_match(c)) {
+            selected.push((format!("{}.{}", c.device_sn, c.data_item), c.key.clone(), 1.0));
+        }
+    } else {
+        for field in fields {
+            let (data_item, scale) = resolve_field(field);
+            for c in columns.iter().filter(|c| device_match(c) && c.data_item == data_item) {
+                selected.push((format!("{}.{}", c.device_sn, field), c.key.clone(), scale));
+            }
+        }
+    }
+    for d in devices {
+        if !columns.iter().any(|c| c.device_sn == *d) {
+            return Err(format!("数据源中不存在设备: {}", d));
+        }
+    }
+    if selected.is_empty() {
+        return Err("没有匹配的数据列".to_string());
+    }
+    Ok(selected)
+}
+
+fn csv_cell(value: Option<f64>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+/// 执行导出，返回导出的时间戳行数
+pub fn run(args: &ExportArgs) -> Result<usize, String> {
+    let source = open_data_source(&args.source, &DataSourceContext::default())?;
+    let columns = select_columns(&source.list_keys()?, &args.devices, &args.fields)?;
+    // 按毫秒时间戳对齐各列
+    let mut rows: BTreeMap<i64, Vec<Option<f64>>> = BTreeMap::new();
+    for (i, (_, key, scale)) in columns.iter().enumerate() {
+        for p in source.fetch_series(key, args.from, args.to, usize::MAX)? {
+            let row = rows.entry((p.timestamp * 1000.0).round() as i64).or_insert_with(|| vec![None; columns.len()]);
+            row[i] = Some(p.value * scale);
+        }
+    }
+
+    let mut out: Box<dyn Write> = match args.output.as_deref() {
+        Some(path) => Box::new(std::io::BufWriter::new(
+            std::fs::File::create(path).map_err(|e| format!("创建输出文件失败 {}: {}", path, e))?,
+        )),
+        None => Box::new(std::io::BufWriter::new(std::io::stdout().lock())),
+    };
+    let io_err = |e: std::io::Error| format!("写入导出数据失败: {}", e);
+    match args.format {
+        ExportFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(out);
+            let header = std::iter::once("timestamp".to_string()).chain(columns.iter().map(|c| c.0.clone()));
+            writer.write_record(header).map_err(|e| format!("写入导出数据失败: {}", e))?;
+            for (ts, values) in &rows {
+                let record = std::iter::once((*ts as f64 / 1000.0).to_string()).chain(values.iter().map(|v| csv_cell(*v)));
+                writer.write_record(record).map_err(|e| format!("写入导出数据失败: {}", e))?;
+            }
+            writer.flush().map_err(io_err)?;
+        }
+        ExportFormat::Json => {
+            let records: Vec<serde_json::Value> = rows
+                .iter()
+                .map(|(ts, values)| {
+                    let mut record = serde_json::Map::new();
+                    record.insert("timestamp".to_string(), serde_json::json!(*ts as f64 / 1000.0));
+                    for ((name, _, _), v) in columns.iter().zip(values) {
+                        record.insert(name.clone(), serde_json::json!(v));
+                    }
+                    serde_json::Value::Object(record)
+                })
+                .collect();
+            serde_json::to_writer_pretty(&mut out, &records).map_err(|e| format!("写入导出数据失败: {}", e))?;
+            writeln!(out).map_err(io_err)?;
+            out.flush().map_err(io_err)?;
+        }
+    }
+    Ok(rows.len())
+}
+
+/// 命令行入口：返回进程退出码（0 成功，2 参数或读取错误）
+pub fn run_cli(args: &[String]) -> i32 {
+    match parse_args(args).and_then(|a| run(&a).map(|n| (a, n))) {
+        Ok((a, n)) => {
+            if let Some(path) = &a.output {
+                eprintln!("已导出 {} 行到 {}", n, path);
+            }
+            0
+        }
+        Err(e) => {
+            eprintln!("导出失败: {}", e);
+            2
+        }
+    }
+}
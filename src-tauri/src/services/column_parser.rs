@@ -0,0 +1,167 @@
+// 宽表 CSV 列名解析策略：将各厂商导出格式的列名拆为 (设备 SN, 数据项)，可按导入选择内置模板或自定义正则
+use crate::commands::dashboard::{make_short_label, ColumnMeta};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// 列名解析策略；series 的 key 始终为原始列名，策略只影响列元信息（设备 SN、数据项、图例标签）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ColumnNameParser {
+    /// 默认：{SN}_{dataItem}，SN 为大写字母+数字
+    #[default]
+    UppercaseSnPrefix,
+    /// 正则模板：须包含命名捕获组 sn 与 item，未匹配的列归入未分组
+    Regex { pattern: String },
+}
+
+/// 内置模板（前端下拉选择）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnParserTemplate {
+    pub name: String,
+    pub description: String,
+    pub example: String,
+    pub parser: ColumnNameParser,
+}
+
+/// 解析预览：按策略解析表头，统计匹配到设备 SN 的列数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnParsePreview {
+    pub columns: Vec<ColumnMeta>,
+    pub matched: usize,
+    pub unmatched: usize,
+    /// 解析出的不重复设备 SN（按出现顺序）
+    pub device_sns: Vec<String>,
+}
+
+/// 默认策略：SN 全为大写字母或数字，后跟 _ 和数据项
+/// 例：TESAR125261GT00CN251225002_activePowerLimit → (TESAR125261GT00CN251225002, activePowerLimit)
+/// 例：TMEAD35K050EI00CN251209001_active_power → (TMEAD35K050EI00CN251209001, active_power)
+pub fn parse_uppercase_sn_prefix(col: &str) -> (String, String) {
+    let bytes = col.as_bytes();
+    let mut split_pos = None;
+    for i in 0..bytes.len() {
+        if bytes[i] == b'_' && i > 0 {
+            // 检查 _ 之前是否全部为大写字母/数字
+            let prefix_valid = bytes[..i].iter().all(|b| b.is_ascii_uppercase() || b.is_ascii_digit());
+            if prefix_valid && i + 1 < bytes.len() {
+                split_pos = Some(i);
+                break;
+            }
+        }
+    }
+    match split_pos {
+        Some(pos) => (col[..pos].to_string(), col[pos + 1..].to_string()),
+        None => (String::new(), col.to_string()),
+    }
+}
+
+/// 编译后的解析器
+pub enum CompiledColumnParser {
+    UppercaseSnPrefix,
+    Regex(Regex),
+}
+
+impl CompiledColumnParser {
+    pub fn parse(&self, col: &str) -> (String, String) {
+        match self {
+            Self::UppercaseSnPrefix => parse_uppercase_sn_prefix(col),
+            Self::Regex(re) => match re.captures(col) {
+                Some(caps) => match (caps.name("sn"), caps.name("item")) {
+                    (Some(sn), Some(item)) if !sn.as_str().is_empty() && !item.as_str().is_empty() => {
+                        (sn.as_str().to_string(), item.as_str().to_string())
+                    }
+                    _ => (String::new(), col.to_string()),
+                },
+                None => (String::new(), col.to_string()),
+            },
+        }
+    }
+}
+
+impl ColumnNameParser {
+    /// 校验并编译：正则须能编译且包含命名捕获组 sn 与 item
+    pub fn compile(&self) -> Result<CompiledColumnParser, String> {
+        match self {
+            Self::UppercaseSnPrefix => Ok(CompiledColumnParser::UppercaseSnPrefix),
+            Self::Regex { pattern } => {
+                let re = Regex::new(pattern).map_err(|e| format!("列名正则无效: {}", e))?;
+                let names: Vec<&str> = re.capture_names().flatten().collect();
+                if !names.contains(&"sn") || !names.contains(&"item") {
+                    return Err("列名正则须包含命名捕获组 (?P<sn>...) 与 (?P<item>...)".to_string());
+                }
+                Ok(CompiledColumnParser::Regex(re))
+            }
+        }
+    }
+
+    /// 按本策略重新计算列元信息（设备匹配结果清空，由调用方重新匹配）
+    pub fn apply(&self, columns: &mut [ColumnMeta]) -> Result<(), String> {
+        if *self == Self::UppercaseSnPrefix {
+            return Ok(());
+        }
+        let parser = self.compile()?;
+        for col in columns.iter_mut() {
+            let (device_sn, data_item) = parser.parse(&col.key);
+            col.short_label = make_short_label(&device_sn, &data_item);
+            col.device_sn = device_sn;
+            col.data_item = data_item;
+            col.device_id = None;
+        }
+        Ok(())
+    }
+
+    /// 内置模板
+    pub fn templates() -> Vec<ColumnParserTemplate> {
+        let template = |name: &str, description: &str, example: &str, parser: ColumnNameParser| ColumnParserTemplate {
+            name: name.to_string(),
+            description: description.to_string(),
+            example: example.to_string(),
+            parser,
+        };
+        let regex = |pattern: &str| ColumnNameParser::Regex { pattern: pattern.to_string() };
+        vec![
+            template(
+                "uppercase_sn_prefix",
+                "大写 SN + 下划线 + 数据项",
+                "TMEAD35K050EI00CN251209001_active_power",
+                ColumnNameParser::UppercaseSnPrefix,
+            ),
+            template("sn_dot_item", "SN + 点号 + 数据项", "sn-1234.activePower", regex(r"^(?P<sn>[^.]+)\.(?P<item>.+)$")),
+            template("device_slash_item", "设备名 + 斜杠 + 数据项", "devA/active_power", regex(r"^(?P<sn>[^/]+)/(?P<item>.+)$")),
+            template(
+                "item_bracket_sn",
+                "数据项 + 方括号内 SN",
+                "active_power[INV-01]",
+                regex(r"^(?P<item>[^\[]+)\[(?P<sn>[^\]]+)\]$"),
+            ),
+        ]
+    }
+}
+
+/// 按策略解析表头生成预览
+pub fn preview(headers: &[String], parser: &ColumnNameParser) -> Result<ColumnParsePreview, String> {
+    let compiled = parser.compile()?;
+    let mut device_sns: Vec<String> = Vec::new();
+    let mut matched = 0;
+    let columns: Vec<ColumnMeta> = headers
+        .iter()
+        .map(|key| {
+            let (device_sn, data_item) = compiled.parse(key);
+            if !device_sn.is_empty() {
+                matched += 1;
+                if !device_sns.contains(&device_sn) {
+                    device_sns.push(device_sn.clone());
+                }
+            }
+            ColumnMeta {
+                key: key.clone(),
+                short_label: make_short_label(&device_sn, &data_item),
+                device_sn,
+                data_item,
+                device_id: None,
+            }
+        })
+        .collect();
+    let unmatched = columns.len() - matched;
+    Ok(ColumnParsePreview { columns, matched, unmatched, device_sns })
+}
@@ -0,0 +1,327 @@
+// 并网规范符合性测试：预定义测试序列自动驱动外部电网/母线边界条件，记录设备响应并给出判定
+use crate::domain::device::WorkMode;
+use crate::services::simulation_engine::SimulationEngine;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex as StdMutex;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter};
+
+/// 测试步骤施加的边界条件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BoundaryAction {
+    /// 外部电网电压（pu）
+    GridVoltage { vm_pu: f64 },
+    /// 电网频率（Hz），激活孤岛频率模型
+    GridFrequency { frequency_hz: f64 },
+    /// 被测设备有功设定（额定功率比例）与无功设定（kVar），测试期间设备切换为手动模式
+    DeviceSetpoint { p_ratio: f64, q_kvar: f64 },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComplianceStep {
+    /// 相对测试开始的时间（秒）
+    pub at_s: f64,
+    pub action: BoundaryAction,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComplianceTestDef {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub duration_s: f64,
+    pub steps: Vec<ComplianceStep>,
+}
+
+/// 单次采样：当前边界条件与被测设备响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComplianceSample {
+    pub t_s: f64,
+    pub vm_pu: f64,
+    pub frequency_hz: f64,
+    pub setpoint_kw: Option<f64>,
+    pub p_kw: Option<f64>,
+    pub q_kvar: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComplianceCheck {
+    pub name: String,
+    pub passed: bool,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComplianceResult {
+    pub test_id: String,
+    pub device_id: String,
+    pub started_at: f64,
+    pub finished_at: f64,
+    pub samples: Vec<ComplianceSample>,
+    pub checks: Vec<ComplianceCheck>,
+    pub passed: bool,
+}
+
+/// 已完成的符合性测试结果（按完成顺序）
+#[derive(Default)]
+pub struct ComplianceResultStore {
+    results: StdMutex<Vec<ComplianceResult>>,
+}
+
+impl ComplianceResultStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&self, result: ComplianceResult) {
+        self.results.lock().unwrap().push(result);
+    }
+
+    pub fn list(&self) -> Vec<ComplianceResult> {
+        self.results.lock().unwrap().clone()
+    }
+
+    pub fn clear(&self) {
+        self.results.lock().unwrap().clear();
+    }
+}
+
+const NOMINAL_FREQUENCY_HZ: f64 = 50.0;
+/// 采样轮询间隔（毫秒）；仅在设备功率时间戳更新时记录新样本
+const SAMPLE_POLL_MS: u64 = 100;
+
+/// 预定义测试序列
+pub fn predefined_tests() -> Vec<ComplianceTestDef> {
+    let voltage = |at_s: f64, vm_pu: f64| ComplianceStep { at_s, action: BoundaryAction::GridVoltage { vm_pu } };
+    let frequency = |at_s: f64, frequency_hz: f64| ComplianceStep { at_s, action: BoundaryAction::GridFrequency { frequency_hz } };
+    let setpoint = |at_s: f64, p_ratio: f64| ComplianceStep { at_s, action: BoundaryAction::DeviceSetpoint { p_ratio, q_kvar: 0.0 } };
+    vec![
+        ComplianceTestDef {
+            id: "lvrt_dip".to_string(),
+            name: "低电压穿越".to_string(),
+            description: "外部电网电压跌落至 0.5 pu 并分段恢复，检验设备不脱网且恢复后出力恢复".to_string(),
+            duration_s: 14.0,
+            steps: vec![
+                voltage(0.0, 1.0),
+                voltage(3.0, 0.5),
+                voltage(5.0, 0.7),
+                voltage(7.0, 0.9),
+                voltage(9.0, 1.0),
+            ],
+        },
+        ComplianceTestDef {
+            id: "frequency_ramp".to_string(),
+            name: "频率斜坡".to_string(),
+            description: "孤岛频率从 50 Hz 分段升至 51.5 Hz 后恢复，检验过频降功率（需启用 P(f) 响应）".to_string(),
+            duration_s: 16.0,
+            steps: vec![
+                frequency(0.0, 50.0),
+                frequency(3.0, 50.3),
+                frequency(5.0, 50.8),
+                frequency(7.0, 51.5),
+                frequency(10.0, 50.0),
+            ],
+        },
+        ComplianceTestDef {
+            id: "setpoint_step".to_string(),
+            name: "有功设定阶跃".to_string(),
+            description: "被测设备有功设定 0 → 50% → 100% → 20% 额定功率阶跃，检验跟踪精度".to_string(),
+            duration_s: 16.0,
+            steps: vec![
+                setpoint(0.0, 0.0),
+                setpoint(4.0, 0.5),
+                setpoint(8.0, 1.0),
+                setpoint(12.0, 0.2),
+            ],
+        },
+    ]
+}
+
+fn now_secs() -> f64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs_f64()
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let (sum, n) = values.fold((0.0, 0usize), |(s, n), v| (s + v, n + 1));
+    if n > 0 {
+        Some(sum / n as f64)
+    } else {
+        None
+    }
+}
+
+/// 运行一次符合性测试：按步骤施加边界条件，采样被测设备响应，结束后恢复边界条件与设备工作模式
+pub async fn run_test(
+    engine: &SimulationEngine,
+    app: &AppHandle,
+    test: &ComplianceTestDef,
+    device_id: &str,
+) -> Result<ComplianceResult, String> {
+    let status = engine.get_status().await;
+    if status.state != crate::domain::simulation::SimulationState::Running {
+        return Err("请先启动仿真再运行符合性测试".to_string());
+    }
+    let topology = engine.get_topology().await.ok_or("拓扑数据未设置")?;
+    let device = topology
+        .devices
+        .get(device_id)
+        .ok_or_else(|| format!("Device {} not found", device_id))?;
+    let rated_kw = crate::domain::topology::rated_power_kw(&device.properties).unwrap_or(0.0);
+
+    let uses_setpoint = test.steps.iter().any(|s| matches!(s.action, BoundaryAction::DeviceSetpoint { .. }));
+    let previous_mode = engine.get_device_modes().await.get(device_id).map(WorkMode::as_str);
+    if uses_setpoint {
+        if rated_kw <= 0.0 {
+            return Err(format!("设备 {} 未配置额定功率，无法执行设定阶跃测试", device_id));
+        }
+        engine.set_device_mode(device_id.to_string(), "manual".to_string()).await?;
+    }
+
+    let started_at = now_secs();
+    let clock = Instant::now();
+    let mut next_step = 0usize;
+    let mut vm_pu = 1.0;
+    let mut frequency_hz = NOMINAL_FREQUENCY_HZ;
+    let mut setpoint_kw: Option<f64> = None;
+    let mut last_sample_ts: Option<f64> = None;
+    let mut samples: Vec<ComplianceSample> = Vec::new();
+    let mut run_error: Option<String> = None;
+
+    while clock.elapsed().as_secs_f64() < test.duration_s {
+        let t_s = clock.elapsed().as_secs_f64();
+        while next_step < test.steps.len() && test.steps[next_step].at_s <= t_s {
+            let applied = match &test.steps[next_step].action {
+                BoundaryAction::GridVoltage { vm_pu: v } => {
+                    vm_pu = *v;
+                    engine.set_ext_grid_voltage(Some(*v)).await
+                }
+                BoundaryAction::GridFrequency { frequency_hz: f } => {
+                    frequency_hz = *f;
+                    engine.set_grid_frequency(*f, true, Some(NOMINAL_FREQUENCY_HZ)).await
+                }
+                BoundaryAction::DeviceSetpoint { p_ratio, q_kvar } => {
+                    let kw = p_ratio * rated_kw;
+                    setpoint_kw = Some(kw);
+                    engine.set_device_manual_setpoint(device_id.to_string(), kw, *q_kvar).await
+                }
+            };
+            if let Err(e) = applied {
+                run_error = Some(e);
+                break;
+            }
+            next_step += 1;
+        }
+        if run_error.is_some() {
+            break;
+        }
+        if let Some((ts, p_kw, q_kvar)) = engine.get_last_device_power(device_id) {
+            if last_sample_ts != Some(ts) {
+                last_sample_ts = Some(ts);
+                let sample = ComplianceSample { t_s, vm_pu, frequency_hz, setpoint_kw, p_kw, q_kvar };
+                let _ = app.emit("compliance-test-progress", serde_json::json!({
+                    "test_id": test.id,
+                    "device_id": device_id,
+                    "duration_s": test.duration_s,
+                    "sample": sample,
+                }));
+                samples.push(sample);
+            }
+        }
+        tokio::time::sleep(tokio::time::Duration::from_millis(SAMPLE_POLL_MS)).await;
+    }
+
+    // 恢复边界条件与设备工作模式（无论测试是否出错）
+    let _ = engine.set_ext_grid_voltage(None).await;
+    let _ = engine.set_grid_frequency(NOMINAL_FREQUENCY_HZ, false, Some(NOMINAL_FREQUENCY_HZ)).await;
+    if uses_setpoint {
+        if let Some(mode) = previous_mode {
+            let _ = engine.set_device_mode(device_id.to_string(), mode.to_string()).await;
+        }
+    }
+    if let Some(e) = run_error {
+        return Err(format!("符合性测试中止: {}", e));
+    }
+
+    let checks = evaluate(test, &samples);
+    let passed = !checks.is_empty() && checks.iter().all(|c| c.passed);
+    Ok(ComplianceResult {
+        test_id: test.id.clone(),
+        device_id: device_id.to_string(),
+        started_at,
+        finished_at: now_secs(),
+        samples,
+        checks,
+        passed,
+    })
+}
+
+/// 按测试类型对采样结果做判定
+fn evaluate(test: &ComplianceTestDef, samples: &[ComplianceSample]) -> Vec<ComplianceCheck> {
+    let mut checks = Vec::new();
+    if samples.is_empty() {
+        checks.push(ComplianceCheck {
+            name: "采样".to_string(),
+            passed: false,
+            detail: "测试期间未采集到设备数据".to_string(),
+        });
+        return checks;
+    }
+    let p_of = |s: &ComplianceSample| s.p_kw.unwrap_or(0.0).abs();
+    match test.id.as_str() {
+        "lvrt_dip" => {
+            let dip: Vec<&ComplianceSample> = samples.iter().filter(|s| s.vm_pu < 0.9).collect();
+            let online = !dip.is_empty() && dip.iter().all(|s| s.p_kw.is_some());
+            checks.push(ComplianceCheck {
+                name: "穿越期间不脱网".to_string(),
+                passed: online,
+                detail: format!("电压跌落期间样本 {} 个", dip.len()),
+            });
+            let pre = mean(samples.iter().filter(|s| s.t_s < 3.0).map(p_of));
+            let post = mean(samples.iter().filter(|s| s.t_s >= 11.0).map(p_of));
+            let recovered = match (pre, post) {
+                (Some(pre), Some(post)) => pre <= 0.0 || post >= pre * 0.9,
+                _ => false,
+            };
+            checks.push(ComplianceCheck {
+                name: "恢复后出力恢复至 90% 以上".to_string(),
+                passed: recovered,
+                detail: format!("跌落前 {:?} kW，恢复后 {:?} kW", pre, post),
+            });
+        }
+        "frequency_ramp" => {
+            let pre = mean(samples.iter().filter(|s| s.frequency_hz <= 50.2 && s.t_s < 3.0).map(p_of));
+            let over = samples
+                .iter()
+                .filter(|s| s.frequency_hz >= 51.0)
+                .map(p_of)
+                .fold(None, |acc: Option<f64>, v| Some(acc.map_or(v, |a| a.min(v))));
+            let curtailed = match (pre, over) {
+                (Some(pre), Some(over)) => pre <= 0.0 || over <= pre * 0.95,
+                _ => false,
+            };
+            checks.push(ComplianceCheck {
+                name: "过频降功率".to_string(),
+                passed: curtailed,
+                detail: format!("过频前 {:?} kW，51 Hz 以上最小 {:?} kW", pre, over),
+            });
+        }
+        "setpoint_step" => {
+            // 每个设定段取段末样本，与设定值偏差在 5% 额定（或 1 kW）以内视为跟踪合格
+            for (i, step) in test.steps.iter().enumerate() {
+                let end = test.steps.get(i + 1).map(|s| s.at_s).unwrap_or(test.duration_s);
+                let last = samples.iter().rfind(|s| s.t_s >= step.at_s && s.t_s < end);
+                let (passed, detail) = match last.and_then(|s| s.setpoint_kw.map(|sp| (sp, p_of(s)))) {
+                    Some((sp, p)) => {
+                        let tolerance = (sp.abs() * 0.05).max(1.0);
+                        ((p - sp.abs()).abs() <= tolerance, format!("设定 {:.2} kW，实际 {:.2} kW", sp, p))
+                    }
+                    None => (false, "该段无样本".to_string()),
+                };
+                checks.push(ComplianceCheck { name: format!("阶跃 {} 跟踪", i + 1), passed, detail });
+            }
+        }
+        _ => {}
+    }
+    checks
+}
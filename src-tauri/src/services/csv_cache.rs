@@ -0,0 +1,181 @@
+// CSV 解析缓存：按文件路径 + 格式 + 时区缓存解析并降采样后的序列（修改时间或大小变化即失效），避免每次分析请求重复解析大文件
+use crate::services::data_source::{CsvFormat, ParsedCsv};
+use crate::services::timezone::ImportTimezone;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// 缓存中每条序列保留的最大点数（高于看板默认 5000，便于按时间范围裁剪后仍有足够分辨率）
+const CACHE_MAX_POINTS_PER_SERIES: usize = 20000;
+
+struct CacheEntry {
+    format: CsvFormat,
+    /// 文件修改时间（秒）与大小，任一变化即视为失效
+    mtime: f64,
+    size: u64,
+    data: Arc<ParsedCsv>,
+    parsed_at: f64,
+    parse_ms: u64,
+    hits: u64,
+}
+
+/// 缓存状态（供前端展示）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CsvCacheStatus {
+    pub path: String,
+    pub format: CsvFormat,
+    pub timezone: ImportTimezone,
+    /// "ready" | "loading" | "stale"（文件已变化，下次访问时重新解析）
+    pub state: String,
+    pub series_count: usize,
+    pub point_count: usize,
+    pub file_mtime: f64,
+    pub parsed_at: f64,
+    pub parse_ms: u64,
+    pub hits: u64,
+}
+
+/// 缓存键：(路径, 格式, 时区)，同一文件按不同时区导入时分别缓存
+type CacheKey = (String, CsvFormat, ImportTimezone);
+
+#[derive(Default)]
+pub struct CsvCache {
+    entries: Mutex<HashMap<CacheKey, CacheEntry>>,
+    /// 正在后台解析的文件
+    loading: Mutex<HashSet<CacheKey>>,
+}
+
+fn file_stamp(path: &str) -> Result<(f64, u64), String> {
+    let meta = std::fs::metadata(path).map_err(|e| format!("读取文件信息失败 {}: {}", path, e))?;
+    let mtime = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs_f64())
+        .unwrap_or_default();
+    Ok((mtime, meta.len()))
+}
+
+impl CsvCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 命中且文件未变化时直接返回，否则解析并写入缓存（同步，调用方可放入 spawn_blocking）
+    pub fn get_or_parse(
+        &self,
+        path: &str,
+        format: CsvFormat,
+        timezone: ImportTimezone,
+    ) -> Result<Arc<ParsedCsv>, String> {
+        let (mtime, size) = file_stamp(path)?;
+        let key = (path.to_string(), format, timezone);
+        {
+            let mut entries = self.entries.lock().unwrap();
+            if let Some(entry) = entries.get_mut(&key) {
+                if entry.mtime == mtime && entry.size == size {
+                    entry.hits += 1;
+                    return Ok(entry.data.clone());
+                }
+            }
+        }
+        let started = Instant::now();
+        let data = Arc::new(ParsedCsv::parse(path, format, timezone, CACHE_MAX_POINTS_PER_SERIES)?);
+        let parsed_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs_f64();
+        self.entries.lock().unwrap().insert(
+            key,
+            CacheEntry {
+                format,
+                mtime,
+                size,
+                data: data.clone(),
+                parsed_at,
+                parse_ms: started.elapsed().as_millis() as u64,
+                hits: 0,
+            },
+        );
+        Ok(data)
+    }
+
+    /// 后台预解析：已在解析中的文件不重复提交
+    pub fn prefetch(self: &Arc<Self>, path: String, format: CsvFormat, timezone: ImportTimezone) {
+        let key = (path.clone(), format, timezone);
+        if !self.loading.lock().unwrap().insert(key.clone()) {
+            return;
+        }
+        let cache = self.clone();
+        tokio::task::spawn_blocking(move || {
+            if let Err(e) = cache.get_or_parse(&path, format, timezone) {
+                eprintln!("CSV 预解析失败 {}: {}", path, e);
+            }
+            cache.loading.lock().unwrap().remove(&key);
+        });
+    }
+
+    /// 显式失效：指定路径时仅清除该文件的缓存，否则清空全部
+    pub fn invalidate(&self, path: Option<&str>) -> usize {
+        let mut entries = self.entries.lock().unwrap();
+        let before = entries.len();
+        match path {
+            Some(p) => entries.retain(|(k, _, _), _| k != p),
+            None => entries.clear(),
+        }
+        before - entries.len()
+    }
+
+    pub fn status(&self) -> Vec<CsvCacheStatus> {
+        // 锁内只复制条目摘要，释放锁后再读取文件信息判断是否失效
+        let (snapshot, loading): (Vec<(CsvCacheStatus, u64)>, Vec<CacheKey>) = {
+            let entries = self.entries.lock().unwrap();
+            let loading = self.loading.lock().unwrap();
+            let snapshot = entries
+                .iter()
+                .map(|((path, _, timezone), e)| {
+                    let status = CsvCacheStatus {
+                        path: path.clone(),
+                        format: e.format,
+                        timezone: *timezone,
+                        state: String::new(),
+                        series_count: e.data.series.len(),
+                        point_count: e.data.series.values().map(|s| s.len()).sum(),
+                        file_mtime: e.mtime,
+                        parsed_at: e.parsed_at,
+                        parse_ms: e.parse_ms,
+                        hits: e.hits,
+                    };
+                    (status, e.size)
+                })
+                .collect();
+            let loading = loading.iter().filter(|k| !entries.contains_key(*k)).cloned().collect();
+            (snapshot, loading)
+        };
+        let mut out: Vec<CsvCacheStatus> = snapshot
+            .into_iter()
+            .map(|(mut status, size)| {
+                let stale = file_stamp(&status.path).map(|s| s != (status.file_mtime, size)).unwrap_or(true);
+                status.state = if stale { "stale" } else { "ready" }.to_string();
+                status
+            })
+            .collect();
+        for (path, format, timezone) in loading {
+            out.push(CsvCacheStatus {
+                path,
+                format,
+                timezone,
+                state: "loading".to_string(),
+                series_count: 0,
+                point_count: 0,
+                file_mtime: 0.0,
+                parsed_at: 0.0,
+                parse_ms: 0,
+                hits: 0,
+            });
+        }
+        out.sort_by(|a, b| a.path.cmp(&b.path));
+        out
+    }
+}
@@ -0,0 +1,90 @@
+// 日电量翻转归档：仿真时间跨日时，将上一日的储能日充放电量、光伏今日发电量与设备日电量写入仿真库元数据，
+// 供日报与历史查询；清零由仿真引擎完成，累计量不受影响
+use crate::domain::simulation::{DeviceEnergyCounters, StorageState};
+use crate::services::database::Database;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 仿真库中日电量归档的元数据键
+const META_DAILY_ENERGY_ARCHIVE: &str = "daily_energy_archive";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct StorageDailyTotals {
+    pub charge_kwh: f64,
+    pub discharge_kwh: f64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct DeviceDailyTotals {
+    pub import_kwh: f64,
+    pub export_kwh: f64,
+}
+
+/// 一日的日电量归档
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyEnergyArchive {
+    /// 归档的日（YYYY-MM-DD，按翻转配置的时区与时刻划分）
+    pub day: String,
+    /// 翻转时的仿真时间（Unix 秒）
+    pub rolled_over_at: f64,
+    #[serde(default)]
+    pub storage: HashMap<String, StorageDailyTotals>,
+    /// 光伏今日发电量（取自 Modbus IR 5003，仅运行 Modbus 服务的光伏）
+    #[serde(default)]
+    pub pv_generation_kwh: HashMap<String, f64>,
+    #[serde(default)]
+    pub device_energy: HashMap<String, DeviceDailyTotals>,
+}
+
+impl DailyEnergyArchive {
+    pub fn collect(
+        day: &str,
+        rolled_over_at: f64,
+        storage: &HashMap<String, StorageState>,
+        pv_generation_kwh: HashMap<String, f64>,
+        device_energy: &HashMap<String, DeviceEnergyCounters>,
+    ) -> Self {
+        Self {
+            day: day.to_string(),
+            rolled_over_at,
+            storage: storage
+                .iter()
+                .map(|(id, s)| {
+                    (id.clone(), StorageDailyTotals { charge_kwh: s.daily_charge_kwh, discharge_kwh: s.daily_discharge_kwh })
+                })
+                .collect(),
+            pv_generation_kwh,
+            // 设备日电量按所属日累计，只归档当日的计数
+            device_energy: device_energy
+                .iter()
+                .filter(|(_, c)| c.day == day)
+                .map(|(id, c)| {
+                    (id.clone(), DeviceDailyTotals { import_kwh: c.daily_import_kwh, export_kwh: c.daily_export_kwh })
+                })
+                .collect(),
+        }
+    }
+}
+
+/// 追加一日归档到仿真库
+pub fn append_archive(db: &Database, archive: DailyEnergyArchive) {
+    let mut archives = load_archives(db);
+    archives.push(archive);
+    match serde_json::to_string(&archives) {
+        Ok(json) => {
+            if let Err(e) = db.set_meta_text(META_DAILY_ENERGY_ARCHIVE, &json) {
+                eprintln!("写入日电量归档失败: {}", e);
+            }
+        }
+        Err(e) => eprintln!("序列化日电量归档失败: {}", e),
+    }
+}
+
+/// 读取仿真库中的日电量归档（按翻转先后；未翻转过或旧库为空）
+pub fn load_archives(db: &Database) -> Vec<DailyEnergyArchive> {
+    db.get_meta_text(META_DAILY_ENERGY_ARCHIVE)
+        .ok()
+        .flatten()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
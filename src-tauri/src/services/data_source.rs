@@ -0,0 +1,460 @@
+// 统一数据源抽象：看板、数据分析等查询统一经 DataSource 读取，可混用实时缓存、仿真 DB、宽/长表 CSV 与 SSH 远程文件
+use crate::commands::dashboard::{
+    dashboard_query_db_series_impl, downsample, list_db_columns, make_short_label, parse_long_csv_file,
+    parse_wide_csv_file, ColumnMeta, TimeSeriesPoint,
+};
+use crate::services::column_parser::ColumnNameParser;
+use crate::services::csv_cache::CsvCache;
+use crate::services::timezone::ImportTimezone;
+use crate::services::simulation_engine::SimulationEngine;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// 数据源描述（前端传入），按 kind 区分
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DataSourceSpec {
+    /// 运行中仿真的实时功率缓存，key 格式 {device_id}:{field}
+    Live,
+    /// 仿真数据库（data_<ts>.db），key 格式 {device_id}:{field_name}
+    Db { path: String },
+    /// 宽表 CSV，key 为原始列名；column_parser 指定列名拆分为设备 SN / 数据项的方式，timezone 为无时区后缀时间戳的时区
+    WideCsv {
+        path: String,
+        #[serde(default)]
+        column_parser: ColumnNameParser,
+        #[serde(default)]
+        timezone: ImportTimezone,
+    },
+    /// 长表 CSV（device_id, timestamp, p_active, ...），key 格式 {device_id}:{field}
+    LongCsv {
+        path: String,
+        #[serde(default)]
+        timezone: ImportTimezone,
+    },
+    /// 远程主机上的文件：经系统 ssh/scp 拉取到本地临时目录后按 format 解析
+    SshRemote {
+        host: String,
+        #[serde(default)]
+        user: Option<String>,
+        #[serde(default)]
+        port: Option<u16>,
+        remote_path: String,
+        /// "db" | "wide_csv" | "long_csv"
+        format: String,
+        /// CSV 格式时无时区后缀时间戳的时区
+        #[serde(default)]
+        timezone: ImportTimezone,
+    },
+}
+
+impl DataSourceSpec {
+    /// 导入时指定的时区（仅 CSV 类数据源；DB 与实时数据为 Unix 时间，无此设置）
+    pub fn timezone(&self) -> Option<ImportTimezone> {
+        match self {
+            Self::WideCsv { timezone, .. } | Self::LongCsv { timezone, .. } => Some(*timezone),
+            Self::SshRemote { format, timezone, .. } if format != "db" => Some(*timezone),
+            _ => None,
+        }
+    }
+}
+
+/// 数据源统一接口：列出可选数据列、按时间范围取序列、取最新值
+pub trait DataSource: Send + Sync {
+    fn list_keys(&self) -> Result<Vec<ColumnMeta>, String>;
+
+    fn fetch_series(
+        &self,
+        key: &str,
+        start_time: Option<f64>,
+        end_time: Option<f64>,
+        max_points: usize,
+    ) -> Result<Vec<TimeSeriesPoint>, String>;
+
+    fn fetch_latest(&self, key: &str) -> Result<Option<TimeSeriesPoint>, String> {
+        Ok(self.fetch_series(key, None, None, usize::MAX)?.pop())
+    }
+}
+
+/// 打开数据源所需的应用状态：实时数据源需要仿真引擎，CSV 数据源可选用解析缓存
+#[derive(Clone, Default)]
+pub struct DataSourceContext {
+    pub engine: Option<Arc<SimulationEngine>>,
+    pub csv_cache: Option<Arc<CsvCache>>,
+}
+
+/// 按描述打开数据源
+pub fn open_data_source(spec: &DataSourceSpec, ctx: &DataSourceContext) -> Result<Box<dyn DataSource>, String> {
+    let cache = ctx.csv_cache.as_deref();
+    match spec {
+        DataSourceSpec::Live => {
+            let engine = ctx.engine.clone().ok_or("实时数据源需要仿真引擎")?;
+            Ok(Box::new(LiveDataSource { engine }))
+        }
+        DataSourceSpec::Db { path } => Ok(Box::new(DbDataSource::new(path.clone()))),
+        DataSourceSpec::WideCsv { path, column_parser, timezone } => Ok(Box::new(
+            CsvDataSource::open(path, CsvFormat::Wide, *timezone, cache)?.with_column_parser(column_parser)?,
+        )),
+        DataSourceSpec::LongCsv { path, timezone } => {
+            Ok(Box::new(CsvDataSource::open(path, CsvFormat::Long, *timezone, cache)?))
+        }
+        DataSourceSpec::SshRemote { host, user, port, remote_path, format, timezone } => {
+            let local = fetch_remote_file(host, user.as_deref(), *port, remote_path)?;
+            let local = local.to_string_lossy().to_string();
+            match format.as_str() {
+                "db" => Ok(Box::new(DbDataSource::new(local))),
+                "wide_csv" => Ok(Box::new(CsvDataSource::open(&local, CsvFormat::Wide, *timezone, cache)?)),
+                "long_csv" => Ok(Box::new(CsvDataSource::open(&local, CsvFormat::Long, *timezone, cache)?)),
+                other => Err(format!("不支持的远程文件格式: {}", other)),
+            }
+        }
+    }
+}
+
+fn split_key(key: &str) -> Result<(&str, &str), String> {
+    key.split_once(':').ok_or_else(|| format!("无效的数据列 key: {}", key))
+}
+
+fn filter_range(points: &[TimeSeriesPoint], start_time: Option<f64>, end_time: Option<f64>) -> Vec<TimeSeriesPoint> {
+    points
+        .iter()
+        .filter(|p| start_time.is_none_or(|s| p.timestamp >= s) && end_time.is_none_or(|e| p.timestamp <= e))
+        .cloned()
+        .collect()
+}
+
+// ====== 实时缓存 ======
+
+pub struct LiveDataSource {
+    engine: Arc<SimulationEngine>,
+}
+
+impl LiveDataSource {
+    fn latest_values(&self) -> HashMap<String, TimeSeriesPoint> {
+        let mut out = HashMap::new();
+        for (device_id, (ts, p, q)) in self.engine.get_all_last_device_power() {
+            if let Some(p) = p {
+                out.insert(format!("{}:p_active", device_id), TimeSeriesPoint { timestamp: ts, value: p });
+            }
+            if let Some(q) = q {
+                out.insert(format!("{}:p_reactive", device_id), TimeSeriesPoint { timestamp: ts, value: q });
+            }
+        }
+        for (device_id, state) in self.engine.get_all_storage_states() {
+            let ts = out
+                .get(&format!("{}:p_active", device_id))
+                .map(|p| p.timestamp)
+                .unwrap_or_default();
+            out.insert(format!("{}:soc", device_id), TimeSeriesPoint { timestamp: ts, value: state.soc_percent });
+        }
+        out
+    }
+}
+
+impl DataSource for LiveDataSource {
+    fn list_keys(&self) -> Result<Vec<ColumnMeta>, String> {
+        let mut keys: Vec<String> = self.latest_values().into_keys().collect();
+        keys.sort();
+        keys.iter()
+            .map(|key| {
+                let (device_id, field) = split_key(key)?;
+                Ok(ColumnMeta {
+                    key: key.clone(),
+                    device_sn: device_id.to_string(),
+                    data_item: field.to_string(),
+                    short_label: make_short_label(device_id, field),
+                    device_id: None,
+                })
+            })
+            .collect()
+    }
+
+    /// 实时缓存只保留最新值，序列至多一个点
+    fn fetch_series(
+        &self,
+        key: &str,
+        start_time: Option<f64>,
+        end_time: Option<f64>,
+        _max_points: usize,
+    ) -> Result<Vec<TimeSeriesPoint>, String> {
+        let latest: Vec<TimeSeriesPoint> = self.fetch_latest(key)?.into_iter().collect();
+        Ok(filter_range(&latest, start_time, end_time))
+    }
+
+    fn fetch_latest(&self, key: &str) -> Result<Option<TimeSeriesPoint>, String> {
+        Ok(self.latest_values().remove(key))
+    }
+}
+
+// ====== 仿真数据库 ======
+
+pub struct DbDataSource {
+    path: String,
+}
+
+impl DbDataSource {
+    pub fn new(path: String) -> Self {
+        Self { path }
+    }
+}
+
+impl DataSource for DbDataSource {
+    fn list_keys(&self) -> Result<Vec<ColumnMeta>, String> {
+        Ok(list_db_columns(&self.path)?
+            .into_iter()
+            .map(|c| ColumnMeta {
+                key: c.key,
+                device_sn: c.device_id,
+                data_item: c.field_name,
+                short_label: c.short_label,
+                device_id: None,
+            })
+            .collect())
+    }
+
+    fn fetch_series(
+        &self,
+        key: &str,
+        start_time: Option<f64>,
+        end_time: Option<f64>,
+        max_points: usize,
+    ) -> Result<Vec<TimeSeriesPoint>, String> {
+        let (device_id, field_name) = split_key(key)?;
+        dashboard_query_db_series_impl(
+            &self.path,
+            device_id.to_string(),
+            field_name.to_string(),
+            start_time,
+            end_time,
+            max_points,
+        )
+    }
+
+    /// 倒序扫描，取该字段最近一个有效值
+    fn fetch_latest(&self, key: &str) -> Result<Option<TimeSeriesPoint>, String> {
+        let (device_id, field_name) = split_key(key)?;
+        let conn = rusqlite::Connection::open(&self.path).map_err(|e| format!("打开数据库失败: {}", e))?;
+        let mut stmt = conn
+            .prepare("SELECT timestamp, p_active, p_reactive, data_json FROM device_data WHERE device_id = ?1 ORDER BY timestamp DESC")
+            .map_err(|e| format!("查询失败: {}", e))?;
+        let mut rows = stmt
+            .query(rusqlite::params![device_id])
+            .map_err(|e| format!("查询失败: {}", e))?;
+        while let Some(row) = rows.next().map_err(|e| format!("查询失败: {}", e))? {
+            let timestamp: f64 = row.get(0).map_err(|e| e.to_string())?;
+            let value = match field_name {
+                "p_active" => row.get::<_, Option<f64>>(1).map_err(|e| e.to_string())?,
+                "p_reactive" => row.get::<_, Option<f64>>(2).map_err(|e| e.to_string())?,
+                _ => row
+                    .get::<_, Option<String>>(3)
+                    .map_err(|e| e.to_string())?
+                    .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok())
+                    .and_then(|v| v.get(field_name).and_then(|x| x.as_f64())),
+            };
+            if let Some(value) = value {
+                return Ok(Some(TimeSeriesPoint { timestamp, value }));
+            }
+        }
+        Ok(None)
+    }
+}
+
+// ====== CSV（解析结果经 CsvCache 缓存，按路径 + 修改时间失效） ======
+
+/// CSV 解析后的统一结构：列元信息 + 按 key 的时间序列（已按时间排序）+ 解析所用时区
+#[derive(Debug, Clone, Default)]
+pub struct ParsedCsv {
+    pub columns: Vec<ColumnMeta>,
+    pub series: HashMap<String, Vec<TimeSeriesPoint>>,
+    pub timezone: ImportTimezone,
+}
+
+/// CSV 格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CsvFormat {
+    Wide,
+    Long,
+}
+
+impl ParsedCsv {
+    pub fn parse(path: &str, format: CsvFormat, timezone: ImportTimezone, max_points: usize) -> Result<Self, String> {
+        let mut parsed = match format {
+            CsvFormat::Wide => {
+                let table = parse_wide_csv_file(path, usize::MAX, &timezone)?;
+                Self { columns: table.columns, series: table.series, timezone }
+            }
+            CsvFormat::Long => Self::from_long(path, timezone)?,
+        };
+        for points in parsed.series.values_mut() {
+            points.sort_by(|a, b| a.timestamp.total_cmp(&b.timestamp));
+            downsample(points, max_points);
+        }
+        Ok(parsed)
+    }
+
+    /// 长表按 {device_id}:{field} 展开：p_active、p_reactive 及 data_json 中的数值字段
+    fn from_long(path: &str, timezone: ImportTimezone) -> Result<Self, String> {
+        let data = parse_long_csv_file(path, &timezone)?;
+        let mut series: HashMap<String, Vec<TimeSeriesPoint>> = HashMap::new();
+        for (device_id, points) in data.points_by_device {
+            for p in points {
+                let mut push = |field: &str, value: f64| {
+                    series
+                        .entry(format!("{}:{}", device_id, field))
+                        .or_default()
+                        .push(TimeSeriesPoint { timestamp: p.timestamp, value });
+                };
+                if let Some(v) = p.p_active {
+                    push("p_active", v);
+                }
+                if let Some(v) = p.p_reactive {
+                    push("p_reactive", v);
+                }
+                if let Some(serde_json::Value::Object(map)) = &p.data_json {
+                    for (field, v) in map {
+                        if let Some(v) = v.as_f64() {
+                            push(field, v);
+                        }
+                    }
+                }
+            }
+        }
+        let mut keys: Vec<&String> = series.keys().collect();
+        keys.sort();
+        let columns = keys
+            .into_iter()
+            .map(|key| {
+                let (device_id, field) = split_key(key)?;
+                Ok(ColumnMeta {
+                    key: key.clone(),
+                    device_sn: device_id.to_string(),
+                    data_item: field.to_string(),
+                    short_label: make_short_label(device_id, field),
+                    device_id: None,
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(Self { columns, series, timezone })
+    }
+}
+
+pub struct CsvDataSource {
+    data: Arc<ParsedCsv>,
+    /// 按非默认列名解析策略重新计算的列元信息；None 时使用解析缓存中的列
+    columns: Option<Vec<ColumnMeta>>,
+}
+
+impl CsvDataSource {
+    pub fn open(
+        path: &str,
+        format: CsvFormat,
+        timezone: ImportTimezone,
+        cache: Option<&CsvCache>,
+    ) -> Result<Self, String> {
+        timezone.validate()?;
+        let data = match cache {
+            Some(c) => c.get_or_parse(path, format, timezone)?,
+            None => Arc::new(ParsedCsv::parse(path, format, timezone, usize::MAX)?),
+        };
+        Ok(Self { data, columns: None })
+    }
+
+    /// 宽表按指定策略重新解析列名（series 的 key 仍为原始列名，缓存共用）
+    pub fn with_column_parser(mut self, parser: &ColumnNameParser) -> Result<Self, String> {
+        if *parser != ColumnNameParser::default() {
+            let mut columns = self.data.columns.clone();
+            parser.apply(&mut columns)?;
+            self.columns = Some(columns);
+        }
+        Ok(self)
+    }
+}
+
+impl DataSource for CsvDataSource {
+    fn list_keys(&self) -> Result<Vec<ColumnMeta>, String> {
+        Ok(self.columns.clone().unwrap_or_else(|| self.data.columns.clone()))
+    }
+
+    fn fetch_series(
+        &self,
+        key: &str,
+        start_time: Option<f64>,
+        end_time: Option<f64>,
+        max_points: usize,
+    ) -> Result<Vec<TimeSeriesPoint>, String> {
+        let points = self.data.series.get(key).ok_or_else(|| format!("CSV 中不存在数据列: {}", key))?;
+        let mut out = filter_range(points, start_time, end_time);
+        downsample(&mut out, max_points);
+        Ok(out)
+    }
+
+    fn fetch_latest(&self, key: &str) -> Result<Option<TimeSeriesPoint>, String> {
+        Ok(self.data.series.get(key).and_then(|p| p.last().cloned()))
+    }
+}
+
+// ====== SSH 远程 ======
+
+/// 校验 scp 目标中的主机名/用户名：不得以 - 开头（会被 scp/ssh 当作选项，如 -oProxyCommand），不得含路径分隔符、.. 或空白
+fn validate_remote_name(value: &str, what: &str) -> Result<(), String> {
+    if value.is_empty()
+        || value.starts_with('-')
+        || value.contains(['/', '\\', '@', ':'])
+        || value.contains("..")
+        || value.chars().any(|c| c.is_whitespace() || c.is_control())
+    {
+        return Err(format!("远程数据源{}无效: {}", what, value));
+    }
+    Ok(())
+}
+
+/// 使用系统 scp 将远程文件拉取到本地临时目录（依赖本机 ssh 密钥认证，不处理交互式密码）
+fn fetch_remote_file(host: &str, user: Option<&str>, port: Option<u16>, remote_path: &str) -> Result<PathBuf, String> {
+    validate_remote_name(host, "主机")?;
+    if let Some(u) = user.filter(|u| !u.is_empty()) {
+        validate_remote_name(u, "用户名")?;
+    }
+    if remote_path.is_empty() || remote_path.starts_with('-') || remote_path.chars().any(|c| c.is_control()) {
+        return Err(format!("远程文件路径无效: {}", remote_path));
+    }
+    let target = match user {
+        Some(u) if !u.is_empty() => format!("{}@{}:{}", u, host, remote_path),
+        _ => format!("{}:{}", host, remote_path),
+    };
+    let file_name = std::path::Path::new(remote_path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "remote_data".to_string());
+    // 本地目录名只保留主机名中的字母数字与 . - _（已排除 ..），不会逃出临时目录
+    let host_dir: String = host
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') { c } else { '_' })
+        .collect();
+    let dir = std::env::temp_dir().join("pvsc_remote_sources").join(host_dir);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("创建临时目录失败: {}", e))?;
+    let local = dir.join(file_name);
+
+    let mut cmd = std::process::Command::new("scp");
+    cmd.arg("-q").arg("-o").arg("BatchMode=yes");
+    if let Some(p) = port {
+        cmd.arg("-P").arg(p.to_string());
+    }
+    // -- 之后的参数不再按选项解析
+    let output = cmd
+        .arg("--")
+        .arg(&target)
+        .arg(&local)
+        .output()
+        .map_err(|e| format!("执行 scp 失败: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "拉取远程文件失败 {}: {}",
+            target,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(local)
+}
@@ -1,11 +1,43 @@
 // 数据库访问
+use crate::domain::simulation::{EvSessionRecord, EvSessionStatus};
+use crate::services::db_writer::DeviceDataRow;
 use rusqlite::{Connection, Result as SqlResult};
 use anyhow::{Result, Context};
+use std::collections::HashMap;
 
 pub struct Database {
     conn: Connection,
 }
 
+/// 各电表测量对象的设备类型（meter_id -> device_type）：优先取仿真启动时按拓扑连接记录的 meter_target_types；
+/// 旧库未记录时，每个电表按其一行数据推断一次（同一时刻 data_json 相同的非电表设备行）
+pub(crate) fn meter_target_types(conn: &Connection) -> SqlResult<HashMap<String, String>> {
+    let recorded: Option<String> = conn
+        .query_row(
+            "SELECT value_text FROM simulation_meta WHERE key = 'meter_target_types'",
+            [],
+            |row| row.get(0),
+        )
+        .ok()
+        .flatten();
+    if let Some(map) = recorded.and_then(|s| serde_json::from_str::<HashMap<String, String>>(&s).ok()) {
+        return Ok(map);
+    }
+    let mut stmt = conn.prepare(
+        "SELECT m.device_id, (SELECT t.device_type FROM device_data t
+             WHERE t.timestamp = m.timestamp AND t.data_json = m.data_json AND t.device_type != 'meter' LIMIT 1)
+         FROM device_data m WHERE m.device_type = 'meter' GROUP BY m.device_id",
+    )?;
+    let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?)))?;
+    let mut map = HashMap::new();
+    for row in rows {
+        if let (meter_id, Some(target_type)) = row? {
+            map.insert(meter_id, target_type);
+        }
+    }
+    Ok(map)
+}
+
 impl Database {
     /// 默认数据库路径：使用 current_dir()/data.db。开发时 cwd 为 src-tauri，故为 src-tauri/data.db（仿真写入此处）。
     pub fn new(db_path: Option<&std::path::Path>) -> Result<Self> {
@@ -23,6 +55,24 @@ impl Database {
         Ok(db)
     }
 
+    /// 只读打开已有的仿真库（历史回看），不执行建表/迁移，避免误删旧库数据
+    pub fn open_readonly(path: &std::path::Path) -> Result<Self> {
+        if !path.exists() {
+            anyhow::bail!("数据库文件不存在: {:?}", path);
+        }
+        let conn = Connection::open_with_flags(path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .context(format!("Failed to open database at {:?}", path))?;
+        let has_table: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='device_data'",
+            [],
+            |row| row.get(0),
+        )?;
+        if has_table == 0 {
+            anyhow::bail!("不是仿真数据库（缺少 device_data 表）: {:?}", path);
+        }
+        Ok(Self { conn })
+    }
+
     fn init_schema(&self) -> SqlResult<()> {
         // 检查是否存在旧版本的 device_data 表（使用 voltage, current, power 列）
         let old_table_exists = self.conn.query_row(
@@ -91,10 +141,94 @@ impl Database {
             )",
             [],
         )?;
+        // 文本型元数据（如本库采用的功率符号约定），忽略已存在
+        let _ = self.conn.execute("ALTER TABLE simulation_meta ADD COLUMN value_text TEXT", []);
+
+        // 充电桩电动汽车充电会话（ev_session 模式，会话结束时写入）
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS ev_sessions (
+                session_id TEXT PRIMARY KEY,
+                device_id TEXT NOT NULL,
+                gun INTEGER,
+                arrival_at REAL NOT NULL,
+                departure_at REAL NOT NULL,
+                charged_at REAL,
+                energy_requested_kwh REAL NOT NULL,
+                energy_delivered_kwh REAL NOT NULL,
+                peak_power_kw REAL NOT NULL,
+                status TEXT NOT NULL
+            )",
+            [],
+        )?;
+        self.conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_ev_sessions_device ON ev_sessions(device_id, arrival_at)",
+            [],
+        )?;
+
+        Ok(())
+    }
 
+    pub fn set_meta_text(&self, key: &str, value: &str) -> SqlResult<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO simulation_meta (key, value_text) VALUES (?1, ?2)",
+            rusqlite::params![key, value],
+        )?;
         Ok(())
     }
 
+    pub fn get_meta_text(&self, key: &str) -> SqlResult<Option<String>> {
+        let mut stmt = self.conn.prepare("SELECT value_text FROM simulation_meta WHERE key = ?1")?;
+        let mut rows = stmt.query(rusqlite::params![key])?;
+        if let Some(row) = rows.next()? {
+            return Ok(row.get(0)?);
+        }
+        Ok(None)
+    }
+
+    /// 翻转指定设备类型的有功/无功符号；电表行按其测量对象的类型判断（见 meter_target_types）。
+    /// 先按设备收集待翻转行的主键再逐行更新，每行恰好翻转一次。返回更新行数
+    pub fn flip_power_sign(&mut self, device_types: &[&str]) -> SqlResult<usize> {
+        if device_types.is_empty() {
+            return Ok(0);
+        }
+        let mut device_ids: Vec<String> = meter_target_types(&self.conn)?
+            .into_iter()
+            .filter(|(_, t)| device_types.contains(&t.as_str()))
+            .map(|(meter_id, _)| meter_id)
+            .collect();
+        let tx = self.conn.transaction()?;
+        {
+            let placeholders = vec!["?"; device_types.len()].join(",");
+            let mut stmt =
+                tx.prepare(&format!("SELECT DISTINCT device_id FROM device_data WHERE device_type IN ({})", placeholders))?;
+            let rows = stmt.query_map(rusqlite::params_from_iter(device_types.iter()), |row| row.get::<_, String>(0))?;
+            for id in rows {
+                device_ids.push(id?);
+            }
+        }
+        let mut row_ids: Vec<i64> = Vec::new();
+        {
+            let mut stmt = tx.prepare("SELECT id FROM device_data WHERE device_id = ?1")?;
+            for device_id in &device_ids {
+                let rows = stmt.query_map(rusqlite::params![device_id], |row| row.get::<_, i64>(0))?;
+                for id in rows {
+                    row_ids.push(id?);
+                }
+            }
+        }
+        row_ids.sort_unstable();
+        row_ids.dedup();
+        {
+            let mut stmt =
+                tx.prepare("UPDATE device_data SET p_active = -p_active, p_reactive = -p_reactive WHERE id = ?1")?;
+            for id in &row_ids {
+                stmt.execute(rusqlite::params![id])?;
+            }
+        }
+        tx.commit()?;
+        Ok(row_ids.len())
+    }
+
     /// 持久化最近一次仿真起始时间（Unix 秒），供监控趋势图从 DB 获取起点
     pub fn set_latest_simulation_start(&self, timestamp: f64) -> SqlResult<()> {
         self.conn.execute(
@@ -116,6 +250,16 @@ impl Database {
         Ok(None)
     }
 
+    /// 库中最后一条设备数据的时间戳
+    pub fn query_latest_timestamp(&self) -> SqlResult<Option<f64>> {
+        self.conn.query_row("SELECT MAX(timestamp) FROM device_data", [], |row| row.get(0))
+    }
+
+    /// 库中第一条设备数据的时间戳
+    pub fn query_earliest_timestamp(&self) -> SqlResult<Option<f64>> {
+        self.conn.query_row("SELECT MIN(timestamp) FROM device_data", [], |row| row.get(0))
+    }
+
     /// 仿真开始时清空设备数据表，避免拓扑变更后旧设备数据残留；每次启动仿真视为新一轮数据。
     pub fn clear_device_data(&self) -> SqlResult<()> {
         self.conn.execute("DELETE FROM device_data", [])?;
@@ -139,6 +283,87 @@ impl Database {
         Ok(())
     }
 
+    /// 批量写入设备数据（单事务，供异步落库任务使用）
+    pub fn insert_device_data_batch(&mut self, rows: &[DeviceDataRow]) -> SqlResult<()> {
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare_cached(
+                "INSERT INTO device_data (device_id, timestamp, p_active, p_reactive, data_json, device_type)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )?;
+            for row in rows {
+                stmt.execute(rusqlite::params![
+                    row.device_id,
+                    row.timestamp,
+                    row.p_active_kw,
+                    row.p_reactive_kvar,
+                    row.data_json,
+                    row.device_type
+                ])?;
+            }
+        }
+        tx.commit()
+    }
+
+    pub fn insert_ev_session(&self, record: &EvSessionRecord) -> SqlResult<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO ev_sessions (session_id, device_id, gun, arrival_at, departure_at, charged_at,
+             energy_requested_kwh, energy_delivered_kwh, peak_power_kw, status)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            rusqlite::params![
+                record.session_id,
+                record.device_id,
+                record.gun,
+                record.arrival_at,
+                record.departure_at,
+                record.charged_at,
+                record.energy_requested_kwh,
+                record.energy_delivered_kwh,
+                record.peak_power_kw,
+                record.status.as_str(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// 按到达时间顺序返回充电会话；device_id 为 None 时返回全部充电桩。旧库没有 ev_sessions 表时返回空
+    pub fn query_ev_sessions(&self, device_id: Option<&str>) -> SqlResult<Vec<EvSessionRecord>> {
+        let has_table: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='ev_sessions'",
+            [],
+            |row| row.get(0),
+        )?;
+        if has_table == 0 {
+            return Ok(Vec::new());
+        }
+        let mut stmt = self.conn.prepare(
+            "SELECT session_id, device_id, gun, arrival_at, departure_at, charged_at, energy_requested_kwh,
+             energy_delivered_kwh, peak_power_kw, status FROM ev_sessions
+             WHERE ?1 IS NULL OR device_id = ?1 ORDER BY arrival_at, session_id",
+        )?;
+        let rows = stmt.query_map(rusqlite::params![device_id], |row| {
+            let status = match row.get::<_, String>(9)?.as_str() {
+                "completed" => EvSessionStatus::Completed,
+                "unmet" => EvSessionStatus::Unmet,
+                "rejected" => EvSessionStatus::Rejected,
+                _ => EvSessionStatus::Interrupted,
+            };
+            Ok(EvSessionRecord {
+                session_id: row.get(0)?,
+                device_id: row.get(1)?,
+                gun: row.get(2)?,
+                arrival_at: row.get(3)?,
+                departure_at: row.get(4)?,
+                charged_at: row.get(5)?,
+                energy_requested_kwh: row.get(6)?,
+                energy_delivered_kwh: row.get(7)?,
+                peak_power_kw: row.get(8)?,
+                status,
+            })
+        })?;
+        rows.collect()
+    }
+
     /// 单行结果：timestamp, p_active, p_reactive, data_json。max_points 为 Some(n) 时若结果超过 n 条则按时间等分桶降采样
     pub fn query_device_data(
         &self,
@@ -259,4 +484,37 @@ impl Database {
             Ok(None)
         }
     }
+
+    /// 按时间顺序返回 [from, until] 内的全部行：device_id, timestamp, p_active, p_reactive, data_json, device_type，供回放分段读取
+    #[allow(clippy::type_complexity)]
+    pub fn query_rows_between(
+        &self,
+        from: f64,
+        until: f64,
+    ) -> SqlResult<Vec<(String, f64, Option<f64>, Option<f64>, Option<String>, Option<String>)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT device_id, timestamp, p_active, p_reactive, data_json, device_type FROM device_data WHERE timestamp >= ?1 AND timestamp <= ?2 ORDER BY timestamp, device_id",
+        )?;
+        let rows = stmt.query_map(rusqlite::params![from, until], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?))
+        })?;
+        rows.collect()
+    }
+
+    /// 返回该设备在 at 时刻及之前的最后一行（历史回看按游标时刻解析状态）
+    pub fn query_device_data_as_of(
+        &self,
+        device_id: &str,
+        at: f64,
+    ) -> SqlResult<Option<(f64, Option<f64>, Option<f64>, Option<String>)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT timestamp, p_active, p_reactive, data_json FROM device_data WHERE device_id = ?1 AND timestamp <= ?2 ORDER BY timestamp DESC LIMIT 1",
+        )?;
+        let mut rows = stmt.query(rusqlite::params![device_id, at])?;
+        if let Some(row) = rows.next()? {
+            Ok(Some((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))
+        } else {
+            Ok(None)
+        }
+    }
 }
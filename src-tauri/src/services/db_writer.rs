@@ -0,0 +1,78 @@
+// 异步落库：计算循环只把设备数据行送入通道，独立写入任务按批（单事务）写入仿真库，
+// SQLite 写入延迟（尤其机械硬盘）不再拉长计算步、影响步耗时统计
+use crate::services::database::Database;
+use std::sync::{Arc, Mutex as StdMutex};
+use tokio::sync::{mpsc, oneshot};
+
+/// 单批最多写入的行数（积压时分批提交，避免长时间占用仿真库）
+const MAX_BATCH_ROWS: usize = 2000;
+
+/// 一行设备数据（字段与 device_data 表一致）
+#[derive(Debug, Clone)]
+pub struct DeviceDataRow {
+    pub device_id: String,
+    pub timestamp: f64,
+    pub p_active_kw: Option<f64>,
+    pub p_reactive_kvar: Option<f64>,
+    pub data_json: Option<String>,
+    pub device_type: Option<String>,
+}
+
+enum WriterMessage {
+    Row(DeviceDataRow),
+    /// 此前送入的行全部写入后应答
+    Flush(oneshot::Sender<()>),
+}
+
+/// 写入任务的发送端；全部克隆释放后写入任务写完剩余行退出
+#[derive(Clone)]
+pub struct DbWriter {
+    tx: mpsc::UnboundedSender<WriterMessage>,
+}
+
+impl DbWriter {
+    /// 启动写入任务；写入时仿真库未打开则丢弃该批
+    pub fn spawn(database: Arc<StdMutex<Option<Database>>>) -> Self {
+        let (tx, mut rx) = mpsc::unbounded_channel::<WriterMessage>();
+        tokio::task::spawn_blocking(move || {
+            let mut batch: Vec<DeviceDataRow> = Vec::new();
+            let mut waiters: Vec<oneshot::Sender<()>> = Vec::new();
+            while let Some(message) = rx.blocking_recv() {
+                let mut next = Some(message);
+                while let Some(message) = next.take() {
+                    match message {
+                        WriterMessage::Row(row) => batch.push(row),
+                        WriterMessage::Flush(done) => waiters.push(done),
+                    }
+                    if batch.len() < MAX_BATCH_ROWS {
+                        next = rx.try_recv().ok();
+                    }
+                }
+                if !batch.is_empty() {
+                    if let Some(ref mut db) = *database.lock().unwrap() {
+                        if let Err(e) = db.insert_device_data_batch(&batch) {
+                            eprintln!("批量写入设备数据失败（{} 行）: {}", batch.len(), e);
+                        }
+                    }
+                    batch.clear();
+                }
+                for done in waiters.drain(..) {
+                    let _ = done.send(());
+                }
+            }
+        });
+        Self { tx }
+    }
+
+    pub fn write(&self, row: DeviceDataRow) {
+        let _ = self.tx.send(WriterMessage::Row(row));
+    }
+
+    /// 等待此前送入的行全部写入（停止仿真、读取运行摘要前调用）
+    pub async fn flush(&self) {
+        let (done, wait) = oneshot::channel();
+        if self.tx.send(WriterMessage::Flush(done)).is_ok() {
+            let _ = wait.await;
+        }
+    }
+}
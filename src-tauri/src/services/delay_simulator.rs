@@ -1,11 +1,39 @@
 // 延迟和误差模拟
+// 传感器延迟：测量值滞后上报（落库、Modbus 与前端看到的是若干秒前的结果），由本模块在 Rust 端缓冲实现
+// 执行器延迟：指令滞后生效（远程/手动设定经过延迟才写入设备），由内核按 responseDelayMs 排队实现
 use rand::Rng;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+/// 设备的延迟配置（毫秒，仿真时间）
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub struct DeviceDelays {
+    /// 传感器上报延迟：测量值滞后显示与落库
+    pub sensor_delay_ms: f64,
+    /// 执行器响应延迟：指令滞后生效
+    pub actuator_delay_ms: f64,
+}
+
+impl DeviceDelays {
+    /// 从设备仿真参数（sensorDelayMs / responseDelayMs）读取
+    pub fn from_sim_params(params: &serde_json::Value) -> Self {
+        let read = |key: &str| params.get(key).and_then(|v| v.as_f64()).unwrap_or(0.0).max(0.0);
+        Self {
+            sensor_delay_ms: read("sensorDelayMs"),
+            actuator_delay_ms: read("responseDelayMs"),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sensor_delay_ms <= 0.0 && self.actuator_delay_ms <= 0.0
+    }
+}
 
 pub struct DelaySimulator {
-    device_delays: HashMap<String, f64>, // 设备ID -> 响应延迟（秒）
+    device_delays: HashMap<String, DeviceDelays>, // 设备ID -> 传感器/执行器延迟
     measurement_errors: HashMap<String, f64>, // 设备ID -> 测量误差（百分比）
-    communication_delays: HashMap<String, f64>, // 设备ID -> 通信延迟（秒）
+    /// 传感器延迟缓冲："结果分组/设备ID" -> (仿真时间, 结果条目)
+    sensor_history: HashMap<String, VecDeque<(f64, serde_json::Value)>>,
 }
 
 impl DelaySimulator {
@@ -13,22 +41,25 @@ impl DelaySimulator {
         Self {
             device_delays: HashMap::new(),
             measurement_errors: HashMap::new(),
-            communication_delays: HashMap::new(),
+            sensor_history: HashMap::new(),
         }
     }
 
-    pub fn set_device_response_delay(&mut self, device_id: &str, delay: f64) {
-        self.device_delays.insert(device_id.to_string(), delay);
+    pub fn set_device_delays(&mut self, device_id: &str, delays: DeviceDelays) {
+        if delays.is_empty() {
+            self.device_delays.remove(device_id);
+        } else {
+            self.device_delays.insert(device_id.to_string(), delays);
+        }
+        if delays.sensor_delay_ms <= 0.0 {
+            self.sensor_history.retain(|k, _| !k.ends_with(&format!("/{}", device_id)));
+        }
     }
 
     pub fn set_device_measurement_error(&mut self, device_id: &str, error_percent: f64) {
         self.measurement_errors.insert(device_id.to_string(), error_percent);
     }
 
-    pub fn set_device_communication_delay(&mut self, device_id: &str, delay: f64) {
-        self.communication_delays.insert(device_id.to_string(), delay);
-    }
-
     pub fn apply_measurement_error(&self, device_id: &str, value: f64) -> f64 {
         if let Some(&error_percent) = self.measurement_errors.get(device_id) {
             let mut rng = rand::thread_rng();
@@ -40,12 +71,59 @@ impl DelaySimulator {
         }
     }
 
-    pub fn get_response_delay(&self, device_id: &str) -> f64 {
-        self.device_delays.get(device_id).copied().unwrap_or(0.0)
+    pub fn get_device_delays(&self, device_id: &str) -> Option<DeviceDelays> {
+        self.device_delays.get(device_id).copied()
     }
 
-    pub fn get_communication_delay(&self, device_id: &str) -> f64 {
-        self.communication_delays.get(device_id).copied().unwrap_or(0.0)
+    /// 新一轮仿真：清空上报缓冲，延迟配置保留
+    pub fn reset(&mut self) {
+        self.sensor_history.clear();
+    }
+
+    /// 对配置了传感器延迟的设备，将本步结果替换为 sensor_delay 之前的结果（历史不足时沿用最早一条）；
+    /// 无设备配置传感器延迟时返回 None，调用方直接使用原结果
+    pub fn apply_sensor_delay(
+        &mut self,
+        topology: &crate::domain::topology::Topology,
+        results: &serde_json::Value,
+        sim_elapsed_s: f64,
+    ) -> Option<serde_json::Value> {
+        let delayed_by_name: HashMap<&str, (&str, f64)> = self
+            .device_delays
+            .iter()
+            .filter(|(_, d)| d.sensor_delay_ms > 0.0)
+            .filter_map(|(id, d)| {
+                topology
+                    .devices
+                    .get(id)
+                    .map(|dev| (dev.name.as_str(), (id.as_str(), d.sensor_delay_ms / 1000.0)))
+            })
+            .collect();
+        if delayed_by_name.is_empty() {
+            return None;
+        }
+        let mut reported = results.clone();
+        let groups = reported.as_object_mut()?;
+        for (group, entries) in groups.iter_mut() {
+            let Some(entries) = entries.as_object_mut() else { continue };
+            for entry in entries.values_mut() {
+                let Some(name) = entry.get("name").and_then(|v| v.as_str()) else { continue };
+                let Some(&(device_id, delay_s)) = delayed_by_name.get(name) else { continue };
+                let history = self
+                    .sensor_history
+                    .entry(format!("{}/{}", group, device_id))
+                    .or_default();
+                history.push_back((sim_elapsed_s, entry.clone()));
+                let cutoff = sim_elapsed_s - delay_s;
+                while history.len() >= 2 && history[1].0 <= cutoff {
+                    history.pop_front();
+                }
+                if let Some((_, value)) = history.front() {
+                    *entry = value.clone();
+                }
+            }
+        }
+        Some(reported)
     }
 }
 
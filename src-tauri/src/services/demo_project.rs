@@ -0,0 +1,132 @@
+// 内置演示项目：示例拓扑、随机功率曲线、分时电价与场景脚本随程序打包，安装到临时工作目录后启动一段脚本化运行
+// 用于销售演示与首次使用引导，无需用户文件或额外配置 Python 环境
+use crate::domain::random_profile::RandomProfile;
+use crate::domain::topology::{DeviceType, Topology};
+use crate::services::scenario::{self, ScenarioScript};
+use crate::services::simulation_engine::SimulationEngine;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter};
+
+const DEMO_TOPOLOGY: &str = include_str!("../../demo/topology.json");
+const DEMO_PROFILES: &str = include_str!("../../demo/profiles.json");
+const DEMO_TARIFF: &str = include_str!("../../demo/tariff.json");
+const DEMO_SCENARIO: &str = include_str!("../../demo/scenario.json");
+
+/// 演示运行结束事件（负载为场景结果或错误信息）
+pub const DEMO_FINISHED_EVENT: &str = "demo-project-finished";
+
+/// 演示设备的随机数据配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DemoProfile {
+    pub min_power: f64,
+    pub max_power: f64,
+    pub profile: RandomProfile,
+}
+
+/// 已安装的演示工作目录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DemoWorkspace {
+    pub dir: String,
+    pub topology_path: String,
+    pub profiles_path: String,
+    pub tariff_path: String,
+    pub scenario_path: String,
+}
+
+/// 已启动的演示运行
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DemoProjectInfo {
+    pub workspace: DemoWorkspace,
+    pub scenario_name: String,
+    pub duration_s: f64,
+}
+
+fn write_file(dir: &Path, name: &str, content: &str) -> Result<String, String> {
+    let path = dir.join(name);
+    std::fs::write(&path, content).map_err(|e| format!("写入演示文件失败 {}: {}", path.display(), e))?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// 安装演示项目到临时目录 pvsc-demo-<unix_ts>：电价写入外部电网的 grid_schedule，场景脚本指向安装后的拓扑文件
+pub fn install_workspace() -> Result<DemoWorkspace, String> {
+    let ts = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    let dir = std::env::temp_dir().join(format!("pvsc-demo-{}", ts));
+    std::fs::create_dir_all(&dir).map_err(|e| format!("创建演示目录失败 {}: {}", dir.display(), e))?;
+
+    let tariff: serde_json::Value =
+        serde_json::from_str(DEMO_TARIFF).map_err(|e| format!("解析演示电价失败: {}", e))?;
+    let mut topology: Topology =
+        serde_json::from_str(DEMO_TOPOLOGY).map_err(|e| format!("解析演示拓扑失败: {}", e))?;
+    for device in topology.devices.values_mut().filter(|d| d.device_type == DeviceType::ExternalGrid) {
+        device.properties.insert("grid_schedule".to_string(), tariff.clone());
+    }
+    let topology_json =
+        serde_json::to_string_pretty(&topology).map_err(|e| format!("序列化演示拓扑失败: {}", e))?;
+    let topology_path = write_file(&dir, "topology.json", &topology_json)?;
+
+    let mut script: ScenarioScript =
+        serde_json::from_str(DEMO_SCENARIO).map_err(|e| format!("解析演示场景失败: {}", e))?;
+    script.topology_path = Some(topology_path.clone());
+    let scenario_json =
+        serde_json::to_string_pretty(&script).map_err(|e| format!("序列化演示场景失败: {}", e))?;
+
+    Ok(DemoWorkspace {
+        dir: dir.to_string_lossy().to_string(),
+        topology_path,
+        profiles_path: write_file(&dir, "profiles.json", DEMO_PROFILES)?,
+        tariff_path: write_file(&dir, "tariff.json", DEMO_TARIFF)?,
+        scenario_path: write_file(&dir, "scenario.json", &scenario_json)?,
+    })
+}
+
+fn load_profiles(path: &str) -> Result<HashMap<String, DemoProfile>, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| format!("读取演示曲线失败 {}: {}", path, e))?;
+    serde_json::from_str(&content).map_err(|e| format!("解析演示曲线失败: {}", e))
+}
+
+/// 启动演示运行（拓扑须已设置到引擎）：按演示步长启动仿真，配置随机曲线，后台运行场景脚本，结束后停止仿真并发出 demo-project-finished
+pub async fn start_demo_run(
+    engine: Arc<SimulationEngine>,
+    app: AppHandle,
+    workspace: DemoWorkspace,
+) -> Result<DemoProjectInfo, String> {
+    let script = ScenarioScript::load(&workspace.scenario_path)?;
+    let profiles = load_profiles(&workspace.profiles_path)?;
+    for profile in profiles.values() {
+        profile.profile.validate()?;
+    }
+    engine.start(Some(app.clone()), script.calculation_interval_ms.unwrap_or(1000)).await?;
+    for (device_id, p) in profiles {
+        let configured = async {
+            engine.set_device_mode(device_id.clone(), "random_data".to_string()).await?;
+            engine
+                .set_device_random_config(device_id.clone(), p.min_power, p.max_power, Some(p.profile), None)
+                .await
+        }
+        .await;
+        if let Err(e) = configured {
+            let _ = engine.stop().await;
+            return Err(format!("配置演示设备 {} 失败: {}", device_id, e));
+        }
+    }
+
+    let info = DemoProjectInfo {
+        workspace,
+        scenario_name: script.name.clone(),
+        duration_s: script.duration_s,
+    };
+    tokio::spawn(async move {
+        let result = scenario::run_scenario(&engine, &app, &script).await;
+        let _ = engine.stop().await;
+        let payload = match result {
+            Ok(r) => serde_json::json!({ "result": r }),
+            Err(e) => serde_json::json!({ "error": e }),
+        };
+        let _ = app.emit(DEMO_FINISHED_EVENT, payload);
+    });
+    Ok(info)
+}
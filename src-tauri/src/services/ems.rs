@@ -0,0 +1,394 @@
+// 能量管理（EMS）调度：DispatchStrategy 每步按上一步结果（关口功率、负荷、光伏、电价、储能 SOC）给出储能有功设定，
+// 引擎在本步计算前下发（与储能计划同一路径）。内置削峰、分时套利与自发自用三种策略，由 ems 命令组选择与配置
+use crate::domain::grid_schedule::GridSchedule;
+use crate::domain::simulation::{sim_hour_of_day, StorageState};
+use crate::domain::topology::{DeviceType, Topology};
+use crate::services::setpoint_limits::power_rating;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 受控储能的当前状态（功率为内核原生约定：正=充电）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageView {
+    pub device_id: String,
+    pub soc_percent: f64,
+    pub capacity_kwh: f64,
+    pub max_power_kw: f64,
+    pub p_kw: f64,
+}
+
+/// 策略输入：上一步结果汇总（功率为内核原生约定：关口正=购电，负荷正=用电，光伏正=发电）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DispatchContext {
+    /// 本步开始的仿真时间（Unix 秒）与本地小时
+    pub timestamp: f64,
+    pub hour: u8,
+    pub dt_s: f64,
+    pub grid_import_kw: f64,
+    pub load_kw: f64,
+    pub pv_kw: f64,
+    /// 当前时段购/售电电价（外部电网分时电价，未配置为 None）
+    pub import_price: Option<f64>,
+    pub sell_price: Option<f64>,
+    pub min_soc_percent: f64,
+    pub max_soc_percent: f64,
+    pub storages: Vec<StorageView>,
+}
+
+impl DispatchContext {
+    /// 不含受控储能时的关口功率（正=购电）
+    pub fn base_import_kw(&self) -> f64 {
+        self.grid_import_kw - self.storages.iter().map(|s| s.p_kw).sum::<f64>()
+    }
+
+    /// 把总功率（正=充电）分配到各储能：按额定功率比例分摊，受 SOC 上下限与本步可充放电量约束
+    pub fn allocate(&self, total_kw: f64) -> Vec<DispatchSetpoint> {
+        let dt_h = self.dt_s / 3600.0;
+        let limits: Vec<(String, f64)> = self
+            .storages
+            .iter()
+            .map(|s| {
+                let room_kwh = if total_kw >= 0.0 {
+                    (self.max_soc_percent - s.soc_percent) / 100.0 * s.capacity_kwh
+                } else {
+                    (s.soc_percent - self.min_soc_percent) / 100.0 * s.capacity_kwh
+                };
+                let limit = if dt_h > 0.0 { (room_kwh.max(0.0) / dt_h).min(s.max_power_kw) } else { 0.0 };
+                (s.device_id.clone(), limit)
+            })
+            .collect();
+        let available: f64 = limits.iter().map(|(_, l)| l).sum();
+        let magnitude = total_kw.abs().min(available);
+        limits
+            .into_iter()
+            .map(|(device_id, limit)| {
+                let share = if available > 0.0 { magnitude * limit / available } else { 0.0 };
+                DispatchSetpoint { device_id, p_kw: share.copysign(total_kw) }
+            })
+            .collect()
+    }
+}
+
+/// 储能有功设定（kW，正=充电，负=放电）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DispatchSetpoint {
+    pub device_id: String,
+    pub p_kw: f64,
+}
+
+/// 调度策略：每步调用一次，返回需下发的储能设定（未返回的储能保持原设定）
+pub trait DispatchStrategy: Send {
+    fn name(&self) -> &'static str;
+    fn dispatch(&mut self, ctx: &DispatchContext) -> Vec<DispatchSetpoint>;
+}
+
+/// 削峰：购电超过限值时放电补足，低于限值时（可选）在不越限的余量内充电
+pub struct PeakShaving {
+    pub import_limit_kw: f64,
+    pub recharge: bool,
+}
+
+impl DispatchStrategy for PeakShaving {
+    fn name(&self) -> &'static str {
+        "peak_shaving"
+    }
+
+    fn dispatch(&mut self, ctx: &DispatchContext) -> Vec<DispatchSetpoint> {
+        let excess_kw = ctx.base_import_kw() - self.import_limit_kw;
+        if excess_kw > 0.0 || self.recharge {
+            ctx.allocate(-excess_kw)
+        } else {
+            ctx.allocate(0.0)
+        }
+    }
+}
+
+/// 分时套利：电价不高于充电价时充电，不低于放电价时放电，其余时段待机
+pub struct TouArbitrage {
+    pub charge_below_price: f64,
+    pub discharge_above_price: f64,
+    /// 充放电功率（kW，总量）；None 为全部储能额定功率之和
+    pub power_kw: Option<f64>,
+}
+
+impl DispatchStrategy for TouArbitrage {
+    fn name(&self) -> &'static str {
+        "tou_arbitrage"
+    }
+
+    fn dispatch(&mut self, ctx: &DispatchContext) -> Vec<DispatchSetpoint> {
+        let power_kw = self
+            .power_kw
+            .unwrap_or_else(|| ctx.storages.iter().map(|s| s.max_power_kw).sum());
+        match ctx.import_price {
+            Some(price) if price <= self.charge_below_price => ctx.allocate(power_kw),
+            Some(price) if price >= self.discharge_above_price => ctx.allocate(-power_kw),
+            _ => ctx.allocate(0.0),
+        }
+    }
+}
+
+/// 自发自用：光伏余电充入储能，用电缺口由储能放电补足，使关口交换趋近于零
+pub struct SelfConsumption;
+
+impl DispatchStrategy for SelfConsumption {
+    fn name(&self) -> &'static str {
+        "self_consumption"
+    }
+
+    fn dispatch(&mut self, ctx: &DispatchContext) -> Vec<DispatchSetpoint> {
+        ctx.allocate(-ctx.base_import_kw())
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// 内置策略及参数
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "strategy", rename_all = "snake_case")]
+pub enum StrategyConfig {
+    PeakShaving {
+        import_limit_kw: f64,
+        #[serde(default = "default_true")]
+        recharge: bool,
+    },
+    TouArbitrage {
+        charge_below_price: f64,
+        discharge_above_price: f64,
+        #[serde(default)]
+        power_kw: Option<f64>,
+    },
+    SelfConsumption,
+}
+
+impl StrategyConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            StrategyConfig::PeakShaving { import_limit_kw, .. } if !import_limit_kw.is_finite() => {
+                Err("削峰购电限值无效".to_string())
+            }
+            StrategyConfig::TouArbitrage { charge_below_price, discharge_above_price, power_kw } => {
+                if !(charge_below_price.is_finite() && discharge_above_price.is_finite())
+                    || charge_below_price >= discharge_above_price
+                {
+                    return Err("套利充电电价须低于放电电价".to_string());
+                }
+                if power_kw.is_some_and(|p| !p.is_finite() || p <= 0.0) {
+                    return Err("套利充放电功率必须大于 0".to_string());
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn build(&self) -> Box<dyn DispatchStrategy> {
+        match *self {
+            StrategyConfig::PeakShaving { import_limit_kw, recharge } => Box::new(PeakShaving { import_limit_kw, recharge }),
+            StrategyConfig::TouArbitrage { charge_below_price, discharge_above_price, power_kw } => {
+                Box::new(TouArbitrage { charge_below_price, discharge_above_price, power_kw })
+            }
+            StrategyConfig::SelfConsumption => Box::new(SelfConsumption),
+        }
+    }
+}
+
+fn default_min_soc() -> f64 {
+    10.0
+}
+
+fn default_max_soc() -> f64 {
+    90.0
+}
+
+fn default_deadband_kw() -> f64 {
+    1.0
+}
+
+/// EMS 配置：策略与受控储能、SOC 运行区间、下发死区
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EmsConfig {
+    #[serde(flatten)]
+    pub strategy: StrategyConfig,
+    /// 受控储能设备 ID；为空时控制拓扑中全部储能
+    #[serde(default)]
+    pub storages: Vec<String>,
+    #[serde(default = "default_min_soc")]
+    pub min_soc_percent: f64,
+    #[serde(default = "default_max_soc")]
+    pub max_soc_percent: f64,
+    /// 设定变化小于该值（kW）时不重新下发
+    #[serde(default = "default_deadband_kw")]
+    pub deadband_kw: f64,
+}
+
+impl EmsConfig {
+    pub fn validate(&self, topology: Option<&Topology>) -> Result<(), String> {
+        self.strategy.validate()?;
+        if !(0.0..=100.0).contains(&self.min_soc_percent)
+            || !(0.0..=100.0).contains(&self.max_soc_percent)
+            || self.min_soc_percent >= self.max_soc_percent
+        {
+            return Err("SOC 运行区间无效".to_string());
+        }
+        if !self.deadband_kw.is_finite() || self.deadband_kw < 0.0 {
+            return Err("下发死区不能为负".to_string());
+        }
+        if let Some(t) = topology {
+            for id in &self.storages {
+                match t.devices.get(id) {
+                    Some(d) if d.device_type == DeviceType::Storage => {}
+                    Some(d) => return Err(format!("设备 {} 不是储能", d.name)),
+                    None => return Err(format!("设备不存在: {}", id)),
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// EMS 运行状态
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EmsStatus {
+    pub config: Option<EmsConfig>,
+    /// 生效策略名称
+    pub strategy: Option<String>,
+    /// 最近一次调度的输入
+    pub last_context: Option<DispatchContext>,
+    /// 各储能最近下发的设定
+    pub applied: HashMap<String, f64>,
+    pub dispatch_steps: u64,
+}
+
+#[derive(Default)]
+pub struct EmsController {
+    config: Option<EmsConfig>,
+    strategy: Option<Box<dyn DispatchStrategy>>,
+    last_context: Option<DispatchContext>,
+    applied: HashMap<String, f64>,
+    dispatch_steps: u64,
+}
+
+impl EmsController {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 新一轮仿真：清空下发记录（配置保留）
+    pub fn reset(&mut self) {
+        self.last_context = None;
+        self.applied.clear();
+        self.dispatch_steps = 0;
+    }
+
+    pub fn configure(&mut self, config: EmsConfig) {
+        self.strategy = Some(config.strategy.build());
+        self.config = Some(config);
+        self.applied.clear();
+    }
+
+    /// 关闭 EMS，返回此前受控的储能（由调用方恢复为零功率）
+    pub fn disable(&mut self) -> Vec<String> {
+        self.config = None;
+        self.strategy = None;
+        self.last_context = None;
+        self.applied.drain().map(|(id, _)| id).collect()
+    }
+
+    pub fn status(&self) -> EmsStatus {
+        EmsStatus {
+            config: self.config.clone(),
+            strategy: self.strategy.as_ref().map(|s| s.name().to_string()),
+            last_context: self.last_context.clone(),
+            applied: self.applied.clone(),
+            dispatch_steps: self.dispatch_steps,
+        }
+    }
+
+    /// 按上一步结果运行策略，返回与已下发值相差超过死区的设定；device_power 为功率缓存（内核原生符号约定，直接使用），
+    /// excluded 为不受 EMS 控制的储能（如正在执行日前计划）；timestamp 为仿真时间，时段按其取小时
+    pub fn dispatch(
+        &mut self,
+        topology: &Topology,
+        excluded: impl Fn(&str) -> bool,
+        storage_state: &HashMap<String, StorageState>,
+        device_power: &HashMap<String, (f64, Option<f64>, Option<f64>)>,
+        timestamp: f64,
+        dt_s: f64,
+    ) -> Vec<DispatchSetpoint> {
+        let (Some(config), Some(strategy)) = (self.config.as_ref(), self.strategy.as_mut()) else {
+            return Vec::new();
+        };
+        // 首步尚无结果时不调度
+        if device_power.is_empty() {
+            return Vec::new();
+        }
+        let native_kw = |device_id: &str| device_power.get(device_id).and_then(|(_, p, _)| *p).unwrap_or(0.0);
+        let hour = sim_hour_of_day(timestamp);
+        let (mut grid_import_kw, mut load_kw, mut pv_kw) = (0.0, 0.0, 0.0);
+        let (mut import_price, mut sell_price) = (None, None);
+        let mut storages = Vec::new();
+        let mut ids: Vec<&String> = topology.devices.keys().collect();
+        ids.sort();
+        for device_id in ids {
+            let device = &topology.devices[device_id];
+            match device.device_type {
+                DeviceType::ExternalGrid => {
+                    grid_import_kw += native_kw(device_id);
+                    if let Some(slot) = GridSchedule::from_properties(&device.properties)
+                        .and_then(|s| s.slot_at(hour).cloned())
+                    {
+                        import_price = import_price.or(slot.price);
+                        sell_price = sell_price.or(slot.sell_price);
+                    }
+                }
+                DeviceType::Load | DeviceType::Charger => load_kw += native_kw(device_id),
+                DeviceType::Pv => pv_kw += native_kw(device_id),
+                DeviceType::Storage
+                    if (config.storages.is_empty() || config.storages.contains(device_id)) && !excluded(device_id) =>
+                {
+                    let (Some(state), Some(rating)) = (storage_state.get(device_id), power_rating(device)) else {
+                        continue;
+                    };
+                    storages.push(StorageView {
+                        device_id: device_id.clone(),
+                        soc_percent: state.soc_percent,
+                        capacity_kwh: state.effective_capacity_kwh,
+                        max_power_kw: rating.max_kw,
+                        p_kw: native_kw(device_id),
+                    });
+                }
+                _ => {}
+            }
+        }
+        let ctx = DispatchContext {
+            timestamp,
+            hour,
+            dt_s,
+            grid_import_kw,
+            load_kw,
+            pv_kw,
+            import_price,
+            sell_price,
+            min_soc_percent: config.min_soc_percent,
+            max_soc_percent: config.max_soc_percent,
+            storages,
+        };
+        let setpoints = strategy.dispatch(&ctx);
+        let deadband_kw = config.deadband_kw;
+        self.last_context = Some(ctx);
+        self.dispatch_steps += 1;
+        setpoints
+            .into_iter()
+            .filter(|s| s.p_kw.is_finite())
+            .filter(|s| self.applied.get(&s.device_id).is_none_or(|p| (p - s.p_kw).abs() >= deadband_kw.max(1e-6)))
+            .collect()
+    }
+
+    /// 设定已下发内核
+    pub fn commit(&mut self, setpoint: &DispatchSetpoint) {
+        self.applied.insert(setpoint.device_id.clone(), setpoint.p_kw);
+    }
+}
@@ -0,0 +1,300 @@
+// 电量平衡核对：电表 Modbus 累计电量（折算一次侧）与被测设备功率积分电量按计量点逐一比对，
+// 用于发现寄存器映射的比例/符号错误并验证电表仿真；可先记录基线，只核对基线之后的增量
+use crate::domain::simulation::DeviceEnergyCounters;
+use crate::domain::topology::{DeviceType, Topology};
+use crate::services::meter_accuracy::MeterMetrology;
+use crate::services::modbus_server;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex as StdMutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 电表正向 / 反向有功电量（与 modbus_server 一致：被测设备内核原生功率为正计入 energy_export_kwh，为负计入 energy_import_kwh）
+const METER_FORWARD_ENERGY_KEY: &str = "energy_export_kwh";
+const METER_REVERSE_ENERGY_KEY: &str = "energy_import_kwh";
+/// 默认允许偏差（%，另加电表准确度等级）
+pub const DEFAULT_TOLERANCE_PCT: f64 = 2.0;
+
+/// 核对基线：电表正/反向电量读数（kWh 二次侧）与设备电量计数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnergyBalanceBaseline {
+    pub taken_at: f64,
+    pub meter_registers: HashMap<String, (f64, f64)>,
+    pub device_energy: HashMap<String, DeviceEnergyCounters>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BalanceStatus {
+    /// 偏差在允许范围内
+    Ok,
+    /// 正反向电量互换，疑似符号/方向错误
+    SignSwapped,
+    /// 偏差接近 10 的整数次幂或 PT×CT 倍数，疑似比例/单位错误
+    Scaled,
+    /// 偏差超限且无明显规律
+    Mismatch,
+    /// 无被测设备、无寄存器或被测对象无电量计数
+    NoData,
+}
+
+/// 单个计量点的核对结果（电量均为一次侧 kWh，正向 = 被测设备内核原生功率为正的方向）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeteringPointBalance {
+    pub meter_id: String,
+    pub meter_name: String,
+    pub target_device_id: Option<String>,
+    pub target_device_type: Option<String>,
+    pub pt_ratio: f64,
+    pub ct_ratio: f64,
+    pub meter_forward_kwh: f64,
+    pub meter_reverse_kwh: f64,
+    pub device_forward_kwh: f64,
+    pub device_reverse_kwh: f64,
+    /// 净电量偏差（电表 - 设备）
+    pub net_diff_kwh: f64,
+    /// 偏差占设备吞吐电量（正向 + 反向）的百分比
+    pub diff_pct: Option<f64>,
+    /// 电表吞吐电量 / 设备吞吐电量
+    pub scale_ratio: Option<f64>,
+    /// 寄存器分辨率折算的一次侧电量
+    pub resolution_kwh: f64,
+    /// 本计量点允许偏差（%）
+    pub tolerance_pct: f64,
+    pub status: BalanceStatus,
+    pub note: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnergyBalanceReport {
+    pub generated_at: f64,
+    /// 基线时间；None 表示按电表与设备计数的全部累计值核对
+    pub baseline_at: Option<f64>,
+    pub tolerance_pct: f64,
+    pub points: Vec<MeteringPointBalance>,
+    pub ok_count: usize,
+    pub issue_count: usize,
+}
+
+/// 基线存储（应用级状态）
+#[derive(Default)]
+pub struct EnergyBalanceStore {
+    baseline: StdMutex<Option<EnergyBalanceBaseline>>,
+}
+
+impl EnergyBalanceStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_baseline(&self, baseline: EnergyBalanceBaseline) {
+        *self.baseline.lock().unwrap() = Some(baseline);
+    }
+
+    pub fn clear_baseline(&self) {
+        *self.baseline.lock().unwrap() = None;
+    }
+
+    pub fn baseline(&self) -> Option<EnergyBalanceBaseline> {
+        self.baseline.lock().unwrap().clone()
+    }
+}
+
+fn now_secs() -> f64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs_f64()
+}
+
+/// 由电量快照（device_id -> key -> 解码后的工程值，见 ModbusService::energy_value_snapshot）取各电表正/反向读数
+fn meter_readings(energy: &HashMap<String, HashMap<String, f64>>) -> HashMap<String, (f64, f64)> {
+    energy
+        .iter()
+        .filter_map(|(id, values)| {
+            let fwd = values.get(METER_FORWARD_ENERGY_KEY)?;
+            let rev = values.get(METER_REVERSE_ENERGY_KEY)?;
+            Some((id.clone(), (*fwd, *rev)))
+        })
+        .collect()
+}
+
+pub fn baseline(
+    registers: &HashMap<String, HashMap<String, f64>>,
+    device_energy: HashMap<String, DeviceEnergyCounters>,
+) -> EnergyBalanceBaseline {
+    EnergyBalanceBaseline { taken_at: now_secs(), meter_registers: meter_readings(registers), device_energy }
+}
+
+/// 被测设备电量按内核原生方向拆分为 (正向, 反向)：光伏正 = 发电（计数中的输出），其余正 = 从电网取电（计数中的输入）
+fn device_native_energy(device_type: &DeviceType, counters: &DeviceEnergyCounters) -> (f64, f64) {
+    match device_type {
+        DeviceType::Pv => (counters.total_export_kwh, counters.total_import_kwh),
+        _ => (counters.total_import_kwh, counters.total_export_kwh),
+    }
+}
+
+fn has_energy_counter(device_type: &DeviceType) -> bool {
+    matches!(
+        device_type,
+        DeviceType::Pv | DeviceType::Storage | DeviceType::Load | DeviceType::Charger | DeviceType::ExternalGrid
+    )
+}
+
+/// 电表到被测设备（电表与其他设备的连接，取第一个）
+fn meter_targets(topology: &Topology) -> HashMap<String, String> {
+    let is_meter = |id: &str| topology.devices.get(id).is_some_and(|d| d.device_type == DeviceType::Meter);
+    let mut targets = HashMap::new();
+    for conn in topology.connections.values() {
+        if is_meter(&conn.from_device_id) && !is_meter(&conn.to_device_id) {
+            targets.entry(conn.from_device_id.clone()).or_insert_with(|| conn.to_device_id.clone());
+        }
+        if is_meter(&conn.to_device_id) && !is_meter(&conn.from_device_id) {
+            targets.entry(conn.to_device_id.clone()).or_insert_with(|| conn.from_device_id.clone());
+        }
+    }
+    targets
+}
+
+/// 比例是否接近常见的错误倍数（10 的整数次幂或 PT×CT 及其倒数），返回说明
+fn typical_scale(ratio: f64, pt_ct: f64, tolerance: f64) -> Option<String> {
+    let near = |target: f64| target > 0.0 && (ratio / target - 1.0).abs() <= tolerance;
+    if pt_ct != 1.0 && near(pt_ct) {
+        return Some(format!("约为 PT×CT（{}）倍，疑似重复乘变比", pt_ct));
+    }
+    if pt_ct != 1.0 && near(1.0 / pt_ct) {
+        return Some(format!("约为 1/(PT×CT)（{}）倍，疑似未乘变比", pt_ct));
+    }
+    (-3..=3)
+        .filter(|k| *k != 0)
+        .map(|k| 10f64.powi(k))
+        .find(|s| near(*s))
+        .map(|s| format!("约为 {} 倍，疑似寄存器单位错误", s))
+}
+
+/// 逐计量点核对；baseline 存在时只核对基线之后的增量
+pub fn reconcile(
+    topology: &Topology,
+    registers: &HashMap<String, HashMap<String, f64>>,
+    device_energy: &HashMap<String, DeviceEnergyCounters>,
+    baseline: Option<&EnergyBalanceBaseline>,
+    tolerance_pct: f64,
+) -> EnergyBalanceReport {
+    let readings = meter_readings(registers);
+    let targets = meter_targets(topology);
+    let mut meters: Vec<_> = topology.devices.values().filter(|d| d.device_type == DeviceType::Meter).collect();
+    meters.sort_by(|a, b| a.id.cmp(&b.id));
+
+    let points: Vec<MeteringPointBalance> = meters
+        .into_iter()
+        .map(|meter| {
+            let metrology = MeterMetrology::from_device(meter);
+            let pt_ct = metrology.pt_ratio * metrology.ct_ratio;
+            let point_tolerance = tolerance_pct + metrology.accuracy_class_pct;
+            let target = targets.get(&meter.id).and_then(|id| topology.devices.get(id));
+            let mut point = MeteringPointBalance {
+                meter_id: meter.id.clone(),
+                meter_name: meter.name.clone(),
+                target_device_id: target.map(|d| d.id.clone()),
+                target_device_type: target.map(|d| d.device_type.as_str().to_string()),
+                pt_ratio: metrology.pt_ratio,
+                ct_ratio: metrology.ct_ratio,
+                meter_forward_kwh: 0.0,
+                meter_reverse_kwh: 0.0,
+                device_forward_kwh: 0.0,
+                device_reverse_kwh: 0.0,
+                net_diff_kwh: 0.0,
+                diff_pct: None,
+                scale_ratio: None,
+                resolution_kwh: pt_ct / modbus_server::energy_register_unit("meter"),
+                tolerance_pct: point_tolerance,
+                status: BalanceStatus::NoData,
+                note: String::new(),
+            };
+            let Some(target) = target else {
+                point.note = "电表未连接被测设备".to_string();
+                return point;
+            };
+            if !has_energy_counter(&target.device_type) {
+                point.note = "被测对象无设备电量计数（仅支持光伏、储能、负荷、充电桩、外部电网）".to_string();
+                return point;
+            }
+            let Some(&(fwd_reg, rev_reg)) = readings.get(&meter.id) else {
+                point.note = "电表 Modbus 服务未运行，无电量寄存器".to_string();
+                return point;
+            };
+            let mut notes: Vec<String> = Vec::new();
+            let (base_fwd, base_rev) = baseline
+                .and_then(|b| b.meter_registers.get(&meter.id).copied())
+                .unwrap_or((0.0, 0.0));
+            if fwd_reg < base_fwd || rev_reg < base_rev {
+                notes.push("基线后电表电量被清零，增量按当前读数计".to_string());
+            }
+            let delta = |cur: f64, base: f64| if cur >= base { cur - base } else { cur };
+            point.meter_forward_kwh = delta(fwd_reg, base_fwd) * pt_ct;
+            point.meter_reverse_kwh = delta(rev_reg, base_rev) * pt_ct;
+
+            let zero = DeviceEnergyCounters::default();
+            let (dev_fwd, dev_rev) =
+                device_native_energy(&target.device_type, device_energy.get(&target.id).unwrap_or(&zero));
+            let (base_dev_fwd, base_dev_rev) = baseline
+                .and_then(|b| b.device_energy.get(&target.id))
+                .map(|c| device_native_energy(&target.device_type, c))
+                .unwrap_or((0.0, 0.0));
+            point.device_forward_kwh = (dev_fwd - base_dev_fwd).max(0.0);
+            point.device_reverse_kwh = (dev_rev - base_dev_rev).max(0.0);
+
+            let meter_net = point.meter_forward_kwh - point.meter_reverse_kwh;
+            let device_net = point.device_forward_kwh - point.device_reverse_kwh;
+            let device_total = point.device_forward_kwh + point.device_reverse_kwh;
+            let meter_total = point.meter_forward_kwh + point.meter_reverse_kwh;
+            point.net_diff_kwh = meter_net - device_net;
+            if device_total > 0.0 {
+                point.diff_pct = Some(point.net_diff_kwh.abs() / device_total * 100.0);
+                point.scale_ratio = Some(meter_total / device_total);
+            }
+            // 允许偏差：按百分比与寄存器分辨率取较大者
+            let allowed = (device_total * point_tolerance / 100.0).max(point.resolution_kwh);
+            let within = |a: f64, b: f64| (a - b).abs() <= allowed;
+
+            point.status = if device_total == 0.0 && meter_total <= point.resolution_kwh {
+                notes.push("核对期间无电量".to_string());
+                BalanceStatus::Ok
+            } else if within(point.meter_forward_kwh, point.device_forward_kwh)
+                && within(point.meter_reverse_kwh, point.device_reverse_kwh)
+            {
+                BalanceStatus::Ok
+            } else if within(point.meter_forward_kwh, point.device_reverse_kwh)
+                && within(point.meter_reverse_kwh, point.device_forward_kwh)
+            {
+                notes.push("电表正向电量与设备反向电量一致，疑似功率符号或安装方向错误".to_string());
+                BalanceStatus::SignSwapped
+            } else if let Some(scale) = point
+                .scale_ratio
+                .and_then(|r| typical_scale(r, pt_ct, point_tolerance / 100.0))
+            {
+                notes.push(scale);
+                BalanceStatus::Scaled
+            } else if device_total == 0.0 {
+                notes.push("设备无电量但电表有计量".to_string());
+                BalanceStatus::Mismatch
+            } else {
+                notes.push("偏差超出允许范围（可能存在电表通信中断、上报延迟或非被测设备的功率）".to_string());
+                BalanceStatus::Mismatch
+            };
+            point.note = notes.join("；");
+            point
+        })
+        .collect();
+
+    let ok_count = points.iter().filter(|p| p.status == BalanceStatus::Ok).count();
+    let issue_count = points
+        .iter()
+        .filter(|p| !matches!(p.status, BalanceStatus::Ok | BalanceStatus::NoData))
+        .count();
+    EnergyBalanceReport {
+        generated_at: now_secs(),
+        baseline_at: baseline.map(|b| b.taken_at),
+        tolerance_pct,
+        points,
+        ok_count,
+        issue_count,
+    }
+}
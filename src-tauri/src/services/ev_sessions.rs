@@ -0,0 +1,298 @@
+// 充电桩电动汽车充电会话模型（ev_session 工作模式）：按设备配置生成车辆到达（泊松过程，可按小时加权）、
+// 停留时长与需求电量，空闲枪接入后以单枪最大功率充电；每步计算前按活动会话汇总需求功率，
+// 经 simulation.set_device_ev_value 下发内核，计算后按实际功率分摊到各枪累计电量。
+// 会话结束（离开、被拒绝或仿真停止）时写入仿真库 ev_sessions 表
+use crate::domain::device::{Distribution, EvSessionConfig};
+use crate::domain::simulation::{sim_hour_of_day, EvSessionRecord, EvSessionStatus};
+use crate::domain::topology::{Device, Topology};
+use crate::services::random_generator::{self, arrives, exp_duration, seeded_rng, standard_normal};
+use crate::services::result_index::ResultIndex;
+use crate::services::setpoint_limits::power_rating;
+use rand::rngs::StdRng;
+use rand::Rng;
+use std::collections::HashMap;
+
+/// 充电枪状态（Modbus IR 100 起每枪一个寄存器）
+pub const GUN_IDLE: u16 = 1;
+/// 已连接车辆但未充电（已充满，等待离开）
+pub const GUN_CONNECTED: u16 = 2;
+pub const GUN_CHARGING: u16 = 3;
+
+/// 停留时长下限（分钟），避免抽样得到 0 时长会话
+const MIN_DWELL_MINUTES: f64 = 1.0;
+/// 需求电量下限（kWh）
+const MIN_ENERGY_KWH: f64 = 0.1;
+
+fn sample(rng: &mut StdRng, distribution: &Distribution) -> f64 {
+    match *distribution {
+        Distribution::Fixed { value } => value,
+        Distribution::Uniform { min, max } if max > min => rng.gen_range(min..max),
+        Distribution::Uniform { min, .. } => min,
+        Distribution::Normal { mean, std_dev } => (mean + std_dev * standard_normal(rng)).max(0.0),
+        Distribution::Exponential { mean } => exp_duration(rng, mean),
+    }
+}
+
+struct ActiveSession {
+    session_id: String,
+    arrival_at: f64,
+    /// 计划离开时间
+    departure_at: f64,
+    energy_requested_kwh: f64,
+    energy_delivered_kwh: f64,
+    charged_at: Option<f64>,
+    peak_power_kw: f64,
+    /// 本步需求功率（kW）
+    demand_kw: f64,
+}
+
+struct ChargerSessions {
+    config: EvSessionConfig,
+    max_gun_kw: f64,
+    rated_kw: Option<f64>,
+    guns: Vec<Option<ActiveSession>>,
+    rng: StdRng,
+    next_session: u64,
+}
+
+impl ChargerSessions {
+    fn record(device_id: &str, gun: usize, session: ActiveSession, departure_at: f64, status: EvSessionStatus) -> EvSessionRecord {
+        EvSessionRecord {
+            session_id: session.session_id,
+            device_id: device_id.to_string(),
+            gun: Some(gun as u32 + 1),
+            arrival_at: session.arrival_at,
+            departure_at,
+            charged_at: session.charged_at,
+            energy_requested_kwh: session.energy_requested_kwh,
+            energy_delivered_kwh: session.energy_delivered_kwh,
+            peak_power_kw: session.peak_power_kw,
+            status,
+        }
+    }
+
+    fn demand_kw(&self) -> f64 {
+        self.guns.iter().flatten().map(|s| s.demand_kw).sum()
+    }
+}
+
+/// 解析充电桩会话参数：枪数与单枪最大功率（缺省为额定功率按枪数均分）
+fn charger_limits(device: &Device, config: &EvSessionConfig) -> Result<(usize, f64, Option<f64>), String> {
+    config.validate()?;
+    let guns = config
+        .guns
+        .or_else(|| device.properties.get("gun_count").and_then(|v| v.as_u64()).map(|n| n as u32))
+        .unwrap_or(1)
+        .max(1) as usize;
+    let rated_kw = power_rating(device).map(|r| r.max_kw).filter(|kw| *kw > 0.0);
+    let max_gun_kw = config
+        .max_gun_power_kw
+        .or(rated_kw.map(|kw| kw / guns as f64))
+        .ok_or_else(|| format!("充电桩 {} 未设置额定功率或单枪最大功率", device.name))?;
+    Ok((guns, max_gun_kw, rated_kw))
+}
+
+/// 校验设备能否使用充电会话模型
+pub fn validate_device(device: &Device) -> Result<(), String> {
+    charger_limits(device, &EvSessionConfig::from_properties(&device.properties)).map(|_| ())
+}
+
+/// 各充电桩的会话状态；每轮仿真开始时清空
+#[derive(Default)]
+pub struct EvSessionBank {
+    chargers: HashMap<String, ChargerSessions>,
+}
+
+impl EvSessionBank {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn reset(&mut self) {
+        self.chargers.clear();
+    }
+
+    /// 推进一步（步初仿真时间 t0、步长 dt_s）：到达计划离开时间的会话结束，新到达车辆接入空闲枪（无空闲枪时拒绝），
+    /// 计算各枪需求功率。返回本步需求功率（kW，用电为正）与结束的会话；参数无效时返回错误
+    pub fn plan(
+        &mut self,
+        device_id: &str,
+        device: &Device,
+        global_seed: Option<u64>,
+        t0: f64,
+        dt_s: f64,
+    ) -> Result<(f64, Vec<EvSessionRecord>), String> {
+        let config = EvSessionConfig::from_properties(&device.properties);
+        let (gun_count, max_gun_kw, rated_kw) = charger_limits(device, &config)?;
+        let mut finished = Vec::new();
+        let charger = self.chargers.entry(device_id.to_string()).or_insert_with(|| ChargerSessions {
+            config: config.clone(),
+            max_gun_kw,
+            rated_kw,
+            guns: Vec::new(),
+            rng: seeded_rng(random_generator::device_seed(global_seed, device_id, config.seed)),
+            next_session: 0,
+        });
+        // 运行中修改参数：立即生效；枪数减少时被移除枪上的会话按中断结束
+        charger.config = config;
+        charger.max_gun_kw = max_gun_kw;
+        charger.rated_kw = rated_kw;
+        while charger.guns.len() > gun_count {
+            let gun = charger.guns.len() - 1;
+            if let Some(session) = charger.guns.pop().flatten() {
+                finished.push(ChargerSessions::record(device_id, gun, session, t0, EvSessionStatus::Interrupted));
+            }
+        }
+        charger.guns.resize_with(gun_count, || None);
+
+        for (gun, slot) in charger.guns.iter_mut().enumerate() {
+            if let Some(session) = slot.take_if(|s| s.departure_at <= t0) {
+                let status = if session.charged_at.is_some() { EvSessionStatus::Completed } else { EvSessionStatus::Unmet };
+                let departure_at = session.departure_at;
+                finished.push(ChargerSessions::record(device_id, gun, session, departure_at, status));
+            }
+        }
+
+        let weight = match charger.config.hourly_weights.as_slice() {
+            [] => 1.0,
+            // 到达加权按仿真时间的时段取值
+            weights => weights[sim_hour_of_day(t0) as usize % weights.len()],
+        };
+        if arrives(&mut charger.rng, charger.config.arrivals_per_hour * weight, dt_s) {
+            charger.next_session += 1;
+            let session_id = format!("{}-{}", device_id, charger.next_session);
+            let dwell_s = sample(&mut charger.rng, &charger.config.dwell_minutes).max(MIN_DWELL_MINUTES) * 60.0;
+            let energy_kwh = sample(&mut charger.rng, &charger.config.energy_kwh).max(MIN_ENERGY_KWH);
+            match charger.guns.iter().position(|g| g.is_none()) {
+                Some(gun) => {
+                    charger.guns[gun] = Some(ActiveSession {
+                        session_id,
+                        arrival_at: t0,
+                        departure_at: t0 + dwell_s,
+                        energy_requested_kwh: energy_kwh,
+                        energy_delivered_kwh: 0.0,
+                        charged_at: None,
+                        peak_power_kw: 0.0,
+                        demand_kw: 0.0,
+                    });
+                }
+                None => finished.push(EvSessionRecord {
+                    session_id,
+                    device_id: device_id.to_string(),
+                    gun: None,
+                    arrival_at: t0,
+                    departure_at: t0,
+                    charged_at: None,
+                    energy_requested_kwh: energy_kwh,
+                    energy_delivered_kwh: 0.0,
+                    peak_power_kw: 0.0,
+                    status: EvSessionStatus::Rejected,
+                }),
+            }
+        }
+
+        // 需求功率：未充满的枪按剩余电量与单枪最大功率取小，总需求不超过额定功率（按比例压缩）
+        let dt_h = dt_s / 3600.0;
+        for session in charger.guns.iter_mut().flatten() {
+            let remaining_kwh = (session.energy_requested_kwh - session.energy_delivered_kwh).max(0.0);
+            session.demand_kw = if session.charged_at.is_some() || dt_h <= 0.0 {
+                0.0
+            } else {
+                (remaining_kwh / dt_h).min(charger.max_gun_kw)
+            };
+        }
+        let total_kw = charger.demand_kw();
+        if let Some(rated_kw) = charger.rated_kw.filter(|kw| total_kw > *kw) {
+            for session in charger.guns.iter_mut().flatten() {
+                session.demand_kw *= rated_kw / total_kw;
+            }
+        }
+        Ok((charger.demand_kw(), finished))
+    }
+
+    /// 按本步结果累计各枪电量：充电桩实际功率（受功率限制等影响，devices.loads 经结果索引定位）按需求比例分摊到各枪；
+    /// 孤岛中不参与潮流的充电桩按内核上报的本拍设定（device_setpoints）计
+    pub fn deliver(
+        &mut self,
+        topology: &Topology,
+        index: &ResultIndex,
+        devices: &serde_json::Value,
+        setpoints: Option<&serde_json::Value>,
+        dt_s: f64,
+        timestamp: f64,
+    ) {
+        let dt_h = dt_s / 3600.0;
+        let mut actual: HashMap<&str, f64> = HashMap::new();
+        if let Some(rows) = devices.get("loads").and_then(|v| v.as_object()) {
+            for row in rows.values() {
+                let Some(name) = row.get("name").and_then(|v| v.as_str()) else { continue };
+                let Some((device_id, _)) = index.lookup(topology, "loads", name) else { continue };
+                if let Some(p_mw) = row.get("p_mw").and_then(|v| v.as_f64()) {
+                    actual.insert(device_id.as_str(), p_mw * 1000.0);
+                }
+            }
+        }
+        for (device_id, charger) in self.chargers.iter_mut() {
+            if !topology.devices.contains_key(device_id) {
+                continue;
+            }
+            let actual_kw = actual
+                .get(device_id.as_str())
+                .copied()
+                .or_else(|| setpoints.and_then(|s| s.get(device_id)).and_then(|v| v.as_f64()))
+                .unwrap_or(0.0)
+                .max(0.0);
+            let demand_kw = charger.demand_kw();
+            if demand_kw <= 0.0 {
+                continue;
+            }
+            let ratio = (actual_kw / demand_kw).min(1.0);
+            for session in charger.guns.iter_mut().flatten().filter(|s| s.demand_kw > 0.0) {
+                let p_kw = session.demand_kw * ratio;
+                session.energy_delivered_kwh += p_kw * dt_h;
+                session.peak_power_kw = session.peak_power_kw.max(p_kw);
+                if session.energy_delivered_kwh >= session.energy_requested_kwh - 1e-6 {
+                    session.energy_delivered_kwh = session.energy_requested_kwh;
+                    session.charged_at = Some(timestamp);
+                }
+            }
+        }
+    }
+
+    /// 各充电桩的枪状态与本步需求功率（写入 Modbus 输入寄存器）
+    pub fn gun_states(&self) -> Vec<(String, Vec<u16>, f64)> {
+        self.chargers
+            .iter()
+            .map(|(device_id, charger)| {
+                let states = charger
+                    .guns
+                    .iter()
+                    .map(|gun| match gun {
+                        None => GUN_IDLE,
+                        Some(s) if s.demand_kw > 0.0 => GUN_CHARGING,
+                        Some(_) => GUN_CONNECTED,
+                    })
+                    .collect();
+                (device_id.clone(), states, charger.demand_kw())
+            })
+            .collect()
+    }
+
+    /// 停止模型（设备切换模式或仿真停止）：仍接入的会话按中断结束
+    pub fn remove(&mut self, device_id: &str, at: f64) -> Vec<EvSessionRecord> {
+        let Some(charger) = self.chargers.remove(device_id) else {
+            return Vec::new();
+        };
+        charger
+            .guns
+            .into_iter()
+            .enumerate()
+            .filter_map(|(gun, session)| session.map(|s| ChargerSessions::record(device_id, gun, s, at, EvSessionStatus::Interrupted)))
+            .collect()
+    }
+
+    pub fn finish_all(&mut self, at: f64) -> Vec<EvSessionRecord> {
+        let ids: Vec<String> = self.chargers.keys().cloned().collect();
+        ids.iter().flat_map(|id| self.remove(id, at)).collect()
+    }
+}
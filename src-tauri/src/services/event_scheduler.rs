@@ -0,0 +1,272 @@
+// 定时事件调度：按 JSON 事件表在计算循环中于指定仿真时间施加设备属性修改（如 t=300s 负荷设为 50 kW、t=600s 断开开关），
+// 用于可重复的测试工况；场景脚本的事件同样经此调度，所有定时事件在计算步内按仿真时间对齐施加，不依赖墙钟轮询
+use serde::{Deserialize, Serialize};
+
+/// 事件动作（场景脚本使用）：电网边界条件、设备手动设定与模式切换
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum EventAction {
+    GridVoltage { vm_pu: f64 },
+    GridFrequency { frequency_hz: f64 },
+    /// 设备有功/无功设定（设备须处于手动模式，可先用 device_mode 切换）
+    DeviceSetpoint { device_id: String, p_kw: f64, #[serde(default)] q_kvar: f64 },
+    DeviceMode { device_id: String, mode: String },
+}
+
+impl EventAction {
+    /// 动作作用的设备；电网边界条件动作为 None
+    pub fn device_id(&self) -> Option<&str> {
+        match self {
+            Self::DeviceSetpoint { device_id, .. } | Self::DeviceMode { device_id, .. } => Some(device_id),
+            Self::GridVoltage { .. } | Self::GridFrequency { .. } => None,
+        }
+    }
+
+    pub fn is_grid_boundary(&self) -> bool {
+        matches!(self, Self::GridVoltage { .. } | Self::GridFrequency { .. })
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            Self::GridVoltage { vm_pu } if !vm_pu.is_finite() || *vm_pu <= 0.0 => {
+                Err(format!("外部电网电压无效: {}", vm_pu))
+            }
+            Self::GridFrequency { frequency_hz } if !frequency_hz.is_finite() || *frequency_hz <= 0.0 => {
+                Err(format!("频率无效: {}", frequency_hz))
+            }
+            _ if self.device_id().is_some_and(|d| d.is_empty()) => Err("缺少 device_id".to_string()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// 电网边界条件（外部电网电压与频率模型状态），场景施加电网事件前记录、结束后恢复
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GridBoundary {
+    /// 外部电网电压设定（pu），None 表示使用拓扑中各外部电网自身的 vm_pu
+    pub vm_pu: Option<f64>,
+    pub frequency_hz: f64,
+    pub islanded: bool,
+    pub nominal_hz: f64,
+}
+
+/// 一条定时事件：at_s 为相对本轮仿真开始的仿真时间（秒）；properties 经 update_device_properties_for_simulation 写入，
+/// 其中 is_closed 按开关操作处理（同时更新内核网络中的开关状态）；指定 action 时按动作施加，忽略 device_id/properties
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledEvent {
+    pub at_s: f64,
+    #[serde(default)]
+    pub device_id: String,
+    #[serde(default)]
+    pub properties: serde_json::Map<String, serde_json::Value>,
+    #[serde(default)]
+    pub action: Option<EventAction>,
+    #[serde(default)]
+    pub label: Option<String>,
+    /// 事件归属：None 为载入的事件表，Some 为运行中的场景（场景结束或新一轮仿真时撤回）
+    #[serde(skip)]
+    pub owner: Option<String>,
+}
+
+impl ScheduledEvent {
+    fn validate(&self) -> Result<(), String> {
+        if !self.at_s.is_finite() || self.at_s < 0.0 {
+            return Err(format!("事件时间无效: {}", self.at_s));
+        }
+        if let Some(action) = &self.action {
+            return action.validate();
+        }
+        if self.device_id.is_empty() {
+            return Err("事件缺少 device_id".to_string());
+        }
+        if self.properties.is_empty() {
+            return Err("事件未指定属性".to_string());
+        }
+        if self.properties.get("is_closed").is_some_and(|v| !v.is_boolean()) {
+            return Err("事件 is_closed 须为布尔值".to_string());
+        }
+        Ok(())
+    }
+
+    /// 事件作用的设备（电网边界条件动作为空）
+    pub fn target_device(&self) -> &str {
+        match &self.action {
+            Some(action) => action.device_id().unwrap_or(""),
+            None => &self.device_id,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EventSchedule {
+    #[serde(default)]
+    pub name: Option<String>,
+    pub events: Vec<ScheduledEvent>,
+}
+
+impl EventSchedule {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path).map_err(|e| format!("读取事件表失败 {}: {}", path, e))?;
+        let schedule: Self = serde_json::from_str(&content).map_err(|e| format!("解析事件表失败: {}", e))?;
+        Ok(schedule)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        validate_events(&self.events)
+    }
+}
+
+fn validate_events(events: &[ScheduledEvent]) -> Result<(), String> {
+    for (i, e) in events.iter().enumerate() {
+        e.validate().map_err(|err| format!("第 {} 条{}", i + 1, err))?;
+    }
+    Ok(())
+}
+
+/// 已施加事件的记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppliedEventRecord {
+    /// 事件在事件表中的序号（按时间排序后）
+    pub index: usize,
+    pub at_s: f64,
+    /// 实际施加时的仿真时间（步对齐，不早于 at_s）
+    pub sim_time_s: f64,
+    /// 作用的设备（电网边界条件动作为空）
+    pub device_id: String,
+    pub properties: serde_json::Map<String, serde_json::Value>,
+    #[serde(default)]
+    pub action: Option<EventAction>,
+    #[serde(default)]
+    pub label: Option<String>,
+    pub ok: bool,
+    #[serde(default)]
+    pub error: Option<String>,
+    #[serde(skip)]
+    pub owner: Option<String>,
+}
+
+impl AppliedEventRecord {
+    pub fn new(index: usize, event: ScheduledEvent, sim_time_s: f64, applied: Result<(), String>) -> Self {
+        Self {
+            index,
+            at_s: event.at_s,
+            sim_time_s,
+            device_id: event.target_device().to_string(),
+            properties: event.properties,
+            action: event.action,
+            label: event.label,
+            ok: applied.is_ok(),
+            error: applied.err(),
+            owner: event.owner,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventScheduleStatus {
+    pub name: Option<String>,
+    pub total: usize,
+    pub applied: usize,
+    pub pending: usize,
+    /// 下一条待施加事件的时间
+    pub next_at_s: Option<f64>,
+    pub log: Vec<AppliedEventRecord>,
+}
+
+#[derive(Default)]
+pub struct EventScheduler {
+    name: Option<String>,
+    /// 按 at_s 升序
+    events: Vec<ScheduledEvent>,
+    next: usize,
+    log: Vec<AppliedEventRecord>,
+}
+
+impl EventScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 载入事件表（替换原有事件表并从头开始；运行中场景的事件保留）
+    pub fn load(&mut self, schedule: EventSchedule) -> Result<(), String> {
+        schedule.validate()?;
+        let scenario_events: Vec<ScheduledEvent> =
+            self.events.drain(self.next..).filter(|e| e.owner.is_some()).collect();
+        let mut events = schedule.events;
+        events.extend(scenario_events);
+        events.sort_by(|a, b| a.at_s.total_cmp(&b.at_s));
+        self.name = schedule.name;
+        self.events = events;
+        self.next = 0;
+        self.log.clear();
+        Ok(())
+    }
+
+    /// 清除事件表（运行中场景的事件保留）
+    pub fn clear(&mut self) {
+        let scenario_events: Vec<ScheduledEvent> =
+            self.events.drain(self.next..).filter(|e| e.owner.is_some()).collect();
+        *self = Self::default();
+        self.events = scenario_events;
+    }
+
+    /// 新一轮仿真：保留事件表，从头施加；场景事件只属于发起它的那轮仿真，一并撤回
+    pub fn rewind(&mut self) {
+        self.events.retain(|e| e.owner.is_none());
+        self.next = 0;
+        self.log.clear();
+    }
+
+    /// 追加事件（如场景脚本，须带 owner）：按时间插入待施加部分，时间已过的事件在下一步立即施加
+    pub fn insert(&mut self, events: Vec<ScheduledEvent>) -> Result<(), String> {
+        validate_events(&events)?;
+        let mut pending = self.events.split_off(self.next);
+        pending.extend(events);
+        pending.sort_by(|a, b| a.at_s.total_cmp(&b.at_s));
+        self.events.extend(pending);
+        Ok(())
+    }
+
+    /// 恢复仿真时跳过仿真时间 sim_time_s 之前已到期的事件（中断前已施加），不记录日志
+    pub fn skip_until(&mut self, sim_time_s: f64) {
+        while self.next < self.events.len() && self.events[self.next].at_s < sim_time_s {
+            self.next += 1;
+        }
+    }
+
+    /// 撤回指定归属尚未施加的事件
+    pub fn withdraw(&mut self, owner: &str) {
+        let pending = self.events.split_off(self.next);
+        self.events.extend(pending.into_iter().filter(|e| e.owner.as_deref() != Some(owner)));
+    }
+
+    /// 指定归属首个施加失败的事件
+    pub fn first_failure(&self, owner: &str) -> Option<&AppliedEventRecord> {
+        self.log.iter().find(|r| !r.ok && r.owner.as_deref() == Some(owner))
+    }
+
+    /// 取出仿真时间 sim_time_s 时已到期的事件，返回 (序号, 事件)
+    pub fn take_due(&mut self, sim_time_s: f64) -> Vec<(usize, ScheduledEvent)> {
+        let mut due = Vec::new();
+        while self.next < self.events.len() && self.events[self.next].at_s <= sim_time_s {
+            due.push((self.next, self.events[self.next].clone()));
+            self.next += 1;
+        }
+        due
+    }
+
+    pub fn record(&mut self, record: AppliedEventRecord) {
+        self.log.push(record);
+    }
+
+    pub fn status(&self) -> EventScheduleStatus {
+        EventScheduleStatus {
+            name: self.name.clone(),
+            total: self.events.len(),
+            applied: self.next,
+            pending: self.events.len() - self.next,
+            next_at_s: self.events.get(self.next).map(|e| e.at_s),
+            log: self.log.clone(),
+        }
+    }
+}
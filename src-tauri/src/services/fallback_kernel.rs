@@ -0,0 +1,851 @@
+// 降级内核：Python 内核无法启动时在进程内应答内核 RPC，由内置前推回代求解器计算辐射状网络
+// 与 Python 内核相同的拓扑格式、设备模式与结果表（buses/lines/transformers/switches/...），计算循环无需区分；
+// 只实现计算循环与设备控制用到的方法，故障注入、状态导入导出、快照等返回 not_implemented。
+// 不含 Modbus 限幅过滤、无功控制、频率响应与测量噪声，每拍按当前拓扑重建网络（适合小规模网络）
+use crate::services::radial_power_flow::{self, Complex, RadialBranch, RadialBus, RadialNetwork, RadialSolution};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::sync::Mutex as StdMutex;
+
+/// 降级内核在 power.list_kernels 中的标识
+pub const FALLBACK_KERNEL_ID: &str = "builtin_radial";
+
+const DEFAULT_MAX_ITERATION: u32 = 100;
+const DEFAULT_TOLERANCE_PU: f64 = 1e-8;
+const DEFAULT_CABLE_TYPE: &str = "NAYY 4x50 SE";
+/// 未配置短路电压时按配电变压器典型值（%）
+const TRAFO_VK_PERCENT: f64 = 6.0;
+const TRAFO_VKR_PERCENT: f64 = 1.2;
+
+/// 线路标准类型参数（与 pandapower 标准类型库一致）：r Ω/km、x Ω/km、载流量 kA
+const LINE_STD_TYPES: &[(&str, f64, f64, f64)] = &[
+    ("NAYY 4x50 SE", 0.642, 0.083, 0.142),
+    ("NAYY 4x120 SE", 0.225, 0.08, 0.242),
+    ("NAYY 4x150 SE", 0.208, 0.08, 0.27),
+    ("NA2XS2Y 1x95 RM/25 12/20 kV", 0.313, 0.132, 0.252),
+    ("NA2XS2Y 1x150 RM/25 12/20 kV", 0.206, 0.116, 0.319),
+    ("NA2XS2Y 1x185 RM/25 12/20 kV", 0.161, 0.117, 0.362),
+    ("NA2XS2Y 1x240 RM/25 12/20 kV", 0.122, 0.112, 0.421),
+    ("15-AL1/3-ST1A 0.4", 1.8769, 0.35, 0.105),
+    ("24-AL1/4-ST1A 0.4", 1.2012, 0.335, 0.14),
+    ("48-AL1/8-ST1A 0.4", 0.5939, 0.3, 0.21),
+    ("94-AL1/15-ST1A 0.4", 0.306, 0.29, 0.35),
+];
+
+#[derive(Default)]
+struct FallbackState {
+    topology: Option<Value>,
+    running: bool,
+    paused: bool,
+    calculation_count: u64,
+    calculation_interval_ms: u64,
+    time_scale: f64,
+    max_iteration: u32,
+    tolerance_pu: f64,
+    device_modes: HashMap<String, String>,
+    /// 手动模式设定（kW, kVar）
+    manual_setpoints: HashMap<String, (f64, f64)>,
+    ext_grid_vm_pu: Option<f64>,
+    errors: Vec<Value>,
+    last_result: Option<Value>,
+    sim_time: Option<f64>,
+    last_calculation_time: Option<f64>,
+}
+
+/// 进程内降级内核（线程安全，由 KernelClient 直接调用）
+pub struct FallbackKernel {
+    state: StdMutex<FallbackState>,
+}
+
+impl FallbackKernel {
+    pub fn new() -> Self {
+        Self {
+            state: StdMutex::new(FallbackState {
+                calculation_interval_ms: 1000,
+                time_scale: 1.0,
+                max_iteration: DEFAULT_MAX_ITERATION,
+                tolerance_pu: DEFAULT_TOLERANCE_PU,
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// 处理一次 RPC，返回 result；未知的顶层方法返回错误（与 Python 内核的 JSON-RPC error 对应）
+    pub fn handle(&self, method: &str, params: &Value) -> Result<Value, String> {
+        if method == "ping" {
+            return Ok(json!({ "status": "ok" }));
+        }
+        if method == "power.list_kernels" {
+            return Ok(json!({
+                "kernels": [{
+                    "id": FALLBACK_KERNEL_ID,
+                    "name": "内置辐射网潮流（降级）",
+                    "description": "Python 内核不可用时的内置前推回代求解器，仅支持单一外部电网供电的辐射状网络",
+                    "available": true,
+                    "unavailable_reason": null,
+                    "unbalanced": false,
+                    "reactive_power": true,
+                    "solver_options": ["max_iteration", "tolerance_mva"],
+                    "features": ["Backward/forward sweep power flow", "Radial networks"],
+                }],
+                "current": FALLBACK_KERNEL_ID,
+            }));
+        }
+        if method.starts_with("power.") {
+            return Ok(json!({ "status": "not_implemented" }));
+        }
+        let Some(action) = method.strip_prefix("simulation.") else {
+            return Err(format!("Unknown method: {}", method));
+        };
+        let mut state = self.state.lock().unwrap();
+        let device_id = params.get("device_id").and_then(|v| v.as_str()).unwrap_or_default();
+        let p_kw = params.get("p_kw").and_then(number).unwrap_or(0.0);
+        let result = match action {
+            "set_topology" | "preload_topology" => {
+                let Some(topology) = params.get("topology_data").filter(|t| !t.is_null()) else {
+                    return Ok(json!({ "status": "error", "message": "拓扑数据未提供" }));
+                };
+                state.topology = Some(topology.clone());
+                state.device_modes.clear();
+                state.manual_setpoints.clear();
+                state.last_result = None;
+                if action == "preload_topology" {
+                    json!({ "status": "ok", "warmed_up": false, "errors": [] })
+                } else {
+                    json!({ "status": "ok", "kernel_type": FALLBACK_KERNEL_ID })
+                }
+            }
+            "start" => {
+                if state.topology.is_none() {
+                    return Ok(json!({ "status": "error", "message": "请先设置拓扑数据" }));
+                }
+                state.set_solver_options(params.get("solver_options"));
+                state.calculation_interval_ms = params.get("calculation_interval_ms").and_then(|v| v.as_u64()).unwrap_or(1000);
+                state.time_scale = params.get("time_scale").and_then(number).filter(|t| *t > 0.0).unwrap_or(1.0);
+                state.calculation_count = 0;
+                state.paused = false;
+                if !state.running {
+                    state.running = true;
+                    state.errors.clear();
+                }
+                json!({ "status": "started" })
+            }
+            "stop" => {
+                state.running = false;
+                state.paused = false;
+                state.device_modes.clear();
+                state.manual_setpoints.clear();
+                state.sim_time = None;
+                json!({ "status": "stopped" })
+            }
+            "pause" => {
+                state.paused = true;
+                json!({ "status": "paused" })
+            }
+            "resume" => {
+                state.paused = false;
+                json!({ "status": "resumed" })
+            }
+            "set_device_mode" => {
+                let mode = params.get("mode").and_then(|v| v.as_str()).unwrap_or_default();
+                state.device_modes.insert(device_id.to_string(), mode.to_string());
+                json!({ "status": "ok" })
+            }
+            "set_device_manual_setpoint" => {
+                let p = params.get("active_power").and_then(number).unwrap_or(0.0);
+                let q = params.get("reactive_power").and_then(number).unwrap_or(0.0);
+                state.manual_setpoints.insert(device_id.to_string(), (p, q));
+                json!({ "status": "ok" })
+            }
+            "set_device_random_value" => {
+                state.set_source_value(device_id, "random_data", p_kw, Some(0.0));
+                json!({ "status": "ok" })
+            }
+            "set_device_historical_value" => {
+                let q_kvar = params.get("q_kvar").and_then(number).unwrap_or(0.0);
+                state.set_source_value(device_id, "historical_data", p_kw, Some(q_kvar));
+                json!({ "status": "ok" })
+            }
+            "set_device_solar_value" => {
+                state.set_source_value(device_id, "solar_model", p_kw, Some(0.0));
+                json!({ "status": "ok" })
+            }
+            "set_device_weather_value" => {
+                state.set_source_value(device_id, "weather_model", p_kw, None);
+                json!({ "status": "ok" })
+            }
+            "set_device_ev_value" => {
+                state.set_source_value(device_id, "ev_session", p_kw, Some(0.0));
+                json!({ "status": "ok" })
+            }
+            "update_device_properties" => {
+                let properties = params.get("properties").and_then(|v| v.as_object()).cloned().unwrap_or_default();
+                state.update_device_properties(device_id, properties);
+                json!({ "status": "ok" })
+            }
+            "update_switch_state" => {
+                let is_closed = params.get("is_closed").and_then(|v| v.as_bool()).unwrap_or(true);
+                if let Some(props) = state.device_properties_mut(device_id) {
+                    props.insert("is_closed".to_string(), json!(is_closed));
+                }
+                json!({ "status": "ok" })
+            }
+            "set_transformer_tap" => {
+                let tap_pos = params.get("tap_pos").and_then(number).unwrap_or(0.0).round() as i64;
+                match state.device_properties_mut(device_id) {
+                    Some(props) => {
+                        props.insert("tap_pos".to_string(), json!(tap_pos));
+                        json!({ "status": "ok" })
+                    }
+                    None => json!({ "status": "error", "message": format!("设备不存在: {}", device_id) }),
+                }
+            }
+            "set_solver_options" => {
+                state.set_solver_options(params.get("options"));
+                json!({ "status": "ok" })
+            }
+            "set_ext_grid_voltage" => {
+                state.ext_grid_vm_pu = params.get("vm_pu").and_then(number);
+                json!({ "status": "ok" })
+            }
+            // 内置内核无频率模型，频率恒为额定值
+            "get_grid_boundary" => json!({
+                "status": "ok",
+                "vm_pu": state.ext_grid_vm_pu,
+                "frequency_hz": 50.0,
+                "islanded": false,
+                "nominal_hz": 50.0,
+            }),
+            "get_calculation_status" => json!({
+                "is_running": state.running,
+                "is_paused": state.paused,
+                "calculation_count": state.calculation_count,
+                "calculation_interval_ms": state.calculation_interval_ms,
+                "time_scale": state.time_scale,
+                "last_calculation_time": state.last_calculation_time,
+                "error_count": state.errors.len(),
+                "network_cached": false,
+                "keep_warm": false,
+            }),
+            "get_errors" => json!({ "errors": state.errors }),
+            "get_last_result" => json!({ "result": state.last_result }),
+            "perform_calculation" => {
+                let force = params.get("force").and_then(|v| v.as_bool()).unwrap_or(false);
+                let sim_time = params.get("sim_time").and_then(number);
+                json!({ "result": state.perform_calculation(force, sim_time) })
+            }
+            _ => json!({ "status": "not_implemented" }),
+        };
+        Ok(result)
+    }
+}
+
+impl FallbackState {
+    /// 求解参数：仅 max_iteration 与 tolerance_mva 生效（收敛判据为电压变化，1 MVA 基准下与功率失配同量级）
+    fn set_solver_options(&mut self, options: Option<&Value>) {
+        self.max_iteration = options
+            .and_then(|o| o.get("max_iteration"))
+            .and_then(|v| v.as_u64())
+            .map_or(DEFAULT_MAX_ITERATION, |v| v as u32);
+        self.tolerance_pu = options
+            .and_then(|o| o.get("tolerance_mva"))
+            .and_then(number)
+            .unwrap_or(DEFAULT_TOLERANCE_PU);
+    }
+
+    fn device_properties_mut(&mut self, device_id: &str) -> Option<&mut Map<String, Value>> {
+        let device = self.topology.as_mut()?.get_mut("devices")?.get_mut(device_id)?.as_object_mut()?;
+        device
+            .entry("properties")
+            .or_insert_with(|| Value::Object(Map::new()))
+            .as_object_mut()
+    }
+
+    /// 数据源写入：仅当设备处于对应模式时覆盖 properties 中的功率（q_kvar 为 None 时保留原值）
+    fn set_source_value(&mut self, device_id: &str, mode: &str, p_kw: f64, q_kvar: Option<f64>) {
+        if self.device_modes.get(device_id).map(String::as_str) != Some(mode) {
+            return;
+        }
+        if let Some(props) = self.device_properties_mut(device_id) {
+            props.insert("p_kw".to_string(), json!(p_kw));
+            if let Some(q) = q_kvar {
+                props.insert("q_kvar".to_string(), json!(q));
+            }
+        }
+    }
+
+    /// 合并属性增量；手动模式设备设定功率时同步手动设定（关机指令不覆盖）
+    fn update_device_properties(&mut self, device_id: &str, properties: Map<String, Value>) {
+        let manual = self.device_modes.get(device_id).map(String::as_str) == Some("manual");
+        let shutdown = properties.get("on_off").and_then(number) == Some(0.0);
+        let power = properties.get("p_kw").and_then(number).map(|p| {
+            (p, properties.get("q_kvar").and_then(number).unwrap_or(0.0))
+        });
+        let Some(props) = self.device_properties_mut(device_id) else { return };
+        if properties.contains_key("power_limit_raw") {
+            props.remove("power_limit_pct");
+        }
+        if properties.contains_key("power_limit_pct") {
+            props.remove("power_limit_raw");
+        }
+        props.extend(properties);
+        if let Some(setpoint) = power.filter(|_| manual && !shutdown) {
+            self.manual_setpoints.insert(device_id.to_string(), setpoint);
+        }
+    }
+
+    fn perform_calculation(&mut self, force: bool, sim_time: Option<f64>) -> Value {
+        if !self.running {
+            return json!({
+                "converged": false,
+                "errors": [{ "type": "runtime", "severity": "error", "message": "仿真未启动", "details": {} }],
+                "devices": {},
+            });
+        }
+        if self.paused && !force {
+            return self
+                .last_result
+                .clone()
+                .unwrap_or_else(|| json!({ "converged": false, "errors": [], "devices": {} }));
+        }
+        if sim_time.is_some() {
+            self.sim_time = sim_time;
+        }
+        // 第1阶段：手动模式设定写入 properties（随机/历史/模型数据源已由 set_device_*_value 写入）
+        let manual: Vec<(String, (f64, f64))> = self
+            .manual_setpoints
+            .iter()
+            .filter(|(id, _)| self.device_modes.get(*id).map(String::as_str) == Some("manual"))
+            .map(|(id, setpoint)| (id.clone(), *setpoint))
+            .collect();
+        for (device_id, (p_kw, q_kvar)) in manual {
+            if let Some(props) = self.device_properties_mut(&device_id) {
+                props.insert("p_kw".to_string(), json!(p_kw));
+                props.insert("q_kvar".to_string(), json!(q_kvar));
+            }
+        }
+
+        let started = std::time::Instant::now();
+        let topology = self.topology.clone().unwrap_or(Value::Null);
+        let mut errors: Vec<Value> = Vec::new();
+        let mut result = match NetworkModel::build(&topology, self.ext_grid_vm_pu, &mut errors) {
+            Ok(model) => match radial_power_flow::solve(&model.network, self.max_iteration, self.tolerance_pu) {
+                Ok(solution) => {
+                    if !solution.converged {
+                        errors.push(calculation_error("error", "潮流计算不收敛".to_string(), None, json!({})));
+                    }
+                    let devices = model.results(&solution, &mut errors);
+                    json!({
+                        "converged": solution.converged,
+                        "devices": devices,
+                        "solver": {
+                            "algorithm": "bfsw",
+                            "init": "flat",
+                            "iterations": solution.iterations,
+                            "elapsed_ms": started.elapsed().as_secs_f64() * 1000.0,
+                        },
+                    })
+                }
+                Err(e) => {
+                    errors.push(calculation_error("error", format!("潮流计算失败: {}", e), None, json!({})));
+                    json!({ "converged": false, "devices": {} })
+                }
+            },
+            Err(e) => {
+                errors.push(calculation_error("error", e, None, json!({})));
+                json!({ "converged": false, "devices": {} })
+            }
+        };
+
+        let converged = result["converged"].as_bool().unwrap_or(false);
+        // 与 Python 内核一致：不收敛或存在严重错误时自动暂停，避免周期性重复报错
+        let auto_paused = !converged || errors.iter().any(|e| e["severity"] == "error");
+        if auto_paused {
+            self.paused = true;
+        }
+        result["errors"] = json!(errors);
+        result["auto_paused"] = json!(auto_paused);
+        result["sim_time"] = json!(self.sim_time);
+        self.calculation_count += 1;
+        self.last_calculation_time = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .ok()
+            .map(|d| d.as_secs_f64());
+        self.errors = errors;
+        self.last_result = Some(result.clone());
+        result
+    }
+}
+
+/// 数值属性（前端可能以字符串保存）
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        Value::Bool(b) => Some(if *b { 1.0 } else { 0.0 }),
+        _ => None,
+    }
+}
+
+fn calculation_error(severity: &str, message: String, device_id: Option<&str>, details: Value) -> Value {
+    json!({
+        "type": "calculation",
+        "severity": severity,
+        "message": message,
+        "device_id": device_id,
+        "details": details,
+    })
+}
+
+/// 合并闭合的母线-母线开关两端（并查集）
+fn find(parent: &mut [usize], x: usize) -> usize {
+    let mut root = x;
+    while parent[root] != root {
+        root = parent[root];
+    }
+    let mut node = x;
+    while parent[node] != root {
+        let next = parent[node];
+        parent[node] = root;
+        node = next;
+    }
+    root
+}
+
+struct NodeRow {
+    id: String,
+    name: String,
+    /// 合并后的计算母线
+    bus: usize,
+}
+
+struct BranchRow {
+    id: String,
+    name: String,
+    branch: usize,
+    /// 线路载流量（kA）或变压器额定容量（MVA）
+    rating: f64,
+}
+
+struct InjectionRow {
+    name: String,
+    node: usize,
+    /// 表中功率（MW, Mvar），符号与 pandapower 对应表一致
+    p_mw: f64,
+    q_mvar: f64,
+    in_service: bool,
+}
+
+enum SwitchTarget {
+    Bus,
+    Line(usize),
+    Trafo(usize),
+}
+
+struct SwitchRow {
+    name: String,
+    node: usize,
+    target: SwitchTarget,
+    closed: bool,
+}
+
+/// 由标准拓扑构建的计算网络与结果表映射
+struct NetworkModel {
+    network: RadialNetwork,
+    nodes: Vec<NodeRow>,
+    lines: Vec<BranchRow>,
+    trafos: Vec<BranchRow>,
+    switches: Vec<SwitchRow>,
+    loads: Vec<InjectionRow>,
+    sgens: Vec<InjectionRow>,
+    storages: Vec<InjectionRow>,
+    ext_grids: Vec<(String, usize)>,
+}
+
+impl NetworkModel {
+    fn build(topology: &Value, ext_grid_vm_pu: Option<f64>, errors: &mut Vec<Value>) -> Result<Self, String> {
+        let empty = Map::new();
+        let devices = topology.get("devices").and_then(|d| d.as_object()).unwrap_or(&empty);
+        let connections: Vec<(&str, &str)> = topology
+            .get("connections")
+            .and_then(|c| c.as_array())
+            .map(|list| {
+                list.iter()
+                    .filter_map(|c| Some((c.get("from")?.as_str()?, c.get("to")?.as_str()?)))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let neighbours = |device_id: &str| -> Vec<&str> {
+            let mut out: Vec<&str> = Vec::new();
+            for (from, to) in &connections {
+                let other = if *from == device_id { *to } else if *to == device_id { *from } else { continue };
+                if !out.contains(&other) {
+                    out.push(other);
+                }
+            }
+            out
+        };
+        let device_type = |device: &Value| device.get("device_type").and_then(|v| v.as_str()).unwrap_or_default().to_string();
+        let props = |device: &Value| device.get("properties").and_then(|p| p.as_object()).cloned().unwrap_or_default();
+        let name = |id: &str, device: &Value| device.get("name").and_then(|v| v.as_str()).unwrap_or(id).to_string();
+
+        // 母线
+        let mut nodes: Vec<NodeRow> = Vec::new();
+        let mut node_vn: Vec<f64> = Vec::new();
+        let mut node_index: HashMap<&str, usize> = HashMap::new();
+        for (id, device) in devices.iter().filter(|(_, d)| device_type(d) == "Node") {
+            let p = props(device);
+            let vn_kv = ["voltage_level", "vn_kv", "voltage_kv", "rated_voltage"]
+                .iter()
+                .find_map(|k| p.get(*k).and_then(number))
+                .unwrap_or(0.4);
+            if vn_kv <= 0.0 {
+                return Err(format!("设备 {} 电压等级无效: {}", id, vn_kv));
+            }
+            node_index.insert(id.as_str(), nodes.len());
+            nodes.push(NodeRow { id: id.clone(), name: name(id, device), bus: 0 });
+            node_vn.push(vn_kv);
+        }
+
+        // 开关：闭合的母线-母线开关合并两端母线，母线-线路/变压器开关断开时对应元件停运
+        let mut parent: Vec<usize> = (0..nodes.len()).collect();
+        let mut pending_switches: Vec<(String, String, usize, String, bool)> = Vec::new();
+        for (id, device) in devices.iter().filter(|(_, d)| device_type(d) == "Switch") {
+            let ends = neighbours(id);
+            let closed = match props(device).get("is_closed") {
+                Some(Value::String(s)) => s.trim().eq_ignore_ascii_case("true"),
+                Some(Value::Null) | None => true,
+                Some(v) => number(v).is_none_or(|n| n != 0.0),
+            };
+            let bus_end = ends.iter().position(|e| node_index.contains_key(e));
+            let (Some(bus_pos), 2) = (bus_end, ends.len()) else {
+                return Err(format!("开关 {} 必须通过连接关系连接两端：一端为母线（Node），另一端为母线/线路/变压器", id));
+            };
+            let node = node_index[ends[bus_pos]];
+            let other = ends[1 - bus_pos];
+            if let Some(&other_node) = node_index.get(other) {
+                if closed {
+                    let (a, b) = (find(&mut parent, node), find(&mut parent, other_node));
+                    parent[a] = b;
+                }
+            }
+            pending_switches.push((id.clone(), name(id, device), node, other.to_string(), closed));
+        }
+        let mut bus_of_root: HashMap<usize, usize> = HashMap::new();
+        let mut buses: Vec<RadialBus> = Vec::new();
+        for (index, node) in nodes.iter_mut().enumerate() {
+            let root = find(&mut parent, index);
+            node.bus = *bus_of_root.entry(root).or_insert_with(|| {
+                buses.push(RadialBus { vn_kv: node_vn[root], load: Complex::default() });
+                buses.len() - 1
+            });
+        }
+
+        let two_nodes = |id: &str| -> Result<(usize, usize), String> {
+            let ends: Vec<usize> = neighbours(id).iter().filter_map(|e| node_index.get(e).copied()).collect();
+            match ends.as_slice() {
+                [a, b] => Ok((*a, *b)),
+                _ => Err(format!("连接设备 {} 必须通过连接关系恰好连接两个母线（Node），请检查 connections", id)),
+            }
+        };
+
+        // 线路与变压器
+        let mut branches: Vec<RadialBranch> = Vec::new();
+        let mut lines: Vec<BranchRow> = Vec::new();
+        let mut trafos: Vec<BranchRow> = Vec::new();
+        let mut line_index: HashMap<&str, usize> = HashMap::new();
+        let mut trafo_index: HashMap<&str, usize> = HashMap::new();
+        for (id, device) in devices.iter().filter(|(_, d)| device_type(d) == "Line") {
+            let (from, to) = two_nodes(id)?;
+            let p = props(device);
+            let length_km = p.get("length").and_then(number).unwrap_or(1.0);
+            let cable_type = p.get("cable_type").and_then(|v| v.as_str()).unwrap_or(DEFAULT_CABLE_TYPE);
+            let &(_, r, x, max_i_ka) = LINE_STD_TYPES
+                .iter()
+                .find(|t| t.0 == cable_type)
+                .unwrap_or_else(|| {
+                    errors.push(calculation_error(
+                        "warning",
+                        format!("线路 {} 的型号 {} 不在内置参数表中，按 {} 计算", id, cable_type, DEFAULT_CABLE_TYPE),
+                        Some(id),
+                        json!({}),
+                    ));
+                    &LINE_STD_TYPES[0]
+                });
+            let z_base = node_vn[to] * node_vn[to];
+            line_index.insert(id.as_str(), lines.len());
+            lines.push(BranchRow { id: id.clone(), name: name(id, device), branch: branches.len(), rating: max_i_ka });
+            branches.push(RadialBranch {
+                from: nodes[from].bus,
+                to: nodes[to].bus,
+                z_pu: Complex::new(r * length_km / z_base, x * length_km / z_base),
+                ratio: 1.0,
+                in_service: true,
+            });
+        }
+        for (id, device) in devices.iter().filter(|(_, d)| device_type(d) == "Transformer") {
+            let (a, b) = two_nodes(id)?;
+            // 电压等级较高的一端为高压侧
+            let (hv, lv) = if node_vn[a] >= node_vn[b] { (a, b) } else { (b, a) };
+            let p = props(device);
+            let sn_mva = p.get("rated_power").and_then(number).filter(|s| *s > 0.0).unwrap_or(630.0) / 1000.0;
+            let mut vn_hv_kv = p.get("high_voltage").and_then(number).unwrap_or(20.0);
+            let mut vn_lv_kv = p.get("low_voltage").and_then(number).unwrap_or(0.4);
+            let tap = |key: &str, default: f64| p.get(key).and_then(number).unwrap_or(default);
+            let step = tap("tap_pos", 0.0) - tap("tap_neutral", 0.0);
+            let factor = 1.0 + step * tap("tap_step_percent", 2.5) / 100.0;
+            if p.get("tap_side").and_then(|v| v.as_str()).is_some_and(|s| s.eq_ignore_ascii_case("lv")) {
+                vn_lv_kv *= factor;
+            } else {
+                vn_hv_kv *= factor;
+            }
+            let vk_percent = p.get("vk_percent").and_then(number).unwrap_or(TRAFO_VK_PERCENT);
+            let vkr_percent = p.get("vkr_percent").and_then(number).unwrap_or(TRAFO_VKR_PERCENT).min(vk_percent);
+            let (vk, vkr) = (vk_percent / 100.0, vkr_percent / 100.0);
+            // 阻抗折算到低压侧母线的系统基准（1 MVA）
+            let scale = (vn_lv_kv / node_vn[lv]).powi(2) / sn_mva;
+            trafo_index.insert(id.as_str(), trafos.len());
+            trafos.push(BranchRow { id: id.clone(), name: name(id, device), branch: branches.len(), rating: sn_mva });
+            branches.push(RadialBranch {
+                from: nodes[hv].bus,
+                to: nodes[lv].bus,
+                z_pu: Complex::new(vkr * scale, (vk * vk - vkr * vkr).sqrt() * scale),
+                ratio: (vn_hv_kv / node_vn[hv]) / (vn_lv_kv / node_vn[lv]),
+                in_service: true,
+            });
+        }
+        let mut switches: Vec<SwitchRow> = Vec::new();
+        for (id, switch_name, node, other, closed) in pending_switches {
+            let target = if node_index.contains_key(other.as_str()) {
+                SwitchTarget::Bus
+            } else if let Some(&line) = line_index.get(other.as_str()) {
+                SwitchTarget::Line(line)
+            } else if let Some(&trafo) = trafo_index.get(other.as_str()) {
+                SwitchTarget::Trafo(trafo)
+            } else {
+                return Err(format!("开关 {} 必须通过连接关系连接两端：一端为母线（Node），另一端为母线/线路/变压器", id));
+            };
+            let branch = match target {
+                SwitchTarget::Line(line) => Some(lines[line].branch),
+                SwitchTarget::Trafo(trafo) => Some(trafos[trafo].branch),
+                SwitchTarget::Bus => None,
+            };
+            if let Some(branch) = branch.filter(|_| !closed) {
+                branches[branch].in_service = false;
+            }
+            switches.push(SwitchRow { name: switch_name, node, target, closed });
+        }
+
+        // 功率设备接入第一个相邻母线；外部电网为平衡节点
+        let mut loads = Vec::new();
+        let mut sgens = Vec::new();
+        let mut storages = Vec::new();
+        let mut ext_grids: Vec<(String, usize)> = Vec::new();
+        for (id, device) in devices {
+            let kind = device_type(device);
+            if !["Pv", "Load", "Charger", "Storage", "ExternalGrid"].contains(&kind.as_str()) {
+                continue;
+            }
+            let Some(node) = neighbours(id).iter().find_map(|e| node_index.get(e).copied()) else {
+                errors.push(calculation_error("warning", format!("设备 {} 未连接到母线，未参与计算", id), Some(id), json!({})));
+                continue;
+            };
+            if kind == "ExternalGrid" {
+                ext_grids.push((name(id, device), node));
+                continue;
+            }
+            let p = props(device);
+            let off = p.get("on_off").and_then(number) == Some(0.0);
+            let p_kw = ["p_kw", "rated_power", "power"].iter().find_map(|k| p.get(*k).and_then(number)).unwrap_or(0.0);
+            let q_kvar = p.get("q_kvar").and_then(number).unwrap_or(0.0);
+            let (p_mw, q_mvar) = if off { (0.0, 0.0) } else { (p_kw / 1000.0, q_kvar / 1000.0) };
+            let in_service = kind != "Storage" || p.get("grid_mode").and_then(number).unwrap_or(0.0) == 0.0;
+            let row = InjectionRow { name: name(id, device), node, p_mw, q_mvar, in_service };
+            // 光伏为发电（注入为正），负载/充电桩/储能（充电为正）为消耗
+            let load = if kind == "Pv" { -Complex::new(p_mw, q_mvar) } else { Complex::new(p_mw, q_mvar) };
+            if in_service {
+                let bus = &mut buses[nodes[node].bus];
+                bus.load = bus.load + load;
+            }
+            match kind.as_str() {
+                "Pv" => sgens.push(row),
+                "Storage" => storages.push(row),
+                _ => loads.push(row),
+            }
+        }
+
+        let Some(&(_, slack_node)) = ext_grids.first() else {
+            return Err("未找到外部电网，内置求解器无法计算".to_string());
+        };
+        let slack = nodes[slack_node].bus;
+        if ext_grids.iter().any(|(_, node)| nodes[*node].bus != slack) {
+            return Err("内置求解器仅支持单一外部电网（平衡节点）".to_string());
+        }
+        Ok(Self {
+            network: RadialNetwork { buses, branches, slack, slack_vm_pu: ext_grid_vm_pu.unwrap_or(1.0) },
+            nodes,
+            lines,
+            trafos,
+            switches,
+            loads,
+            sgens,
+            storages,
+            ext_grids,
+        })
+    }
+
+    /// 按 pandapower 结果表字段输出（行以索引为键并带 name），同时检查过载与电压越限
+    fn results(&self, solution: &RadialSolution, errors: &mut Vec<Value>) -> Value {
+        let network = &self.network;
+        let voltage = |node: usize| solution.voltages[self.nodes[node].bus];
+
+        let mut buses = Map::new();
+        for (index, node) in self.nodes.iter().enumerate() {
+            let v = voltage(index);
+            let mut s = network.buses[node.bus].load;
+            if node.bus == network.slack {
+                s = s - solution.slack_power;
+            }
+            // 合并母线的净功率只计入第一个母线行
+            let first = self.nodes.iter().position(|n| n.bus == node.bus) == Some(index);
+            let s = if first && v.is_some() { s } else { Complex::default() };
+            let vm_pu = v.map(|v| v.norm());
+            buses.insert(index.to_string(), json!({
+                "name": node.name,
+                "vm_pu": vm_pu,
+                "va_degree": v.map(|v| v.arg_degrees()),
+                "p_mw": s.re,
+                "q_mvar": s.im,
+            }));
+            if let Some(vm) = vm_pu.filter(|vm| !(0.9..=1.1).contains(vm)) {
+                let severity = if !(0.85..=1.15).contains(&vm) { "error" } else { "warning" };
+                errors.push(calculation_error(
+                    severity,
+                    format!("母线 {} 电压异常: {:.3} pu", node.name, vm),
+                    Some(&node.id),
+                    json!({ "bus_index": index, "voltage_pu": vm, "vn_kv": network.buses[node.bus].vn_kv }),
+                ));
+            }
+        }
+
+        let mut lines = Map::new();
+        for (index, line) in self.lines.iter().enumerate() {
+            let flow = solution.flows[line.branch];
+            let (s_from, s_to, i_from, i_to) = flow.map_or(
+                (Complex::default(), Complex::default(), 0.0, 0.0),
+                |f| (f.s_from, f.s_to, f.i_from_ka, f.i_to_ka),
+            );
+            let i_ka = i_from.max(i_to);
+            let loading = i_ka / line.rating * 100.0;
+            lines.insert(index.to_string(), json!({
+                "name": line.name,
+                "p_from_mw": s_from.re,
+                "q_from_mvar": s_from.im,
+                "p_to_mw": s_to.re,
+                "q_to_mvar": s_to.im,
+                "pl_mw": s_from.re + s_to.re,
+                "ql_mvar": s_from.im + s_to.im,
+                "i_from_ka": i_from,
+                "i_to_ka": i_to,
+                "i_ka": i_ka,
+                "loading_percent": loading,
+            }));
+            if loading > 100.0 {
+                errors.push(calculation_error(
+                    "warning",
+                    format!("线路 {} 过载: {:.2}%", line.name, loading),
+                    Some(&line.id),
+                    json!({ "line_index": index, "loading_percent": loading, "p_from_mw": s_from.re, "p_to_mw": s_to.re }),
+                ));
+            }
+        }
+
+        let mut trafos = Map::new();
+        for (index, trafo) in self.trafos.iter().enumerate() {
+            let flow = solution.flows[trafo.branch];
+            let (s_hv, s_lv, i_hv, i_lv) = flow.map_or(
+                (Complex::default(), Complex::default(), 0.0, 0.0),
+                |f| (f.s_from, f.s_to, f.i_from_ka, f.i_to_ka),
+            );
+            let loading = s_hv.norm().max(s_lv.norm()) / trafo.rating * 100.0;
+            trafos.insert(index.to_string(), json!({
+                "name": trafo.name,
+                "p_hv_mw": s_hv.re,
+                "q_hv_mvar": s_hv.im,
+                "p_lv_mw": s_lv.re,
+                "q_lv_mvar": s_lv.im,
+                "pl_mw": s_hv.re + s_lv.re,
+                "ql_mvar": s_hv.im + s_lv.im,
+                "i_hv_ka": i_hv,
+                "i_lv_ka": i_lv,
+                "loading_percent": loading,
+            }));
+            if loading > 100.0 {
+                errors.push(calculation_error(
+                    "warning",
+                    format!("变压器 {} 过载: {:.2}%", trafo.name, loading),
+                    Some(&trafo.id),
+                    json!({ "trafo_index": index, "loading_percent": loading, "p_hv_mw": s_hv.re, "p_lv_mw": s_lv.re }),
+                ));
+            }
+        }
+
+        // 母线-线路/变压器开关取元件在开关所接母线一端的潮流，母线-母线开关不计潮流
+        let mut switches = Map::new();
+        for (index, switch) in self.switches.iter().enumerate() {
+            let side = |branch: usize, rating_ka: Option<f64>| {
+                let b = &network.branches[branch];
+                let bus = self.nodes[switch.node].bus;
+                solution.flows[branch].filter(|_| switch.closed).map(|f| {
+                    let (s, i) = if b.from == bus { (f.s_from, f.i_from_ka) } else { (f.s_to, f.i_to_ka) };
+                    (s, i, rating_ka.map_or(0.0, |r| i / r * 100.0))
+                })
+            };
+            let (s, i_ka, loading) = match switch.target {
+                SwitchTarget::Bus => None,
+                SwitchTarget::Line(line) => side(self.lines[line].branch, Some(self.lines[line].rating)),
+                SwitchTarget::Trafo(trafo) => side(self.trafos[trafo].branch, None),
+            }
+            .unwrap_or((Complex::default(), 0.0, 0.0));
+            switches.insert(index.to_string(), json!({
+                "name": switch.name,
+                "p_from_mw": s.re,
+                "q_from_mvar": s.im,
+                "i_ka": i_ka,
+                "loading_percent": loading,
+            }));
+        }
+
+        let injections = |rows: &[InjectionRow]| -> Map<String, Value> {
+            rows.iter()
+                .enumerate()
+                .map(|(index, row)| {
+                    let energized = row.in_service && voltage(row.node).is_some();
+                    let (p, q) = if energized { (row.p_mw, row.q_mvar) } else { (0.0, 0.0) };
+                    (index.to_string(), json!({ "name": row.name, "bus": row.node, "p_mw": p, "q_mvar": q }))
+                })
+                .collect()
+        };
+        let ext_grids: Map<String, Value> = self
+            .ext_grids
+            .iter()
+            .enumerate()
+            .map(|(index, (ext_name, node))| {
+                // 同一母线上的多个外部电网由第一个承担全部功率
+                let s = if index == 0 { solution.slack_power } else { Complex::default() };
+                (index.to_string(), json!({ "name": ext_name, "bus": node, "p_mw": s.re, "q_mvar": s.im }))
+            })
+            .collect();
+
+        json!({
+            "buses": buses,
+            "lines": lines,
+            "transformers": trafos,
+            "switches": switches,
+            "loads": injections(&self.loads),
+            "generators": injections(&self.sgens),
+            "storages": injections(&self.storages),
+            "ext_grids": ext_grids,
+        })
+    }
+}
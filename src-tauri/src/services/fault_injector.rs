@@ -0,0 +1,159 @@
+// 故障注入：线路/变压器停运、母线经接地电阻短路；可指定持续时间（仿真秒），到期由计算循环自动恢复。
+// 用于保护与韧性研究，故障只在本轮仿真内有效
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 故障记录保留上限
+const MAX_FAULT_HISTORY: usize = 200;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FaultType {
+    /// 线路/变压器退出运行
+    Outage,
+    /// 母线（线路取首端母线、变压器取高压侧母线）经接地电阻短路
+    ShortCircuit,
+}
+
+impl FaultType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FaultType::Outage => "outage",
+            FaultType::ShortCircuit => "short_circuit",
+        }
+    }
+}
+
+/// 生效中的故障
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActiveFault {
+    pub fault_id: u64,
+    pub device_id: String,
+    pub fault_type: FaultType,
+    /// 短路接地电阻（Ω），仅短路故障
+    #[serde(default)]
+    pub fault_resistance_ohm: Option<f64>,
+    /// 注入时刻（Unix 秒）
+    pub injected_at: f64,
+    /// 注入时的仿真时间（相对本轮开始，秒）
+    pub injected_sim_s: f64,
+    /// 持续时间（仿真秒），None 表示持续到手动清除或仿真停止
+    #[serde(default)]
+    pub duration_s: Option<f64>,
+    /// 内核计算的短路电流（ikss_ka / skss_mw，或计算失败时的 error）
+    #[serde(default)]
+    pub short_circuit: Option<serde_json::Value>,
+}
+
+/// 已结束的故障
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FaultRecord {
+    #[serde(flatten)]
+    pub fault: ActiveFault,
+    pub cleared_at: f64,
+    pub cleared_sim_s: f64,
+    /// "duration" 到期恢复 | "command" 手动清除 | "stop" 仿真停止 | "error" 恢复失败
+    pub cleared_by: String,
+}
+
+#[derive(Default)]
+pub struct FaultInjector {
+    active: HashMap<String, ActiveFault>,
+    history: Vec<FaultRecord>,
+    next_id: u64,
+    /// 最近一步的仿真时间（计算循环每步更新）
+    sim_time_s: f64,
+}
+
+impl FaultInjector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 新一轮仿真：清空故障与记录
+    pub fn reset(&mut self) {
+        *self = Self { next_id: self.next_id, ..Self::default() };
+    }
+
+    pub fn set_sim_time(&mut self, sim_time_s: f64) {
+        self.sim_time_s = sim_time_s;
+    }
+
+    pub fn sim_time(&self) -> f64 {
+        self.sim_time_s
+    }
+
+    pub fn is_active(&self, device_id: &str) -> bool {
+        self.active.contains_key(device_id)
+    }
+
+    /// 登记已在内核生效的故障
+    pub fn insert(
+        &mut self,
+        device_id: &str,
+        fault_type: FaultType,
+        fault_resistance_ohm: Option<f64>,
+        duration_s: Option<f64>,
+        short_circuit: Option<serde_json::Value>,
+        now: f64,
+    ) -> ActiveFault {
+        self.next_id += 1;
+        let fault = ActiveFault {
+            fault_id: self.next_id,
+            device_id: device_id.to_string(),
+            fault_type,
+            fault_resistance_ohm,
+            injected_at: now,
+            injected_sim_s: self.sim_time_s,
+            duration_s,
+            short_circuit,
+        };
+        self.active.insert(device_id.to_string(), fault.clone());
+        fault
+    }
+
+    /// 取出到期的故障（不写记录，恢复后由调用方 finish）
+    pub fn take_expired(&mut self, sim_time_s: f64) -> Vec<ActiveFault> {
+        let expired: Vec<String> = self
+            .active
+            .values()
+            .filter(|f| f.duration_s.is_some_and(|d| sim_time_s >= f.injected_sim_s + d))
+            .map(|f| f.device_id.clone())
+            .collect();
+        expired.into_iter().filter_map(|id| self.active.remove(&id)).collect()
+    }
+
+    pub fn remove(&mut self, device_id: &str) -> Option<ActiveFault> {
+        self.active.remove(device_id)
+    }
+
+    pub fn take_all(&mut self) -> Vec<ActiveFault> {
+        self.active.drain().map(|(_, f)| f).collect()
+    }
+
+    /// 记录故障结束
+    pub fn finish(&mut self, fault: ActiveFault, cleared_by: &str, now: f64) -> FaultRecord {
+        let record = FaultRecord {
+            fault,
+            cleared_at: now,
+            cleared_sim_s: self.sim_time_s,
+            cleared_by: cleared_by.to_string(),
+        };
+        self.history.push(record.clone());
+        if self.history.len() > MAX_FAULT_HISTORY {
+            let excess = self.history.len() - MAX_FAULT_HISTORY;
+            self.history.drain(..excess);
+        }
+        record
+    }
+
+    pub fn active(&self) -> Vec<ActiveFault> {
+        let mut faults: Vec<ActiveFault> = self.active.values().cloned().collect();
+        faults.sort_by_key(|f| f.fault_id);
+        faults
+    }
+
+    pub fn history(&self) -> Vec<FaultRecord> {
+        self.history.clone()
+    }
+}
@@ -0,0 +1,204 @@
+// 预测精度跟踪：每步对比有预测的设备（及其类型分组、自定义分组）的预测与实际功率，按仿真时间滚动窗口统计 MAPE/RMSE/偏差。
+// 预测来源：导入的预测曲线；未导入预测、且设置了经纬度的光伏使用内置晴空出力预测
+use crate::domain::forecast::{AccuracyWindow, DeviceForecast, ForecastAccuracy};
+use crate::domain::topology::{Device, DeviceType, Topology};
+use crate::services::database::Database;
+use crate::services::solar_model::{self, SolarPanelConfig};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+/// 滚动窗口（秒，仿真时间）：15 分钟、1 小时、24 小时
+pub const ACCURACY_WINDOWS_S: [f64; 3] = [900.0, 3600.0, 86400.0];
+const META_FORECAST_ACCURACY: &str = "forecast_accuracy";
+/// 实际功率绝对值低于该值（kW）的样本不计入 MAPE，避免除零放大
+const MAPE_MIN_ACTUAL_KW: f64 = 0.01;
+
+#[derive(Default)]
+struct RunningTotals {
+    samples: u64,
+    mape_samples: u64,
+    sum_abs_pct: f64,
+    sum_sq: f64,
+    sum_err: f64,
+}
+
+impl RunningTotals {
+    fn add(&mut self, forecast: f64, actual: f64) {
+        let err = forecast - actual;
+        self.samples += 1;
+        self.sum_sq += err * err;
+        self.sum_err += err;
+        if actual.abs() >= MAPE_MIN_ACTUAL_KW {
+            self.mape_samples += 1;
+            self.sum_abs_pct += (err / actual).abs() * 100.0;
+        }
+    }
+
+    fn to_window(&self, window_s: Option<f64>) -> AccuracyWindow {
+        let n = self.samples as f64;
+        AccuracyWindow {
+            window_s,
+            samples: self.samples,
+            mape_pct: (self.mape_samples > 0).then(|| self.sum_abs_pct / self.mape_samples as f64),
+            rmse_kw: (self.samples > 0).then(|| (self.sum_sq / n).sqrt()),
+            bias_kw: (self.samples > 0).then(|| self.sum_err / n),
+        }
+    }
+}
+
+/// 预测来源：导入的预测曲线 / 内置晴空出力预测；分组内来源不一致时为 mixed
+const SOURCE_IMPORTED: &str = "imported";
+const SOURCE_CLEAR_SKY: &str = "clear_sky";
+const SOURCE_MIXED: &str = "mixed";
+
+#[derive(Default)]
+struct SeriesRuntime {
+    device_ids: Vec<String>,
+    source: String,
+    /// (时间戳, 预测, 实际)，保留最长窗口内的样本
+    samples: VecDeque<(f64, f64, f64)>,
+    lifetime: RunningTotals,
+}
+
+#[derive(Default)]
+pub struct ForecastAccuracyTracker {
+    forecasts: HashMap<String, DeviceForecast>,
+    series: BTreeMap<String, SeriesRuntime>,
+}
+
+impl ForecastAccuracyTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_forecast(&mut self, forecast: DeviceForecast) {
+        self.forecasts.insert(forecast.device_id.clone(), forecast);
+        // 分组成员可能变化，统计从头开始
+        self.series.clear();
+    }
+
+    pub fn remove_forecast(&mut self, device_id: &str) -> bool {
+        let removed = self.forecasts.remove(device_id).is_some();
+        if removed {
+            self.series.clear();
+        }
+        removed
+    }
+
+    pub fn get_forecast(&self, device_id: &str) -> Option<DeviceForecast> {
+        self.forecasts.get(device_id).cloned()
+    }
+
+    /// 设备本步的预测值与来源：优先导入的预测曲线，光伏无导入预测时按晴空模型预测
+    fn predict(&self, device: &Device, timestamp: f64) -> Option<(f64, &'static str)> {
+        if let Some(forecast) = self.forecasts.get(&device.id) {
+            return forecast.value_at(timestamp).map(|p| (p, SOURCE_IMPORTED));
+        }
+        if device.device_type != DeviceType::Pv {
+            return None;
+        }
+        let config = SolarPanelConfig::from_device(device).ok()?;
+        Some((solar_model::pv_output_kw(&config, timestamp), SOURCE_CLEAR_SKY))
+    }
+
+    /// 新一轮仿真：清空统计，预测曲线保留
+    pub fn reset(&mut self) {
+        self.series.clear();
+    }
+
+    /// 记录一步：timestamp 为步末仿真时间，power 为设备本步实际有功（kW，内核原生约定）
+    pub fn record_step(&mut self, topology: &Topology, timestamp: f64, power: &HashMap<String, (f64, Option<f64>, Option<f64>)>) {
+        let mut aggregates: BTreeMap<String, (Vec<String>, &'static str, f64, f64)> = BTreeMap::new();
+        for (device_id, device) in &topology.devices {
+            let Some((predicted, source)) = self.predict(device, timestamp) else { continue };
+            let Some(actual) = power.get(device_id).and_then(|(_, p, _)| *p) else { continue };
+            let mut keys = vec![device_id.clone(), format!("type:{}", device.device_type.as_str())];
+            if let Some(group) = self.forecasts.get(device_id).and_then(|f| f.group.as_ref()) {
+                keys.push(format!("group:{}", group));
+            }
+            for key in keys {
+                let entry = aggregates.entry(key).or_insert_with(|| (Vec::new(), source, 0.0, 0.0));
+                entry.0.push(device_id.clone());
+                if entry.1 != source {
+                    entry.1 = SOURCE_MIXED;
+                }
+                entry.2 += predicted;
+                entry.3 += actual;
+            }
+        }
+        let horizon = ACCURACY_WINDOWS_S.iter().cloned().fold(0.0, f64::max);
+        for (key, (mut device_ids, source, predicted, actual)) in aggregates {
+            device_ids.sort();
+            let runtime = self.series.entry(key).or_default();
+            runtime.device_ids = device_ids;
+            runtime.source = source.to_string();
+            runtime.samples.push_back((timestamp, predicted, actual));
+            while runtime.samples.front().is_some_and(|(t, _, _)| timestamp - t > horizon) {
+                runtime.samples.pop_front();
+            }
+            runtime.lifetime.add(predicted, actual);
+        }
+    }
+
+    fn accuracy_of(key: &str, runtime: &SeriesRuntime) -> ForecastAccuracy {
+        let last = runtime.samples.back();
+        let now = last.map(|(t, _, _)| *t).unwrap_or(0.0);
+        let mut windows: Vec<AccuracyWindow> = ACCURACY_WINDOWS_S
+            .iter()
+            .map(|&w| {
+                let mut totals = RunningTotals::default();
+                for &(t, predicted, actual) in runtime.samples.iter().rev() {
+                    if now - t > w {
+                        break;
+                    }
+                    totals.add(predicted, actual);
+                }
+                totals.to_window(Some(w))
+            })
+            .collect();
+        windows.push(runtime.lifetime.to_window(None));
+        ForecastAccuracy {
+            key: key.to_string(),
+            device_ids: runtime.device_ids.clone(),
+            source: runtime.source.clone(),
+            windows,
+            last_forecast_kw: last.map(|s| s.1),
+            last_actual_kw: last.map(|s| s.2),
+            updated_at: last.map(|s| s.0),
+        }
+    }
+
+    /// 精度报告；key 为空时返回全部设备与分组
+    pub fn report(&self, key: Option<&str>) -> Vec<ForecastAccuracy> {
+        self.series
+            .iter()
+            .filter(|(k, _)| match key {
+                Some(want) => want == k.as_str(),
+                None => true,
+            })
+            .map(|(k, runtime)| Self::accuracy_of(k, runtime))
+            .collect()
+    }
+
+    /// 将当前精度报告写入仿真库（随检查点周期写入，仿真结束后仍可查询）
+    pub fn persist(&self, db: &Database) {
+        if self.series.is_empty() {
+            return;
+        }
+        if let Ok(json) = serde_json::to_string(&self.report(None)) {
+            if let Err(e) = db.set_meta_text(META_FORECAST_ACCURACY, &json) {
+                eprintln!("写入预测精度失败: {}", e);
+            }
+        }
+    }
+}
+
+/// 读取仿真库中保存的预测精度报告
+pub fn load_persisted(db: &Database) -> Result<Vec<ForecastAccuracy>, String> {
+    match db
+        .get_meta_text(META_FORECAST_ACCURACY)
+        .map_err(|e| format!("读取预测精度失败: {}", e))?
+    {
+        Some(json) => serde_json::from_str(&json).map_err(|e| format!("解析预测精度失败: {}", e)),
+        None => Ok(Vec::new()),
+    }
+}
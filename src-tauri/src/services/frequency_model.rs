@@ -0,0 +1,275 @@
+// 孤岛系统频率模型：外部电网断开（功率设备所在孤岛不含外部电网）时，按聚合惯量与储能下垂
+// 逐步积分摇摆方程得到系统频率，下一步前经 simulation.set_grid_frequency 下发内核使 P(f) 响应生效；
+// 孤岛内设备不参与潮流，功率不平衡按内核上报的本拍设定计算。多个孤岛按同一频率聚合（内核只有一个频率）。
+// 未启用 P(f) 曲线的储能按下垂系数计算一次调频出力，经 simulation.set_droop_setpoint 下发内核叠加到储能设定上
+use crate::domain::topology::{prop_f64, Device, DeviceType, Topology};
+use crate::services::setpoint_limits::power_rating;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 仿真库中系统频率行的伪设备 ID 与类型
+pub const FREQUENCY_DEVICE_ID: &str = "system_frequency";
+pub const FREQUENCY_DEVICE_TYPE: &str = "system";
+
+/// 储能默认虚拟惯量时间常数（秒，属性 inertia_s 覆盖；光伏默认无惯量）
+const DEFAULT_STORAGE_INERTIA_S: f64 = 2.0;
+/// 储能默认下垂系数（%，属性 droop_pct 覆盖，0 表示不参与一次调频）
+const DEFAULT_STORAGE_DROOP_PCT: f64 = 5.0;
+/// 负荷频率调节效应（每 1 pu 频率偏差对应的负荷有功变化，pu）
+const LOAD_DAMPING_PU: f64 = 1.0;
+/// 频率偏差上限（额定频率的比例），功率严重失衡时不再继续发散
+const MAX_DEVIATION_PU: f64 = 0.1;
+/// 频率变化超过该值（Hz）才重新下发内核
+const PUSH_TOLERANCE_HZ: f64 = 0.001;
+/// 储能下垂出力变化超过该值（kW）才重新下发内核
+const DROOP_PUSH_TOLERANCE_KW: f64 = 0.01;
+
+/// 一步频率计算结果
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct FrequencySample {
+    pub frequency_hz: f64,
+    /// 频率变化率（Hz/s）
+    pub rocof_hz_per_s: f64,
+    pub islanded: bool,
+    /// 孤岛内发用电不平衡（kW，正=发电多于用电）
+    pub imbalance_kw: f64,
+    /// 储能下垂一次调频出力（kW，正=增加出力）
+    pub droop_kw: f64,
+    pub timestamp: f64,
+}
+
+/// 孤岛划分的缓存键：设备数、连接数与断开的开关；拓扑或开关状态变化时重新划分孤岛
+#[derive(Debug, PartialEq)]
+struct IslandKey {
+    devices: usize,
+    connections: usize,
+    open_switches: Vec<String>,
+}
+
+impl IslandKey {
+    fn of(topology: &Topology) -> Self {
+        let mut open_switches: Vec<String> = topology
+            .devices
+            .values()
+            .filter(|d| {
+                d.device_type == DeviceType::Switch && d.properties.get("is_closed").and_then(|v| v.as_bool()) == Some(false)
+            })
+            .map(|d| d.id.clone())
+            .collect();
+        open_switches.sort();
+        Self { devices: topology.devices.len(), connections: topology.connections.len(), open_switches }
+    }
+}
+
+/// 待下发内核的频率状态
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrequencyCommand {
+    pub frequency_hz: f64,
+    pub islanded: bool,
+}
+
+#[derive(Default)]
+pub struct FrequencyModel {
+    /// 当前频率（Hz）；None 表示并网（未进入孤岛）
+    frequency_hz: Option<f64>,
+    nominal_hz: Option<f64>,
+    /// 已下发内核的频率状态
+    applied: Option<FrequencyCommand>,
+    pending: Option<FrequencyCommand>,
+    /// 不含外部电网的孤岛内的功率设备 ID，按 IslandKey 缓存
+    islands: Option<(IslandKey, Vec<String>)>,
+    /// 各储能本步的下垂出力（kW，正=增加出力）与已下发内核的值
+    droop_target: HashMap<String, f64>,
+    droop_applied: HashMap<String, f64>,
+}
+
+impl FrequencyModel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// 内核重启后频率状态丢失：清空已下发记录，孤岛中时重新下发
+    pub fn invalidate(&mut self) {
+        self.applied = None;
+        self.droop_applied.clear();
+        if let (Some(frequency_hz), None) = (self.frequency_hz, self.pending) {
+            self.pending = Some(FrequencyCommand { frequency_hz, islanded: true });
+        }
+    }
+
+    /// 拓扑替换后重新划分孤岛（开关状态变化由缓存键识别）
+    pub fn invalidate_islands(&mut self) {
+        self.islands = None;
+    }
+
+    /// 按本步结果推进频率：results 中 device_setpoints 为各功率设备本拍有功设定，frequency.nominal_hz 为额定频率。
+    /// 存在孤岛时返回本步频率；刚恢复并网时安排下发额定频率并返回 None
+    pub fn step(&mut self, topology: &Topology, results: &serde_json::Value, dt_s: f64, timestamp: f64) -> Option<FrequencySample> {
+        let nominal_hz = results
+            .get("frequency")
+            .and_then(|f| f.get("nominal_hz"))
+            .and_then(|v| v.as_f64())
+            .filter(|v| *v > 0.0)
+            .or(self.nominal_hz)
+            .unwrap_or(50.0);
+        self.nominal_hz = Some(nominal_hz);
+        let setpoints = results.get("device_setpoints").and_then(|v| v.as_object());
+
+        // 不含外部电网的孤岛内的功率设备（拓扑与开关状态不变时沿用上次划分）
+        let key = IslandKey::of(topology);
+        if self.islands.as_ref().is_none_or(|(cached, _)| *cached != key) {
+            let ids = topology
+                .split_islands()
+                .into_iter()
+                .filter(|island| !island.devices.values().any(|d| d.device_type == DeviceType::ExternalGrid))
+                .flat_map(|island| island.devices.into_values())
+                .filter(|d| matches!(d.device_type, DeviceType::Pv | DeviceType::Storage | DeviceType::Load | DeviceType::Charger))
+                .map(|d| d.id)
+                .collect();
+            self.islands = Some((key, ids));
+        }
+        let islanded: Vec<&Device> = self
+            .islands
+            .as_ref()
+            .map(|(_, ids)| ids.iter().filter_map(|id| topology.devices.get(id)).collect())
+            .unwrap_or_default();
+        if islanded.is_empty() {
+            self.droop_target.clear();
+            if self.frequency_hz.take().is_some() {
+                self.pending = Some(FrequencyCommand { frequency_hz: nominal_hz, islanded: false });
+            }
+            return None;
+        }
+
+        let mut imbalance_kw = 0.0;
+        let mut load_kw = 0.0;
+        // 聚合惯量 Σ 2·H·S（kW·s）与下垂增益 Σ S/R（kW/pu）
+        let mut inertia_kws = 0.0;
+        let mut droop_gain_kw = 0.0;
+        // 参与下垂的储能：(设备 ID, 下垂增益 kW/pu, 额定功率 kW)
+        let mut droop_devices: Vec<(&str, f64, f64)> = Vec::new();
+        for device in &islanded {
+            let p_kw = setpoints
+                .and_then(|s| s.get(&device.id))
+                .and_then(|v| v.as_f64())
+                .unwrap_or(0.0);
+            let rated_kw = power_rating(device).map(|r| r.max_kw).unwrap_or(0.0);
+            match device.device_type {
+                DeviceType::Pv => {
+                    imbalance_kw += p_kw;
+                    inertia_kws += 2.0 * prop_f64(&device.properties, "inertia_s").unwrap_or(0.0).max(0.0) * rated_kw;
+                }
+                DeviceType::Storage => {
+                    // 储能设定为充电正（pandapower 约定）；内核上报的设定不含下垂出力，下垂按增益单独计入
+                    imbalance_kw -= p_kw;
+                    inertia_kws += 2.0
+                        * prop_f64(&device.properties, "inertia_s").unwrap_or(DEFAULT_STORAGE_INERTIA_S).max(0.0)
+                        * rated_kw;
+                    // 已启用 P(f) 曲线的储能由内核按下发频率响应，不重复计入下垂
+                    let pf_enabled = device
+                        .properties
+                        .get("pf_response")
+                        .and_then(|c| c.get("enabled"))
+                        .and_then(|v| v.as_bool())
+                        .unwrap_or(false);
+                    let droop_pct = prop_f64(&device.properties, "droop_pct").unwrap_or(DEFAULT_STORAGE_DROOP_PCT);
+                    if !pf_enabled && droop_pct > 0.0 && rated_kw > 0.0 {
+                        let gain = rated_kw / (droop_pct / 100.0);
+                        droop_gain_kw += gain;
+                        droop_devices.push((device.id.as_str(), gain, rated_kw));
+                    }
+                }
+                _ => {
+                    imbalance_kw -= p_kw;
+                    load_kw += p_kw.max(0.0);
+                }
+            }
+        }
+
+        // 摇摆方程（偏差 x = Δf/f0）：Σ2HS · dx/dt = ΔP − K·x，K = 下垂增益 + 负荷调节效应；
+        // 步内 ΔP 不变，按指数解精确积分，步长较大时也不会振荡
+        let gain_kw = droop_gain_kw + LOAD_DAMPING_PU * load_kw;
+        let previous_hz = self.frequency_hz.unwrap_or(nominal_hz);
+        let x0 = (previous_hz - nominal_hz) / nominal_hz;
+        let x = if gain_kw > 0.0 {
+            let steady = imbalance_kw / gain_kw;
+            if inertia_kws > 0.0 {
+                steady + (x0 - steady) * (-dt_s * gain_kw / inertia_kws).exp()
+            } else {
+                steady
+            }
+        } else if inertia_kws > 0.0 {
+            x0 + imbalance_kw * dt_s / inertia_kws
+        } else if imbalance_kw.abs() > f64::EPSILON {
+            // 无惯量、无调节：功率失衡时频率直接越限
+            MAX_DEVIATION_PU * imbalance_kw.signum()
+        } else {
+            x0
+        }
+        .clamp(-MAX_DEVIATION_PU, MAX_DEVIATION_PU);
+        let frequency_hz = nominal_hz * (1.0 + x);
+        // 各储能下垂出力按自身增益计算并限制在额定功率内，下一步前下发内核
+        self.droop_target = droop_devices
+            .iter()
+            .map(|(id, gain, rated_kw)| (id.to_string(), (-gain * x).clamp(-rated_kw, *rated_kw)))
+            .collect();
+        let droop_kw = self.droop_target.values().sum();
+
+        self.frequency_hz = Some(frequency_hz);
+        let stale = self
+            .applied
+            .is_none_or(|a| !a.islanded || (a.frequency_hz - frequency_hz).abs() > PUSH_TOLERANCE_HZ);
+        if stale {
+            self.pending = Some(FrequencyCommand { frequency_hz, islanded: true });
+        }
+        Some(FrequencySample {
+            frequency_hz,
+            rocof_hz_per_s: if dt_s > 0.0 { (frequency_hz - previous_hz) / dt_s } else { 0.0 },
+            islanded: true,
+            imbalance_kw,
+            droop_kw,
+            timestamp,
+        })
+    }
+
+    /// 取出待下发内核的频率状态，下发成功后须调用 commit
+    pub fn take_pending(&mut self) -> Option<FrequencyCommand> {
+        self.pending.take()
+    }
+
+    pub fn commit(&mut self, command: FrequencyCommand) {
+        self.applied = Some(command);
+    }
+
+    /// 待下发内核的储能下垂出力 (设备 ID, kW)，None 表示撤销（恢复并网或设备离开孤岛）；下发成功后须调用 commit_droop
+    pub fn pending_droop(&self) -> Vec<(String, Option<f64>)> {
+        let mut pending: Vec<(String, Option<f64>)> = self
+            .droop_target
+            .iter()
+            .filter(|(id, kw)| self.droop_applied.get(*id).is_none_or(|a| (a - *kw).abs() > DROOP_PUSH_TOLERANCE_KW))
+            .map(|(id, kw)| (id.clone(), Some(*kw)))
+            .collect();
+        pending.extend(
+            self.droop_applied
+                .keys()
+                .filter(|id| !self.droop_target.contains_key(*id))
+                .map(|id| (id.clone(), None)),
+        );
+        pending
+    }
+
+    pub fn commit_droop(&mut self, device_id: &str, droop_kw: Option<f64>) {
+        match droop_kw {
+            Some(kw) => {
+                self.droop_applied.insert(device_id.to_string(), kw);
+            }
+            None => {
+                self.droop_applied.remove(device_id);
+            }
+        }
+    }
+}
@@ -0,0 +1,129 @@
+// 光伏逆变器电网支撑控制层：按上一步潮流结果中光伏并网点母线电压，依 volt-var 曲线计算无功设定、
+// 依 volt-watt 曲线计算有功上限，在下一步计算前经 simulation.set_grid_support_setpoint 下发内核；
+// 设定变化超过容差时才下发，避免每步 RPC
+use crate::domain::device::GridSupportConfig;
+use crate::domain::topology::Topology;
+use crate::services::result_index::ResultIndex;
+use crate::services::setpoint_limits::power_rating;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 设定变化下发容差（kW / kVar）
+const SETPOINT_TOLERANCE_KW: f64 = 0.01;
+
+/// 下发内核的电网支撑设定；两项均为 None 表示撤销
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct GridSupportSetpoint {
+    pub q_kvar: Option<f64>,
+    pub p_limit_kw: Option<f64>,
+}
+
+impl GridSupportSetpoint {
+    fn differs(&self, other: &Self) -> bool {
+        let differs = |a: Option<f64>, b: Option<f64>| match (a, b) {
+            (Some(a), Some(b)) => (a - b).abs() > SETPOINT_TOLERANCE_KW,
+            (None, None) => false,
+            _ => true,
+        };
+        differs(self.q_kvar, other.q_kvar) || differs(self.p_limit_kw, other.p_limit_kw)
+    }
+}
+
+/// 分段线性插值，曲线两端外保持端点值（与内核 Q(U) 插值一致）
+fn interpolate(points: &[(f64, f64)], x: f64) -> Option<f64> {
+    let mut points = points.to_vec();
+    points.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let (first, last) = (points.first()?, points.last()?);
+    if x <= first.0 {
+        return Some(first.1);
+    }
+    if x >= last.0 {
+        return Some(last.1);
+    }
+    points.windows(2).find(|w| w[0].0 <= x && x <= w[1].0).map(|w| {
+        let ((x0, y0), (x1, y1)) = (w[0], w[1]);
+        if x1 == x0 {
+            y0
+        } else {
+            y0 + (y1 - y0) * (x - x0) / (x1 - x0)
+        }
+    })
+}
+
+#[derive(Default)]
+pub struct GridSupportController {
+    /// 已下发内核的设定
+    applied: HashMap<String, GridSupportSetpoint>,
+    /// 按最近结果计算、待下一步下发的设定
+    pending: HashMap<String, GridSupportSetpoint>,
+}
+
+impl GridSupportController {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn reset(&mut self) {
+        self.applied.clear();
+        self.pending.clear();
+    }
+
+    /// 内核重启后设定丢失：清空已下发记录，下一次评估时全部重新下发
+    pub fn invalidate(&mut self) {
+        self.applied.clear();
+    }
+
+    /// 按本步结果计算各光伏的电网支撑设定（结果中光伏行带所接母线索引 bus）
+    pub fn observe(&mut self, topology: &Topology, index: &ResultIndex, results: &serde_json::Value) {
+        let (Some(generators), Some(buses)) = (
+            results.get("generators").and_then(|v| v.as_object()),
+            results.get("buses").and_then(|v| v.as_object()),
+        ) else {
+            return;
+        };
+        for row in generators.values() {
+            let Some(name) = row.get("name").and_then(|v| v.as_str()) else { continue };
+            let Some((device_id, device)) = index.lookup(topology, "generators", name) else {
+                continue;
+            };
+            let config = GridSupportConfig::from_properties(&device.properties);
+            let rated_kw = power_rating(device).map(|r| r.max_kw);
+            let vm_pu = row
+                .get("bus")
+                .and_then(|b| b.as_u64())
+                .and_then(|b| buses.get(&b.to_string()))
+                .and_then(|bus| bus.get("vm_pu"))
+                .and_then(|v| v.as_f64())
+                .filter(|v| v.is_finite());
+            let setpoint = match (config.is_active(), rated_kw, vm_pu) {
+                (true, Some(rated_kw), Some(vm_pu)) => {
+                    let curve = |enabled: bool, points: Vec<(f64, f64)>| {
+                        enabled.then(|| interpolate(&points, vm_pu)).flatten().map(|pct| rated_kw * pct / 100.0)
+                    };
+                    GridSupportSetpoint {
+                        q_kvar: curve(config.volt_var_enabled, config.volt_var_curve.iter().map(|p| (p.u_pu, p.q_pct)).collect()),
+                        p_limit_kw: curve(config.volt_watt_enabled, config.volt_watt_curve.iter().map(|p| (p.u_pu, p.p_pct)).collect()),
+                    }
+                }
+                // 未启用（或缺少额定功率）：曾下发过设定的须撤销
+                (false, _, _) | (true, None, _) => GridSupportSetpoint::default(),
+                // 本步无电压结果时保持原设定
+                (true, Some(_), None) => continue,
+            };
+            self.pending.insert(device_id.clone(), setpoint);
+        }
+    }
+
+    /// 取出需下发的设定（与已下发设定不同的），下发成功后须调用 commit
+    pub fn take_changes(&mut self) -> Vec<(String, GridSupportSetpoint)> {
+        let applied = &self.applied;
+        self.pending
+            .drain()
+            .filter(|(id, sp)| applied.get(id).copied().unwrap_or_default().differs(sp))
+            .collect()
+    }
+
+    pub fn commit(&mut self, device_id: String, setpoint: GridSupportSetpoint) {
+        self.applied.insert(device_id, setpoint);
+    }
+}
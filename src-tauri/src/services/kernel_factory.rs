@@ -1,14 +1,70 @@
 // 内核工厂（计算内核和AI内核）
-// Rust 端主要负责内核选择和配置管理
-// 具体的内核实现在 Python 中
+// Rust 端主要负责内核选择和配置管理：每个仿真实例可选用不同的潮流求解内核，随 simulation.set_topology 下发
+// 具体的内核实现在 Python 中（能力由 power.list_kernels 报告）；大规模计算时可额外拉起多个内核进程并行求解
 
+use crate::services::fallback_kernel::FALLBACK_KERNEL_ID;
+use crate::services::python_bridge::{KernelClient, PythonBridge};
+use crate::services::settings::SettingsStore;
 use serde::{Deserialize, Serialize};
+use tauri::Manager;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// 潮流求解内核
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum KernelType {
+    /// pandapower 平衡（正序）交流潮流
+    #[default]
     Pandapower,
-    PyPSA,
-    GridCal,
+    /// pandapower 三相不平衡潮流
+    #[serde(rename = "pandapower_3ph")]
+    Pandapower3Ph,
+    /// 简化直流潮流（电压幅值恒为 1.0 pu，不计无功）
+    Simplified,
+    /// 内置辐射网前推回代（Python 内核不可用时的降级内核，自动启用，不可手动选择）
+    #[serde(rename = "builtin_radial")]
+    BuiltinRadial,
+}
+
+impl KernelType {
+    /// 内核标识（与 Python 端 PowerKernelFactory 一致）
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KernelType::Pandapower => "pandapower",
+            KernelType::Pandapower3Ph => "pandapower_3ph",
+            KernelType::Simplified => "simplified",
+            KernelType::BuiltinRadial => FALLBACK_KERNEL_ID,
+        }
+    }
+}
+
+/// 内核能力描述（Python 端报告，含尚未实现或依赖缺失的内核）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KernelCapabilities {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub available: bool,
+    #[serde(default)]
+    pub unavailable_reason: Option<String>,
+    /// 支持三相不平衡潮流
+    #[serde(default)]
+    pub unbalanced: bool,
+    /// 求解无功与电压幅值
+    #[serde(default)]
+    pub reactive_power: bool,
+    /// 生效的求解参数（SolverOptions 字段名）
+    #[serde(default)]
+    pub solver_options: Vec<String>,
+    #[serde(default)]
+    pub features: Vec<String>,
+}
+
+/// 内核列表与仿真实例当前选用的内核
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PowerKernelCatalog {
+    pub kernels: Vec<KernelCapabilities>,
+    pub selected: KernelType,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,11 +77,26 @@ pub enum AIKernelType {
 pub struct KernelFactory;
 
 impl KernelFactory {
-    pub fn create_power_kernel(kernel_type: KernelType) -> Result<String, String> {
-        match kernel_type {
-            KernelType::Pandapower => Ok("pandapower".to_string()),
-            KernelType::PyPSA => Ok("pypsa".to_string()),
-            KernelType::GridCal => Ok("gridcal".to_string()),
+    /// 查询内核进程中各计算内核的能力
+    pub async fn query_capabilities(kernel: &KernelClient) -> Result<Vec<KernelCapabilities>, String> {
+        let result = kernel
+            .call("power.list_kernels", serde_json::json!({}))
+            .await
+            .map_err(|e| format!("查询计算内核失败: {}", e))?;
+        let kernels = result.get("kernels").cloned().ok_or("内核未返回计算内核列表")?;
+        serde_json::from_value(kernels).map_err(|e| format!("解析计算内核列表失败: {}", e))
+    }
+
+    /// 校验内核可用（切换前调用，避免启动时才失败）
+    pub async fn ensure_available(kernel: &KernelClient, kernel_type: KernelType) -> Result<(), String> {
+        let kernels = Self::query_capabilities(kernel).await?;
+        match kernels.iter().find(|k| k.id == kernel_type.as_str()) {
+            Some(k) if k.available => Ok(()),
+            Some(k) => Err(format!(
+                "计算内核不可用: {}",
+                k.unavailable_reason.as_deref().unwrap_or(&k.name)
+            )),
+            None => Err(format!("内核进程不支持计算内核: {}", kernel_type.as_str())),
         }
     }
 
@@ -36,4 +107,32 @@ impl KernelFactory {
             AIKernelType::OpenAIGym => Ok("gym".to_string()),
         }
     }
+
+    /// 拉起一个独立的 Python 内核进程并等待其响应 ping（与主内核相同的启动方式）
+    pub async fn spawn_power_kernel_process(app_handle: Option<&tauri::AppHandle>) -> Result<PythonBridge, String> {
+        let mut bridge = PythonBridge::new();
+        // 并行求解的进程沿用设置中的超时表与结果传输方式
+        if let Some(settings) = app_handle.and_then(|app| app.try_state::<SettingsStore>()) {
+            bridge.client().set_rpc_config(settings.kernel_rpc());
+            bridge.client().set_result_transfer(settings.result_transfer());
+        }
+        bridge
+            .start(app_handle)
+            .await
+            .map_err(|e| format!("启动内核进程失败: {}", e))?;
+        let mut retries = 10;
+        loop {
+            match bridge.call("ping", serde_json::json!({})).await {
+                Ok(_) => return Ok(bridge),
+                Err(e) => {
+                    retries -= 1;
+                    if retries == 0 {
+                        let _ = bridge.stop().await;
+                        return Err(format!("内核进程未就绪: {}", e));
+                    }
+                    tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,186 @@
+// Python 内核日志：内核进程的 stderr 按级别过滤后写入 exe 同目录的 python-kernel.log，
+// 超过上限时滚动为 python-kernel.log.1、.2…（只保留设定个数）；诊断面板读取末尾若干行。
+// 主内核与并行求解的内核进程共用一个实例，写入在锁内进行，各进程的行不会交错
+use crate::domain::simulation::{KernelLogConfig, KernelLogLevel};
+use serde::Serialize;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex as StdMutex;
+
+/// 诊断面板单次读取的最大行数
+pub const MAX_TAIL_LINES: usize = 5000;
+
+/// 日志文件路径（exe 同目录）
+pub fn log_path() -> PathBuf {
+    std::env::current_exe()
+        .ok()
+        .and_then(|p| p.parent().map(|p| p.join("python-kernel.log")))
+        .unwrap_or_else(|| PathBuf::from("python-kernel.log"))
+}
+
+/// 日志末尾若干行（可跨越最近一个旧文件）
+#[derive(Debug, Clone, Serialize)]
+pub struct KernelLogTail {
+    pub path: String,
+    /// 当前日志文件大小（字节）
+    pub size_bytes: u64,
+    pub lines: Vec<String>,
+}
+
+struct LogFile {
+    config: KernelLogConfig,
+    file: Option<File>,
+    size: u64,
+}
+
+pub struct KernelLog {
+    path: PathBuf,
+    state: StdMutex<LogFile>,
+}
+
+impl KernelLog {
+    pub fn new(config: KernelLogConfig) -> Self {
+        let path = log_path();
+        let size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        Self {
+            path,
+            state: StdMutex::new(LogFile { config, file: None, size }),
+        }
+    }
+
+    pub fn level(&self) -> KernelLogLevel {
+        self.state.lock().unwrap().config.level
+    }
+
+    /// 更新级别与滚动上限；当前文件已超过新上限时立即滚动
+    pub fn configure(&self, config: KernelLogConfig) {
+        let mut state = self.state.lock().unwrap();
+        state.config = config;
+        if state.size > state.config.max_size_kb * 1024 {
+            self.rotate_locked(&mut state);
+        }
+    }
+
+    /// 内核进程启动标记（不受级别过滤）
+    pub fn mark_started(&self) {
+        let line = format!("\n=== Python Kernel Started at {:?} ===", std::time::SystemTime::now());
+        let mut state = self.state.lock().unwrap();
+        self.write_locked(&mut state, &line);
+    }
+
+    /// 写入一行 stderr 输出；低于设定级别的行丢弃
+    pub fn append(&self, level: KernelLogLevel, line: &str) {
+        let mut state = self.state.lock().unwrap();
+        if level < state.config.level {
+            return;
+        }
+        self.write_locked(&mut state, line);
+    }
+
+    /// 立即滚动：当前文件改名为 .1（旧文件依次后移），之后写入新文件
+    pub fn rotate(&self) {
+        let mut state = self.state.lock().unwrap();
+        self.rotate_locked(&mut state);
+    }
+
+    /// 读取末尾 lines 行；当前文件不足时从最近的旧文件补齐
+    pub fn tail(&self, lines: usize) -> Result<KernelLogTail, String> {
+        let lines = lines.clamp(1, MAX_TAIL_LINES);
+        // 持锁读取，避免读到滚动过程中的文件
+        let state = self.state.lock().unwrap();
+        let mut out = read_lines(&self.path)?;
+        if out.len() < lines && state.config.max_backups > 0 {
+            let mut previous = read_lines(&backup_path(&self.path, 1))?;
+            previous.append(&mut out);
+            out = previous;
+        }
+        let start = out.len().saturating_sub(lines);
+        Ok(KernelLogTail {
+            path: self.path.display().to_string(),
+            size_bytes: state.size,
+            lines: out.split_off(start),
+        })
+    }
+
+    fn write_locked(&self, state: &mut LogFile, line: &str) {
+        let len = line.len() as u64 + 1;
+        if state.size > 0 && state.size + len > state.config.max_size_kb * 1024 {
+            self.rotate_locked(state);
+        }
+        if state.file.is_none() {
+            state.file = OpenOptions::new().create(true).append(true).open(&self.path).ok();
+        }
+        if let Some(file) = state.file.as_mut() {
+            if writeln!(file, "{}", line).and_then(|_| file.flush()).is_ok() {
+                state.size += len;
+            }
+        }
+    }
+
+    fn rotate_locked(&self, state: &mut LogFile) {
+        // Windows 上须先关闭文件才能改名
+        state.file = None;
+        let backups = state.config.max_backups;
+        let result = if backups == 0 {
+            std::fs::remove_file(&self.path)
+        } else {
+            let _ = std::fs::remove_file(backup_path(&self.path, backups));
+            for index in (1..backups).rev() {
+                let _ = std::fs::rename(backup_path(&self.path, index), backup_path(&self.path, index + 1));
+            }
+            std::fs::rename(&self.path, backup_path(&self.path, 1))
+        };
+        match result {
+            Ok(()) => state.size = 0,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => state.size = 0,
+            Err(e) => eprintln!("滚动内核日志失败: {}", e),
+        }
+    }
+}
+
+fn backup_path(path: &Path, index: u32) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", index));
+    PathBuf::from(name)
+}
+
+fn read_lines(path: &Path) -> Result<Vec<String>, String> {
+    match std::fs::read(path) {
+        Ok(bytes) => Ok(String::from_utf8_lossy(&bytes).lines().map(str::to_string).collect()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(format!("读取内核日志失败: {}", e)),
+    }
+}
+
+/// 判定 stderr 行的级别：logging 输出带 [级别] 前缀；Python 异常回溯（Traceback 至异常信息行）为 error；其余 print 输出为 info
+#[derive(Default)]
+pub struct StderrClassifier {
+    in_traceback: bool,
+}
+
+impl StderrClassifier {
+    pub fn level(&mut self, line: &str) -> KernelLogLevel {
+        if line.starts_with("Traceback (most recent call last)") {
+            self.in_traceback = true;
+            return KernelLogLevel::Error;
+        }
+        if self.in_traceback {
+            // 回溯的栈帧行缩进，第一个非缩进行为异常信息，回溯到此结束
+            if !line.starts_with(char::is_whitespace) {
+                self.in_traceback = false;
+            }
+            return KernelLogLevel::Error;
+        }
+        let prefixed = |prefix: &str| line.starts_with(prefix);
+        if prefixed("[DEBUG]") {
+            KernelLogLevel::Debug
+        } else if prefixed("[WARNING]") {
+            KernelLogLevel::Warning
+        } else if prefixed("[ERROR]") || prefixed("[CRITICAL]") {
+            KernelLogLevel::Error
+        } else {
+            KernelLogLevel::Info
+        }
+    }
+}
@@ -0,0 +1,149 @@
+// 多内核进程池：大规模网络按孤岛拆分、或蒙特卡洛/多方案快照等相互独立的潮流任务分发到多个内核并行求解
+// 池中内核与主仿真内核相互独立，不持有仿真状态，只执行无状态的快照类请求；进程上限与默认并行度由设置配置
+use crate::domain::simulation::KernelPoolConfig;
+use crate::services::kernel_factory::KernelFactory;
+use crate::services::python_bridge::PythonBridge;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex as StdMutex};
+use tauri::AppHandle;
+use tokio::sync::Mutex;
+
+/// 可配置的内核进程上限的最大值（每个进程独立加载 pandapower，内存占用较大）
+pub const MAX_POOL_KERNELS: usize = 8;
+
+/// 内核池中的一个任务：JSON-RPC 方法与参数
+#[derive(Debug, Clone)]
+pub struct KernelJob {
+    pub method: String,
+    pub params: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KernelPoolStatus {
+    pub kernels: usize,
+    pub max_kernels: usize,
+    /// 累计完成的任务数
+    pub completed_jobs: u64,
+}
+
+#[derive(Default)]
+pub struct KernelPool {
+    workers: Mutex<Vec<Arc<Mutex<PythonBridge>>>>,
+    completed_jobs: Arc<StdMutex<u64>>,
+    config: StdMutex<KernelPoolConfig>,
+}
+
+impl KernelPool {
+    pub fn new(config: KernelPoolConfig) -> Self {
+        Self { config: StdMutex::new(config), ..Self::default() }
+    }
+
+    /// 更新进程上限与默认并行度；已有内核超过新上限时关闭多余的进程
+    pub async fn configure(&self, config: KernelPoolConfig) {
+        let max_kernels = config.max_kernels;
+        *self.config.lock().unwrap() = config;
+        let extra: Vec<Arc<Mutex<PythonBridge>>> = {
+            let mut workers = self.workers.lock().await;
+            let keep = workers.len().min(max_kernels);
+            workers.drain(keep..).collect()
+        };
+        for worker in extra {
+            if let Err(e) = worker.lock().await.shutdown().await {
+                eprintln!("关闭并行求解内核失败: {}", e);
+            }
+        }
+    }
+
+    /// 实际使用的内核数：requested 为空时取设置中的默认并行度，均不超过进程上限
+    pub fn parallelism(&self, requested: Option<usize>) -> usize {
+        let config = self.config.lock().unwrap();
+        requested.unwrap_or_else(|| config.parallelism()).clamp(1, config.max_kernels)
+    }
+
+    /// 保证池中至少有 count 个内核（按需增补，不超过上限），返回可用的内核
+    async fn ensure_workers(&self, app: &AppHandle, count: usize) -> Result<Vec<Arc<Mutex<PythonBridge>>>, String> {
+        let count = count.clamp(1, self.config.lock().unwrap().max_kernels);
+        let mut workers = self.workers.lock().await;
+        while workers.len() < count {
+            match KernelFactory::spawn_power_kernel_process(Some(app)).await {
+                Ok(bridge) => workers.push(Arc::new(Mutex::new(bridge))),
+                // 已有内核时降级使用现有数量，一个都没有才报错
+                Err(e) if workers.is_empty() => return Err(e),
+                Err(e) => {
+                    eprintln!("内核池扩容失败，使用现有 {} 个内核: {}", workers.len(), e);
+                    break;
+                }
+            }
+        }
+        Ok(workers.iter().take(count).cloned().collect())
+    }
+
+    /// 将任务分发到最多 parallelism 个内核并行执行，结果按任务顺序返回
+    pub async fn run_jobs(
+        &self,
+        app: &AppHandle,
+        parallelism: usize,
+        jobs: Vec<KernelJob>,
+    ) -> Result<Vec<Result<serde_json::Value, String>>, String> {
+        if jobs.is_empty() {
+            return Ok(Vec::new());
+        }
+        let workers = self.ensure_workers(app, parallelism.min(jobs.len())).await?;
+        let total = jobs.len();
+        let queue: Arc<StdMutex<VecDeque<(usize, KernelJob)>>> =
+            Arc::new(StdMutex::new(jobs.into_iter().enumerate().collect()));
+        let mut handles = Vec::with_capacity(workers.len());
+        for worker in workers {
+            let queue = queue.clone();
+            let completed = self.completed_jobs.clone();
+            handles.push(tokio::spawn(async move {
+                let mut done = Vec::new();
+                loop {
+                    let next = queue.lock().unwrap().pop_front();
+                    let Some((index, job)) = next else { break };
+                    let result = worker
+                        .lock()
+                        .await
+                        .call(&job.method, job.params)
+                        .await
+                        .map_err(|e| format!("内核任务失败: {}", e));
+                    *completed.lock().unwrap() += 1;
+                    done.push((index, result));
+                }
+                done
+            }));
+        }
+        let mut results: Vec<Option<Result<serde_json::Value, String>>> = (0..total).map(|_| None).collect();
+        for handle in handles {
+            let done = handle.await.map_err(|e| format!("内核任务异常退出: {}", e))?;
+            for (index, result) in done {
+                results[index] = Some(result);
+            }
+        }
+        Ok(results
+            .into_iter()
+            .map(|r| r.unwrap_or_else(|| Err("任务未执行".to_string())))
+            .collect())
+    }
+
+    pub async fn status(&self) -> KernelPoolStatus {
+        KernelPoolStatus {
+            kernels: self.workers.lock().await.len(),
+            max_kernels: self.config.lock().unwrap().max_kernels,
+            completed_jobs: *self.completed_jobs.lock().unwrap(),
+        }
+    }
+
+    /// 关闭池中全部内核进程（kernel.shutdown 后限时强制结束），返回关闭数量
+    pub async fn shutdown(&self) -> usize {
+        let mut workers = self.workers.lock().await;
+        let n = workers.len();
+        for worker in workers.drain(..) {
+            if let Err(e) = worker.lock().await.shutdown().await {
+                eprintln!("关闭并行求解内核失败: {}", e);
+            }
+        }
+        n
+    }
+}
@@ -0,0 +1,276 @@
+// 内核模型一致性校验：读取内核当前网络的元件表，与 Rust 端拓扑逐项比对（名称、数量、所连母线、开关状态）
+// 用于热编辑、内核重连或重启后确认内核中的网络与界面拓扑一致
+use crate::domain::topology::{DeviceType, Topology};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum KernelSyncIssue {
+    /// 拓扑中有、内核中没有
+    Missing,
+    /// 内核中有、拓扑中没有（适配器自动补建的默认母线/线路也归为此类，级别为 warning）
+    Extra,
+    NameMismatch,
+    ConnectionMismatch,
+    StateMismatch,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KernelSyncMismatch {
+    pub issue: KernelSyncIssue,
+    /// "error" | "warning"
+    pub severity: String,
+    pub table: String,
+    pub device_id: Option<String>,
+    pub kernel_index: Option<i64>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KernelTableCount {
+    pub table: String,
+    pub expected: usize,
+    pub actual: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KernelSyncReport {
+    /// 无 error 级差异
+    pub in_sync: bool,
+    pub network_built: bool,
+    pub checked_at: f64,
+    pub table_counts: Vec<KernelTableCount>,
+    pub mismatches: Vec<KernelSyncMismatch>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct KernelRow {
+    index: i64,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    buses: Vec<String>,
+    #[serde(default)]
+    closed: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct KernelTables {
+    #[serde(default)]
+    network_built: bool,
+    #[serde(default)]
+    tables: HashMap<String, Vec<KernelRow>>,
+    #[serde(default)]
+    bus_map: HashMap<String, i64>,
+    #[serde(default)]
+    device_map: HashMap<String, HashMap<String, i64>>,
+}
+
+/// 设备类型对应的 pandapower 表与适配器映射键（母线用 bus_map，外部电网无映射按名称匹配）
+fn kernel_table(device_type: &DeviceType) -> Option<(&'static str, Option<&'static str>)> {
+    match device_type {
+        DeviceType::Node => Some(("bus", None)),
+        DeviceType::Line => Some(("line", Some("lines"))),
+        DeviceType::Transformer => Some(("trafo", Some("transformers"))),
+        DeviceType::Switch => Some(("switch", Some("switches"))),
+        DeviceType::Pv => Some(("sgen", Some("generators"))),
+        DeviceType::Load | DeviceType::Charger => Some(("load", Some("loads"))),
+        DeviceType::Storage => Some(("storage", Some("storages"))),
+        DeviceType::ExternalGrid => Some(("ext_grid", None)),
+        DeviceType::Meter => None,
+    }
+}
+
+fn mismatch(
+    issue: KernelSyncIssue,
+    severity: &str,
+    table: &str,
+    device_id: Option<&str>,
+    kernel_index: Option<i64>,
+    message: String,
+) -> KernelSyncMismatch {
+    KernelSyncMismatch {
+        issue,
+        severity: severity.to_string(),
+        table: table.to_string(),
+        device_id: device_id.map(|s| s.to_string()),
+        kernel_index,
+        message,
+    }
+}
+
+/// 比对拓扑与内核元件表（kernel_tables 为 simulation.get_element_tables 的返回值）
+pub fn verify(topology: &Topology, kernel_tables: &serde_json::Value, checked_at: f64) -> Result<KernelSyncReport, String> {
+    let kernel: KernelTables =
+        serde_json::from_value(kernel_tables.clone()).map_err(|e| format!("解析内核元件表失败: {}", e))?;
+    if !kernel.network_built {
+        return Ok(KernelSyncReport {
+            in_sync: false,
+            network_built: false,
+            checked_at,
+            table_counts: Vec::new(),
+            mismatches: vec![mismatch(
+                KernelSyncIssue::Missing,
+                "error",
+                "bus",
+                None,
+                None,
+                "内核尚未构建网络（请先启动仿真或预加载拓扑）".to_string(),
+            )],
+        });
+    }
+
+    // 每台设备通过连接关系直接相连的母线名称
+    let mut neighbor_buses: HashMap<&str, BTreeSet<&str>> = HashMap::new();
+    for conn in topology.connections.values() {
+        for (a, b) in [(&conn.from_device_id, &conn.to_device_id), (&conn.to_device_id, &conn.from_device_id)] {
+            if let Some(node) = topology.devices.get(b).filter(|d| d.device_type == DeviceType::Node) {
+                neighbor_buses.entry(a.as_str()).or_default().insert(node.name.as_str());
+            }
+        }
+    }
+
+    let rows_by_index: HashMap<&str, HashMap<i64, &KernelRow>> = kernel
+        .tables
+        .iter()
+        .map(|(t, rows)| (t.as_str(), rows.iter().map(|r| (r.index, r)).collect()))
+        .collect();
+    let mut claimed: HashMap<&str, HashSet<i64>> = HashMap::new();
+    let mut expected_counts: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut mismatches = Vec::new();
+
+    let mut devices: Vec<_> = topology.devices.values().collect();
+    devices.sort_by(|a, b| a.id.cmp(&b.id));
+    for device in devices {
+        let Some((table, map_key)) = kernel_table(&device.device_type) else { continue };
+        *expected_counts.entry(table).or_insert(0) += 1;
+        let rows = rows_by_index.get(table);
+        // 定位内核元件：母线查 bus_map，其余查适配器映射，外部电网按名称
+        let index = match (device.device_type == DeviceType::Node, map_key) {
+            (true, _) => kernel.bus_map.get(&device.id).copied(),
+            (false, Some(key)) => kernel.device_map.get(key).and_then(|m| m.get(&device.id)).copied(),
+            (false, None) => rows.and_then(|r| {
+                r.values()
+                    .find(|row| row.name == device.name && !claimed.get(table).is_some_and(|c| c.contains(&row.index)))
+                    .map(|row| row.index)
+            }),
+        };
+        let Some(row) = index.and_then(|i| rows.and_then(|r| r.get(&i))) else {
+            mismatches.push(mismatch(
+                KernelSyncIssue::Missing,
+                "error",
+                table,
+                Some(&device.id),
+                index,
+                format!("设备 {}（{}）在内核 {} 表中不存在", device.name, device.id, table),
+            ));
+            continue;
+        };
+        claimed.entry(table).or_default().insert(row.index);
+
+        if row.name != device.name {
+            mismatches.push(mismatch(
+                KernelSyncIssue::NameMismatch,
+                "error",
+                table,
+                Some(&device.id),
+                Some(row.index),
+                format!("设备 {} 名称不一致：拓扑 {}，内核 {}", device.id, device.name, row.name),
+            ));
+        }
+
+        if device.device_type != DeviceType::Node {
+            let expected = neighbor_buses.get(device.id.as_str()).cloned().unwrap_or_default();
+            let actual: BTreeSet<&str> = row.buses.iter().map(|s| s.as_str()).collect();
+            // 线路/变压器两端须完全一致；开关只有一端接母线时内核仅记录该母线；
+            // 功率设备取第一个相邻母线，未连接时适配器补建默认母线 "{id}_bus"
+            let connected = match device.device_type {
+                DeviceType::Line | DeviceType::Transformer => actual == expected,
+                DeviceType::Switch => actual.is_subset(&expected),
+                _ if expected.is_empty() => {
+                    let default_bus = format!("{}_bus", device.id);
+                    actual.len() == 1 && actual.contains(default_bus.as_str())
+                }
+                _ => actual.len() == 1 && actual.is_subset(&expected),
+            };
+            if !connected {
+                mismatches.push(mismatch(
+                    KernelSyncIssue::ConnectionMismatch,
+                    "error",
+                    table,
+                    Some(&device.id),
+                    Some(row.index),
+                    format!(
+                        "设备 {} 所连母线不一致：拓扑 [{}]，内核 [{}]",
+                        device.name,
+                        expected.iter().cloned().collect::<Vec<_>>().join(", "),
+                        actual.iter().cloned().collect::<Vec<_>>().join(", ")
+                    ),
+                ));
+            }
+        }
+
+        if device.device_type == DeviceType::Switch {
+            let expected_closed = device.properties.get("is_closed").and_then(|v| v.as_bool()).unwrap_or(true);
+            if row.closed.is_some_and(|c| c != expected_closed) {
+                mismatches.push(mismatch(
+                    KernelSyncIssue::StateMismatch,
+                    "error",
+                    table,
+                    Some(&device.id),
+                    Some(row.index),
+                    format!(
+                        "开关 {} 状态不一致：拓扑{}，内核{}",
+                        device.name,
+                        if expected_closed { "闭合" } else { "断开" },
+                        if expected_closed { "断开" } else { "闭合" }
+                    ),
+                ));
+            }
+        }
+    }
+
+    // 内核中未被任何拓扑设备占用的元件（适配器补建的默认母线/线路为 warning）
+    let mut tables: Vec<&String> = kernel.tables.keys().collect();
+    tables.sort();
+    for table in tables {
+        for row in &kernel.tables[table] {
+            if claimed.get(table.as_str()).is_some_and(|c| c.contains(&row.index)) {
+                continue;
+            }
+            let auto_created = (table == "bus" && row.name.ends_with("_bus")) || (table == "line" && row.name.starts_with("line_"));
+            mismatches.push(mismatch(
+                KernelSyncIssue::Extra,
+                if auto_created { "warning" } else { "error" },
+                table,
+                None,
+                Some(row.index),
+                if auto_created {
+                    format!("内核 {} 表中的 {} 为适配器自动补建（设备未连接或连接无对应设备）", table, row.name)
+                } else {
+                    format!("内核 {} 表中的 {} 在拓扑中不存在", table, row.name)
+                },
+            ));
+        }
+    }
+
+    let mut all_tables: BTreeSet<&str> = expected_counts.keys().copied().collect();
+    all_tables.extend(kernel.tables.keys().map(|s| s.as_str()));
+    let table_counts = all_tables
+        .into_iter()
+        .map(|t| KernelTableCount {
+            table: t.to_string(),
+            expected: expected_counts.get(t).copied().unwrap_or(0),
+            actual: kernel.tables.get(t).map(|r| r.len()).unwrap_or(0),
+        })
+        .collect();
+
+    Ok(KernelSyncReport {
+        in_sync: !mismatches.iter().any(|m| m.severity == "error"),
+        network_built: true,
+        checked_at,
+        table_counts,
+        mismatches,
+    })
+}
@@ -0,0 +1,470 @@
+// 内核传输：stdio 管道，或在 TCP / WebSocket 端点上监听的远程 Python 内核（容器或远程主机，见 python-kernel/transport.py）。
+// 消息编码启动时经 kernel.negotiate 协商（协商本身固定为 JSON）：JSON 时管道与 TCP 每行一条、WebSocket 每个文本帧一条；
+// MessagePack 时管道与 TCP 为 4 字节大端长度前缀 + 消息体、WebSocket 每个二进制帧一条。与 stdio 一致使用同步 I/O 与读取线程
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/// 设置项未配置时读取的环境变量（优先于设置项）
+pub const KERNEL_ENDPOINT_ENV: &str = "PYTHON_KERNEL_ENDPOINT";
+/// 远程内核的共享令牌（与内核端 --token / PYTHON_KERNEL_TOKEN 一致），随 kernel.negotiate 发送
+pub const KERNEL_TOKEN_ENV: &str = "PYTHON_KERNEL_TOKEN";
+/// 设为 json 时不协商二进制编码（排查协议问题时使用）
+pub const KERNEL_ENCODING_ENV: &str = "PYTHON_KERNEL_ENCODING";
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// 单条消息上限（大型网络的每步结果）
+const MAX_MESSAGE_BYTES: u64 = 256 * 1024 * 1024;
+
+/// 消息编码
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WireEncoding {
+    Json,
+    MessagePack,
+}
+
+impl WireEncoding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::MessagePack => "msgpack",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "json" => Some(Self::Json),
+            "msgpack" => Some(Self::MessagePack),
+            _ => None,
+        }
+    }
+
+    /// 协商时提供的编码（按优先级）
+    pub fn offered() -> Vec<Self> {
+        match std::env::var(KERNEL_ENCODING_ENV).ok().as_deref() {
+            Some("json") => vec![Self::Json],
+            _ => vec![Self::MessagePack, Self::Json],
+        }
+    }
+
+    pub fn encode(&self, message: &serde_json::Value) -> io::Result<Vec<u8>> {
+        match self {
+            Self::Json => serde_json::to_vec(message).map_err(io::Error::other),
+            Self::MessagePack => {
+                let mut body = Vec::new();
+                rmpv::encode::write_value(&mut body, &json_to_msgpack(message)).map_err(io::Error::other)?;
+                Ok(body)
+            }
+        }
+    }
+
+    pub fn decode(&self, body: &[u8]) -> Result<serde_json::Value, String> {
+        match self {
+            Self::Json => serde_json::from_slice(body).map_err(|e| e.to_string()),
+            Self::MessagePack => rmpv::decode::read_value(&mut &body[..])
+                .map(msgpack_to_json)
+                .map_err(|e| e.to_string()),
+        }
+    }
+}
+
+fn json_to_msgpack(value: &serde_json::Value) -> rmpv::Value {
+    match value {
+        serde_json::Value::Null => rmpv::Value::Nil,
+        serde_json::Value::Bool(b) => rmpv::Value::Boolean(*b),
+        serde_json::Value::Number(n) => {
+            if let Some(u) = n.as_u64() {
+                rmpv::Value::from(u)
+            } else if let Some(i) = n.as_i64() {
+                rmpv::Value::from(i)
+            } else {
+                rmpv::Value::F64(n.as_f64().unwrap_or(f64::NAN))
+            }
+        }
+        serde_json::Value::String(s) => rmpv::Value::from(s.as_str()),
+        serde_json::Value::Array(items) => rmpv::Value::Array(items.iter().map(json_to_msgpack).collect()),
+        serde_json::Value::Object(map) => rmpv::Value::Map(
+            map.iter()
+                .map(|(k, v)| (rmpv::Value::from(k.as_str()), json_to_msgpack(v)))
+                .collect(),
+        ),
+    }
+}
+
+/// 与 Python json.dumps 的结果保持一致：非字符串键转为字符串（如 pandas 整数索引），NaN/Inf 转为 null
+fn msgpack_to_json(value: rmpv::Value) -> serde_json::Value {
+    match value {
+        rmpv::Value::Nil | rmpv::Value::Ext(..) => serde_json::Value::Null,
+        rmpv::Value::Boolean(b) => serde_json::Value::Bool(b),
+        rmpv::Value::Integer(i) => match (i.as_u64(), i.as_i64()) {
+            (Some(u), _) => serde_json::Value::from(u),
+            (_, Some(v)) => serde_json::Value::from(v),
+            _ => serde_json::Value::Null,
+        },
+        rmpv::Value::F32(f) => serde_json::Value::from(f as f64),
+        rmpv::Value::F64(f) => serde_json::Value::from(f),
+        rmpv::Value::String(s) => serde_json::Value::String(s.into_str().unwrap_or_default()),
+        rmpv::Value::Binary(bytes) => serde_json::Value::Array(bytes.into_iter().map(serde_json::Value::from).collect()),
+        rmpv::Value::Array(items) => serde_json::Value::Array(items.into_iter().map(msgpack_to_json).collect()),
+        rmpv::Value::Map(entries) => serde_json::Value::Object(
+            entries
+                .into_iter()
+                .map(|(k, v)| {
+                    let key = match k {
+                        rmpv::Value::String(s) => s.into_str().unwrap_or_default(),
+                        rmpv::Value::Nil => "null".to_string(),
+                        other => other.to_string(),
+                    };
+                    (key, msgpack_to_json(v))
+                })
+                .collect(),
+        ),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum KernelEndpoint {
+    /// tcp://主机:端口
+    Tcp { host: String, port: u16 },
+    /// ws://主机:端口/路径
+    WebSocket { host: String, port: u16, path: String },
+}
+
+impl KernelEndpoint {
+    pub fn parse(url: &str) -> Result<Self, String> {
+        let url = url.trim();
+        let invalid = || format!("内核端点格式无效（应为 tcp://主机:端口 或 ws://主机:端口/路径）: {}", url);
+        let (scheme, rest) = url.split_once("://").ok_or_else(invalid)?;
+        let (authority, path) = match rest.find('/') {
+            Some(i) => (&rest[..i], &rest[i..]),
+            None => (rest, "/"),
+        };
+        let (host, port) = authority.rsplit_once(':').ok_or_else(invalid)?;
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        let port: u16 = port.parse().map_err(|_| invalid())?;
+        if host.is_empty() || port == 0 {
+            return Err(invalid());
+        }
+        match scheme.to_ascii_lowercase().as_str() {
+            "tcp" => Ok(Self::Tcp { host: host.to_string(), port }),
+            "ws" => Ok(Self::WebSocket { host: host.to_string(), port, path: path.to_string() }),
+            "wss" => Err("暂不支持 wss，请使用 ws 并经 SSH 隧道或 VPN 连接远程内核".to_string()),
+            _ => Err(invalid()),
+        }
+    }
+
+    /// 启动时使用的端点：环境变量优先，其次设置项；均未配置或格式无效时为 None（本地 stdio 进程）
+    pub fn resolve(configured: Option<&str>) -> Option<Self> {
+        let url = std::env::var(KERNEL_ENDPOINT_ENV)
+            .ok()
+            .filter(|s| !s.trim().is_empty())
+            .or_else(|| configured.map(str::to_string))?;
+        match Self::parse(&url) {
+            Ok(endpoint) => Some(endpoint),
+            Err(e) => {
+                eprintln!("{}，改用本地内核进程", e);
+                None
+            }
+        }
+    }
+
+    fn host_port(&self) -> (&str, u16) {
+        match self {
+            Self::Tcp { host, port } | Self::WebSocket { host, port, .. } => (host, *port),
+        }
+    }
+}
+
+impl std::fmt::Display for KernelEndpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Tcp { host, port } => write!(f, "tcp://{}:{}", host, port),
+            Self::WebSocket { host, port, path } => write!(f, "ws://{}:{}{}", host, port, path),
+        }
+    }
+}
+
+enum Sink {
+    Pipe(std::process::ChildStdin),
+    Tcp(TcpStream),
+    WebSocket(TcpStream),
+}
+
+/// 请求写入端：协商前按 JSON 写入，协商后由调用方切换编码
+pub struct KernelWriter {
+    sink: Sink,
+    encoding: WireEncoding,
+}
+
+impl KernelWriter {
+    pub fn pipe(stdin: std::process::ChildStdin) -> Self {
+        Self { sink: Sink::Pipe(stdin), encoding: WireEncoding::Json }
+    }
+
+    pub fn set_encoding(&mut self, encoding: WireEncoding) {
+        self.encoding = encoding;
+    }
+
+    /// 写入一条完整消息（调用方持锁，并发请求不会交错）
+    pub fn send(&mut self, message: &serde_json::Value) -> io::Result<()> {
+        let body = self.encoding.encode(message)?;
+        match &mut self.sink {
+            Sink::Pipe(w) => write_stream_message(w, self.encoding, &body),
+            Sink::Tcp(w) => write_stream_message(w, self.encoding, &body),
+            Sink::WebSocket(w) => {
+                let opcode = match self.encoding {
+                    WireEncoding::Json => OPCODE_TEXT,
+                    WireEncoding::MessagePack => OPCODE_BINARY,
+                };
+                write_frame(w, opcode, &body)
+            }
+        }
+    }
+}
+
+fn write_stream_message(w: &mut impl Write, encoding: WireEncoding, body: &[u8]) -> io::Result<()> {
+    match encoding {
+        WireEncoding::Json => {
+            w.write_all(body)?;
+            w.write_all(b"\n")?;
+        }
+        WireEncoding::MessagePack => {
+            let len = u32::try_from(body.len()).map_err(|_| io::Error::other("消息过大"))?;
+            w.write_all(&len.to_be_bytes())?;
+            w.write_all(body)?;
+        }
+    }
+    w.flush()
+}
+
+/// 响应读取端（供读取线程阻塞读取）
+pub trait MessageReader: Send {
+    /// 读取下一条完整消息；连接关闭时返回 None
+    fn next_message(&mut self) -> io::Result<Option<Vec<u8>>>;
+
+    /// 协商完成后切换编码（影响管道与 TCP 的分帧方式）
+    fn set_encoding(&mut self, encoding: WireEncoding);
+}
+
+pub type KernelReader = Box<dyn MessageReader>;
+
+/// 管道与 TCP 的读取端：JSON 按行（跳过空行）、MessagePack 按长度前缀分帧
+pub struct StreamReader<R> {
+    inner: R,
+    encoding: WireEncoding,
+}
+
+impl<R: BufRead + Send> StreamReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self { inner, encoding: WireEncoding::Json }
+    }
+}
+
+impl<R: BufRead + Send> MessageReader for StreamReader<R> {
+    fn next_message(&mut self) -> io::Result<Option<Vec<u8>>> {
+        match self.encoding {
+            WireEncoding::Json => loop {
+                let mut line = Vec::new();
+                if self.inner.read_until(b'\n', &mut line)? == 0 {
+                    return Ok(None);
+                }
+                if !line.trim_ascii().is_empty() {
+                    return Ok(Some(line));
+                }
+            },
+            WireEncoding::MessagePack => {
+                let mut len = [0u8; 4];
+                match self.inner.read_exact(&mut len) {
+                    Ok(()) => {}
+                    Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+                    Err(e) => return Err(e),
+                }
+                let len = u32::from_be_bytes(len) as u64;
+                if len > MAX_MESSAGE_BYTES {
+                    return Err(io::Error::other("消息过大"));
+                }
+                let mut body = vec![0u8; len as usize];
+                self.inner.read_exact(&mut body)?;
+                Ok(Some(body))
+            }
+        }
+    }
+
+    fn set_encoding(&mut self, encoding: WireEncoding) {
+        self.encoding = encoding;
+    }
+}
+
+/// 已建立的远程连接；socket 用于断开（读写两端共享同一套接字）
+pub struct RemoteConnection {
+    pub writer: KernelWriter,
+    pub reader: KernelReader,
+    pub socket: TcpStream,
+}
+
+impl RemoteConnection {
+    pub fn connect(endpoint: &KernelEndpoint) -> io::Result<Self> {
+        let (host, port) = endpoint.host_port();
+        let mut last_error = io::Error::new(io::ErrorKind::NotFound, format!("无法解析内核地址: {}", endpoint));
+        let mut connected = None;
+        for addr in (host, port).to_socket_addrs()? {
+            match TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT) {
+                Ok(stream) => {
+                    connected = Some(stream);
+                    break;
+                }
+                Err(e) => last_error = e,
+            }
+        }
+        let socket = connected.ok_or(last_error)?;
+        socket.set_nodelay(true)?;
+        let mut reader = BufReader::new(socket.try_clone()?);
+        let (writer, reader): (KernelWriter, KernelReader) = match endpoint {
+            KernelEndpoint::Tcp { .. } => (
+                KernelWriter { sink: Sink::Tcp(socket.try_clone()?), encoding: WireEncoding::Json },
+                Box::new(StreamReader::new(reader)),
+            ),
+            KernelEndpoint::WebSocket { host, port, path } => {
+                let mut writer = socket.try_clone()?;
+                websocket_handshake(&mut writer, &mut reader, host, *port, path)?;
+                (
+                    KernelWriter { sink: Sink::WebSocket(writer), encoding: WireEncoding::Json },
+                    Box::new(WebSocketMessages { reader }),
+                )
+            }
+        };
+        Ok(Self { writer, reader, socket })
+    }
+}
+
+const OPCODE_CONTINUATION: u8 = 0x0;
+const OPCODE_TEXT: u8 = 0x1;
+const OPCODE_BINARY: u8 = 0x2;
+const OPCODE_CLOSE: u8 = 0x8;
+
+/// 客户端握手；服务端为本项目内核，仅校验 101 状态（校验 Sec-WebSocket-Accept 需 SHA-1，未引入）
+fn websocket_handshake(
+    writer: &mut TcpStream,
+    reader: &mut BufReader<TcpStream>,
+    host: &str,
+    port: u16,
+    path: &str,
+) -> io::Result<()> {
+    let key = base64_encode(&rand::random::<[u8; 16]>());
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: {}:{}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: {}\r\nSec-WebSocket-Version: 13\r\n\r\n",
+        path, host, port, key
+    );
+    writer.write_all(request.as_bytes())?;
+    writer.flush()?;
+    let mut status = String::new();
+    reader.read_line(&mut status)?;
+    if status.split_whitespace().nth(1) != Some("101") {
+        return Err(io::Error::other(format!("WebSocket 握手失败: {}", status.trim())));
+    }
+    // 跳过其余响应头
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            return Ok(());
+        }
+    }
+}
+
+/// 写入一帧（客户端帧须加掩码）
+fn write_frame(w: &mut TcpStream, opcode: u8, payload: &[u8]) -> io::Result<()> {
+    let mut frame = Vec::with_capacity(payload.len() + 14);
+    frame.push(0x80 | opcode);
+    match payload.len() {
+        len if len < 126 => frame.push(0x80 | len as u8),
+        len if len <= u16::MAX as usize => {
+            frame.push(0x80 | 126);
+            frame.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            frame.push(0x80 | 127);
+            frame.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    let mask = rand::random::<[u8; 4]>();
+    frame.extend_from_slice(&mask);
+    frame.extend(payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+    w.write_all(&frame)?;
+    w.flush()
+}
+
+/// 按消息读取服务端文本/二进制帧（合并分片；帧本身分隔消息，与编码无关）；收到关闭帧或连接关闭时结束
+struct WebSocketMessages {
+    reader: BufReader<TcpStream>,
+}
+
+impl MessageReader for WebSocketMessages {
+    fn next_message(&mut self) -> io::Result<Option<Vec<u8>>> {
+        let mut message = Vec::new();
+        loop {
+            let mut header = [0u8; 2];
+            match self.reader.read_exact(&mut header) {
+                Ok(()) => {}
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+                Err(e) => return Err(e),
+            }
+            let fin = header[0] & 0x80 != 0;
+            let opcode = header[0] & 0x0F;
+            let masked = header[1] & 0x80 != 0;
+            let len = match header[1] & 0x7F {
+                126 => {
+                    let mut ext = [0u8; 2];
+                    self.reader.read_exact(&mut ext)?;
+                    u16::from_be_bytes(ext) as u64
+                }
+                127 => {
+                    let mut ext = [0u8; 8];
+                    self.reader.read_exact(&mut ext)?;
+                    u64::from_be_bytes(ext)
+                }
+                len => len as u64,
+            };
+            if len + message.len() as u64 > MAX_MESSAGE_BYTES {
+                return Err(io::Error::other("WebSocket 消息过大"));
+            }
+            let mut mask = [0u8; 4];
+            if masked {
+                self.reader.read_exact(&mut mask)?;
+            }
+            let mut payload = vec![0u8; len as usize];
+            self.reader.read_exact(&mut payload)?;
+            if masked {
+                payload.iter_mut().enumerate().for_each(|(i, b)| *b ^= mask[i % 4]);
+            }
+            match opcode {
+                OPCODE_CLOSE => return Ok(None),
+                OPCODE_TEXT | OPCODE_BINARY | OPCODE_CONTINUATION => {
+                    message.extend_from_slice(&payload);
+                    if fin {
+                        return Ok(Some(message));
+                    }
+                }
+                // ping/pong：内核服务端不主动发送，忽略
+                _ => {}
+            }
+        }
+    }
+
+    fn set_encoding(&mut self, _encoding: WireEncoding) {}
+}
+
+fn base64_encode(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |acc, (i, b)| acc | ((*b as u32) << (16 - 8 * i)));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[((n >> (18 - 6 * i)) & 0x3F) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
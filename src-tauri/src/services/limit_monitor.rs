@@ -0,0 +1,275 @@
+// 设备软限值监控：按设备配置告警带（warning）与报警带（alarm），每步评估计算结果
+use crate::domain::topology::{DeviceType, Topology};
+use crate::services::result_index::ResultIndex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// 单个量的限值带：越过 warning 为预警，越过 alarm 为报警；未配置的边界不检查
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct LimitBand {
+    #[serde(default)]
+    pub warning_low: Option<f64>,
+    #[serde(default)]
+    pub warning_high: Option<f64>,
+    #[serde(default)]
+    pub alarm_low: Option<f64>,
+    #[serde(default)]
+    pub alarm_high: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum LimitLevel {
+    Normal,
+    Warning,
+    Alarm,
+}
+
+impl LimitLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LimitLevel::Normal => "normal",
+            LimitLevel::Warning => "warning",
+            LimitLevel::Alarm => "alarm",
+        }
+    }
+}
+
+impl LimitBand {
+    pub fn evaluate(&self, value: f64) -> LimitLevel {
+        let below = |b: Option<f64>| b.map(|b| value < b).unwrap_or(false);
+        let above = |b: Option<f64>| b.map(|b| value > b).unwrap_or(false);
+        if below(self.alarm_low) || above(self.alarm_high) {
+            LimitLevel::Alarm
+        } else if below(self.warning_low) || above(self.warning_high) {
+            LimitLevel::Warning
+        } else {
+            LimitLevel::Normal
+        }
+    }
+}
+
+/// 被监控的量：母线电压（pu）、线路/变压器负载率（%）、功率设备出力占额定功率比例（%）
+pub const QUANTITY_VOLTAGE_PU: &str = "voltage_pu";
+pub const QUANTITY_LOADING_PERCENT: &str = "loading_percent";
+pub const QUANTITY_POWER_RATIO_PCT: &str = "power_ratio_pct";
+
+/// 按设备类型的默认限值带
+pub fn default_bands(device_type: &DeviceType) -> HashMap<String, LimitBand> {
+    let mut bands = HashMap::new();
+    match device_type {
+        DeviceType::Node => {
+            bands.insert(QUANTITY_VOLTAGE_PU.to_string(), LimitBand {
+                warning_low: Some(0.97),
+                warning_high: Some(1.03),
+                alarm_low: Some(0.95),
+                alarm_high: Some(1.05),
+            });
+        }
+        DeviceType::Line | DeviceType::Transformer => {
+            bands.insert(QUANTITY_LOADING_PERCENT.to_string(), LimitBand {
+                warning_high: Some(80.0),
+                alarm_high: Some(100.0),
+                ..Default::default()
+            });
+        }
+        DeviceType::Pv | DeviceType::Storage | DeviceType::Charger => {
+            bands.insert(QUANTITY_POWER_RATIO_PCT.to_string(), LimitBand {
+                warning_high: Some(95.0),
+                alarm_high: Some(100.0),
+                ..Default::default()
+            });
+        }
+        _ => {}
+    }
+    bands
+}
+
+/// 当前处于预警/报警状态的限值告警
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LimitAlert {
+    pub device_id: String,
+    pub quantity: String,
+    pub value: f64,
+    pub level: LimitLevel,
+    pub band: LimitBand,
+    /// 首次进入当前等级的时间（秒）
+    pub since: f64,
+    pub timestamp: f64,
+}
+
+/// 设备限值 KPI：评估步数、预警步数、报警步数、设定值超额定被限幅次数、储能计划偏差率
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LimitKpi {
+    pub evaluated_steps: u64,
+    pub warning_steps: u64,
+    pub alarm_steps: u64,
+    #[serde(default)]
+    pub clamped_setpoints: u64,
+    /// 储能计划偏差率（%）；未执行计划的设备为 None
+    #[serde(default)]
+    pub schedule_deviation_percent: Option<f64>,
+}
+
+#[derive(Debug, Default)]
+pub struct LimitMonitor {
+    /// 运行时覆盖配置：device_id -> quantity -> 限值带（优先于设备属性 limit_bands 与默认值）
+    overrides: HashMap<String, HashMap<String, LimitBand>>,
+    /// 当前告警：(device_id, quantity) -> 告警
+    active: HashMap<(String, String), LimitAlert>,
+    kpis: HashMap<String, LimitKpi>,
+}
+
+impl LimitMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 新一轮仿真开始时清空告警与 KPI（保留配置）
+    pub fn reset(&mut self) {
+        self.active.clear();
+        self.kpis.clear();
+    }
+
+    /// 运行中清零 KPI（告警与配置保留）；device_ids 为 None 表示全部设备，返回被清零的设备
+    pub fn reset_kpis(&mut self, device_ids: Option<&HashSet<String>>) -> Vec<String> {
+        let cleared: Vec<String> = self
+            .kpis
+            .keys()
+            .filter(|id| device_ids.is_none_or(|ids| ids.contains(*id)))
+            .cloned()
+            .collect();
+        for id in &cleared {
+            self.kpis.remove(id);
+        }
+        cleared
+    }
+
+    pub fn set_device_bands(&mut self, device_id: String, bands: HashMap<String, LimitBand>) {
+        if bands.is_empty() {
+            self.overrides.remove(&device_id);
+        } else {
+            self.overrides.insert(device_id, bands);
+        }
+    }
+
+    /// 设备生效的限值带：默认值 <- 设备属性 limit_bands <- 运行时覆盖
+    pub fn effective_bands(&self, device_id: &str, topology: &Topology) -> HashMap<String, LimitBand> {
+        let mut bands = topology
+            .devices
+            .get(device_id)
+            .map(|d| {
+                let mut b = default_bands(&d.device_type);
+                if let Some(v) = d.properties.get("limit_bands") {
+                    if let Ok(props) = serde_json::from_value::<HashMap<String, LimitBand>>(v.clone()) {
+                        b.extend(props);
+                    }
+                }
+                b
+            })
+            .unwrap_or_default();
+        if let Some(o) = self.overrides.get(device_id) {
+            bands.extend(o.clone());
+        }
+        bands
+    }
+
+    pub fn active_alerts(&self) -> Vec<LimitAlert> {
+        let mut alerts: Vec<LimitAlert> = self.active.values().cloned().collect();
+        alerts.sort_by(|a, b| b.level.cmp(&a.level).then_with(|| a.device_id.cmp(&b.device_id)));
+        alerts
+    }
+
+    pub fn kpis(&self) -> HashMap<String, LimitKpi> {
+        self.kpis.clone()
+    }
+
+    /// 评估一次计算结果，返回告警集合是否发生变化（新增、消除或等级变化）
+    pub fn evaluate(&mut self, results: &serde_json::Value, topology: &Topology, index: &ResultIndex, timestamp: f64) -> bool {
+        let mut observed: Vec<(String, String, f64)> = Vec::new();
+        let tables: [(&str, &[DeviceType]); 6] = [
+            ("buses", &[DeviceType::Node]),
+            ("lines", &[DeviceType::Line]),
+            ("transformers", &[DeviceType::Transformer]),
+            ("generators", &[DeviceType::Pv]),
+            ("storages", &[DeviceType::Storage]),
+            ("loads", &[DeviceType::Charger]),
+        ];
+        for (table, types) in tables {
+            let Some(rows) = results.get(table).and_then(|v| v.as_object()) else { continue };
+            for row in rows.values() {
+                let Some(name) = row.get("name").and_then(|v| v.as_str()) else { continue };
+                let Some((device_id, device)) =
+                    index.lookup(topology, table, name).filter(|(_, d)| types.contains(&d.device_type))
+                else {
+                    continue;
+                };
+                match device.device_type {
+                    DeviceType::Node => {
+                        if let Some(v) = row.get("vm_pu").and_then(|v| v.as_f64()) {
+                            observed.push((device_id.clone(), QUANTITY_VOLTAGE_PU.to_string(), v));
+                        }
+                    }
+                    DeviceType::Line | DeviceType::Transformer => {
+                        if let Some(v) = row.get("loading_percent").and_then(|v| v.as_f64()) {
+                            observed.push((device_id.clone(), QUANTITY_LOADING_PERCENT.to_string(), v));
+                        }
+                    }
+                    _ => {
+                        let rated_kw = crate::domain::topology::rated_power_kw(&device.properties).filter(|r| *r > 0.0);
+                        let p_kw = row.get("p_mw").and_then(|v| v.as_f64()).map(|p| p * 1000.0);
+                        if let (Some(rated), Some(p)) = (rated_kw, p_kw) {
+                            observed.push((device_id.clone(), QUANTITY_POWER_RATIO_PCT.to_string(), p.abs() / rated * 100.0));
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut changed = false;
+        let mut still_active: Vec<(String, String)> = Vec::new();
+        let mut step_levels: HashMap<String, LimitLevel> = HashMap::new();
+        for (device_id, quantity, value) in observed {
+            let bands = self.effective_bands(&device_id, topology);
+            let Some(band) = bands.get(&quantity) else { continue };
+            let level = band.evaluate(value);
+            let worst = step_levels.entry(device_id.clone()).or_insert(LimitLevel::Normal);
+            *worst = (*worst).max(level);
+            if level == LimitLevel::Normal {
+                continue;
+            }
+            let key = (device_id.clone(), quantity.clone());
+            let since = match self.active.get(&key) {
+                Some(prev) if prev.level == level => prev.since,
+                _ => {
+                    changed = true;
+                    timestamp
+                }
+            };
+            self.active.insert(key.clone(), LimitAlert {
+                device_id,
+                quantity,
+                value,
+                level,
+                band: band.clone(),
+                since,
+                timestamp,
+            });
+            still_active.push(key);
+        }
+        let before = self.active.len();
+        self.active.retain(|k, _| still_active.contains(k));
+        changed |= self.active.len() != before;
+
+        for (device_id, level) in step_levels {
+            let kpi = self.kpis.entry(device_id).or_default();
+            kpi.evaluated_steps += 1;
+            match level {
+                LimitLevel::Warning => kpi.warning_steps += 1,
+                LimitLevel::Alarm => kpi.alarm_steps += 1,
+                LimitLevel::Normal => {}
+            }
+        }
+        changed
+    }
+}
@@ -0,0 +1,117 @@
+// 电表计量模型：PT/CT 变比与准确度等级。电表上报（寄存器、电表落库行）为二次侧值 = 一次侧值 ÷ (PT×CT)，并叠加等级误差；
+// 被测设备自身仍保存一次侧真实值，便于计量准确性研究
+use crate::domain::topology::{Device, DeviceType, Topology};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 电表计量参数（来自电表属性 pt_ratio / ct_ratio / accuracy_class）
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct MeterMetrology {
+    pub pt_ratio: f64,
+    pub ct_ratio: f64,
+    /// 准确度等级（%），如 0.2 / 0.5 / 1 / 2；0 表示理想电表
+    pub accuracy_class_pct: f64,
+}
+
+impl Default for MeterMetrology {
+    fn default() -> Self {
+        Self { pt_ratio: 1.0, ct_ratio: 1.0, accuracy_class_pct: 0.0 }
+    }
+}
+
+/// 变比支持数值或 "一次/二次" 写法（如 "10000/100"、"200/5"）
+fn parse_ratio(value: &serde_json::Value) -> Option<f64> {
+    let ratio = match value {
+        serde_json::Value::Number(n) => n.as_f64()?,
+        serde_json::Value::String(s) => match s.split_once('/') {
+            Some((p, q)) => p.trim().parse::<f64>().ok()? / q.trim().parse::<f64>().ok()?,
+            None => s.trim().parse::<f64>().ok()?,
+        },
+        _ => return None,
+    };
+    (ratio.is_finite() && ratio > 0.0).then_some(ratio)
+}
+
+/// 准确度等级支持数值或 "0.2S" / "0.5S" 写法（S 级按同数值处理）
+fn parse_accuracy_class(value: &serde_json::Value) -> Option<f64> {
+    let class = match value {
+        serde_json::Value::Number(n) => n.as_f64()?,
+        serde_json::Value::String(s) => s.trim().trim_end_matches(['S', 's']).parse::<f64>().ok()?,
+        _ => return None,
+    };
+    (class.is_finite() && class >= 0.0).then_some(class)
+}
+
+impl MeterMetrology {
+    pub fn from_device(device: &Device) -> Self {
+        let props = &device.properties;
+        Self {
+            pt_ratio: props.get("pt_ratio").and_then(parse_ratio).unwrap_or(1.0),
+            ct_ratio: props.get("ct_ratio").and_then(parse_ratio).unwrap_or(1.0),
+            accuracy_class_pct: props.get("accuracy_class").and_then(parse_accuracy_class).unwrap_or(0.0),
+        }
+    }
+
+    pub fn is_identity(&self) -> bool {
+        self.pt_ratio == 1.0 && self.ct_ratio == 1.0 && self.accuracy_class_pct <= 0.0
+    }
+}
+
+/// 各电表的计量误差：固定比差（每轮仿真抽取一次，±等级/2 内均匀分布）+ 每步随机误差（正态，截断到 ±等级/2），合计不超过等级限值
+#[derive(Default)]
+pub struct MeterAccuracyModel {
+    ratio_errors: HashMap<String, f64>,
+}
+
+impl MeterAccuracyModel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 新一轮仿真：重新抽取比差
+    pub fn reset(&mut self) {
+        self.ratio_errors.clear();
+    }
+
+    /// 本步各电表的读数系数：二次侧读数 = 一次侧值 × 系数；理想电表（变比 1、无误差）不出现在结果中
+    pub fn step_factors(&mut self, topology: &Topology) -> HashMap<String, f64> {
+        let mut rng = rand::thread_rng();
+        let mut factors = HashMap::new();
+        for device in topology.devices.values().filter(|d| d.device_type == DeviceType::Meter) {
+            let metrology = MeterMetrology::from_device(device);
+            if metrology.is_identity() {
+                continue;
+            }
+            let half = metrology.accuracy_class_pct / 2.0 / 100.0;
+            let error = if half > 0.0 {
+                let ratio_error = *self
+                    .ratio_errors
+                    .entry(device.id.clone())
+                    .or_insert_with(|| rng.gen_range(-half..=half));
+                // Box–Muller，σ = 等级/6，截断到 ±等级/2
+                let u1: f64 = rng.gen_range(f64::EPSILON..1.0);
+                let u2: f64 = rng.gen();
+                let z = (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos();
+                ratio_error + (z * half / 3.0).clamp(-half, half)
+            } else {
+                0.0
+            };
+            factors.insert(device.id.clone(), (1.0 + error) / (metrology.pt_ratio * metrology.ct_ratio));
+        }
+        factors
+    }
+}
+
+/// 按电表读数系数换算 (有功, 无功)；无系数的电表原样返回
+pub fn meter_reading(
+    factors: &HashMap<String, f64>,
+    meter_id: &str,
+    p_kw: Option<f64>,
+    q_kvar: Option<f64>,
+) -> (Option<f64>, Option<f64>) {
+    match factors.get(meter_id) {
+        Some(f) => (p_kw.map(|p| p * f), q_kvar.map(|q| q * f)),
+        None => (p_kw, q_kvar),
+    }
+}
@@ -0,0 +1,130 @@
+// 电表通信中断模拟：每步根据各电表的随机缺口与计划中断配置决定本步是否丢数，并记录缺口日志
+use crate::domain::meter_dropout::{MeterDropoutConfig, MeterDropoutGap, MeterDropoutStats};
+use crate::domain::topology::{DeviceType, Topology};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// 缺口日志保留上限
+const MAX_DROPOUT_GAPS: usize = 2000;
+
+#[derive(Default)]
+struct MeterRuntime {
+    /// 随机缺口结束的仿真时间
+    random_gap_until_s: Option<f64>,
+    /// 当前进行中缺口在日志中的序号
+    open_gap: Option<usize>,
+    stats: MeterDropoutStats,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeterDropoutReport {
+    pub stats: HashMap<String, MeterDropoutStats>,
+    pub gaps: Vec<MeterDropoutGap>,
+}
+
+#[derive(Default)]
+pub struct MeterDropoutEmulator {
+    meters: HashMap<String, MeterRuntime>,
+    gaps: Vec<MeterDropoutGap>,
+}
+
+impl MeterDropoutEmulator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn reset(&mut self) {
+        self.meters.clear();
+        self.gaps.clear();
+    }
+
+    /// 推进一步，返回本步应丢弃对外数据的电表集合；sim_elapsed_s 为本步对应的仿真经过时间
+    pub fn step(&mut self, topology: &Topology, sim_elapsed_s: f64, timestamp: f64) -> HashSet<String> {
+        let mut rng = rand::thread_rng();
+        let mut dropped = HashSet::new();
+        for (meter_id, device) in &topology.devices {
+            if device.device_type != DeviceType::Meter {
+                continue;
+            }
+            let Some(config) = MeterDropoutConfig::from_properties(&device.properties) else {
+                continue;
+            };
+            let runtime = self.meters.entry(meter_id.clone()).or_default();
+            runtime.stats.total_steps += 1;
+
+            if runtime.random_gap_until_s.is_some_and(|until| sim_elapsed_s >= until) {
+                runtime.random_gap_until_s = None;
+            }
+            let scheduled = config.scheduled_outages.iter().any(|o| o.contains(sim_elapsed_s));
+            if !scheduled && runtime.random_gap_until_s.is_none() && config.gap_probability > 0.0 && rng.gen_bool(config.gap_probability) {
+                let duration = if config.gap_max_s > config.gap_min_s {
+                    rng.gen_range(config.gap_min_s..config.gap_max_s)
+                } else {
+                    config.gap_min_s
+                };
+                runtime.random_gap_until_s = Some(sim_elapsed_s + duration);
+            }
+            let reason = if scheduled {
+                Some("scheduled")
+            } else if runtime.random_gap_until_s.is_some() {
+                Some("random")
+            } else {
+                None
+            };
+
+            match (reason, runtime.open_gap) {
+                (Some(reason), open) => {
+                    let continues = open.and_then(|i| self.gaps.get(i)).is_some_and(|g| g.reason == reason);
+                    if !continues {
+                        if let Some(gap) = open.and_then(|i| self.gaps.get_mut(i)) {
+                            gap.ended_at = Some(timestamp);
+                        }
+                        self.gaps.push(MeterDropoutGap {
+                            meter_id: meter_id.clone(),
+                            reason: reason.to_string(),
+                            started_at: timestamp,
+                            ended_at: None,
+                            dropped_steps: 0,
+                        });
+                        runtime.open_gap = Some(self.gaps.len() - 1);
+                        runtime.stats.gap_count += 1;
+                    }
+                    if let Some(gap) = runtime.open_gap.and_then(|i| self.gaps.get_mut(i)) {
+                        gap.dropped_steps += 1;
+                    }
+                    runtime.stats.dropped_steps += 1;
+                    dropped.insert(meter_id.clone());
+                }
+                (None, Some(i)) => {
+                    if let Some(gap) = self.gaps.get_mut(i) {
+                        gap.ended_at = Some(timestamp);
+                    }
+                    runtime.open_gap = None;
+                }
+                (None, None) => {}
+            }
+        }
+        self.trim_gaps();
+        dropped
+    }
+
+    /// 超过上限时丢弃最早的已结束缺口，并修正进行中缺口的序号
+    fn trim_gaps(&mut self) {
+        let overflow = self.gaps.len().saturating_sub(MAX_DROPOUT_GAPS);
+        if overflow == 0 {
+            return;
+        }
+        self.gaps.drain(..overflow);
+        for runtime in self.meters.values_mut() {
+            runtime.open_gap = runtime.open_gap.and_then(|i| i.checked_sub(overflow));
+        }
+    }
+
+    pub fn report(&self) -> MeterDropoutReport {
+        MeterDropoutReport {
+            stats: self.meters.iter().map(|(id, r)| (id.clone(), r.stats.clone())).collect(),
+            gaps: self.gaps.clone(),
+        }
+    }
+}
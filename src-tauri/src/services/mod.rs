@@ -4,11 +4,68 @@ pub mod python_bridge;
 pub mod simulation_engine;
 pub mod mode_handler;
 pub mod kernel_factory;
+pub mod kernel_pool;
+pub mod kernel_transport;
+pub mod kernel_log;
+pub mod bridge_metrics;
+pub mod shared_results;
+pub mod python_env;
+pub mod radial_power_flow;
+pub mod fallback_kernel;
 pub mod delay_simulator;
 pub mod modbus;
 pub mod modbus_filter;
 pub mod modbus_schema;
+pub mod modbus_doc;
 pub mod modbus_server;
 pub mod database;
+pub mod db_writer;
+pub mod limit_monitor;
+pub mod compliance;
+pub mod settings;
+pub mod data_source;
+pub mod csv_cache;
+pub mod column_parser;
+pub mod timezone;
+pub mod series_codec;
+pub mod series_tail;
+pub mod meter_dropout;
+pub mod meter_accuracy;
+pub mod energy_balance;
+pub mod storage_schedule;
+pub mod calibration;
+pub mod scenario;
+pub mod event_scheduler;
+pub mod fault_injector;
+pub mod demo_project;
+pub mod run_recovery;
+pub mod webhook;
+pub mod random_generator;
+pub mod forecast_accuracy;
+pub mod setpoint_limits;
+pub mod kernel_sync;
+pub mod api_auth;
+pub mod window_hub;
+pub mod results_pipeline;
+pub mod result_index;
+pub mod simulation_manager;
+pub mod partial_topology;
+pub mod replay;
+pub mod chart_renderer;
+pub mod cli_export;
+pub mod multi_rate;
+pub mod daily_rollover;
+pub mod oltc;
+pub mod grid_support;
+pub mod frequency_model;
+pub mod profile_playback;
+pub mod solar_model;
+pub mod weather;
+pub mod ev_sessions;
+pub mod run_summary;
+pub mod breakpoints;
+pub mod protection;
+pub mod ems;
+pub mod sweep;
 
-// pub use modbus::ModbusService; // 已移除 modbus 模块
\ No newline at end of file
+// pub use modbus::ModbusService; // 已移除 modbus 模块
@@ -1,13 +1,16 @@
-// Modbus TCP 管理：每设备独立 TCP 服务，四类寄存器由 modbus_server 实现
+// Modbus TCP 管理：每设备一个 TCP 服务（同一端口上的多个设备按 unit id 共享监听，可另经串口以 RTU 从站应答），四类寄存器由 modbus_server 实现
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use serde_json::Value as JsonValue;
 use std::sync::{Arc, Mutex as StdMutex};
 use tokio::sync::{mpsc, RwLock};
 use crate::commands::device::ModbusRegisterEntry;
+use crate::domain::auth::ApiScope;
+use crate::domain::simulation::CounterGroup;
+use crate::services::api_auth::ApiAuth;
 use crate::services::modbus_filter::{self, ModbusControlStateStore};
-use crate::services::modbus_schema::holding_register_default_key;
-use crate::services::modbus_server::{self, ModbusDeviceContext, OnHoldingRegisterWrite};
+use crate::services::modbus_schema::{self, coil_default_key, holding_register_default_key};
+use crate::services::modbus_server::{self, ModbusDeviceContext, OnCoilWrite, OnHoldingRegisterWrite, UnitRoutes};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModbusServerConfig {
@@ -16,6 +19,107 @@ pub struct ModbusServerConfig {
     pub enabled: bool,
 }
 
+/// 串口校验位
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SerialParity {
+    #[default]
+    None,
+    Even,
+    Odd,
+}
+
+/// Modbus RTU 串口配置：设备在 TCP 之外同时经串口（RS-485）以 RTU 从站应答
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModbusRtuConfig {
+    /// 串口名（Windows 如 COM3，Linux 如 /dev/ttyUSB0）
+    pub port_name: String,
+    #[serde(default = "default_baud_rate")]
+    pub baud_rate: u32,
+    #[serde(default)]
+    pub parity: SerialParity,
+    #[serde(default = "default_data_bits")]
+    pub data_bits: u8,
+    #[serde(default = "default_stop_bits")]
+    pub stop_bits: u8,
+    /// 从站地址（1-247），只应答发给本站的请求
+    #[serde(default = "default_rtu_slave_id")]
+    pub slave_id: u8,
+}
+
+fn default_baud_rate() -> u32 {
+    9600
+}
+
+fn default_data_bits() -> u8 {
+    8
+}
+
+fn default_stop_bits() -> u8 {
+    1
+}
+
+fn default_rtu_slave_id() -> u8 {
+    1
+}
+
+impl ModbusRtuConfig {
+    /// 从设备属性读取（serial_port 非空时启用；baud_rate / parity / data_bits / stop_bits / slave_id 可选）
+    pub fn from_properties(properties: &HashMap<String, JsonValue>) -> Option<Self> {
+        let port_name = properties
+            .get("serial_port")
+            .and_then(|v| v.as_str())
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())?;
+        let number = |key: &str| {
+            properties
+                .get(key)
+                .and_then(|v| v.as_u64().or_else(|| v.as_str().and_then(|s| s.trim().parse::<u64>().ok())))
+        };
+        let parity = match properties.get("parity").and_then(|v| v.as_str()).map(|s| s.trim().to_lowercase()).as_deref() {
+            Some("even") | Some("e") => SerialParity::Even,
+            Some("odd") | Some("o") => SerialParity::Odd,
+            _ => SerialParity::None,
+        };
+        Some(Self {
+            port_name,
+            baud_rate: number("baud_rate").map(|n| u32::try_from(n).unwrap_or(0)).unwrap_or_else(default_baud_rate),
+            parity,
+            data_bits: number("data_bits").map(|n| u8::try_from(n).unwrap_or(0)).unwrap_or_else(default_data_bits),
+            stop_bits: number("stop_bits").map(|n| u8::try_from(n).unwrap_or(0)).unwrap_or_else(default_stop_bits),
+            slave_id: unit_id_from_properties(properties).unwrap_or_else(default_rtu_slave_id),
+        })
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.port_name.trim().is_empty() {
+            return Err("串口名不能为空".to_string());
+        }
+        if self.baud_rate == 0 {
+            return Err("波特率必须大于 0".to_string());
+        }
+        if !(5..=8).contains(&self.data_bits) {
+            return Err("数据位须为 5 至 8".to_string());
+        }
+        if !(1..=2).contains(&self.stop_bits) {
+            return Err("停止位须为 1 或 2".to_string());
+        }
+        if !(1..=247).contains(&self.slave_id) {
+            return Err("RTU 从站地址须在 1 至 247 之间".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// 设备属性中的 unit id（unit_id，兼容 slave_id）；TCP 与 RTU 共用
+pub fn unit_id_from_properties(properties: &HashMap<String, JsonValue>) -> Option<u8> {
+    properties
+        .get("unit_id")
+        .or_else(|| properties.get("slave_id"))
+        .and_then(|v| v.as_u64().or_else(|| v.as_str().and_then(|s| s.trim().parse::<u64>().ok())))
+        .and_then(|n| u8::try_from(n).ok())
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DeviceRegisterMapping {
     pub device_id: String,
@@ -23,31 +127,98 @@ pub struct DeviceRegisterMapping {
     pub registers: HashMap<String, u16>,
 }
 
-/// 每设备 Modbus TCP 服务：通过 abort JoinHandle 停止；持有共享上下文与寄存器列表（含 key/address）供自定义地址解析
+/// 每设备 Modbus TCP 服务：挂在 (ip, port) 监听的 unit id 路由上；持有共享上下文与寄存器列表（含 key/address）供自定义地址解析
 pub struct RunningDeviceServer {
-    pub join: tokio::task::JoinHandle<std::io::Result<()>>,
+    /// 所在监听的 (ip, port)
+    pub endpoint: (String, u16),
+    pub unit_id: u8,
+    /// 同一设备的 RTU 串口服务（与 TCP 共享上下文），随 TCP 服务一起停止
+    pub rtu_join: Option<tokio::task::JoinHandle<std::io::Result<()>>>,
     pub device_type: String,
     pub context: Arc<RwLock<ModbusDeviceContext>>,
     /// 启动时传入的寄存器列表（含 key），用于 HR 写入时按地址解析 key、IR 更新时按 key 取地址
     pub registers: Vec<ModbusRegisterEntry>,
+    /// 距该设备上次同步寄存器经过的仿真时间（秒），用于电量积分；被跳过的同步（采样间隔未到、电表丢数）累加到下次
+    pub unsynced_seconds: f64,
+}
+
+/// 一个 TCP 监听：通过 abort JoinHandle 停止；端口上的设备按 unit id 路由，最后一个设备停止时关闭
+struct TcpListenerEntry {
+    join: tokio::task::JoinHandle<std::io::Result<()>>,
+    routes: UnitRoutes,
+    /// unit id -> device_id
+    devices: HashMap<u8, String>,
 }
 
 /// 保持寄存器写入事件：(device_id, address, value)，由接收端发出 Tauri 事件供命令逻辑使用
 pub type HoldingRegisterWriteEvent = (String, u16, u16);
 
+/// 线圈写入事件：(device_id, address, value)，开关合/分闸经 Modbus 过滤后推送到仿真
+pub type CoilWriteEvent = (String, u16, bool);
+
 pub struct ModbusService {
     config: Arc<RwLock<ModbusServerConfig>>,
     device_mappings: Arc<StdMutex<HashMap<String, DeviceRegisterMapping>>>,
     /// device_id -> RunningDeviceServer
     running_servers: Arc<StdMutex<HashMap<String, RunningDeviceServer>>>,
+    /// (ip, port) -> 监听；配置了相同 ip/port 的设备共享
+    tcp_listeners: Arc<StdMutex<HashMap<(String, u16), TcpListenerEntry>>>,
     /// 客户端写 HR 时发送 (device_id, addr, value)，由 main 中任务接收并 emit 事件
     hr_write_tx: mpsc::Sender<HoldingRegisterWriteEvent>,
+    /// 客户端写线圈时发送 (device_id, addr, value)，由 main 中任务接收
+    coil_write_tx: mpsc::Sender<CoilWriteEvent>,
     /// 每设备 Modbus 控制状态：四条指令独立，冲突时只响应最新一条
     pub control_state: Arc<ModbusControlStateStore>,
+    /// 待恢复的电量寄存器（恢复中断的仿真时写入）：设备服务启动时写入其上下文
+    pending_energy_registers: Arc<StdMutex<HashMap<String, HashMap<u16, u16>>>>,
+    /// 外部接口认证：各设备 Modbus 服务共享，按匿名权限授权读写并记录审计
+    api_auth: Arc<ApiAuth>,
+}
+
+/// 由仿真积分得到的电量寄存器（电表四象限电量与组合有功总电能、光伏今日/累计发电量），中断恢复时需延续
+fn energy_register_addresses(device_type: &str) -> &'static [u16] {
+    match device_type {
+        "meter" => &[7, 8, 9, 10, 11],
+        "static_generator" => &[5003, 5004],
+        _ => &[],
+    }
+}
+
+/// 写入不可变寄存器：光伏额定功率 IR 5001、充电桩额定功率 IR 4（0.1 kW）、储能额定容量 IR 39（0.1 kWh）；
+/// 条目指定了数据类型时按其类型编码（见 modbus_server::write_ir_value）
+fn write_immutable_registers(
+    ctx: &mut ModbusDeviceContext,
+    registers: &[ModbusRegisterEntry],
+    device_type: &str,
+    rated_power_kw: Option<f64>,
+    rated_capacity_kwh: Option<f64>,
+) {
+    let x10 = |v: f64| (v * 10.0).round().clamp(0.0, 65535.0) as u16;
+    let (key, addr, value) = match device_type {
+        "static_generator" => ("rated_power_kw", 5001, rated_power_kw),
+        "charger" | "Charger" => ("rated_power_kw", 4, rated_power_kw),
+        "storage" => ("capacity_kwh", 39, rated_capacity_kwh),
+        _ => return,
+    };
+    if let Some(v) = value {
+        modbus_server::write_ir_value(ctx, Some(registers), v, &[(key, addr, x10(v))]);
+    }
+}
+
+/// 累计量分组对应的设备类型与电量寄存器（储能日/累计电量每步由引擎状态重写，清零时同步写 0 使暂停中也立即可见）
+fn counter_group_registers(group: CounterGroup) -> Option<(&'static str, &'static [u16])> {
+    match group {
+        CounterGroup::MeterEnergy => Some(("meter", &[7, 8, 9, 10, 11])),
+        CounterGroup::PvDailyGeneration => Some(("static_generator", &[5003])),
+        CounterGroup::PvTotalGeneration => Some(("static_generator", &[5004])),
+        CounterGroup::StorageDaily => Some(("storage", &[426, 427])),
+        CounterGroup::StorageTotal => Some(("storage", &[428, 429, 430, 431])),
+        CounterGroup::DeviceEnergy | CounterGroup::KpiWindows => None,
+    }
 }
 
 impl ModbusService {
-    pub fn new(hr_write_tx: mpsc::Sender<HoldingRegisterWriteEvent>) -> Self {
+    pub fn new(hr_write_tx: mpsc::Sender<HoldingRegisterWriteEvent>, coil_write_tx: mpsc::Sender<CoilWriteEvent>) -> Self {
         Self {
             config: Arc::new(RwLock::new(ModbusServerConfig {
                 host: "localhost".to_string(),
@@ -56,11 +227,19 @@ impl ModbusService {
             })),
             device_mappings: Arc::new(StdMutex::new(HashMap::new())),
             running_servers: Arc::new(StdMutex::new(HashMap::new())),
+            tcp_listeners: Arc::new(StdMutex::new(HashMap::new())),
             hr_write_tx,
+            coil_write_tx,
             control_state: Arc::new(ModbusControlStateStore::new()),
+            pending_energy_registers: Arc::new(StdMutex::new(HashMap::new())),
+            api_auth: Arc::new(ApiAuth::new(Vec::new(), Some(ApiScope::ReadOnly))),
         }
     }
 
+    pub fn api_auth(&self) -> Arc<ApiAuth> {
+        self.api_auth.clone()
+    }
+
     /// 从运行中设备的寄存器列表中按地址解析 HR 的语义 key（先查条目 key，再回退到默认）
     pub fn get_key_for_holding_register(&self, device_id: &str, address: u16) -> Option<String> {
         let running = self.running_servers.lock().ok()?;
@@ -76,22 +255,45 @@ impl ModbusService {
         holding_register_default_key(&server.device_type, address).map(String::from)
     }
 
-    /// 应用一次 HR 写入（更新控制状态），返回应推送到 Python 的有效属性；支持自定义地址（按 key 解析）
+    /// 应用一次 HR 写入（更新控制状态），返回应推送到 Python 的有效属性；支持自定义地址（按 key 解析）；
+    /// power_sign 为设备在项目符号约定下的系数，功率设定按它换回内核原生约定
     pub fn apply_hr_write_and_effective_properties(
         &self,
         device_id: &str,
         device_type: &str,
         address: u16,
         value: u16,
+        power_sign: f64,
     ) -> Option<serde_json::Value> {
         let key = self.get_key_for_holding_register(device_id, address);
         if let Some(k) = key {
             let mut map = self.control_state.per_device.lock().ok()?;
             let state = map.entry(device_id.to_string()).or_default();
-            return modbus_filter::apply_hr_write_by_key(state, device_type, &k, value);
+            return modbus_filter::apply_hr_write_by_key(state, device_type, &k, value, power_sign);
         }
         self.control_state
-            .apply_hr_write(device_id, device_type, address, value)
+            .apply_hr_write(device_id, device_type, address, value, power_sign)
+    }
+
+    /// 应用一次线圈写入，返回应推送到 Python 的属性；线圈地址先按运行中寄存器列表的 key 解析，再回退到默认
+    pub fn apply_coil_write_and_effective_properties(
+        &self,
+        device_id: &str,
+        device_type: &str,
+        address: u16,
+        value: bool,
+    ) -> Option<serde_json::Value> {
+        let key = {
+            let running = self.running_servers.lock().ok()?;
+            let server = running.get(device_id)?;
+            server
+                .registers
+                .iter()
+                .find(|e| e.type_ == "coils" && e.address == address)
+                .and_then(|e| e.key.clone())
+                .or_else(|| coil_default_key(device_type, address).map(String::from))?
+        };
+        modbus_filter::apply_coil_write(device_type, &key, value)
     }
 
     pub async fn set_config(&self, config: ModbusServerConfig) {
@@ -118,13 +320,16 @@ impl ModbusService {
     }
 
     /// 启动指定设备的 Modbus TCP 服务（ip, port, 寄存器列表来自前端）；创建共享上下文供仿真同步
+    /// unit_id：同一 ip/port 上的多个设备按请求的 unit id 区分（端口上只有一个设备时应答任意 unit id）
     /// rated_power_kw：光伏/充电桩额定功率，加载拓扑时写 IR 5001/IR 4；rated_capacity_kwh：储能额定容量，写 IR 39
+    #[allow(clippy::too_many_arguments)]
     pub async fn start_device_modbus(
         &self,
         device_id: String,
         device_type: String,
         ip: String,
         port: u16,
+        unit_id: u8,
         registers: Vec<ModbusRegisterEntry>,
         rated_power_kw: Option<f64>,
         rated_capacity_kwh: Option<f64>,
@@ -135,58 +340,136 @@ impl ModbusService {
                 return Err("该设备 Modbus 服务已在运行".to_string());
             }
         }
+        if let Some(e) = registers.iter().find(|e| e.scale.is_some_and(|s| s == 0.0 || !s.is_finite())) {
+            return Err(format!("寄存器 {} 的倍率必须为非零有限值", e.address));
+        }
+        modbus_server::validate_register_layout(&registers)?;
         let tx = self.hr_write_tx.clone();
         let did = device_id.clone();
         let on_holding_write: OnHoldingRegisterWrite = Arc::new(move |addr: u16, value: u16| {
             let _ = tx.try_send((did.clone(), addr, value));
         });
-        let context = Arc::new(RwLock::new(ModbusDeviceContext::from_entries(&registers, Some(on_holding_write))));
+        let coil_tx = self.coil_write_tx.clone();
+        let did = device_id.clone();
+        let on_coil_write: OnCoilWrite = Arc::new(move |addr: u16, value: bool| {
+            let _ = coil_tx.try_send((did.clone(), addr, value));
+        });
+        let mut context = ModbusDeviceContext::from_entries(&registers, Some(on_holding_write));
+        context.on_coil_write = Some(on_coil_write);
+        let context = Arc::new(RwLock::new(context));
+        self.attach_tcp_route(&device_id, &ip, port, unit_id, context.clone())?;
         // 不可变数据：仅加载拓扑或设备属性编辑时写入（在 await 前释放 MutexGuard，保证 future 为 Send）
         {
             let mut ctx = context.write().await;
-            if device_type == "static_generator" {
-                if let Some(kw) = rated_power_kw {
-                    let v = (kw * 10.0_f64).round().clamp(0.0, 65535.0) as u16; // 0.1 kW
-                    ctx.set_input_register(5001, v);
-                }
-            } else if device_type == "storage" {
-                if let Some(kwh) = rated_capacity_kwh {
-                    let v = (kwh * 10.0_f64).round().clamp(0.0, 65535.0) as u16; // 0.1 kWh
-                    ctx.set_input_register(39, v);
-                }
-            } else if device_type == "charger" {
-                if let Some(kw) = rated_power_kw {
-                    let v = (kw * 10.0_f64).round().clamp(0.0, 65535.0) as u16; // 0.1 kW
-                    ctx.set_input_register(4, v);
-                }
+            write_immutable_registers(&mut ctx, &registers, &device_type, rated_power_kw, rated_capacity_kwh);
+            // 中断恢复：延续该设备此前积分的电量
+            let pending = self.pending_energy_registers.lock().ok().and_then(|mut p| p.remove(&device_id));
+            for (addr, value) in pending.unwrap_or_default() {
+                ctx.set_input_register(addr, value);
             }
         }
-        let context_for_task = context.clone();
-        let join = tokio::task::spawn(async move {
-            modbus_server::run_modbus_tcp_server(&ip, port, context_for_task).await
-        });
         let mut running = self.running_servers.lock().map_err(|e| e.to_string())?;
         running.insert(
             device_id,
             RunningDeviceServer {
-                join,
+                endpoint: (ip, port),
+                unit_id,
+                rtu_join: None,
                 device_type: device_type.clone(),
                 context,
                 registers,
+                unsynced_seconds: 0.0,
             },
         );
         Ok(())
     }
 
-    /// 停止指定设备的 Modbus TCP 服务（abort 任务）
+    /// 把设备上下文挂到 (ip, port) 的监听上：端口尚无监听（或监听已退出，如绑定失败）时新建，已有时按 unit id 共享
+    fn attach_tcp_route(
+        &self,
+        device_id: &str,
+        ip: &str,
+        port: u16,
+        unit_id: u8,
+        context: Arc<RwLock<ModbusDeviceContext>>,
+    ) -> Result<(), String> {
+        let mut listeners = self.tcp_listeners.lock().map_err(|e| e.to_string())?;
+        let key = (ip.to_string(), port);
+        if let Some(listener) = listeners.get_mut(&key).filter(|l| !l.join.is_finished()) {
+            if let Some(owner) = listener.devices.get(&unit_id) {
+                return Err(format!("{}:{} 上的 unit id {} 已被设备 {} 使用", ip, port, unit_id, owner));
+            }
+            listener.devices.insert(unit_id, device_id.to_string());
+            listener.routes.write().unwrap().insert(unit_id, context);
+            return Ok(());
+        }
+        let routes: UnitRoutes = Arc::new(std::sync::RwLock::new(HashMap::from([(unit_id, context)])));
+        let (task_ip, task_routes, auth) = (ip.to_string(), routes.clone(), self.api_auth.clone());
+        let join = tokio::task::spawn(async move {
+            modbus_server::run_modbus_tcp_server(&task_ip, port, task_routes, auth).await
+        });
+        let devices = HashMap::from([(unit_id, device_id.to_string())]);
+        listeners.insert(key, TcpListenerEntry { join, routes, devices });
+        Ok(())
+    }
+
+    /// 停止设备的服务：从监听上摘下（端口上最后一个设备摘下时关闭监听），并停止其 RTU 服务
+    async fn release_device(&self, device_id: &str, server: RunningDeviceServer) {
+        let join = {
+            let mut listeners = match self.tcp_listeners.lock() {
+                Ok(l) => l,
+                Err(_) => return,
+            };
+            let empty = match listeners.get_mut(&server.endpoint) {
+                Some(listener) => {
+                    if listener.devices.get(&server.unit_id).map(String::as_str) == Some(device_id) {
+                        listener.devices.remove(&server.unit_id);
+                        listener.routes.write().unwrap().remove(&server.unit_id);
+                    }
+                    listener.devices.is_empty()
+                }
+                None => false,
+            };
+            if empty {
+                listeners.remove(&server.endpoint).map(|l| l.join)
+            } else {
+                None
+            }
+        };
+        if let Some(join) = join {
+            join.abort();
+            let _ = join.await;
+        }
+        if let Some(rtu) = server.rtu_join {
+            rtu.abort();
+            let _ = rtu.await;
+        }
+    }
+
+    /// 为运行中的设备另开 RTU 串口服务（须先启动其 TCP 服务）；串口打开失败时返回错误，TCP 服务不受影响
+    pub fn start_device_rtu(&self, device_id: &str, config: &ModbusRtuConfig) -> Result<(), String> {
+        config.validate()?;
+        let mut running = self.running_servers.lock().map_err(|e| e.to_string())?;
+        let server = running
+            .get_mut(device_id)
+            .ok_or_else(|| format!("设备 {} 的 Modbus 服务未运行", device_id))?;
+        if server.rtu_join.is_some() {
+            return Err("该设备 Modbus RTU 服务已在运行".to_string());
+        }
+        let serve = modbus_server::open_modbus_rtu_server(config, server.context.clone(), self.api_auth.clone())
+            .map_err(|e| format!("打开串口 {} 失败: {}", config.port_name, e))?;
+        server.rtu_join = Some(tokio::task::spawn(serve));
+        Ok(())
+    }
+
+    /// 停止指定设备的 Modbus TCP 服务（abort 任务，含其 RTU 服务）
     pub async fn stop_device_modbus(&self, device_id: &str) -> Result<(), String> {
         let server = {
             let mut running = self.running_servers.lock().map_err(|e| e.to_string())?;
             running.remove(device_id)
         };
         if let Some(server) = server {
-            server.join.abort();
-            let _ = server.join.await;
+            self.release_device(device_id, server).await;
         }
         Ok(())
     }
@@ -200,9 +483,8 @@ impl ModbusService {
             };
             std::mem::take(&mut *running)
         };
-        for (_id, server) in servers {
-            server.join.abort();
-            let _ = server.join.await;
+        for (id, server) in servers {
+            self.release_device(&id, server).await;
         }
     }
 
@@ -218,6 +500,12 @@ impl ModbusService {
             .unwrap_or_default()
     }
 
+    /// 运行中设备的启动寄存器表（含用户覆盖）与设备类型，用于生成点表
+    pub fn running_registers(&self, device_id: &str) -> Option<(String, Vec<ModbusRegisterEntry>)> {
+        let running = self.running_servers.lock().ok()?;
+        running.get(device_id).map(|s| (s.device_type.clone(), s.registers.clone()))
+    }
+
     /// 获取某设备当前输入寄存器与保持寄存器的快照（地址→值），供前端显示
     pub async fn get_device_register_snapshot(
         &self,
@@ -231,30 +519,140 @@ impl ModbusService {
         Some((ctx.input_registers.clone(), ctx.holding_registers.clone()))
     }
 
-    /// 设备属性编辑后同步不可变寄存器：光伏 IR 5001、储能 IR 39、充电桩 IR 4（仅当该设备 Modbus 在运行且属性含对应字段时写入）
+    /// 运行中设备的电量寄存器快照（device_id -> 地址 -> 值），用于仿真检查点
+    pub async fn energy_register_snapshot(&self) -> HashMap<String, HashMap<u16, u16>> {
+        let contexts: Vec<(String, Vec<u16>, Arc<RwLock<ModbusDeviceContext>>)> = match self.running_servers.lock() {
+            Ok(r) => r
+                .iter()
+                .map(|(id, s)| {
+                    let addrs = energy_register_addresses(&s.device_type);
+                    let addrs = modbus_server::resolve_ir_addresses(&s.registers, &s.device_type, addrs);
+                    (id.clone(), addrs, s.context.clone())
+                })
+                .collect(),
+            Err(_) => return HashMap::new(),
+        };
+        let mut out = HashMap::new();
+        for (device_id, addrs, context) in contexts {
+            if addrs.is_empty() {
+                continue;
+            }
+            let ctx = context.read().await;
+            let values: HashMap<u16, u16> = addrs
+                .iter()
+                .filter_map(|a| ctx.input_registers.get(a).map(|v| (*a, *v)))
+                .collect();
+            out.insert(device_id, values);
+        }
+        out
+    }
+
+    /// 运行中设备电量类仿真量的工程值（device_id -> key -> kWh/kvarh）：按寄存器列表中的地址与数据类型解码，
+    /// 供电量平衡核对与日翻转归档读取，不依赖默认地址与 16 位原始值
+    pub async fn energy_value_snapshot(&self) -> HashMap<String, HashMap<String, f64>> {
+        let contexts: Vec<(String, String, Vec<ModbusRegisterEntry>, Arc<RwLock<ModbusDeviceContext>>)> = match self.running_servers.lock() {
+            Ok(r) => r
+                .iter()
+                .filter(|(_, s)| !energy_register_addresses(&s.device_type).is_empty())
+                .map(|(id, s)| (id.clone(), s.device_type.clone(), s.registers.clone(), s.context.clone()))
+                .collect(),
+            Err(_) => return HashMap::new(),
+        };
+        let mut out = HashMap::new();
+        for (device_id, device_type, registers, context) in contexts {
+            let defaults = energy_register_addresses(&device_type);
+            let unit = modbus_server::energy_register_unit(&device_type);
+            let ctx = context.read().await;
+            let values: HashMap<String, f64> = modbus_schema::input_register_value_keys(&device_type)
+                .iter()
+                .filter(|(addr, _)| defaults.contains(addr))
+                .filter_map(|(addr, key)| {
+                    modbus_server::decode_ir_value(&ctx.input_registers, Some(&registers), key, *addr, unit)
+                        .map(|v| (key.to_string(), v))
+                })
+                .collect();
+            out.insert(device_id, values);
+        }
+        out
+    }
+
+    /// 恢复电量寄存器：运行中的设备立即写入，未启动的设备在其服务启动时写入
+    pub async fn restore_energy_registers(&self, snapshot: HashMap<String, HashMap<u16, u16>>) {
+        let contexts: HashMap<String, Arc<RwLock<ModbusDeviceContext>>> = match self.running_servers.lock() {
+            Ok(r) => r.iter().map(|(id, s)| (id.clone(), s.context.clone())).collect(),
+            Err(_) => HashMap::new(),
+        };
+        let mut pending = HashMap::new();
+        for (device_id, values) in snapshot {
+            if let Some(context) = contexts.get(&device_id) {
+                let mut ctx = context.write().await;
+                for (addr, value) in values {
+                    ctx.set_input_register(addr, value);
+                }
+            } else {
+                pending.insert(device_id, values);
+            }
+        }
+        if let Ok(mut guard) = self.pending_energy_registers.lock() {
+            *guard = pending;
+        }
+    }
+
+    /// 运行中清零某组电量寄存器（含待恢复的寄存器）；device_ids 为 None 表示该类型全部设备，返回被清零的设备
+    pub async fn reset_counter_registers(&self, group: CounterGroup, device_ids: Option<&HashSet<String>>) -> Vec<String> {
+        let Some((device_type, addrs)) = counter_group_registers(group) else {
+            return Vec::new();
+        };
+        let selected = |id: &String| device_ids.is_none_or(|ids| ids.contains(id));
+        // 运行中设备按其寄存器列表解析实际地址（自定义地址、32 位类型）；待恢复的寄存器按默认地址清除
+        let contexts: Vec<(String, Vec<u16>, Arc<RwLock<ModbusDeviceContext>>)> = match self.running_servers.lock() {
+            Ok(r) => r
+                .iter()
+                .filter(|(id, s)| s.device_type == device_type && selected(id))
+                .map(|(id, s)| (id.clone(), modbus_server::resolve_ir_addresses(&s.registers, device_type, addrs), s.context.clone()))
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        let mut cleared = Vec::new();
+        for (device_id, device_addrs, context) in contexts {
+            let mut ctx = context.write().await;
+            for addr in device_addrs {
+                ctx.set_input_register(addr, 0);
+            }
+            cleared.push(device_id);
+        }
+        if let Ok(mut pending) = self.pending_energy_registers.lock() {
+            for (device_id, values) in pending.iter_mut().filter(|(id, _)| selected(id)) {
+                let before = values.len();
+                values.retain(|addr, _| !addrs.contains(addr));
+                if values.len() != before && !cleared.contains(device_id) {
+                    cleared.push(device_id.clone());
+                }
+            }
+        }
+        cleared.sort();
+        cleared
+    }
+
+    /// 设备属性编辑后同步不可变寄存器：光伏 IR 5001/5042、储能 IR 39、充电桩 IR 4（仅当该设备 Modbus 在运行且属性含对应字段时写入）
     pub async fn update_device_immutable_registers(
         &self,
         device_id: &str,
         device_type: &str,
         properties: &HashMap<String, JsonValue>,
     ) {
-        let context = {
+        let (context, registers) = {
             let running = match self.running_servers.lock() {
                 Ok(r) => r,
                 Err(_) => return,
             };
             match running.get(device_id) {
-                Some(s) => s.context.clone(),
+                Some(s) => (s.context.clone(), s.registers.clone()),
                 None => return,
             }
         };
         let (rated_power_kw, rated_capacity_kwh) = if device_type == "static_generator" || device_type == "charger" || device_type == "Charger" {
-            let kw = properties
-                .get("rated_power_kw")
-                .or_else(|| properties.get("max_power_kw"))
-                .or_else(|| properties.get("rated_power"))
-                .and_then(|v| v.as_f64().or_else(|| v.as_u64().map(|u| u as f64)));
-            (kw, None)
+            (crate::domain::topology::rated_power_kw(properties), None)
         } else if device_type == "storage" {
             let kwh = properties
                 .get("capacity_kwh")
@@ -266,44 +664,82 @@ impl ModbusService {
             (None, None)
         };
         let mut ctx = context.write().await;
+        write_immutable_registers(&mut ctx, &registers, device_type, rated_power_kw, rated_capacity_kwh);
         if device_type == "static_generator" {
-            if let Some(kw) = rated_power_kw {
-                let v = (kw * 10.0_f64).round().clamp(0.0, 65535.0) as u16;
-                ctx.set_input_register(5001, v);
-            }
-        } else if device_type == "storage" {
-            if let Some(kwh) = rated_capacity_kwh {
-                let v = (kwh * 10.0_f64).round().clamp(0.0, 65535.0) as u16;
-                ctx.set_input_register(39, v);
-            }
-        } else if device_type == "charger" || device_type == "Charger" {
-            if let Some(kw) = rated_power_kw {
-                let v = (kw * 10.0_f64).round().clamp(0.0, 65535.0) as u16;
-                ctx.set_input_register(4, v);
+            // 无功控制模式（IR 5042）：0=无 1=固定功率因数 2=Q(U) 3=固定无功
+            let reactive = crate::domain::device::ReactiveControlConfig::from_properties(properties);
+            ctx.set_input_register(5042, reactive.mode.register_value());
+        }
+    }
+
+    /// 充电会话模型的充电桩：写入需求功率 IR 2（0.1 kW）、枪数量 IR 3 与各枪状态 IR 100 起（1-空闲，2-已连接未充电，3-充电中）
+    pub async fn update_charger_guns(&self, device_id: &str, gun_states: &[u16], demand_kw: f64) {
+        let context = {
+            let running = match self.running_servers.lock() {
+                Ok(r) => r,
+                Err(_) => return,
+            };
+            match running.get(device_id) {
+                Some(s) => s.context.clone(),
+                None => return,
             }
+        };
+        let mut ctx = context.write().await;
+        ctx.set_input_register(2, (demand_kw * 10.0).round().clamp(0.0, 65535.0) as u16);
+        ctx.set_input_register(3, gun_states.len().min(u16::MAX as usize) as u16);
+        for (i, state) in gun_states.iter().enumerate() {
+            ctx.set_input_register(100 + i as u16, *state);
+        }
+    }
+
+    /// 开关实际状态（device_id -> 是否闭合）回写运行中开关设备的合/分闸线圈，使本地或定时操作对 SCADA 可见
+    pub async fn update_switch_coils(&self, switch_states: &HashMap<String, bool>) {
+        let to_update: Vec<(bool, Arc<RwLock<ModbusDeviceContext>>, Vec<ModbusRegisterEntry>)> = {
+            let Ok(running) = self.running_servers.lock() else { return };
+            running
+                .iter()
+                .filter(|(_, s)| s.device_type == "switch")
+                .filter_map(|(id, s)| switch_states.get(id).map(|&closed| (closed, s.context.clone(), s.registers.clone())))
+                .collect()
+        };
+        for (closed, context, registers) in to_update {
+            let addr = registers
+                .iter()
+                .find(|e| e.type_ == "coils" && e.key.as_deref() == Some("closed"))
+                .map(|e| e.address)
+                .unwrap_or(0);
+            context.write().await.set_coil_silent(addr, closed);
         }
     }
 
-    /// 根据仿真功率缓存与储能状态更新所有运行中设备的 Modbus 输入寄存器（v1.5.0 update_* 逻辑）
-    /// dt_seconds：本步时长（秒）；storage_states：储能 SOC/日/累计电量。额定功率等不可变数据仅在加载拓扑启动时写入。
+    /// 根据仿真功率缓存与储能状态更新所有运行中设备的 Modbus 输入寄存器（v1.5.0 update_* 逻辑）；power_snapshot 中没有的设备不更新
+    /// dt_seconds：距上次同步的仿真时长（秒），按设备累加，电量按各设备自身距上次更新的时长积分；storage_states：储能 SOC/日/累计电量。额定功率等不可变数据仅在加载拓扑启动时写入。
     pub async fn update_all_devices_from_simulation(
         &self,
         power_snapshot: &HashMap<String, (f64, Option<f64>, Option<f64>)>,
         dt_seconds: f64,
         storage_states: Option<&HashMap<String, crate::domain::simulation::StorageState>>,
+        sign_factors: &HashMap<String, f64>,
     ) {
-        let to_update: Vec<(String, String, Arc<RwLock<ModbusDeviceContext>>, Vec<ModbusRegisterEntry>)> = {
-            let running = self.running_servers.lock().map_err(|_| ()).ok();
-            let Some(r) = running else { return };
-            r.iter()
-                .map(|(id, s)| (id.clone(), s.device_type.clone(), s.context.clone(), s.registers.clone()))
+        #[allow(clippy::type_complexity)]
+        let to_update: Vec<(String, String, Arc<RwLock<ModbusDeviceContext>>, Vec<ModbusRegisterEntry>, f64, (f64, Option<f64>, Option<f64>))> = {
+            let Ok(mut running) = self.running_servers.lock() else { return };
+            running
+                .iter_mut()
+                .filter_map(|(id, s)| {
+                    s.unsynced_seconds += dt_seconds;
+                    // 不在快照中的设备（丢数电表、采样间隔未到的设备）本次不更新，寄存器保持上次的值，时长留到下次积分
+                    let power = *power_snapshot.get(id)?;
+                    let elapsed = std::mem::take(&mut s.unsynced_seconds);
+                    Some((id.clone(), s.device_type.clone(), s.context.clone(), s.registers.clone(), elapsed, power))
+                })
                 .collect()
         };
-        for (device_id, device_type, context, registers) in to_update {
-            let (_, p_active, p_reactive) = power_snapshot.get(&device_id).copied().unwrap_or((0.0, None, None));
+        for (device_id, device_type, context, registers, elapsed, (_, p_active, p_reactive)) in to_update {
             let p_kw = p_active.unwrap_or(0.0);
             let q_kvar = p_reactive;
             let storage_state = storage_states.and_then(|m| m.get(&device_id));
+            let power_sign = sign_factors.get(&device_id).copied().unwrap_or(1.0);
             let mut ctx = context.write().await;
             modbus_server::update_context_from_simulation(
                 &mut *ctx,
@@ -311,18 +747,20 @@ impl ModbusService {
                 Some(&registers),
                 Some(p_kw),
                 q_kvar,
-                Some(dt_seconds),
+                Some(elapsed),
                 storage_state,
+                power_sign,
             );
         }
     }
 }
 
 impl ModbusService {
-    /// 用于测试或无需 HR 事件时的构造；HR 写入将被丢弃
+    /// 用于测试或无需 HR 事件时的构造；HR 与线圈写入将被丢弃
     pub fn new_without_hr_events() -> Self {
         let (tx, _rx) = mpsc::channel(64);
-        Self::new(tx)
+        let (coil_tx, _coil_rx) = mpsc::channel(64);
+        Self::new(tx, coil_tx)
     }
 }
 
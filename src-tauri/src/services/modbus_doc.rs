@@ -0,0 +1,315 @@
+// Modbus 点表生成：把设备当前生效的寄存器表（默认表 + 用户覆盖）连同数据类型、倍率、单位与语义渲染为
+// Markdown / CSV 点表，交给 SCADA 集成方，文档随运行配置自动同步
+use crate::commands::device::{get_modbus_register_defaults, ModbusRegisterEntry};
+use crate::services::modbus_schema::input_register_value_keys;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PointListFormat {
+    #[default]
+    Markdown,
+    Csv,
+}
+
+/// 寄存器语义：数据类型、工程单位、倍率（工程值 = 寄存器值 × scale）与说明
+#[derive(Debug, Clone, Copy)]
+struct PointSemantics {
+    data_type: &'static str,
+    unit: &'static str,
+    scale: f64,
+    description: &'static str,
+}
+
+const fn sem(data_type: &'static str, unit: &'static str, scale: f64, description: &'static str) -> PointSemantics {
+    PointSemantics { data_type, unit, scale, description }
+}
+
+/// 语义表：(设备类型, 寄存器类型, 默认地址, 语义键, 语义)；带 key 的条目按 key 匹配（支持自定义地址），其余按地址匹配
+const SEMANTICS: &[(&str, &str, u16, Option<&str>, PointSemantics)] = &[
+    // 电表
+    ("meter", "input_registers", 0, Some("active_power"), sem("int16", "kW", 0.5, "有功功率，按项目功率符号约定编码")),
+    ("meter", "input_registers", 1, None, sem("uint16", "V", 1.0, "A 相电压（固定值，仿真不更新）")),
+    ("meter", "input_registers", 2, None, sem("uint16", "V", 1.0, "B 相电压（固定值，仿真不更新）")),
+    ("meter", "input_registers", 3, None, sem("uint16", "V", 1.0, "C 相电压（固定值，仿真不更新）")),
+    ("meter", "input_registers", 4, None, sem("uint16", "A", 1.0, "A 相电流（固定值，仿真不更新）")),
+    ("meter", "input_registers", 5, None, sem("uint16", "A", 1.0, "B 相电流（固定值，仿真不更新）")),
+    ("meter", "input_registers", 6, None, sem("uint16", "A", 1.0, "C 相电流（固定值，仿真不更新）")),
+    ("meter", "input_registers", 7, Some("energy_export_kwh"), sem("uint16", "kWh", 1.0, "有功导出（上网）电量，由有功功率积分")),
+    ("meter", "input_registers", 8, Some("energy_import_kwh"), sem("uint16", "kWh", 1.0, "有功导入（下网）电量，由有功功率积分")),
+    ("meter", "input_registers", 9, Some("energy_total_kwh"), sem("uint16", "kWh", 1.0, "组合有功总电能 = 导出 + 导入")),
+    ("meter", "input_registers", 10, Some("energy_export_kvarh"), sem("uint16", "kVarh", 1.0, "无功导出电量，由无功功率积分")),
+    ("meter", "input_registers", 11, Some("energy_import_kvarh"), sem("uint16", "kVarh", 1.0, "无功导入电量，由无功功率积分")),
+    ("meter", "input_registers", 20, Some("reactive_power"), sem("int16", "kVar", 0.5, "无功功率，按项目功率符号约定编码")),
+    // 光伏
+    ("static_generator", "holding_registers", 5005, Some("on_off"), sem("uint16", "", 1.0, "开关机：0-关机，其他-开机")),
+    ("static_generator", "holding_registers", 5007, Some("power_limit_pct"), sem("uint16", "%", 1.0, "有功功率百分比限制（基准为额定功率），与功率限制互斥、最新指令生效")),
+    ("static_generator", "holding_registers", 5038, Some("power_limit_raw"), sem("uint16", "kW", 0.1, "有功功率限制，0x7FFF 为不限制，与百分比限制互斥、最新指令生效")),
+    ("static_generator", "holding_registers", 5040, Some("reactive_comp_pct"), sem("int16", "%", 0.1, "无功补偿百分比，-1000~1000 对应 -100%~100%，与功率因数互斥")),
+    ("static_generator", "holding_registers", 5041, Some("power_factor"), sem("int16", "", 0.001, "功率因数，[-1000,-800] 与 [800,1000] 对应 (-1,-0.8] 与 [0.8,1]，与无功补偿互斥")),
+    ("static_generator", "input_registers", 5001, Some("rated_power_kw"), sem("uint16", "kW", 0.1, "额定功率（加载拓扑时写入）")),
+    ("static_generator", "input_registers", 5003, Some("daily_energy_kwh"), sem("uint16", "kWh", 0.1, "今日发电量，由有功功率积分")),
+    ("static_generator", "input_registers", 5004, Some("total_energy_kwh"), sem("uint16", "kWh", 0.1, "总发电量，由有功功率积分")),
+    ("static_generator", "input_registers", 5030, Some("active_power_low"), sem("uint32 低字", "kW", 0.1, "当前有功功率低 16 位")),
+    ("static_generator", "input_registers", 5031, Some("active_power_high"), sem("uint32 高字", "kW", 0.1, "当前有功功率高 16 位")),
+    ("static_generator", "input_registers", 5032, Some("reactive_power_low"), sem("int32 低字", "kVar", 0.1, "无功功率低 16 位（补码，可为负）")),
+    ("static_generator", "input_registers", 5033, Some("reactive_power_high"), sem("int32 高字", "kVar", 0.1, "无功功率高 16 位（补码，可为负）")),
+    ("static_generator", "input_registers", 5042, None, sem("uint16", "", 1.0, "无功控制模式：0-无，1-固定 PF，2-Q(U)，3-固定 Q")),
+    // 储能
+    ("storage", "holding_registers", 4, Some("set_power"), sem("int16", "kW", 0.1, "功率设定，正为充电、负为放电")),
+    ("storage", "holding_registers", 55, Some("on_off"), sem("uint16", "", 1.0, "开关机：240-关机，243-开机（默认）")),
+    ("storage", "holding_registers", 5095, Some("grid_mode"), sem("uint16", "", 1.0, "并离网模式：0-并网，1-离网（同步到 IR 432）")),
+    ("storage", "holding_registers", 5033, Some("pcs_charge_discharge_state"), sem("uint16", "", 1.0, "PCS 充放电状态：1-放电，2-充电")),
+    ("storage", "input_registers", 0, None, sem("uint16", "", 1.0, "运行状态 1：1-待机/停机，2-充电，3-放电")),
+    ("storage", "input_registers", 2, Some("soc"), sem("uint16", "%", 0.1, "SOC")),
+    ("storage", "input_registers", 8, None, sem("uint16", "kW", 0.1, "最大充电功率")),
+    ("storage", "input_registers", 9, None, sem("uint16", "kW", 0.1, "最大放电功率")),
+    ("storage", "input_registers", 12, Some("remaining_energy_kwh"), sem("uint16", "kWh", 0.1, "剩余可放电容量")),
+    ("storage", "input_registers", 39, Some("capacity_kwh"), sem("uint16", "kWh", 0.1, "额定容量（加载拓扑时写入，运行中按衰减后的有效容量更新）")),
+    ("storage", "input_registers", 420, Some("active_power_low"), sem("int32 低字", "kW", 0.1, "有功功率低 16 位（补码，负为放电）")),
+    ("storage", "input_registers", 421, Some("active_power_high"), sem("int32 高字", "kW", 0.1, "有功功率高 16 位（补码，负为放电）")),
+    ("storage", "input_registers", 426, Some("daily_charge_kwh"), sem("uint16", "kWh", 0.1, "日充电量")),
+    ("storage", "input_registers", 427, Some("daily_discharge_kwh"), sem("uint16", "kWh", 0.1, "日放电量")),
+    ("storage", "input_registers", 428, Some("total_charge_kwh_low"), sem("uint32 低字", "kWh", 0.1, "累计充电总量低 16 位")),
+    ("storage", "input_registers", 429, Some("total_charge_kwh_high"), sem("uint32 高字", "kWh", 0.1, "累计充电总量高 16 位")),
+    ("storage", "input_registers", 430, Some("total_discharge_kwh_low"), sem("uint32 低字", "kWh", 0.1, "累计放电总量低 16 位")),
+    ("storage", "input_registers", 431, Some("total_discharge_kwh_high"), sem("uint32 高字", "kWh", 0.1, "累计放电总量高 16 位")),
+    ("storage", "input_registers", 432, None, sem("bitfield", "", 1.0, "PCS 工作模式：bit9-并网，bit10-离网")),
+    ("storage", "input_registers", 839, None, sem("uint16", "", 1.0, "运行状态 3：240-停机，243/245-正常，242/246-故障")),
+    // 充电桩
+    ("charger", "holding_registers", 0, Some("power_limit_raw"), sem("uint16", "kW", 0.1, "功率限制，0x7FFF 为不限制")),
+    ("charger", "input_registers", 0, Some("active_power"), sem("uint16", "kW", 0.1, "有功功率")),
+    ("charger", "input_registers", 2, None, sem("uint16", "kW", 0.1, "需求功率（充电会话模型下为各枪需求之和）")),
+    ("charger", "input_registers", 3, None, sem("uint16", "", 1.0, "枪数量（充电会话模型下按配置写入）")),
+    ("charger", "input_registers", 4, Some("rated_power_kw"), sem("uint16", "kW", 0.1, "额定功率（加载拓扑时写入）")),
+    ("charger", "input_registers", 100, None, sem("uint16", "", 1.0, "枪1状态：1-空闲，2-已连接未充电（已充满），3-充电中，4-故障")),
+    ("charger", "input_registers", 101, None, sem("uint16", "", 1.0, "枪2状态（编码同枪1）")),
+    ("charger", "input_registers", 102, None, sem("uint16", "", 1.0, "枪3状态（编码同枪1）")),
+    ("charger", "input_registers", 103, None, sem("uint16", "", 1.0, "枪4状态（编码同枪1）")),
+    // 开关
+    ("switch", "coils", 0, Some("closed"), sem("bool", "", 1.0, "断路器状态：1-合闸，0-分闸；写线圈即远程合/分闸（需允许远程控制），本地操作也会回写")),
+];
+
+fn lookup_semantics(device_type: &str, entry: &ModbusRegisterEntry) -> Option<PointSemantics> {
+    let same_table = |dt: &str, t: &str| dt == device_type && t == entry.type_;
+    match entry.key.as_deref() {
+        Some(key) => SEMANTICS
+            .iter()
+            .find(|(dt, t, _, k, _)| same_table(dt, t) && *k == Some(key))
+            .map(|e| e.4),
+        // 未带 key 的条目（旧版寄存器列表）按地址匹配；电量等仿真量后来才有 key，仍可按默认地址匹配
+        None => SEMANTICS
+            .iter()
+            .find(|(dt, t, addr, k, _)| {
+                same_table(dt, t)
+                    && *addr == entry.address
+                    && k.is_none_or(|k| input_register_value_keys(device_type).iter().any(|(_, vk)| *vk == k))
+            })
+            .map(|e| e.4),
+    }
+}
+
+fn access_of(type_: &str) -> &'static str {
+    match type_ {
+        "holding_registers" | "coils" => "RW",
+        _ => "R",
+    }
+}
+
+fn function_codes(type_: &str) -> &'static str {
+    match type_ {
+        "coils" => "01/05/15",
+        "discrete_inputs" => "02",
+        "input_registers" => "04",
+        _ => "03/06/16",
+    }
+}
+
+/// 点表中的一行
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegisterPoint {
+    /// coils | discrete_inputs | input_registers | holding_registers
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub address: u16,
+    pub function_codes: String,
+    pub access: String,
+    pub name: String,
+    #[serde(default)]
+    pub key: Option<String>,
+    pub data_type: String,
+    pub unit: String,
+    pub scale: f64,
+    pub initial_value: u16,
+    pub description: String,
+    /// 与默认表不同（地址、初值或名称被用户覆盖，或为新增寄存器）
+    pub overridden: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegisterPointList {
+    pub device_id: String,
+    pub device_name: String,
+    pub device_type: String,
+    /// 设备 Modbus 服务运行中时为其启动寄存器表，否则为默认表
+    pub from_running_server: bool,
+    pub points: Vec<RegisterPoint>,
+}
+
+/// 合并默认表与用户覆盖得到生效点表；registers 为 None 时即默认表
+pub fn build_point_list(
+    device_id: &str,
+    device_name: &str,
+    device_type: &str,
+    registers: Option<&[ModbusRegisterEntry]>,
+) -> Result<RegisterPointList, String> {
+    let defaults = get_modbus_register_defaults(device_type.to_string())?;
+    let effective = registers.unwrap_or(&defaults);
+    let mut points: Vec<RegisterPoint> = effective
+        .iter()
+        .map(|entry| {
+            // 对应的默认条目：有 key 按 key，否则按地址
+            let default = defaults.iter().find(|d| {
+                d.type_ == entry.type_
+                    && match entry.key.as_deref() {
+                        Some(key) => d.key.as_deref() == Some(key),
+                        None => d.key.is_none() && d.address == entry.address,
+                    }
+            });
+            let overridden = match default {
+                Some(d) => {
+                    d.address != entry.address
+                        || d.value != entry.value
+                        || (entry.name.is_some() && entry.name != d.name)
+                        || entry.data_type.is_some()
+                        || entry.scale.is_some()
+                        || entry.word_order.is_some()
+                }
+                None => true,
+            };
+            let semantics = lookup_semantics(device_type, entry);
+            let name = entry
+                .name
+                .clone()
+                .or_else(|| default.and_then(|d| d.name.clone()))
+                .or_else(|| entry.key.clone())
+                .unwrap_or_else(|| format!("{}_{}", entry.type_, entry.address));
+            RegisterPoint {
+                type_: entry.type_.clone(),
+                address: entry.address,
+                function_codes: function_codes(&entry.type_).to_string(),
+                access: access_of(&entry.type_).to_string(),
+                name,
+                key: entry.key.clone(),
+                // 条目指定的数据类型、字序与倍率优先于内置语义
+                data_type: match entry.data_type {
+                    Some(t) if t.is_32bit() => format!("{} {}", t.as_str(), entry.word_order.unwrap_or_default().as_str()),
+                    Some(t) => t.as_str().to_string(),
+                    None => semantics.map(|s| s.data_type).unwrap_or("uint16").to_string(),
+                },
+                unit: semantics.map(|s| s.unit).unwrap_or_default().to_string(),
+                scale: entry.scale.or(semantics.map(|s| s.scale)).unwrap_or(1.0),
+                initial_value: entry.value,
+                description: semantics.map(|s| s.description).unwrap_or("自定义寄存器（无内置语义）").to_string(),
+                overridden,
+            }
+        })
+        .collect();
+    // 同类寄存器按地址排序，保持输入寄存器在前（与协议文档惯例一致）
+    let type_order = |t: &str| match t {
+        "input_registers" => 0,
+        "holding_registers" => 1,
+        "discrete_inputs" => 2,
+        _ => 3,
+    };
+    points.sort_by(|a, b| type_order(&a.type_).cmp(&type_order(&b.type_)).then(a.address.cmp(&b.address)));
+    Ok(RegisterPointList {
+        device_id: device_id.to_string(),
+        device_name: device_name.to_string(),
+        device_type: device_type.to_string(),
+        from_running_server: registers.is_some(),
+        points,
+    })
+}
+
+fn format_scale(scale: f64) -> String {
+    if scale == 1.0 {
+        "1".to_string()
+    } else {
+        format!("{}", scale)
+    }
+}
+
+fn markdown_cell(s: &str) -> String {
+    s.replace('|', "\\|").replace('\n', " ")
+}
+
+pub fn render_markdown(list: &RegisterPointList) -> String {
+    let mut out = format!("# {} Modbus 点表\n\n", markdown_cell(&list.device_name));
+    out.push_str(&format!("- 设备 id：{}\n", list.device_id));
+    out.push_str(&format!("- 设备类型：{}\n", list.device_type));
+    out.push_str(&format!(
+        "- 寄存器来源：{}\n",
+        if list.from_running_server { "运行中的 Modbus 服务配置" } else { "默认寄存器表" }
+    ));
+    out.push_str("- 工程值 = 寄存器值 × 倍率；未标字序的 32 位量低字在前\n\n");
+    out.push_str("| 类型 | 地址 | 功能码 | 读写 | 名称 | 语义键 | 数据类型 | 单位 | 倍率 | 初值 | 说明 | 覆盖 |\n");
+    out.push_str("|---|---|---|---|---|---|---|---|---|---|---|---|\n");
+    for p in &list.points {
+        out.push_str(&format!(
+            "| {} | {} | {} | {} | {} | {} | {} | {} | {} | {} | {} | {} |\n",
+            p.type_,
+            p.address,
+            p.function_codes,
+            p.access,
+            markdown_cell(&p.name),
+            p.key.as_deref().unwrap_or(""),
+            p.data_type,
+            p.unit,
+            format_scale(p.scale),
+            p.initial_value,
+            markdown_cell(&p.description),
+            if p.overridden { "是" } else { "" },
+        ));
+    }
+    out
+}
+
+pub fn render_csv(list: &RegisterPointList) -> Result<String, String> {
+    let err = |e: csv::Error| format!("生成 CSV 点表失败: {}", e);
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer
+        .write_record([
+            "device_id", "type", "address", "function_codes", "access", "name", "key", "data_type", "unit", "scale",
+            "initial_value", "description", "overridden",
+        ])
+        .map_err(err)?;
+    for p in &list.points {
+        writer
+            .write_record([
+                list.device_id.clone(),
+                p.type_.clone(),
+                p.address.to_string(),
+                p.function_codes.clone(),
+                p.access.clone(),
+                p.name.clone(),
+                p.key.clone().unwrap_or_default(),
+                p.data_type.clone(),
+                p.unit.clone(),
+                format_scale(p.scale),
+                p.initial_value.to_string(),
+                p.description.clone(),
+                p.overridden.to_string(),
+            ])
+            .map_err(err)?;
+    }
+    let bytes = writer.into_inner().map_err(|e| format!("生成 CSV 点表失败: {}", e))?;
+    String::from_utf8(bytes).map_err(|e| format!("生成 CSV 点表失败: {}", e))
+}
+
+pub fn render(list: &RegisterPointList, format: PointListFormat) -> Result<String, String> {
+    match format {
+        PointListFormat::Markdown => Ok(render_markdown(list)),
+        PointListFormat::Csv => render_csv(list),
+    }
+}
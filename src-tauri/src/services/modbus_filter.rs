@@ -95,15 +95,17 @@ fn hr_address_to_command(device_type: &str, address: u16) -> Option<HrCommandId>
         .map(|(_, c)| *c)
 }
 
-/// 按 (device_type, key) 应用 HR 写入并返回有效属性（支持自定义地址时由调用方先解析 address -> key）
+/// 按 (device_type, key) 应用 HR 写入并返回有效属性（支持自定义地址时由调用方先解析 address -> key）；
+/// power_sign 为设备在项目符号约定下的系数（SignConvention::factor，自逆），用于把写入的功率设定换回内核原生约定
 pub fn apply_hr_write_by_key(
     state: &mut ModbusDeviceControlState,
     device_type: &str,
     key: &str,
     value: u16,
+    power_sign: f64,
 ) -> Option<serde_json::Value> {
     let cmd = hr_key_to_command_id(key)?;
-    apply_hr_write_inner(state, device_type, cmd, value)
+    apply_hr_write_inner(state, device_type, cmd, value, power_sign)
 }
 
 fn apply_hr_write_inner(
@@ -111,6 +113,7 @@ fn apply_hr_write_inner(
     device_type: &str,
     cmd: HrCommandId,
     value: u16,
+    power_sign: f64,
 ) -> Option<serde_json::Value> {
     match (device_type, cmd) {
         ("static_generator", HrCommandId::OnOff) | ("Pv", HrCommandId::OnOff) => {
@@ -139,9 +142,10 @@ fn apply_hr_write_inner(
         }
         ("storage", HrCommandId::SetPower) => {
             state.seq += 1;
-            // 储能功率单位 0.1 kW，寄存器为有符号 16 位（负=放电）；客户端写 (-300*10)&0xFFFF 即 62536，按 i16 解析为 -3000 → -300 kW
+            // 储能功率单位 0.1 kW，寄存器为有符号 16 位，方向与上送的功率寄存器一致（按项目符号约定）；
+            // 客户端写 (-300*10)&0xFFFF 即 62536，按 i16 解析为 -3000 → -300 kW，再乘 power_sign 换回内核原生约定（负=放电）
             let raw_i16 = value as i16;
-            let p_kw = (raw_i16 as f64) / 10.0;
+            let p_kw = (raw_i16 as f64) / 10.0 * power_sign;
             state.power_setpoint_kw = Some((p_kw, state.seq));
             Some(state.effective_properties())
         }
@@ -167,9 +171,18 @@ pub fn apply_hr_write_and_effective_properties(
     device_type: &str,
     address: u16,
     value: u16,
+    power_sign: f64,
 ) -> Option<serde_json::Value> {
     let cmd = hr_address_to_command(device_type, address)?;
-    apply_hr_write_inner(state, device_type, cmd, value)
+    apply_hr_write_inner(state, device_type, cmd, value, power_sign)
+}
+
+/// 按 (device_type, key) 应用线圈写入并返回应推送到 Python 的属性：开关线圈 closed 写 1/0 即合/分闸
+pub fn apply_coil_write(device_type: &str, key: &str, value: bool) -> Option<serde_json::Value> {
+    match (device_type, key) {
+        ("switch", "closed") => Some(json!({ "closed": value })),
+        _ => None,
+    }
 }
 
 /// 全局每设备 Modbus 控制状态，供 HR 写入时更新并计算有效属性
@@ -191,10 +204,11 @@ impl ModbusControlStateStore {
         device_type: &str,
         address: u16,
         value: u16,
+        power_sign: f64,
     ) -> Option<serde_json::Value> {
         let mut map = self.per_device.lock().ok()?;
         let state = map.entry(device_id.to_string()).or_default();
-        apply_hr_write_and_effective_properties(state, device_type, address, value)
+        apply_hr_write_and_effective_properties(state, device_type, address, value, power_sign)
     }
 }
 
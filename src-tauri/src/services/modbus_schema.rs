@@ -1,5 +1,7 @@
 // 每类设备的寄存器设置是固定的：每个输入寄存器对应更新逻辑，每个保持寄存器对应命令逻辑。
-// 本模块为各设备类型定义 IR 的 update_key 与 HR 的 command_id，作为单一事实来源。
+// 本模块为各设备类型定义 IR 的 update_key 与 HR 的 command_id，作为单一事实来源；
+// 另提供可按寄存器条目配置的数据类型与字序编码（浮点电表等）。
+use serde::{Deserialize, Serialize};
 
 /// 输入寄存器更新键：仿真结果写入该寄存器时使用的数据源
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -59,6 +61,34 @@ pub fn input_register_updates(device_type: &str) -> &'static [(u16, IrUpdateKey)
     }
 }
 
+/// 按设备类型返回由仿真写入的其余输入寄存器（电量、SOC、容量等工程量）：(默认地址, 语义 key)；
+/// 32 位量拆为 _low/_high 两个字，条目指定数据类型时须标在低位字上
+pub fn input_register_value_keys(device_type: &str) -> &'static [(u16, &'static str)] {
+    match device_type {
+        "meter" => &[
+            (7, "energy_export_kwh"),
+            (8, "energy_import_kwh"),
+            (9, "energy_total_kwh"),
+            (10, "energy_export_kvarh"),
+            (11, "energy_import_kvarh"),
+        ],
+        "static_generator" | "Pv" => &[(5001, "rated_power_kw"), (5003, "daily_energy_kwh"), (5004, "total_energy_kwh")],
+        "storage" => &[
+            (2, "soc"),
+            (12, "remaining_energy_kwh"),
+            (39, "capacity_kwh"),
+            (426, "daily_charge_kwh"),
+            (427, "daily_discharge_kwh"),
+            (428, "total_charge_kwh_low"),
+            (429, "total_charge_kwh_high"),
+            (430, "total_discharge_kwh_low"),
+            (431, "total_discharge_kwh_high"),
+        ],
+        "charger" | "Charger" => &[(4, "rated_power_kw")],
+        _ => &[],
+    }
+}
+
 /// 按设备类型返回具有命令逻辑的保持寄存器：(地址, 命令 id)
 pub fn holding_register_commands(device_type: &str) -> &'static [(u16, HrCommandId)] {
     match device_type {
@@ -102,6 +132,14 @@ pub fn holding_register_default_key(device_type: &str, address: u16) -> Option<&
     keys.iter().find(|(a, _)| *a == address).map(|(_, k)| *k)
 }
 
+/// 按 (device_type, address) 查线圈的默认语义 key（开关：线圈 0 为合/分闸）
+pub fn coil_default_key(device_type: &str, address: u16) -> Option<&'static str> {
+    match (device_type, address) {
+        ("switch", 0) => Some("closed"),
+        _ => None,
+    }
+}
+
 /// 语义 key -> HrCommandId，用于按 key 应用 HR 写入（支持自定义地址）
 pub fn hr_key_to_command_id(key: &str) -> Option<HrCommandId> {
     match key {
@@ -128,3 +166,102 @@ pub fn ir_update_key_to_default_key(k: IrUpdateKey) -> &'static str {
         IrUpdateKey::ReactivePowerHigh => "reactive_power_high",
     }
 }
+
+/// 寄存器数据类型（32 位类型占两个连续寄存器）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RegisterDataType {
+    Uint16,
+    Int16,
+    Uint32,
+    Int32,
+    Float32,
+}
+
+impl RegisterDataType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Uint16 => "uint16",
+            Self::Int16 => "int16",
+            Self::Uint32 => "uint32",
+            Self::Int32 => "int32",
+            Self::Float32 => "float32",
+        }
+    }
+
+    pub fn is_32bit(&self) -> bool {
+        matches!(self, Self::Uint32 | Self::Int32 | Self::Float32)
+    }
+}
+
+/// 字节/字序：按 32 位值的字节 A（最高）B C D 依次写入寄存器的顺序；16 位类型只区分是否字节交换
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum WordOrder {
+    /// 大端：高字在前（Modbus 标准）
+    #[default]
+    Abcd,
+    /// 字交换：低字在前
+    Cdab,
+    /// 字内字节交换
+    Badc,
+    /// 小端
+    Dcba,
+}
+
+impl WordOrder {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Abcd => "ABCD",
+            Self::Cdab => "CDAB",
+            Self::Badc => "BADC",
+            Self::Dcba => "DCBA",
+        }
+    }
+}
+
+/// 将工程值按数据类型、倍率（工程值 = 寄存器值 × scale）与字序编码为寄存器值；整数类型四舍五入并钳位到类型范围
+pub fn encode_value(value: f64, data_type: RegisterDataType, scale: f64, order: WordOrder) -> Vec<u16> {
+    let raw = if scale != 0.0 && scale.is_finite() { value / scale } else { value };
+    let bits: u32 = match data_type {
+        RegisterDataType::Uint16 => raw.round().clamp(0.0, u16::MAX as f64) as u32,
+        RegisterDataType::Int16 => raw.round().clamp(i16::MIN as f64, i16::MAX as f64) as i16 as u16 as u32,
+        RegisterDataType::Uint32 => raw.round().clamp(0.0, u32::MAX as f64) as u32,
+        RegisterDataType::Int32 => raw.round().clamp(i32::MIN as f64, i32::MAX as f64) as i32 as u32,
+        RegisterDataType::Float32 => (raw as f32).to_bits(),
+    };
+    let swap_bytes = matches!(order, WordOrder::Badc | WordOrder::Dcba);
+    let word = |w: u32| if swap_bytes { (w as u16).swap_bytes() } else { w as u16 };
+    if !data_type.is_32bit() {
+        return vec![word(bits)];
+    }
+    let (high, low) = (word(bits >> 16), word(bits & 0xFFFF));
+    match order {
+        WordOrder::Abcd | WordOrder::Badc => vec![high, low],
+        WordOrder::Cdab | WordOrder::Dcba => vec![low, high],
+    }
+}
+
+/// encode_value 的逆：按数据类型、倍率与字序把寄存器值解码为工程值；缺少的字按 0 处理
+pub fn decode_value(words: &[u16], data_type: RegisterDataType, scale: f64, order: WordOrder) -> f64 {
+    let swap_bytes = matches!(order, WordOrder::Badc | WordOrder::Dcba);
+    let word = |i: usize| {
+        let w = words.get(i).copied().unwrap_or(0);
+        (if swap_bytes { w.swap_bytes() } else { w }) as u32
+    };
+    let bits = if !data_type.is_32bit() {
+        word(0)
+    } else {
+        match order {
+            WordOrder::Abcd | WordOrder::Badc => (word(0) << 16) | word(1),
+            WordOrder::Cdab | WordOrder::Dcba => (word(1) << 16) | word(0),
+        }
+    };
+    let raw = match data_type {
+        RegisterDataType::Uint16 | RegisterDataType::Uint32 => bits as f64,
+        RegisterDataType::Int16 => bits as u16 as i16 as f64,
+        RegisterDataType::Int32 => bits as i32 as f64,
+        RegisterDataType::Float32 => f32::from_bits(bits) as f64,
+    };
+    if scale != 0.0 && scale.is_finite() { raw * scale } else { raw }
+}
@@ -1,4 +1,4 @@
-// Modbus TCP 服务端：四类寄存器上下文与 Service 实现（tokio-modbus）
+// Modbus TCP / RTU 服务端：四类寄存器上下文与 Service 实现（tokio-modbus）
 use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::sync::Arc;
@@ -9,10 +9,19 @@ use tokio_modbus::server::Service;
 use tokio_modbus::*;
 use crate::commands::device::ModbusRegisterEntry;
 use crate::services::modbus_schema;
+use crate::domain::auth::ApiScope;
+use crate::services::api_auth::ApiAuth;
+use crate::services::modbus::{ModbusRtuConfig, SerialParity};
 
 /// 保持寄存器写入回调：客户端写 HR 时调用 (地址, 值)，用于命令逻辑
 pub type OnHoldingRegisterWrite = Arc<dyn Fn(u16, u16) + Send + Sync>;
 
+/// 线圈写入回调：客户端写线圈时调用 (地址, 值)，用于开关合/分闸
+pub type OnCoilWrite = Arc<dyn Fn(u16, bool) + Send + Sync>;
+
+/// 单元路由表：unit id（从站地址）-> 设备上下文；同一端口上的多个设备按请求的 unit id 分发，启停设备时增删
+pub type UnitRoutes = Arc<std::sync::RwLock<HashMap<u8, Arc<RwLock<ModbusDeviceContext>>>>>;
+
 /// 四类寄存器存储：Coils / Discrete Inputs / Input Registers / Holding Registers
 /// 每类设备寄存器设置固定，每个 IR 有更新逻辑、每个 HR 有命令逻辑（见 modbus_schema）
 #[derive(Default)]
@@ -23,6 +32,8 @@ pub struct ModbusDeviceContext {
     pub holding_registers: HashMap<u16, u16>,
     /// 客户端写保持寄存器时调用，用于远程控制命令逻辑
     pub on_holding_register_write: Option<OnHoldingRegisterWrite>,
+    /// 客户端写线圈时调用，用于开关远程合/分闸
+    pub on_coil_write: Option<OnCoilWrite>,
 }
 
 impl ModbusDeviceContext {
@@ -70,6 +81,9 @@ impl ModbusDeviceContext {
 
     fn set_coil(&mut self, addr: u16, value: bool) {
         self.coils.insert(addr, value);
+        if let Some(ref cb) = self.on_coil_write {
+            cb(addr, value);
+        }
     }
 
     fn set_holding_register(&mut self, addr: u16, value: u16) {
@@ -89,19 +103,53 @@ impl ModbusDeviceContext {
         self.holding_registers.insert(addr, value);
     }
 
+    /// 供仿真同步写入线圈，不触发 on_coil_write（开关实际状态回写线圈）
+    pub fn set_coil_silent(&mut self, addr: u16, value: bool) {
+        self.coils.insert(addr, value);
+    }
+
     pub fn set_discrete_input(&mut self, addr: u16, value: bool) {
         self.discrete_inputs.insert(addr, value);
     }
 }
 
-/// Service 实现：共享 ModbusDeviceContext，处理 Request 并返回 Response
+/// Service 实现：按请求的 unit id 在路由表中找到设备上下文，处理 Request 并返回 Response
+/// Modbus 协议不携带凭据，客户端按匿名权限授权：读需 read_only，写需 control
 pub struct ModbusContextService {
-    pub context: Arc<RwLock<ModbusDeviceContext>>,
+    routes: UnitRoutes,
+    auth: Arc<ApiAuth>,
+    /// 审计用的来源：TCP 为客户端地址，RTU 为串口名
+    peer: String,
+    /// RTU 串口：只应答路由表中的从站地址（总线上还有其他从站），地址 0 为广播：执行写入但不应答
+    serial: bool,
 }
 
 impl ModbusContextService {
-    pub fn new(context: Arc<RwLock<ModbusDeviceContext>>) -> Self {
-        Self { context }
+    pub fn new(routes: UnitRoutes, auth: Arc<ApiAuth>, peer: String, serial: bool) -> Self {
+        Self { routes, auth, peer, serial }
+    }
+
+    /// 请求对应的设备上下文；TCP 端口上只有一个设备时忽略 unit id（兼容按 0 / 255 访问的客户端）
+    fn route(&self, unit_id: u8, broadcast: bool) -> Option<Arc<RwLock<ModbusDeviceContext>>> {
+        let routes = self.routes.read().unwrap();
+        match routes.get(&unit_id) {
+            Some(context) => Some(context.clone()),
+            None if (broadcast || !self.serial) && routes.len() == 1 => routes.values().next().cloned(),
+            None => None,
+        }
+    }
+}
+
+/// 请求所需权限与审计用的动作描述
+fn request_scope(request: &Request<'_>) -> (ApiScope, String) {
+    match request {
+        Request::WriteSingleCoil(addr, value) => (ApiScope::Control, format!("write_coil {}={}", addr, value)),
+        Request::WriteMultipleCoils(addr, values) => (ApiScope::Control, format!("write_coils {}+{}", addr, values.len())),
+        Request::WriteSingleRegister(addr, value) => (ApiScope::Control, format!("write_register {}={}", addr, value)),
+        Request::WriteMultipleRegisters(addr, values) => {
+            (ApiScope::Control, format!("write_registers {}+{}", addr, values.len()))
+        }
+        _ => (ApiScope::ReadOnly, "read".to_string()),
     }
 }
 
@@ -112,7 +160,16 @@ impl Service for ModbusContextService {
     type Future = std::pin::Pin<Box<dyn std::future::Future<Output = std::result::Result<Self::Response, Self::Exception>> + Send>>;
 
     fn call(&self, req: Self::Request) -> Self::Future {
-        let context = self.context.clone();
+        let broadcast = self.serial && req.slave == 0;
+        let Some(context) = self.route(req.slave, broadcast) else {
+            // RTU 总线上发给其他从站的请求不应答；TCP 端口上未映射的 unit id 按网关惯例返回目标设备无响应
+            let reply = if self.serial { Ok(None) } else { Err(ExceptionCode::GatewayTargetDevice) };
+            return Box::pin(std::future::ready(reply));
+        };
+        let (required, action) = request_scope(&req.request);
+        if self.auth.authorize("modbus", None, Some(&self.peer), &action, required).is_err() {
+            return Box::pin(std::future::ready(Err(ExceptionCode::IllegalFunction)));
+        }
         Box::pin(async move {
             let mut ctx = context.write().await;
             let response = match req.request {
@@ -154,17 +211,18 @@ impl Service for ModbusContextService {
                 }
                 _ => return Err(ExceptionCode::IllegalFunction),
             };
-            Ok(response)
+            Ok(if broadcast { None } else { response })
         })
     }
 }
 
-/// 在 (ip, port) 上启动 Modbus TCP 服务，使用共享上下文；任务被 abort 时退出
+/// 在 (ip, port) 上启动 Modbus TCP 服务，按 unit id 路由到共享上下文；任务被 abort 时退出
 /// 当 port < 1024 时，在 Linux/WSL 上无 root 会 EACCES，故改用 127.0.0.1:(10000+port) 绑定
 pub async fn run_modbus_tcp_server(
     ip: &str,
     port: u16,
-    context: Arc<RwLock<ModbusDeviceContext>>,
+    routes: UnitRoutes,
+    auth: Arc<ApiAuth>,
 ) -> std::io::Result<()> {
     let (bind_ip, bind_port) = if port < 1024 {
         let high_port = 10000u32.saturating_add(port as u32).min(65535) as u16;
@@ -180,11 +238,12 @@ pub async fn run_modbus_tcp_server(
     let server = Server::new(listener);
 
     let on_connected = move |stream: TcpStream, socket_addr: SocketAddr| {
-        let ctx = context.clone();
+        let routes = routes.clone();
+        let auth = auth.clone();
         std::future::ready(accept_tcp_connection(
             stream,
             socket_addr,
-            move |_| Ok(Some(ModbusContextService::new(ctx.clone()))),
+            move |peer| Ok(Some(ModbusContextService::new(routes.clone(), auth.clone(), peer.to_string(), false))),
         ))
     };
 
@@ -197,6 +256,40 @@ pub async fn run_modbus_tcp_server(
     Ok(())
 }
 
+/// 在串口上启动 Modbus RTU 从站（供只支持 RS-485 的网关接入），与该设备的 TCP 服务共享上下文；
+/// 串口在调用时打开（不存在或被占用时立即返回错误），返回的 future 持续服务直到任务被 abort
+pub fn open_modbus_rtu_server(
+    config: &ModbusRtuConfig,
+    context: Arc<RwLock<ModbusDeviceContext>>,
+    auth: Arc<ApiAuth>,
+) -> std::io::Result<impl std::future::Future<Output = std::io::Result<()>> + Send> {
+    let data_bits = match config.data_bits {
+        5 => tokio_serial::DataBits::Five,
+        6 => tokio_serial::DataBits::Six,
+        7 => tokio_serial::DataBits::Seven,
+        _ => tokio_serial::DataBits::Eight,
+    };
+    let stop_bits = if config.stop_bits == 2 { tokio_serial::StopBits::Two } else { tokio_serial::StopBits::One };
+    let parity = match config.parity {
+        SerialParity::None => tokio_serial::Parity::None,
+        SerialParity::Even => tokio_serial::Parity::Even,
+        SerialParity::Odd => tokio_serial::Parity::Odd,
+    };
+    let builder = tokio_serial::new(&config.port_name, config.baud_rate)
+        .data_bits(data_bits)
+        .stop_bits(stop_bits)
+        .parity(parity);
+    let serial = tokio_serial::SerialStream::open(&builder)?;
+    let server = tokio_modbus::server::rtu::Server::new(serial);
+    let routes: UnitRoutes = Arc::new(std::sync::RwLock::new(HashMap::from([(config.slave_id, context)])));
+    let service = ModbusContextService::new(routes, auth, format!("serial:{}", config.port_name), true);
+    Ok(async move {
+        server.serve_forever(service).await
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+        Ok(())
+    })
+}
+
 /// 光伏/储能/充电桩等（非电表）：功率寄存器单位 0.1 kW（寄存器值 = p_kw × 10）；储能可为负（放电）
 const POWER_UNIT_KW: f64 = 10.0;
 /// 电表有功/无功：int16 有符号，单位 0.5 kW（不修改，保持原样）
@@ -214,11 +307,13 @@ fn clamp_i16_as_u16(v: i32) -> u16 {
 
 /// 根据设备类型与 modbus_schema 将仿真结果写入对应输入寄存器（每个 IR 有固定更新逻辑）
 /// 电表：有功/无功为 int16、单位 0.5 kW；四象限电量与组合有功总电能为 kWh（0.1 kWh/单位），由 P/Q 积分得到
-/// 储能：Rust 维护的 SOC、日充电量、日放电量、累计充电/放电总量写入 IR 2/12/426-431
+/// 储能：Rust 维护的 SOC、日充电量、日放电量、累计充电/放电总量写入 IR 2/12/426-431，衰减后的有效容量写入 IR 39
 /// 光伏额定功率 IR 5001 仅在加载拓扑启动 Modbus 时写入，不在此处每步写入
-/// entries 可选：若提供则按 key 查找自定义地址，否则使用 schema 默认地址
+/// entries 可选：若提供则按 key 查找自定义地址，否则使用 schema 默认地址；指定了 data_type 的条目（功率、电量、SOC 等仿真量）
+/// 按其类型、倍率与字序编码工程值（见 write_ir_value）
 /// dt_seconds：本步时长（秒），用于电表四象限电量与总电能积分；仅电表且为 Some 时累加
 /// storage_state：储能状态（SOC、日/累计电量），仅 storage 且为 Some 时写 IR 2/12/426-431
+#[allow(clippy::too_many_arguments)]
 pub fn update_context_from_simulation(
     ctx: &mut ModbusDeviceContext,
     device_type: &str,
@@ -227,70 +322,65 @@ pub fn update_context_from_simulation(
     p_reactive_kvar: Option<f64>,
     dt_seconds: Option<f64>,
     storage_state: Option<&crate::domain::simulation::StorageState>,
+    power_sign: f64,
 ) {
     use modbus_schema::{input_register_updates, ir_update_key_to_default_key, IrUpdateKey};
     let p_kw = p_active_kw.unwrap_or(0.0);
     let q_kvar = p_reactive_kvar.unwrap_or(0.0);
+    // power_sign：项目符号约定系数（1/-1），仅作用于功率寄存器编码；电量积分与状态判断仍按内核原生约定
+    let (p_enc, q_enc) = (p_kw * power_sign, q_kvar * power_sign);
 
     // 电表：int16 有符号，单位 0.5 kW（保持不变）
-    let p_reg_meter = clamp_i16_as_u16((p_kw * METER_POWER_UNIT_KW).round() as i32);
-    let q_reg_meter = clamp_i16_as_u16((q_kvar * METER_POWER_UNIT_KW).round() as i32);
+    let p_reg_meter = clamp_i16_as_u16((p_enc * METER_POWER_UNIT_KW).round() as i32);
+    let q_reg_meter = clamp_i16_as_u16((q_enc * METER_POWER_UNIT_KW).round() as i32);
     // 光伏/储能/充电桩：功率寄存器单位 0.1 kW（寄存器值 = kW × 10），32 位拆高低字
-    let p_reg_10 = (p_kw * POWER_UNIT_KW).round() as i32;
-    let q_reg_10 = (q_kvar * POWER_UNIT_KW).round() as i32;
+    let p_reg_10 = (p_enc * POWER_UNIT_KW).round() as i32;
+    let q_reg_10 = (q_enc * POWER_UNIT_KW).round() as i32;
     let p_reg_other = if device_type == "storage" {
         p_reg_10 as u32
     } else {
         p_reg_10.max(0) as u32
     };
-    let q_reg_other = if device_type == "storage" {
+    // 光伏无功可为负（无功控制模式下吸收无功），与储能一样按 32 位补码写入
+    let q_reg_other = if device_type == "storage" || device_type == "static_generator" {
         q_reg_10 as u32
     } else {
         q_reg_10.max(0) as u32
     };
 
-    for &(default_addr, ir_key) in input_register_updates(device_type) {
-        let key = ir_update_key_to_default_key(ir_key);
-        let addr = entries
-            .and_then(|e| {
-                e.iter()
-                    .find(|r| r.type_ == "input_registers" && r.key.as_deref() == Some(key))
-                    .map(|r| r.address)
-            })
-            .unwrap_or(default_addr);
-        let value = match ir_key {
-            IrUpdateKey::ActivePower => {
-                if device_type == "meter" {
-                    p_reg_meter
-                } else {
-                    (p_reg_other & 0xFFFF) as u16
-                }
-            }
-            IrUpdateKey::ReactivePower => {
-                if device_type == "meter" {
-                    q_reg_meter
-                } else {
-                    (q_reg_other & 0xFFFF) as u16
-                }
-            }
-            IrUpdateKey::ActivePowerLow => (p_reg_other & 0xFFFF) as u16,
+    // 功率寄存器：32 位功率拆为低/高两个字；单字或低字条目指定了 data_type 时按其类型编码整个工程值（kW / kVar）
+    let updates = input_register_updates(device_type);
+    let power_word = |k: IrUpdateKey| -> Option<IrWord> {
+        let &(addr, _) = updates.iter().find(|(_, key)| *key == k)?;
+        let value = match k {
+            IrUpdateKey::ActivePower if device_type == "meter" => p_reg_meter,
+            IrUpdateKey::ReactivePower if device_type == "meter" => q_reg_meter,
+            IrUpdateKey::ActivePower | IrUpdateKey::ActivePowerLow => (p_reg_other & 0xFFFF) as u16,
             IrUpdateKey::ActivePowerHigh => (p_reg_other >> 16) as u16,
-            IrUpdateKey::ReactivePowerLow => (q_reg_other & 0xFFFF) as u16,
+            IrUpdateKey::ReactivePower | IrUpdateKey::ReactivePowerLow => (q_reg_other & 0xFFFF) as u16,
             IrUpdateKey::ReactivePowerHigh => (q_reg_other >> 16) as u16,
         };
-        ctx.set_input_register(addr, value);
+        Some((ir_update_key_to_default_key(k), addr, value))
+    };
+    let power_groups: [(f64, &[IrUpdateKey]); 4] = [
+        (p_enc, &[IrUpdateKey::ActivePower]),
+        (q_enc, &[IrUpdateKey::ReactivePower]),
+        (p_enc, &[IrUpdateKey::ActivePowerLow, IrUpdateKey::ActivePowerHigh]),
+        (q_enc, &[IrUpdateKey::ReactivePowerLow, IrUpdateKey::ReactivePowerHigh]),
+    ];
+    for (value, keys) in power_groups {
+        let words: Vec<IrWord> = keys.iter().filter_map(|k| power_word(*k)).collect();
+        write_ir_value(ctx, entries, value, &words);
     }
 
     // 电表：四象限电量与组合有功总电能（单位 kWh，寄存器 1 kWh/单位），由 P/Q 积分
     if device_type == "meter" {
         let dt_h = dt_seconds.map(|s| s / 3600.0).unwrap_or(0.0);
-        let read_energy = |ctx: &ModbusDeviceContext, addr: u16| {
-            ctx.input_registers.get(&addr).copied().unwrap_or(0) as f64 / METER_ENERGY_UNIT_KWH
-        };
-        let mut e_export_p = read_energy(ctx, 7);
-        let mut e_import_p = read_energy(ctx, 8);
-        let mut e_export_q = read_energy(ctx, 10);
-        let mut e_import_q = read_energy(ctx, 11);
+        let read_energy = |ctx: &ModbusDeviceContext, key: &str, addr: u16| read_ir_value(ctx, entries, key, addr, METER_ENERGY_UNIT_KWH);
+        let mut e_export_p = read_energy(ctx, "energy_export_kwh", 7);
+        let mut e_import_p = read_energy(ctx, "energy_import_kwh", 8);
+        let mut e_export_q = read_energy(ctx, "energy_export_kvarh", 10);
+        let mut e_import_q = read_energy(ctx, "energy_import_kvarh", 11);
         if dt_h > 0.0 {
             if p_kw > 0.0 {
                 e_export_p += p_kw * dt_h;
@@ -307,30 +397,31 @@ pub fn update_context_from_simulation(
         }
         let e_total_p = e_export_p + e_import_p;
         let write_energy = |v: f64| (v * METER_ENERGY_UNIT_KWH).round().clamp(0.0, 65535.0) as u16;
-        ctx.set_input_register(7, write_energy(e_export_p));
-        ctx.set_input_register(8, write_energy(e_import_p));
-        ctx.set_input_register(9, write_energy(e_total_p));
-        ctx.set_input_register(10, write_energy(e_export_q));
-        ctx.set_input_register(11, write_energy(e_import_q));
+        for (key, addr, v) in [
+            ("energy_export_kwh", 7, e_export_p),
+            ("energy_import_kwh", 8, e_import_p),
+            ("energy_total_kwh", 9, e_total_p),
+            ("energy_export_kvarh", 10, e_export_q),
+            ("energy_import_kvarh", 11, e_import_q),
+        ] {
+            write_ir_value(ctx, entries, v, &[(key, addr, write_energy(v))]);
+        }
     }
 
     // 光伏：今日发电量(IR 5003)、总发电量(IR 5004)，单位 0.1 kWh；由有功功率积分（仅 p_kw>0 累加）
-    // 今日：当前为自仿真启动以来累计，未按自然日清零；可按需扩展按日重置
+    // 今日：由仿真引擎在日电量翻转时清零（按仿真时间）
     if device_type == "static_generator" {
         let dt_h = dt_seconds.map(|s| s / 3600.0).unwrap_or(0.0);
-        let read_pv_energy = |ctx: &ModbusDeviceContext, addr: u16| {
-            ctx.input_registers.get(&addr).copied().unwrap_or(0) as f64 / PV_ENERGY_UNIT_KWH
-        };
-        let mut today_kwh = read_pv_energy(ctx, 5003);
-        let mut total_kwh = read_pv_energy(ctx, 5004);
+        let mut today_kwh = read_ir_value(ctx, entries, "daily_energy_kwh", 5003, PV_ENERGY_UNIT_KWH);
+        let mut total_kwh = read_ir_value(ctx, entries, "total_energy_kwh", 5004, PV_ENERGY_UNIT_KWH);
         if dt_h > 0.0 && p_kw > 0.0 {
             let delta = p_kw * dt_h;
             today_kwh += delta;
             total_kwh += delta;
         }
         let write_pv_energy = |v: f64| (v * PV_ENERGY_UNIT_KWH).round().clamp(0.0, 65535.0) as u16;
-        ctx.set_input_register(5003, write_pv_energy(today_kwh));
-        ctx.set_input_register(5004, write_pv_energy(total_kwh));
+        write_ir_value(ctx, entries, today_kwh, &[("daily_energy_kwh", 5003, write_pv_energy(today_kwh))]);
+        write_ir_value(ctx, entries, total_kwh, &[("total_energy_kwh", 5004, write_pv_energy(total_kwh))]);
     }
 
     // 储能 state_map：HR 55 仅表示开关机。关机=停机；开机后按实际功率 p_kw 区分就绪/充电/放电（1=放电 2=充电 0=就绪），故障由其他异常表示。
@@ -359,20 +450,137 @@ pub fn update_context_from_simulation(
     // 储能：Rust 维护的 SOC、日充电量、日放电量、累计充电/放电总量 → IR 2/12/426-431（单位与 modbus_manager 一致）
     if device_type == "storage" {
         if let Some(s) = storage_state {
-            let soc_reg = (s.soc_percent * 10.0).round().clamp(0.0, 1000.0) as u16;
-            ctx.set_input_register(2, soc_reg);
-            let remaining_kwh_x10 = (s.energy_kwh * 10.0).round().clamp(0.0, 65535.0) as u16;
-            ctx.set_input_register(12, remaining_kwh_x10);
-            let daily_charge = (s.daily_charge_kwh * 10.0).round().clamp(0.0, 65535.0) as u16;
-            let daily_discharge = (s.daily_discharge_kwh * 10.0).round().clamp(0.0, 65535.0) as u16;
-            ctx.set_input_register(426, daily_charge);
-            ctx.set_input_register(427, daily_discharge);
+            let x10 = |v: f64| (v * 10.0).round().clamp(0.0, 65535.0) as u16;
+            write_ir_value(ctx, entries, s.soc_percent, &[("soc", 2, (s.soc_percent * 10.0).round().clamp(0.0, 1000.0) as u16)]);
+            write_ir_value(ctx, entries, s.energy_kwh, &[("remaining_energy_kwh", 12, x10(s.energy_kwh))]);
+            // 额定容量按衰减后的有效容量上报（未开始积分时保持加载拓扑时写入的值）
+            if s.effective_capacity_kwh > 0.0 {
+                write_ir_value(ctx, entries, s.effective_capacity_kwh, &[("capacity_kwh", 39, x10(s.effective_capacity_kwh))]);
+            }
+            write_ir_value(ctx, entries, s.daily_charge_kwh, &[("daily_charge_kwh", 426, x10(s.daily_charge_kwh))]);
+            write_ir_value(ctx, entries, s.daily_discharge_kwh, &[("daily_discharge_kwh", 427, x10(s.daily_discharge_kwh))]);
             let total_charge_x10 = (s.total_charge_kwh * 10.0).round().clamp(0.0, u32::MAX as f64) as u32;
-            ctx.set_input_register(428, (total_charge_x10 & 0xFFFF) as u16);
-            ctx.set_input_register(429, (total_charge_x10 >> 16) as u16);
+            write_ir_value(ctx, entries, s.total_charge_kwh, &[
+                ("total_charge_kwh_low", 428, (total_charge_x10 & 0xFFFF) as u16),
+                ("total_charge_kwh_high", 429, (total_charge_x10 >> 16) as u16),
+            ]);
             let total_discharge_x10 = (s.total_discharge_kwh * 10.0).round().clamp(0.0, u32::MAX as f64) as u32;
-            ctx.set_input_register(430, (total_discharge_x10 & 0xFFFF) as u16);
-            ctx.set_input_register(431, (total_discharge_x10 >> 16) as u16);
+            write_ir_value(ctx, entries, s.total_discharge_kwh, &[
+                ("total_discharge_kwh_low", 430, (total_discharge_x10 & 0xFFFF) as u16),
+                ("total_discharge_kwh_high", 431, (total_discharge_x10 >> 16) as u16),
+            ]);
+        }
+    }
+}
+
+/// 仿真量的一个寄存器字：(语义 key, 默认地址, 默认编码的寄存器值)
+pub type IrWord = (&'static str, u16, u16);
+
+/// 按语义 key 查找输入寄存器条目
+fn ir_entry<'a>(entries: Option<&'a [ModbusRegisterEntry]>, key: &str) -> Option<&'a ModbusRegisterEntry> {
+    entries?.iter().find(|r| r.type_ == "input_registers" && r.key.as_deref() == Some(key))
+}
+
+/// 写入一个仿真量（工程值 value）：首字 key 的条目指定了 data_type 时按其类型、倍率与字序编码到该条目地址起的寄存器；
+/// 否则各字按默认编码写入各自 key 的条目地址（无条目时为默认地址）
+pub fn write_ir_value(ctx: &mut ModbusDeviceContext, entries: Option<&[ModbusRegisterEntry]>, value: f64, words: &[IrWord]) {
+    let typed = words
+        .first()
+        .and_then(|(key, _, _)| ir_entry(entries, key))
+        .and_then(|e| e.data_type.map(|t| (e, t)));
+    if let Some((entry, data_type)) = typed {
+        let encoded = modbus_schema::encode_value(value, data_type, entry.scale.unwrap_or(1.0), entry.word_order.unwrap_or_default());
+        for (i, word) in encoded.into_iter().enumerate() {
+            ctx.set_input_register(entry.address.wrapping_add(i as u16), word);
+        }
+        return;
+    }
+    for &(key, default_addr, word) in words {
+        let addr = ir_entry(entries, key).map(|e| e.address).unwrap_or(default_addr);
+        ctx.set_input_register(addr, word);
+    }
+}
+
+/// 读回上一步写入的仿真量工程值（电量积分延续用）；寄存器未写入时为 0
+fn read_ir_value(ctx: &ModbusDeviceContext, entries: Option<&[ModbusRegisterEntry]>, key: &str, default_addr: u16, unit: f64) -> f64 {
+    decode_ir_value(&ctx.input_registers, entries, key, default_addr, unit).unwrap_or(0.0)
+}
+
+/// 按 key 解码输入寄存器中的仿真量工程值：寄存器列表有该 key 的条目时取其地址，指定了 data_type 的按其类型解码，
+/// 否则为寄存器值 / unit；寄存器（低位字）不存在时为 None
+pub fn decode_ir_value(
+    registers: &HashMap<u16, u16>,
+    entries: Option<&[ModbusRegisterEntry]>,
+    key: &str,
+    default_addr: u16,
+    unit: f64,
+) -> Option<f64> {
+    let entry = ir_entry(entries, key);
+    let addr = entry.map(|e| e.address).unwrap_or(default_addr);
+    let first = *registers.get(&addr)?;
+    match entry.and_then(|e| e.data_type.map(|t| (e, t))) {
+        Some((e, data_type)) => {
+            let second = registers.get(&addr.wrapping_add(1)).copied().unwrap_or(0);
+            Some(modbus_schema::decode_value(&[first, second], data_type, e.scale.unwrap_or(1.0), e.word_order.unwrap_or_default()))
         }
+        None => Some(first as f64 / unit),
     }
 }
+
+/// 电量类仿真量未指定数据类型时的寄存器单位（寄存器值 = kWh × unit）：光伏 0.1 kWh，电表 1 kWh
+pub fn energy_register_unit(device_type: &str) -> f64 {
+    match device_type {
+        "static_generator" | "Pv" => PV_ENERGY_UNIT_KWH,
+        _ => METER_ENERGY_UNIT_KWH,
+    }
+}
+
+/// 把默认地址上的仿真量换算为寄存器列表中实际占用的地址（自定义地址；32 位类型占两个寄存器），用于电量寄存器快照与清零
+pub fn resolve_ir_addresses(entries: &[ModbusRegisterEntry], device_type: &str, default_addrs: &[u16]) -> Vec<u16> {
+    let keys = modbus_schema::input_register_value_keys(device_type);
+    let mut out = Vec::new();
+    for &addr in default_addrs {
+        let entry = keys
+            .iter()
+            .find(|(a, _)| *a == addr)
+            .and_then(|(_, key)| ir_entry(Some(entries), key));
+        match entry {
+            Some(e) => {
+                let span = if e.data_type.is_some_and(|t| t.is_32bit()) { 2 } else { 1 };
+                out.extend((0..span).map(|i| e.address.wrapping_add(i)));
+            }
+            None => out.push(addr),
+        }
+    }
+    out.sort_unstable();
+    out.dedup();
+    out
+}
+
+/// 校验寄存器布局：32 位数据类型占 address 与 address+1，不得与同类寄存器的其他条目重叠；
+/// 拆为高/低字的量只能在低位字条目上指定数据类型
+pub fn validate_register_layout(entries: &[ModbusRegisterEntry]) -> Result<(), String> {
+    let span = |e: &ModbusRegisterEntry| if e.data_type.is_some_and(|t| t.is_32bit()) { 2u32 } else { 1u32 };
+    for (i, e) in entries.iter().enumerate() {
+        if e.data_type.is_some() && e.key.as_deref().is_some_and(|k| k.ends_with("_high")) {
+            return Err(format!("寄存器 {} 为 32 位量的高位字，数据类型须在对应低位字条目上指定", e.address));
+        }
+        if span(e) < 2 {
+            continue;
+        }
+        let (start, end) = (e.address as u32, e.address as u32 + 1);
+        if let Some(other) = entries.iter().enumerate().find(|(j, f)| {
+            *j != i && f.type_ == e.type_ && (f.address as u32) <= end && start < f.address as u32 + span(f)
+        }) {
+            return Err(format!(
+                "寄存器 {} 的 {} 类型占 {}-{} 两个寄存器，与寄存器 {} 重叠",
+                e.address,
+                e.data_type.map(|t| t.as_str()).unwrap_or_default(),
+                start,
+                end,
+                other.1.address
+            ));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,62 @@
+// 多速率仿真：设备可在拓扑属性 update_interval_ms 中声明自身更新周期（按仿真时间），
+// 引擎只在周期到期的步为其下发设定（随机数据）并采样结果（落库、前端设备事件、Modbus 同步），
+// 未声明的设备每步更新；大拓扑中慢变设备（如负荷）可显著减少每步 RPC 与落库量
+use crate::domain::topology::Topology;
+use std::collections::{HashMap, HashSet};
+
+/// 设备属性键：更新周期（毫秒，仿真时间）
+pub const UPDATE_INTERVAL_PROPERTY: &str = "update_interval_ms";
+
+/// 读取设备声明的更新周期（秒）；未声明或非正数时为 None（每步更新）
+pub fn device_update_interval_s(properties: &HashMap<String, serde_json::Value>) -> Option<f64> {
+    properties
+        .get(UPDATE_INTERVAL_PROPERTY)
+        .and_then(|v| v.as_f64().or_else(|| v.as_str().and_then(|s| s.trim().parse().ok())))
+        .filter(|ms| ms.is_finite() && *ms > 0.0)
+        .map(|ms| ms / 1000.0)
+}
+
+#[derive(Default)]
+pub struct MultiRateScheduler {
+    /// device_id -> 上次更新的仿真时间（秒，相对仿真起点）
+    last_update_s: HashMap<String, f64>,
+    /// 本步未到期的设备
+    deferred: HashSet<String>,
+}
+
+impl MultiRateScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 进入新的一步：按拓扑中的更新周期计算本步未到期的设备；sim_time_s 为本步开始时的仿真时间
+    pub fn begin_step(&mut self, topology: Option<&Topology>, sim_time_s: f64) {
+        self.deferred.clear();
+        let Some(topology) = topology else {
+            return;
+        };
+        for (device_id, device) in &topology.devices {
+            let Some(interval_s) = device_update_interval_s(&device.properties) else {
+                self.last_update_s.remove(device_id);
+                continue;
+            };
+            match self.last_update_s.get(device_id) {
+                // 容差避免浮点累计误差使整周期的设备晚一步更新
+                Some(last) if sim_time_s - last < interval_s - 1e-6 => {
+                    self.deferred.insert(device_id.clone());
+                }
+                _ => {
+                    self.last_update_s.insert(device_id.clone(), sim_time_s);
+                }
+            }
+        }
+    }
+
+    pub fn is_due(&self, device_id: &str) -> bool {
+        !self.deferred.contains(device_id)
+    }
+
+    pub fn deferred(&self) -> &HashSet<String> {
+        &self.deferred
+    }
+}
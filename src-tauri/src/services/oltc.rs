@@ -0,0 +1,177 @@
+// 有载调压（OLTC）控制：变压器配置了分接头参数与受控母线时，引擎在步间读取上一步受控母线电压，
+// 越出目标电压死区（并持续超过动作延时）后每步调整一档分接头并下发内核，分接头动作记录保留供查询
+use crate::domain::topology::{prop_f64, DeviceType, Topology};
+use crate::services::result_index::ResultIndex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+/// 分接头动作记录保留上限
+const MAX_TAP_OPERATIONS: usize = 500;
+
+/// 变压器属性中的 OLTC 配置：
+/// oltc_regulated_bus（受控母线设备 ID，必填）、tap_min / tap_max（必填）、tap_neutral、tap_pos（初始档位）、
+/// tap_side（hv / lv，默认 hv）、oltc_target_vm_pu（默认 1.0）、oltc_deadband_pu（默认 0.01）、oltc_delay_s（默认 0）
+#[derive(Debug, Clone, PartialEq)]
+pub struct OltcConfig {
+    pub regulated_bus: String,
+    pub tap_min: i32,
+    pub tap_max: i32,
+    pub initial_tap: i32,
+    /// 分接头在低压侧：升档抬高低压侧电压；高压侧分接头升档降低低压侧电压
+    pub tap_on_lv_side: bool,
+    pub target_vm_pu: f64,
+    pub deadband_pu: f64,
+    pub delay_s: f64,
+}
+
+impl OltcConfig {
+    /// 解析变压器的 OLTC 配置；未配置受控母线或分接头范围时为 None（不参与调压）
+    pub fn from_properties(properties: &HashMap<String, serde_json::Value>) -> Option<Self> {
+        let regulated_bus = properties
+            .get("oltc_regulated_bus")
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|s| !s.is_empty())?
+            .to_string();
+        let tap_min = prop_f64(properties, "tap_min")?.round() as i32;
+        let tap_max = prop_f64(properties, "tap_max")?.round() as i32;
+        if tap_min >= tap_max {
+            return None;
+        }
+        let neutral = prop_f64(properties, "tap_neutral").map(|v| v.round() as i32).unwrap_or(0);
+        let initial_tap = prop_f64(properties, "tap_pos").map(|v| v.round() as i32).unwrap_or(neutral);
+        Some(Self {
+            regulated_bus,
+            tap_min,
+            tap_max,
+            initial_tap: initial_tap.clamp(tap_min, tap_max),
+            tap_on_lv_side: properties
+                .get("tap_side")
+                .and_then(|v| v.as_str())
+                .is_some_and(|s| s.eq_ignore_ascii_case("lv")),
+            target_vm_pu: prop_f64(properties, "oltc_target_vm_pu").filter(|v| *v > 0.0).unwrap_or(1.0),
+            deadband_pu: prop_f64(properties, "oltc_deadband_pu").map(f64::abs).unwrap_or(0.01),
+            delay_s: prop_f64(properties, "oltc_delay_s").filter(|v| *v > 0.0).unwrap_or(0.0),
+        })
+    }
+}
+
+/// 一次分接头动作
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TapOperation {
+    pub device_id: String,
+    pub regulated_bus: String,
+    pub from_tap: i32,
+    pub to_tap: i32,
+    /// 触发动作的受控母线电压（上一步结果）
+    pub vm_pu: f64,
+    pub target_vm_pu: f64,
+    /// 动作时的仿真时间（Unix 秒）
+    pub timestamp: f64,
+}
+
+#[derive(Default)]
+pub struct OltcController {
+    /// 当前档位（首次参与调压时取配置的初始档位）
+    tap_pos: HashMap<String, i32>,
+    /// 电压越出死区的起始仿真时间（秒，相对仿真起点）
+    out_of_band_since: HashMap<String, f64>,
+    /// 最近一步结果中的母线电压：母线设备 ID -> vm_pu（每次评估后清空，避免未出新结果时重复动作）
+    bus_vm_pu: HashMap<String, f64>,
+    operations: VecDeque<TapOperation>,
+}
+
+impl OltcController {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 新一轮仿真：清空档位、计时与动作记录
+    pub fn reset(&mut self) {
+        self.tap_pos.clear();
+        self.out_of_band_since.clear();
+        self.bus_vm_pu.clear();
+        self.operations.clear();
+    }
+
+    /// 记录本步结果中的母线电压（res_bus 按名称经结果索引对应母线设备）
+    pub fn observe(&mut self, topology: &Topology, index: &ResultIndex, results: &serde_json::Value) {
+        let Some(buses) = results.get("buses").and_then(|v| v.as_object()) else {
+            return;
+        };
+        for row in buses.values() {
+            let (Some(name), Some(vm_pu)) = (
+                row.get("name").and_then(|v| v.as_str()),
+                row.get("vm_pu").and_then(|v| v.as_f64()).filter(|v| v.is_finite()),
+            ) else {
+                continue;
+            };
+            if let Some((device_id, _)) = index.lookup(topology, "buses", name) {
+                self.bus_vm_pu.insert(device_id.clone(), vm_pu);
+            }
+        }
+    }
+
+    /// 评估各 OLTC 变压器，返回本步应执行的分接头动作（每台每步至多一档）；下发成功后须调用 commit
+    pub fn evaluate(&mut self, topology: &Topology, sim_time_s: f64, timestamp: f64) -> Vec<TapOperation> {
+        let voltages = std::mem::take(&mut self.bus_vm_pu);
+        let mut operations = Vec::new();
+        for (device_id, device) in &topology.devices {
+            if device.device_type != DeviceType::Transformer {
+                continue;
+            }
+            let Some(config) = OltcConfig::from_properties(&device.properties) else {
+                continue;
+            };
+            let Some(&vm_pu) = voltages.get(&config.regulated_bus) else {
+                continue;
+            };
+            let error = vm_pu - config.target_vm_pu;
+            if error.abs() <= config.deadband_pu {
+                self.out_of_band_since.remove(device_id);
+                continue;
+            }
+            let since = *self.out_of_band_since.entry(device_id.clone()).or_insert(sim_time_s);
+            if sim_time_s - since < config.delay_s {
+                continue;
+            }
+            // 电压偏低须抬高受控侧电压：低压侧分接头升档，高压侧分接头降档
+            let raise = error < 0.0;
+            let step = if raise == config.tap_on_lv_side { 1 } else { -1 };
+            let from_tap = *self.tap_pos.entry(device_id.clone()).or_insert(config.initial_tap);
+            let to_tap = (from_tap + step).clamp(config.tap_min, config.tap_max);
+            if to_tap == from_tap {
+                continue;
+            }
+            operations.push(TapOperation {
+                device_id: device_id.clone(),
+                regulated_bus: config.regulated_bus.clone(),
+                from_tap,
+                to_tap,
+                vm_pu,
+                target_vm_pu: config.target_vm_pu,
+                timestamp,
+            });
+        }
+        operations
+    }
+
+    /// 分接头动作已下发内核：更新档位并记录；动作后重新计时，下一档须再次满足动作延时
+    pub fn commit(&mut self, operation: TapOperation, sim_time_s: f64) {
+        self.tap_pos.insert(operation.device_id.clone(), operation.to_tap);
+        self.out_of_band_since.insert(operation.device_id.clone(), sim_time_s);
+        if self.operations.len() >= MAX_TAP_OPERATIONS {
+            self.operations.pop_front();
+        }
+        self.operations.push_back(operation);
+    }
+
+    /// 已调整过的变压器当前档位（内核重启后重新下发）
+    pub fn tap_positions(&self) -> HashMap<String, i32> {
+        self.tap_pos.clone()
+    }
+
+    pub fn operations(&self) -> Vec<TapOperation> {
+        self.operations.iter().cloned().collect()
+    }
+}
@@ -0,0 +1,189 @@
+// 局部拓扑仿真：只仿真某一分组（馈线）的子网，切断处以边界等值（平衡节点或定功率注入）代替外部网络，
+// 便于在大模型中对单条馈线快速迭代
+use crate::domain::topology::{Connection, Device, DeviceType, Topology};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 边界等值设备 id 前缀（不会出现在界面拓扑中）
+pub const BOUNDARY_DEVICE_PREFIX: &str = "__boundary_";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BoundaryMode {
+    /// 一个边界母线接平衡节点（外部电网），其余边界母线按定功率注入
+    #[default]
+    Slack,
+    /// 全部边界母线按定功率注入，子网须自带外部电网
+    Injection,
+}
+
+/// 边界母线的等值注入：按负荷约定，从子网流出为正（kW / kVar）
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct BoundaryInjection {
+    pub p_kw: f64,
+    #[serde(default)]
+    pub q_kvar: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoundaryConfig {
+    #[serde(default)]
+    pub mode: BoundaryMode,
+    /// 平衡节点所在的边界母线；未指定时取第一个边界母线
+    #[serde(default)]
+    pub slack_bus_id: Option<String>,
+    /// 平衡节点电压（pu）
+    #[serde(default = "default_vm_pu")]
+    pub vm_pu: f64,
+    /// 各边界母线的等值注入；未列出的边界母线注入为 0
+    #[serde(default)]
+    pub injections: HashMap<String, BoundaryInjection>,
+}
+
+fn default_vm_pu() -> f64 {
+    1.0
+}
+
+impl Default for BoundaryConfig {
+    fn default() -> Self {
+        Self {
+            mode: BoundaryMode::default(),
+            slack_bus_id: None,
+            vm_pu: default_vm_pu(),
+            injections: HashMap::new(),
+        }
+    }
+}
+
+impl BoundaryConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.vm_pu.is_finite() || !(0.8..=1.2).contains(&self.vm_pu) {
+            return Err(format!("平衡节点电压须在 0.8~1.2 pu 之间: {}", self.vm_pu));
+        }
+        for (bus_id, injection) in &self.injections {
+            if !injection.p_kw.is_finite() || !injection.q_kvar.is_finite() {
+                return Err(format!("边界母线 {} 的等值注入无效", bus_id));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 接入子网的一个边界等值
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoundaryEquivalent {
+    pub bus_id: String,
+    /// 等值设备 id（外部电网或负荷）
+    pub device_id: String,
+    pub mode: BoundaryMode,
+    #[serde(default)]
+    pub vm_pu: Option<f64>,
+    pub p_kw: f64,
+    pub q_kvar: f64,
+}
+
+/// 局部仿真所用的子网
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartialTopology {
+    pub group_id: String,
+    #[serde(skip)]
+    pub topology: Option<Topology>,
+    pub boundary: Vec<BoundaryEquivalent>,
+    /// 子网设备数（不含边界等值设备）
+    pub device_count: usize,
+    /// 完整拓扑中未参与本次仿真的设备数
+    pub excluded_device_count: usize,
+}
+
+fn equivalent_device(id: String, device_type: DeviceType, bus: &Device, properties: HashMap<String, serde_json::Value>) -> Device {
+    Device {
+        name: format!("边界等值 {}", bus.name),
+        id,
+        device_type,
+        properties,
+        position: None,
+        location: None,
+    }
+}
+
+fn equivalent_connection(device_id: &str, bus_id: &str) -> Connection {
+    Connection {
+        id: format!("{}conn_{}", BOUNDARY_DEVICE_PREFIX, device_id),
+        from_device_id: device_id.to_string(),
+        to_device_id: bus_id.to_string(),
+        from_port: None,
+        to_port: None,
+        connection_type: "power".to_string(),
+        properties: HashMap::new(),
+        is_active: true,
+    }
+}
+
+/// 从完整拓扑提取分组子网并接入边界等值
+pub fn build(full: &Topology, group_id: &str, config: &BoundaryConfig) -> Result<PartialTopology, String> {
+    config.validate()?;
+    let (mut sub, boundary_buses) = full.extract_group(group_id)?;
+    let device_count = sub.devices.len();
+    let has_ext_grid = sub.devices.values().any(|d| d.device_type == DeviceType::ExternalGrid);
+
+    for bus_id in config.slack_bus_id.iter().chain(config.injections.keys()) {
+        if !boundary_buses.contains(bus_id) {
+            return Err(format!("{} 不是分组 {} 的边界母线", bus_id, group_id));
+        }
+    }
+    if boundary_buses.is_empty() && !has_ext_grid {
+        return Err(format!("分组 {} 与外部网络无连接且不含外部电网，无法求解", group_id));
+    }
+    if config.mode == BoundaryMode::Injection && !has_ext_grid {
+        return Err("注入等值模式下子网须包含外部电网（平衡节点）".to_string());
+    }
+
+    let slack_bus = match config.mode {
+        BoundaryMode::Slack => config.slack_bus_id.clone().or_else(|| boundary_buses.first().cloned()),
+        BoundaryMode::Injection => None,
+    };
+    let mut boundary = Vec::with_capacity(boundary_buses.len());
+    for bus_id in &boundary_buses {
+        let bus = sub.devices[bus_id].clone();
+        let voltage_level = bus.properties.get("voltage_level").cloned();
+        let equivalent = if slack_bus.as_deref() == Some(bus_id.as_str()) {
+            let device_id = format!("{}slack_{}", BOUNDARY_DEVICE_PREFIX, bus_id);
+            let mut properties = HashMap::from([("vm_pu".to_string(), serde_json::json!(config.vm_pu))]);
+            if let Some(v) = voltage_level {
+                properties.insert("voltage_level".to_string(), v);
+            }
+            sub.devices.insert(device_id.clone(), equivalent_device(device_id.clone(), DeviceType::ExternalGrid, &bus, properties));
+            BoundaryEquivalent {
+                bus_id: bus_id.clone(),
+                device_id,
+                mode: BoundaryMode::Slack,
+                vm_pu: Some(config.vm_pu),
+                p_kw: 0.0,
+                q_kvar: 0.0,
+            }
+        } else {
+            let injection = config.injections.get(bus_id).copied().unwrap_or_default();
+            let device_id = format!("{}injection_{}", BOUNDARY_DEVICE_PREFIX, bus_id);
+            sub.devices.insert(device_id.clone(), equivalent_device(device_id.clone(), DeviceType::Load, &bus, HashMap::new()));
+            BoundaryEquivalent {
+                bus_id: bus_id.clone(),
+                device_id,
+                mode: BoundaryMode::Injection,
+                vm_pu: None,
+                p_kw: injection.p_kw,
+                q_kvar: injection.q_kvar,
+            }
+        };
+        let conn = equivalent_connection(&equivalent.device_id, bus_id);
+        sub.connections.insert(conn.id.clone(), conn);
+        boundary.push(equivalent);
+    }
+
+    Ok(PartialTopology {
+        group_id: group_id.to_string(),
+        topology: Some(sub),
+        boundary,
+        device_count,
+        excluded_device_count: full.devices.len().saturating_sub(device_count),
+    })
+}
@@ -0,0 +1,196 @@
+// 历史曲线回放（historical_data 模式的 Rust 端实现）：按设备导入 CSV 功率曲线（时间、p、q），
+// 每步按仿真经过时间在相邻数据点间线性插值，经 simulation.set_device_historical_value 下发内核；
+// 曲线时间相对首个数据点，支持循环回放与时间偏移
+use crate::services::timezone::{parse_timestamp, ImportTimezone};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 回放选项
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProfilePlaybackOptions {
+    /// 到达曲线末尾后从头循环；否则保持最后一个数据点
+    #[serde(default = "default_loop")]
+    pub loop_playback: bool,
+    /// 时间偏移（秒）：仿真起点对应曲线中的时刻，可为负（起点前保持首个数据点）
+    #[serde(default)]
+    pub time_offset_s: f64,
+    /// 循环周期（秒）；未指定时为曲线跨度加末段间隔（如逐时 24 点曲线周期为 24 小时）
+    #[serde(default)]
+    pub period_s: Option<f64>,
+    /// 无时区后缀时间戳的时区
+    #[serde(default)]
+    pub timezone: ImportTimezone,
+}
+
+fn default_loop() -> bool {
+    true
+}
+
+impl Default for ProfilePlaybackOptions {
+    fn default() -> Self {
+        Self { loop_playback: true, time_offset_s: 0.0, period_s: None, timezone: ImportTimezone::default() }
+    }
+}
+
+/// 曲线数据点：at 为相对首个数据点的秒数
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct ProfilePoint {
+    pub at: f64,
+    /// 有功（kW，与内核原生约定一致：光伏正=发电，负荷/储能/充电桩正=用电）
+    pub p_kw: f64,
+    pub q_kvar: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PowerProfile {
+    pub device_id: String,
+    /// 来源文件
+    pub file_path: String,
+    /// 首个数据点的原始时间戳（Unix 秒）
+    pub start_at: f64,
+    pub points: Vec<ProfilePoint>,
+    pub options: ProfilePlaybackOptions,
+}
+
+impl PowerProfile {
+    /// 从 CSV 导入：需含时间列（timestamp/time）与有功列（p/p_kw/power_kw），无功列（q/q_kvar）可选
+    pub fn from_csv(device_id: &str, path: &str, options: ProfilePlaybackOptions) -> Result<Self, String> {
+        options.timezone.validate()?;
+        let mut reader = csv::Reader::from_path(path).map_err(|e| format!("打开曲线文件失败: {}", e))?;
+        let headers: Vec<String> = reader
+            .headers()
+            .map_err(|e| format!("读取表头失败: {}", e))?
+            .iter()
+            .map(|h| h.trim().to_lowercase())
+            .collect();
+        let find = |names: &[&str]| headers.iter().position(|h| names.contains(&h.as_str()));
+        let time_col = find(&["timestamp", "time", "时间"]).ok_or("曲线文件缺少时间列 timestamp/time")?;
+        let p_col = find(&["p", "p_kw", "power_kw"]).ok_or("曲线文件缺少有功列 p/p_kw")?;
+        let q_col = find(&["q", "q_kvar"]);
+
+        let mut rows: Vec<(f64, f64, f64)> = Vec::new();
+        for (i, record) in reader.records().enumerate() {
+            let record = record.map_err(|e| format!("第 {} 行读取失败: {}", i + 2, e))?;
+            let raw_time = record.get(time_col).unwrap_or("");
+            let at = parse_timestamp(raw_time, &options.timezone)
+                .ok_or_else(|| format!("第 {} 行时间无法解析: {}", i + 2, raw_time))?;
+            let p_kw = record
+                .get(p_col)
+                .and_then(|v| v.trim().parse::<f64>().ok())
+                .ok_or_else(|| format!("第 {} 行有功无法解析", i + 2))?;
+            let q_kvar = match q_col.and_then(|c| record.get(c)).map(str::trim).filter(|v| !v.is_empty()) {
+                Some(v) => v.parse::<f64>().map_err(|_| format!("第 {} 行无功无法解析", i + 2))?,
+                None => 0.0,
+            };
+            rows.push((at, p_kw, q_kvar));
+        }
+        rows.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let start_at = rows.first().map(|r| r.0).ok_or("曲线为空")?;
+        let profile = Self {
+            device_id: device_id.to_string(),
+            file_path: path.to_string(),
+            start_at,
+            points: rows
+                .into_iter()
+                .map(|(at, p_kw, q_kvar)| ProfilePoint { at: at - start_at, p_kw, q_kvar })
+                .collect(),
+            options,
+        };
+        profile.validate()?;
+        Ok(profile)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.points.is_empty() {
+            return Err("曲线为空".to_string());
+        }
+        if self.points.windows(2).any(|w| w[1].at <= w[0].at) {
+            return Err("曲线时间不能重复".to_string());
+        }
+        if !self.options.time_offset_s.is_finite() {
+            return Err("时间偏移无效".to_string());
+        }
+        if let Some(period) = self.options.period_s {
+            let span = self.points.last().map(|p| p.at).unwrap_or(0.0);
+            if !(period.is_finite() && period > 0.0 && period >= span) {
+                return Err(format!("循环周期须为正且不小于曲线跨度 {:.0} 秒", span));
+            }
+        }
+        Ok(())
+    }
+
+    /// 循环周期：指定值，或曲线跨度加末段间隔；单点曲线为 None（恒定值）
+    fn period_s(&self) -> Option<f64> {
+        if let Some(period) = self.options.period_s {
+            return Some(period);
+        }
+        match self.points.as_slice() {
+            [.., a, b] => Some(b.at + (b.at - a.at)),
+            _ => None,
+        }
+    }
+
+    /// 仿真经过 elapsed_s 秒时的 (p_kw, q_kvar)：相邻数据点线性插值；循环时末点与下一周期首点之间也插值
+    pub fn value_at(&self, elapsed_s: f64) -> (f64, f64) {
+        let first = self.points[0];
+        let last = self.points[self.points.len() - 1];
+        let mut t = elapsed_s + self.options.time_offset_s;
+        // 负偏移使起点落在曲线之前：保持首个数据点，循环回放也不向前回绕
+        if t <= first.at {
+            return (first.p_kw, first.q_kvar);
+        }
+        let period = self.period_s().filter(|_| self.options.loop_playback);
+        if let Some(period) = period {
+            t = t.rem_euclid(period);
+        }
+        let Some(i) = self.points.iter().rposition(|p| p.at <= t) else {
+            return (first.p_kw, first.q_kvar);
+        };
+        let a = self.points[i];
+        let b = match (self.points.get(i + 1), period) {
+            (Some(next), _) => *next,
+            (None, Some(period)) => ProfilePoint { at: period, ..first },
+            (None, None) => return (last.p_kw, last.q_kvar),
+        };
+        if b.at <= a.at {
+            return (a.p_kw, a.q_kvar);
+        }
+        let ratio = (t - a.at) / (b.at - a.at);
+        (a.p_kw + (b.p_kw - a.p_kw) * ratio, a.q_kvar + (b.q_kvar - a.q_kvar) * ratio)
+    }
+}
+
+/// 各设备已导入的曲线；导入配置跨仿真轮次保留，回放时间取本轮仿真经过时间
+#[derive(Default)]
+pub struct ProfilePlayer {
+    profiles: HashMap<String, PowerProfile>,
+}
+
+impl ProfilePlayer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(&mut self, profile: PowerProfile) {
+        self.profiles.insert(profile.device_id.clone(), profile);
+    }
+
+    pub fn remove(&mut self, device_id: &str) -> bool {
+        self.profiles.remove(device_id).is_some()
+    }
+
+    pub fn get(&self, device_id: &str) -> Option<PowerProfile> {
+        self.profiles.get(device_id).cloned()
+    }
+
+    /// 指定设备在仿真经过 elapsed_s 秒时的设定；未导入曲线的设备跳过
+    pub fn values_at(&self, device_ids: &[String], elapsed_s: f64) -> Vec<(String, f64, f64)> {
+        device_ids
+            .iter()
+            .filter_map(|id| {
+                let (p_kw, q_kvar) = self.profiles.get(id)?.value_at(elapsed_s);
+                Some((id.clone(), p_kw, q_kvar))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,172 @@
+// 过流保护继电器：线路/变压器配置了保护开关与动作定值时，引擎每步记录电流与负载率，
+// 越过定值并持续超过动作延时（定时限）后在下一步开始前断开关联开关，保护动作记录保留供查询
+use crate::domain::topology::{prop_f64, Topology};
+use crate::services::result_index::ResultIndex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+/// 保护动作记录保留上限
+const MAX_PROTECTION_EVENTS: usize = 500;
+
+/// 线路/变压器属性中的保护配置：
+/// protection_switch（跳闸开关设备 ID，必填）、protection_pickup_ka（电流定值，线路取 i_ka、变压器取高压侧 i_hv_ka）、
+/// protection_pickup_loading_pct（负载率定值）、protection_delay_s（动作延时，默认 0 即越限当步跳闸）；两个定值至少配置一个
+#[derive(Debug, Clone, PartialEq)]
+pub struct RelayConfig {
+    pub switch_id: String,
+    pub pickup_ka: Option<f64>,
+    pub pickup_loading_pct: Option<f64>,
+    pub delay_s: f64,
+}
+
+impl RelayConfig {
+    /// 解析保护配置；未配置跳闸开关或定值时为 None（不投入保护）
+    pub fn from_properties(properties: &HashMap<String, serde_json::Value>) -> Option<Self> {
+        let switch_id = properties
+            .get("protection_switch")
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|s| !s.is_empty())?
+            .to_string();
+        let pickup_ka = prop_f64(properties, "protection_pickup_ka").filter(|v| *v > 0.0);
+        let pickup_loading_pct = prop_f64(properties, "protection_pickup_loading_pct").filter(|v| *v > 0.0);
+        if pickup_ka.is_none() && pickup_loading_pct.is_none() {
+            return None;
+        }
+        Some(Self {
+            switch_id,
+            pickup_ka,
+            pickup_loading_pct,
+            delay_s: prop_f64(properties, "protection_delay_s").filter(|v| *v > 0.0).unwrap_or(0.0),
+        })
+    }
+}
+
+/// 一次保护动作
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProtectionTrip {
+    /// 被保护的线路/变压器
+    pub device_id: String,
+    pub switch_id: String,
+    /// 动作时的电流（kA）与负载率（%），结果中缺失时为 None
+    pub i_ka: Option<f64>,
+    pub loading_percent: Option<f64>,
+    pub pickup_ka: Option<f64>,
+    pub pickup_loading_pct: Option<f64>,
+    pub delay_s: f64,
+    /// 越限开始与动作时的仿真时间（Unix 秒）
+    pub picked_up_at: f64,
+    pub timestamp: f64,
+    /// 开关是否已成功断开
+    pub ok: bool,
+    pub error: Option<String>,
+}
+
+/// 最近一步结果中的电流与负载率
+#[derive(Debug, Clone, Copy, Default)]
+struct Measurement {
+    i_ka: Option<f64>,
+    loading_percent: Option<f64>,
+}
+
+#[derive(Default)]
+pub struct ProtectionRelays {
+    /// 越限起始：设备 ID -> (相对仿真起点的秒数, 仿真时间)
+    picked_up: HashMap<String, (f64, f64)>,
+    /// 最近一步结果的测量值（每次评估后清空，避免未出新结果时重复计时）
+    measurements: HashMap<String, Measurement>,
+    events: VecDeque<ProtectionTrip>,
+}
+
+impl ProtectionRelays {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 新一轮仿真：清空计时、测量与动作记录
+    pub fn reset(&mut self) {
+        self.picked_up.clear();
+        self.measurements.clear();
+        self.events.clear();
+    }
+
+    /// 记录本步结果中配置了保护的线路/变压器的电流与负载率（按名称对应设备）
+    pub fn observe(&mut self, topology: &Topology, index: &ResultIndex, results: &serde_json::Value) {
+        for (table, current_field) in [("lines", "i_ka"), ("transformers", "i_hv_ka")] {
+            let Some(rows) = results.get(table).and_then(|v| v.as_object()) else { continue };
+            for row in rows.values() {
+                let Some(name) = row.get("name").and_then(|v| v.as_str()) else { continue };
+                let Some((device_id, device)) = index.lookup(topology, table, name) else { continue };
+                if RelayConfig::from_properties(&device.properties).is_none() {
+                    continue;
+                }
+                let field = |key: &str| row.get(key).and_then(|v| v.as_f64()).filter(|v| v.is_finite());
+                // 线路缺少 i_ka 时取首末端电流较大者
+                let i_ka = field(current_field).or_else(|| match (field("i_from_ka"), field("i_to_ka")) {
+                    (Some(a), Some(b)) => Some(a.max(b)),
+                    (a, b) => a.or(b),
+                });
+                self.measurements.insert(device_id.clone(), Measurement { i_ka, loading_percent: field("loading_percent") });
+            }
+        }
+    }
+
+    /// 评估各保护，返回本步应跳闸的保护（开关已断开的不再动作）；断开开关后须调用 record
+    pub fn evaluate(&mut self, topology: &Topology, sim_time_s: f64, timestamp: f64) -> Vec<ProtectionTrip> {
+        let measurements = std::mem::take(&mut self.measurements);
+        let mut trips = Vec::new();
+        for (device_id, measurement) in measurements {
+            let Some(config) = topology
+                .devices
+                .get(&device_id)
+                .and_then(|d| RelayConfig::from_properties(&d.properties))
+            else {
+                continue;
+            };
+            let switch_closed = topology
+                .devices
+                .get(&config.switch_id)
+                .and_then(|s| s.properties.get("is_closed"))
+                .is_none_or(|v| v.as_bool().unwrap_or(true));
+            let over = |value: Option<f64>, pickup: Option<f64>| matches!((value, pickup), (Some(v), Some(p)) if v > p);
+            let picked_up = over(measurement.i_ka, config.pickup_ka)
+                || over(measurement.loading_percent, config.pickup_loading_pct);
+            if !picked_up || !switch_closed {
+                self.picked_up.remove(&device_id);
+                continue;
+            }
+            let (since_s, picked_up_at) = *self.picked_up.entry(device_id.clone()).or_insert((sim_time_s, timestamp));
+            if sim_time_s - since_s < config.delay_s {
+                continue;
+            }
+            self.picked_up.remove(&device_id);
+            trips.push(ProtectionTrip {
+                device_id,
+                switch_id: config.switch_id,
+                i_ka: measurement.i_ka,
+                loading_percent: measurement.loading_percent,
+                pickup_ka: config.pickup_ka,
+                pickup_loading_pct: config.pickup_loading_pct,
+                delay_s: config.delay_s,
+                picked_up_at,
+                timestamp,
+                ok: false,
+                error: None,
+            });
+        }
+        trips.sort_by(|a, b| a.device_id.cmp(&b.device_id));
+        trips
+    }
+
+    /// 记录保护动作（含开关断开结果）
+    pub fn record(&mut self, trip: ProtectionTrip) {
+        if self.events.len() >= MAX_PROTECTION_EVENTS {
+            self.events.pop_front();
+        }
+        self.events.push_back(trip);
+    }
+
+    pub fn events(&self) -> Vec<ProtectionTrip> {
+        self.events.iter().cloned().collect()
+    }
+}
@@ -1,6 +1,9 @@
 // Python 通信桥接
 // 注意：stdout/stderr/stdin 使用标准同步 I/O（std::thread），
 // 因为 tokio 的异步管道读取在 Windows 匿名管道上存在延迟问题。
+// 请求经 KernelClient 发出：各调用方共享 stdin 写入端与待响应表，按请求 ID 匹配响应，
+// 互不等待（内核按序处理）；只有启动/停止/重启进程需要独占 PythonBridge。
+// Python 内核无法启动时可切换到进程内的降级内核（FallbackKernel），请求由其直接应答。
 use serde::{Deserialize, Serialize};
 use anyhow::{Result, Context};
 use std::collections::HashMap;
@@ -9,9 +12,28 @@ use std::sync::Arc;
 use tauri::path::BaseDirectory;
 use tauri::Manager;
 use std::sync::Mutex as StdMutex;
-use tokio::sync::oneshot;
+use crate::domain::events::{PythonKernelUnhealthy, EVENT_SCHEMA_VERSION};
+use crate::domain::simulation::{KernelRpcConfig, ResultTransferConfig, SimulationState};
+use crate::services::bridge_metrics::{BridgeMetrics, BridgeMetricsRecorder};
+use crate::services::fallback_kernel::FallbackKernel;
+use crate::services::kernel_log::{KernelLog, StderrClassifier};
+use crate::services::kernel_transport::{KernelEndpoint, KernelReader, KernelWriter, RemoteConnection, StreamReader, WireEncoding, KERNEL_TOKEN_ENV};
+use crate::services::settings::SettingsStore;
+use crate::services::shared_results::SharedResultFile;
+use crate::services::simulation_manager::SimulationManager;
+use crate::services::window_hub;
+use tauri::Emitter;
+use tokio::sync::{oneshot, Mutex};
 use tokio::time::{timeout, Duration};
 
+/// 编码协商超时（含内核进程启动时间）
+const NEGOTIATE_TIMEOUT: Duration = Duration::from_secs(30);
+/// 远程内核令牌认证失败的错误码（python-kernel/transport.py AUTH_ERROR_CODE）
+const KERNEL_AUTH_ERROR_CODE: i32 = -32001;
+/// 关闭时等待 kernel.shutdown 应答与进程退出的时间，超过后强制结束进程
+const SHUTDOWN_RPC_TIMEOUT: Duration = Duration::from_secs(2);
+const SHUTDOWN_EXIT_TIMEOUT: Duration = Duration::from_secs(3);
+
 #[derive(Debug, Serialize, Deserialize)]
 struct JsonRpcRequest {
     jsonrpc: String,
@@ -34,10 +56,234 @@ struct JsonRpcError {
     message: String,
 }
 
-pub struct PythonBridge {
-    stdin: Option<Arc<StdMutex<std::process::ChildStdin>>>,
+// 内核返回的错误以该类型传递，调用方据此区分内核错误（不重试）与超时、连接失败
+impl std::fmt::Display for JsonRpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "JSON-RPC error {}: {}", self.code, self.message)
+    }
+}
+
+impl std::error::Error for JsonRpcError {}
+
+type PendingRequests = Arc<StdMutex<HashMap<u64, oneshot::Sender<Result<serde_json::Value>>>>>;
+
+/// 一次启动的内核进程（或远程连接）的请求通道：写入端与待响应表（由该进程的 stdout 读取线程应答）
+#[derive(Clone)]
+struct KernelChannel {
+    writer: Arc<StdMutex<KernelWriter>>,
+    pending: PendingRequests,
+    /// 本机拉起的内核进程（可经共享文件传输结果）；远程连接为 false
+    local: bool,
+}
+
+/// 内核 JSON-RPC 客户端：可克隆、无需独占即可并发调用；进程重启后自动使用新进程的通道
+#[derive(Clone)]
+pub struct KernelClient {
+    channel: Arc<StdMutex<Option<KernelChannel>>>,
+    /// 降级内核；设置后请求不再发往 Python 进程
+    fallback: Arc<StdMutex<Option<Arc<FallbackKernel>>>>,
     request_id: Arc<std::sync::atomic::AtomicU64>,
-    pending_requests: Arc<StdMutex<HashMap<u64, oneshot::Sender<Result<serde_json::Value>>>>>,
+    /// 各方法的超时与重试策略
+    rpc: Arc<StdMutex<KernelRpcConfig>>,
+    /// 各方法的调用次数、失败与耗时统计（进程重启后继续累计）
+    metrics: Arc<BridgeMetricsRecorder>,
+    /// 单步结果的传输方式
+    transfer: Arc<StdMutex<ResultTransferConfig>>,
+    /// 共享结果文件（首次使用时创建）；持锁期间只有一个请求使用，读回前不会被下一次结果覆盖
+    shared_results: Arc<Mutex<Option<SharedResultFile>>>,
+}
+
+impl KernelClient {
+    fn new() -> Self {
+        Self {
+            channel: Arc::new(StdMutex::new(None)),
+            fallback: Arc::new(StdMutex::new(None)),
+            request_id: Arc::new(std::sync::atomic::AtomicU64::new(0)),
+            rpc: Arc::new(StdMutex::new(KernelRpcConfig::default())),
+            metrics: Arc::new(BridgeMetricsRecorder::new()),
+            transfer: Arc::new(StdMutex::new(ResultTransferConfig::default())),
+            shared_results: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn metrics(&self) -> BridgeMetrics {
+        self.metrics.snapshot()
+    }
+
+    pub fn reset_metrics(&self) {
+        self.metrics.reset();
+    }
+
+    /// 更新超时表（共享同一客户端的调用方立即生效，进行中的请求不受影响）
+    pub fn set_rpc_config(&self, config: KernelRpcConfig) {
+        *self.rpc.lock().unwrap() = config;
+    }
+
+    pub fn set_result_transfer(&self, config: ResultTransferConfig) {
+        *self.transfer.lock().unwrap() = config;
+    }
+
+    fn is_connected(&self) -> bool {
+        self.channel.lock().unwrap().is_some()
+    }
+
+    /// 是否正由降级内核应答
+    pub fn is_fallback(&self) -> bool {
+        self.fallback.lock().unwrap().is_some()
+    }
+
+    /// 按超时表调用：超时或连接失败时按该方法的重试次数重试；内核返回的错误与降级内核的应答直接返回
+    pub async fn call(&self, method: &str, params: serde_json::Value) -> Result<serde_json::Value> {
+        let (timeout_ms, retries, retry_delay_ms) = self.rpc.lock().unwrap().resolve(method);
+        let mut attempt = 0;
+        loop {
+            let result = self.call_with_timeout(method, params.clone(), Duration::from_millis(timeout_ms)).await;
+            match result {
+                Err(e) if attempt < retries && !e.is::<JsonRpcError>() && !self.is_fallback() => {
+                    attempt += 1;
+                    eprintln!("内核请求 {} 失败（{}），{} ms 后第 {} 次重试", method, e, retry_delay_ms, attempt);
+                    tokio::time::sleep(Duration::from_millis(retry_delay_ms)).await;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// 返回大块结果的调用（每步计算）：开启共享文件传输且为本机内核进程时，随请求下发共享文件路径，
+    /// 内核返回描述符 result_ref 时从文件读回并还原为 {"result": ...}；其余情况与 call 相同
+    pub async fn call_bulk(&self, method: &str, mut params: serde_json::Value) -> Result<serde_json::Value> {
+        let config = self.transfer.lock().unwrap().clone();
+        let local = self.channel.lock().unwrap().as_ref().is_some_and(|channel| channel.local);
+        if !config.shared_file || !local || self.is_fallback() {
+            return self.call(method, params).await;
+        }
+        let mut shared = self.shared_results.lock().await;
+        if shared.is_none() {
+            match SharedResultFile::create() {
+                Ok(file) => *shared = Some(file),
+                Err(e) => {
+                    eprintln!("创建共享结果文件失败，结果改为内联返回: {}", e);
+                    return self.call(method, params).await;
+                }
+            }
+        }
+        let file = shared.as_ref().expect("共享结果文件已创建");
+        if let Some(object) = params.as_object_mut() {
+            object.insert(
+                "result_file".to_string(),
+                serde_json::json!({ "path": file.path().to_string_lossy(), "min_bytes": config.min_bytes }),
+            );
+        }
+        let response = self.call(method, params).await?;
+        match response.get("result_ref") {
+            Some(descriptor) => Ok(serde_json::json!({ "result": file.read(descriptor).map_err(anyhow::Error::msg)? })),
+            None => Ok(response),
+        }
+    }
+
+    pub async fn call_with_timeout(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+        timeout_duration: Duration,
+    ) -> Result<serde_json::Value> {
+        let started = std::time::Instant::now();
+        let result = self.dispatch(method, params, timeout_duration).await;
+        let kernel_error = result.as_ref().err().is_some_and(|e| e.is::<JsonRpcError>());
+        self.metrics.record(method, started.elapsed(), result.is_ok(), kernel_error);
+        result
+    }
+
+    async fn dispatch(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+        timeout_duration: Duration,
+    ) -> Result<serde_json::Value> {
+        let fallback = self.fallback.lock().unwrap().clone();
+        if let Some(fallback) = fallback {
+            // 降级内核的潮流求解为同步计算，放到阻塞线程池，避免占用异步工作线程
+            let method = method.to_string();
+            return tokio::task::spawn_blocking(move || fallback.handle(&method, &params))
+                .await
+                .map_err(|e| anyhow::anyhow!("降级内核任务异常退出: {}", e))?
+                .map_err(anyhow::Error::msg);
+        }
+        // 取当前进程的通道；等待响应期间进程被重启时，本请求随旧待响应表清空而失败
+        let channel = self
+            .channel
+            .lock()
+            .unwrap()
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("Python process not started"))?;
+        let request_id = self.next_request_id();
+        Self::request(&channel, request_id, method, params, timeout_duration).await
+    }
+
+    fn next_request_id(&self) -> u64 {
+        self.request_id.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+    }
+
+    async fn request(
+        channel: &KernelChannel,
+        request_id: u64,
+        method: &str,
+        params: serde_json::Value,
+        timeout_duration: Duration,
+    ) -> Result<serde_json::Value> {
+        let request = serde_json::to_value(JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: request_id,
+            method: method.to_string(),
+            params,
+        })?;
+        
+        // 创建响应通道
+        let (tx, rx) = oneshot::channel();
+        channel.pending.lock().unwrap().insert(request_id, tx);
+
+        // 发送请求（整条消息在写入端锁内写入，并发请求不会交错；写入量小且管道缓冲区足够，不会阻塞）
+        let written = channel.writer.lock().unwrap().send(&request);
+        if let Err(e) = written {
+            channel.pending.lock().unwrap().remove(&request_id);
+            return Err(e.into());
+        }
+
+        // 等待响应（带超时）
+        match timeout(timeout_duration, rx).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => Err(anyhow::anyhow!("Response channel closed")),
+            Err(_) => {
+                // 超时，移除 pending 请求
+                channel.pending.lock().unwrap().remove(&request_id);
+                Err(anyhow::anyhow!("Request timeout after {} ms", timeout_duration.as_millis()))
+            }
+        }
+    }
+}
+
+/// 本地内核的启动方式
+#[derive(Debug, Clone)]
+pub struct KernelLaunch {
+    /// 打包后的内核可执行文件或 Python 解释器
+    pub executable: String,
+    /// 解释器方式时为 main.py 路径
+    pub args: Vec<String>,
+    /// 是否为打包后的可执行文件
+    pub bundled: bool,
+}
+
+pub struct PythonBridge {
+    client: KernelClient,
+    child: Option<std::process::Child>,
+    /// 本次启动的进程 stdout 已关闭（进程退出）；每次启动新建，旧读取线程不影响新进程
+    exited: Arc<std::sync::atomic::AtomicBool>,
+    /// 远程内核端点；为 None 时拉起本地进程（stdio）
+    endpoint: Option<KernelEndpoint>,
+    /// 远程连接的套接字（停止时断开）
+    remote: Option<std::net::TcpStream>,
+    /// 已关闭（应用退出中），不再启动进程，避免看门狗或计算循环在退出期间重新拉起内核
+    closed: bool,
     _stdout_thread: Option<std::thread::JoinHandle<()>>,
     _stderr_thread: Option<std::thread::JoinHandle<()>>,
 }
@@ -45,15 +291,29 @@ pub struct PythonBridge {
 impl PythonBridge {
     pub fn new() -> Self {
         Self {
-            stdin: None,
-            request_id: Arc::new(std::sync::atomic::AtomicU64::new(0)),
-            pending_requests: Arc::new(StdMutex::new(HashMap::new())),
+            client: KernelClient::new(),
+            child: None,
+            exited: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            endpoint: None,
+            remote: None,
+            closed: false,
             _stdout_thread: None,
             _stderr_thread: None,
         }
     }
 
-    pub async fn start(&mut self, app_handle: Option<&tauri::AppHandle>) -> Result<()> {
+    /// 连接远程内核（TCP/WebSocket）的桥接；None 时与 new 相同
+    pub fn with_endpoint(endpoint: Option<KernelEndpoint>) -> Self {
+        Self { endpoint, ..Self::new() }
+    }
+
+    /// 共享的请求客户端（与本桥接同一进程，重启后仍有效）
+    pub fn client(&self) -> KernelClient {
+        self.client.clone()
+    }
+
+    /// 定位本地内核：发布模式优先使用打包后的可执行文件（资源目录或文件系统），否则用 Python 解释器运行 main.py
+    pub async fn locate_kernel(app_handle: Option<&tauri::AppHandle>) -> Result<KernelLaunch> {
         // 优先尝试使用打包后的可执行文件
         let (executable_path, args) = if cfg!(not(debug_assertions)) {
             // 发布模式：优先从 bundle resources 加载，其次从文件系统查找
@@ -91,6 +351,17 @@ impl PythonBridge {
             let script_path = Self::get_python_script_path()?;
             (python_path, vec![script_path])
         };
+        Ok(KernelLaunch { bundled: args.is_empty(), executable: executable_path, args })
+    }
+
+    pub async fn start(&mut self, app_handle: Option<&tauri::AppHandle>) -> Result<()> {
+        if self.closed {
+            return Err(anyhow::anyhow!("内核已关闭（应用正在退出）"));
+        }
+        if let Some(endpoint) = self.endpoint.clone() {
+            return self.connect(endpoint).await;
+        }
+        let KernelLaunch { executable: executable_path, args, .. } = Self::locate_kernel(app_handle).await?;
 
         // 使用标准库 Command（而非 tokio::process::Command），
         // 配合同步线程读取 stdout/stderr，避免 tokio 异步管道在 Windows 上的延迟问题
@@ -98,6 +369,12 @@ impl PythonBridge {
         for arg in &args {
             cmd.arg(arg);
         }
+        // 日志写入应用共享的内核日志（按级别过滤、滚动）；未注册时（无 AppHandle）按默认配置单独写入
+        let kernel_log = app_handle
+            .and_then(|h| h.try_state::<Arc<KernelLog>>())
+            .map(|log| log.inner().clone())
+            .unwrap_or_else(|| Arc::new(KernelLog::new(Default::default())));
+        cmd.env("PYTHON_KERNEL_LOG_LEVEL", kernel_log.level().as_python());
 
         // Windows 上禁止弹出控制台窗口
         #[cfg(target_os = "windows")]
@@ -121,39 +398,26 @@ impl PythonBridge {
         let stderr = child.stderr.take()
             .ok_or_else(|| anyhow::anyhow!("Failed to get stderr"))?;
 
-        let stdin_arc = Arc::new(StdMutex::new(stdin));
-        self.stdin = Some(stdin_arc);
+        self.child = Some(child);
+        let exited = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        self.exited = exited.clone();
+        // 每个进程使用独立的待响应表，进程退出时清空（等待中的请求立即失败，不必等超时）
+        let pending: PendingRequests = Arc::new(StdMutex::new(HashMap::new()));
 
         // 启动同步线程读取 stderr 并记录日志
         let stderr_thread = std::thread::Builder::new()
             .name("python-stderr-reader".into())
             .spawn(move || {
-                use std::io::{BufRead, Write};
+                use std::io::BufRead;
                 let reader = std::io::BufReader::new(stderr);
-
-                let log_path = std::env::current_exe()
-                    .ok()
-                    .and_then(|p| p.parent().map(|p| p.join("python-kernel.log")))
-                    .unwrap_or_else(|| std::path::PathBuf::from("python-kernel.log"));
-
-                let mut log_file = std::fs::OpenOptions::new()
-                    .create(true)
-                    .append(true)
-                    .open(&log_path)
-                    .ok();
-
-                if let Some(ref mut f) = log_file {
-                    let _ = writeln!(f, "\n=== Python Kernel Started at {:?} ===", std::time::SystemTime::now());
-                }
+                let mut classifier = StderrClassifier::default();
+                kernel_log.mark_started();
 
                 for line in reader.lines() {
                     match line {
                         Ok(line) => {
                             eprintln!("[Python stderr] {}", line);
-                            if let Some(ref mut f) = log_file {
-                                let _ = writeln!(f, "{}", line);
-                                let _ = f.flush();
-                            }
+                            kernel_log.append(classifier.level(&line), &line);
                         }
                         Err(_) => break,
                     }
@@ -163,31 +427,109 @@ impl PythonBridge {
         self._stderr_thread = Some(stderr_thread);
 
         // 启动同步线程读取 stdout（解决 tokio 在 Windows 管道上的异步读取延迟问题）
-        let pending = self.pending_requests.clone();
+        let reader: KernelReader = Box::new(StreamReader::new(std::io::BufReader::new(stdout)));
+        let negotiate_id = self.client.next_request_id();
+        self._stdout_thread = Some(Self::spawn_reader(reader, pending.clone(), exited, negotiate_id)?);
+        let channel = KernelChannel {
+            writer: Arc::new(StdMutex::new(KernelWriter::pipe(stdin))),
+            pending,
+            local: true,
+        };
+        Self::negotiate(&channel, negotiate_id).await?;
+        *self.client.channel.lock().unwrap() = Some(channel);
+        *self.client.fallback.lock().unwrap() = None;
 
-        let stdout_thread = std::thread::Builder::new()
-            .name("python-stdout-reader".into())
-            .spawn(move || {
-                use std::io::BufRead;
-                let reader = std::io::BufReader::new(stdout);
+        Ok(())
+    }
 
-                for line in reader.lines() {
-                    match line {
-                        Ok(line) => {
-                            if line.trim().is_empty() {
-                                continue;
-                            }
+    /// 协商消息编码（通道发布前进行，此间没有其他请求）；旧版内核不支持协商时沿用 JSON。
+    /// 远程内核同时以共享令牌认证，认证失败时中止连接
+    async fn negotiate(channel: &KernelChannel, negotiate_id: u64) -> Result<()> {
+        let offered: Vec<&str> = WireEncoding::offered().iter().map(|e| e.as_str()).collect();
+        let mut params = serde_json::json!({ "encodings": offered });
+        if !channel.local {
+            let token = std::env::var(KERNEL_TOKEN_ENV).ok().filter(|t| !t.is_empty());
+            params["token"] = serde_json::json!(token);
+        }
+        let encoding = match KernelClient::request(channel, negotiate_id, "kernel.negotiate", params, NEGOTIATE_TIMEOUT).await {
+            Err(e) if e.downcast_ref::<JsonRpcError>().is_some_and(|e| e.code == KERNEL_AUTH_ERROR_CODE) => {
+                return Err(e.context(format!("远程内核认证失败，请检查环境变量 {}", KERNEL_TOKEN_ENV)));
+            }
+            Ok(result) => result
+                .get("encoding")
+                .and_then(|v| v.as_str())
+                .and_then(WireEncoding::parse)
+                .unwrap_or(WireEncoding::Json),
+            Err(e) if e.to_string().starts_with("JSON-RPC error") => WireEncoding::Json,
+            Err(e) => return Err(e.context("内核编码协商失败")),
+        };
+        channel.writer.lock().unwrap().set_encoding(encoding);
+        eprintln!("内核消息编码: {}", encoding.as_str());
+        Ok(())
+    }
 
+    /// 连接远程内核（不拉起本地进程、无 stderr 日志）；连接断开视同进程退出
+    async fn connect(&mut self, endpoint: KernelEndpoint) -> Result<()> {
+        eprintln!("连接远程 Python 内核: {}", endpoint);
+        let connection = tokio::task::spawn_blocking(move || RemoteConnection::connect(&endpoint))
+            .await
+            .context("Remote kernel connect task failed")?
+            .context("Failed to connect remote Python kernel")?;
+        let exited = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        self.exited = exited.clone();
+        let pending: PendingRequests = Arc::new(StdMutex::new(HashMap::new()));
+        let negotiate_id = self.client.next_request_id();
+        self._stdout_thread = Some(Self::spawn_reader(connection.reader, pending.clone(), exited, negotiate_id)?);
+        self.remote = Some(connection.socket);
+        let channel = KernelChannel {
+            writer: Arc::new(StdMutex::new(connection.writer)),
+            pending,
+            local: false,
+        };
+        Self::negotiate(&channel, negotiate_id).await?;
+        *self.client.channel.lock().unwrap() = Some(channel);
+        *self.client.fallback.lock().unwrap() = None;
+        Ok(())
+    }
+
+    /// 响应读取线程：按请求 ID 应答等待中的调用；收到协商请求（negotiate_id）的响应后切换为协商的编码。
+    /// 连接关闭后标记退出并清空待响应表（等待中的请求立即失败）
+    fn spawn_reader(
+        mut reader: KernelReader,
+        pending: PendingRequests,
+        exited: Arc<std::sync::atomic::AtomicBool>,
+        negotiate_id: u64,
+    ) -> Result<std::thread::JoinHandle<()>> {
+        std::thread::Builder::new()
+            .name("python-stdout-reader".into())
+            .spawn(move || {
+                let mut encoding = WireEncoding::Json;
+                loop {
+                    match reader.next_message() {
+                        Ok(Some(message)) => {
                             // 解析 JSON-RPC 响应
-                            match serde_json::from_str::<JsonRpcResponse>(&line) {
+                            let decoded = encoding
+                                .decode(&message)
+                                .and_then(|value| serde_json::from_value::<JsonRpcResponse>(value).map_err(|e| e.to_string()));
+                            match decoded {
                                 Ok(response) => {
+                                    if response.id == Some(negotiate_id) {
+                                        let negotiated = response
+                                            .result
+                                            .as_ref()
+                                            .and_then(|r| r.get("encoding"))
+                                            .and_then(|v| v.as_str())
+                                            .and_then(WireEncoding::parse);
+                                        if let Some(next) = negotiated {
+                                            encoding = next;
+                                            reader.set_encoding(next);
+                                        }
+                                    }
                                     if let Some(id) = response.id {
                                         let mut pending = pending.lock().unwrap();
                                         if let Some(sender) = pending.remove(&id) {
                                             let _ = if let Some(error) = response.error {
-                                                sender.send(Err(anyhow::anyhow!(
-                                                    "JSON-RPC error {}: {}", error.code, error.message
-                                                )))
+                                                sender.send(Err(error.into()))
                                             } else if let Some(result) = response.result {
                                                 sender.send(Ok(result))
                                             } else {
@@ -197,73 +539,192 @@ impl PythonBridge {
                                     }
                                 }
                                 Err(e) => {
-                                    eprintln!("Failed to parse JSON-RPC response: {} - {}", e, line);
+                                    eprintln!("Failed to parse JSON-RPC response: {} - {}", e, String::from_utf8_lossy(&message));
                                 }
                             }
                         }
-                        Err(_) => break,
+                        Ok(None) | Err(_) => break,
                     }
                 }
+                exited.store(true, std::sync::atomic::Ordering::SeqCst);
+                pending.lock().unwrap().clear();
             })
-            .context("Failed to spawn stdout reader thread")?;
-        self._stdout_thread = Some(stdout_thread);
-        self.request_id.store(0, std::sync::atomic::Ordering::Relaxed);
-
-        Ok(())
+            .context("Failed to spawn stdout reader thread")
     }
 
     pub async fn stop(&mut self) -> Result<()> {
         // 释放 stdin 会导致 Python 进程收到 EOF 并退出，
-        // 进而 stdout/stderr 关闭，读取线程自然结束
-        self.stdin = None;
+        // 进而 stdout/stderr 关闭，读取线程自然结束；远程连接则主动断开（远程内核继续等待下一个连接）
+        *self.client.channel.lock().unwrap() = None;
+        self.child = None;
+        if let Some(socket) = self.remote.take() {
+            let _ = socket.shutdown(std::net::Shutdown::Both);
+        }
         Ok(())
     }
 
-    pub async fn call(&mut self, method: &str, params: serde_json::Value) -> Result<serde_json::Value> {
-        let request_id = self.request_id.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-
-        let request = JsonRpcRequest {
-            jsonrpc: "2.0".to_string(),
-            id: request_id,
-            method: method.to_string(),
-            params,
+    /// 关闭内核（应用退出时）：本地进程先发送 kernel.shutdown 使其停止仿真后自行退出，
+    /// 未应答或限时内未退出则强制结束，避免残留 python-kernel 进程；远程内核只断开连接，降级内核无进程可关
+    pub async fn shutdown(&mut self) -> Result<()> {
+        self.closed = true;
+        if self.child.is_some() {
+            if let Err(e) = self.client.call_with_timeout("kernel.shutdown", serde_json::json!({}), SHUTDOWN_RPC_TIMEOUT).await {
+                eprintln!("内核未应答 kernel.shutdown: {}", e);
+            }
+        }
+        let child = self.child.take();
+        self.stop().await?;
+        let Some(mut child) = child else {
+            return Ok(());
         };
-
-        let request_json = serde_json::to_string(&request)?;
-        
-        // 创建响应通道
-        let (tx, rx) = oneshot::channel();
-        {
-            let mut pending = self.pending_requests.lock().unwrap();
-            pending.insert(request_id, tx);
+        let deadline = tokio::time::Instant::now() + SHUTDOWN_EXIT_TIMEOUT;
+        loop {
+            match child.try_wait() {
+                Ok(Some(_)) => return Ok(()),
+                Ok(None) if tokio::time::Instant::now() < deadline => {
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                }
+                _ => {
+                    eprintln!("内核进程未在限时内退出，强制结束");
+                    child.kill().context("强制结束内核进程失败")?;
+                    let _ = child.wait();
+                    return Ok(());
+                }
+            }
         }
+    }
 
-        // 发送请求（同步写入管道，写入量小且管道缓冲区足够，不会阻塞）
-        if let Some(ref stdin) = self.stdin {
-            use std::io::Write;
-            let mut stdin = stdin.lock().unwrap();
-            stdin.write_all(request_json.as_bytes())?;
-            stdin.write_all(b"\n")?;
-            stdin.flush()?;
-        } else {
-            return Err(anyhow::anyhow!("Python process not started"));
+    /// 切换到降级内核：停止（可能半启动的）内核进程，此后请求由内置辐射网求解器在进程内应答
+    pub async fn enable_fallback(&mut self) -> Result<()> {
+        self.stop().await?;
+        *self.client.fallback.lock().unwrap() = Some(Arc::new(FallbackKernel::new()));
+        Ok(())
+    }
+
+    /// 内核进程是否仍在运行（已启动且未退出）；用于运行中检测进程崩溃。降级内核始终可用
+    pub fn is_alive(&mut self) -> bool {
+        if self.client.is_fallback() {
+            return true;
+        }
+        if !self.client.is_connected() || self.exited.load(std::sync::atomic::Ordering::SeqCst) {
+            return false;
         }
+        match self.child.as_mut() {
+            Some(child) => matches!(child.try_wait(), Ok(None)),
+            None => self.remote.is_some(),
+        }
+    }
 
-        // 等待响应（带超时）
-        let timeout_duration = if method == "simulation.set_topology" {
-            Duration::from_secs(60)  // 设置拓扑可能需要更长时间（首次加载库）
-        } else {
-            Duration::from_secs(10)  // 普通操作 10 秒超时
-        };
-        
-        match timeout(timeout_duration, rx).await {
-            Ok(Ok(result)) => result,
-            Ok(Err(_)) => Err(anyhow::anyhow!("Response channel closed")),
-            Err(_) => {
-                // 超时，移除 pending 请求
-                let mut pending = self.pending_requests.lock().unwrap();
-                pending.remove(&request_id);
-                Err(anyhow::anyhow!("Request timeout after {} seconds", timeout_duration.as_secs()))
+    pub async fn call(&self, method: &str, params: serde_json::Value) -> Result<serde_json::Value> {
+        self.client.call(method, params).await
+    }
+
+    pub async fn call_with_timeout(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+        timeout_duration: Duration,
+    ) -> Result<serde_json::Value> {
+        self.client.call_with_timeout(method, params, timeout_duration).await
+    }
+
+    /// 启动内核看门狗（主内核）：按设置中的间隔 ping 内核，连续失败达到阈值时推送 python-kernel-unhealthy，
+    /// 开启自动重启时重启进程，失败后按退避间隔（逐次翻倍至上限）重试。仿真运行或暂停中由计算循环负责崩溃重启
+    /// （需重新下发拓扑与设备模式），看门狗跳过；内核正在启动或重启（桥接被占用）时本轮跳过
+    pub fn spawn_watchdog(bridge: Arc<Mutex<PythonBridge>>, app: tauri::AppHandle) {
+        tauri::async_runtime::spawn(async move {
+            let mut failures: u32 = 0;
+            let mut backoff_ms: u64 = 0;
+            let mut next_restart_at: Option<std::time::Instant> = None;
+            loop {
+                let config = app
+                    .try_state::<SettingsStore>()
+                    .map(|s| s.kernel_watchdog())
+                    .unwrap_or_default();
+                tokio::time::sleep(Duration::from_millis(config.interval_ms.max(500))).await;
+                let simulating = match app.try_state::<SimulationManager>().and_then(|m| m.get(None).ok()) {
+                    Some(engine) => engine.get_status().await.state != SimulationState::Stopped,
+                    None => false,
+                };
+                if !config.enabled || simulating {
+                    failures = 0;
+                    continue;
+                }
+                let Ok(mut guard) = bridge.try_lock() else { continue };
+                let probe = if guard.is_alive() {
+                    guard
+                        .call_with_timeout("ping", serde_json::json!({}), Duration::from_millis(config.ping_timeout_ms))
+                        .await
+                        .map(|_| ())
+                } else {
+                    Err(anyhow::anyhow!("内核进程未运行"))
+                };
+                let error = match probe {
+                    Ok(()) => {
+                        failures = 0;
+                        backoff_ms = 0;
+                        next_restart_at = None;
+                        continue;
+                    }
+                    Err(e) => e.to_string(),
+                };
+                failures += 1;
+                if failures < config.failure_threshold {
+                    continue;
+                }
+                let now = std::time::Instant::now();
+                let restarting = config.auto_restart && next_restart_at.is_none_or(|at| now >= at);
+                // 达到阈值时推送一次，此后仅在尝试重启时推送
+                if failures == config.failure_threshold || restarting {
+                    window_hub::publish_typed(&app, None, PythonKernelUnhealthy {
+                        schema_version: EVENT_SCHEMA_VERSION,
+                        consecutive_failures: failures,
+                        error: error.clone(),
+                        restarting,
+                        next_restart_in_ms: next_restart_at
+                            .filter(|_| config.auto_restart && !restarting)
+                            .map(|at| at.saturating_duration_since(now).as_millis() as u64),
+                    });
+                }
+                if !restarting {
+                    continue;
+                }
+                eprintln!("Python 内核无响应（连续 {} 次）: {}，正在重启", failures, error);
+                match guard.restart(&app).await {
+                    Ok(()) => {
+                        eprintln!("Python 内核已由看门狗重启");
+                        failures = 0;
+                        backoff_ms = 0;
+                        next_restart_at = None;
+                        let _ = app.emit("python-kernel-ready", ());
+                    }
+                    Err(e) => {
+                        backoff_ms = if backoff_ms == 0 {
+                            config.backoff_initial_ms
+                        } else {
+                            (backoff_ms * 2).min(config.backoff_max_ms)
+                        };
+                        next_restart_at = Some(std::time::Instant::now() + Duration::from_millis(backoff_ms));
+                        eprintln!("看门狗重启 Python 内核失败: {}，{} ms 后重试", e, backoff_ms);
+                    }
+                }
+            }
+        });
+    }
+
+    /// 重启内核进程并等待 ping 就绪
+    async fn restart(&mut self, app: &tauri::AppHandle) -> Result<()> {
+        self.stop().await?;
+        self.start(Some(app)).await?;
+        let mut retries = 10;
+        loop {
+            match self.call_with_timeout("ping", serde_json::json!({}), Duration::from_secs(2)).await {
+                Ok(_) => return Ok(()),
+                Err(e) if retries <= 1 => return Err(e.context("内核进程未就绪")),
+                Err(_) => {
+                    retries -= 1;
+                    tokio::time::sleep(Duration::from_millis(500)).await;
+                }
             }
         }
     }
@@ -407,7 +868,10 @@ impl PythonBridge {
 
 impl Drop for PythonBridge {
     fn drop(&mut self) {
-        // 注意：在 Drop 中不能使用 async，所以这里只是清理资源
-        // 实际的停止操作应该在外部显式调用 stop()
+        // 注意：在 Drop 中不能使用 async，优雅关闭应在外部显式调用 shutdown()；
+        // 这里只兜底强制结束仍在运行的进程（例如未经关闭即被丢弃的并行求解内核）
+        if let Some(child) = self.child.as_mut() {
+            let _ = child.kill();
+        }
     }
 }
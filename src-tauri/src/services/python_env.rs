@@ -0,0 +1,220 @@
+// Python 环境自检：按启动内核的同一规则定位内核（打包的可执行文件或解释器 + main.py），
+// 以 --check-environment 运行一次读取解释器与依赖版本（远程内核经 kernel.environment 查询），
+// 汇总为逐项诊断与修复建议，供界面在开始仿真前显示
+use crate::services::kernel_transport::KernelEndpoint;
+use crate::services::python_bridge::{KernelClient, PythonBridge};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// 自检进程（含导入 pandapower）的时限
+const CHECK_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Ok,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EnvironmentCheck {
+    pub item: String,
+    pub status: CheckStatus,
+    pub detail: String,
+    /// 修复建议
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+}
+
+/// 依赖包版本（由内核报告）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackageStatus {
+    pub name: String,
+    pub minimum: String,
+    pub optional: bool,
+    pub version: Option<String>,
+    pub ok: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct KernelEnvironment {
+    python_version: String,
+    executable: String,
+    frozen: bool,
+    packages: Vec<PackageStatus>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EnvironmentReport {
+    /// 没有错误级别的检查项，可以开始仿真
+    pub ready: bool,
+    /// "bundled" | "script" | "remote"
+    pub mode: String,
+    pub executable: Option<String>,
+    pub script: Option<String>,
+    pub endpoint: Option<String>,
+    pub python_version: Option<String>,
+    pub packages: Vec<PackageStatus>,
+    pub checks: Vec<EnvironmentCheck>,
+}
+
+impl EnvironmentReport {
+    fn push(&mut self, item: &str, status: CheckStatus, detail: String, hint: Option<String>) {
+        self.checks.push(EnvironmentCheck { item: item.to_string(), status, detail, hint });
+    }
+}
+
+/// 执行自检；endpoint 为设置中的远程内核端点，kernel 为主内核客户端（远程内核与降级状态由其查询）
+pub async fn check(app: &tauri::AppHandle, endpoint: Option<KernelEndpoint>, kernel: &KernelClient) -> EnvironmentReport {
+    let mut report = EnvironmentReport {
+        ready: false,
+        mode: "script".to_string(),
+        executable: None,
+        script: None,
+        endpoint: endpoint.as_ref().map(|e| e.to_string()),
+        python_version: None,
+        packages: Vec::new(),
+        checks: Vec::new(),
+    };
+
+    let environment = if let Some(endpoint) = endpoint {
+        report.mode = "remote".to_string();
+        let queried = kernel
+            .call_with_timeout("kernel.environment", serde_json::json!({}), CHECK_TIMEOUT)
+            .await
+            .map_err(|e| e.to_string())
+            .and_then(|value| serde_json::from_value::<KernelEnvironment>(value).map_err(|e| format!("应答格式无效: {}", e)));
+        match queried {
+            Ok(environment) => {
+                report.push("远程内核", CheckStatus::Ok, format!("已连接 {}", endpoint), None);
+                Some(environment)
+            }
+            Err(e) => {
+                report.push(
+                    "远程内核",
+                    CheckStatus::Error,
+                    format!("无法读取远程内核 {} 的运行环境: {}", endpoint, e),
+                    Some("确认远程内核已以 --listen 启动且端点可达；更换端点后须重启应用".to_string()),
+                );
+                None
+            }
+        }
+    } else {
+        match PythonBridge::locate_kernel(Some(app)).await {
+            Ok(launch) => {
+                report.mode = if launch.bundled { "bundled" } else { "script" }.to_string();
+                report.executable = Some(launch.executable.clone());
+                report.script = launch.args.first().cloned();
+                let detail = match &report.script {
+                    Some(script) => format!("{} {}", launch.executable, script),
+                    None => launch.executable.clone(),
+                };
+                report.push("内核程序", CheckStatus::Ok, detail, None);
+                match run_self_check(&launch.executable, &launch.args).await {
+                    Ok(environment) => Some(environment),
+                    Err(e) => {
+                        let hint = if launch.bundled {
+                            "内核可执行文件无法运行：请重新安装应用，或在项目根执行 python pack_python_kernel.py 重新打包内核".to_string()
+                        } else {
+                            format!("确认解释器可用（{} --version），或在项目根执行 python setup_venv.py 重建 venv-dev", launch.executable)
+                        };
+                        report.push("内核自检", CheckStatus::Error, e, Some(hint));
+                        None
+                    }
+                }
+            }
+            Err(e) => {
+                report.push(
+                    "内核程序",
+                    CheckStatus::Error,
+                    format!("未找到 Python 内核: {}", e),
+                    Some("在项目根执行 python setup_venv.py 创建 venv-dev 并安装依赖；发布版请确认安装目录下的 python-kernel 完整".to_string()),
+                );
+                None
+            }
+        }
+    };
+
+    if let Some(environment) = environment {
+        report.push(
+            "Python",
+            CheckStatus::Ok,
+            format!("{}（{}）", environment.python_version, environment.executable),
+            None,
+        );
+        for package in &environment.packages {
+            let (status, detail) = match (&package.version, package.ok) {
+                (Some(version), true) => (CheckStatus::Ok, format!("{} {}", package.name, version)),
+                (Some(version), false) => (
+                    severity(package),
+                    format!("{} {} 低于最低版本 {}", package.name, version, package.minimum),
+                ),
+                (None, _) => (
+                    severity(package),
+                    format!("{} 未安装或无法导入: {}", package.name, package.error.as_deref().unwrap_or("未知错误")),
+                ),
+            };
+            let hint = (status != CheckStatus::Ok).then(|| {
+                let mut hint = if environment.frozen {
+                    "重新打包内核（python pack_python_kernel.py）以包含所需依赖".to_string()
+                } else {
+                    format!("{} -m pip install \"{}>={}\"", environment.executable, package.name, package.minimum)
+                };
+                if package.optional {
+                    hint.push_str("；未安装时内核通信使用 JSON 编码，大电网传输较慢");
+                }
+                hint
+            });
+            report.push(&package.name, status, detail, hint);
+        }
+        report.python_version = Some(environment.python_version);
+        report.packages = environment.packages;
+    }
+
+    if kernel.is_fallback() {
+        report.push(
+            "当前内核",
+            CheckStatus::Warning,
+            "Python 内核未能启动，正在使用内置辐射网降级内核".to_string(),
+            Some("修复以上问题后重启应用以使用完整的 pandapower 内核".to_string()),
+        );
+    }
+    report.ready = report.checks.iter().all(|c| c.status != CheckStatus::Error);
+    report
+}
+
+fn severity(package: &PackageStatus) -> CheckStatus {
+    if package.optional {
+        CheckStatus::Warning
+    } else {
+        CheckStatus::Error
+    }
+}
+
+/// 以 --check-environment 运行内核并解析其输出的 JSON（最后一行）
+async fn run_self_check(executable: &str, args: &[String]) -> Result<KernelEnvironment, String> {
+    let mut cmd = tokio::process::Command::new(executable);
+    cmd.args(args).arg("--check-environment").kill_on_drop(true);
+    #[cfg(target_os = "windows")]
+    {
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    }
+    let output = tokio::time::timeout(CHECK_TIMEOUT, cmd.output())
+        .await
+        .map_err(|_| format!("内核自检超过 {} 秒未完成", CHECK_TIMEOUT.as_secs()))?
+        .map_err(|e| format!("无法运行内核: {}", e))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let last_line = stdout.lines().rev().find(|l| !l.trim().is_empty());
+    match last_line.map(serde_json::from_str::<KernelEnvironment>) {
+        Some(Ok(environment)) => Ok(environment),
+        _ => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let lines: Vec<&str> = stderr.lines().collect();
+            let tail = lines[lines.len().saturating_sub(5)..].join(" | ");
+            Err(format!("内核自检未返回结果（退出状态 {}）: {}", output.status, tail))
+        }
+    }
+}
@@ -0,0 +1,250 @@
+// 内置辐射网潮流（前推回代）：Python 内核不可用时的降级求解器。
+// 只支持由单一平衡节点供电的辐射状网络：回代由末端向电源累加支路电流，前推由电源向末端计算电压降；
+// 功率按恒功率注入，线路不计对地电容，变压器不计励磁支路。系统基准容量 1 MVA，标幺功率数值即 MW/Mvar
+use std::collections::VecDeque;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Complex {
+    pub re: f64,
+    pub im: f64,
+}
+
+impl Complex {
+    pub fn new(re: f64, im: f64) -> Self {
+        Self { re, im }
+    }
+
+    pub fn conj(self) -> Self {
+        Self::new(self.re, -self.im)
+    }
+
+    pub fn norm(self) -> f64 {
+        self.re.hypot(self.im)
+    }
+
+    pub fn arg_degrees(self) -> f64 {
+        self.im.atan2(self.re).to_degrees()
+    }
+
+    fn scale(self, k: f64) -> Self {
+        Self::new(self.re * k, self.im * k)
+    }
+}
+
+impl Add for Complex {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.re + rhs.re, self.im + rhs.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.re - rhs.re, self.im - rhs.im)
+    }
+}
+
+impl Neg for Complex {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self::new(-self.re, -self.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        Self::new(self.re * rhs.re - self.im * rhs.im, self.re * rhs.im + self.im * rhs.re)
+    }
+}
+
+impl Div for Complex {
+    type Output = Self;
+    fn div(self, rhs: Self) -> Self {
+        let d = rhs.re * rhs.re + rhs.im * rhs.im;
+        Self::new((self.re * rhs.re + self.im * rhs.im) / d, (self.im * rhs.re - self.re * rhs.im) / d)
+    }
+}
+
+/// 母线：额定电压与净负荷（MW + j Mvar，消耗为正、发电为负）
+#[derive(Debug, Clone)]
+pub struct RadialBus {
+    pub vn_kv: f64,
+    pub load: Complex,
+}
+
+/// 支路：from 端为理想变比 ratio（V_from / ratio 后经串联阻抗到 to 端），线路 ratio = 1
+#[derive(Debug, Clone)]
+pub struct RadialBranch {
+    pub from: usize,
+    pub to: usize,
+    /// 串联阻抗（系统基准标幺值，按 to 端电压基准）
+    pub z_pu: Complex,
+    pub ratio: f64,
+    pub in_service: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct RadialNetwork {
+    pub buses: Vec<RadialBus>,
+    pub branches: Vec<RadialBranch>,
+    pub slack: usize,
+    pub slack_vm_pu: f64,
+}
+
+/// 支路两端流入支路的功率（MW + j Mvar）与电流（kA）
+#[derive(Debug, Clone, Copy)]
+pub struct BranchFlow {
+    pub s_from: Complex,
+    pub s_to: Complex,
+    pub i_from_ka: f64,
+    pub i_to_ka: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct RadialSolution {
+    pub converged: bool,
+    pub iterations: u32,
+    /// 母线电压（标幺值）；与平衡节点不连通的母线为 None
+    pub voltages: Vec<Option<Complex>>,
+    /// 支路潮流；停运或不带电的支路为 None
+    pub flows: Vec<Option<BranchFlow>>,
+    /// 平衡节点（外部电网）注入网络的功率
+    pub slack_power: Complex,
+}
+
+/// 由平衡节点出发的生成树中，母线经哪条支路接到父母线
+#[derive(Debug, Clone, Copy)]
+struct TreeLink {
+    parent: usize,
+    branch: usize,
+    /// 父母线为支路 from 端
+    downstream: bool,
+}
+
+/// 基准电流（kA）：1 MVA / (√3 · U)
+fn base_current_ka(vn_kv: f64) -> f64 {
+    1.0 / (3f64.sqrt() * vn_kv)
+}
+
+/// 广度优先建立生成树；带电部分出现环网时报错
+fn build_tree(network: &RadialNetwork) -> Result<(Vec<usize>, Vec<Option<TreeLink>>), String> {
+    let n = network.buses.len();
+    let mut adjacency: Vec<Vec<usize>> = vec![Vec::new(); n];
+    for (index, branch) in network.branches.iter().enumerate().filter(|(_, b)| b.in_service) {
+        adjacency[branch.from].push(index);
+        adjacency[branch.to].push(index);
+    }
+    let mut order = vec![network.slack];
+    let mut links: Vec<Option<TreeLink>> = vec![None; n];
+    let mut visited = vec![false; n];
+    let mut used = vec![false; network.branches.len()];
+    visited[network.slack] = true;
+    let mut queue = VecDeque::from([network.slack]);
+    while let Some(bus) = queue.pop_front() {
+        for &index in &adjacency[bus] {
+            if used[index] {
+                continue;
+            }
+            used[index] = true;
+            let branch = &network.branches[index];
+            let (child, downstream) = if branch.from == bus { (branch.to, true) } else { (branch.from, false) };
+            if child == bus {
+                continue;
+            }
+            if visited[child] {
+                return Err("网络存在环网，内置求解器仅支持辐射状网络".to_string());
+            }
+            visited[child] = true;
+            links[child] = Some(TreeLink { parent: bus, branch: index, downstream });
+            order.push(child);
+            queue.push_back(child);
+        }
+    }
+    Ok((order, links))
+}
+
+/// 由父母线电压与流入子树的电流求子母线电压，返回 (子母线电压, 父母线侧电流)
+fn step_down(branch: &RadialBranch, link: &TreeLink, v_parent: Complex, i_child: Complex) -> (Complex, Complex) {
+    if link.downstream {
+        (v_parent.scale(1.0 / branch.ratio) - branch.z_pu * i_child, i_child.scale(1.0 / branch.ratio))
+    } else {
+        let i_series = i_child.scale(branch.ratio);
+        ((v_parent - branch.z_pu * i_series).scale(branch.ratio), i_series)
+    }
+}
+
+/// 前推回代求解；max_iteration 次内电压最大变化小于 tolerance_pu 视为收敛
+pub fn solve(network: &RadialNetwork, max_iteration: u32, tolerance_pu: f64) -> Result<RadialSolution, String> {
+    let n = network.buses.len();
+    if network.slack >= n {
+        return Err("平衡节点无效".to_string());
+    }
+    let (order, links) = build_tree(network)?;
+    let slack_v = Complex::new(network.slack_vm_pu, 0.0);
+    let mut voltages = vec![slack_v; n];
+    // 初值：空载电压（仅计变比）
+    for &bus in order.iter().skip(1) {
+        let link = links[bus].expect("生成树母线必有父支路");
+        voltages[bus] = step_down(&network.branches[link.branch], &link, voltages[link.parent], Complex::default()).0;
+    }
+
+    let mut subtree_current = vec![Complex::default(); n];
+    let mut parent_current = vec![Complex::default(); n];
+    let mut converged = false;
+    let mut iterations = 0;
+    while iterations < max_iteration.max(1) {
+        iterations += 1;
+        // 回代：由末端向电源累加子树电流
+        subtree_current.iter_mut().for_each(|i| *i = Complex::default());
+        for &bus in order.iter().rev() {
+            let injected = (network.buses[bus].load / voltages[bus]).conj();
+            subtree_current[bus] = subtree_current[bus] + injected;
+            if let Some(link) = links[bus] {
+                let branch = &network.branches[link.branch];
+                let (_, i_parent) = step_down(branch, &link, voltages[link.parent], subtree_current[bus]);
+                parent_current[bus] = i_parent;
+                subtree_current[link.parent] = subtree_current[link.parent] + i_parent;
+            }
+        }
+        // 前推：由电源向末端更新电压
+        let mut max_change: f64 = 0.0;
+        for &bus in order.iter().skip(1) {
+            let link = links[bus].expect("生成树母线必有父支路");
+            let (v, _) = step_down(&network.branches[link.branch], &link, voltages[link.parent], subtree_current[bus]);
+            max_change = max_change.max((v - voltages[bus]).norm());
+            voltages[bus] = v;
+        }
+        if !voltages.iter().all(|v| v.re.is_finite() && v.im.is_finite()) {
+            break;
+        }
+        if max_change < tolerance_pu {
+            converged = true;
+            break;
+        }
+    }
+
+    let mut solved: Vec<Option<Complex>> = vec![None; n];
+    for &bus in &order {
+        solved[bus] = Some(voltages[bus]);
+    }
+    let mut flows: Vec<Option<BranchFlow>> = vec![None; network.branches.len()];
+    for &bus in order.iter().skip(1) {
+        let link = links[bus].expect("生成树母线必有父支路");
+        let v_parent = voltages[link.parent];
+        let v_child = voltages[bus];
+        let s_parent = v_parent * parent_current[bus].conj();
+        let s_child = -(v_child * subtree_current[bus].conj());
+        let i_parent_ka = parent_current[bus].norm() * base_current_ka(network.buses[link.parent].vn_kv);
+        let i_child_ka = subtree_current[bus].norm() * base_current_ka(network.buses[bus].vn_kv);
+        flows[link.branch] = Some(if link.downstream {
+            BranchFlow { s_from: s_parent, s_to: s_child, i_from_ka: i_parent_ka, i_to_ka: i_child_ka }
+        } else {
+            BranchFlow { s_from: s_child, s_to: s_parent, i_from_ka: i_child_ka, i_to_ka: i_parent_ka }
+        });
+    }
+    let slack_power = slack_v * subtree_current[network.slack].conj();
+    Ok(RadialSolution { converged, iterations, voltages: solved, flows, slack_power })
+}
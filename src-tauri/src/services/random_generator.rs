@@ -0,0 +1,156 @@
+// 随机数据源生成器：按设备配置的生成模型逐步产生有功功率（kW，内核原生约定），由仿真循环作为设定值下发
+use crate::domain::random_profile::RandomProfile;
+use crate::domain::simulation::sim_seconds_of_day;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+
+struct DeviceGenerator {
+    profile: RandomProfile,
+    min_power: f64,
+    max_power: f64,
+    /// OU 过程当前值
+    ou_value: Option<f64>,
+    /// 当前云遮 / 充电块剩余时长（秒）
+    block_remaining_s: f64,
+    /// 设备独立的随机数发生器：有种子时各设备序列互不影响，与配置顺序无关
+    rng: StdRng,
+}
+
+#[derive(Default)]
+pub struct RandomGeneratorBank {
+    devices: HashMap<String, DeviceGenerator>,
+    /// 全局随机种子：未单独指定种子的设备由其与设备 ID 派生；新一轮仿真不清除
+    global_seed: Option<u64>,
+}
+
+/// FNV-1a 64 位哈希（跨平台、跨编译器版本稳定，用于派生设备种子）
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325u64, |h, b| (h ^ *b as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+/// 设备随机种子：显式种子优先，其次由全局种子与设备 ID 派生；均未设置时为 None（取系统熵，不可复现）
+pub fn device_seed(global_seed: Option<u64>, device_id: &str, explicit: Option<u64>) -> Option<u64> {
+    explicit.or_else(|| global_seed.map(|g| g ^ fnv1a(device_id.as_bytes())))
+}
+
+pub fn seeded_rng(seed: Option<u64>) -> StdRng {
+    match seed {
+        Some(s) => StdRng::seed_from_u64(s),
+        None => StdRng::from_entropy(),
+    }
+}
+
+/// 按泊松到达判断本步是否发生事件
+pub fn arrives(rng: &mut impl Rng, rate_per_hour: f64, dt_s: f64) -> bool {
+    let p = 1.0 - (-rate_per_hour / 3600.0 * dt_s).exp();
+    p > 0.0 && rng.gen_bool(p.min(1.0))
+}
+
+/// 指数分布时长（均值 mean_s）
+pub fn exp_duration(rng: &mut impl Rng, mean_s: f64) -> f64 {
+    -mean_s * (1.0 - rng.gen::<f64>()).ln()
+}
+
+/// 标准正态分布（Box–Muller）
+pub fn standard_normal(rng: &mut impl Rng) -> f64 {
+    let u1 = 1.0 - rng.gen::<f64>();
+    let u2 = rng.gen::<f64>();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
+impl RandomGeneratorBank {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_global_seed(&mut self, seed: Option<u64>) {
+        self.global_seed = seed;
+    }
+
+    pub fn global_seed(&self) -> Option<u64> {
+        self.global_seed
+    }
+
+    /// 设置设备的生成模型；Uniform 表示交回内核生成，从生成器中移除。seed 为已派生的设备种子（见 device_seed）
+    pub fn configure(&mut self, device_id: &str, profile: RandomProfile, min_power: f64, max_power: f64, seed: Option<u64>) {
+        if !profile.is_engine_generated() {
+            self.devices.remove(device_id);
+            return;
+        }
+        self.devices.insert(
+            device_id.to_string(),
+            DeviceGenerator {
+                profile,
+                min_power,
+                max_power,
+                ou_value: None,
+                block_remaining_s: 0.0,
+                rng: seeded_rng(seed),
+            },
+        );
+    }
+
+    pub fn profiles(&self) -> HashMap<String, RandomProfile> {
+        self.devices.iter().map(|(id, g)| (id.clone(), g.profile.clone())).collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.devices.clear();
+    }
+
+    /// 推进一步：返回 device_ids 中各设备本步的有功功率；sim_time 为本步仿真时间（Unix 秒，日内时段按其取），dt_s 为仿真步长（秒）
+    pub fn step<'a>(
+        &mut self,
+        device_ids: impl IntoIterator<Item = &'a String>,
+        sim_time: f64,
+        dt_s: f64,
+    ) -> Vec<(String, f64)> {
+        let hour_of_day = sim_seconds_of_day(sim_time) / 3600.0;
+        let mut out = Vec::new();
+        for device_id in device_ids {
+            let Some(gen) = self.devices.get_mut(device_id) else { continue };
+            let rng = &mut gen.rng;
+            let (lo, hi) = (gen.min_power.min(gen.max_power), gen.min_power.max(gen.max_power));
+            let p_kw = match gen.profile.clone() {
+                RandomProfile::Uniform => continue,
+                RandomProfile::OuNoise { theta_per_s, volatility } => {
+                    let mean = (lo + hi) / 2.0;
+                    let stationary_sd = volatility * (hi - lo);
+                    // 精确离散化：x' = mean + (x - mean)·e^{-θdt} + sd·√(1 - e^{-2θdt})·N(0,1)
+                    let decay = (-theta_per_s * dt_s).exp();
+                    let z = standard_normal(rng);
+                    let x = gen.ou_value.unwrap_or(mean);
+                    let next = mean + (x - mean) * decay + stationary_sd * (1.0 - decay * decay).sqrt() * z;
+                    let next = next.clamp(lo, hi);
+                    gen.ou_value = Some(next);
+                    next
+                }
+                RandomProfile::ClearSkyPv { sunrise_hour, sunset_hour, clouds_per_hour, cloud_depth, cloud_duration_s } => {
+                    let clear = if hour_of_day > sunrise_hour && hour_of_day < sunset_hour {
+                        (std::f64::consts::PI * (hour_of_day - sunrise_hour) / (sunset_hour - sunrise_hour)).sin()
+                    } else {
+                        0.0
+                    };
+                    if gen.block_remaining_s > 0.0 {
+                        gen.block_remaining_s -= dt_s;
+                    } else if arrives(rng, clouds_per_hour, dt_s) {
+                        gen.block_remaining_s = exp_duration(rng, cloud_duration_s);
+                    }
+                    let shading = if gen.block_remaining_s > 0.0 { 1.0 - cloud_depth } else { 1.0 };
+                    lo + (hi - lo) * clear * shading
+                }
+                RandomProfile::ChargingBlocks { sessions_per_hour, mean_session_minutes } => {
+                    if gen.block_remaining_s > 0.0 {
+                        gen.block_remaining_s -= dt_s;
+                    } else if arrives(rng, sessions_per_hour, dt_s) {
+                        gen.block_remaining_s = exp_duration(rng, mean_session_minutes * 60.0);
+                    }
+                    if gen.block_remaining_s > 0.0 { hi } else { lo }
+                }
+            };
+            out.push((device_id.clone(), p_kw));
+        }
+        out
+    }
+}
@@ -0,0 +1,367 @@
+// 回放：读取历史仿真库（data_<ts>.db），按录制节奏（或倍速）重新推送 device-data-update，并驱动运行中的 Modbus 服务器，
+// 不调用 pandapower，便于用录制数据测试 SCADA 客户端。回放期间不可启动主仿真
+use crate::domain::events::{DeviceDataUpdate, ModbusRegistersUpdated, ReplayStateChanged, EVENT_SCHEMA_VERSION};
+use crate::domain::sign_convention::SignConvention;
+use crate::domain::simulation::StorageState;
+use crate::domain::topology::{DeviceType, Topology};
+use crate::services::database::Database;
+use crate::services::modbus::ModbusService;
+use crate::services::simulation_engine::SimulationEngine;
+use crate::services::window_hub;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex as StdMutex};
+use tauri::{AppHandle, Manager};
+use tokio::task::JoinHandle;
+use tokio::time::{Duration, Instant};
+
+/// 每次从库中读取的录制时长（秒）
+const REPLAY_CHUNK_S: f64 = 60.0;
+/// 相邻两帧的最长等待（回放时间，秒）：录制中的暂停或中断不按原样等待
+const MAX_FRAME_GAP_S: f64 = 5.0;
+pub const MIN_REPLAY_SPEED: f64 = 0.1;
+pub const MAX_REPLAY_SPEED: f64 = 1000.0;
+
+fn default_speed() -> f64 {
+    1.0
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayOptions {
+    pub db_path: String,
+    /// 倍速：1 为录制原速
+    #[serde(default = "default_speed")]
+    pub speed: f64,
+    /// 回放起止时刻（Unix 秒），默认整库
+    #[serde(default)]
+    pub start_at: Option<f64>,
+    #[serde(default)]
+    pub end_at: Option<f64>,
+    /// 到末尾后从头循环
+    #[serde(default)]
+    pub loop_playback: bool,
+    /// 同步写入运行中的 Modbus 服务器寄存器
+    #[serde(default = "default_true")]
+    pub drive_modbus: bool,
+}
+
+impl ReplayOptions {
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.speed.is_finite() || !(MIN_REPLAY_SPEED..=MAX_REPLAY_SPEED).contains(&self.speed) {
+            return Err(format!("回放倍速须在 {}~{} 之间: {}", MIN_REPLAY_SPEED, MAX_REPLAY_SPEED, self.speed));
+        }
+        if let (Some(start), Some(end)) = (self.start_at, self.end_at) {
+            if start > end {
+                return Err("回放起始时刻晚于结束时刻".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReplayStatus {
+    pub running: bool,
+    #[serde(default)]
+    pub db_path: Option<String>,
+    pub speed: f64,
+    #[serde(default)]
+    pub start_timestamp: Option<f64>,
+    #[serde(default)]
+    pub end_timestamp: Option<f64>,
+    /// 最近推送的录制时刻
+    #[serde(default)]
+    pub current_timestamp: Option<f64>,
+    pub frames_emitted: u64,
+    pub loops_completed: u32,
+    /// 结束原因：finished | stopped | error: ...
+    #[serde(default)]
+    pub finished_reason: Option<String>,
+}
+
+pub struct ReplayService {
+    status: Arc<StdMutex<ReplayStatus>>,
+    task: StdMutex<Option<JoinHandle<()>>>,
+}
+
+impl Default for ReplayService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReplayService {
+    pub fn new() -> Self {
+        Self {
+            status: Arc::new(StdMutex::new(ReplayStatus::default())),
+            task: StdMutex::new(None),
+        }
+    }
+
+    pub fn status(&self) -> ReplayStatus {
+        self.status.lock().unwrap().clone()
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.status.lock().unwrap().running
+    }
+
+    /// 开始回放；topology 为当前加载的拓扑（用于电表指向与储能 SOC），convention 为当前项目符号约定（Modbus 寄存器按此约定写入）
+    pub fn start(
+        &self,
+        app: AppHandle,
+        options: ReplayOptions,
+        topology: Option<Topology>,
+        convention: SignConvention,
+    ) -> Result<ReplayStatus, String> {
+        options.validate()?;
+        if self.is_running() {
+            return Err("回放进行中，请先停止回放".to_string());
+        }
+        let db = Database::open_readonly(std::path::Path::new(&options.db_path)).map_err(|e| format!("打开仿真库失败: {}", e))?;
+        let first = db.query_earliest_timestamp().map_err(|e| e.to_string())?;
+        let last = db.query_latest_timestamp().map_err(|e| e.to_string())?;
+        let (Some(first), Some(last)) = (first, last) else {
+            return Err("仿真库中没有设备数据".to_string());
+        };
+        let start = options.start_at.unwrap_or(first).max(first);
+        let end = options.end_at.unwrap_or(last).min(last);
+        if start > end {
+            return Err("回放区间内没有设备数据".to_string());
+        }
+        // 库中记录的符号约定，回放时还原为内核原生约定（与实时仿真推送一致）；未记录的旧库即为原生约定
+        let stored = db
+            .get_meta_text("sign_convention")
+            .map_err(|e| e.to_string())?
+            .and_then(|s| serde_json::from_str::<SignConvention>(&s).ok())
+            .unwrap_or_else(SignConvention::native);
+
+        let status = ReplayStatus {
+            running: true,
+            db_path: Some(options.db_path.clone()),
+            speed: options.speed,
+            start_timestamp: Some(start),
+            end_timestamp: Some(end),
+            ..Default::default()
+        };
+        *self.status.lock().unwrap() = status.clone();
+        window_hub::publish_typed(&app, None, ReplayStateChanged {
+            schema_version: EVENT_SCHEMA_VERSION,
+            phase: "started".to_string(),
+            db_path: options.db_path.clone(),
+            speed: options.speed,
+            timestamp: Some(start),
+            reason: None,
+        });
+
+        let player = Player::new(db, &options, topology, &stored, &convention);
+        let shared_status = self.status.clone();
+        let handle = tokio::spawn(async move {
+            let reason = match player.run(&app, &shared_status, start, end).await {
+                Ok(()) => "finished".to_string(),
+                Err(e) => format!("error: {}", e),
+            };
+            finish(&app, &shared_status, &reason);
+        });
+        if let Some(previous) = self.task.lock().unwrap().replace(handle) {
+            previous.abort();
+        }
+        Ok(status)
+    }
+
+    /// 停止回放；寄存器保持最后一帧的值
+    pub fn stop(&self, app: &AppHandle) -> ReplayStatus {
+        if let Some(handle) = self.task.lock().unwrap().take() {
+            handle.abort();
+        }
+        if self.is_running() {
+            finish(app, &self.status, "stopped");
+        }
+        self.status()
+    }
+}
+
+fn finish(app: &AppHandle, status: &StdMutex<ReplayStatus>, reason: &str) {
+    let snapshot = {
+        let mut s = status.lock().unwrap();
+        if !s.running {
+            return;
+        }
+        s.running = false;
+        s.finished_reason = Some(reason.to_string());
+        s.clone()
+    };
+    window_hub::publish_typed(app, None, ReplayStateChanged {
+        schema_version: EVENT_SCHEMA_VERSION,
+        phase: "finished".to_string(),
+        db_path: snapshot.db_path.unwrap_or_default(),
+        speed: snapshot.speed,
+        timestamp: snapshot.current_timestamp,
+        reason: Some(reason.to_string()),
+    });
+}
+
+/// 一帧：同一录制时刻的全部设备行
+struct Frame {
+    timestamp: f64,
+    rows: Vec<(String, Option<f64>, Option<f64>, Option<String>, Option<String>)>,
+}
+
+struct Player {
+    /// 回放任务跨 await 持有 &Player，连接需经 Mutex 才可在线程间共享
+    db: StdMutex<Database>,
+    speed: f64,
+    loop_playback: bool,
+    drive_modbus: bool,
+    topology: Option<Topology>,
+    /// 电表 id -> 其测量对象类型（电表行按测量对象的约定落库）
+    meter_targets: HashMap<String, DeviceType>,
+    stored: SignConvention,
+    modbus_sign_factors: HashMap<String, f64>,
+}
+
+impl Player {
+    fn new(db: Database, options: &ReplayOptions, topology: Option<Topology>, stored: &SignConvention, convention: &SignConvention) -> Self {
+        let mut meter_targets = HashMap::new();
+        let mut modbus_sign_factors = HashMap::new();
+        if let Some(t) = &topology {
+            for (target_id, meters) in SimulationEngine::build_target_to_meters(t) {
+                if let Some(target) = t.devices.get(&target_id) {
+                    for meter_id in meters {
+                        meter_targets.entry(meter_id).or_insert(target.device_type.clone());
+                    }
+                }
+            }
+            modbus_sign_factors = SimulationEngine::modbus_sign_factors(t, convention);
+        }
+        Self {
+            db: StdMutex::new(db),
+            speed: options.speed,
+            loop_playback: options.loop_playback,
+            drive_modbus: options.drive_modbus,
+            topology,
+            meter_targets,
+            stored: stored.clone(),
+            modbus_sign_factors,
+        }
+    }
+
+    /// 库中行还原为内核原生约定的功率
+    fn native_power(&self, device_id: &str, device_type: Option<&str>, p: Option<f64>, q: Option<f64>) -> (Option<f64>, Option<f64>) {
+        let device_type = self
+            .meter_targets
+            .get(device_id)
+            .cloned()
+            .or_else(|| device_type.and_then(|s| crate::commands::topology::parse_device_type(s).ok()));
+        match device_type {
+            Some(t) if t != DeviceType::Meter => self.stored.apply(&t, p, q),
+            _ => (p, q),
+        }
+    }
+
+    /// 读取 [from, until] 内的帧，跳过不晚于 after 的行（分段边界上的行已在上一段推送）
+    fn load_frames(&self, from: f64, until: f64, after: Option<f64>) -> Result<Vec<Frame>, String> {
+        let rows = self.db.lock().unwrap().query_rows_between(from, until).map_err(|e| format!("读取仿真库失败: {}", e))?;
+        let mut frames: Vec<Frame> = Vec::new();
+        for (device_id, timestamp, p, q, data_json, device_type) in rows {
+            if after.is_some_and(|a| timestamp <= a) {
+                continue;
+            }
+            match frames.last_mut() {
+                Some(frame) if frame.timestamp == timestamp => frame.rows.push((device_id, p, q, data_json, device_type)),
+                _ => frames.push(Frame { timestamp, rows: vec![(device_id, p, q, data_json, device_type)] }),
+            }
+        }
+        Ok(frames)
+    }
+
+    async fn run(&self, app: &AppHandle, status: &StdMutex<ReplayStatus>, start: f64, end: f64) -> Result<(), String> {
+        loop {
+            self.play_once(app, status, start, end).await?;
+            if !self.loop_playback {
+                return Ok(());
+            }
+            status.lock().unwrap().loops_completed += 1;
+        }
+    }
+
+    async fn play_once(&self, app: &AppHandle, status: &StdMutex<ReplayStatus>, start: f64, end: f64) -> Result<(), String> {
+        let mut last_power: HashMap<String, (f64, Option<f64>, Option<f64>)> = HashMap::new();
+        let mut storage_states: HashMap<String, StorageState> = HashMap::new();
+        let mut previous: Option<f64> = None;
+        let mut due = Instant::now();
+        let mut chunk_start = start;
+        while chunk_start <= end {
+            let chunk_end = (chunk_start + REPLAY_CHUNK_S).min(end);
+            for frame in self.load_frames(chunk_start, chunk_end, previous)? {
+                let dt_seconds = previous.map(|p| frame.timestamp - p).unwrap_or(0.0);
+                due += Duration::from_secs_f64((dt_seconds / self.speed).clamp(0.0, MAX_FRAME_GAP_S));
+                tokio::time::sleep_until(due).await;
+                // 处理落后较多时（如读库较慢）不再追赶，以当前时刻为基准
+                let now = Instant::now();
+                if now > due + Duration::from_secs(1) {
+                    due = now;
+                }
+                self.emit_frame(app, &frame, dt_seconds, &mut last_power, &mut storage_states).await;
+                previous = Some(frame.timestamp);
+                let mut s = status.lock().unwrap();
+                s.current_timestamp = Some(frame.timestamp);
+                s.frames_emitted += 1;
+                drop(s);
+            }
+            if chunk_end >= end {
+                break;
+            }
+            chunk_start = chunk_end;
+        }
+        Ok(())
+    }
+
+    async fn emit_frame(
+        &self,
+        app: &AppHandle,
+        frame: &Frame,
+        dt_seconds: f64,
+        last_power: &mut HashMap<String, (f64, Option<f64>, Option<f64>)>,
+        storage_states: &mut HashMap<String, StorageState>,
+    ) {
+        for (device_id, p, q, data_json, device_type) in &frame.rows {
+            let (p_kw, q_kvar) = self.native_power(device_id, device_type.as_deref(), *p, *q);
+            let data = data_json
+                .as_deref()
+                .and_then(|s| serde_json::from_str::<serde_json::Value>(s).ok())
+                .unwrap_or(serde_json::Value::Null);
+            window_hub::publish_typed(app, Some(device_id.as_str()), DeviceDataUpdate::new(device_id, p_kw, q_kvar, frame.timestamp, &data));
+            last_power.insert(device_id.clone(), (frame.timestamp, p_kw, q_kvar));
+            let storage = self
+                .topology
+                .as_ref()
+                .and_then(|t| t.devices.get(device_id))
+                .filter(|d| d.device_type == DeviceType::Storage);
+            if let Some(device) = storage {
+                SimulationEngine::advance_storage_state(storage_states, device_id, device, p_kw.unwrap_or(0.0), dt_seconds / 3600.0);
+            }
+        }
+
+        let Some(modbus) = app.try_state::<ModbusService>().filter(|_| self.drive_modbus) else {
+            return;
+        };
+        modbus
+            .update_all_devices_from_simulation(last_power, dt_seconds, Some(storage_states), &self.modbus_sign_factors)
+            .await;
+        for device_id in modbus.running_device_ids() {
+            if let Some((ir, hr)) = modbus.get_device_register_snapshot(&device_id).await {
+                window_hub::publish_typed(app, None, ModbusRegistersUpdated {
+                    schema_version: EVENT_SCHEMA_VERSION,
+                    device_id,
+                    input_registers: ir.into_iter().map(|(k, v)| (k.to_string(), v)).collect(),
+                    holding_registers: hr.into_iter().map(|(k, v)| (k.to_string(), v)).collect(),
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,63 @@
+// 潮流结果索引：内核各结果表（buses、lines 等）按元素名称返回，设置拓扑时一次性建立
+// (结果表, 元素名称) -> 设备 ID 的映射与电表指向关系，结果处理按索引直接定位设备，避免每个结果元素扫描全部设备
+use crate::domain::topology::{Device, DeviceType, Topology};
+use std::collections::HashMap;
+
+/// 设备类型对应的内核结果表；电表不参与潮流计算，无结果表
+fn result_table(device_type: &DeviceType) -> Option<&'static str> {
+    match device_type {
+        DeviceType::Node => Some("buses"),
+        DeviceType::Line => Some("lines"),
+        DeviceType::Transformer => Some("transformers"),
+        DeviceType::Switch => Some("switches"),
+        DeviceType::Load | DeviceType::Charger => Some("loads"),
+        DeviceType::Pv => Some("generators"),
+        DeviceType::Storage => Some("storages"),
+        DeviceType::ExternalGrid => Some("ext_grids"),
+        DeviceType::Meter => None,
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ResultIndex {
+    /// 结果表 -> 元素名称 -> 设备 ID
+    elements: HashMap<&'static str, HashMap<String, String>>,
+    /// 目标设备 ID -> 指向该设备的电表 ID 列表
+    target_to_meters: HashMap<String, Vec<String>>,
+}
+
+impl ResultIndex {
+    pub fn build(topology: &Topology) -> Self {
+        let mut elements: HashMap<&'static str, HashMap<String, String>> = HashMap::new();
+        // 按设备 ID 排序建立，同名设备固定取 ID 最小者
+        let mut ids: Vec<&String> = topology.devices.keys().collect();
+        ids.sort();
+        for device_id in ids {
+            let device = &topology.devices[device_id];
+            if let Some(table) = result_table(&device.device_type) {
+                elements
+                    .entry(table)
+                    .or_default()
+                    .entry(device.name.clone())
+                    .or_insert_with(|| device_id.clone());
+            }
+        }
+        Self {
+            elements,
+            target_to_meters: crate::services::simulation_engine::SimulationEngine::build_target_to_meters(topology),
+        }
+    }
+
+    /// 按结果表与元素名称定位设备；索引与当前拓扑不一致（设备已删除或改名）时返回 None
+    pub fn lookup<'a>(&self, topology: &'a Topology, table: &str, name: &str) -> Option<(&'a String, &'a Device)> {
+        let device_id = self.elements.get(table)?.get(name)?;
+        topology
+            .devices
+            .get_key_value(device_id)
+            .filter(|(_, device)| device.name == name)
+    }
+
+    pub fn target_to_meters(&self) -> &HashMap<String, Vec<String>> {
+        &self.target_to_meters
+    }
+}
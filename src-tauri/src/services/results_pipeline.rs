@@ -0,0 +1,326 @@
+// 结果输出管线：按各消费方的输出间隔（落库 / 前端事件 / Modbus 同步）决定每步的输出，落库可按间隔取平均值；
+// 配置突发落库时，告警/故障等事件前后的步全部入库。设备数据行交给异步落库任务写入，不在计算步内等待磁盘
+use crate::domain::events::EventPayload;
+use crate::domain::preset::{ConsumerRates, PersistAggregation};
+use crate::services::database::Database;
+use crate::services::db_writer::{DbWriter, DeviceDataRow};
+use crate::services::window_hub;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use tauri::AppHandle;
+
+/// 仿真库中突发落库窗口的元数据键
+const META_BURST_WINDOWS: &str = "burst_windows";
+
+/// 突发落库窗口：该时间段内每一步均已落库（瞬时值）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BurstWindow {
+    pub start_timestamp: f64,
+    /// 计划结束时刻（按事件后窗口推算；仿真提前停止时晚于最后一条数据）
+    pub end_timestamp: f64,
+    /// 首个触发事件时刻
+    pub event_timestamp: f64,
+    /// 触发事件：alarm | fault | scheduled_event | protection | error | manual，窗口内再次触发时追加并延长窗口
+    pub triggers: Vec<String>,
+}
+
+/// 突发窗口前的待定行（未到落库步，触发时补写）
+struct BufferedRow {
+    step: u64,
+    row: DeviceDataRow,
+}
+
+/// 落库平均窗口内的累计：(有功和, 有功样本数, 无功和, 无功样本数)，并保留最近一步的明细供停止时补写
+#[derive(Default)]
+struct RowAccumulator {
+    p_sum: f64,
+    p_count: u32,
+    q_sum: f64,
+    q_count: u32,
+    data_json: Option<String>,
+    device_type: Option<String>,
+}
+
+impl RowAccumulator {
+    fn add(&mut self, p: Option<f64>, q: Option<f64>) {
+        if let Some(p) = p {
+            self.p_sum += p;
+            self.p_count += 1;
+        }
+        if let Some(q) = q {
+            self.q_sum += q;
+            self.q_count += 1;
+        }
+    }
+
+    fn mean(&self) -> (Option<f64>, Option<f64>) {
+        (
+            (self.p_count > 0).then(|| self.p_sum / self.p_count as f64),
+            (self.q_count > 0).then(|| self.q_sum / self.q_count as f64),
+        )
+    }
+}
+
+pub struct ResultsPipeline {
+    rates: ConsumerRates,
+    persist_every_n_steps: u32,
+    persist_every: u64,
+    emit_every: u64,
+    modbus_every: u64,
+    aggregation: PersistAggregation,
+    step: u64,
+    last_modbus_step: u64,
+    accumulators: HashMap<String, RowAccumulator>,
+    /// 独立仿真实例：只落库，不推送前端事件、不同步 Modbus
+    headless: bool,
+    /// 突发落库：事件前补写的步数与事件后逐步落库的步数（0 表示未启用）
+    burst_pre_steps: u64,
+    burst_post_steps: u64,
+    pre_buffer: VecDeque<BufferedRow>,
+    /// 逐步落库截止步（含）
+    burst_until: Option<u64>,
+    burst_windows: Vec<BurstWindow>,
+    calculation_interval_ms: u64,
+    /// 多速率仿真：本步更新周期未到期的设备，不落库、不推送设备事件
+    deferred_devices: HashSet<String>,
+    writer: DbWriter,
+}
+
+/// 间隔（毫秒）换算为步数，至少 1 步
+fn interval_steps(interval_ms: Option<u64>, calculation_interval_ms: u64) -> Option<u64> {
+    interval_ms.map(|ms| ((ms as f64 / calculation_interval_ms.max(1) as f64).round() as u64).max(1))
+}
+
+impl ResultsPipeline {
+    pub fn new(rates: &ConsumerRates, persist_every_n_steps: u32, calculation_interval_ms: u64, writer: DbWriter) -> Self {
+        let mut pipeline = Self {
+            rates: rates.clone(),
+            persist_every_n_steps,
+            persist_every: 1,
+            emit_every: 1,
+            modbus_every: 1,
+            aggregation: rates.persist_aggregation,
+            step: 0,
+            last_modbus_step: 0,
+            accumulators: HashMap::new(),
+            headless: false,
+            burst_pre_steps: 0,
+            burst_post_steps: 0,
+            pre_buffer: VecDeque::new(),
+            burst_until: None,
+            burst_windows: Vec::new(),
+            calculation_interval_ms,
+            deferred_devices: HashSet::new(),
+            writer,
+        };
+        pipeline.set_step_interval_ms(calculation_interval_ms);
+        pipeline
+    }
+
+    /// 按当前求解步长重新换算各输出间隔的步数（自适应计算间隔调整步长后调用）
+    pub fn set_step_interval_ms(&mut self, calculation_interval_ms: u64) {
+        let rates = &self.rates;
+        self.persist_every = interval_steps(rates.persist_interval_ms, calculation_interval_ms)
+            .unwrap_or(self.persist_every_n_steps.max(1) as u64);
+        self.emit_every = interval_steps(rates.emit_interval_ms, calculation_interval_ms).unwrap_or(1);
+        self.modbus_every = interval_steps(rates.modbus_interval_ms, calculation_interval_ms).unwrap_or(1);
+        self.burst_pre_steps = rates
+            .burst
+            .as_ref()
+            .filter(|b| b.pre_event_ms > 0)
+            .and_then(|b| interval_steps(Some(b.pre_event_ms), calculation_interval_ms))
+            .unwrap_or(0);
+        self.burst_post_steps = rates
+            .burst
+            .as_ref()
+            .and_then(|b| interval_steps(Some(b.post_event_ms), calculation_interval_ms))
+            .unwrap_or(0);
+        self.calculation_interval_ms = calculation_interval_ms;
+    }
+
+    pub fn headless(mut self) -> Self {
+        self.headless = true;
+        self
+    }
+
+    pub fn is_headless(&self) -> bool {
+        self.headless
+    }
+
+    /// 进入新的一步（step 从 1 开始）
+    pub fn begin_step(&mut self, step: u64) {
+        self.step = step;
+        let oldest = step.saturating_sub(self.burst_pre_steps);
+        while self.pre_buffer.front().is_some_and(|r| r.step < oldest) {
+            self.pre_buffer.pop_front();
+        }
+    }
+
+    /// 设置本步更新周期未到期的设备（多速率仿真），在 begin_step 之后调用
+    pub fn set_deferred_devices(&mut self, devices: &HashSet<String>) {
+        self.deferred_devices.clone_from(devices);
+    }
+
+    fn in_burst(&self) -> bool {
+        self.burst_until.is_some_and(|until| self.step <= until)
+    }
+
+    /// 标记事件触发突发落库：补写事件前被抽稀的步，此后 post 窗口内逐步落库，并在库中记录窗口。
+    /// 可在一步的计算前（故障、定时事件）或计算后（告警）调用；未配置突发落库时忽略
+    pub fn trigger_burst(&mut self, db: &Database, trigger: &str, timestamp: f64) {
+        if self.burst_post_steps == 0 {
+            return;
+        }
+        let end_timestamp = timestamp + (self.burst_post_steps * self.calculation_interval_ms) as f64 / 1000.0;
+        let extend = self.burst_until.is_some_and(|until| self.step <= until);
+        self.burst_until = Some(self.step + self.burst_post_steps);
+        let start_timestamp = self.pre_buffer.front().map(|r| r.row.timestamp).unwrap_or(timestamp);
+        for buffered in self.pre_buffer.drain(..) {
+            self.writer.write(buffered.row);
+        }
+        match self.burst_windows.last_mut().filter(|_| extend) {
+            Some(window) => {
+                window.end_timestamp = end_timestamp;
+                window.triggers.push(trigger.to_string());
+            }
+            None => self.burst_windows.push(BurstWindow {
+                start_timestamp,
+                end_timestamp,
+                event_timestamp: timestamp,
+                triggers: vec![trigger.to_string()],
+            }),
+        }
+        if let Ok(json) = serde_json::to_string(&self.burst_windows) {
+            if let Err(e) = db.set_meta_text(META_BURST_WINDOWS, &json) {
+                eprintln!("写入突发落库窗口失败: {}", e);
+            }
+        }
+    }
+
+    /// 本步是否落库：取瞬时值时为每个间隔的第一步（与逐步落库兼容），取平均值时为间隔的最后一步
+    pub fn persist_now(&self) -> bool {
+        match self.aggregation {
+            PersistAggregation::Sample => (self.step.max(1) - 1) % self.persist_every == 0,
+            PersistAggregation::Average => self.step % self.persist_every == 0,
+        }
+    }
+
+    pub fn emit_now(&self) -> bool {
+        !self.headless && (self.step.max(1) - 1) % self.emit_every == 0
+    }
+
+    pub fn sync_modbus_now(&self) -> bool {
+        !self.headless && (self.step.max(1) - 1) % self.modbus_every == 0
+    }
+
+    /// 标记本步已同步 Modbus，返回距上次同步经过的步数（用于寄存器电量积分）
+    pub fn mark_modbus_synced(&mut self) -> u64 {
+        let steps = self.step.saturating_sub(self.last_modbus_step).max(1);
+        self.last_modbus_step = self.step;
+        steps
+    }
+
+    /// 写入一行设备数据：取平均值时每步累计、落库步写入间隔平均值（data_json 为落库步的瞬时结果）
+    #[allow(clippy::too_many_arguments)]
+    pub fn record_row(
+        &mut self,
+        device_id: &str,
+        timestamp: f64,
+        p_active_kw: Option<f64>,
+        p_reactive_kvar: Option<f64>,
+        data_json: Option<&str>,
+        device_type: Option<&str>,
+    ) {
+        if self.deferred_devices.contains(device_id) {
+            return;
+        }
+        let row = |p_active_kw, p_reactive_kvar| DeviceDataRow {
+            device_id: device_id.to_string(),
+            timestamp,
+            p_active_kw,
+            p_reactive_kvar,
+            data_json: data_json.map(str::to_string),
+            device_type: device_type.map(str::to_string),
+        };
+        // 突发窗口内逐步写入瞬时值，窗口结束后平均值重新累计
+        if self.in_burst() {
+            self.accumulators.remove(device_id);
+            self.writer.write(row(p_active_kw, p_reactive_kvar));
+            return;
+        }
+        if self.burst_pre_steps > 0 && !self.persist_now() {
+            self.pre_buffer.push_back(BufferedRow { step: self.step, row: row(p_active_kw, p_reactive_kvar) });
+        }
+        let (p, q) = match self.aggregation {
+            PersistAggregation::Sample => (p_active_kw, p_reactive_kvar),
+            PersistAggregation::Average => {
+                let persist_now = self.persist_now();
+                let acc = self.accumulators.entry(device_id.to_string()).or_default();
+                acc.add(p_active_kw, p_reactive_kvar);
+                if !persist_now {
+                    acc.data_json = data_json.map(str::to_string);
+                    acc.device_type = device_type.map(str::to_string);
+                    return;
+                }
+                let mean = acc.mean();
+                self.accumulators.remove(device_id);
+                mean
+            }
+        };
+        if self.persist_now() {
+            self.writer.write(row(p, q));
+        }
+    }
+
+    /// 仿真结束：取平均值时把未到落库步的累计按已累计的样本写入平均值，避免最后一个不完整间隔丢失
+    pub fn finish(&mut self, timestamp: f64) {
+        for (device_id, acc) in self.accumulators.drain() {
+            let (p_active_kw, p_reactive_kvar) = acc.mean();
+            if p_active_kw.is_none() && p_reactive_kvar.is_none() {
+                continue;
+            }
+            self.writer.write(DeviceDataRow {
+                device_id,
+                timestamp,
+                p_active_kw,
+                p_reactive_kvar,
+                data_json: acc.data_json,
+                device_type: acc.device_type,
+            });
+        }
+    }
+
+    /// 等待已送入异步落库任务的行全部写入
+    pub async fn flush(&self) {
+        self.writer.flush().await;
+    }
+
+    /// 前端事件：仅在推送步发送
+    pub fn publish<S: Serialize + Clone>(&self, app: &AppHandle, event: &str, device_id: Option<&str>, payload: S) {
+        if self.emit_now() {
+            window_hub::publish(app, event, device_id, payload);
+        }
+    }
+
+    pub fn publish_typed<T: EventPayload + Clone>(&self, app: &AppHandle, device_id: Option<&str>, payload: T) {
+        if self.emit_now() && device_id.is_none_or(|id| !self.deferred_devices.contains(id)) {
+            window_hub::publish_typed(app, device_id, payload);
+        }
+    }
+
+    /// 通知类事件（错误、故障、告警等）：不受推送间隔限制，独立实例不推送
+    pub fn notify_typed<T: EventPayload + Clone>(&self, app: &AppHandle, device_id: Option<&str>, payload: T) {
+        if !self.headless {
+            window_hub::publish_typed(app, device_id, payload);
+        }
+    }
+}
+
+/// 读取仿真库中记录的突发落库窗口（未启用或旧库为空）
+pub fn load_burst_windows(db: &Database) -> Vec<BurstWindow> {
+    db.get_meta_text(META_BURST_WINDOWS)
+        .ok()
+        .flatten()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
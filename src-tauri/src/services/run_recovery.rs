@@ -0,0 +1,208 @@
+// 仿真中断恢复：运行时在仿真库 simulation_meta 中记录运行清单（拓扑快照、步长、运行参数）与周期检查点（储能状态、电量寄存器），
+// 应用崩溃后重启时据此发现未正常结束的仿真，并在同一数据库上继续追加数据
+use crate::domain::preset::RunOptions;
+use crate::domain::simulation::{DeviceEnergyCounters, DeviceWorkModes, SimClock, StorageState};
+use crate::domain::topology::Topology;
+use crate::services::database::Database;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// 检查点写入间隔（仿真步数）：按仿真进度而非墙钟写入，加速或降速运行时检查点覆盖的仿真时长一致
+pub const RUN_CHECKPOINT_INTERVAL_STEPS: u64 = 60;
+
+const META_RUN_STATUS: &str = "run_status";
+const META_RUN_MANIFEST: &str = "run_manifest";
+const META_RUN_CHECKPOINT: &str = "run_checkpoint";
+
+/// 运行状态：仿真中为 running，正常停止为 completed，用户放弃恢复为 dismissed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunStatus {
+    Running,
+    Completed,
+    Dismissed,
+}
+
+impl RunStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            RunStatus::Running => "running",
+            RunStatus::Completed => "completed",
+            RunStatus::Dismissed => "dismissed",
+        }
+    }
+}
+
+/// 运行清单：启动时写入一次，恢复时据此重建仿真
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunManifest {
+    pub topology: Topology,
+    pub calculation_interval_ms: u64,
+    pub run_options: RunOptions,
+    pub remote_control_enabled: bool,
+}
+
+/// 周期检查点：储能 SOC/电量、功率设备电量计数与 Modbus 电量寄存器
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RunCheckpoint {
+    pub timestamp: f64,
+    pub storage_state: HashMap<String, StorageState>,
+    #[serde(default)]
+    pub device_energy: HashMap<String, DeviceEnergyCounters>,
+    /// device_id -> 寄存器地址 -> 值
+    #[serde(default)]
+    pub energy_registers: HashMap<String, HashMap<u16, u16>>,
+    /// 仿真时钟（起点与累计仿真时长），恢复后仿真经过时间不归零；旧版本检查点为 None
+    #[serde(default)]
+    pub sim_clock: Option<SimClock>,
+}
+
+/// 检查点文件格式版本
+pub const CHECKPOINT_FILE_VERSION: u32 = 1;
+
+/// 手动保存的仿真检查点文件：运行清单、储能/电量检查点、设备工作模式与 Python 内核运行时状态，
+/// 关闭应用后可据此恢复长时间运行的仿真
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulationCheckpointFile {
+    pub version: u32,
+    pub saved_at: f64,
+    /// 保存时使用的仿真库；恢复时若仍存在则继续追加，否则新建
+    pub db_path: String,
+    pub manifest: RunManifest,
+    pub checkpoint: RunCheckpoint,
+    #[serde(default)]
+    pub device_modes: DeviceWorkModes,
+    /// 内核 simulation.export_state 的结果，恢复时原样传回 simulation.import_state
+    #[serde(default)]
+    pub kernel_state: serde_json::Value,
+}
+
+/// 检查点文件摘要（供前端展示）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckpointFileInfo {
+    pub path: String,
+    pub saved_at: f64,
+    pub checkpoint_at: f64,
+    pub db_path: String,
+    pub topology_name: String,
+    pub device_count: usize,
+    pub storage_count: usize,
+}
+
+impl SimulationCheckpointFile {
+    pub fn info(&self, path: &str) -> CheckpointFileInfo {
+        CheckpointFileInfo {
+            path: path.to_string(),
+            saved_at: self.saved_at,
+            checkpoint_at: self.checkpoint.timestamp,
+            db_path: self.db_path.clone(),
+            topology_name: self.manifest.topology.name.clone(),
+            device_count: self.manifest.topology.devices.len(),
+            storage_count: self.checkpoint.storage_state.len(),
+        }
+    }
+}
+
+pub fn write_checkpoint_file(path: &Path, file: &SimulationCheckpointFile) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(file).map_err(|e| format!("序列化仿真检查点失败: {}", e))?;
+    // 先写临时文件再改名，避免写入中途崩溃损坏已有检查点
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, json).map_err(|e| format!("写入仿真检查点文件失败: {}", e))?;
+    std::fs::rename(&tmp, path).map_err(|e| format!("写入仿真检查点文件失败: {}", e))
+}
+
+pub fn read_checkpoint_file(path: &Path) -> Result<SimulationCheckpointFile, String> {
+    let json = std::fs::read_to_string(path).map_err(|e| format!("读取仿真检查点文件失败: {}", e))?;
+    let file: SimulationCheckpointFile =
+        serde_json::from_str(&json).map_err(|e| format!("解析仿真检查点文件失败: {}", e))?;
+    if file.version > CHECKPOINT_FILE_VERSION {
+        return Err(format!("检查点文件版本 {} 高于当前支持的版本 {}", file.version, CHECKPOINT_FILE_VERSION));
+    }
+    Ok(file)
+}
+
+/// 未正常结束的仿真（供前端提示恢复）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InterruptedRun {
+    pub db_path: String,
+    pub started_at: Option<f64>,
+    /// 库中最后一条数据的时间戳
+    pub last_timestamp: Option<f64>,
+    pub checkpoint_at: Option<f64>,
+    pub topology_name: String,
+    pub device_count: usize,
+}
+
+pub fn set_run_status(db: &Database, status: RunStatus) {
+    if let Err(e) = db.set_meta_text(META_RUN_STATUS, status.as_str()) {
+        eprintln!("写入仿真运行状态失败: {}", e);
+    }
+}
+
+pub fn write_manifest(db: &Database, manifest: &RunManifest) {
+    match serde_json::to_string(manifest) {
+        Ok(json) => {
+            if let Err(e) = db.set_meta_text(META_RUN_MANIFEST, &json) {
+                eprintln!("写入仿真运行清单失败: {}", e);
+            }
+        }
+        Err(e) => eprintln!("序列化仿真运行清单失败: {}", e),
+    }
+}
+
+pub fn write_checkpoint(db: &Database, checkpoint: &RunCheckpoint) {
+    if let Ok(json) = serde_json::to_string(checkpoint) {
+        if let Err(e) = db.set_meta_text(META_RUN_CHECKPOINT, &json) {
+            eprintln!("写入仿真检查点失败: {}", e);
+        }
+    }
+}
+
+/// 读取可恢复仿真的清单与最近检查点；库未记录清单（旧版本库）时报错
+pub fn load_resume_state(db: &Database) -> Result<(RunManifest, Option<RunCheckpoint>), String> {
+    let manifest = db
+        .get_meta_text(META_RUN_MANIFEST)
+        .map_err(|e| format!("读取仿真运行清单失败: {}", e))?
+        .ok_or("该仿真库未记录运行清单，无法恢复")?;
+    let manifest: RunManifest =
+        serde_json::from_str(&manifest).map_err(|e| format!("解析仿真运行清单失败: {}", e))?;
+    let checkpoint = db
+        .get_meta_text(META_RUN_CHECKPOINT)
+        .ok()
+        .flatten()
+        .and_then(|s| serde_json::from_str::<RunCheckpoint>(&s).ok());
+    Ok((manifest, checkpoint))
+}
+
+fn is_run_db(path: &Path) -> Option<u64> {
+    let name = path.file_name()?.to_str()?;
+    name.strip_prefix("data_")?.strip_suffix(".db")?.parse().ok()
+}
+
+/// 在工作目录中查找最近一次未正常结束的仿真（只检查最新的仿真库；exclude 为当前正在使用的库）
+pub fn find_interrupted_run(dir: &Path, exclude: Option<&str>) -> Option<InterruptedRun> {
+    let mut candidates: Vec<(u64, PathBuf)> = std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter_map(|p| is_run_db(&p).map(|ts| (ts, p)))
+        .collect();
+    candidates.sort_by(|a, b| b.0.cmp(&a.0));
+    let (_, path) = candidates.into_iter().next()?;
+    let path_str = path.to_string_lossy().to_string();
+    if exclude == Some(path_str.as_str()) {
+        return None;
+    }
+    let db = Database::new(Some(path.as_path())).ok()?;
+    if db.get_meta_text(META_RUN_STATUS).ok().flatten().as_deref() != Some(RunStatus::Running.as_str()) {
+        return None;
+    }
+    let (manifest, checkpoint) = load_resume_state(&db).ok()?;
+    Some(InterruptedRun {
+        db_path: path_str,
+        started_at: db.get_latest_simulation_start().ok().flatten(),
+        last_timestamp: db.query_latest_timestamp().ok().flatten(),
+        checkpoint_at: checkpoint.map(|c| c.timestamp),
+        topology_name: manifest.topology.name.clone(),
+        device_count: manifest.topology.devices.len(),
+    })
+}
@@ -0,0 +1,186 @@
+// 运行摘要：计算循环逐步统计步数、未收敛次数与母线电压极值，仿真停止时与设备电量、储能计划执行情况合成本次运行摘要，
+// 写入仿真库 simulation_meta 并推送前端（simulation-summary 事件）
+use crate::domain::simulation::DeviceEnergyCounters;
+use crate::domain::storage_schedule::ScheduleAdherence;
+use crate::domain::topology::Topology;
+use crate::services::database::Database;
+use crate::services::result_index::ResultIndex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 仿真库中运行摘要的元数据键
+const META_RUN_SUMMARY: &str = "run_summary";
+
+/// 母线电压极值
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoltageExtreme {
+    /// 母线设备 ID（按内核结果中的母线名称匹配拓扑节点，匹配不到时为 None）
+    pub bus_id: Option<String>,
+    pub bus_name: String,
+    pub vm_pu: f64,
+    /// 出现时刻（仿真时间）
+    pub timestamp: f64,
+}
+
+/// 功率设备本次运行的累计电量（输入 = 从电网流入设备，输出 = 设备送入电网）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceEnergySummary {
+    pub device_id: String,
+    pub name: Option<String>,
+    pub device_type: Option<String>,
+    pub import_kwh: f64,
+    pub export_kwh: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunSummary {
+    /// 停止原因：stop（手动停止）| max_steps / max_duration（达到自动停止条件）| error（严重错误自动停止）|
+    /// kernel_failure（内核重启失败）
+    pub reason: String,
+    /// 启动与停止的墙钟时间（Unix 秒）
+    pub started_at: f64,
+    pub stopped_at: f64,
+    pub wall_duration_s: f64,
+    /// 首步与末步结果的仿真时间；未完成任何一步时为 None
+    pub sim_start: Option<f64>,
+    pub sim_end: Option<f64>,
+    pub sim_duration_s: f64,
+    pub steps: u64,
+    pub convergence_failures: u64,
+    pub min_voltage: Option<VoltageExtreme>,
+    pub max_voltage: Option<VoltageExtreme>,
+    pub device_energy: Vec<DeviceEnergySummary>,
+    /// 执行日前计划的储能的计划执行情况（偏差能量、被远程接管步数等）
+    #[serde(default)]
+    pub schedule_adherence: Vec<ScheduleAdherence>,
+}
+
+fn now_s() -> f64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs_f64()
+}
+
+#[derive(Default)]
+pub struct RunStatsCollector {
+    /// 本次运行启动的墙钟时间；None 表示没有进行中的运行（摘要已生成）
+    started_at: Option<f64>,
+    /// 本次运行首步开始的仿真时间
+    sim_start: Option<f64>,
+    sim_end: Option<f64>,
+    steps: u64,
+    convergence_failures: u64,
+    min_voltage: Option<VoltageExtreme>,
+    max_voltage: Option<VoltageExtreme>,
+}
+
+impl RunStatsCollector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 新一轮运行开始
+    pub fn reset(&mut self) {
+        *self = Self { started_at: Some(now_s()), ..Self::default() };
+    }
+
+    /// 统计一步内核结果（timestamp 为步末仿真时间，dt_s 为步长）
+    pub fn record_step(&mut self, result: &serde_json::Value, timestamp: f64, dt_s: f64) {
+        if self.started_at.is_none() {
+            return;
+        }
+        self.steps += 1;
+        self.sim_start.get_or_insert(timestamp - dt_s);
+        self.sim_end = Some(timestamp);
+        if !result.get("converged").and_then(|v| v.as_bool()).unwrap_or(false) {
+            self.convergence_failures += 1;
+            return;
+        }
+        let Some(buses) = result.get("devices").and_then(|d| d.get("buses")).and_then(|v| v.as_object()) else {
+            return;
+        };
+        for bus in buses.values() {
+            let Some(vm_pu) = bus.get("vm_pu").and_then(|v| v.as_f64()).filter(|v| v.is_finite()) else { continue };
+            let extreme = || VoltageExtreme {
+                bus_id: None,
+                bus_name: bus.get("name").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+                vm_pu,
+                timestamp,
+            };
+            if self.min_voltage.as_ref().is_none_or(|m| vm_pu < m.vm_pu) {
+                self.min_voltage = Some(extreme());
+            }
+            if self.max_voltage.as_ref().is_none_or(|m| vm_pu > m.vm_pu) {
+                self.max_voltage = Some(extreme());
+            }
+        }
+    }
+
+    /// 结束本次运行并生成摘要；同一轮只生成一次（已生成或未启动时返回 None）
+    pub fn finish(
+        &mut self,
+        reason: &str,
+        topology: Option<&Topology>,
+        index: &ResultIndex,
+        device_energy: &HashMap<String, DeviceEnergyCounters>,
+        schedule_adherence: Vec<ScheduleAdherence>,
+    ) -> Option<RunSummary> {
+        let started_at = self.started_at.take()?;
+        let stopped_at = now_s();
+        let bus_id = |name: &str| topology.and_then(|t| index.lookup(t, "buses", name)).map(|(id, _)| id.clone());
+        let with_bus_id = |extreme: Option<VoltageExtreme>| {
+            extreme.map(|e| VoltageExtreme { bus_id: bus_id(&e.bus_name), ..e })
+        };
+        let mut energy: Vec<DeviceEnergySummary> = device_energy
+            .iter()
+            .map(|(device_id, counters)| {
+                let device = topology.and_then(|t| t.devices.get(device_id));
+                DeviceEnergySummary {
+                    device_id: device_id.clone(),
+                    name: device.map(|d| d.name.clone()),
+                    device_type: device.map(|d| d.device_type.as_str().to_string()),
+                    import_kwh: counters.total_import_kwh,
+                    export_kwh: counters.total_export_kwh,
+                }
+            })
+            .collect();
+        energy.sort_by(|a, b| a.device_id.cmp(&b.device_id));
+        Some(RunSummary {
+            reason: reason.to_string(),
+            started_at,
+            stopped_at,
+            wall_duration_s: (stopped_at - started_at).max(0.0),
+            sim_start: self.sim_start,
+            sim_end: self.sim_end,
+            sim_duration_s: match (self.sim_start, self.sim_end) {
+                (Some(start), Some(end)) => (end - start).max(0.0),
+                _ => 0.0,
+            },
+            steps: self.steps,
+            convergence_failures: self.convergence_failures,
+            min_voltage: with_bus_id(self.min_voltage.take()),
+            max_voltage: with_bus_id(self.max_voltage.take()),
+            device_energy: energy,
+            schedule_adherence,
+        })
+    }
+}
+
+/// 写入本次运行摘要
+pub fn record_summary(db: &Database, summary: &RunSummary) {
+    match serde_json::to_string(summary) {
+        Ok(json) => {
+            if let Err(e) = db.set_meta_text(META_RUN_SUMMARY, &json) {
+                eprintln!("写入运行摘要失败: {}", e);
+            }
+        }
+        Err(e) => eprintln!("序列化运行摘要失败: {}", e),
+    }
+}
+
+/// 读取仿真库记录的运行摘要（运行未正常结束或旧库为 None）
+pub fn load_summary(db: &Database) -> Option<RunSummary> {
+    db.get_meta_text(META_RUN_SUMMARY)
+        .ok()
+        .flatten()
+        .and_then(|json| serde_json::from_str(&json).ok())
+}
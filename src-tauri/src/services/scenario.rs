@@ -0,0 +1,412 @@
+// 场景脚本与结果断言：事件交由计算循环的定时事件调度按仿真时间施加，采样设备状态并评估断言，给出通过/失败及失败明细（供自动化回归测试）
+use crate::domain::webhook::WebhookEvent;
+use crate::services::chart_renderer::{self, ChartSpec};
+use crate::services::database::Database;
+use crate::services::event_scheduler::{EventAction, ScheduledEvent};
+use crate::services::limit_monitor::LimitLevel;
+use crate::services::simulation_engine::SimulationEngine;
+use crate::services::webhook::WebhookDispatcher;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioEvent {
+    /// 相对场景开始的仿真时间（秒）
+    pub at_s: f64,
+    pub action: EventAction,
+}
+
+/// 断言；quantity 取 p_kw | q_kvar | soc
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ScenarioAssertion {
+    /// 在 at_s 时刻（取该时刻及之后的首个样本）数值须在 [min, max] 内
+    ValueAt { at_s: f64, device_id: String, quantity: String, min: Option<f64>, max: Option<f64> },
+    /// [from_s, to_s] 内所有样本须在 [min, max] 内
+    RangeDuring { from_s: f64, to_s: f64, device_id: String, quantity: String, min: Option<f64>, max: Option<f64> },
+    /// [from_s, to_s] 内不得出现软限值越限（默认只看报警；quantity 如 voltage_pu，device_id 为空表示全部设备）
+    NoLimitViolation {
+        from_s: f64,
+        to_s: f64,
+        #[serde(default)]
+        quantity: Option<String>,
+        #[serde(default)]
+        device_id: Option<String>,
+        #[serde(default)]
+        include_warnings: bool,
+    },
+}
+
+impl ScenarioAssertion {
+    fn describe(&self) -> String {
+        let range = |min: &Option<f64>, max: &Option<f64>| {
+            format!(
+                "{}–{}",
+                min.map(|v| v.to_string()).unwrap_or_else(|| "-∞".to_string()),
+                max.map(|v| v.to_string()).unwrap_or_else(|| "+∞".to_string())
+            )
+        };
+        match self {
+            Self::ValueAt { at_s, device_id, quantity, min, max } => {
+                format!("t={}s 时 {} {} 在 {} 内", at_s, device_id, quantity, range(min, max))
+            }
+            Self::RangeDuring { from_s, to_s, device_id, quantity, min, max } => {
+                format!("{}–{}s 内 {} {} 始终在 {} 内", from_s, to_s, device_id, quantity, range(min, max))
+            }
+            Self::NoLimitViolation { from_s, to_s, quantity, device_id, .. } => format!(
+                "{}–{}s 内 {} 无 {} 越限",
+                from_s,
+                to_s,
+                device_id.as_deref().unwrap_or("所有设备"),
+                quantity.as_deref().unwrap_or("任何")
+            ),
+        }
+    }
+
+    fn sampled_quantity(&self) -> Option<(&str, &str)> {
+        match self {
+            Self::ValueAt { device_id, quantity, .. } | Self::RangeDuring { device_id, quantity, .. } => {
+                Some((device_id.as_str(), quantity.as_str()))
+            }
+            Self::NoLimitViolation { .. } => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioScript {
+    pub name: String,
+    /// 场景时长（仿真秒）
+    pub duration_s: f64,
+    #[serde(default)]
+    pub events: Vec<ScenarioEvent>,
+    #[serde(default)]
+    pub assertions: Vec<ScenarioAssertion>,
+    /// 命令行模式使用：拓扑文件路径与计算步长
+    #[serde(default)]
+    pub topology_path: Option<String>,
+    #[serde(default)]
+    pub calculation_interval_ms: Option<u64>,
+    /// 命令行模式使用：运行结束后按规格从本次仿真库渲染曲线图（需指定 output_path）
+    #[serde(default)]
+    pub charts: Vec<ChartSpec>,
+}
+
+impl ScenarioScript {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path).map_err(|e| format!("读取场景文件失败 {}: {}", path, e))?;
+        let script: Self = serde_json::from_str(&content).map_err(|e| format!("解析场景文件失败: {}", e))?;
+        script.validate()?;
+        Ok(script)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.duration_s <= 0.0 {
+            return Err("场景时长须大于 0".to_string());
+        }
+        for a in &self.assertions {
+            if let Some((_, q)) = a.sampled_quantity() {
+                if !["p_kw", "q_kvar", "soc"].contains(&q) {
+                    return Err(format!("不支持的断言量: {}", q));
+                }
+            }
+        }
+        for chart in &self.charts {
+            chart.validate()?;
+            if chart.output_path.is_none() {
+                return Err("场景曲线图须指定 output_path".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssertionOutcome {
+    pub index: usize,
+    pub description: String,
+    pub passed: bool,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioResult {
+    pub name: String,
+    pub started_at: f64,
+    pub finished_at: f64,
+    pub passed: bool,
+    pub outcomes: Vec<AssertionOutcome>,
+    /// 失败的断言（outcomes 中 passed=false 的子集）
+    pub failed: Vec<AssertionOutcome>,
+}
+
+impl ScenarioResult {
+    /// 命令行模式退出码：全部通过为 0，否则为 1
+    pub fn exit_code(&self) -> i32 {
+        if self.passed {
+            0
+        } else {
+            1
+        }
+    }
+}
+
+/// 采样轮询间隔（毫秒）
+const SAMPLE_POLL_MS: u64 = 100;
+
+fn now_secs() -> f64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs_f64()
+}
+
+/// 越限记录：(仿真时间, 设备, 量, 等级)
+type ViolationSample = (f64, String, String, LimitLevel);
+
+/// 运行场景（需仿真运行中）：场景时间为仿真时钟自场景开始推进的仿真时间，暂停期间不计时
+pub async fn run_scenario(
+    engine: &SimulationEngine,
+    app: &AppHandle,
+    script: &ScenarioScript,
+) -> Result<ScenarioResult, String> {
+    use crate::domain::simulation::SimulationState;
+    script.validate()?;
+    if engine.get_status().await.state != SimulationState::Running {
+        return Err("请先启动仿真再运行场景".to_string());
+    }
+    let sampled: HashSet<(String, String)> = script
+        .assertions
+        .iter()
+        .filter_map(|a| a.sampled_quantity().map(|(d, q)| (d.to_string(), q.to_string())))
+        .collect();
+
+    // 施加电网事件前记录边界条件，场景结束后恢复为场景前的值
+    let boundary_before = if script.events.iter().any(|e| e.action.is_grid_boundary()) {
+        Some(engine.get_grid_boundary().await?)
+    } else {
+        None
+    };
+    let started_at = now_secs();
+    let sim_start_s = engine.sim_elapsed_s();
+    let owner = format!("scenario:{}@{}", script.name, started_at);
+    engine.insert_scheduled_events(
+        script
+            .events
+            .iter()
+            .map(|e| ScheduledEvent {
+                at_s: sim_start_s + e.at_s,
+                device_id: String::new(),
+                properties: serde_json::Map::new(),
+                action: Some(e.action.clone()),
+                label: Some(script.name.clone()),
+                owner: Some(owner.clone()),
+            })
+            .collect(),
+    )?;
+    let mut samples: HashMap<(String, String), Vec<(f64, f64)>> = HashMap::new();
+    let mut violations: Vec<ViolationSample> = Vec::new();
+    let mut run_error: Option<String> = None;
+
+    loop {
+        let t_s = engine.sim_elapsed_s() - sim_start_s;
+        if t_s >= script.duration_s {
+            break;
+        }
+        if let Some(failed) = engine.scheduled_event_failure(&owner) {
+            run_error = Some(format!(
+                "场景事件执行失败（t={}s）: {}",
+                failed.at_s - sim_start_s,
+                failed.error.unwrap_or_default()
+            ));
+            break;
+        }
+        if engine.get_status().await.state == SimulationState::Stopped {
+            run_error = Some("仿真已停止，场景中止".to_string());
+            break;
+        }
+
+        let storage = engine.get_all_storage_states();
+        for (device_id, quantity) in &sampled {
+            let Some((_, p_kw, q_kvar)) = engine.get_last_device_power(device_id) else {
+                continue;
+            };
+            let value = match quantity.as_str() {
+                "p_kw" => p_kw,
+                "q_kvar" => q_kvar,
+                "soc" => storage.get(device_id).map(|s| s.soc_percent),
+                _ => None,
+            };
+            if let Some(v) = value {
+                let series = samples.entry((device_id.clone(), quantity.clone())).or_default();
+                series.push((t_s, v));
+            }
+        }
+        for alert in engine.get_active_limit_alerts() {
+            violations.push((t_s, alert.device_id, alert.quantity, alert.level));
+        }
+        let _ = app.emit("scenario-progress", serde_json::json!({
+            "name": script.name,
+            "t_s": t_s,
+            "duration_s": script.duration_s,
+        }));
+        tokio::time::sleep(tokio::time::Duration::from_millis(SAMPLE_POLL_MS)).await;
+    }
+
+    // 撤回未施加的场景事件，恢复场景前的电网边界条件（无论场景是否出错）
+    engine.withdraw_scheduled_events(&owner);
+    if let Some(boundary) = boundary_before {
+        let _ = engine.restore_grid_boundary(&boundary).await;
+    }
+    if let Some(e) = run_error {
+        return Err(e);
+    }
+
+    let outcomes: Vec<AssertionOutcome> = script
+        .assertions
+        .iter()
+        .enumerate()
+        .map(|(index, a)| {
+            let (passed, detail) = evaluate(a, &samples, &violations);
+            AssertionOutcome { index, description: a.describe(), passed, detail }
+        })
+        .collect();
+    let failed: Vec<AssertionOutcome> = outcomes.iter().filter(|o| !o.passed).cloned().collect();
+    let result = ScenarioResult {
+        name: script.name.clone(),
+        started_at,
+        finished_at: now_secs(),
+        passed: failed.is_empty(),
+        outcomes,
+        failed,
+    };
+    let _ = app.emit("scenario-finished", &result);
+    if let Some(webhooks) = app.try_state::<WebhookDispatcher>() {
+        webhooks.notify(WebhookEvent::ScenarioCompleted, serde_json::json!({
+            "name": result.name,
+            "passed": result.passed,
+            "assertions": result.outcomes.len(),
+            "failed": result.failed.len(),
+        }));
+        if !result.passed {
+            webhooks.notify(WebhookEvent::AssertionFailed, serde_json::json!({
+                "name": result.name,
+                "failed": result.failed,
+            }));
+        }
+    }
+    Ok(result)
+}
+
+fn in_range(v: f64, min: &Option<f64>, max: &Option<f64>) -> bool {
+    min.is_none_or(|m| v >= m) && max.is_none_or(|m| v <= m)
+}
+
+fn evaluate(
+    assertion: &ScenarioAssertion,
+    samples: &HashMap<(String, String), Vec<(f64, f64)>>,
+    violations: &[ViolationSample],
+) -> (bool, String) {
+    let series_of = |device_id: &str, quantity: &str| {
+        samples
+            .get(&(device_id.to_string(), quantity.to_string()))
+            .map(|v| v.as_slice())
+            .unwrap_or(&[])
+    };
+    match assertion {
+        ScenarioAssertion::ValueAt { at_s, device_id, quantity, min, max } => {
+            match series_of(device_id, quantity).iter().find(|(t, _)| t >= at_s) {
+                Some((t, v)) => (in_range(*v, min, max), format!("t={:.1}s 实际值 {:.3}", t, v)),
+                None => (false, "该时刻之后无采样数据".to_string()),
+            }
+        }
+        ScenarioAssertion::RangeDuring { from_s, to_s, device_id, quantity, min, max } => {
+            let window: Vec<&(f64, f64)> = series_of(device_id, quantity)
+                .iter()
+                .filter(|(t, _)| t >= from_s && t <= to_s)
+                .collect();
+            if window.is_empty() {
+                return (false, "时间窗内无采样数据".to_string());
+            }
+            match window.iter().find(|(_, v)| !in_range(*v, min, max)) {
+                Some((t, v)) => (false, format!("t={:.1}s 首次越出范围，值 {:.3}", t, v)),
+                None => (true, format!("{} 个样本均在范围内", window.len())),
+            }
+        }
+        ScenarioAssertion::NoLimitViolation { from_s, to_s, quantity, device_id, include_warnings } => {
+            let threshold = if *include_warnings { LimitLevel::Warning } else { LimitLevel::Alarm };
+            let hit = violations.iter().find(|(t, d, q, level)| {
+                t >= from_s
+                    && t <= to_s
+                    && *level >= threshold
+                    && quantity.as_ref().is_none_or(|x| x == q)
+                    && device_id.as_ref().is_none_or(|x| x == d)
+            });
+            match hit {
+                Some((t, d, q, level)) => (false, format!("t={:.1}s {} {} 越限（{:?}）", t, d, q, level)),
+                None => (true, "无越限".to_string()),
+            }
+        }
+    }
+}
+
+/// 命令行模式：加载场景文件与其拓扑，启动仿真运行场景，结果 JSON 输出到 stdout；返回退出码（0 通过，1 断言失败，2 运行错误）
+pub async fn run_cli(app: &AppHandle, path: &str) -> i32 {
+    match run_cli_inner(app, path).await {
+        Ok(result) => {
+            println!("{}", serde_json::to_string_pretty(&result).unwrap_or_default());
+            for f in &result.failed {
+                eprintln!("断言失败 #{}: {}（{}）", f.index, f.description, f.detail);
+            }
+            result.exit_code()
+        }
+        Err(e) => {
+            eprintln!("场景运行失败: {}", e);
+            2
+        }
+    }
+}
+
+async fn run_cli_inner(app: &AppHandle, path: &str) -> Result<ScenarioResult, String> {
+    use std::sync::Arc;
+    let script = ScenarioScript::load(path)?;
+    let topology_path = script
+        .topology_path
+        .as_deref()
+        .ok_or("命令行模式需在场景文件中指定 topology_path")?;
+    let topology = crate::commands::topology::read_topology_file(topology_path)?;
+    let engine = app.state::<Arc<SimulationEngine>>().inner().clone();
+    engine.set_topology(topology).await;
+    let interval_ms = script.calculation_interval_ms.unwrap_or(1000);
+    // Python 内核在应用启动时异步拉起，未就绪时重试
+    let mut attempts = 0;
+    loop {
+        match engine.start(Some(app.clone()), interval_ms).await {
+            Ok(()) => break,
+            Err(e) if attempts >= 30 => return Err(e),
+            Err(_) => {
+                attempts += 1;
+                tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+            }
+        }
+    }
+    let result = run_scenario(&engine, app, &script).await;
+    if !script.charts.is_empty() {
+        // 停止前渲染：当前仿真库即本次运行数据；绘制在阻塞线程中进行，库锁只在读取曲线时持有；配图失败不影响场景结果
+        let db = app.state::<Arc<std::sync::Mutex<Option<Database>>>>().inner().clone();
+        let charts = script.charts.clone();
+        let rendered = tokio::task::spawn_blocking(move || {
+            for chart in &charts {
+                match chart_renderer::render_with_fallback(chart, &db) {
+                    Ok(r) => eprintln!("曲线图已生成: {}", r.output_path.unwrap_or_default()),
+                    Err(e) => eprintln!("曲线图生成失败: {}", e),
+                }
+            }
+        })
+        .await;
+        if let Err(e) = rendered {
+            eprintln!("曲线图生成失败: {}", e);
+        }
+    }
+    let _ = engine.stop().await;
+    result
+}
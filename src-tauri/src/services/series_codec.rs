@@ -0,0 +1,66 @@
+// 看板序列响应编码：默认 JSON；前端协商 binary 时使用紧凑列式二进制，避免大量 TimeSeriesPoint 的 JSON 序列化开销
+//
+// 二进制布局（小端，每段按 8 字节对齐，前端可直接以 Float64Array 视图读取）：
+//   header: "PVSB" | version u8 | 3 字节保留 | series_count u32 | 4 字节填充
+//   每条序列: key_len u32 | point_count u32 | key UTF-8（填充至 8 字节对齐）| timestamps f64 × n | values f64 × n
+use crate::commands::dashboard::TimeSeriesPoint;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::ipc::{InvokeResponseBody, Response};
+
+pub const SERIES_BINARY_MAGIC: &[u8; 4] = b"PVSB";
+pub const SERIES_BINARY_VERSION: u8 = 1;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SeriesEncoding {
+    #[default]
+    Json,
+    Binary,
+}
+
+fn pad8(buf: &mut Vec<u8>) {
+    while buf.len() % 8 != 0 {
+        buf.push(0);
+    }
+}
+
+pub fn encode_series_binary(series: &HashMap<String, Vec<TimeSeriesPoint>>) -> Vec<u8> {
+    let total_points: usize = series.values().map(|v| v.len()).sum();
+    let mut buf = Vec::with_capacity(16 + series.len() * 32 + total_points * 16);
+    buf.extend_from_slice(SERIES_BINARY_MAGIC);
+    buf.push(SERIES_BINARY_VERSION);
+    buf.extend_from_slice(&[0u8; 3]);
+    buf.extend_from_slice(&(series.len() as u32).to_le_bytes());
+    pad8(&mut buf);
+    let mut keys: Vec<&String> = series.keys().collect();
+    keys.sort();
+    for key in keys {
+        let points = &series[key];
+        buf.extend_from_slice(&(key.len() as u32).to_le_bytes());
+        buf.extend_from_slice(&(points.len() as u32).to_le_bytes());
+        buf.extend_from_slice(key.as_bytes());
+        pad8(&mut buf);
+        for p in points {
+            buf.extend_from_slice(&p.timestamp.to_le_bytes());
+        }
+        for p in points {
+            buf.extend_from_slice(&p.value.to_le_bytes());
+        }
+    }
+    buf
+}
+
+/// 按协商的编码构造 IPC 响应：binary 返回原始字节（前端收到 ArrayBuffer），json 与原接口返回结构一致
+pub fn encode_series_response(
+    series: &HashMap<String, Vec<TimeSeriesPoint>>,
+    encoding: SeriesEncoding,
+) -> Result<Response, String> {
+    match encoding {
+        SeriesEncoding::Json => {
+            let json = serde_json::to_string(series).map_err(|e| e.to_string())?;
+            Ok(Response::new(InvokeResponseBody::Json(json)))
+        }
+        SeriesEncoding::Binary => Ok(Response::new(InvokeResponseBody::Raw(encode_series_binary(series)))),
+    }
+}
@@ -0,0 +1,110 @@
+// 实时尾随订阅：按订阅周期增量读取当前仿真数据库，只推送各数据列自上次推送以来的新点（事件 series-tail-update）；
+// 取消订阅或主仿真停止时中止轮询任务
+use crate::commands::dashboard::TimeSeriesPoint;
+use crate::services::data_source::{DataSource, DbDataSource};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter};
+use tokio::task::JoinHandle;
+
+/// 单次推送的最大点数（每列），避免订阅初期或长时间停顿后一次推送过多
+const MAX_POINTS_PER_PUSH: usize = 5000;
+
+#[derive(Default)]
+pub struct SeriesTailManager {
+    next_id: AtomicU64,
+    /// 订阅 id -> 轮询任务
+    subscriptions: Mutex<HashMap<String, JoinHandle<()>>>,
+}
+
+impl SeriesTailManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 新建订阅并启动轮询任务；since 为起始时间（None 表示只推送订阅之后写入的点）
+    pub fn subscribe(
+        &self,
+        app: AppHandle,
+        current_db_path: Arc<Mutex<String>>,
+        keys: Vec<String>,
+        interval_ms: u64,
+        since: Option<f64>,
+    ) -> String {
+        let id = format!("tail_{}", self.next_id.fetch_add(1, Ordering::Relaxed) + 1);
+        let subscription_id = id.clone();
+        let handle = tokio::spawn(async move {
+            let start = since.unwrap_or_else(|| {
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs_f64()
+            });
+            let mut last_ts: HashMap<String, f64> = keys.iter().map(|k| (k.clone(), start)).collect();
+            let mut last_db_path = String::new();
+            let mut ticker = tokio::time::interval(std::time::Duration::from_millis(interval_ms.max(100)));
+            loop {
+                ticker.tick().await;
+                let db_path = current_db_path.lock().unwrap().clone();
+                if db_path.is_empty() {
+                    continue;
+                }
+                // 新一轮仿真换了数据库文件：从新文件开头继续推送
+                if db_path != last_db_path {
+                    if !last_db_path.is_empty() {
+                        for ts in last_ts.values_mut() {
+                            *ts = f64::MIN;
+                        }
+                    }
+                    last_db_path = db_path.clone();
+                }
+                let source = DbDataSource::new(db_path.clone());
+                let mut series: HashMap<String, Vec<TimeSeriesPoint>> = HashMap::new();
+                for key in &keys {
+                    let since_ts = last_ts.get(key).copied().unwrap_or(f64::MIN);
+                    let Ok(mut points) = source.fetch_series(key, Some(since_ts), None, usize::MAX) else {
+                        continue;
+                    };
+                    points.retain(|p| p.timestamp > since_ts);
+                    if points.len() > MAX_POINTS_PER_PUSH {
+                        points.drain(..points.len() - MAX_POINTS_PER_PUSH);
+                    }
+                    if let Some(last) = points.last() {
+                        last_ts.insert(key.clone(), last.timestamp);
+                        series.insert(key.clone(), points);
+                    }
+                }
+                if !series.is_empty() {
+                    let _ = app.emit("series-tail-update", serde_json::json!({
+                        "subscription_id": subscription_id,
+                        "db_path": db_path,
+                        "series": series,
+                    }));
+                }
+            }
+        });
+        self.subscriptions.lock().unwrap().insert(id.clone(), handle);
+        id
+    }
+
+    pub fn unsubscribe(&self, subscription_id: &str) -> bool {
+        match self.subscriptions.lock().unwrap().remove(subscription_id) {
+            Some(handle) => {
+                handle.abort();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 中止全部订阅（主仿真停止时调用），返回中止数量
+    pub fn stop_all(&self) -> usize {
+        let mut subscriptions = self.subscriptions.lock().unwrap();
+        let n = subscriptions.len();
+        for (_, handle) in subscriptions.drain() {
+            handle.abort();
+        }
+        n
+    }
+}
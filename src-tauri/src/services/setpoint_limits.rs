@@ -0,0 +1,138 @@
+// 设定值物理限值：手动设定、Modbus 功率设定等超出设备铭牌额定功率时按额定限幅，记录限幅事件并按设备计数
+use crate::domain::topology::{Device, DeviceType};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// 限幅事件保留上限
+const MAX_CLAMP_EVENTS: usize = 500;
+
+/// 设备有功可调范围（kW，内核原生约定）
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct PowerRating {
+    pub min_kw: f64,
+    pub max_kw: f64,
+}
+
+/// 从设备铭牌读取有功范围：光伏/负荷/充电桩 [0, 额定]，储能 ±最大功率；
+/// 只认 rated_power_kw / max_power_kw（rated_power 会被手动设定覆盖，不作为铭牌）；未配置铭牌时不限幅
+pub fn power_rating(device: &Device) -> Option<PowerRating> {
+    let rated_kw = ["rated_power_kw", "max_power_kw"]
+        .iter()
+        .find_map(|k| device.properties.get(*k).and_then(|v| v.as_f64()))
+        .filter(|r| *r > 0.0)?;
+    match device.device_type {
+        DeviceType::Pv | DeviceType::Load | DeviceType::Charger => Some(PowerRating { min_kw: 0.0, max_kw: rated_kw }),
+        DeviceType::Storage => Some(PowerRating { min_kw: -rated_kw, max_kw: rated_kw }),
+        _ => None,
+    }
+}
+
+/// 一次限幅：原始请求值与实际下发值
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetpointClamp {
+    pub device_id: String,
+    /// 指令来源：manual / modbus / command 等
+    pub source: String,
+    /// 被限幅的属性（p_kw / rated_power / active_power）
+    pub field: String,
+    pub requested_kw: f64,
+    pub applied_kw: f64,
+    pub rating: PowerRating,
+    /// 限幅时的仿真时间戳
+    pub timestamp: f64,
+}
+
+#[derive(Default)]
+pub struct SetpointLimiter {
+    counts: HashMap<String, u64>,
+    events: VecDeque<SetpointClamp>,
+    /// 待仿真循环推送到前端的限幅事件
+    pending: Vec<SetpointClamp>,
+}
+
+impl SetpointLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 新一轮仿真：清空计数与事件
+    pub fn reset(&mut self) {
+        self.counts.clear();
+        self.events.clear();
+        self.pending.clear();
+    }
+
+    /// 运行中清零限幅计数（事件记录保留）；device_ids 为 None 表示全部设备，返回被清零的设备
+    pub fn reset_counts(&mut self, device_ids: Option<&HashSet<String>>) -> Vec<String> {
+        let cleared: Vec<String> = self
+            .counts
+            .keys()
+            .filter(|id| device_ids.is_none_or(|ids| ids.contains(*id)))
+            .cloned()
+            .collect();
+        for id in &cleared {
+            self.counts.remove(id);
+        }
+        cleared
+    }
+
+    /// 按设备额定范围限幅，超限时按仿真时间戳 timestamp 记录事件；返回实际下发值
+    pub fn clamp(&mut self, device: &Device, source: &str, field: &str, requested_kw: f64, timestamp: f64) -> f64 {
+        let Some(rating) = power_rating(device) else {
+            return requested_kw;
+        };
+        let applied_kw = requested_kw.clamp(rating.min_kw, rating.max_kw);
+        if applied_kw != requested_kw {
+            eprintln!(
+                "设备 {} 设定值超出额定范围 [{}, {}] kW：请求 {} kW，按 {} kW 下发（来源 {}）",
+                device.id, rating.min_kw, rating.max_kw, requested_kw, applied_kw, source
+            );
+            let event = SetpointClamp {
+                device_id: device.id.clone(),
+                source: source.to_string(),
+                field: field.to_string(),
+                requested_kw,
+                applied_kw,
+                rating,
+                timestamp,
+            };
+            *self.counts.entry(device.id.clone()).or_insert(0) += 1;
+            self.events.push_back(event.clone());
+            while self.events.len() > MAX_CLAMP_EVENTS {
+                self.events.pop_front();
+            }
+            self.pending.push(event);
+        }
+        applied_kw
+    }
+
+    /// 对属性增量中的功率设定（p_kw / rated_power）限幅，原地修改
+    pub fn clamp_properties(
+        &mut self,
+        device: &Device,
+        source: &str,
+        properties: &mut serde_json::Map<String, serde_json::Value>,
+        timestamp: f64,
+    ) {
+        for field in ["p_kw", "rated_power"] {
+            if let Some(requested) = properties.get(field).and_then(|v| v.as_f64()) {
+                let applied = self.clamp(device, source, field, requested, timestamp);
+                if applied != requested {
+                    properties.insert(field.to_string(), serde_json::json!(applied));
+                }
+            }
+        }
+    }
+
+    pub fn counts(&self) -> HashMap<String, u64> {
+        self.counts.clone()
+    }
+
+    pub fn events(&self) -> Vec<SetpointClamp> {
+        self.events.iter().cloned().collect()
+    }
+
+    pub fn take_pending(&mut self) -> Vec<SetpointClamp> {
+        std::mem::take(&mut self.pending)
+    }
+}
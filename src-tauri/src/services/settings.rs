@@ -0,0 +1,442 @@
+// 应用设置：持久化到工作目录 settings.json（与仿真数据库同目录），包含用户计算预设、功率符号约定、内核保温开关、Webhook 配置、外部接口令牌、随机种子、设备控制状态、内核看门狗、内核日志、内核请求超时表、并行求解内核池、结果共享文件传输、远程内核端点、Modbus 网关与潮流求解内核
+use crate::domain::auth::{default_peer_scopes, ApiScope, ApiToken, PeerScopeRule};
+use crate::domain::device::StoredDeviceControl;
+use crate::domain::modbus_gateway::ModbusGatewayConfig;
+use crate::domain::preset::{builtin_presets, CalculationPreset};
+use crate::domain::sign_convention::SignConvention;
+use crate::domain::simulation::{KernelLogConfig, KernelPoolConfig, KernelRpcConfig, KernelWatchdogConfig, ResultTransferConfig};
+use crate::domain::webhook::WebhookConfig;
+use crate::services::kernel_factory::KernelType;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppSettings {
+    /// 用户预设（与内置预设同名时覆盖内置）
+    #[serde(default)]
+    pub calculation_presets: Vec<CalculationPreset>,
+    /// 项目级功率符号约定（落库、Modbus 编码与分析统一使用）
+    #[serde(default)]
+    pub sign_convention: SignConvention,
+    /// 内核保温：停止仿真后保留已构建的网络，缩短下次启动时间
+    #[serde(default = "default_keep_kernel_warm")]
+    pub keep_kernel_warm: bool,
+    /// 外部 Webhook 通知
+    #[serde(default)]
+    pub webhooks: Vec<WebhookConfig>,
+    /// 外部接口 API 令牌（仅保存摘要）
+    #[serde(default)]
+    pub api_tokens: Vec<ApiToken>,
+    /// 未携带令牌、且不匹配地址规则的外部连接（Modbus TCP 客户端等）的权限；None 表示拒绝。默认只读
+    #[serde(default = "default_anonymous_scope")]
+    pub external_anonymous_scope: Option<ApiScope>,
+    /// 按客户端地址（IP / CIDR）授予的权限，先于匿名权限匹配；默认本机回环地址为 control
+    #[serde(default = "default_peer_scopes")]
+    pub external_peer_scopes: Vec<PeerScopeRule>,
+    /// 随机模式全局种子：设置后随机数据在各次会话间可复现
+    #[serde(default)]
+    pub random_seed: Option<u64>,
+    /// 设备控制状态（工作模式、随机配置、手动设定）：设备 ID -> 状态，启动仿真时自动重新下发
+    #[serde(default)]
+    pub device_controls: HashMap<String, StoredDeviceControl>,
+    /// Python 内核看门狗
+    #[serde(default)]
+    pub kernel_watchdog: KernelWatchdogConfig,
+    /// Python 内核日志级别与滚动上限
+    #[serde(default)]
+    pub kernel_log: KernelLogConfig,
+    /// 内核 JSON-RPC 各方法的超时与重试
+    #[serde(default)]
+    pub kernel_rpc: KernelRpcConfig,
+    /// 并行求解内核池的进程上限与默认并行度
+    #[serde(default)]
+    pub kernel_pool: KernelPoolConfig,
+    /// 单步结果经共享文件传输的开关与阈值
+    #[serde(default)]
+    pub result_transfer: ResultTransferConfig,
+    /// 远程内核端点（tcp://主机:端口 或 ws://主机:端口/路径）；None 时拉起本地内核进程。启动时读取，环境变量 PYTHON_KERNEL_ENDPOINT 优先；共享令牌经环境变量 PYTHON_KERNEL_TOKEN 提供
+    #[serde(default)]
+    pub kernel_endpoint: Option<String>,
+    /// Modbus 网关模式（单端口按 unit id 转发）及设备 → unit id 映射表
+    #[serde(default)]
+    pub modbus_gateway: ModbusGatewayConfig,
+    /// 主仿真选用的潮流求解内核，启动时恢复
+    #[serde(default)]
+    pub power_kernel: KernelType,
+}
+
+fn default_keep_kernel_warm() -> bool {
+    true
+}
+
+fn default_anonymous_scope() -> Option<ApiScope> {
+    Some(ApiScope::ReadOnly)
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            calculation_presets: Vec::new(),
+            sign_convention: SignConvention::default(),
+            keep_kernel_warm: default_keep_kernel_warm(),
+            webhooks: Vec::new(),
+            api_tokens: Vec::new(),
+            external_anonymous_scope: default_anonymous_scope(),
+            external_peer_scopes: default_peer_scopes(),
+            random_seed: None,
+            device_controls: HashMap::new(),
+            kernel_watchdog: KernelWatchdogConfig::default(),
+            kernel_log: KernelLogConfig::default(),
+            kernel_rpc: KernelRpcConfig::default(),
+            kernel_pool: KernelPoolConfig::default(),
+            result_transfer: ResultTransferConfig::default(),
+            kernel_endpoint: None,
+            modbus_gateway: ModbusGatewayConfig::default(),
+            power_kernel: KernelType::default(),
+        }
+    }
+}
+
+pub struct SettingsStore {
+    path: PathBuf,
+    settings: Mutex<AppSettings>,
+}
+
+impl SettingsStore {
+    /// 从工作目录 settings.json 加载；文件不存在或解析失败时使用默认设置
+    pub fn load() -> Self {
+        let path = std::env::current_dir()
+            .unwrap_or_default()
+            .join("settings.json");
+        let settings = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|s| match serde_json::from_str::<AppSettings>(&s) {
+                Ok(v) => Some(v),
+                Err(e) => {
+                    eprintln!("解析设置文件失败，使用默认设置: {}", e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+        Self {
+            path,
+            settings: Mutex::new(settings),
+        }
+    }
+
+    fn save(&self, settings: &AppSettings) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+        std::fs::write(&self.path, content).map_err(|e| format!("写入设置文件失败: {}", e))
+    }
+
+    /// 全部可用预设：内置预设（可被同名用户预设覆盖）+ 用户预设
+    pub fn list_presets(&self) -> Vec<CalculationPreset> {
+        let user = self.settings.lock().unwrap().calculation_presets.clone();
+        let mut presets: Vec<CalculationPreset> = builtin_presets()
+            .into_iter()
+            .filter(|b| !user.iter().any(|u| u.name == b.name))
+            .collect();
+        presets.extend(user);
+        presets
+    }
+
+    pub fn get_preset(&self, name: &str) -> Option<CalculationPreset> {
+        self.list_presets().into_iter().find(|p| p.name == name)
+    }
+
+    pub fn save_preset(&self, mut preset: CalculationPreset) -> Result<(), String> {
+        preset.validate()?;
+        preset.builtin = false;
+        let mut guard = self.settings.lock().unwrap();
+        let mut next = guard.clone();
+        next.calculation_presets.retain(|p| p.name != preset.name);
+        next.calculation_presets.push(preset);
+        self.save(&next)?;
+        *guard = next;
+        Ok(())
+    }
+
+    /// 删除用户预设；内置预设不可删除（删除同名覆盖后恢复内置值）
+    pub fn delete_preset(&self, name: &str) -> Result<(), String> {
+        let mut guard = self.settings.lock().unwrap();
+        if !guard.calculation_presets.iter().any(|p| p.name == name) {
+            return Err(if builtin_presets().iter().any(|p| p.name == name) {
+                format!("内置预设不可删除: {}", name)
+            } else {
+                format!("预设不存在: {}", name)
+            });
+        }
+        let mut next = guard.clone();
+        next.calculation_presets.retain(|p| p.name != name);
+        self.save(&next)?;
+        *guard = next;
+        Ok(())
+    }
+
+    pub fn sign_convention(&self) -> SignConvention {
+        self.settings.lock().unwrap().sign_convention.clone()
+    }
+
+    pub fn set_sign_convention(&self, convention: SignConvention) -> Result<(), String> {
+        let mut guard = self.settings.lock().unwrap();
+        let mut next = guard.clone();
+        next.sign_convention = convention;
+        self.save(&next)?;
+        *guard = next;
+        Ok(())
+    }
+
+    pub fn keep_kernel_warm(&self) -> bool {
+        self.settings.lock().unwrap().keep_kernel_warm
+    }
+
+    pub fn set_keep_kernel_warm(&self, enabled: bool) -> Result<(), String> {
+        let mut guard = self.settings.lock().unwrap();
+        let mut next = guard.clone();
+        next.keep_kernel_warm = enabled;
+        self.save(&next)?;
+        *guard = next;
+        Ok(())
+    }
+
+    pub fn random_seed(&self) -> Option<u64> {
+        self.settings.lock().unwrap().random_seed
+    }
+
+    pub fn set_random_seed(&self, seed: Option<u64>) -> Result<(), String> {
+        let mut guard = self.settings.lock().unwrap();
+        let mut next = guard.clone();
+        next.random_seed = seed;
+        self.save(&next)?;
+        *guard = next;
+        Ok(())
+    }
+
+    pub fn kernel_watchdog(&self) -> KernelWatchdogConfig {
+        self.settings.lock().unwrap().kernel_watchdog.clone()
+    }
+
+    pub fn set_kernel_watchdog(&self, config: KernelWatchdogConfig) -> Result<(), String> {
+        let mut guard = self.settings.lock().unwrap();
+        let mut next = guard.clone();
+        next.kernel_watchdog = config;
+        self.save(&next)?;
+        *guard = next;
+        Ok(())
+    }
+
+    pub fn kernel_log(&self) -> KernelLogConfig {
+        self.settings.lock().unwrap().kernel_log.clone()
+    }
+
+    pub fn set_kernel_log(&self, config: KernelLogConfig) -> Result<(), String> {
+        let mut guard = self.settings.lock().unwrap();
+        let mut next = guard.clone();
+        next.kernel_log = config;
+        self.save(&next)?;
+        *guard = next;
+        Ok(())
+    }
+
+    pub fn kernel_rpc(&self) -> KernelRpcConfig {
+        self.settings.lock().unwrap().kernel_rpc.clone()
+    }
+
+    pub fn set_kernel_rpc(&self, config: KernelRpcConfig) -> Result<(), String> {
+        let mut guard = self.settings.lock().unwrap();
+        let mut next = guard.clone();
+        next.kernel_rpc = config;
+        self.save(&next)?;
+        *guard = next;
+        Ok(())
+    }
+
+    pub fn kernel_pool(&self) -> KernelPoolConfig {
+        self.settings.lock().unwrap().kernel_pool.clone()
+    }
+
+    pub fn set_kernel_pool(&self, config: KernelPoolConfig) -> Result<(), String> {
+        let mut guard = self.settings.lock().unwrap();
+        let mut next = guard.clone();
+        next.kernel_pool = config;
+        self.save(&next)?;
+        *guard = next;
+        Ok(())
+    }
+
+    pub fn result_transfer(&self) -> ResultTransferConfig {
+        self.settings.lock().unwrap().result_transfer.clone()
+    }
+
+    pub fn set_result_transfer(&self, config: ResultTransferConfig) -> Result<(), String> {
+        let mut guard = self.settings.lock().unwrap();
+        let mut next = guard.clone();
+        next.result_transfer = config;
+        self.save(&next)?;
+        *guard = next;
+        Ok(())
+    }
+
+    pub fn modbus_gateway(&self) -> ModbusGatewayConfig {
+        self.settings.lock().unwrap().modbus_gateway.clone()
+    }
+
+    pub fn set_modbus_gateway(&self, config: ModbusGatewayConfig) -> Result<(), String> {
+        config.validate()?;
+        let mut guard = self.settings.lock().unwrap();
+        let mut next = guard.clone();
+        next.modbus_gateway = config;
+        self.save(&next)?;
+        *guard = next;
+        Ok(())
+    }
+
+    pub fn kernel_endpoint(&self) -> Option<String> {
+        self.settings.lock().unwrap().kernel_endpoint.clone()
+    }
+
+    pub fn set_kernel_endpoint(&self, endpoint: Option<String>) -> Result<(), String> {
+        let mut guard = self.settings.lock().unwrap();
+        let mut next = guard.clone();
+        next.kernel_endpoint = endpoint;
+        self.save(&next)?;
+        *guard = next;
+        Ok(())
+    }
+
+    pub fn power_kernel(&self) -> KernelType {
+        self.settings.lock().unwrap().power_kernel
+    }
+
+    pub fn set_power_kernel(&self, kernel_type: KernelType) -> Result<(), String> {
+        let mut guard = self.settings.lock().unwrap();
+        let mut next = guard.clone();
+        next.power_kernel = kernel_type;
+        self.save(&next)?;
+        *guard = next;
+        Ok(())
+    }
+
+    pub fn webhooks(&self) -> Vec<WebhookConfig> {
+        self.settings.lock().unwrap().webhooks.clone()
+    }
+
+    /// 保存 Webhook（同 ID 覆盖），返回保存后的全部配置
+    pub fn save_webhook(&self, webhook: WebhookConfig) -> Result<Vec<WebhookConfig>, String> {
+        webhook.validate()?;
+        let mut guard = self.settings.lock().unwrap();
+        let mut next = guard.clone();
+        match next.webhooks.iter_mut().find(|w| w.id == webhook.id) {
+            Some(existing) => *existing = webhook,
+            None => next.webhooks.push(webhook),
+        }
+        self.save(&next)?;
+        *guard = next;
+        Ok(guard.webhooks.clone())
+    }
+
+    pub fn delete_webhook(&self, id: &str) -> Result<Vec<WebhookConfig>, String> {
+        let mut guard = self.settings.lock().unwrap();
+        if !guard.webhooks.iter().any(|w| w.id == id) {
+            return Err(format!("Webhook 不存在: {}", id));
+        }
+        let mut next = guard.clone();
+        next.webhooks.retain(|w| w.id != id);
+        self.save(&next)?;
+        *guard = next;
+        Ok(guard.webhooks.clone())
+    }
+
+    pub fn api_tokens(&self) -> Vec<ApiToken> {
+        self.settings.lock().unwrap().api_tokens.clone()
+    }
+
+    pub fn add_api_token(&self, token: ApiToken) -> Result<Vec<ApiToken>, String> {
+        let mut guard = self.settings.lock().unwrap();
+        let mut next = guard.clone();
+        next.api_tokens.push(token);
+        self.save(&next)?;
+        *guard = next;
+        Ok(guard.api_tokens.clone())
+    }
+
+    /// 吊销令牌（保留记录以便审计追溯）
+    pub fn revoke_api_token(&self, id: &str) -> Result<Vec<ApiToken>, String> {
+        let mut guard = self.settings.lock().unwrap();
+        let mut next = guard.clone();
+        let token = next
+            .api_tokens
+            .iter_mut()
+            .find(|t| t.id == id)
+            .ok_or_else(|| format!("令牌不存在: {}", id))?;
+        token.revoked = true;
+        self.save(&next)?;
+        *guard = next;
+        Ok(guard.api_tokens.clone())
+    }
+
+    pub fn external_anonymous_scope(&self) -> Option<ApiScope> {
+        self.settings.lock().unwrap().external_anonymous_scope
+    }
+
+    pub fn set_external_anonymous_scope(&self, scope: Option<ApiScope>) -> Result<(), String> {
+        let mut guard = self.settings.lock().unwrap();
+        let mut next = guard.clone();
+        next.external_anonymous_scope = scope;
+        self.save(&next)?;
+        *guard = next;
+        Ok(())
+    }
+
+    pub fn external_peer_scopes(&self) -> Vec<PeerScopeRule> {
+        self.settings.lock().unwrap().external_peer_scopes.clone()
+    }
+
+    pub fn set_external_peer_scopes(&self, rules: Vec<PeerScopeRule>) -> Result<(), String> {
+        for rule in &rules {
+            rule.validate()?;
+        }
+        let mut guard = self.settings.lock().unwrap();
+        let mut next = guard.clone();
+        next.external_peer_scopes = rules;
+        self.save(&next)?;
+        *guard = next;
+        Ok(())
+    }
+
+    pub fn device_controls(&self) -> HashMap<String, StoredDeviceControl> {
+        self.settings.lock().unwrap().device_controls.clone()
+    }
+
+    /// 修改并保存单个设备的控制状态（不存在时新建）
+    pub fn update_device_control(&self, device_id: &str, update: impl FnOnce(&mut StoredDeviceControl)) -> Result<(), String> {
+        let mut guard = self.settings.lock().unwrap();
+        let mut next = guard.clone();
+        let control = next.device_controls.entry(device_id.to_string()).or_default();
+        update(control);
+        control.updated_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs_f64())
+            .unwrap_or(0.0);
+        self.save(&next)?;
+        *guard = next;
+        Ok(())
+    }
+
+    /// 清除指定设备（未指定时为全部）的控制状态，返回清除的设备数
+    pub fn clear_device_controls(&self, device_ids: Option<&[String]>) -> Result<usize, String> {
+        let mut guard = self.settings.lock().unwrap();
+        let mut next = guard.clone();
+        let before = next.device_controls.len();
+        match device_ids {
+            Some(ids) => next.device_controls.retain(|id, _| !ids.contains(id)),
+            None => next.device_controls.clear(),
+        }
+        let removed = before - next.device_controls.len();
+        self.save(&next)?;
+        *guard = next;
+        Ok(removed)
+    }
+}
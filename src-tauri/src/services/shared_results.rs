@@ -0,0 +1,71 @@
+// 大结果共享文件：本地内核把单步结果写入此处创建的内存映射文件（见 python-kernel/shared_results.py），
+// JSON-RPC 只返回描述符 {path, sequence, length, encoding}；这里按描述符校验头部后读回并解码。
+// 文件布局：头部 20 字节（小端：魔数 b"PVSR"、序号 u64、数据长度 u64）+ 结果数据
+use crate::services::kernel_transport::WireEncoding;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+const MAGIC: &[u8; 4] = b"PVSR";
+const HEADER_LEN: usize = 20;
+/// 单步结果上限（与管道单条消息上限一致）
+pub const MAX_SHARED_RESULT_BYTES: u64 = 256 * 1024 * 1024;
+
+/// 一个内核客户端独占的共享结果文件；释放时删除
+pub struct SharedResultFile {
+    path: PathBuf,
+}
+
+impl SharedResultFile {
+    /// 在临时目录创建空文件（由内核按需扩大并映射）
+    pub fn create() -> std::io::Result<Self> {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.subsec_nanos())
+            .unwrap_or(0);
+        let path = std::env::temp_dir().join(format!("pvsc-kernel-results-{}-{}.bin", std::process::id(), nanos));
+        File::create(&path)?;
+        Ok(Self { path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 按描述符读回结果：头部的序号与长度须与描述符一致，否则说明文件已被覆盖或不是本次写入
+    pub fn read(&self, descriptor: &serde_json::Value) -> Result<serde_json::Value, String> {
+        let sequence = descriptor.get("sequence").and_then(|v| v.as_u64()).ok_or("共享结果描述符缺少序号")?;
+        let length = descriptor.get("length").and_then(|v| v.as_u64()).ok_or("共享结果描述符缺少长度")?;
+        let encoding = descriptor
+            .get("encoding")
+            .and_then(|v| v.as_str())
+            .and_then(WireEncoding::parse)
+            .ok_or("共享结果描述符的编码无效")?;
+        if length > MAX_SHARED_RESULT_BYTES {
+            return Err(format!("共享结果过大: {} 字节", length));
+        }
+
+        let mut file = File::open(&self.path).map_err(|e| format!("打开共享结果文件失败: {}", e))?;
+        let mut header = [0u8; HEADER_LEN];
+        file.read_exact(&mut header).map_err(|e| format!("读取共享结果头部失败: {}", e))?;
+        let header_sequence = u64::from_le_bytes(header[4..12].try_into().unwrap());
+        let header_length = u64::from_le_bytes(header[12..20].try_into().unwrap());
+        if &header[..4] != MAGIC || header_sequence != sequence || header_length != length {
+            return Err(format!(
+                "共享结果文件与描述符不一致（序号 {} / {}，长度 {} / {}）",
+                header_sequence, sequence, header_length, length
+            ));
+        }
+
+        let mut body = vec![0u8; length as usize];
+        file.read_exact(&mut body).map_err(|e| format!("读取共享结果失败: {}", e))?;
+        encoding.decode(&body).map_err(|e| format!("解码共享结果失败: {}", e))
+    }
+}
+
+impl Drop for SharedResultFile {
+    fn drop(&mut self) {
+        // 内核进程仍持有映射时（Windows）删除会失败，留给系统清理临时目录
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
@@ -1,24 +1,127 @@
 // 仿真引擎核心
-use crate::domain::simulation::{SimulationStatus, DeviceWorkModes, StorageState};
-use crate::domain::topology::Topology;
-use crate::services::python_bridge::PythonBridge;
+use crate::domain::simulation::{CounterGroup, SimClock, SimulationStatus, DeviceWorkModes, StorageState, PropertyChangeRecord, DevicePropertyDrift, PropertyDrift, DeviceEnergyCounters, SimulationState, TopologyPreloadResult, IslandSnapshotSummary, PartitionedSnapshotResult, SimulationError, SnapshotBatchResult, SnapshotSampleSummary, SnapshotSweepResult, BranchOverload, BusVoltageViolation, ContingencyCase, ContingencyLimits, ContingencyReport};
+use crate::domain::device::{GridSupportConfig, PfResponseConfig, ReactiveControlConfig, StoredDeviceControl};
+use crate::domain::grid_schedule::{GridLimitViolation, GridSchedule};
+use crate::domain::preset::RunOptions;
+use crate::domain::topology::{Device, DeviceType, Topology};
+use crate::services::python_bridge::{KernelClient, PythonBridge};
+use crate::services::kernel_factory::KernelType;
+use crate::services::kernel_pool::{KernelJob, KernelPool};
+use crate::services::sweep::{self, SweepParameter};
+use crate::services::run_recovery::{self, CheckpointFileInfo, RunCheckpoint, RunManifest, RunStatus, SimulationCheckpointFile};
 use crate::services::database::Database;
+use crate::services::db_writer::DbWriter;
+use crate::services::breakpoints::{Breakpoint, BreakpointHit, BreakpointSet};
+use crate::services::limit_monitor::{LimitAlert, LimitBand, LimitKpi, LimitLevel, LimitMonitor};
+use crate::services::webhook::WebhookDispatcher;
+use crate::domain::webhook::WebhookEvent;
+use crate::services::meter_dropout::{MeterDropoutEmulator, MeterDropoutReport};
+use crate::services::meter_accuracy::{self, MeterAccuracyModel};
+use crate::services::random_generator::{self, RandomGeneratorBank};
+use crate::services::forecast_accuracy::ForecastAccuracyTracker;
+use crate::services::delay_simulator::{DelaySimulator, DeviceDelays};
+use crate::services::setpoint_limits::{SetpointClamp, SetpointLimiter};
+use crate::services::oltc::{OltcController, TapOperation};
+use crate::services::ems::{EmsConfig, EmsController, EmsStatus};
+use crate::services::protection::{ProtectionRelays, ProtectionTrip};
+use crate::services::grid_support::GridSupportController;
+use crate::services::frequency_model::{self, FrequencyModel};
+use crate::services::ev_sessions::{self, EvSessionBank};
+use crate::services::run_summary::{self, RunStatsCollector, RunSummary};
+use crate::services::profile_playback::{PowerProfile, ProfilePlayer};
+use crate::services::solar_model::SolarPanelConfig;
+use crate::services::weather::{self, WeatherBinding, WeatherSeries, WeatherSourceSpec};
+use crate::services::results_pipeline::ResultsPipeline;
+use crate::services::result_index::ResultIndex;
+use crate::services::multi_rate::MultiRateScheduler;
+use crate::services::daily_rollover::{self, DailyEnergyArchive};
+use crate::services::settings::SettingsStore;
+use crate::services::simulation_manager::SimulationManager;
+use crate::services::fault_injector::{ActiveFault, FaultInjector, FaultRecord, FaultType};
+use crate::services::event_scheduler::{
+    AppliedEventRecord, EventAction, EventSchedule, EventScheduleStatus, EventScheduler, GridBoundary, ScheduledEvent,
+};
+use crate::services::kernel_sync::{self, KernelSyncReport};
+use crate::services::window_hub::{self, WindowEventHub};
+use crate::services::replay::ReplayService;
+use crate::domain::events::{
+    DeviceDataUpdate, FaultStateChanged, GridLimitViolationUpdate, LimitAlertsUpdate, ModbusRegistersUpdated, ScheduledEventApplied,
+    PythonKernelRestarted, DailyCountersRolledOver, EvSessionEnded, FrequencyUpdate, SetpointClamped, SimulationSummary, TransformerTapChanged, ProtectionTripped, SimulationAutoStopped, SimulationBreakpointHit, SimulationErrorsUpdate, StateStepCommitted, EVENT_SCHEMA_VERSION,
+};
+use crate::domain::forecast::{DeviceForecast, ForecastAccuracy};
+use crate::domain::random_profile::RandomProfile;
+use crate::services::storage_schedule::StorageScheduleExecutor;
+use crate::domain::storage_schedule::{ScheduleAdherence, StorageSchedule};
+use crate::domain::sign_convention::SignConvention;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use tokio::sync::Mutex;
-use std::collections::HashMap;
-use tauri::{AppHandle, Emitter, Manager};
+use std::collections::{HashMap, HashSet};
+use tauri::{AppHandle, Manager};
 use tokio::time::{interval, Duration};
 use tokio::sync::mpsc;
 use std::time::{SystemTime, UNIX_EPOCH};
 use std::sync::Mutex as StdMutex;
 
+/// 本步的电表映射：目标设备的结果按读数系数换算为各电表读数，落库时剔除本步通信中断的电表
+struct MeterFanout<'a> {
+    devices: &'a HashMap<String, Device>,
+    target_to_meters: &'a HashMap<String, Vec<String>>,
+    dropped_meters: &'a HashSet<String>,
+    factors: &'a HashMap<String, f64>,
+}
+
+impl<'a> MeterFanout<'a> {
+    fn new(
+        topology: &'a Topology,
+        target_to_meters: &'a HashMap<String, Vec<String>>,
+        dropped_meters: &'a HashSet<String>,
+        factors: &'a HashMap<String, f64>,
+    ) -> Self {
+        Self { devices: &topology.devices, target_to_meters, dropped_meters, factors }
+    }
+
+    /// 指向目标设备的电表及其读数 (电表 id, 有功, 无功)
+    fn readings(&self, target_id: &str, p_kw: Option<f64>, q_kvar: Option<f64>) -> impl Iterator<Item = (&'a String, Option<f64>, Option<f64>)> + '_ {
+        self.target_to_meters.get(target_id).into_iter().flatten().map(move |meter_id| {
+            let (p, q) = meter_accuracy::meter_reading(self.factors, meter_id, p_kw, q_kvar);
+            (meter_id, p, q)
+        })
+    }
+
+    /// 电表读数落库（data_json 与目标设备相同），跳过本步通信中断的电表
+    fn record_rows(&self, pipeline: &mut ResultsPipeline, target_id: &str, timestamp: f64, p_kw: Option<f64>, q_kvar: Option<f64>, data_json: Option<&str>) {
+        for (meter_id, p, q) in self.readings(target_id, p_kw, q_kvar).filter(|(m, _, _)| !self.dropped_meters.contains(*m)) {
+            pipeline.record_row(meter_id, timestamp, p, q, data_json, self.devices.get(meter_id).map(|d| d.device_type.as_str()));
+        }
+    }
+
+    /// 电表读数写入当前功率缓存（不剔除通信中断的电表）
+    fn cache_readings(&self, cache: &mut HashMap<String, (f64, Option<f64>, Option<f64>)>, target_id: &str, timestamp: f64, p_kw: Option<f64>, q_kvar: Option<f64>) {
+        for (meter_id, p, q) in self.readings(target_id, p_kw, q_kvar) {
+            cache.insert(meter_id.clone(), (timestamp, p, q));
+        }
+    }
+}
+
+/// 本地控制来源（定时事件、储能计划、EMS）：不受远程控制开关限制，设定值同样经额定限幅
+fn is_local_source(source: &str) -> bool {
+    matches!(source, "schedule" | "storage_schedule" | "ems")
+}
+
 pub struct SimulationEngine {
     status: Arc<tokio::sync::Mutex<SimulationStatus>>,
     device_modes: Arc<tokio::sync::Mutex<DeviceWorkModes>>,
+    /// 内核进程的生命周期（启动/停止/崩溃重启）需独占
     python_bridge: Arc<Mutex<PythonBridge>>,
+    /// 内核 RPC 客户端：计算循环与设备命令并发调用，互不持锁
+    kernel: KernelClient,
     topology: Arc<tokio::sync::Mutex<Option<Topology>>>,
+    /// 潮流结果元素 -> 设备 ID 索引（set_topology 时重建）
+    result_index: Arc<StdMutex<Arc<ResultIndex>>>,
     database: Arc<StdMutex<Option<Database>>>,
+    /// 本轮仿真的异步落库任务（停止时等待写完）
+    db_writer: Arc<StdMutex<Option<DbWriter>>>,
     /// 当前仿真使用的数据库文件路径（每次启动仿真时切换为新文件，供数据看板「当前应用数据库」使用）
     current_db_path: Arc<StdMutex<String>>,
     /// 全局是否允许远程控制（总闸）
@@ -37,11 +140,94 @@ pub struct SimulationEngine {
     cancel_tx: Arc<tokio::sync::Mutex<Option<mpsc::Sender<()>>>>,
     /// 设备级仿真参数（采集频率 samplingIntervalMs 等），用于 Modbus IR 更新节流
     device_sim_params: Arc<tokio::sync::Mutex<HashMap<String, serde_json::Value>>>,
+    /// 设备软限值监控（预警/报警带），每步评估计算结果
+    limit_monitor: Arc<StdMutex<LimitMonitor>>,
+    /// 条件断点，每步评估，命中时暂停仿真
+    breakpoints: Arc<StdMutex<BreakpointSet>>,
+    /// 外部电网分时功率限值越限记录（最近 MAX_GRID_LIMIT_VIOLATIONS 条）
+    grid_limit_violations: Arc<StdMutex<Vec<GridLimitViolation>>>,
+    /// 启动时加载的拓扑快照，用于对比运行中被远程控制等修改的设备属性
+    baseline_topology: Arc<tokio::sync::Mutex<Option<Topology>>>,
+    /// 运行中属性修改来源：(device_id, key) -> 最近一次修改记录
+    property_changes: Arc<StdMutex<HashMap<(String, String), PropertyChangeRecord>>>,
+    /// 运行参数（时间倍率、落库粒度、求解参数），启动前由预设设置
+    run_options: Arc<StdMutex<RunOptions>>,
+    /// 电表通信中断模拟（仅影响落库与 Modbus 对外数据）
+    meter_dropout: Arc<StdMutex<MeterDropoutEmulator>>,
+    /// 电表 PT/CT 变比与准确度等级误差（仅影响电表上报值，被测设备保持一次侧真实值）
+    meter_accuracy: Arc<StdMutex<MeterAccuracyModel>>,
+    /// 储能日前充放电计划及其执行偏差
+    storage_schedules: Arc<StdMutex<StorageScheduleExecutor>>,
+    /// EMS 调度策略（储能有功设定），每步计算前按上一步结果运行
+    ems: Arc<StdMutex<EmsController>>,
+    /// 历史数据模式设备的 CSV 功率曲线（Rust 端插值回放）
+    profile_player: Arc<StdMutex<ProfilePlayer>>,
+    /// 绑定的气象数据源（跨轮次保留，启动时记录到仿真库）
+    weather_series: Arc<StdMutex<Option<WeatherSeries>>>,
+    /// 项目级功率符号约定：落库与 Modbus 编码按此转换，内部缓存与计算保持内核原生约定
+    sign_convention: Arc<StdMutex<SignConvention>>,
+    /// 内核保温：停止后保留已构建的网络，下次启动无需重建
+    keep_kernel_warm: Arc<AtomicBool>,
+    /// 本实例选用的潮流求解内核，随设置拓扑下发，下次启动生效
+    power_kernel: Arc<StdMutex<KernelType>>,
+    /// 功率设备日/累计输入输出电量（停止后保留，下次启动清零）
+    device_energy: Arc<StdMutex<HashMap<String, DeviceEnergyCounters>>>,
+    /// random_data 模式下由 Rust 端生成的设备功率曲线（OU 负荷、晴空光伏、充电块）
+    random_generators: Arc<StdMutex<RandomGeneratorBank>>,
+    /// 设备功率预测与预测精度统计
+    forecast_accuracy: Arc<StdMutex<ForecastAccuracyTracker>>,
+    /// 设备传感器上报延迟与执行器响应延迟
+    delay_simulator: Arc<StdMutex<DelaySimulator>>,
+    /// 设定值按设备额定功率限幅及限幅统计
+    setpoint_limiter: Arc<StdMutex<SetpointLimiter>>,
+    /// 有载调压变压器分接头控制与动作记录
+    oltc: Arc<StdMutex<OltcController>>,
+    /// 线路/变压器过流保护与动作记录
+    protection: Arc<StdMutex<ProtectionRelays>>,
+    /// 光伏 volt-var / volt-watt 电网支撑控制层
+    grid_support: Arc<StdMutex<GridSupportController>>,
+    /// 孤岛运行系统频率模型（聚合惯量 + 储能下垂）
+    frequency_model: Arc<StdMutex<FrequencyModel>>,
+    /// 充电桩电动汽车充电会话（ev_session 模式）
+    ev_sessions: Arc<StdMutex<EvSessionBank>>,
+    /// 本次运行的步数、未收敛次数与电压极值统计，停止时生成运行摘要
+    run_stats: Arc<StdMutex<RunStatsCollector>>,
+    /// 本次运行的 AppHandle（仅主仿真），stop() 时推送运行摘要
+    run_app: StdMutex<Option<AppHandle>>,
+    /// 定时事件表：计算循环按仿真时间施加到期事件
+    event_scheduler: Arc<StdMutex<EventScheduler>>,
+    /// 注入的线路/变压器停运与短路故障
+    fault_injector: Arc<StdMutex<FaultInjector>>,
+    /// 计算循环外（命令注入/清除故障、手动标记）请求的突发落库，下一步由计算循环处理
+    burst_requests: Arc<StdMutex<Vec<String>>>,
+    /// 单步调试：暂停中请求执行一步，计算循环完成后回传步序号
+    single_step: Arc<StdMutex<Option<tokio::sync::oneshot::Sender<u64>>>>,
+    /// 唤醒计算循环立即处理单步请求（不等下一次定时）
+    step_wakeup: Arc<tokio::sync::Notify>,
+    /// 仿真时钟：落库时间戳、定时事件与储能计划均按仿真时间，暂停不前进、加速时按倍率前进
+    sim_clock: Arc<StdMutex<SimClock>>,
+    /// 多实例仿真中的实例 id（主仿真为 None）；仿真库文件名带此 id，避免并发实例写同一文件
+    instance_id: Option<String>,
+}
+
+/// 越限记录保留上限
+const MAX_GRID_LIMIT_VIOLATIONS: usize = 1000;
+/// 内核崩溃后连续重启失败次数上限，达到后自动停止仿真
+const MAX_KERNEL_RESTART_FAILURES: u32 = 3;
+
+/// 恢复中断仿真（或从检查点文件恢复）时沿用的数据库、检查点与内核状态
+struct ResumeFrom {
+    /// 继续追加的仿真库；None 表示新建（检查点文件对应的库已不存在）
+    db_path: Option<String>,
+    checkpoint: Option<RunCheckpoint>,
+    /// 检查点文件中的内核运行时状态，启动前经 simulation.import_state 导入
+    kernel_state: Option<serde_json::Value>,
 }
 
 impl SimulationEngine {
     pub fn new(
         python_bridge: Arc<Mutex<PythonBridge>>,
+        kernel: KernelClient,
         database: Arc<StdMutex<Option<Database>>>,
         current_db_path: Arc<StdMutex<String>>,
     ) -> Self {
@@ -49,8 +235,11 @@ impl SimulationEngine {
             status: Arc::new(tokio::sync::Mutex::new(SimulationStatus::new())),
             device_modes: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
             python_bridge,
+            kernel,
             topology: Arc::new(tokio::sync::Mutex::new(None)),
+            result_index: Arc::new(StdMutex::new(Arc::new(ResultIndex::default()))),
             database,
+            db_writer: Arc::new(StdMutex::new(None)),
             current_db_path,
             remote_control_enabled: Arc::new(AtomicBool::new(true)),
             device_remote_control_allowed: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
@@ -60,9 +249,63 @@ impl SimulationEngine {
             calculation_loop_started: Arc::new(AtomicBool::new(false)),
             cancel_tx: Arc::new(tokio::sync::Mutex::new(None)),
             device_sim_params: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+            limit_monitor: Arc::new(StdMutex::new(LimitMonitor::new())),
+            breakpoints: Arc::new(StdMutex::new(BreakpointSet::new())),
+            grid_limit_violations: Arc::new(StdMutex::new(Vec::new())),
+            baseline_topology: Arc::new(tokio::sync::Mutex::new(None)),
+            property_changes: Arc::new(StdMutex::new(HashMap::new())),
+            run_options: Arc::new(StdMutex::new(RunOptions::default())),
+            meter_dropout: Arc::new(StdMutex::new(MeterDropoutEmulator::new())),
+            meter_accuracy: Arc::new(StdMutex::new(MeterAccuracyModel::new())),
+            storage_schedules: Arc::new(StdMutex::new(StorageScheduleExecutor::new())),
+            ems: Arc::new(StdMutex::new(EmsController::new())),
+            profile_player: Arc::new(StdMutex::new(ProfilePlayer::new())),
+            weather_series: Arc::new(StdMutex::new(None)),
+            sign_convention: Arc::new(StdMutex::new(SignConvention::default())),
+            keep_kernel_warm: Arc::new(AtomicBool::new(true)),
+            power_kernel: Arc::new(StdMutex::new(KernelType::default())),
+            device_energy: Arc::new(StdMutex::new(HashMap::new())),
+            random_generators: Arc::new(StdMutex::new(RandomGeneratorBank::new())),
+            forecast_accuracy: Arc::new(StdMutex::new(ForecastAccuracyTracker::new())),
+            delay_simulator: Arc::new(StdMutex::new(DelaySimulator::new())),
+            setpoint_limiter: Arc::new(StdMutex::new(SetpointLimiter::new())),
+            oltc: Arc::new(StdMutex::new(OltcController::new())),
+            protection: Arc::new(StdMutex::new(ProtectionRelays::new())),
+            grid_support: Arc::new(StdMutex::new(GridSupportController::new())),
+            frequency_model: Arc::new(StdMutex::new(FrequencyModel::new())),
+            ev_sessions: Arc::new(StdMutex::new(EvSessionBank::new())),
+            run_stats: Arc::new(StdMutex::new(RunStatsCollector::new())),
+            run_app: StdMutex::new(None),
+            event_scheduler: Arc::new(StdMutex::new(EventScheduler::new())),
+            fault_injector: Arc::new(StdMutex::new(FaultInjector::new())),
+            burst_requests: Arc::new(StdMutex::new(Vec::new())),
+            single_step: Arc::new(StdMutex::new(None)),
+            step_wakeup: Arc::new(tokio::sync::Notify::new()),
+            sim_clock: Arc::new(StdMutex::new(SimClock::default())),
+            instance_id: None,
         }
     }
 
+    /// 作为独立仿真实例创建（见 SimulationManager）
+    pub fn with_instance_id(mut self, instance_id: &str) -> Self {
+        self.instance_id = Some(instance_id.to_string());
+        self
+    }
+
+    pub fn instance_id(&self) -> Option<&str> {
+        self.instance_id.as_deref()
+    }
+
+    /// 前端事件与 Modbus 只对接主仿真：独立实例的计算循环仍需 AppHandle，但不经其推送事件或读写共享服务
+    fn shared_app<'a>(&self, app_handle: Option<&'a AppHandle>) -> Option<&'a AppHandle> {
+        app_handle.filter(|_| self.instance_id.is_none())
+    }
+
+    /// 当前仿真库文件路径（尚未启动过时为空）
+    pub fn current_db_path(&self) -> String {
+        self.current_db_path.lock().map(|p| p.clone()).unwrap_or_default()
+    }
+
     pub fn set_remote_control_enabled(&self, enabled: bool) {
         self.remote_control_enabled.store(enabled, Ordering::Relaxed);
     }
@@ -87,9 +330,143 @@ impl SimulationEngine {
     }
 
     pub async fn start(&self, app_handle: Option<AppHandle>, calculation_interval_ms: u64) -> Result<(), String> {
+        self.start_run(app_handle, calculation_interval_ms, None).await
+    }
+
+    /// 恢复中断的仿真：按库中运行清单重建拓扑与运行参数，恢复储能状态与电量寄存器，继续向同一数据库追加数据。返回恢复的拓扑
+    pub async fn resume_interrupted_run(&self, app_handle: Option<AppHandle>, db_path: &str) -> Result<Topology, String> {
+        if self.status.lock().await.state != SimulationState::Stopped {
+            return Err("仿真运行中，无法恢复中断的仿真".to_string());
+        }
+        let (manifest, checkpoint) = {
+            let db = Database::new(Some(std::path::Path::new(db_path)))
+                .map_err(|e| format!("打开仿真数据库失败: {}", e))?;
+            self.check_db_sign_convention(&db)?;
+            run_recovery::load_resume_state(&db)?
+        };
+        self.set_topology(manifest.topology.clone()).await;
+        self.set_run_options(manifest.run_options.clone());
+        self.set_remote_control_enabled(manifest.remote_control_enabled);
+        let resume = ResumeFrom {
+            db_path: Some(db_path.to_string()),
+            checkpoint,
+            kernel_state: None,
+        };
+        self.start_run(app_handle, manifest.calculation_interval_ms, Some(resume)).await?;
+        Ok(manifest.topology)
+    }
+
+    /// 同一仿真库内符号约定须一致
+    fn check_db_sign_convention(&self, db: &Database) -> Result<(), String> {
+        let stored_convention = db
+            .get_meta_text("sign_convention")
+            .ok()
+            .flatten()
+            .and_then(|s| serde_json::from_str::<SignConvention>(&s).ok());
+        if stored_convention.is_some_and(|c| c != *self.sign_convention.lock().unwrap()) {
+            return Err("该仿真库的功率符号约定与当前设置不一致，请先切换约定后再恢复".to_string());
+        }
+        Ok(())
+    }
+
+    /// 将运行中（或暂停中）的仿真保存为检查点文件：当前拓扑与运行参数、设备工作模式、储能状态与电量计数、
+    /// Modbus 电量寄存器及 Python 内核运行时状态。停止后这些状态会被清空，须在停止前保存
+    pub async fn save_checkpoint(&self, app_handle: Option<&AppHandle>, path: &str) -> Result<CheckpointFileInfo, String> {
+        if self.status.lock().await.state == SimulationState::Stopped {
+            return Err("仿真未运行，没有可保存的状态".to_string());
+        }
+        let db_path = self.current_db_path.lock().map(|p| p.clone()).unwrap_or_default();
+        let mut manifest = match *self.database.lock().unwrap() {
+            Some(ref db) => run_recovery::load_resume_state(db)?.0,
+            None => return Err("仿真数据库未打开".to_string()),
+        };
+        if let Some(topology) = self.topology.lock().await.clone() {
+            manifest.topology = topology;
+        }
+        manifest.run_options = self.run_options.lock().unwrap().clone();
+        manifest.remote_control_enabled = self.remote_control_enabled();
+        let energy_registers = match self.shared_app(app_handle).and_then(|app| app.try_state::<crate::services::modbus::ModbusService>()) {
+            Some(modbus) => modbus.energy_register_snapshot().await,
+            None => HashMap::new(),
+        };
+        let kernel_state = {
+            let bridge = &self.kernel;
+            let result = bridge
+                .call("simulation.export_state", serde_json::json!({}))
+                .await
+                .map_err(|e| format!("导出内核状态失败: {}", e))?;
+            if result.get("status").and_then(|v| v.as_str()) == Some("error") {
+                let msg = result.get("message").and_then(|v| v.as_str()).unwrap_or("未知错误");
+                return Err(format!("导出内核状态失败: {}", msg));
+            }
+            result.get("state").cloned().unwrap_or(serde_json::Value::Null)
+        };
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs_f64();
+        let sim_clock = *self.sim_clock.lock().unwrap();
+        let file = SimulationCheckpointFile {
+            version: run_recovery::CHECKPOINT_FILE_VERSION,
+            saved_at: now,
+            db_path,
+            manifest,
+            checkpoint: RunCheckpoint {
+                timestamp: sim_clock.now(),
+                storage_state: self.storage_state.lock().unwrap().clone(),
+                device_energy: self.device_energy.lock().unwrap().clone(),
+                energy_registers,
+                sim_clock: Some(sim_clock),
+            },
+            device_modes: self.device_modes.lock().await.clone(),
+            kernel_state,
+        };
+        run_recovery::write_checkpoint_file(std::path::Path::new(path), &file)?;
+        Ok(file.info(path))
+    }
+
+    /// 从检查点文件恢复仿真：重建拓扑、运行参数与设备工作模式，恢复储能 SOC/电量计数与电量寄存器，导入内核状态后启动；
+    /// 原仿真库仍存在时继续追加数据，否则新建。返回恢复的拓扑
+    pub async fn restore_checkpoint(&self, app_handle: Option<AppHandle>, path: &str) -> Result<Topology, String> {
+        if self.status.lock().await.state != SimulationState::Stopped {
+            return Err("仿真运行中，请先停止后再恢复检查点".to_string());
+        }
+        let file = run_recovery::read_checkpoint_file(std::path::Path::new(path))?;
+        let db_path = std::path::Path::new(&file.db_path)
+            .is_file()
+            .then(|| file.db_path.clone());
+        if let Some(ref p) = db_path {
+            let db = Database::new(Some(std::path::Path::new(p))).map_err(|e| format!("打开仿真数据库失败: {}", e))?;
+            self.check_db_sign_convention(&db)?;
+        }
+        let manifest = file.manifest;
+        self.set_topology(manifest.topology.clone()).await;
+        self.set_run_options(manifest.run_options.clone());
+        self.set_remote_control_enabled(manifest.remote_control_enabled);
+        *self.device_modes.lock().await = file.device_modes;
+        let resume = ResumeFrom {
+            db_path,
+            checkpoint: Some(file.checkpoint),
+            kernel_state: (!file.kernel_state.is_null()).then_some(file.kernel_state),
+        };
+        self.start_run(app_handle, manifest.calculation_interval_ms, Some(resume)).await?;
+        Ok(manifest.topology)
+    }
+
+    async fn start_run(
+        &self,
+        app_handle: Option<AppHandle>,
+        calculation_interval_ms: u64,
+        resume: Option<ResumeFrom>,
+    ) -> Result<(), String> {
+        // 回放与主仿真共用前端事件与 Modbus 服务器，不可同时进行
+        let replaying = self
+            .shared_app(app_handle.as_ref())
+            .and_then(|app| app.try_state::<ReplayService>())
+            .is_some_and(|replay| replay.is_running());
+        if replaying {
+            return Err("回放进行中，请先停止回放".to_string());
+        }
         // 检查 Python bridge 是否已就绪（应该在应用启动时已启动）
         {
-            let mut bridge = self.python_bridge.lock().await;
+            let bridge = &self.kernel;
             // 尝试 ping 确认 bridge 已就绪
             // 如果失败，说明启动时有问题，返回错误
             let mut retries = 3;
@@ -112,12 +489,8 @@ impl SimulationEngine {
             
             if !bridge_ready {
                 // 获取日志文件路径提示用户
-                let log_path = std::env::current_exe()
-                    .ok()
-                    .and_then(|p| p.parent().map(|p| p.join("python-kernel.log")))
-                    .map(|p| p.display().to_string())
-                    .unwrap_or_else(|| "python-kernel.log".to_string());
-                return Err(format!("Python 内核未就绪。请检查日志文件: {}", log_path));
+                let log_path = crate::services::kernel_log::log_path();
+                return Err(format!("Python 内核未就绪。请检查日志文件: {}", log_path.display()));
             }
         }
         
@@ -128,12 +501,46 @@ impl SimulationEngine {
         }
         
         // 将拓扑数据转换为标准格式并传递给Python内核
-        let topology_data = self.convert_topology_to_standard_format(&topology.unwrap()).await?;
+        let topology = topology.unwrap();
+        let topology_data = self.convert_topology_to_standard_format(&topology).await?;
+        *self.baseline_topology.lock().await = Some(topology.clone());
+        self.property_changes.lock().unwrap().clear();
         
         // 新一轮仿真开始，清空设备在线状态、功率缓存与储能状态，等首拍成功后再标记为在线
         self.device_active_status.lock().await.clear();
         self.last_device_power.lock().unwrap().clear();
         self.storage_state.lock().unwrap().clear();
+        self.limit_monitor.lock().unwrap().reset();
+        self.breakpoints.lock().unwrap().reset();
+        self.grid_limit_violations.lock().unwrap().clear();
+        self.meter_dropout.lock().unwrap().reset();
+        self.meter_accuracy.lock().unwrap().reset();
+        self.storage_schedules.lock().unwrap().reset();
+        self.ems.lock().unwrap().reset();
+        self.device_energy.lock().unwrap().clear();
+        self.random_generators.lock().unwrap().clear();
+        self.forecast_accuracy.lock().unwrap().reset();
+        self.delay_simulator.lock().unwrap().reset();
+        self.setpoint_limiter.lock().unwrap().reset();
+        self.oltc.lock().unwrap().reset();
+        self.protection.lock().unwrap().reset();
+        self.grid_support.lock().unwrap().reset();
+        self.frequency_model.lock().unwrap().reset();
+        self.ev_sessions.lock().unwrap().reset();
+        self.run_stats.lock().unwrap().reset();
+        *self.run_app.lock().unwrap() = self.shared_app(app_handle.as_ref()).cloned();
+        self.event_scheduler.lock().unwrap().rewind();
+        self.fault_injector.lock().unwrap().reset();
+        self.burst_requests.lock().unwrap().clear();
+        self.single_step.lock().unwrap().take();
+        if let Some(hub) = self.shared_app(app_handle.as_ref()).and_then(|app| app.try_state::<Arc<WindowEventHub>>()) {
+            hub.reset_steps();
+        }
+        // 恢复中断的仿真：储能 SOC、充放电量与设备电量从最近检查点延续
+        if let Some(checkpoint) = resume.as_ref().and_then(|r| r.checkpoint.as_ref()) {
+            *self.storage_state.lock().unwrap() = checkpoint.storage_state.clone();
+            *self.device_energy.lock().unwrap() = checkpoint.device_energy.clone();
+        }
         
         // 清除之前的错误列表（新仿真开始，避免旧错误继续显示）
         {
@@ -141,11 +548,12 @@ impl SimulationEngine {
             status.errors.clear();
         }
         
-        let mut bridge = self.python_bridge.lock().await;
+        let bridge = &self.kernel;
         
         // 设置拓扑数据
         let set_topology_params = serde_json::json!({
-            "topology_data": topology_data
+            "topology_data": topology_data,
+            "kernel_type": self.power_kernel().as_str(),
         });
         let set_topology_result = bridge.call("simulation.set_topology", set_topology_params).await
             .map_err(|e| format!("Failed to set topology: {}", e))?;
@@ -159,16 +567,38 @@ impl SimulationEngine {
             }
         }
         
-        // 启动仿真：每次使用新数据库文件 data_<unix_ts>.db，便于按仿真轮次保留历史
+        // 检查点文件中的内核状态（设备模式设定、历史回放进度、仿真累计时间等）在启动前导入；
+        // 须在置为运行状态、安装仿真库之前完成，导入失败时直接返回不留下半启动的状态
+        if let Some(state) = resume.as_ref().and_then(|r| r.kernel_state.as_ref()) {
+            let result = bridge
+                .call("simulation.import_state", serde_json::json!({ "state": state }))
+                .await
+                .map_err(|e| format!("导入内核状态失败: {}", e))?;
+            if result.get("status").and_then(|v| v.as_str()) == Some("error") {
+                let msg = result.get("message").and_then(|v| v.as_str()).unwrap_or("未知错误");
+                return Err(format!("导入内核状态失败: {}", msg));
+            }
+        }
+
+        // 启动仿真：每次使用新数据库文件 data_<unix_ts>.db（独立实例为 data_<实例 id>_<unix_ts>.db），便于按仿真轮次保留历史
         let mut status = self.status.lock().await;
         status.start();
         let start_ts = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs_f64();
         let start_ts_secs = start_ts as u64;
         drop(status);
 
-        let mut dir = std::env::current_dir().map_err(|e| format!("获取工作目录失败: {}", e))?;
-        let new_name = format!("data_{}.db", start_ts_secs);
-        dir.push(&new_name);
+        // 恢复时继续追加到原数据库，否则新建
+        let dir = match resume.as_ref().and_then(|r| r.db_path.as_ref()) {
+            Some(p) => std::path::PathBuf::from(p),
+            None => {
+                let mut dir = std::env::current_dir().map_err(|e| format!("获取工作目录失败: {}", e))?;
+                match &self.instance_id {
+                    Some(id) => dir.push(format!("data_{}_{}.db", id, start_ts_secs)),
+                    None => dir.push(format!("data_{}.db", start_ts_secs)),
+                }
+                dir
+            }
+        };
         let new_db = Database::new(Some(dir.as_path())).map_err(|e| format!("创建仿真数据库失败: {}", e))?;
         {
             let mut db_guard = self.database.lock().map_err(|_| "数据库锁异常")?;
@@ -179,16 +609,86 @@ impl SimulationEngine {
         }
         if let Ok(guard) = self.database.lock() {
             if let Some(ref db) = *guard {
-                let _ = db.set_latest_simulation_start(start_ts);
+                // 仿真时钟：新库从设定起点（默认启动时刻）开始；恢复时沿用检查点中的时钟，仿真经过时间不归零，
+                // 续写原库时推进到库中最后时间戳（检查点之后仍有落库数据）
+                let checkpoint_clock = resume.as_ref().and_then(|r| r.checkpoint.as_ref()).and_then(|c| c.sim_clock);
+                let clock = match (resume.as_ref().and_then(|r| r.db_path.as_ref()), checkpoint_clock) {
+                    (Some(_), Some(clock)) => {
+                        let latest = db.query_latest_timestamp().ok().flatten().unwrap_or(clock.now());
+                        SimClock { start_epoch: clock.start_epoch, elapsed_s: (latest - clock.start_epoch).max(clock.elapsed_s) }
+                    }
+                    (Some(_), None) => SimClock::new(db.query_latest_timestamp().ok().flatten().unwrap_or(start_ts)),
+                    (None, Some(clock)) => clock,
+                    (None, None) => SimClock::new(self.run_options.lock().unwrap().sim_start_epoch.unwrap_or(start_ts)),
+                };
+                let sim_start = clock.now();
+                *self.sim_clock.lock().unwrap() = clock;
+                // 中断前已到期的定时事件不再重复施加
+                self.event_scheduler.lock().unwrap().skip_until(clock.elapsed_s);
+                if resume.as_ref().is_none_or(|r| r.db_path.is_none()) {
+                    let _ = db.set_latest_simulation_start(sim_start);
+                    // 记录本库采用的符号约定，供分析与迁移识别
+                    if let Ok(convention) = serde_json::to_string(&*self.sign_convention.lock().unwrap()) {
+                        let _ = db.set_meta_text("sign_convention", &convention);
+                    }
+                    // 记录各电表测量对象的类型（按拓扑连接），迁移与分析据此判断电表功率方向，不依赖数据内容匹配
+                    let mut meter_targets: HashMap<String, String> = HashMap::new();
+                    for (target_id, meters) in Self::build_target_to_meters(&topology) {
+                        if let Some(target) = topology.devices.get(&target_id) {
+                            for meter_id in meters {
+                                meter_targets.entry(meter_id).or_insert_with(|| target.device_type.as_str().to_string());
+                            }
+                        }
+                    }
+                    if let Ok(json) = serde_json::to_string(&meter_targets) {
+                        let _ = db.set_meta_text("meter_target_types", &json);
+                    }
+                    // 运行清单：崩溃后据此恢复
+                    let manifest = RunManifest {
+                        topology: topology.clone(),
+                        calculation_interval_ms,
+                        run_options: self.run_options.lock().unwrap().clone(),
+                        remote_control_enabled: self.remote_control_enabled(),
+                    };
+                    run_recovery::write_manifest(db, &manifest);
+                    if let Some(series) = self.weather_series.lock().unwrap().as_ref() {
+                        weather::record_binding(db, &WeatherBinding::of(series));
+                    }
+                }
+                run_recovery::set_run_status(db, RunStatus::Running);
             }
         }
-
+        // 电表/光伏电量寄存器从检查点延续（未启动的 Modbus 服务在启动时写入）
+        if let (Some(app), Some(checkpoint)) = (self.shared_app(app_handle.as_ref()), resume.as_ref().and_then(|r| r.checkpoint.as_ref())) {
+            if let Some(modbus) = app.try_state::<crate::services::modbus::ModbusService>() {
+                modbus.restore_energy_registers(checkpoint.energy_registers.clone()).await;
+            }
+        }
+        // 内核仿真时钟（历史回放、响应延迟、P(f) 延时）按时间倍率推进，与 Rust 端电量积分步长一致；
+        // 潮流求解参数随启动下发（空参数即恢复内核默认）
+        let (solver_options, time_scale) = {
+            let options = self.run_options.lock().unwrap();
+            (options.solver_options.clone(), options.time_scale)
+        };
         let start_params = serde_json::json!({
-            "calculation_interval_ms": calculation_interval_ms
+            "calculation_interval_ms": calculation_interval_ms,
+            "time_scale": time_scale,
+            "solver_options": solver_options,
         });
         bridge.call("simulation.start", start_params).await
             .map_err(|e| format!("Failed to start simulation: {}", e))?;
-        drop(bridge);
+        // 主仿真：重新下发持久化的设备控制状态（内核在设置拓扑时清空设备模式）；从检查点导入内核状态时不覆盖
+        if resume.as_ref().is_none_or(|r| r.kernel_state.is_none()) {
+            let controls = self
+                .shared_app(app_handle.as_ref())
+                .and_then(|app| app.try_state::<SettingsStore>())
+                .map(|settings| settings.device_controls())
+                .unwrap_or_default();
+            if !controls.is_empty() {
+                let restored = self.apply_stored_controls(bridge, &controls).await;
+                eprintln!("已恢复 {} 个设备的控制状态", restored);
+            }
+        }
         
         // 只 spawn 一次计算循环，避免「暂停后再点启动」产生多个循环导致计算次数暴增（如 1000ms 间隔却 3s 内 18 次）
         let should_spawn = !self.calculation_loop_started.swap(true, Ordering::SeqCst);
@@ -201,6 +701,181 @@ impl SimulationEngine {
         Ok(())
     }
     
+    /// 运行中内核进程崩溃后重启：拉起新进程，重新下发当前拓扑、求解参数、设备工作模式、持久化的控制状态与分接头档位并启动内核仿真；
+    /// 由计算循环在独占内核桥接时调用（其他请求此间失败），返回恢复的设备模式数。历史数据源配置与故障不恢复
+    async fn restart_kernel(&self, bridge: &mut PythonBridge, app: &AppHandle, calculation_interval_ms: u64) -> Result<usize, String> {
+        Self::relaunch_kernel(bridge, app).await?;
+        let topology = self.topology.lock().await.clone().ok_or("拓扑数据未设置")?;
+        self.push_topology(bridge, &topology).await?;
+        let (solver_options, time_scale) = {
+            let options = self.run_options.lock().unwrap();
+            (options.solver_options.clone(), options.time_scale)
+        };
+        bridge
+            .call(
+                "simulation.start",
+                serde_json::json!({
+                    "calculation_interval_ms": calculation_interval_ms,
+                    "time_scale": time_scale,
+                    "solver_options": solver_options,
+                }),
+            )
+            .await
+            .map_err(|e| format!("启动内核仿真失败: {}", e))?;
+        // 内核在设置拓扑时清空设备模式，须在启动后逐个恢复
+        let restored = self.push_device_modes(bridge).await?;
+        let controls = self
+            .shared_app(Some(app))
+            .and_then(|app| app.try_state::<SettingsStore>())
+            .map(|settings| settings.device_controls())
+            .unwrap_or_default();
+        self.apply_stored_controls(&self.kernel, &controls).await;
+        // 电网支撑设定随内核进程丢失，下一步重新下发
+        self.grid_support.lock().unwrap().invalidate();
+        self.frequency_model.lock().unwrap().invalidate();
+        // 内核重建网络后分接头回到拓扑初始档位，按控制器当前档位重新下发
+        let taps = self.oltc.lock().unwrap().tap_positions();
+        for (device_id, tap_pos) in taps {
+            if let Err(e) = bridge
+                .call("simulation.set_transformer_tap", serde_json::json!({ "device_id": device_id, "tap_pos": tap_pos }))
+                .await
+            {
+                eprintln!("恢复分接头档位失败 {}: {}", device_id, e);
+            }
+        }
+        Ok(restored)
+    }
+
+    /// 热重载内核（开发时修改 Python 内核代码后使用，无需重启应用）：重启内核进程，重新下发拓扑与设备工作模式。
+    /// 仿真运行或暂停中按崩溃重启的流程恢复并启动内核仿真（暂停中随后重新暂停）；返回恢复的设备模式数
+    pub async fn reload_kernel(&self, app: &AppHandle) -> Result<usize, String> {
+        let status = self.get_status().await;
+        // 独占桥接期间计算循环的存活检测等待重载完成，不会并发重启
+        let mut bridge = self.python_bridge.lock().await;
+        if status.state != SimulationState::Stopped {
+            let restored = self.restart_kernel(&mut bridge, app, status.current_interval_ms).await?;
+            if status.state == SimulationState::Paused {
+                bridge
+                    .call("simulation.pause", serde_json::json!({ "action": "pause" }))
+                    .await
+                    .map_err(|e| format!("重新暂停内核仿真失败: {}", e))?;
+            }
+            return Ok(restored);
+        }
+        Self::relaunch_kernel(&mut bridge, app).await?;
+        let Some(topology) = self.topology.lock().await.clone() else {
+            return Ok(0);
+        };
+        self.push_topology(&bridge, &topology).await?;
+        self.push_device_modes(&bridge).await
+    }
+
+    /// 停止并重新拉起内核进程，等待其应答 ping
+    async fn relaunch_kernel(bridge: &mut PythonBridge, app: &AppHandle) -> Result<(), String> {
+        let _ = bridge.stop().await;
+        bridge.start(Some(app)).await.map_err(|e| format!("启动内核进程失败: {}", e))?;
+        let mut retries = 10;
+        while let Err(e) = bridge.call("ping", serde_json::json!({})).await {
+            retries -= 1;
+            if retries == 0 {
+                return Err(format!("内核进程未就绪: {}", e));
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+        }
+        Ok(())
+    }
+
+    /// 向（重新拉起的）内核下发拓扑与所选潮流内核
+    async fn push_topology(&self, bridge: &PythonBridge, topology: &Topology) -> Result<(), String> {
+        let topology_data = self.convert_topology_to_standard_format(topology).await?;
+        let result = bridge
+            .call(
+                "simulation.set_topology",
+                serde_json::json!({ "topology_data": topology_data, "kernel_type": self.power_kernel().as_str() }),
+            )
+            .await
+            .map_err(|e| format!("设置拓扑失败: {}", e))?;
+        if result.get("status").and_then(|v| v.as_str()) == Some("error") {
+            let msg = result.get("message").and_then(|v| v.as_str()).unwrap_or("设置拓扑失败");
+            return Err(format!("拓扑设置失败: {}", msg));
+        }
+        Ok(())
+    }
+
+    /// 重新下发 Rust 端记录的设备工作模式，返回下发数量
+    async fn push_device_modes(&self, bridge: &PythonBridge) -> Result<usize, String> {
+        let modes = self.device_modes.lock().await.clone();
+        for (device_id, mode) in &modes {
+            bridge
+                .call("simulation.set_device_mode", serde_json::json!({ "device_id": device_id, "mode": mode.as_str() }))
+                .await
+                .map_err(|e| format!("恢复设备 {} 工作模式失败: {}", device_id, e))?;
+        }
+        Ok(modes.len())
+    }
+
+    /// 经内核客户端重新下发持久化的设备控制状态（工作模式、随机配置、手动设定），
+    /// 同步更新 Rust 端模式与随机生成器；拓扑中不存在的设备跳过，返回全部下发成功的设备数
+    async fn apply_stored_controls(&self, bridge: &KernelClient, controls: &HashMap<String, StoredDeviceControl>) -> usize {
+        let Some(topology) = self.topology.lock().await.clone() else {
+            return 0;
+        };
+        let mut restored = 0;
+        for (device_id, control) in controls {
+            let Some(device) = topology.devices.get(device_id) else {
+                continue;
+            };
+            let mut calls: Vec<(&str, serde_json::Value)> = Vec::new();
+            if let Some(mode) = &control.mode {
+                self.device_modes.lock().await.insert(device_id.clone(), mode.clone().into());
+                calls.push(("simulation.set_device_mode", serde_json::json!({ "device_id": device_id, "mode": mode })));
+            }
+            if let Some(random) = &control.random {
+                let profile = random.profile.clone().unwrap_or_else(|| RandomProfile::default_for(&device.device_type));
+                let engine_generated = profile.is_engine_generated();
+                let seed = {
+                    let mut generators = self.random_generators.lock().unwrap();
+                    let seed = random_generator::device_seed(generators.global_seed(), device_id, random.seed);
+                    generators.configure(device_id, profile, random.min_power, random.max_power, seed);
+                    seed
+                };
+                calls.push((
+                    "simulation.set_device_random_config",
+                    serde_json::json!({
+                        "device_id": device_id,
+                        "min_power": random.min_power,
+                        "max_power": random.max_power,
+                        "external": engine_generated,
+                        "seed": seed
+                    }),
+                ));
+            }
+            if let Some(setpoint) = control.manual_setpoint {
+                let now = self.sim_time();
+                let active_power = self.setpoint_limiter.lock().unwrap().clamp(device, "manual", "active_power", setpoint.active_power, now);
+                calls.push((
+                    "simulation.set_device_manual_setpoint",
+                    serde_json::json!({
+                        "device_id": device_id,
+                        "active_power": active_power,
+                        "reactive_power": setpoint.reactive_power
+                    }),
+                ));
+            }
+            let mut ok = true;
+            for (method, params) in calls {
+                if let Err(e) = bridge.call(method, params).await {
+                    eprintln!("恢复设备 {} 控制状态失败（{}）: {}", device_id, method, e);
+                    ok = false;
+                }
+            }
+            if ok {
+                restored += 1;
+            }
+        }
+        restored
+    }
+
     async fn convert_topology_to_standard_format(&self, topology: &Topology) -> Result<serde_json::Value, String> {
         // 转换设备
         let mut devices: serde_json::Map<String, serde_json::Value> = serde_json::Map::new();
@@ -278,6 +953,7 @@ impl SimulationEngine {
         }
         let status = self.status.clone();
         let python_bridge = self.python_bridge.clone();
+        let kernel = self.kernel.clone();
         let topology = self.topology.clone();
         let database = self.database.clone();
         let device_active_status = self.device_active_status.clone();
@@ -285,19 +961,83 @@ impl SimulationEngine {
         let storage_state = self.storage_state.clone();
         let calculation_loop_started = self.calculation_loop_started.clone();
         let device_sim_params = self.device_sim_params.clone();
+        let limit_monitor = self.limit_monitor.clone();
+        let breakpoints = self.breakpoints.clone();
+        let result_index = self.result_index.clone();
+        let grid_limit_violations = self.grid_limit_violations.clone();
+        let run_options = self.run_options.lock().unwrap().clone();
+        let meter_dropout = self.meter_dropout.clone();
+        let meter_accuracy = self.meter_accuracy.clone();
+        let storage_schedules = self.storage_schedules.clone();
+        let ems = self.ems.clone();
+        let profile_player = self.profile_player.clone();
+        let weather_series = self.weather_series.clone();
+        let sign_convention = self.sign_convention.lock().unwrap().clone();
+        let current_db_path = self.current_db_path.clone();
+        let device_energy = self.device_energy.clone();
+        let random_generators = self.random_generators.clone();
+        let forecast_accuracy = self.forecast_accuracy.clone();
+        let delay_simulator = self.delay_simulator.clone();
+        let setpoint_limiter = self.setpoint_limiter.clone();
+        let oltc = self.oltc.clone();
+        let protection = self.protection.clone();
+        let grid_support = self.grid_support.clone();
+        let frequency_model = self.frequency_model.clone();
+        let ev_sessions = self.ev_sessions.clone();
+        let run_stats = self.run_stats.clone();
+        let device_modes = self.device_modes.clone();
+        let event_scheduler = self.event_scheduler.clone();
+        let fault_injector = self.fault_injector.clone();
+        let burst_requests = self.burst_requests.clone();
+        let single_step_request = self.single_step.clone();
+        let step_wakeup = self.step_wakeup.clone();
+        let sim_clock = self.sim_clock.clone();
+        let instance_id = self.instance_id.clone();
+        
+        // 设备数据行由异步落库任务成批写入，计算步不等待磁盘
+        let db_writer = DbWriter::spawn(database.clone());
+        *self.db_writer.lock().unwrap() = Some(db_writer.clone());
         
         tokio::spawn(async move {
+            // 落库、前端事件与 Modbus 同步各按自身间隔输出；未到落库步时结果仍更新缓存
+            let mut results_pipeline = ResultsPipeline::new(&run_options.consumer_rates, run_options.persist_every_n_steps, calculation_interval_ms, db_writer);
+            // 独立仿真实例只落库，不推送事件、不对接 Modbus 与 Webhook
+            if instance_id.is_some() {
+                results_pipeline = results_pipeline.headless();
+            }
             let mut interval = interval(Duration::from_millis(calculation_interval_ms));
+            // 步耗时超过间隔时跳过错过的节拍（不连续补发），由 missed_steps 统计
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            // 当前计算间隔（自适应时随步耗时调整）；每步仿真时间 = 当前间隔 × 时间倍率，累计在仿真时钟中
+            let mut step_interval_ms = calculation_interval_ms;
+            // 步间隔抖动：实际步间隔相对计算间隔的偏差（毫秒，近 100 步）
+            let mut last_tick: Option<std::time::Instant> = None;
+            let mut tick_jitters: Vec<f64> = Vec::new();
+            status.lock().await.current_interval_ms = step_interval_ms;
             let mut calculation_times: Vec<f64> = Vec::new();
             // 设备级 Modbus 采样间隔节流：device_id -> 上次更新的仿真步计数
             let mut last_modbus_update_step: HashMap<String, u64> = HashMap::new();
+            // 日电量翻转：当前仿真日
+            let mut current_day: Option<String> = None;
+            // 多速率仿真：按设备声明的更新周期决定本步是否下发设定与采样结果
+            let mut multi_rate = MultiRateScheduler::new();
             let mut step_count: u64 = 0;
+            // 中断恢复检查点：按仿真步数间隔写入储能状态与电量寄存器
+            let mut last_checkpoint_step: u64 = 0;
+            // 已通过 Webhook 通知的告警 (设备, 量, 等级)
+            let mut notified_alerts: std::collections::HashSet<(String, String, LimitLevel)> = std::collections::HashSet::new();
+            // 内核进程崩溃重启：本轮累计重启次数与连续失败次数
+            let mut kernel_restarts: u32 = 0;
+            let mut kernel_restart_failures: u32 = 0;
             
             loop {
                 tokio::select! {
                     _ = interval.tick() => {}
+                    _ = step_wakeup.notified() => {}
                     _ = rx.recv() => {
                         calculation_loop_started.store(false, Ordering::SeqCst);
+                        // 写入最后一个不完整落库间隔的平均值
+                        results_pipeline.finish(sim_clock.lock().unwrap().now());
                         break;
                     }
                 }
@@ -305,16 +1045,135 @@ impl SimulationEngine {
                 // 检查仿真是否运行中
                 let status_guard = status.lock().await;
                 let is_running = status_guard.state == crate::domain::simulation::SimulationState::Running;
+                let is_paused = status_guard.state == crate::domain::simulation::SimulationState::Paused;
                 drop(status_guard);
                 
-                if !is_running {
+                // 单步调试：暂停中有单步请求时执行一步，完成后保持暂停
+                let single_step = if is_paused { single_step_request.lock().unwrap().take() } else { None };
+                if !is_running && single_step.is_none() {
+                    last_tick = None;
                     continue;
                 }
                 
                 let start_time = std::time::Instant::now();
+                if let Some(previous) = last_tick.filter(|_| single_step.is_none()) {
+                    let period_ms = start_time.duration_since(previous).as_secs_f64() * 1000.0;
+                    tick_jitters.push((period_ms - step_interval_ms as f64).abs());
+                    if tick_jitters.len() > 100 {
+                        tick_jitters.remove(0);
+                    }
+                }
+                last_tick = single_step.is_none().then_some(start_time);
+                
+                // 定时事件（事件表与场景脚本）：按本步开始时的仿真时间（SimClock）施加到期事件，须在占用内核连接前执行
+                let sim_time_s = sim_clock.lock().unwrap().elapsed_s;
+                multi_rate.begin_step(topology.lock().await.as_ref(), sim_time_s);
+                fault_injector.lock().unwrap().set_sim_time(sim_time_s);
+                let due_events = event_scheduler.lock().unwrap().take_due(sim_time_s);
+                if !due_events.is_empty() {
+                    let engine = app
+                        .try_state::<SimulationManager>()
+                        .and_then(|m| m.get(instance_id.as_deref()).ok());
+                    for (index, event) in due_events {
+                        // 到期事件已出队，无法施加时也须记录失败，不能静默丢弃
+                        let applied = match engine.as_ref() {
+                            Some(engine) => engine.apply_scheduled_event(&event).await,
+                            None => Err("仿真实例不可用".to_string()),
+                        };
+                        if let Err(e) = &applied {
+                            eprintln!("定时事件施加失败（t={}s, {}）: {}", event.at_s, event.target_device(), e);
+                        }
+                        let record = AppliedEventRecord::new(index, event, sim_time_s, applied);
+                        let device = (!record.device_id.is_empty()).then(|| record.device_id.clone());
+                        results_pipeline.notify_typed(&app, device.as_deref(), ScheduledEventApplied::new(record.clone()));
+                        event_scheduler.lock().unwrap().record(record);
+                    }
+                    let now = sim_clock.lock().unwrap().now();
+                    Self::trigger_burst(&database, &mut results_pipeline, "scheduled_event", now);
+                }
+                
+                // 过流保护：按上一步电流/负载率计时，达到动作延时后断开关联开关，本步潮流生效；须在占用内核连接前执行
+                let trips = match topology.lock().await.as_ref() {
+                    Some(t) => protection.lock().unwrap().evaluate(t, sim_time_s, sim_clock.lock().unwrap().now()),
+                    None => Vec::new(),
+                };
+                if !trips.is_empty() {
+                    let engine = app
+                        .try_state::<SimulationManager>()
+                        .and_then(|m| m.get(instance_id.as_deref()).ok());
+                    for mut trip in trips {
+                        let opened = match engine {
+                            Some(ref engine) => engine.update_switch_state(trip.switch_id.clone(), false).await,
+                            None => Err("仿真实例不存在".to_string()),
+                        };
+                        trip.ok = opened.is_ok();
+                        trip.error = opened.err();
+                        eprintln!("保护动作：{} 越限，断开开关 {}", trip.device_id, trip.switch_id);
+                        protection.lock().unwrap().record(trip.clone());
+                        results_pipeline.notify_typed(&app, Some(&trip.device_id), ProtectionTripped::new(trip));
+                    }
+                    let now = sim_clock.lock().unwrap().now();
+                    Self::trigger_burst(&database, &mut results_pipeline, "protection", now);
+                }
                 
                 // 获取计算状态和结果
-                let mut bridge = python_bridge.lock().await;
+                // 内核进程意外退出：重启并恢复拓扑与设备模式后继续本步；连续失败达到上限时停止仿真
+                // （仅检测与重启时独占桥接，本步其余请求经共享客户端发出，不阻塞设备命令）
+                let kernel_alive = python_bridge.lock().await.is_alive();
+                if !kernel_alive {
+                    kernel_restarts += 1;
+                    eprintln!("检测到 Python 内核进程退出，正在重启（第 {} 次）...", kernel_restarts);
+                    let engine = app
+                        .try_state::<SimulationManager>()
+                        .and_then(|m| m.get(instance_id.as_deref()).ok());
+                    let restarted = match engine {
+                        Some(engine) => engine.restart_kernel(&mut *python_bridge.lock().await, &app, step_interval_ms).await,
+                        None => Err("仿真实例不存在".to_string()),
+                    };
+                    let restored_modes = *restarted.as_ref().unwrap_or(&0);
+                    results_pipeline.notify_typed(&app, None, PythonKernelRestarted {
+                        schema_version: EVENT_SCHEMA_VERSION,
+                        attempt: kernel_restarts,
+                        ok: restarted.is_ok(),
+                        restored_modes,
+                        timestamp: sim_clock.lock().unwrap().now(),
+                        error: restarted.as_ref().err().cloned(),
+                    });
+                    match restarted {
+                        Ok(_) => kernel_restart_failures = 0,
+                        Err(e) => {
+                            eprintln!("Python 内核重启失败: {}", e);
+                            kernel_restart_failures += 1;
+                            if kernel_restart_failures >= MAX_KERNEL_RESTART_FAILURES {
+                                status.lock().await.stop();
+                                device_active_status.lock().await.clear();
+                                last_device_power.lock().unwrap().clear();
+                                storage_state.lock().unwrap().clear();
+                                if let Some(ref db) = *database.lock().unwrap() {
+                                    run_recovery::set_run_status(db, RunStatus::Completed);
+                                }
+                                results_pipeline.finish(sim_clock.lock().unwrap().now());
+                                results_pipeline.flush().await;
+                                let summary = {
+                                    let topo = topology.lock().await;
+                                    let index = result_index.lock().unwrap().clone();
+                                    Self::finish_run_summary(&run_stats, "kernel_failure", topo.as_ref(), &index, &device_energy, &storage_schedules, &database)
+                                };
+                                if let Some(summary) = summary {
+                                    results_pipeline.notify_typed(&app, None, SimulationSummary::new(summary));
+                                }
+                                let reason = format!("Python 内核连续 {} 次重启失败: {}", kernel_restart_failures, e);
+                                eprintln!("{}，仿真已自动停止", reason);
+                                results_pipeline.notify_typed(&app, None, SimulationAutoStopped {
+                                    schema_version: EVENT_SCHEMA_VERSION,
+                                    reason,
+                                });
+                            }
+                            continue;
+                        }
+                    }
+                }
+                let bridge = &kernel;
                 
                 // 获取计算状态
                 if let Ok(status_result) = bridge.call("simulation.get_calculation_status", serde_json::json!({})).await {
@@ -331,31 +1190,7 @@ impl SimulationEngine {
                         // 将 Python 返回的错误数组转换为 Rust 结构
                         let new_errors: Vec<crate::domain::simulation::SimulationError> = errors_array
                             .iter()
-                            .filter_map(|e| {
-                                // 转换字段名和格式：Python 返回 "type"，Rust 期望 "error_type"
-                                let mut error_obj = e.clone();
-                                
-                                // 将 "type" 字段重命名为 "error_type"
-                                // 需要先检查是否是 Object 类型，然后转换为 Map 进行操作
-                                if let serde_json::Value::Object(ref mut map) = error_obj {
-                                    if let Some(type_value) = map.remove("type") {
-                                        map.insert("error_type".to_string(), type_value);
-                                    }
-                                    
-                                    // 转换时间戳：Python 返回 float（秒），需要转换为 u64
-                                    if let Some(serde_json::Value::Number(timestamp_num)) = map.get("timestamp") {
-                                        if let Some(timestamp_f64) = timestamp_num.as_f64() {
-                                            map.insert("timestamp".to_string(), serde_json::json!(timestamp_f64 as u64));
-                                        }
-                                    }
-                                }
-                                
-                                serde_json::from_value::<crate::domain::simulation::SimulationError>(error_obj)
-                                    .map_err(|err| {
-                                        eprintln!("解析错误对象失败: {} - 原始数据: {}", err, serde_json::to_string(e).unwrap_or_default());
-                                    })
-                                    .ok()
-                            })
+                            .filter_map(crate::domain::simulation::SimulationError::from_kernel_value)
                             .collect();
 
                         let status_guard = status.lock().await;
@@ -373,17 +1208,280 @@ impl SimulationEngine {
                             status_guard.errors = new_errors.clone();
                             drop(status_guard);
 
-                            let _ = app.emit("simulation-errors-update", serde_json::json!({
-                                "errors": new_errors
-                            }));
+                            results_pipeline.notify_typed(&app, None, SimulationErrorsUpdate::new(new_errors.clone()));
+                            if !new_errors.is_empty() {
+                                let now = sim_clock.lock().unwrap().now();
+                                Self::trigger_burst(&database, &mut results_pipeline, "error", now);
+                            }
+                        }
+                    }
+                }
+                
+                // 储能计划：进入新时段（或远程指令后需恢复计划）时下发设定，与 Modbus set_power 走同一路径（额定限幅、属性变更记录）；时段按仿真时间判断
+                let now_ts = sim_clock.lock().unwrap().now();
+                let due_setpoints = storage_schedules.lock().unwrap().due_setpoints(now_ts);
+                let setpoint_engine = if due_setpoints.is_empty() {
+                    None
+                } else {
+                    app.try_state::<SimulationManager>().and_then(|m| m.get(instance_id.as_deref()).ok())
+                };
+                for (device_id, p_kw, q_kvar) in due_setpoints {
+                    let Some(engine) = setpoint_engine.as_ref() else {
+                        eprintln!("下发储能计划设定失败 {}: 仿真实例不可用", device_id);
+                        continue;
+                    };
+                    let properties = serde_json::json!({ "p_kw": p_kw, "q_kvar": q_kvar });
+                    if let Err(e) = engine.update_device_properties_for_simulation(device_id.clone(), properties, "storage_schedule").await {
+                        eprintln!("下发储能计划设定失败 {}: {}", device_id, e);
+                    }
+                }
+                
+                // EMS 调度：策略按上一步结果给出储能设定（执行日前计划的储能不受控），变化超过死区时下发
+                let ems_setpoints = match topology.lock().await.as_ref() {
+                    Some(t) => {
+                        let schedules = storage_schedules.lock().unwrap();
+                        ems.lock().unwrap().dispatch(
+                            t,
+                            |id| schedules.has_schedule(id),
+                            &storage_state.lock().unwrap(),
+                            &last_device_power.lock().unwrap(),
+                            now_ts,
+                            step_interval_ms as f64 / 1000.0 * run_options.time_scale,
+                        )
+                    }
+                    None => Vec::new(),
+                };
+                let setpoint_engine = if ems_setpoints.is_empty() {
+                    None
+                } else {
+                    app.try_state::<SimulationManager>().and_then(|m| m.get(instance_id.as_deref()).ok())
+                };
+                for setpoint in ems_setpoints {
+                    let Some(engine) = setpoint_engine.as_ref() else {
+                        eprintln!("下发 EMS 设定失败 {}: 仿真实例不可用", setpoint.device_id);
+                        continue;
+                    };
+                    let properties = serde_json::json!({ "p_kw": setpoint.p_kw });
+                    match engine.update_device_properties_for_simulation(setpoint.device_id.clone(), properties, "ems").await {
+                        Ok(_) => ems.lock().unwrap().commit(&setpoint),
+                        Err(e) => eprintln!("下发 EMS 设定失败 {}: {}", setpoint.device_id, e),
+                    }
+                }
+                
+                // 故障到期：恢复网络并推送故障清除事件
+                let expired_faults = fault_injector.lock().unwrap().take_expired(sim_time_s);
+                for fault in expired_faults {
+                    let params = serde_json::json!({ "device_id": fault.device_id });
+                    let cleared_by = match bridge.call("simulation.clear_fault", params).await {
+                        Ok(_) => "duration",
+                        Err(e) => {
+                            eprintln!("恢复故障失败 {}: {}", fault.device_id, e);
+                            "error"
+                        }
+                    };
+                    let record = fault_injector.lock().unwrap().finish(fault, cleared_by, now_ts);
+                    results_pipeline.notify_typed(&app, Some(&record.fault.device_id), FaultStateChanged::cleared(record));
+                    Self::trigger_burst(&database, &mut results_pipeline, "fault", now_ts);
+                }
+                let requested_bursts: Vec<String> = std::mem::take(&mut *burst_requests.lock().unwrap());
+                for trigger in requested_bursts {
+                    Self::trigger_burst(&database, &mut results_pipeline, &trigger, now_ts);
+                }
+                
+                // 设定值限幅事件（手动/Modbus 指令超出额定功率）推送前端
+                for clamp in setpoint_limiter.lock().unwrap().take_pending() {
+                    results_pipeline.notify_typed(&app, None, SetpointClamped::new(clamp));
+                }
+                
+                // 有载调压：按上一步受控母线电压调整分接头，本步潮流生效
+                let tap_operations = match topology.lock().await.as_ref() {
+                    Some(t) => oltc.lock().unwrap().evaluate(t, sim_time_s, now_ts),
+                    None => Vec::new(),
+                };
+                for operation in tap_operations {
+                    let params = serde_json::json!({ "device_id": operation.device_id, "tap_pos": operation.to_tap });
+                    match bridge.call("simulation.set_transformer_tap", params).await {
+                        Ok(value) if value.get("status").and_then(|v| v.as_str()) != Some("error") => {
+                            oltc.lock().unwrap().commit(operation.clone(), sim_time_s);
+                            results_pipeline.notify_typed(&app, Some(&operation.device_id), TransformerTapChanged::new(operation));
+                        }
+                        Ok(value) => eprintln!("下发分接头档位失败 {}: {}", operation.device_id, value),
+                        Err(e) => eprintln!("下发分接头档位失败 {}: {}", operation.device_id, e),
+                    }
+                }
+                
+                // 光伏电网支撑：按上一步并网点电压计算的 volt-var / volt-watt 设定，变化时下发
+                let grid_support_changes = grid_support.lock().unwrap().take_changes();
+                for (device_id, setpoint) in grid_support_changes {
+                    let params = serde_json::json!({
+                        "device_id": device_id,
+                        "q_kvar": setpoint.q_kvar,
+                        "p_limit_kw": setpoint.p_limit_kw,
+                    });
+                    match bridge.call("simulation.set_grid_support_setpoint", params).await {
+                        Ok(value) if value.get("status").and_then(|v| v.as_str()) != Some("error") => {
+                            grid_support.lock().unwrap().commit(device_id, setpoint);
+                        }
+                        Ok(value) => eprintln!("下发电网支撑设定失败 {}: {}", device_id, value),
+                        Err(e) => eprintln!("下发电网支撑设定失败 {}: {}", device_id, e),
+                    }
+                }
+                
+                // 孤岛频率：按上一步结果积分的系统频率下发内核（P(f) 响应本步生效），恢复并网时下发额定频率
+                let frequency_command = frequency_model.lock().unwrap().take_pending();
+                if let Some(command) = frequency_command {
+                    let params = serde_json::json!({ "frequency_hz": command.frequency_hz, "islanded": command.islanded });
+                    match bridge.call("simulation.set_grid_frequency", params).await {
+                        Ok(value) if value.get("status").and_then(|v| v.as_str()) != Some("error") => {
+                            frequency_model.lock().unwrap().commit(command);
+                        }
+                        Ok(value) => eprintln!("下发孤岛频率失败: {}", value),
+                        Err(e) => eprintln!("下发孤岛频率失败: {}", e),
+                    }
+                }
+                // 储能下垂一次调频：按上一步频率偏差计算的各储能下垂出力下发内核（叠加在本拍设定上），恢复并网时撤销
+                let droop_commands = frequency_model.lock().unwrap().pending_droop();
+                for (device_id, droop_kw) in droop_commands {
+                    let params = serde_json::json!({ "device_id": device_id, "droop_kw": droop_kw });
+                    match bridge.call("simulation.set_droop_setpoint", params).await {
+                        Ok(value) if value.get("status").and_then(|v| v.as_str()) != Some("error") => {
+                            frequency_model.lock().unwrap().commit_droop(&device_id, droop_kw);
+                        }
+                        Ok(value) => eprintln!("下发储能下垂出力失败 {}: {}", device_id, value),
+                        Err(e) => eprintln!("下发储能下垂出力失败 {}: {}", device_id, e),
+                    }
+                }
+                
+                // 随机数据源：Rust 端生成模型的设备按本步仿真时长推进并下发功率
+                let random_devices: Vec<String> = device_modes
+                    .lock()
+                    .await
+                    .iter()
+                    .filter(|(_, mode)| matches!(mode, crate::domain::device::WorkMode::RandomData))
+                    .map(|(id, _)| id.clone())
+                    .collect();
+                if !random_devices.is_empty() {
+                    let dt_s = step_interval_ms as f64 / 1000.0 * run_options.time_scale;
+                    let values = random_generators.lock().unwrap().step(&random_devices, now_ts, dt_s);
+                    // 生成模型每步推进，仅更新周期到期的设备下发
+                    for (device_id, p_kw) in values.into_iter().filter(|(id, _)| multi_rate.is_due(id)) {
+                        let params = serde_json::json!({ "device_id": device_id, "p_kw": p_kw });
+                        if let Err(e) = bridge.call("simulation.set_device_random_value", params).await {
+                            eprintln!("下发随机数据失败 {}: {}", device_id, e);
+                        }
+                    }
+                }
+                
+                // 历史曲线回放：已导入曲线的历史数据模式设备按本步步末仿真时间插值下发
+                let historical_devices: Vec<String> = device_modes
+                    .lock()
+                    .await
+                    .iter()
+                    .filter(|(id, mode)| matches!(mode, crate::domain::device::WorkMode::HistoricalData) && multi_rate.is_due(id))
+                    .map(|(id, _)| id.clone())
+                    .collect();
+                if !historical_devices.is_empty() {
+                    let elapsed_s = sim_time_s + step_interval_ms as f64 / 1000.0 * run_options.time_scale;
+                    let values = profile_player.lock().unwrap().values_at(&historical_devices, elapsed_s);
+                    for (device_id, p_kw, q_kvar) in values {
+                        let params = serde_json::json!({ "device_id": device_id, "p_kw": p_kw, "q_kvar": q_kvar });
+                        if let Err(e) = bridge.call("simulation.set_device_historical_value", params).await {
+                            eprintln!("下发历史曲线数据失败 {}: {}", device_id, e);
+                        }
+                    }
+                }
+                
+                // 辐照 / 气象模型：按本步步末仿真时间计算出力并下发；绑定了气象数据源时辐照模型使用实测辐照与气温，
+                // 未绑定时为晴空辐照
+                let model_values: Vec<(String, f64, &str)> = {
+                    let modes = device_modes.lock().await;
+                    let topo = topology.lock().await;
+                    let at = now_ts + step_interval_ms as f64 / 1000.0 * run_options.time_scale;
+                    let sample = weather_series.lock().unwrap().as_ref().map(|s| s.sample_at(at));
+                    modes
+                        .iter()
+                        .filter(|(id, _)| multi_rate.is_due(id))
+                        .filter_map(|(id, mode)| {
+                            let device = topo.as_ref()?.devices.get(id)?;
+                            match mode {
+                                crate::domain::device::WorkMode::SolarModel => {
+                                    let config = SolarPanelConfig::from_device(device).ok()?;
+                                    let p_kw = weather::pv_output_kw(device, &config, sample.as_ref(), at);
+                                    Some((id.clone(), p_kw, "simulation.set_device_solar_value"))
+                                }
+                                crate::domain::device::WorkMode::WeatherModel => {
+                                    let p_kw = weather::weather_model_output_kw(device, sample.as_ref(), at)?;
+                                    Some((id.clone(), p_kw, "simulation.set_device_weather_value"))
+                                }
+                                _ => None,
+                            }
+                        })
+                        .collect()
+                };
+                for (device_id, p_kw, method) in model_values {
+                    let params = serde_json::json!({ "device_id": device_id, "p_kw": p_kw });
+                    if let Err(e) = bridge.call(method, params).await {
+                        eprintln!("下发模型出力失败 {}: {}", device_id, e);
+                    }
+                }
+                
+                // 电动汽车充电会话：按步初仿真时间推进到达 / 离开并下发需求功率（会话每步推进，仅周期到期的设备下发）；
+                // 结束的会话落库并通知前端
+                let (ev_demands, ev_finished) = {
+                    let modes = device_modes.lock().await;
+                    let topo = topology.lock().await;
+                    let global_seed = random_generators.lock().unwrap().global_seed();
+                    let dt_s = step_interval_ms as f64 / 1000.0 * run_options.time_scale;
+                    let mut bank = ev_sessions.lock().unwrap();
+                    let mut demands = Vec::new();
+                    let mut finished = Vec::new();
+                    for (device_id, mode) in modes.iter() {
+                        if !matches!(mode, crate::domain::device::WorkMode::EvSession) {
+                            continue;
+                        }
+                        let Some(device) = topo.as_ref().and_then(|t| t.devices.get(device_id)) else { continue };
+                        match bank.plan(device_id, device, global_seed, now_ts, dt_s) {
+                            Ok((p_kw, records)) => {
+                                if multi_rate.is_due(device_id) {
+                                    demands.push((device_id.clone(), p_kw));
+                                }
+                                finished.extend(records);
+                            }
+                            Err(e) => eprintln!("充电会话模型参数无效 {}: {}", device_id, e),
                         }
                     }
+                    (demands, finished)
+                };
+                if !ev_finished.is_empty() {
+                    if let Some(ref db) = *database.lock().unwrap() {
+                        for record in &ev_finished {
+                            if let Err(e) = db.insert_ev_session(record) {
+                                eprintln!("写入充电会话失败 {}: {}", record.session_id, e);
+                            }
+                        }
+                    }
+                    for record in ev_finished {
+                        results_pipeline.notify_typed(&app, None, EvSessionEnded::new(record));
+                    }
+                }
+                for (device_id, p_kw) in ev_demands {
+                    let params = serde_json::json!({ "device_id": device_id, "p_kw": p_kw });
+                    if let Err(e) = bridge.call("simulation.set_device_ev_value", params).await {
+                        eprintln!("下发充电需求失败 {}: {}", device_id, e);
+                    }
                 }
                 
                 // 主动触发计算并获取结果（避免时序问题）
                 // 这样可以确保获取的是最新计算结果，而不是滞后的结果
-                if let Ok(result_data) = bridge.call("simulation.perform_calculation", serde_json::json!({})).await {
+                // 本步结果对应的仿真时间（步末），随计算请求下发内核
+                let step_dt_s = step_interval_ms as f64 / 1000.0 * run_options.time_scale;
+                let step_sim_time = sim_clock.lock().unwrap().now() + step_dt_s;
+                let calc_params = serde_json::json!({ "force": single_step.is_some(), "sim_time": step_sim_time });
+                // 本步命中的条件断点（步末释放内核后暂停）
+                let mut breakpoint_hits: Vec<BreakpointHit> = Vec::new();
+                if let Ok(result_data) = bridge.call_bulk("simulation.perform_calculation", calc_params).await {
                     if let Some(result) = result_data.get("result") {
+                        run_stats.lock().unwrap().record_step(result, step_sim_time, step_dt_s);
                         // 检查是否因错误需要自动停止：显式 auto_paused 或（未收敛且有错误）
                         let auto_paused = result.get("auto_paused").and_then(|v| v.as_bool()).unwrap_or(false);
                         let converged = result.get("converged").and_then(|v| v.as_bool()).unwrap_or(false);
@@ -394,26 +1492,15 @@ impl SimulationEngine {
                             if let Some(errors_array) = result.get("errors").and_then(|v| v.as_array()) {
                                 let new_errors: Vec<crate::domain::simulation::SimulationError> = errors_array
                                     .iter()
-                                    .filter_map(|e| {
-                                        let mut error_obj = e.clone();
-                                        if let serde_json::Value::Object(ref mut map) = error_obj {
-                                            if let Some(type_value) = map.remove("type") {
-                                                map.insert("error_type".to_string(), type_value);
-                                            }
-                                            if let Some(serde_json::Value::Number(timestamp_num)) = map.get("timestamp") {
-                                                if let Some(timestamp_f64) = timestamp_num.as_f64() {
-                                                    map.insert("timestamp".to_string(), serde_json::json!(timestamp_f64 as u64));
-                                                }
-                                            }
-                                        }
-                                        serde_json::from_value::<crate::domain::simulation::SimulationError>(error_obj).ok()
-                                    })
+                                    .filter_map(crate::domain::simulation::SimulationError::from_kernel_value)
                                     .collect();
                                 if !new_errors.is_empty() {
                                     let mut status_guard = status.lock().await;
                                     status_guard.errors = new_errors.clone();
                                     drop(status_guard);
-                                    let _ = app.emit("simulation-errors-update", serde_json::json!({ "errors": new_errors }));
+                                    results_pipeline.notify_typed(&app, None, SimulationErrorsUpdate::new(new_errors.clone()));
+                                    // 停止前补写事件前被抽稀的步
+                                    Self::trigger_burst(&database, &mut results_pipeline, "error", now_ts);
                                 }
                             }
                             // 再执行停止，与用户点击「停止」一致
@@ -425,48 +1512,238 @@ impl SimulationEngine {
                             device_active_status.lock().await.clear();
                             last_device_power.lock().unwrap().clear();
                             storage_state.lock().unwrap().clear();
+                            if let Some(ref db) = *database.lock().unwrap() {
+                                run_recovery::set_run_status(db, RunStatus::Completed);
+                                forecast_accuracy.lock().unwrap().persist(db);
+                            }
+                            results_pipeline.finish(sim_clock.lock().unwrap().now());
+                            results_pipeline.flush().await;
+                            let summary = {
+                                let topo = topology.lock().await;
+                                let index = result_index.lock().unwrap().clone();
+                                Self::finish_run_summary(&run_stats, "error", topo.as_ref(), &index, &device_energy, &storage_schedules, &database)
+                            };
+                            if let Some(summary) = summary {
+                                results_pipeline.notify_typed(&app, None, SimulationSummary::new(summary));
+                            }
 
                             let stop_params = serde_json::json!({ "action": "stop" });
                             if let Err(e) = bridge.call("simulation.stop", stop_params).await {
                                 eprintln!("自动停止时调用 simulation.stop 失败: {}", e);
                             }
                             eprintln!("检测到严重错误，仿真已自动停止");
-                            let _ = app.emit("simulation-auto-stopped", serde_json::json!({
-                                "reason": "严重错误导致计算失败"
-                            }));
+                            results_pipeline.notify_typed(&app, None, SimulationAutoStopped {
+                                schema_version: EVENT_SCHEMA_VERSION,
+                                reason: "严重错误导致计算失败".to_string(),
+                            });
+                            if let Some(webhooks) = app.try_state::<WebhookDispatcher>().filter(|_| !results_pipeline.is_headless()) {
+                                let errors = status.lock().await.errors.clone();
+                                webhooks.notify(WebhookEvent::SimulationAutoStopped, serde_json::json!({
+                                    "reason": "严重错误导致计算失败",
+                                    "db_path": current_db_path.lock().map(|p| p.clone()).unwrap_or_default(),
+                                    "errors": errors,
+                                }));
+                            }
                         }
                         
+                        // 配置了传感器延迟时，前端收到的也是延迟后的设备结果
+                        let mut reported_result: Option<serde_json::Value> = None;
                         // 处理计算结果并存储到数据库
                         if let Some(devices) = result.get("devices") {
                             // 提取设备数据并存储
                             let topo = topology.lock().await;
                             if let Some(ref t) = topo.as_ref() {
-                                // 获取当前时间戳
-                                let timestamp = SystemTime::now()
-                                    .duration_since(UNIX_EPOCH)
-                                    .unwrap()
-                                    .as_secs_f64();
-                                
-                                let dt_seconds = calculation_interval_ms as f64 / 1000.0;
+                                // 每步仿真时间 = 计算步长 × 时间倍率；时间戳取步末仿真时间（暂停不前进、加速时按倍率前进）
+                                let dt_seconds = step_dt_s;
+                                let (step_sim_start_s, timestamp) = {
+                                    let mut clock = sim_clock.lock().unwrap();
+                                    let start_s = clock.elapsed_s;
+                                    clock.advance(dt_seconds);
+                                    (start_s, clock.now())
+                                };
+                                status.lock().await.sim_time = Some(timestamp);
+                                // 日电量翻转：步末仿真时间跨日时先归档并清零上一日的日电量，本步电量计入新的一日；
+                                // 关闭翻转时日电量始终归入同一日（不清零）
+                                let day = if run_options.daily_rollover.enabled {
+                                    run_options.daily_rollover.day_of(timestamp)
+                                } else {
+                                    String::new()
+                                };
+                                if let Some(previous_day) = current_day.clone().filter(|d| *d != day) {
+                                    let modbus = app
+                                        .try_state::<crate::services::modbus::ModbusService>()
+                                        .filter(|_| !results_pipeline.is_headless());
+                                    let mut pv_generation: HashMap<String, f64> = HashMap::new();
+                                    if let Some(ref modbus) = modbus {
+                                        // 按寄存器列表解码今日发电量（自定义地址与数据类型生效）
+                                        let energy = modbus.energy_value_snapshot().await;
+                                        for (device_id, _) in t.devices.iter().filter(|(_, d)| d.device_type == DeviceType::Pv) {
+                                            if let Some(today) = energy.get(device_id).and_then(|v| v.get("daily_energy_kwh")) {
+                                                pv_generation.insert(device_id.clone(), *today);
+                                            }
+                                        }
+                                    }
+                                    let archive = DailyEnergyArchive::collect(
+                                        &previous_day,
+                                        timestamp,
+                                        &storage_state.lock().unwrap(),
+                                        pv_generation,
+                                        &device_energy.lock().unwrap(),
+                                    );
+                                    let storage_count = archive.storage.len();
+                                    if let Some(ref db) = *database.lock().unwrap() {
+                                        daily_rollover::append_archive(db, archive);
+                                    }
+                                    for s in storage_state.lock().unwrap().values_mut() {
+                                        s.daily_charge_kwh = 0.0;
+                                        s.daily_discharge_kwh = 0.0;
+                                    }
+                                    let pv_count = match modbus {
+                                        Some(ref modbus) => modbus.reset_counter_registers(CounterGroup::PvDailyGeneration, None).await.len(),
+                                        None => 0,
+                                    };
+                                    results_pipeline.notify_typed(&app, None, DailyCountersRolledOver {
+                                        schema_version: EVENT_SCHEMA_VERSION,
+                                        day: previous_day,
+                                        timestamp,
+                                        storage_count,
+                                        pv_count,
+                                    });
+                                }
+                                current_day = Some(day.clone());
                                 step_count += 1;
+                                results_pipeline.begin_step(step_count);
+                                results_pipeline.set_deferred_devices(multi_rate.deferred());
+                                // 电表通信中断：本步丢数的电表不落库、不更新 Modbus 寄存器，内部功率缓存与前端事件仍为真实值
+                                let dropped_meters = meter_dropout
+                                    .lock()
+                                    .unwrap()
+                                    .step(t, step_sim_start_s, timestamp);
+                                // 传感器上报延迟：落库、功率缓存与 Modbus 使用延迟后的结果，限值评估仍按真实潮流
+                                let delayed_devices = delay_simulator
+                                    .lock()
+                                    .unwrap()
+                                    .apply_sensor_delay(t, devices, step_sim_start_s);
+                                let reported_devices = delayed_devices.as_ref().unwrap_or(devices);
+                                if let Some(ref delayed) = delayed_devices {
+                                    let mut r = result.clone();
+                                    r["devices"] = delayed.clone();
+                                    reported_result = Some(r);
+                                }
+                                // 电表计量：本步各电表的变比与误差系数（落库与 Modbus 上报二次侧读数）
+                                let meter_factors = meter_accuracy.lock().unwrap().step_factors(t);
                                 // 处理并存储计算结果（传入完整拓扑、储能状态与步长；更新功率缓存与储能 SOC/日/累计电量）
-                                Self::process_calculation_results_inline(&app, devices, t, &database, &last_device_power, &storage_state, timestamp, dt_seconds);
+                                let index = result_index.lock().unwrap().clone();
+                                Self::process_calculation_results_inline(&app, reported_devices, t, &index, &last_device_power, &storage_state, timestamp, dt_seconds, &dropped_meters, &meter_factors, &sign_convention, &mut results_pipeline);
+                                Self::accumulate_device_energy(&device_energy, t, &last_device_power, &day, timestamp, dt_seconds / 3600.0);
+                                // 有载调压按真实潮流的母线电压调节（不受传感器延迟影响）
+                                oltc.lock().unwrap().observe(t, &index, devices);
+                                protection.lock().unwrap().observe(t, &index, devices);
+                                grid_support.lock().unwrap().observe(t, &index, devices);
+                                // 孤岛频率：外部电网断开时按本拍设备设定推进摇摆方程，推送前端并落库为系统频率行
+                                let frequency_sample = frequency_model.lock().unwrap().step(t, result, dt_seconds, timestamp);
+                                if let Some(sample) = frequency_sample {
+                                    let data_json = serde_json::to_string(&sample).ok();
+                                    results_pipeline.record_row(
+                                        frequency_model::FREQUENCY_DEVICE_ID,
+                                        timestamp,
+                                        Some(sample.imbalance_kw),
+                                        None,
+                                        data_json.as_deref(),
+                                        Some(frequency_model::FREQUENCY_DEVICE_TYPE),
+                                    );
+                                    results_pipeline.publish_typed(&app, None, FrequencyUpdate::new(sample));
+                                }
+                                // 充电会话：按充电桩实际功率累计各枪电量，更新枪状态寄存器
+                                let gun_states = {
+                                    let mut bank = ev_sessions.lock().unwrap();
+                                    bank.deliver(t, &index, devices, result.get("device_setpoints"), dt_seconds, timestamp);
+                                    bank.gun_states()
+                                };
+                                if let Some(modbus) = app
+                                    .try_state::<crate::services::modbus::ModbusService>()
+                                    .filter(|_| !results_pipeline.is_headless() && !gun_states.is_empty())
+                                {
+                                    for (device_id, states, demand_kw) in &gun_states {
+                                        modbus.update_charger_guns(device_id, states, *demand_kw).await;
+                                    }
+                                }
+                                // 储能计划执行偏差：按本步实际功率累计
+                                {
+                                    let power = last_device_power.lock().unwrap();
+                                    let mut schedules = storage_schedules.lock().unwrap();
+                                    for (device_id, (_, p_kw, _)) in power.iter() {
+                                        if schedules.has_schedule(device_id) {
+                                            schedules.record_step(device_id, timestamp, p_kw.unwrap_or(0.0), dt_seconds / 3600.0);
+                                        }
+                                    }
+                                }
+                                // 预测精度：有导入预测或内置晴空预测的设备按步末仿真时间对比本步实际功率
+                                forecast_accuracy.lock().unwrap().record_step(t, timestamp, &last_device_power.lock().unwrap());
+                                // 软限值评估：告警集合变化时推送当前全部告警
+                                let limit_alerts = {
+                                    let mut monitor = limit_monitor.lock().unwrap();
+                                    monitor.evaluate(devices, t, &index, timestamp).then(|| monitor.active_alerts())
+                                };
+                                // 条件断点：按真实潮流、储能状态与功率缓存评估
+                                {
+                                    let mut set = breakpoints.lock().unwrap();
+                                    if !set.is_empty() {
+                                        breakpoint_hits = set.evaluate(devices, t, &storage_state.lock().unwrap(), &last_device_power.lock().unwrap());
+                                    }
+                                }
+                                if let Some(alerts) = limit_alerts {
+                                    // Webhook 只通知新出现或等级变化的告警
+                                    let current_keys: std::collections::HashSet<(String, String, LimitLevel)> = alerts
+                                        .iter()
+                                        .map(|a| (a.device_id.clone(), a.quantity.clone(), a.level))
+                                        .collect();
+                                    let raised: Vec<&LimitAlert> = alerts
+                                        .iter()
+                                        .filter(|a| !notified_alerts.contains(&(a.device_id.clone(), a.quantity.clone(), a.level)))
+                                        .collect();
+                                    if !raised.is_empty() {
+                                        Self::trigger_burst(&database, &mut results_pipeline, "alarm", timestamp);
+                                        if let Some(webhooks) = app.try_state::<WebhookDispatcher>().filter(|_| !results_pipeline.is_headless()) {
+                                            webhooks.notify(WebhookEvent::AlertRaised, serde_json::json!({ "alerts": raised }));
+                                        }
+                                    }
+                                    notified_alerts = current_keys;
+                                    results_pipeline.notify_typed(&app, None, LimitAlertsUpdate { schema_version: EVENT_SCHEMA_VERSION, alerts });
+                                }
+                                // 外部电网分时功率限值检查：越限时记录并通知前端
+                                let violations = Self::check_grid_schedule_limits(devices, t, &index, timestamp, &sign_convention);
+                                if !violations.is_empty() {
+                                    results_pipeline.notify_typed(&app, None, GridLimitViolationUpdate { schema_version: EVENT_SCHEMA_VERSION, violations: violations.clone() });
+                                    let mut guard = grid_limit_violations.lock().unwrap();
+                                    guard.extend(violations);
+                                    let overflow = guard.len().saturating_sub(MAX_GRID_LIMIT_VIOLATIONS);
+                                    guard.drain(..overflow);
+                                }
                                 // 仿真结果同步到运行中的 Modbus 设备寄存器（v1.5.0 update_* 逻辑）；额定功率等不可变数据仅在加载拓扑启动时写入
-                                // 按设备采样间隔节流：只有当距离上次更新已过采样间隔时才更新该设备的 Modbus IR
-                                if let Some(modbus) = app.try_state::<crate::services::modbus::ModbusService>() {
+                                // 按 Modbus 同步间隔与设备采样间隔节流：只有当距离上次更新已过采样间隔时才更新该设备的 Modbus IR
+                                let modbus_service = app
+                                    .try_state::<crate::services::modbus::ModbusService>()
+                                    .filter(|_| results_pipeline.sync_modbus_now());
+                                if let Some(modbus) = modbus_service {
+                                    // 电量积分步长取距上次同步经过的仿真时间
+                                    let modbus_dt_seconds = dt_seconds * results_pipeline.mark_modbus_synced() as f64;
                                     let full_power_snapshot: HashMap<String, (f64, Option<f64>, Option<f64>)> =
                                         last_device_power.lock().unwrap().clone();
                                     // 按设备过滤：仅保留采样间隔到期的设备
                                     let sim_params_guard = device_sim_params.lock().await;
                                     let mut filtered_power: HashMap<String, (f64, Option<f64>, Option<f64>)> = HashMap::new();
-                                    for (did, val) in &full_power_snapshot {
+                                    for (did, val) in full_power_snapshot
+                                        .iter()
+                                        .filter(|(did, _)| !dropped_meters.contains(*did) && multi_rate.is_due(did))
+                                    {
                                         let sampling_ms = sim_params_guard
                                             .get(did)
                                             .and_then(|p| p.get("samplingIntervalMs"))
                                             .and_then(|v| v.as_f64())
                                             .unwrap_or(0.0);
                                         if sampling_ms > 0.0 {
-                                            let interval_steps = (sampling_ms / (calculation_interval_ms as f64)).max(1.0).ceil() as u64;
+                                            let interval_steps = (sampling_ms / (step_interval_ms as f64)).max(1.0).ceil() as u64;
                                             let last_step = last_modbus_update_step.get(did).copied().unwrap_or(0);
                                             if step_count - last_step >= interval_steps {
                                                 filtered_power.insert(did.clone(), val.clone());
@@ -479,7 +1756,9 @@ impl SimulationEngine {
                                     }
                                     drop(sim_params_guard);
                                     let storage_states = storage_state.lock().unwrap().clone();
-                                    let _ = modbus.update_all_devices_from_simulation(&filtered_power, dt_seconds, Some(&storage_states)).await;
+                                    let sign_factors = Self::modbus_sign_factors(t, &sign_convention);
+                                    let _ = modbus.update_all_devices_from_simulation(&filtered_power, modbus_dt_seconds, Some(&storage_states), &sign_factors).await;
+                                    modbus.update_switch_coils(&Self::switch_states(t)).await;
                                     // 推送寄存器快照到前端，联动更新 Modbus 页面的寄存器值显示
                                     for device_id in modbus.running_device_ids() {
                                         if let Some((ir, hr)) = modbus.get_device_register_snapshot(&device_id).await {
@@ -487,14 +1766,36 @@ impl SimulationEngine {
                                                 ir.into_iter().map(|(k, v)| (k.to_string(), v)).collect();
                                             let hr_map: std::collections::HashMap<String, u16> =
                                                 hr.into_iter().map(|(k, v)| (k.to_string(), v)).collect();
-                                            let _ = app.emit("modbus-registers-updated", serde_json::json!({
-                                                "device_id": device_id,
-                                                "input_registers": ir_map,
-                                                "holding_registers": hr_map,
-                                            }));
+                                            results_pipeline.notify_typed(&app, None, ModbusRegistersUpdated {
+                                                schema_version: EVENT_SCHEMA_VERSION,
+                                                device_id,
+                                                input_registers: ir_map,
+                                                holding_registers: hr_map,
+                                            });
                                         }
                                     }
                                 }
+                                if step_count - last_checkpoint_step >= run_recovery::RUN_CHECKPOINT_INTERVAL_STEPS {
+                                    last_checkpoint_step = step_count;
+                                    let energy_registers = match app
+                                        .try_state::<crate::services::modbus::ModbusService>()
+                                        .filter(|_| !results_pipeline.is_headless())
+                                    {
+                                        Some(modbus) => modbus.energy_register_snapshot().await,
+                                        None => HashMap::new(),
+                                    };
+                                    let checkpoint = RunCheckpoint {
+                                        timestamp,
+                                        storage_state: storage_state.lock().unwrap().clone(),
+                                        device_energy: device_energy.lock().unwrap().clone(),
+                                        energy_registers,
+                                        sim_clock: Some(*sim_clock.lock().unwrap()),
+                                    };
+                                    if let Some(ref db) = *database.lock().unwrap() {
+                                        run_recovery::write_checkpoint(db, &checkpoint);
+                                        forecast_accuracy.lock().unwrap().persist(db);
+                                    }
+                                }
                                 // 本拍成功获取到数据，标记拓扑内设备在本轮仿真中为在线
                                 let mut active = device_active_status.lock().await;
                                 for id in t.devices.keys() {
@@ -504,13 +1805,29 @@ impl SimulationEngine {
                             drop(topo);
                         }
                         
-                        // 发送计算结果更新事件
-                        let _ = app.emit("calculation-result-update", result);
+                        // 提交本步状态（供多窗口一致快照），再发送计算结果更新事件与步提交事件
+                        let step_result = reported_result.as_ref().unwrap_or(result);
+                        let committed = app.try_state::<Arc<WindowEventHub>>().filter(|_| !results_pipeline.is_headless()).map(|hub| {
+                            let committed_at = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs_f64();
+                            let seq = hub.commit_step(
+                                committed_at,
+                                step_result.clone(),
+                                last_device_power.lock().unwrap().clone(),
+                                storage_state.lock().unwrap().clone(),
+                            );
+                            (seq, committed_at)
+                        });
+                        results_pipeline.publish(&app, "calculation-result-update", None, step_result);
+                        if let Some((seq, committed_at)) = committed {
+                            results_pipeline.publish_typed(&app, None, StateStepCommitted {
+                                schema_version: EVENT_SCHEMA_VERSION,
+                                seq,
+                                timestamp: committed_at,
+                            });
+                        }
                     }
                 }
                 
-                drop(bridge);
-                
                 // 本步总耗时（含 RPC + 计算 + 处理），用于更新每步平均耗时
                 let elapsed_ms = start_time.elapsed().as_millis() as f64;
                 calculation_times.push(elapsed_ms);
@@ -519,11 +1836,39 @@ impl SimulationEngine {
                 }
                 
                 let avg_delay = calculation_times.iter().sum::<f64>() / calculation_times.len() as f64;
+                let overrun = elapsed_ms > step_interval_ms as f64;
+                // 本步期间错过的节拍数（单步执行不计）
+                let missed_ticks = if overrun && single_step.is_none() {
+                    (elapsed_ms / step_interval_ms as f64).floor() as u64
+                } else {
+                    0
+                };
+                // 自适应计算间隔：按平均步耗时调整，变化超过 10% 才重建定时器，避免频繁抖动
+                if let Some(adaptive) = run_options.adaptive_interval.as_ref().filter(|_| single_step.is_none() && calculation_times.len() >= 5) {
+                    let target_ms = adaptive.target_interval_ms(avg_delay);
+                    if (target_ms as f64 - step_interval_ms as f64).abs() > step_interval_ms as f64 * 0.1 {
+                        step_interval_ms = target_ms;
+                        results_pipeline.set_step_interval_ms(target_ms);
+                        let period = Duration::from_millis(target_ms);
+                        interval = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+                        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+                        last_tick = None;
+                    }
+                }
                 let mut status_guard = status.lock().await;
                 status_guard.average_delay = avg_delay;
+                status_guard.current_interval_ms = step_interval_ms;
+                if overrun {
+                    status_guard.overrun_count += 1;
+                    status_guard.missed_steps += missed_ticks;
+                }
+                if !tick_jitters.is_empty() {
+                    status_guard.jitter_mean_ms = tick_jitters.iter().sum::<f64>() / tick_jitters.len() as f64;
+                    status_guard.jitter_max_ms = tick_jitters.iter().cloned().fold(0.0, f64::max);
+                }
                 
-                // 更新运行时间（仅统计运行中时间，减去累计暂停时长，与 calculation_count 同步）
-                if let Some(start_time) = status_guard.start_time {
+                // 更新运行时间（仅统计运行中时间，减去累计暂停时长，与 calculation_count 同步）；单步时仍处于暂停，不更新
+                if let Some(start_time) = status_guard.start_time.filter(|_| single_step.is_none()) {
                     let now = SystemTime::now()
                         .duration_since(UNIX_EPOCH)
                         .unwrap()
@@ -533,62 +1878,323 @@ impl SimulationEngine {
                         .saturating_sub(status_guard.total_paused_secs);
                 }
                 drop(status_guard);
+                if let Some(done) = single_step {
+                    let _ = done.send(step_count);
+                }
+                // 条件断点命中：暂停仿真（单步执行本就保持暂停）并推送命中值
+                if !breakpoint_hits.is_empty() {
+                    if single_step.is_none() {
+                        let engine = app
+                            .try_state::<SimulationManager>()
+                            .and_then(|m| m.get(instance_id.as_deref()).ok());
+                        if let Some(engine) = engine {
+                            if let Err(e) = engine.pause().await {
+                                eprintln!("断点暂停仿真失败: {}", e);
+                            }
+                        }
+                    }
+                    results_pipeline.notify_typed(&app, None, SimulationBreakpointHit {
+                        schema_version: EVENT_SCHEMA_VERSION,
+                        timestamp: sim_clock.lock().unwrap().now(),
+                        step: step_count,
+                        hits: breakpoint_hits,
+                    });
+                }
+                // 自动停止条件：完成步数或仿真时长达到上限时按停止流程结束（生成运行摘要），循环随后收到退出信号
+                let elapsed_s = sim_clock.lock().unwrap().elapsed_s;
+                let auto_stop = if run_options.max_steps.is_some_and(|n| step_count >= n) {
+                    Some(("max_steps", format!("已完成设定的 {} 步", step_count)))
+                } else if run_options.max_duration_secs.is_some_and(|d| elapsed_s >= d - 1e-6) {
+                    Some(("max_duration", format!("仿真时长已达 {:.0} 秒", elapsed_s)))
+                } else {
+                    None
+                };
+                if let Some((reason, message)) = auto_stop {
+                    let engine = app
+                        .try_state::<SimulationManager>()
+                        .and_then(|m| m.get(instance_id.as_deref()).ok());
+                    if let Some(engine) = engine {
+                        eprintln!("{}，仿真自动停止", message);
+                        let db_path = current_db_path.lock().map(|p| p.clone()).unwrap_or_default();
+                        if let Err(e) = engine.stop_with_reason(reason).await {
+                            eprintln!("自动停止仿真失败: {}", e);
+                        }
+                        // 达到设定步数/时长的正常结束同样通知 Webhook（无人值守的长时间运行据此得知结束）
+                        if let Some(webhooks) = app.try_state::<WebhookDispatcher>().filter(|_| !results_pipeline.is_headless()) {
+                            webhooks.notify(WebhookEvent::SimulationAutoStopped, serde_json::json!({
+                                "reason": reason,
+                                "message": message,
+                                "db_path": db_path,
+                                "steps": step_count,
+                                "elapsed_s": elapsed_s,
+                            }));
+                        }
+                        results_pipeline.notify_typed(&app, None, SimulationAutoStopped {
+                            schema_version: EVENT_SCHEMA_VERSION,
+                            reason: message,
+                        });
+                    }
+                }
             }
         });
     }
     
-    /// 从拓扑构建 目标设备 id -> 指向该设备的电表 id 列表（用于落库时把目标数据也写入电表）
-    fn build_target_to_meters(topology: &Topology) -> HashMap<String, Vec<String>> {
-        use crate::domain::topology::DeviceType;
-        let mut target_to_meters: HashMap<String, Vec<String>> = HashMap::new();
-        for conn in topology.connections.values() {
-            let from_id = &conn.from_device_id;
-            let to_id = &conn.to_device_id;
-            let from_is_meter = topology.devices.get(from_id).map(|d| d.device_type == DeviceType::Meter).unwrap_or(false);
-            let to_is_meter = topology.devices.get(to_id).map(|d| d.device_type == DeviceType::Meter).unwrap_or(false);
-            if from_is_meter {
-                target_to_meters.entry(to_id.clone()).or_default().push(from_id.clone());
+    /// 按 ExternalGrid 的 grid_schedule 检查本步仿真时间所在小时的购电/上网功率限值（res_ext_grid p_mw 正=购电）；记录中的功率按项目符号约定输出
+    fn check_grid_schedule_limits(
+        results: &serde_json::Value,
+        topology: &Topology,
+        index: &ResultIndex,
+        timestamp: f64,
+        sign_convention: &SignConvention,
+    ) -> Vec<GridLimitViolation> {
+        let mut violations = Vec::new();
+        let Some(ext_grids) = results.get("ext_grids").and_then(|v| v.as_object()) else {
+            return violations;
+        };
+        let hour = crate::domain::simulation::sim_hour_of_day(timestamp);
+        for row in ext_grids.values() {
+            let (Some(name), Some(p_mw)) = (
+                row.get("name").and_then(|v| v.as_str()),
+                row.get("p_mw").and_then(|v| v.as_f64()),
+            ) else {
+                continue;
+            };
+            let Some((device_id, device)) = index.lookup(topology, "ext_grids", name) else {
+                continue;
+            };
+            let Some(slot) = GridSchedule::from_properties(&device.properties).and_then(|s| s.slot_at(hour).cloned()) else {
+                continue;
+            };
+            let p_kw = p_mw * 1000.0;
+            let exceeded = if p_kw >= 0.0 {
+                slot.import_limit_kw.filter(|l| p_kw > *l).map(|l| ("import", l))
+            } else {
+                slot.export_limit_kw.filter(|l| -p_kw > *l).map(|l| ("export", l))
+            };
+            if let Some((direction, limit_kw)) = exceeded {
+                violations.push(GridLimitViolation {
+                    device_id: device_id.clone(),
+                    timestamp,
+                    hour,
+                    direction: direction.to_string(),
+                    p_kw: p_kw * sign_convention.factor(&device.device_type),
+                    limit_kw,
+                });
             }
-            if to_is_meter {
-                target_to_meters.entry(from_id.clone()).or_default().push(to_id.clone());
+        }
+        violations
+    }
+
+    /// 按本步功率累加功率设备电量（内核原生约定：光伏正=发电，其余正=从电网取电，外部电网正=购电）
+    fn accumulate_device_energy(
+        device_energy: &Arc<StdMutex<HashMap<String, DeviceEnergyCounters>>>,
+        topology: &Topology,
+        last_device_power: &Arc<StdMutex<HashMap<String, (f64, Option<f64>, Option<f64>)>>>,
+        day: &str,
+        timestamp: f64,
+        dt_h: f64,
+    ) {
+        use crate::domain::topology::DeviceType;
+        if dt_h <= 0.0 {
+            return;
+        }
+        let power = last_device_power.lock().unwrap();
+        let mut counters = device_energy.lock().unwrap();
+        for (device_id, device) in &topology.devices {
+            let consumed_sign = match device.device_type {
+                DeviceType::Pv => -1.0,
+                DeviceType::Storage | DeviceType::Load | DeviceType::Charger | DeviceType::ExternalGrid => 1.0,
+                _ => continue,
+            };
+            // 只积分本步刷新的功率：本步未更新（离线、内核无结果）的设备缓存值已过期，不计入电量
+            let Some(p_kw) = power
+                .get(device_id)
+                .filter(|(ts, _, _)| *ts >= timestamp)
+                .and_then(|(_, p, _)| *p)
+            else {
+                continue;
+            };
+            counters
+                .entry(device_id.clone())
+                .or_default()
+                .accumulate(day, p_kw * consumed_sign, dt_h);
+        }
+    }
+
+    /// 通知结果管线触发突发落库（无仿真库时忽略）
+    fn trigger_burst(database: &Arc<StdMutex<Option<Database>>>, pipeline: &mut ResultsPipeline, trigger: &str, timestamp: f64) {
+        if let Some(ref db) = *database.lock().unwrap() {
+            pipeline.trigger_burst(db, trigger, timestamp);
+        }
+    }
+
+    /// 储能模型的正数属性（数值或数字字符串）；未配置、非正或非有限值时为 None
+    fn storage_property(device: &crate::domain::topology::Device, key: &str) -> Option<f64> {
+        device
+            .properties
+            .get(key)
+            .and_then(|v| v.as_f64().or_else(|| v.as_str().and_then(|s| s.trim().parse::<f64>().ok())))
+            .filter(|v| v.is_finite() && *v > 0.0)
+    }
+
+    /// 储能效率属性：0–1 的小数，大于 1 时按百分数解析（与标定写回的 charge_efficiency / discharge_efficiency 一致）；未配置时无损
+    fn storage_efficiency(device: &crate::domain::topology::Device, key: &str) -> f64 {
+        Self::storage_property(device, key)
+            .filter(|v| *v <= 100.0)
+            .map(|v| if v > 1.0 { v / 100.0 } else { v })
+            .unwrap_or(1.0)
+    }
+
+    /// 按一步功率积分储能能量与 SOC（p_kw 为内核原生约定，正=充电）；首次出现时按拓扑容量与初始 SOC 初始化。
+    /// 能量增量 ΔE = η_c·E_充 − E_放/η_d − 自放电（self_discharge_pct_per_hour，按当前储能量每小时百分比），
+    /// 日/累计充放电量仍为并网侧电量。
+    /// 衰减：等效满充放循环 = 并网侧充放电吞吐量 / (2 × 额定容量)，容量衰减 = 循环数 × cycle_fade_pct
+    /// + 运行天数 × calendar_fade_pct_per_day，SOC 按衰减后的有效容量计算
+    pub(crate) fn advance_storage_state(
+        state_map: &mut HashMap<String, StorageState>,
+        device_id: &str,
+        device: &crate::domain::topology::Device,
+        p_kw: f64,
+        dt_h: f64,
+    ) {
+        // 容量：支持 capacity / capacity_kwh（设备详情用 capacity_kwh）；max_e_mwh 单位 MWh -> kWh
+        let capacity_kwh: f64 = device
+            .properties
+            .get("capacity_kwh")
+            .or_else(|| device.properties.get("capacity"))
+            .and_then(|v| v.as_f64().or_else(|| v.as_str().and_then(|s| s.parse::<f64>().ok())))
+            .or_else(|| {
+                device.properties.get("max_e_mwh")
+                    .and_then(|v| v.as_f64().or_else(|| v.as_str().and_then(|s| s.parse::<f64>().ok())))
+                    .map(|v| v * 1000.0)
+            })
+            .unwrap_or(1000.0);
+        // 初始 SOC：设备详情修改并保存后从 properties.initial_soc 读取（0–100），默认 50
+        let initial_soc: f64 = device
+            .properties
+            .get("initial_soc")
+            .and_then(|v| v.as_f64().or_else(|| v.as_str().and_then(|s| s.parse::<f64>().ok())))
+            .map(|v| v.clamp(0.0, 100.0))
+            .unwrap_or(50.0);
+        if capacity_kwh > 0.0 {
+            let state = state_map.entry(device_id.to_string()).or_insert_with(|| StorageState {
+                capacity_kwh,
+                effective_capacity_kwh: capacity_kwh,
+                energy_kwh: capacity_kwh * (initial_soc / 100.0),
+                soc_percent: initial_soc,
+                ..Default::default()
+            });
+            if (state.capacity_kwh - capacity_kwh).abs() > 1e-6 {
+                state.capacity_kwh = capacity_kwh;
+            }
+            // pandapower 约定：p_kw 正=充电(能量流入)，负=放电(能量流出)；充电按充电效率计入，放电按放电效率多耗电芯能量
+            let cell_energy_kwh = if p_kw > 0.0 {
+                p_kw * dt_h * Self::storage_efficiency(device, "charge_efficiency")
+            } else {
+                p_kw * dt_h / Self::storage_efficiency(device, "discharge_efficiency")
+            };
+            let self_discharge_pct_per_hour = Self::storage_property(device, "self_discharge_pct_per_hour").unwrap_or(0.0);
+            let self_discharge_kwh = state.energy_kwh * (self_discharge_pct_per_hour / 100.0 * dt_h).min(1.0);
+            // 循环与日历衰减；衰减上限 99% 以保留非零有效容量
+            if dt_h > 0.0 {
+                state.equivalent_full_cycles += p_kw.abs() * dt_h / (2.0 * state.capacity_kwh);
+                state.operating_hours += dt_h;
+            }
+            let cycle_fade_pct = Self::storage_property(device, "cycle_fade_pct").unwrap_or(0.0);
+            let calendar_fade_pct_per_day = Self::storage_property(device, "calendar_fade_pct_per_day").unwrap_or(0.0);
+            state.capacity_fade_percent = (state.equivalent_full_cycles * cycle_fade_pct
+                + state.operating_hours / 24.0 * calendar_fade_pct_per_day)
+                .min(99.0);
+            state.effective_capacity_kwh = state.capacity_kwh * (1.0 - state.capacity_fade_percent / 100.0);
+            state.energy_kwh += cell_energy_kwh - self_discharge_kwh;
+            state.energy_kwh = state.energy_kwh.clamp(0.0, state.effective_capacity_kwh);
+            state.soc_percent = (state.energy_kwh / state.effective_capacity_kwh * 100.0).clamp(0.0, 100.0);
+            if p_kw > 0.0 {
+                state.daily_charge_kwh += p_kw * dt_h;
+                state.total_charge_kwh += p_kw * dt_h;
+            } else if p_kw < 0.0 {
+                state.daily_discharge_kwh += -p_kw * dt_h;
+                state.total_discharge_kwh += -p_kw * dt_h;
+            }
+        }
+    }
+
+    /// Modbus 有符号功率寄存器的符号系数：储能按储能约定，电表按其测量对象的约定；其余设备寄存器为无符号，不翻转
+    pub(crate) fn modbus_sign_factors(topology: &Topology, sign_convention: &SignConvention) -> HashMap<String, f64> {
+        use crate::domain::topology::DeviceType;
+        let mut factors = HashMap::new();
+        for (target_id, meters) in Self::build_target_to_meters(topology) {
+            if let Some(target) = topology.devices.get(&target_id) {
+                for meter_id in meters {
+                    factors.entry(meter_id).or_insert(sign_convention.factor(&target.device_type));
+                }
+            }
+        }
+        for (device_id, device) in &topology.devices {
+            if device.device_type == DeviceType::Storage {
+                factors.insert(device_id.clone(), sign_convention.factor(&device.device_type));
+            }
+        }
+        factors
+    }
+
+    /// 拓扑中各开关的闭合状态（is_closed 缺省为闭合，兼容字符串 "true"/"false"），回写开关设备的 Modbus 线圈
+    pub(crate) fn switch_states(topology: &Topology) -> HashMap<String, bool> {
+        topology
+            .devices
+            .iter()
+            .filter(|(_, d)| d.device_type == DeviceType::Switch)
+            .map(|(id, d)| {
+                let closed = match d.properties.get("is_closed") {
+                    Some(serde_json::Value::Bool(b)) => *b,
+                    Some(serde_json::Value::String(s)) => s.trim().eq_ignore_ascii_case("true"),
+                    _ => true,
+                };
+                (id.clone(), closed)
+            })
+            .collect()
+    }
+
+    /// 从拓扑构建 目标设备 id -> 指向该设备的电表 id 列表（用于落库时把目标数据也写入电表）
+    pub(crate) fn build_target_to_meters(topology: &Topology) -> HashMap<String, Vec<String>> {
+        use crate::domain::topology::DeviceType;
+        let mut target_to_meters: HashMap<String, Vec<String>> = HashMap::new();
+        for conn in topology.connections.values() {
+            let from_id = &conn.from_device_id;
+            let to_id = &conn.to_device_id;
+            let from_is_meter = topology.devices.get(from_id).map(|d| d.device_type == DeviceType::Meter).unwrap_or(false);
+            let to_is_meter = topology.devices.get(to_id).map(|d| d.device_type == DeviceType::Meter).unwrap_or(false);
+            if from_is_meter {
+                target_to_meters.entry(to_id.clone()).or_default().push(from_id.clone());
+            }
+            if to_is_meter {
+                target_to_meters.entry(from_id.clone()).or_default().push(to_id.clone());
             }
         }
         target_to_meters
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn process_calculation_results_inline(
         app: &AppHandle,
         results: &serde_json::Value,
         topology: &Topology,
-        database: &Arc<StdMutex<Option<Database>>>,
+        index: &ResultIndex,
         last_device_power: &Arc<StdMutex<HashMap<String, (f64, Option<f64>, Option<f64>)>>>,
         storage_state: &Arc<StdMutex<HashMap<String, StorageState>>>,
         timestamp: f64,
         dt_seconds: f64,
+        dropped_meters: &std::collections::HashSet<String>,
+        meter_factors: &HashMap<String, f64>,
+        sign_convention: &SignConvention,
+        pipeline: &mut ResultsPipeline,
     ) {
-        let devices = &topology.devices;
-        let target_to_meters = Self::build_target_to_meters(topology);
+        let meters = MeterFanout::new(topology, index.target_to_meters(), dropped_meters, meter_factors);
         let dt_h = dt_seconds / 3600.0;
 
         // 处理计算结果并存储到数据库：功率设备、母线、线路、变压器与电表落库，供监控界面分析所有设备运行状态
         // 同时发送事件通知前端
         
-        // 建立设备ID到设备类型的映射，用于查找设备（保留以备将来使用）
-        let _device_id_to_type: HashMap<String, &str> = devices.iter()
-            .map(|(id, device)| (id.clone(), match device.device_type {
-                crate::domain::topology::DeviceType::Node => "Node",
-                crate::domain::topology::DeviceType::Line => "Line",
-                crate::domain::topology::DeviceType::Transformer => "Transformer",
-                crate::domain::topology::DeviceType::Load => "Load",
-                crate::domain::topology::DeviceType::Pv => "Pv",
-                crate::domain::topology::DeviceType::Storage => "Storage",
-                crate::domain::topology::DeviceType::Charger => "Charger",
-                crate::domain::topology::DeviceType::ExternalGrid => "ExternalGrid",
-                crate::domain::topology::DeviceType::Switch => "Switch",
-                crate::domain::topology::DeviceType::Meter => "Meter",
-            }))
-            .collect();
-        
         // 处理母线结果：res_bus 含 vm_pu、va_degree、p_mw、q_mvar，落库并通知前端
         if let Some(buses) = results.get("buses").and_then(|v| v.as_object()) {
             for (_bus_idx_str, bus_data) in buses {
@@ -597,49 +2203,27 @@ impl SimulationEngine {
                 let p_reactive_mvar = bus_data.get("q_mvar").and_then(|v| v.as_f64());
                 let p_reactive_kvar = p_reactive_mvar.map(|q| q * 1000.0);
                 if let Some(bus_name) = bus_data.get("name").and_then(|v| v.as_str()) {
-                    for (device_id, device) in devices {
-                        if device.device_type == crate::domain::topology::DeviceType::Node
-                            && device.name == bus_name
+                    if let Some((device_id, device)) = index.lookup(topology, "buses", bus_name) {
                         {
-                            if let Some(ref db) = *database.lock().unwrap() {
-                                let data_json = serde_json::to_string(bus_data).ok();
-                                let _ = db.insert_device_data(
-                                    device_id,
-                                    timestamp,
-                                    p_active_kw,
-                                    p_reactive_kvar,
-                                    data_json.as_deref(),
-                                    Some(device.device_type.as_str()),
-                                );
-                                for meter_id in target_to_meters.get(device_id).unwrap_or(&vec![]) {
-                                    let _ = db.insert_device_data(
-                                        meter_id,
-                                        timestamp,
-                                        p_active_kw,
-                                        p_reactive_kvar,
-                                        data_json.as_deref(),
-                                        devices.get(meter_id).map(|d| d.device_type.as_str()),
-                                    );
-                                }
-                            }
-                            let _ = app.emit("device-data-update", serde_json::json!({
-                                "device_id": device_id,
-                                "data": {
-                                    "active_power": p_active_kw,
-                                    "reactive_power": p_reactive_kvar,
-                                    "timestamp": timestamp,
-                                    "data_json": bus_data
-                                }
-                            }));
-                            if let Ok(mut cache) = last_device_power.lock() {
-                                cache.insert(device_id.clone(), (timestamp, p_active_kw, p_reactive_kvar));
-                                for meter_id in target_to_meters.get(device_id).unwrap_or(&vec![]) {
-                                    cache.insert(meter_id.clone(), (timestamp, p_active_kw, p_reactive_kvar));
-                                }
-                            }
-                            let _ = app.emit("bus-voltage-update", bus_data);
-                            break;
+                            // 落库按项目符号约定（电表行与其测量对象一致）
+                            let (p_active_kw, p_reactive_kvar) = sign_convention.apply(&device.device_type, p_active_kw, p_reactive_kvar);
+                            let data_json = serde_json::to_string(bus_data).ok();
+                            pipeline.record_row(
+                                device_id,
+                                timestamp,
+                                p_active_kw,
+                                p_reactive_kvar,
+                                data_json.as_deref(),
+                                Some(device.device_type.as_str()),
+                            );
+                            meters.record_rows(pipeline, device_id, timestamp, p_active_kw, p_reactive_kvar, data_json.as_deref());
+                        }
+                        pipeline.publish_typed(app, Some(device_id.as_str()), DeviceDataUpdate::new(device_id, p_active_kw, p_reactive_kvar, timestamp, bus_data));
+                        if let Ok(mut cache) = last_device_power.lock() {
+                            cache.insert(device_id.clone(), (timestamp, p_active_kw, p_reactive_kvar));
+                            meters.cache_readings(&mut cache, device_id, timestamp, p_active_kw, p_reactive_kvar);
                         }
+                        pipeline.publish(app, "bus-voltage-update", None, bus_data);
                     }
                 }
             }
@@ -653,51 +2237,29 @@ impl SimulationEngine {
                 let q_from_mvar = line_data.get("q_from_mvar").and_then(|v| v.as_f64());
                 let p_reactive_kvar = q_from_mvar.map(|q| q * 1000.0);
                 if let Some(line_name) = line_data.get("name").and_then(|v| v.as_str()) {
-                    for (device_id, device) in devices {
-                        if device.device_type == crate::domain::topology::DeviceType::Line
-                            && device.name == line_name
+                    if let Some((device_id, device)) = index.lookup(topology, "lines", line_name) {
                         {
-                            if let Some(ref db) = *database.lock().unwrap() {
-                                let data_json = serde_json::to_string(line_data).ok();
-                                let _ = db.insert_device_data(
-                                    device_id,
-                                    timestamp,
-                                    p_active_kw,
-                                    p_reactive_kvar,
-                                    data_json.as_deref(),
-                                    Some(device.device_type.as_str()),
-                                );
-                                for meter_id in target_to_meters.get(device_id).unwrap_or(&vec![]) {
-                                    let _ = db.insert_device_data(
-                                        meter_id,
-                                        timestamp,
-                                        p_active_kw,
-                                        p_reactive_kvar,
-                                        data_json.as_deref(),
-                                        devices.get(meter_id).map(|d| d.device_type.as_str()),
-                                    );
-                                }
-                            }
-                            let _ = app.emit("device-data-update", serde_json::json!({
-                                "device_id": device_id,
-                                "data": {
-                                    "active_power": p_active_kw,
-                                    "reactive_power": p_reactive_kvar,
-                                    "timestamp": timestamp,
-                                    "data_json": line_data
-                                }
-                            }));
-                            if let Ok(mut cache) = last_device_power.lock() {
-                                cache.insert(device_id.clone(), (timestamp, p_active_kw, p_reactive_kvar));
-                                for meter_id in target_to_meters.get(device_id).unwrap_or(&vec![]) {
-                                    cache.insert(meter_id.clone(), (timestamp, p_active_kw, p_reactive_kvar));
-                                }
-                            }
-                            break;
+                            // 落库按项目符号约定（电表行与其测量对象一致）
+                            let (p_active_kw, p_reactive_kvar) = sign_convention.apply(&device.device_type, p_active_kw, p_reactive_kvar);
+                            let data_json = serde_json::to_string(line_data).ok();
+                            pipeline.record_row(
+                                device_id,
+                                timestamp,
+                                p_active_kw,
+                                p_reactive_kvar,
+                                data_json.as_deref(),
+                                Some(device.device_type.as_str()),
+                            );
+                            meters.record_rows(pipeline, device_id, timestamp, p_active_kw, p_reactive_kvar, data_json.as_deref());
+                        }
+                        pipeline.publish_typed(app, Some(device_id.as_str()), DeviceDataUpdate::new(device_id, p_active_kw, p_reactive_kvar, timestamp, line_data));
+                        if let Ok(mut cache) = last_device_power.lock() {
+                            cache.insert(device_id.clone(), (timestamp, p_active_kw, p_reactive_kvar));
+                            meters.cache_readings(&mut cache, device_id, timestamp, p_active_kw, p_reactive_kvar);
                         }
                     }
                 }
-                let _ = app.emit("line-data-update", line_data);
+                pipeline.publish(app, "line-data-update", None, line_data);
             }
         }
 
@@ -709,51 +2271,29 @@ impl SimulationEngine {
                 let q_from_mvar = sw_data.get("q_from_mvar").and_then(|v| v.as_f64());
                 let p_reactive_kvar = q_from_mvar.map(|q| q * 1000.0);
                 if let Some(sw_name) = sw_data.get("name").and_then(|v| v.as_str()) {
-                    for (device_id, device) in devices {
-                        if device.device_type == crate::domain::topology::DeviceType::Switch
-                            && device.name == sw_name
+                    if let Some((device_id, device)) = index.lookup(topology, "switches", sw_name) {
                         {
-                            if let Some(ref db) = *database.lock().unwrap() {
-                                let data_json = serde_json::to_string(sw_data).ok();
-                                let _ = db.insert_device_data(
-                                    device_id,
-                                    timestamp,
-                                    p_active_kw,
-                                    p_reactive_kvar,
-                                    data_json.as_deref(),
-                                    Some(device.device_type.as_str()),
-                                );
-                                for meter_id in target_to_meters.get(device_id).unwrap_or(&vec![]) {
-                                    let _ = db.insert_device_data(
-                                        meter_id,
-                                        timestamp,
-                                        p_active_kw,
-                                        p_reactive_kvar,
-                                        data_json.as_deref(),
-                                        devices.get(meter_id).map(|d| d.device_type.as_str()),
-                                    );
-                                }
-                            }
-                            let _ = app.emit("device-data-update", serde_json::json!({
-                                "device_id": device_id,
-                                "data": {
-                                    "active_power": p_active_kw,
-                                    "reactive_power": p_reactive_kvar,
-                                    "timestamp": timestamp,
-                                    "data_json": sw_data
-                                }
-                            }));
-                            if let Ok(mut cache) = last_device_power.lock() {
-                                cache.insert(device_id.clone(), (timestamp, p_active_kw, p_reactive_kvar));
-                                for meter_id in target_to_meters.get(device_id).unwrap_or(&vec![]) {
-                                    cache.insert(meter_id.clone(), (timestamp, p_active_kw, p_reactive_kvar));
-                                }
-                            }
-                            break;
+                            // 落库按项目符号约定（电表行与其测量对象一致）
+                            let (p_active_kw, p_reactive_kvar) = sign_convention.apply(&device.device_type, p_active_kw, p_reactive_kvar);
+                            let data_json = serde_json::to_string(sw_data).ok();
+                            pipeline.record_row(
+                                device_id,
+                                timestamp,
+                                p_active_kw,
+                                p_reactive_kvar,
+                                data_json.as_deref(),
+                                Some(device.device_type.as_str()),
+                            );
+                            meters.record_rows(pipeline, device_id, timestamp, p_active_kw, p_reactive_kvar, data_json.as_deref());
+                        }
+                        pipeline.publish_typed(app, Some(device_id.as_str()), DeviceDataUpdate::new(device_id, p_active_kw, p_reactive_kvar, timestamp, sw_data));
+                        if let Ok(mut cache) = last_device_power.lock() {
+                            cache.insert(device_id.clone(), (timestamp, p_active_kw, p_reactive_kvar));
+                            meters.cache_readings(&mut cache, device_id, timestamp, p_active_kw, p_reactive_kvar);
                         }
                     }
                 }
-                let _ = app.emit("switch-data-update", sw_data);
+                pipeline.publish(app, "switch-data-update", None, sw_data);
             }
         }
 
@@ -769,54 +2309,31 @@ impl SimulationEngine {
                 
                 // 尝试找到对应的 Load/Charger 设备（Python 端 Charger 也建为 load；仅功率设备落库；电表落库其指向节点的数据）
                 if let Some(load_name) = load_data.get("name").and_then(|v| v.as_str()) {
-                    for (device_id, device) in devices {
-                        if (device.device_type == crate::domain::topology::DeviceType::Load
-                            || device.device_type == crate::domain::topology::DeviceType::Charger)
-                            && device.name == load_name
+                    if let Some((device_id, device)) = index.lookup(topology, "loads", load_name) {
                         {
-                            if let Some(ref db) = *database.lock().unwrap() {
-                                let data_json = serde_json::to_string(load_data).ok();
-                                let _ = db.insert_device_data(
-                                    device_id,
-                                    timestamp,
-                                    p_active_kw,
-                                    p_reactive_kvar,
-                                    data_json.as_deref(),
-                                    Some(device.device_type.as_str()),
-                                );
-                                for meter_id in target_to_meters.get(device_id).unwrap_or(&vec![]) {
-                                    let _ = db.insert_device_data(
-                                        meter_id,
-                                        timestamp,
-                                        p_active_kw,
-                                        p_reactive_kvar,
-                                        data_json.as_deref(),
-                                        devices.get(meter_id).map(|d| d.device_type.as_str()),
-                                    );
-                                }
-                            }
-                            let _ = app.emit("device-data-update", serde_json::json!({
-                                "device_id": device_id,
-                                "data": {
-                                    "active_power": p_active_kw,
-                                    "reactive_power": p_reactive_kvar,
-                                    "timestamp": timestamp,
-                                    "data_json": load_data
-                                }
-                            }));
-                            if let Ok(mut cache) = last_device_power.lock() {
-                                cache.insert(device_id.clone(), (timestamp, p_active_kw, p_reactive_kvar));
-                                for meter_id in target_to_meters.get(device_id).unwrap_or(&vec![]) {
-                                    cache.insert(meter_id.clone(), (timestamp, p_active_kw, p_reactive_kvar));
-                                }
-                            }
-                            break;
+                            // 落库按项目符号约定（电表行与其测量对象一致）
+                            let (p_active_kw, p_reactive_kvar) = sign_convention.apply(&device.device_type, p_active_kw, p_reactive_kvar);
+                            let data_json = serde_json::to_string(load_data).ok();
+                            pipeline.record_row(
+                                device_id,
+                                timestamp,
+                                p_active_kw,
+                                p_reactive_kvar,
+                                data_json.as_deref(),
+                                Some(device.device_type.as_str()),
+                            );
+                            meters.record_rows(pipeline, device_id, timestamp, p_active_kw, p_reactive_kvar, data_json.as_deref());
+                        }
+                        pipeline.publish_typed(app, Some(device_id.as_str()), DeviceDataUpdate::new(device_id, p_active_kw, p_reactive_kvar, timestamp, load_data));
+                        if let Ok(mut cache) = last_device_power.lock() {
+                            cache.insert(device_id.clone(), (timestamp, p_active_kw, p_reactive_kvar));
+                            meters.cache_readings(&mut cache, device_id, timestamp, p_active_kw, p_reactive_kvar);
                         }
                     }
                 }
                 
                 if let Some(p_kw) = p_active_kw {
-                    let _ = app.emit("load-power-update", serde_json::json!({
+                    pipeline.publish(app, "load-power-update", None, serde_json::json!({
                         "p_active_kw": p_kw,
                         "p_reactive_kvar": p_reactive_kvar,
                         "data": load_data
@@ -837,52 +2354,31 @@ impl SimulationEngine {
                 
                 // 尝试找到对应的Pv设备（功率设备落库；电表落库其指向节点的数据）
                 if let Some(gen_name) = gen_data.get("name").and_then(|v| v.as_str()) {
-                    for (device_id, device) in devices {
-                        if device.device_type == crate::domain::topology::DeviceType::Pv 
-                            && device.name == gen_name {
-                            if let Some(ref db) = *database.lock().unwrap() {
-                                let data_json = serde_json::to_string(gen_data).ok();
-                                let _ = db.insert_device_data(
-                                    device_id,
-                                    timestamp,
-                                    p_active_kw,
-                                    p_reactive_kvar,
-                                    data_json.as_deref(),
-                                    Some(device.device_type.as_str()),
-                                );
-                                for meter_id in target_to_meters.get(device_id).unwrap_or(&vec![]) {
-                                    let _ = db.insert_device_data(
-                                        meter_id,
-                                        timestamp,
-                                        p_active_kw,
-                                        p_reactive_kvar,
-                                        data_json.as_deref(),
-                                        devices.get(meter_id).map(|d| d.device_type.as_str()),
-                                    );
-                                }
-                            }
-                            let _ = app.emit("device-data-update", serde_json::json!({
-                                "device_id": device_id,
-                                "data": {
-                                    "active_power": p_active_kw,
-                                    "reactive_power": p_reactive_kvar,
-                                    "timestamp": timestamp,
-                                    "data_json": gen_data
-                                }
-                            }));
-                            if let Ok(mut cache) = last_device_power.lock() {
-                                cache.insert(device_id.clone(), (timestamp, p_active_kw, p_reactive_kvar));
-                                for meter_id in target_to_meters.get(device_id).unwrap_or(&vec![]) {
-                                    cache.insert(meter_id.clone(), (timestamp, p_active_kw, p_reactive_kvar));
-                                }
-                            }
-                            break;
+                    if let Some((device_id, device)) = index.lookup(topology, "generators", gen_name) {
+                        {
+                            // 落库按项目符号约定（电表行与其测量对象一致）
+                            let (p_active_kw, p_reactive_kvar) = sign_convention.apply(&device.device_type, p_active_kw, p_reactive_kvar);
+                            let data_json = serde_json::to_string(gen_data).ok();
+                            pipeline.record_row(
+                                device_id,
+                                timestamp,
+                                p_active_kw,
+                                p_reactive_kvar,
+                                data_json.as_deref(),
+                                Some(device.device_type.as_str()),
+                            );
+                            meters.record_rows(pipeline, device_id, timestamp, p_active_kw, p_reactive_kvar, data_json.as_deref());
+                        }
+                        pipeline.publish_typed(app, Some(device_id.as_str()), DeviceDataUpdate::new(device_id, p_active_kw, p_reactive_kvar, timestamp, gen_data));
+                        if let Ok(mut cache) = last_device_power.lock() {
+                            cache.insert(device_id.clone(), (timestamp, p_active_kw, p_reactive_kvar));
+                            meters.cache_readings(&mut cache, device_id, timestamp, p_active_kw, p_reactive_kvar);
                         }
                     }
                 }
                 
                 if let Some(p_kw) = p_active_kw {
-                    let _ = app.emit("generator-power-update", serde_json::json!({
+                    pipeline.publish(app, "generator-power-update", None, serde_json::json!({
                         "p_active_kw": p_kw,
                         "p_reactive_kvar": p_reactive_kvar,
                         "data": gen_data
@@ -903,94 +2399,31 @@ impl SimulationEngine {
                 
                 // 尝试找到对应的Storage设备（功率设备落库；电表落库其指向节点的数据）
                 if let Some(storage_name) = storage_data.get("name").and_then(|v| v.as_str()) {
-                    for (device_id, device) in devices {
-                        if device.device_type == crate::domain::topology::DeviceType::Storage 
-                            && device.name == storage_name {
-                            let p_kw = p_active_kw.unwrap_or(0.0);
-                            // 容量：支持 capacity / capacity_kwh（设备详情用 capacity_kwh）；max_e_mwh 单位 MWh -> kWh
-                            let capacity_kwh: f64 = device
-                                .properties
-                                .get("capacity_kwh")
-                                .or_else(|| device.properties.get("capacity"))
-                                .and_then(|v| v.as_f64().or_else(|| v.as_str().and_then(|s| s.parse::<f64>().ok())))
-                                .or_else(|| {
-                                    device.properties.get("max_e_mwh")
-                                        .and_then(|v| v.as_f64().or_else(|| v.as_str().and_then(|s| s.parse::<f64>().ok())))
-                                        .map(|v| v * 1000.0)
-                                })
-                                .unwrap_or(1000.0);
-                            // 初始 SOC：设备详情修改并保存后从 properties.initial_soc 读取（0–100），默认 50
-                            let initial_soc: f64 = device
-                                .properties
-                                .get("initial_soc")
-                                .and_then(|v| v.as_f64().or_else(|| v.as_str().and_then(|s| s.parse::<f64>().ok())))
-                                .map(|v| v.clamp(0.0, 100.0))
-                                .unwrap_or(50.0);
-                            if capacity_kwh > 0.0 {
-                                let mut state_map = storage_state.lock().unwrap();
-                                let state = state_map.entry(device_id.clone()).or_insert_with(|| StorageState {
-                                    capacity_kwh,
-                                    energy_kwh: capacity_kwh * (initial_soc / 100.0),
-                                    soc_percent: initial_soc,
-                                    ..Default::default()
-                                });
-                                if (state.capacity_kwh - capacity_kwh).abs() > 1e-6 {
-                                    state.capacity_kwh = capacity_kwh;
-                                }
-                                // pandapower 约定：p_kw 正=充电(能量流入)，负=放电(能量流出)；能量增量 = p_kw * dt_h
-                                state.energy_kwh += p_kw * dt_h;
-                                state.energy_kwh = state.energy_kwh.clamp(0.0, state.capacity_kwh);
-                                state.soc_percent = (state.energy_kwh / state.capacity_kwh * 100.0).clamp(0.0, 100.0);
-                                if p_kw > 0.0 {
-                                    state.daily_charge_kwh += p_kw * dt_h;
-                                    state.total_charge_kwh += p_kw * dt_h;
-                                } else if p_kw < 0.0 {
-                                    state.daily_discharge_kwh += -p_kw * dt_h;
-                                    state.total_discharge_kwh += -p_kw * dt_h;
-                                }
-                            }
-                            if let Some(ref db) = *database.lock().unwrap() {
-                                let data_json = serde_json::to_string(storage_data).ok();
-                                let _ = db.insert_device_data(
-                                    device_id,
-                                    timestamp,
-                                    p_active_kw,
-                                    p_reactive_kvar,
-                                    data_json.as_deref(),
-                                    Some(device.device_type.as_str()),
-                                );
-                                for meter_id in target_to_meters.get(device_id).unwrap_or(&vec![]) {
-                                    let _ = db.insert_device_data(
-                                        meter_id,
-                                        timestamp,
-                                        p_active_kw,
-                                        p_reactive_kvar,
-                                        data_json.as_deref(),
-                                        devices.get(meter_id).map(|d| d.device_type.as_str()),
-                                    );
-                                }
-                            }
-                            let _ = app.emit("device-data-update", serde_json::json!({
-                                "device_id": device_id,
-                                "data": {
-                                    "active_power": p_active_kw,
-                                    "reactive_power": p_reactive_kvar,
-                                    "timestamp": timestamp,
-                                    "data_json": storage_data
-                                }
-                            }));
-                            if let Ok(mut cache) = last_device_power.lock() {
-                                cache.insert(device_id.clone(), (timestamp, p_active_kw, p_reactive_kvar));
-                                for meter_id in target_to_meters.get(device_id).unwrap_or(&vec![]) {
-                                    cache.insert(meter_id.clone(), (timestamp, p_active_kw, p_reactive_kvar));
-                                }
-                            }
-                            break;
+                    if let Some((device_id, device)) = index.lookup(topology, "storages", storage_name) {
+                        Self::advance_storage_state(&mut storage_state.lock().unwrap(), device_id, device, p_active_kw.unwrap_or(0.0), dt_h);
+                        {
+                            // 落库按项目符号约定（电表行与其测量对象一致）
+                            let (p_active_kw, p_reactive_kvar) = sign_convention.apply(&device.device_type, p_active_kw, p_reactive_kvar);
+                            let data_json = serde_json::to_string(storage_data).ok();
+                            pipeline.record_row(
+                                device_id,
+                                timestamp,
+                                p_active_kw,
+                                p_reactive_kvar,
+                                data_json.as_deref(),
+                                Some(device.device_type.as_str()),
+                            );
+                            meters.record_rows(pipeline, device_id, timestamp, p_active_kw, p_reactive_kvar, data_json.as_deref());
+                        }
+                        pipeline.publish_typed(app, Some(device_id.as_str()), DeviceDataUpdate::new(device_id, p_active_kw, p_reactive_kvar, timestamp, storage_data));
+                        if let Ok(mut cache) = last_device_power.lock() {
+                            cache.insert(device_id.clone(), (timestamp, p_active_kw, p_reactive_kvar));
+                            meters.cache_readings(&mut cache, device_id, timestamp, p_active_kw, p_reactive_kvar);
                         }
                     }
                 }
                 
-                let _ = app.emit("storage-data-update", storage_data);
+                pipeline.publish(app, "storage-data-update", None, storage_data);
             }
         }
 
@@ -1002,47 +2435,25 @@ impl SimulationEngine {
                 let p_reactive_mvar = ext_data.get("q_mvar").and_then(|v| v.as_f64());
                 let p_reactive_kvar = p_reactive_mvar.map(|q| q * 1000.0);
                 if let Some(ext_name) = ext_data.get("name").and_then(|v| v.as_str()) {
-                    for (device_id, device) in devices {
-                        if device.device_type == crate::domain::topology::DeviceType::ExternalGrid
-                            && device.name == ext_name
+                    if let Some((device_id, device)) = index.lookup(topology, "ext_grids", ext_name) {
                         {
-                            if let Some(ref db) = *database.lock().unwrap() {
-                                let data_json = serde_json::to_string(ext_data).ok();
-                                let _ = db.insert_device_data(
-                                    device_id,
-                                    timestamp,
-                                    p_active_kw,
-                                    p_reactive_kvar,
-                                    data_json.as_deref(),
-                                    Some(device.device_type.as_str()),
-                                );
-                                for meter_id in target_to_meters.get(device_id).unwrap_or(&vec![]) {
-                                    let _ = db.insert_device_data(
-                                        meter_id,
-                                        timestamp,
-                                        p_active_kw,
-                                        p_reactive_kvar,
-                                        data_json.as_deref(),
-                                        devices.get(meter_id).map(|d| d.device_type.as_str()),
-                                    );
-                                }
-                            }
-                            let _ = app.emit("device-data-update", serde_json::json!({
-                                "device_id": device_id,
-                                "data": {
-                                    "active_power": p_active_kw,
-                                    "reactive_power": p_reactive_kvar,
-                                    "timestamp": timestamp,
-                                    "data_json": ext_data
-                                }
-                            }));
-                            if let Ok(mut cache) = last_device_power.lock() {
-                                cache.insert(device_id.clone(), (timestamp, p_active_kw, p_reactive_kvar));
-                                for meter_id in target_to_meters.get(device_id).unwrap_or(&vec![]) {
-                                    cache.insert(meter_id.clone(), (timestamp, p_active_kw, p_reactive_kvar));
-                                }
-                            }
-                            break;
+                            // 落库按项目符号约定（电表行与其测量对象一致）
+                            let (p_active_kw, p_reactive_kvar) = sign_convention.apply(&device.device_type, p_active_kw, p_reactive_kvar);
+                            let data_json = serde_json::to_string(ext_data).ok();
+                            pipeline.record_row(
+                                device_id,
+                                timestamp,
+                                p_active_kw,
+                                p_reactive_kvar,
+                                data_json.as_deref(),
+                                Some(device.device_type.as_str()),
+                            );
+                            meters.record_rows(pipeline, device_id, timestamp, p_active_kw, p_reactive_kvar, data_json.as_deref());
+                        }
+                        pipeline.publish_typed(app, Some(device_id.as_str()), DeviceDataUpdate::new(device_id, p_active_kw, p_reactive_kvar, timestamp, ext_data));
+                        if let Ok(mut cache) = last_device_power.lock() {
+                            cache.insert(device_id.clone(), (timestamp, p_active_kw, p_reactive_kvar));
+                            meters.cache_readings(&mut cache, device_id, timestamp, p_active_kw, p_reactive_kvar);
                         }
                     }
                 }
@@ -1057,51 +2468,29 @@ impl SimulationEngine {
                 let q_hv_mvar = trafo_data.get("q_hv_mvar").and_then(|v| v.as_f64());
                 let p_reactive_kvar = q_hv_mvar.map(|q| q * 1000.0);
                 if let Some(trafo_name) = trafo_data.get("name").and_then(|v| v.as_str()) {
-                    for (device_id, device) in devices {
-                        if device.device_type == crate::domain::topology::DeviceType::Transformer
-                            && device.name == trafo_name
+                    if let Some((device_id, device)) = index.lookup(topology, "transformers", trafo_name) {
                         {
-                            if let Some(ref db) = *database.lock().unwrap() {
-                                let data_json = serde_json::to_string(trafo_data).ok();
-                                let _ = db.insert_device_data(
-                                    device_id,
-                                    timestamp,
-                                    p_active_kw,
-                                    p_reactive_kvar,
-                                    data_json.as_deref(),
-                                    Some(device.device_type.as_str()),
-                                );
-                                for meter_id in target_to_meters.get(device_id).unwrap_or(&vec![]) {
-                                    let _ = db.insert_device_data(
-                                        meter_id,
-                                        timestamp,
-                                        p_active_kw,
-                                        p_reactive_kvar,
-                                        data_json.as_deref(),
-                                        devices.get(meter_id).map(|d| d.device_type.as_str()),
-                                    );
-                                }
-                            }
-                            let _ = app.emit("device-data-update", serde_json::json!({
-                                "device_id": device_id,
-                                "data": {
-                                    "active_power": p_active_kw,
-                                    "reactive_power": p_reactive_kvar,
-                                    "timestamp": timestamp,
-                                    "data_json": trafo_data
-                                }
-                            }));
-                            if let Ok(mut cache) = last_device_power.lock() {
-                                cache.insert(device_id.clone(), (timestamp, p_active_kw, p_reactive_kvar));
-                                for meter_id in target_to_meters.get(device_id).unwrap_or(&vec![]) {
-                                    cache.insert(meter_id.clone(), (timestamp, p_active_kw, p_reactive_kvar));
-                                }
-                            }
-                            break;
+                            // 落库按项目符号约定（电表行与其测量对象一致）
+                            let (p_active_kw, p_reactive_kvar) = sign_convention.apply(&device.device_type, p_active_kw, p_reactive_kvar);
+                            let data_json = serde_json::to_string(trafo_data).ok();
+                            pipeline.record_row(
+                                device_id,
+                                timestamp,
+                                p_active_kw,
+                                p_reactive_kvar,
+                                data_json.as_deref(),
+                                Some(device.device_type.as_str()),
+                            );
+                            meters.record_rows(pipeline, device_id, timestamp, p_active_kw, p_reactive_kvar, data_json.as_deref());
+                        }
+                        pipeline.publish_typed(app, Some(device_id.as_str()), DeviceDataUpdate::new(device_id, p_active_kw, p_reactive_kvar, timestamp, trafo_data));
+                        if let Ok(mut cache) = last_device_power.lock() {
+                            cache.insert(device_id.clone(), (timestamp, p_active_kw, p_reactive_kvar));
+                            meters.cache_readings(&mut cache, device_id, timestamp, p_active_kw, p_reactive_kvar);
                         }
                     }
                 }
-                let _ = app.emit("transformer-data-update", trafo_data);
+                pipeline.publish(app, "transformer-data-update", None, trafo_data);
             }
         }
     }
@@ -1122,7 +2511,7 @@ impl SimulationEngine {
                     let _ = bus_idx_str; // 保留变量名用于调试
                 if let Some(_voltage_pu) = bus_data.get("vm_pu").and_then(|v| v.as_f64()) {
                     // 发送电压数据更新事件
-                    let _ = app.emit("bus-voltage-update", bus_data);
+                    window_hub::publish(app, "bus-voltage-update", None, bus_data);
                 }
             }
         }
@@ -1131,7 +2520,7 @@ impl SimulationEngine {
         if let Some(lines) = results.get("lines").and_then(|v| v.as_object()) {
             for (_line_idx_str, line_data) in lines {
                 // 发送线路数据更新事件
-                let _ = app.emit("line-data-update", line_data);
+                window_hub::publish(app, "line-data-update", None, line_data);
             }
         }
         
@@ -1141,7 +2530,7 @@ impl SimulationEngine {
                 if let Some(p_mw) = load_data.get("p_mw").and_then(|v| v.as_f64()) {
                     let power_kw = p_mw * 1000.0;
                     // 发送负载功率更新事件
-                    let _ = app.emit("load-power-update", serde_json::json!({
+                    window_hub::publish(app, "load-power-update", None, serde_json::json!({
                         "power_kw": power_kw,
                         "data": load_data
                     }));
@@ -1155,7 +2544,7 @@ impl SimulationEngine {
                 if let Some(p_mw) = gen_data.get("p_mw").and_then(|v| v.as_f64()) {
                     let power_kw = p_mw * 1000.0;
                     // 发送发电机功率更新事件
-                    let _ = app.emit("generator-power-update", serde_json::json!({
+                    window_hub::publish(app, "generator-power-update", None, serde_json::json!({
                         "power_kw": power_kw,
                         "data": gen_data
                     }));
@@ -1167,12 +2556,25 @@ impl SimulationEngine {
         if let Some(storages) = results.get("storages").and_then(|v| v.as_object()) {
             for (_storage_idx_str, storage_data) in storages {
                 // 发送储能数据更新事件
-                let _ = app.emit("storage-data-update", storage_data);
+                window_hub::publish(app, "storage-data-update", None, storage_data);
             }
         }
     }
 
+    /// 等待异步落库任务写完已送入的设备数据（停止、删除实例或移动仿真库前调用）
+    pub async fn flush_device_data(&self) {
+        let writer = self.db_writer.lock().unwrap().clone();
+        if let Some(writer) = writer {
+            writer.flush().await;
+        }
+    }
+
     pub async fn stop(&self) -> Result<(), String> {
+        self.stop_with_reason("stop").await
+    }
+
+    /// 停止仿真；reason 记入运行摘要（stop 手动停止，max_steps / max_duration 达到自动停止条件）
+    pub async fn stop_with_reason(&self, reason: &str) -> Result<(), String> {
         let mut status = self.status.lock().await;
         status.stop();
         drop(status);
@@ -1180,10 +2582,40 @@ impl SimulationEngine {
         if let Some(tx) = self.cancel_tx.lock().await.take() {
             let _ = tx.send(()).await;
         }
+        self.flush_device_data().await;
+        // 正常停止，不再作为中断的仿真提示恢复
+        if let Some(ref db) = *self.database.lock().unwrap() {
+            run_recovery::set_run_status(db, RunStatus::Completed);
+            self.forecast_accuracy.lock().unwrap().persist(db);
+            // 仍接入的充电会话按中断结束落库
+            for record in self.ev_sessions.lock().unwrap().finish_all(self.sim_time()) {
+                if let Err(e) = db.insert_ev_session(&record) {
+                    eprintln!("写入充电会话失败 {}: {}", record.session_id, e);
+                }
+            }
+        }
+        // 运行摘要：落库并推送前端（循环已因错误自动停止时摘要已生成）
+        let summary = {
+            let topology = self.topology.lock().await;
+            let index = self.result_index.lock().unwrap().clone();
+            Self::finish_run_summary(&self.run_stats, reason, topology.as_ref(), &index, &self.device_energy, &self.storage_schedules, &self.database)
+        };
+        if let (Some(summary), Some(app)) = (summary, self.run_app.lock().unwrap().take()) {
+            window_hub::publish_typed(&app, None, SimulationSummary::new(summary));
+        }
         // 仿真已停止，设备数据通道关闭，全部视为离线；清空功率缓存与储能状态
         self.device_active_status.lock().await.clear();
         self.last_device_power.lock().unwrap().clear();
         self.storage_state.lock().unwrap().clear();
+        self.random_generators.lock().unwrap().clear();
+        // 内核停止时恢复全部故障
+        {
+            let now = self.sim_time();
+            let mut injector = self.fault_injector.lock().unwrap();
+            for fault in injector.take_all() {
+                injector.finish(fault, "stop", now);
+            }
+        }
         
         // 停止时清空错误列表（防止旧错误持久显示）
         {
@@ -1192,9 +2624,10 @@ impl SimulationEngine {
         }
         
         // 通过 Python 桥接停止仿真
-        let mut bridge = self.python_bridge.lock().await;
+        let bridge = &self.kernel;
         let params = serde_json::json!({
-            "action": "stop"
+            "action": "stop",
+            "keep_warm": self.keep_kernel_warm.load(Ordering::Relaxed)
         });
         bridge.call("simulation.stop", params).await
             .map_err(|e| format!("Failed to stop simulation: {}", e))?;
@@ -1202,11 +2635,29 @@ impl SimulationEngine {
         Ok(())
     }
 
+    /// 结束本次运行统计：生成运行摘要并写入仿真库；本轮摘要已生成时返回 None
+    fn finish_run_summary(
+        run_stats: &StdMutex<RunStatsCollector>,
+        reason: &str,
+        topology: Option<&Topology>,
+        index: &ResultIndex,
+        device_energy: &StdMutex<HashMap<String, DeviceEnergyCounters>>,
+        storage_schedules: &StdMutex<StorageScheduleExecutor>,
+        database: &StdMutex<Option<Database>>,
+    ) -> Option<RunSummary> {
+        let adherence = storage_schedules.lock().unwrap().adherence();
+        let summary = run_stats.lock().unwrap().finish(reason, topology, index, &device_energy.lock().unwrap(), adherence)?;
+        if let Some(ref db) = *database.lock().unwrap() {
+            run_summary::record_summary(db, &summary);
+        }
+        Some(summary)
+    }
+
     pub async fn pause(&self) -> Result<(), String> {
         let mut status = self.status.lock().await;
         status.pause();
         
-        let mut bridge = self.python_bridge.lock().await;
+        let bridge = &self.kernel;
         let params = serde_json::json!({
             "action": "pause"
         });
@@ -1216,11 +2667,38 @@ impl SimulationEngine {
         Ok(())
     }
 
+    /// 单步调试：暂停中执行恰好一步计算（含结果处理、落库与事件），完成后保持暂停；返回该步的步序号
+    pub async fn step_once(&self) -> Result<u64, String> {
+        if self.status.lock().await.state != SimulationState::Paused {
+            return Err("仅在仿真暂停时可单步执行".to_string());
+        }
+        if !self.calculation_loop_started.load(Ordering::SeqCst) {
+            return Err("计算循环未运行".to_string());
+        }
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        {
+            let mut pending = self.single_step.lock().unwrap();
+            if pending.is_some() {
+                return Err("上一次单步尚未完成".to_string());
+            }
+            *pending = Some(tx);
+        }
+        self.step_wakeup.notify_one();
+        match tokio::time::timeout(Duration::from_secs(60), rx).await {
+            Ok(Ok(step)) => Ok(step),
+            Ok(Err(_)) => Err("单步执行未完成（计算失败或仿真已停止）".to_string()),
+            Err(_) => {
+                self.single_step.lock().unwrap().take();
+                Err("单步执行超时".to_string())
+            }
+        }
+    }
+
     pub async fn resume(&self) -> Result<(), String> {
         let mut status = self.status.lock().await;
         status.resume();
         
-        let mut bridge = self.python_bridge.lock().await;
+        let bridge = &self.kernel;
         let params = serde_json::json!({
             "action": "resume"
         });
@@ -1246,83 +2724,456 @@ impl SimulationEngine {
     }
 
     /// 储能状态：SOC、日充电量、日放电量、累计充电/放电总量（Rust 独立维护）
+    /// 全部设备的当前功率缓存：device_id -> (timestamp, p_active_kw, p_reactive_kvar)
+    pub fn get_all_last_device_power(&self) -> HashMap<String, (f64, Option<f64>, Option<f64>)> {
+        self.last_device_power.lock().unwrap().clone()
+    }
+
     pub fn get_storage_state(&self, device_id: &str) -> Option<StorageState> {
         let m = self.storage_state.lock().unwrap();
         m.get(device_id).cloned()
     }
 
+    pub fn get_device_energy(&self, device_id: &str) -> Option<DeviceEnergyCounters> {
+        self.device_energy.lock().unwrap().get(device_id).cloned()
+    }
+
+    pub fn get_all_device_energy(&self) -> HashMap<String, DeviceEnergyCounters> {
+        self.device_energy.lock().unwrap().clone()
+    }
+
+    /// 运行中清零引擎维护的累计量（储能日/累计电量、设备电量、KPI），不影响 SOC 与仿真状态；
+    /// device_ids 为 None 表示全部设备，返回被清零的设备。电表/光伏电量在 Modbus 寄存器中积分，由 ModbusService 清零
+    pub fn reset_counters(&self, group: CounterGroup, device_ids: Option<&HashSet<String>>) -> Vec<String> {
+        let selected = |id: &String| device_ids.is_none_or(|ids| ids.contains(id));
+        let mut cleared: Vec<String> = match group {
+            CounterGroup::StorageDaily | CounterGroup::StorageTotal => {
+                let mut states = self.storage_state.lock().unwrap();
+                let mut cleared = Vec::new();
+                for (id, s) in states.iter_mut().filter(|(id, _)| selected(id)) {
+                    if group == CounterGroup::StorageDaily {
+                        s.daily_charge_kwh = 0.0;
+                        s.daily_discharge_kwh = 0.0;
+                    } else {
+                        s.total_charge_kwh = 0.0;
+                        s.total_discharge_kwh = 0.0;
+                    }
+                    cleared.push(id.clone());
+                }
+                cleared
+            }
+            CounterGroup::DeviceEnergy => {
+                let mut counters = self.device_energy.lock().unwrap();
+                let cleared: Vec<String> = counters.keys().filter(|id| selected(id)).cloned().collect();
+                for id in &cleared {
+                    counters.remove(id);
+                }
+                cleared
+            }
+            CounterGroup::KpiWindows => {
+                let mut cleared = self.limit_monitor.lock().unwrap().reset_kpis(device_ids);
+                cleared.extend(self.setpoint_limiter.lock().unwrap().reset_counts(device_ids));
+                if device_ids.is_none() {
+                    self.forecast_accuracy.lock().unwrap().reset();
+                }
+                cleared
+            }
+            CounterGroup::MeterEnergy | CounterGroup::PvDailyGeneration | CounterGroup::PvTotalGeneration => Vec::new(),
+        };
+        cleared.sort();
+        cleared.dedup();
+        cleared
+    }
+
     /// 所有储能设备状态快照（供 Modbus 同步写 IR）
     pub fn get_all_storage_states(&self) -> HashMap<String, StorageState> {
         let m = self.storage_state.lock().unwrap();
         m.clone()
     }
 
-    pub async fn set_device_mode(&self, device_id: String, mode: String) -> Result<(), String> {
-        // 验证模式
-        let valid_modes = ["random_data", "manual", "remote", "historical_data"];
-        if !valid_modes.contains(&mode.as_str()) {
-            return Err(format!("Invalid mode: {}", mode));
+    /// 设置设备软限值带（空表示恢复默认/属性配置）
+    pub fn set_device_limit_bands(&self, device_id: String, bands: HashMap<String, LimitBand>) {
+        self.limit_monitor.lock().unwrap().set_device_bands(device_id, bands);
+    }
+
+    /// 获取设备当前生效的软限值带
+    pub async fn get_device_limit_bands(&self, device_id: &str) -> HashMap<String, LimitBand> {
+        let topo = self.topology.lock().await;
+        match topo.as_ref() {
+            Some(t) => self.limit_monitor.lock().unwrap().effective_bands(device_id, t),
+            None => HashMap::new(),
         }
+    }
 
-        // 更新设备模式
-        self.device_modes.lock().await.insert(device_id.clone(), mode.clone().into());
-        
-        // 通知 Python 内核
-        let mut bridge = self.python_bridge.lock().await;
-        let params = serde_json::json!({
-            "device_id": device_id,
-            "mode": mode
-        });
-        bridge.call("simulation.set_device_mode", params).await
-            .map_err(|e| format!("Failed to set device mode: {}", e))?;
-        
-        Ok(())
+    /// 登记或更新条件断点（已加载拓扑时校验设备与量），返回生效的断点
+    pub async fn set_breakpoint(&self, breakpoint: Breakpoint) -> Result<Breakpoint, String> {
+        breakpoint.validate(self.topology.lock().await.as_ref())?;
+        Ok(self.breakpoints.lock().unwrap().upsert(breakpoint))
     }
 
-    pub async fn set_device_random_config(
-        &self,
-        device_id: String,
-        min_power: f64,
-        max_power: f64,
-    ) -> Result<(), String> {
-        let mut bridge = self.python_bridge.lock().await;
-        let params = serde_json::json!({
-            "device_id": device_id,
-            "min_power": min_power,
-            "max_power": max_power
-        });
-        bridge
-            .call("simulation.set_device_random_config", params)
-            .await
-            .map_err(|e| format!("设置设备随机配置失败: {}", e))?;
-        Ok(())
+    pub fn remove_breakpoint(&self, id: &str) -> bool {
+        self.breakpoints.lock().unwrap().remove(id)
     }
 
-    pub async fn set_device_manual_setpoint(
-        &self,
-        device_id: String,
-        active_power: f64,
-        reactive_power: f64,
-    ) -> Result<(), String> {
-        let mut bridge = self.python_bridge.lock().await;
-        let params = serde_json::json!({
-            "device_id": device_id,
-            "active_power": active_power,
-            "reactive_power": reactive_power
-        });
-        bridge
-            .call("simulation.set_device_manual_setpoint", params)
-            .await
-            .map_err(|e| format!("设置设备手动设定失败: {}", e))?;
-        Ok(())
+    pub fn clear_breakpoints(&self) {
+        self.breakpoints.lock().unwrap().clear();
     }
 
+    pub fn list_breakpoints(&self) -> Vec<Breakpoint> {
+        self.breakpoints.lock().unwrap().list()
+    }
+
+    pub fn set_storage_schedule(&self, schedule: StorageSchedule) {
+        self.storage_schedules.lock().unwrap().set_schedule(schedule);
+    }
+
+    pub fn remove_storage_schedule(&self, device_id: &str) -> bool {
+        self.storage_schedules.lock().unwrap().remove_schedule(device_id)
+    }
+
+    pub fn get_storage_schedule(&self, device_id: &str) -> Option<StorageSchedule> {
+        self.storage_schedules.lock().unwrap().get_schedule(device_id)
+    }
+
+    pub fn get_storage_schedule_adherence(&self) -> Vec<ScheduleAdherence> {
+        self.storage_schedules.lock().unwrap().adherence()
+    }
+
+    /// 启用或更换 EMS 策略（已加载拓扑时校验受控储能），下一步起生效
+    pub async fn set_ems_config(&self, config: EmsConfig) -> Result<EmsStatus, String> {
+        config.validate(self.topology.lock().await.as_ref())?;
+        let mut ems = self.ems.lock().unwrap();
+        ems.configure(config);
+        Ok(ems.status())
+    }
+
+    /// 关闭 EMS：仿真运行中时将此前受控的储能设定恢复为 0
+    pub async fn disable_ems(&self) -> Result<(), String> {
+        let released = self.ems.lock().unwrap().disable();
+        if released.is_empty() || !self.calculation_loop_started.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+        for device_id in released {
+            self.update_device_properties_for_simulation(device_id.clone(), serde_json::json!({ "p_kw": 0.0 }), "ems")
+                .await
+                .map_err(|e| format!("恢复储能设定失败 {}: {}", device_id, e))?;
+        }
+        Ok(())
+    }
+
+    pub fn get_ems_status(&self) -> EmsStatus {
+        self.ems.lock().unwrap().status()
+    }
+
+    pub fn set_sign_convention(&self, convention: SignConvention) {
+        *self.sign_convention.lock().unwrap() = convention;
+    }
+
+    pub fn get_sign_convention(&self) -> SignConvention {
+        self.sign_convention.lock().unwrap().clone()
+    }
+
+    pub fn get_meter_dropout_report(&self) -> MeterDropoutReport {
+        self.meter_dropout.lock().unwrap().report()
+    }
+
+    pub fn get_grid_limit_violations(&self) -> Vec<GridLimitViolation> {
+        self.grid_limit_violations.lock().unwrap().clone()
+    }
+
+    /// 仅更新引擎拓扑中的设备属性（不推送内核），用于 Rust 端消费的配置（如外部电网分时计划）
+    pub async fn set_device_property(&self, device_id: &str, key: &str, value: serde_json::Value, source: &str) {
+        let mut topo_guard = self.topology.lock().await;
+        if let Some(device) = topo_guard.as_mut().and_then(|t| t.devices.get_mut(device_id)) {
+            device.properties.insert(key.to_string(), value);
+            self.record_property_change(device_id, [key], source);
+        }
+    }
+
+    /// 记录运行中属性修改来源（仅仿真运行期间记录，供属性偏移报告使用）
+    fn record_property_change<'a>(&self, device_id: &str, keys: impl IntoIterator<Item = &'a str>, source: &str) {
+        if !self.calculation_loop_started.load(Ordering::Relaxed) {
+            return;
+        }
+        let changed_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs_f64();
+        let mut changes = self.property_changes.lock().unwrap();
+        for key in keys {
+            changes.insert(
+                (device_id.to_string(), key.to_string()),
+                PropertyChangeRecord { source: source.to_string(), changed_at },
+            );
+        }
+    }
+
+    /// 各设备当前生效属性相对启动时拓扑的差异及修改来源；未运行过仿真时返回空
+    pub async fn get_effective_property_drift(&self) -> Vec<DevicePropertyDrift> {
+        let baseline = self.baseline_topology.lock().await.clone();
+        let current = self.topology.lock().await.clone();
+        let (Some(baseline), Some(current)) = (baseline, current) else {
+            return Vec::new();
+        };
+        let changes = self.property_changes.lock().unwrap().clone();
+        let mut drifts: Vec<DevicePropertyDrift> = current
+            .devices
+            .iter()
+            .filter_map(|(device_id, device)| {
+                let original_props = baseline.devices.get(device_id).map(|d| &d.properties);
+                let mut keys: Vec<&String> = device.properties.keys().collect();
+                if let Some(orig) = original_props {
+                    keys.extend(orig.keys().filter(|k| !device.properties.contains_key(*k)));
+                }
+                let mut items: Vec<PropertyDrift> = keys
+                    .into_iter()
+                    .filter_map(|key| {
+                        let original = original_props.and_then(|p| p.get(key)).cloned();
+                        let now = device.properties.get(key).cloned();
+                        if original == now {
+                            return None;
+                        }
+                        let record = changes.get(&(device_id.clone(), key.clone()));
+                        Some(PropertyDrift {
+                            key: key.clone(),
+                            original,
+                            current: now,
+                            source: record.map(|r| r.source.clone()),
+                            changed_at: record.map(|r| r.changed_at),
+                        })
+                    })
+                    .collect();
+                if items.is_empty() {
+                    return None;
+                }
+                items.sort_by(|a, b| a.key.cmp(&b.key));
+                Some(DevicePropertyDrift {
+                    device_id: device_id.clone(),
+                    device_name: device.name.clone(),
+                    changes: items,
+                })
+            })
+            .collect();
+        drifts.sort_by(|a, b| a.device_id.cmp(&b.device_id));
+        drifts
+    }
+
+    pub fn get_active_limit_alerts(&self) -> Vec<LimitAlert> {
+        self.limit_monitor.lock().unwrap().active_alerts()
+    }
+
+    pub fn get_limit_kpis(&self) -> HashMap<String, LimitKpi> {
+        let mut kpis = self.limit_monitor.lock().unwrap().kpis();
+        for (device_id, count) in self.setpoint_limiter.lock().unwrap().counts() {
+            kpis.entry(device_id).or_default().clamped_setpoints = count;
+        }
+        for adherence in self.storage_schedules.lock().unwrap().adherence() {
+            if adherence.steps > 0 {
+                kpis.entry(adherence.device_id).or_default().schedule_deviation_percent = Some(adherence.deviation_percent);
+            }
+        }
+        kpis
+    }
+
+    /// 本次仿真的设定值限幅事件（最近 500 条）
+    pub fn get_setpoint_clamps(&self) -> Vec<SetpointClamp> {
+        self.setpoint_limiter.lock().unwrap().events()
+    }
+
+    /// 本次仿真的有载调压分接头动作（最近 500 条）
+    pub fn get_tap_operations(&self) -> Vec<TapOperation> {
+        self.oltc.lock().unwrap().operations()
+    }
+
+    /// 本次仿真的过流保护动作（最近 500 条）
+    pub fn get_protection_events(&self) -> Vec<ProtectionTrip> {
+        self.protection.lock().unwrap().events()
+    }
+
+    /// 读取内核当前元件表，与拓扑比对（名称、数量、所连母线、开关状态）；未传入拓扑时使用引擎持有的拓扑
+    pub async fn verify_kernel_sync(&self, topology: Option<Topology>) -> Result<KernelSyncReport, String> {
+        let topology = match topology {
+            Some(t) => t,
+            None => self.topology.lock().await.clone().ok_or("拓扑未加载")?,
+        };
+        let tables = {
+            let bridge = &self.kernel;
+            bridge
+                .call("simulation.get_element_tables", serde_json::json!({}))
+                .await
+                .map_err(|e| format!("读取内核元件表失败: {}", e))?
+        };
+        if tables.get("status").and_then(|v| v.as_str()) == Some("error") {
+            let msg = tables.get("message").and_then(|v| v.as_str()).unwrap_or("未知错误");
+            return Err(format!("读取内核元件表失败: {}", msg));
+        }
+        let checked_at = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs_f64();
+        kernel_sync::verify(&topology, &tables, checked_at)
+    }
+
+    pub async fn set_device_mode(&self, device_id: String, mode: String) -> Result<(), String> {
+        // 验证模式
+        let valid_modes = ["random_data", "manual", "remote", "historical_data", "solar_model", "weather_model", "ev_session"];
+        if !valid_modes.contains(&mode.as_str()) {
+            return Err(format!("Invalid mode: {}", mode));
+        }
+        // 辐照模型只适用于光伏，且须有经纬度与装机容量
+        if mode == "solar_model" {
+            if let Some(device) = self.topology.lock().await.as_ref().and_then(|t| t.devices.get(&device_id)) {
+                if device.device_type != DeviceType::Pv {
+                    return Err(format!("设备 {} 不是光伏，不能使用辐照模型", device_id));
+                }
+                SolarPanelConfig::from_device(device)?;
+            }
+        }
+        // 气象模型适用于光伏 / 风机（静态发电机）与负荷
+        if mode == "weather_model" {
+            if let Some(device) = self.topology.lock().await.as_ref().and_then(|t| t.devices.get(&device_id)) {
+                if !matches!(device.device_type, DeviceType::Pv | DeviceType::Load) {
+                    return Err(format!("设备 {} 不支持气象模型", device_id));
+                }
+            }
+        }
+        // 充电会话模型只适用于充电桩，且须有额定功率或单枪最大功率
+        if mode == "ev_session" {
+            if let Some(device) = self.topology.lock().await.as_ref().and_then(|t| t.devices.get(&device_id)) {
+                if device.device_type != DeviceType::Charger {
+                    return Err(format!("设备 {} 不是充电桩，不能使用充电会话模型", device_id));
+                }
+                ev_sessions::validate_device(device)?;
+            }
+        } else {
+            // 切出充电会话模式：仍接入的会话按中断结束
+            let records = self.ev_sessions.lock().unwrap().remove(&device_id, self.sim_time());
+            if let Some(ref db) = *self.database.lock().unwrap() {
+                for record in &records {
+                    if let Err(e) = db.insert_ev_session(record) {
+                        eprintln!("写入充电会话失败 {}: {}", record.session_id, e);
+                    }
+                }
+            }
+        }
+
+        // 更新设备模式
+        self.device_modes.lock().await.insert(device_id.clone(), mode.clone().into());
+        
+        // 通知 Python 内核
+        let bridge = &self.kernel;
+        let params = serde_json::json!({
+            "device_id": device_id,
+            "mode": mode
+        });
+        bridge.call("simulation.set_device_mode", params).await
+            .map_err(|e| format!("Failed to set device mode: {}", e))?;
+        
+        Ok(())
+    }
+
+    /// 设置随机数据源：profile 未指定时按设备类型选默认生成模型；Uniform 由内核生成，其余由 Rust 端生成后下发。
+    /// seed 为设备随机种子，未指定时由全局种子派生；两者皆无时不可复现
+    pub async fn set_device_random_config(
+        &self,
+        device_id: String,
+        min_power: f64,
+        max_power: f64,
+        profile: Option<RandomProfile>,
+        seed: Option<u64>,
+    ) -> Result<(), String> {
+        let profile = match profile {
+            Some(p) => p,
+            None => {
+                let topology = self.topology.lock().await;
+                topology
+                    .as_ref()
+                    .and_then(|t| t.devices.get(&device_id))
+                    .map(|d| RandomProfile::default_for(&d.device_type))
+                    .unwrap_or(RandomProfile::Uniform)
+            }
+        };
+        profile.validate()?;
+        let engine_generated = profile.is_engine_generated();
+        let seed = {
+            let mut generators = self.random_generators.lock().unwrap();
+            let seed = random_generator::device_seed(generators.global_seed(), &device_id, seed);
+            generators.configure(&device_id, profile, min_power, max_power, seed);
+            seed
+        };
+        let bridge = &self.kernel;
+        let params = serde_json::json!({
+            "device_id": device_id,
+            "min_power": min_power,
+            "max_power": max_power,
+            "external": engine_generated,
+            "seed": seed
+        });
+        bridge
+            .call("simulation.set_device_random_config", params)
+            .await
+            .map_err(|e| format!("设置设备随机配置失败: {}", e))?;
+        Ok(())
+    }
+
+    pub fn set_device_forecast(&self, forecast: DeviceForecast) {
+        self.forecast_accuracy.lock().unwrap().set_forecast(forecast);
+    }
+
+    pub fn remove_device_forecast(&self, device_id: &str) -> bool {
+        self.forecast_accuracy.lock().unwrap().remove_forecast(device_id)
+    }
+
+    pub fn get_device_forecast(&self, device_id: &str) -> Option<DeviceForecast> {
+        self.forecast_accuracy.lock().unwrap().get_forecast(device_id)
+    }
+
+    pub fn get_forecast_accuracy(&self, key: Option<&str>) -> Vec<ForecastAccuracy> {
+        self.forecast_accuracy.lock().unwrap().report(key)
+    }
+
+    pub fn get_random_profiles(&self) -> HashMap<String, RandomProfile> {
+        self.random_generators.lock().unwrap().profiles()
+    }
+
+    /// 全局随机种子：之后配置的随机模式设备按此派生种子（已配置的设备需重新配置才生效）
+    pub fn set_random_seed(&self, seed: Option<u64>) {
+        self.random_generators.lock().unwrap().set_global_seed(seed);
+    }
+
+    pub fn random_seed(&self) -> Option<u64> {
+        self.random_generators.lock().unwrap().global_seed()
+    }
+
+    pub async fn set_device_manual_setpoint(
+        &self,
+        device_id: String,
+        active_power: f64,
+        reactive_power: f64,
+    ) -> Result<(), String> {
+        let now = self.sim_time();
+        let active_power = match self.topology.lock().await.as_ref().and_then(|t| t.devices.get(&device_id)) {
+            Some(device) => self.setpoint_limiter.lock().unwrap().clamp(device, "manual", "active_power", active_power, now),
+            None => active_power,
+        };
+        let bridge = &self.kernel;
+        let params = serde_json::json!({
+            "device_id": device_id,
+            "active_power": active_power,
+            "reactive_power": reactive_power
+        });
+        bridge
+            .call("simulation.set_device_manual_setpoint", params)
+            .await
+            .map_err(|e| format!("设置设备手动设定失败: {}", e))?;
+        Ok(())
+    }
+
+    /// 内核侧历史数据源配置；会替换该设备已导入的 Rust 端回放曲线
     pub async fn set_device_historical_config(
         &self,
         device_id: String,
         config: serde_json::Value,
     ) -> Result<(), String> {
-        let mut bridge = self.python_bridge.lock().await;
+        self.profile_player.lock().unwrap().remove(&device_id);
+        let bridge = &self.kernel;
         let params = serde_json::json!({
             "device_id": device_id,
             "config": config
@@ -1334,19 +3185,73 @@ impl SimulationEngine {
         Ok(())
     }
 
-    /// 设置设备级仿真参数（采集频率/响应延迟/测量误差）；同时写 Rust 端（用于 Modbus IR 节流）和 Python 端（用于延迟/噪声）
+    /// 导入设备功率曲线：由 Rust 端回放，内核不再按历史配置读取数据源；设备须处于历史数据模式才会下发
+    pub async fn set_device_profile(&self, profile: PowerProfile) -> Result<(), String> {
+        let device_id = profile.device_id.clone();
+        let bridge = &self.kernel;
+        let params = serde_json::json!({
+            "device_id": device_id,
+            "config": { "external": true, "filePath": profile.file_path }
+        });
+        let value = bridge
+            .call("simulation.set_device_historical_config", params)
+            .await
+            .map_err(|e| format!("设置设备历史配置失败: {}", e))?;
+        if value.get("status").and_then(|v| v.as_str()) == Some("error") {
+            let msg = value.get("message").and_then(|v| v.as_str()).unwrap_or("未知错误");
+            return Err(format!("设置设备历史配置失败: {}", msg));
+        }
+        self.profile_player.lock().unwrap().set(profile);
+        Ok(())
+    }
+
+    pub fn remove_device_profile(&self, device_id: &str) -> bool {
+        self.profile_player.lock().unwrap().remove(device_id)
+    }
+
+    pub fn get_device_profile(&self, device_id: &str) -> Option<PowerProfile> {
+        self.profile_player.lock().unwrap().get(device_id)
+    }
+
+    /// 绑定气象数据源（加载失败时保留原绑定）；运行中绑定的下一步生效，本次运行的仿真库同时记录该数据源
+    pub async fn bind_weather_source(&self, source: WeatherSourceSpec) -> Result<WeatherBinding, String> {
+        let series = WeatherSeries::load(source).await?;
+        let binding = WeatherBinding::of(&series);
+        *self.weather_series.lock().unwrap() = Some(series);
+        let running = self.status.lock().await.state != crate::domain::simulation::SimulationState::Stopped;
+        if running {
+            if let Some(ref db) = *self.database.lock().unwrap() {
+                weather::record_binding(db, &binding);
+            }
+        }
+        Ok(binding)
+    }
+
+    pub fn unbind_weather_source(&self) -> bool {
+        self.weather_series.lock().unwrap().take().is_some()
+    }
+
+    pub fn weather_binding(&self) -> Option<WeatherBinding> {
+        self.weather_series.lock().unwrap().as_ref().map(WeatherBinding::of)
+    }
+
+    /// 设置设备级仿真参数（采集频率/传感器延迟/响应延迟/测量误差）；同时写 Rust 端（用于 Modbus IR 节流）和 Python 端（用于延迟/噪声）
     pub async fn set_device_sim_params(
         &self,
         device_id: String,
         params: serde_json::Value,
     ) -> Result<(), String> {
-        // 1) 存 Rust 端
+        // 1) 存 Rust 端（传感器延迟在 Rust 端缓冲上报值）
         {
             let mut m = self.device_sim_params.lock().await;
             m.insert(device_id.clone(), params.clone());
         }
+        self.delay_simulator
+            .lock()
+            .unwrap()
+            .set_device_delays(&device_id, DeviceDelays::from_sim_params(&params));
         // 2) 转发 Python 端
-        let mut bridge = self.python_bridge.lock().await;
+        let bridge = &self.kernel;
         let rpc_params = serde_json::json!({
             "device_id": device_id,
             "params": params
@@ -1358,25 +3263,674 @@ impl SimulationEngine {
         Ok(())
     }
 
+    /// 设置设备传感器上报延迟与执行器响应延迟（毫秒）：与其余仿真参数合并后同步到内核，执行器延迟由内核排队实现
+    pub async fn set_device_delays(
+        &self,
+        device_id: String,
+        sensor_delay_ms: f64,
+        actuator_delay_ms: f64,
+    ) -> Result<(), String> {
+        if sensor_delay_ms < 0.0 || actuator_delay_ms < 0.0 {
+            return Err("延迟不能为负".to_string());
+        }
+        let mut params = self
+            .device_sim_params
+            .lock()
+            .await
+            .get(&device_id)
+            .cloned()
+            .unwrap_or_else(|| serde_json::json!({}));
+        if let Some(obj) = params.as_object_mut() {
+            obj.insert("sensorDelayMs".to_string(), serde_json::json!(sensor_delay_ms));
+            obj.insert("responseDelayMs".to_string(), serde_json::json!(actuator_delay_ms));
+        }
+        self.set_device_sim_params(device_id, params).await
+    }
+
+    pub fn get_device_delays(&self, device_id: &str) -> Option<DeviceDelays> {
+        self.delay_simulator.lock().unwrap().get_device_delays(device_id)
+    }
+
+    /// 设置光伏无功控制模式：写入引擎拓扑 properties（下次启动仍生效）并推送到 Python 内核
+    pub async fn set_device_reactive_control(
+        &self,
+        device_id: String,
+        config: ReactiveControlConfig,
+    ) -> Result<(), String> {
+        config.validate()?;
+        {
+            let mut topo_guard = self.topology.lock().await;
+            if let Some(device) = topo_guard.as_mut().and_then(|t| t.devices.get_mut(&device_id)) {
+                if device.device_type != crate::domain::topology::DeviceType::Pv {
+                    return Err(format!("设备 {} 不是光伏，不支持无功控制模式", device_id));
+                }
+                let properties = config.to_properties();
+                self.record_property_change(&device_id, properties.keys().map(|k| k.as_str()), "command");
+                device.properties.extend(properties);
+            }
+        }
+        let bridge = &self.kernel;
+        let rpc_params = serde_json::json!({
+            "device_id": device_id,
+            "config": config
+        });
+        bridge
+            .call("simulation.set_device_reactive_control", rpc_params)
+            .await
+            .map_err(|e| format!("设置无功控制模式失败: {}", e))?;
+        Ok(())
+    }
+
+    /// 设置光伏电网支撑功能（volt-var / volt-watt）：写入引擎拓扑 properties.grid_support，
+    /// 由计算循环按每步结果计算设定并下发内核；关闭后下一步撤销已下发的设定
+    pub async fn set_device_grid_support(&self, device_id: String, config: GridSupportConfig) -> Result<(), String> {
+        config.validate()?;
+        let mut topo_guard = self.topology.lock().await;
+        if let Some(device) = topo_guard.as_mut().and_then(|t| t.devices.get_mut(&device_id)) {
+            if device.device_type != crate::domain::topology::DeviceType::Pv {
+                return Err(format!("设备 {} 不是光伏，不支持电网支撑功能", device_id));
+            }
+            device
+                .properties
+                .insert("grid_support".to_string(), serde_json::to_value(&config).map_err(|e| e.to_string())?);
+            self.record_property_change(&device_id, ["grid_support"], "command");
+        }
+        Ok(())
+    }
+
+    /// 设置光伏/储能 P(f) 响应配置：写入引擎拓扑 properties.pf_response 并推送到 Python 内核
+    pub async fn set_device_pf_response(
+        &self,
+        device_id: String,
+        config: PfResponseConfig,
+    ) -> Result<(), String> {
+        config.validate()?;
+        {
+            let mut topo_guard = self.topology.lock().await;
+            if let Some(device) = topo_guard.as_mut().and_then(|t| t.devices.get_mut(&device_id)) {
+                device.properties.insert(
+                    "pf_response".to_string(),
+                    serde_json::to_value(&config).unwrap_or_default(),
+                );
+                self.record_property_change(&device_id, ["pf_response"], "command");
+            }
+        }
+        let bridge = &self.kernel;
+        let rpc_params = serde_json::json!({
+            "device_id": device_id,
+            "config": config
+        });
+        bridge
+            .call("simulation.set_device_pf_response", rpc_params)
+            .await
+            .map_err(|e| format!("设置 P(f) 响应失败: {}", e))?;
+        Ok(())
+    }
+
+    /// 单次潮流快照：按负荷假设对给定拓扑计算一次，不影响当前仿真拓扑与状态
+    pub async fn run_snapshot_powerflow(
+        &self,
+        topology: &Topology,
+        assumptions: &serde_json::Value,
+    ) -> Result<serde_json::Value, String> {
+        let topology_data = self.convert_topology_to_standard_format(topology).await?;
+        let bridge = &self.kernel;
+        let result = bridge
+            .call("power.snapshot", serde_json::json!({
+                "topology_data": topology_data,
+                "assumptions": assumptions,
+                "kernel_type": self.power_kernel().as_str(),
+            }))
+            .await
+            .map_err(|e| format!("快照潮流计算失败: {}", e))?;
+        if result.get("status").and_then(|v| v.as_str()) == Some("error") {
+            let msg = result.get("message").and_then(|v| v.as_str()).unwrap_or("快照潮流计算失败");
+            return Err(msg.to_string());
+        }
+        Ok(result)
+    }
+
+    /// N-1 预想故障分析：先算基态，再逐一退出每条线路/变压器执行快照潮流，
+    /// 汇总各预想故障的支路过载、母线电压越限与失电母线。parallelism 大于 1 时各预想故障分发到内核池并行求解，
+    /// 否则（或主内核处于降级模式时）经主内核逐个计算，每次计算单独持锁，不阻塞运行中的仿真步进
+    pub async fn run_contingency_analysis(
+        &self,
+        app: &AppHandle,
+        pool: &KernelPool,
+        topology: &Topology,
+        assumptions: &serde_json::Value,
+        limits: ContingencyLimits,
+        parallelism: usize,
+    ) -> Result<ContingencyReport, String> {
+        let started = std::time::Instant::now();
+        let topology_data = self.convert_topology_to_standard_format(topology).await?;
+        let mut branches: Vec<&crate::domain::topology::Device> = topology
+            .devices
+            .values()
+            .filter(|d| matches!(d.device_type, DeviceType::Line | DeviceType::Transformer))
+            .collect();
+        if branches.is_empty() {
+            return Err("拓扑中没有线路或变压器".to_string());
+        }
+        branches.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let outages: Vec<Option<&crate::domain::topology::Device>> =
+            std::iter::once(None).chain(branches.into_iter().map(Some)).collect();
+        let jobs: Vec<KernelJob> = outages
+            .iter()
+            .map(|outage| KernelJob {
+                method: "power.snapshot".to_string(),
+                params: serde_json::json!({
+                    "topology_data": topology_data,
+                    "assumptions": assumptions,
+                    "kernel_type": self.power_kernel().as_str(),
+                    "out_of_service": outage.iter().map(|d| d.id.as_str()).collect::<Vec<&str>>(),
+                }),
+            })
+            .collect();
+        let kernels_used = if self.kernel.is_fallback() { 1 } else { parallelism.clamp(1, jobs.len()) };
+        let results = if kernels_used > 1 {
+            pool.run_jobs(app, kernels_used, jobs).await?
+        } else {
+            let mut results = Vec::with_capacity(jobs.len());
+            for job in jobs {
+                results.push(self.kernel.call(&job.method, job.params).await.map_err(|e| e.to_string()));
+            }
+            results
+        };
+
+        let mut cases = Vec::with_capacity(outages.len());
+        for (outage, result) in outages.into_iter().zip(results) {
+            let mut case = match result {
+                Ok(value) if value.get("status").and_then(|v| v.as_str()) != Some("error") => {
+                    Self::evaluate_contingency(topology, &value, &limits)
+                }
+                Ok(value) => Self::unsolved_contingency(vec![
+                    value.get("message").and_then(|v| v.as_str()).unwrap_or("快照潮流计算失败").to_string(),
+                ]),
+                Err(e) => Self::unsolved_contingency(vec![format!("快照潮流计算失败: {}", e)]),
+            };
+            if let Some(device) = outage {
+                case.outage_device_id = Some(device.id.clone());
+                case.outage_name = Some(device.name.clone());
+                case.outage_type = Some(device.device_type.as_str().to_string());
+                // 被切除支路本身无潮流，不计入过载
+                case.overloads.retain(|o| o.device_id != device.id);
+            }
+            cases.push(case);
+        }
+
+        let base_case = cases.remove(0);
+        let critical_count = cases.iter().filter(|c| c.is_critical()).count();
+        Ok(ContingencyReport {
+            limits,
+            kernels_used,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+            base_case,
+            contingencies: cases,
+            critical_count,
+        })
+    }
+
+    fn unsolved_contingency(errors: Vec<String>) -> ContingencyCase {
+        ContingencyCase {
+            outage_device_id: None,
+            outage_name: None,
+            outage_type: None,
+            converged: false,
+            overloads: Vec::new(),
+            voltage_violations: Vec::new(),
+            isolated_buses: Vec::new(),
+            min_vm_pu: None,
+            max_vm_pu: None,
+            max_loading_percent: None,
+            errors,
+        }
+    }
+
+    /// 按 power.snapshot 结果（devices + element_ids）判定过载与电压越限
+    fn evaluate_contingency(topology: &Topology, value: &serde_json::Value, limits: &ContingencyLimits) -> ContingencyCase {
+        let converged = value.get("converged").and_then(|v| v.as_bool()).unwrap_or(false);
+        let errors = value
+            .get("errors")
+            .and_then(|v| v.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(SimulationError::from_kernel_value)
+                    .filter(|e| e.severity == "error")
+                    .map(|e| e.message)
+                    .collect()
+            })
+            .unwrap_or_default();
+        let mut case = ContingencyCase { converged, ..Self::unsolved_contingency(errors) };
+        if !converged {
+            return case;
+        }
+        // (结果表索引, 设备 ID, 设备名称, 结果行)
+        let rows = |table: &str| -> Vec<(String, String, serde_json::Value)> {
+            let ids = value.get("element_ids").and_then(|e| e.get(table));
+            value
+                .get("devices")
+                .and_then(|d| d.get(table))
+                .and_then(|t| t.as_object())
+                .map(|rows| {
+                    rows.iter()
+                        .filter_map(|(idx, row)| {
+                            let device_id = ids.and_then(|m| m.get(idx)).and_then(|v| v.as_str())?.to_string();
+                            let name = topology
+                                .devices
+                                .get(&device_id)
+                                .map(|d| d.name.clone())
+                                .unwrap_or_else(|| device_id.clone());
+                            Some((device_id, name, row.clone()))
+                        })
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        for (device_id, name, row) in rows("buses") {
+            let Some(vm) = row.get("vm_pu").and_then(|v| v.as_f64()) else {
+                case.isolated_buses.push(device_id);
+                continue;
+            };
+            case.min_vm_pu = Some(case.min_vm_pu.map_or(vm, |m| m.min(vm)));
+            case.max_vm_pu = Some(case.max_vm_pu.map_or(vm, |m| m.max(vm)));
+            let kind = if vm < limits.min_vm_pu {
+                "low"
+            } else if vm > limits.max_vm_pu {
+                "high"
+            } else {
+                continue;
+            };
+            case.voltage_violations.push(BusVoltageViolation { device_id, name, vm_pu: vm, kind: kind.to_string() });
+        }
+        for (table, element_type) in [("lines", "line"), ("transformers", "transformer")] {
+            for (device_id, name, row) in rows(table) {
+                let Some(loading) = row.get("loading_percent").and_then(|v| v.as_f64()) else { continue };
+                case.max_loading_percent = Some(case.max_loading_percent.map_or(loading, |m| m.max(loading)));
+                if loading > limits.max_loading_percent {
+                    case.overloads.push(BranchOverload {
+                        device_id,
+                        name,
+                        element_type: element_type.to_string(),
+                        loading_percent: loading,
+                    });
+                }
+            }
+        }
+        case.isolated_buses.sort();
+        case.overloads.sort_by(|a, b| b.loading_percent.total_cmp(&a.loading_percent));
+        case.voltage_violations.sort_by(|a, b| a.device_id.cmp(&b.device_id));
+        case
+    }
+
+    /// 按电气孤岛拆分拓扑，各孤岛快照潮流分发到内核池并行求解后合并结果
+    pub async fn run_partitioned_snapshot(
+        &self,
+        app: &AppHandle,
+        pool: &KernelPool,
+        topology: &Topology,
+        assumptions: &serde_json::Value,
+        parallelism: usize,
+    ) -> Result<PartitionedSnapshotResult, String> {
+        let started = std::time::Instant::now();
+        let islands = topology.split_islands();
+        if islands.is_empty() {
+            return Err("拓扑中没有可求解的孤岛（缺少母线）".to_string());
+        }
+        let mut jobs = Vec::with_capacity(islands.len());
+        for island in &islands {
+            jobs.push(KernelJob {
+                method: "power.snapshot".to_string(),
+                params: serde_json::json!({
+                    "topology_data": self.convert_topology_to_standard_format(island).await?,
+                    "assumptions": assumptions,
+                    "kernel_type": self.power_kernel().as_str(),
+                }),
+            });
+        }
+        let kernels_used = parallelism.clamp(1, islands.len());
+        let results = pool.run_jobs(app, kernels_used, jobs).await?;
+
+        let mut merged: serde_json::Map<String, serde_json::Value> = serde_json::Map::new();
+        let mut summaries = Vec::with_capacity(islands.len());
+        for (index, (island, result)) in islands.iter().zip(results).enumerate() {
+            let (converged, errors) = match result {
+                Ok(value) => {
+                    let errors = value
+                        .get("errors")
+                        .and_then(|v| v.as_array())
+                        .map(|arr| arr.iter().filter_map(SimulationError::from_kernel_value).collect())
+                        .unwrap_or_default();
+                    // 各孤岛内核的元素索引互相独立，合并时以孤岛序号作前缀避免冲突
+                    if let Some(tables) = value.get("devices").and_then(|v| v.as_object()) {
+                        for (table, rows) in tables {
+                            let Some(rows) = rows.as_object() else { continue };
+                            let target = merged
+                                .entry(table.clone())
+                                .or_insert_with(|| serde_json::Value::Object(serde_json::Map::new()));
+                            if let Some(target) = target.as_object_mut() {
+                                for (key, row) in rows {
+                                    target.insert(format!("{}:{}", index, key), row.clone());
+                                }
+                            }
+                        }
+                    }
+                    (value.get("status").and_then(|v| v.as_str()) != Some("error")
+                        && value.get("converged").and_then(|v| v.as_bool()).unwrap_or(false), errors)
+                }
+                Err(e) => {
+                    let error = SimulationError::from_kernel_value(&serde_json::json!({
+                        "type": "calculation",
+                        "severity": "error",
+                        "message": format!("孤岛 {} 求解失败: {}", index, e),
+                    }));
+                    (false, error.into_iter().collect())
+                }
+            };
+            summaries.push(IslandSnapshotSummary {
+                index,
+                device_count: island.devices.len(),
+                converged,
+                errors,
+            });
+        }
+        Ok(PartitionedSnapshotResult {
+            converged: summaries.iter().all(|s| s.converged),
+            kernels_used,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+            islands: summaries,
+            devices: serde_json::Value::Object(merged),
+        })
+    }
+
+    /// 同一拓扑的多组负荷假设（蒙特卡洛抽样或预想工况）分发到内核池并行求解，汇总电压与负载率极值
+    pub async fn run_snapshot_batch(
+        &self,
+        app: &AppHandle,
+        pool: &KernelPool,
+        topology: &Topology,
+        samples: &[serde_json::Value],
+        parallelism: usize,
+    ) -> Result<SnapshotBatchResult, String> {
+        if samples.is_empty() {
+            return Err("请至少提供一组工况".to_string());
+        }
+        let started = std::time::Instant::now();
+        let topology_data = self.convert_topology_to_standard_format(topology).await?;
+        let jobs = samples
+            .iter()
+            .map(|assumptions| KernelJob {
+                method: "power.snapshot".to_string(),
+                params: serde_json::json!({
+                    "topology_data": topology_data,
+                    "assumptions": assumptions,
+                    "kernel_type": self.power_kernel().as_str(),
+                }),
+            })
+            .collect();
+        let kernels_used = parallelism.clamp(1, samples.len());
+        let results = pool.run_jobs(app, kernels_used, jobs).await?;
+        Ok(Self::summarize_snapshot_batch(results, kernels_used, started))
+    }
+
+    /// 按 power.snapshot 结果汇总各样本的电压与负载率极值
+    fn summarize_snapshot_batch(
+        results: Vec<Result<serde_json::Value, String>>,
+        kernels_used: usize,
+        started: std::time::Instant,
+    ) -> SnapshotBatchResult {
+        let column = |value: &serde_json::Value, table: &str, field: &str| -> Vec<f64> {
+            value
+                .get("devices")
+                .and_then(|d| d.get(table))
+                .and_then(|t| t.as_object())
+                .map(|rows| rows.values().filter_map(|r| r.get(field).and_then(|v| v.as_f64())).collect())
+                .unwrap_or_default()
+        };
+        let fold_min = |v: &[f64]| v.iter().copied().reduce(f64::min);
+        let fold_max = |v: &[f64]| v.iter().copied().reduce(f64::max);
+        let samples: Vec<SnapshotSampleSummary> = results
+            .into_iter()
+            .enumerate()
+            .map(|(index, result)| match result {
+                Ok(value) if value.get("status").and_then(|v| v.as_str()) != Some("error") => {
+                    let vm = column(&value, "buses", "vm_pu");
+                    let mut loading = column(&value, "lines", "loading_percent");
+                    loading.extend(column(&value, "transformers", "loading_percent"));
+                    SnapshotSampleSummary {
+                        index,
+                        converged: value.get("converged").and_then(|v| v.as_bool()).unwrap_or(false),
+                        min_vm_pu: fold_min(&vm),
+                        max_vm_pu: fold_max(&vm),
+                        max_loading_percent: fold_max(&loading),
+                        error: None,
+                    }
+                }
+                Ok(value) => SnapshotSampleSummary {
+                    index,
+                    converged: false,
+                    min_vm_pu: None,
+                    max_vm_pu: None,
+                    max_loading_percent: None,
+                    error: Some(value.get("message").and_then(|v| v.as_str()).unwrap_or("快照潮流计算失败").to_string()),
+                },
+                Err(e) => SnapshotSampleSummary {
+                    index,
+                    converged: false,
+                    min_vm_pu: None,
+                    max_vm_pu: None,
+                    max_loading_percent: None,
+                    error: Some(e),
+                },
+            })
+            .collect();
+        let converged: Vec<&SnapshotSampleSummary> = samples.iter().filter(|s| s.converged).collect();
+        SnapshotBatchResult {
+            kernels_used,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+            converged_count: converged.len(),
+            min_vm_pu: converged.iter().filter_map(|s| s.min_vm_pu).reduce(f64::min),
+            max_vm_pu: converged.iter().filter_map(|s| s.max_vm_pu).reduce(f64::max),
+            max_loading_percent: converged.iter().filter_map(|s| s.max_loading_percent).reduce(f64::max),
+            samples,
+        }
+    }
+
+    /// 潮流参数扫描：按参数网格修改设备属性，每个组合一次快照潮流，分发到内核池并行求解后汇总（样本顺序与组合一致）
+    pub async fn run_snapshot_sweep(
+        &self,
+        app: &AppHandle,
+        pool: &KernelPool,
+        topology: &Topology,
+        parameters: &[SweepParameter],
+        assumptions: &serde_json::Value,
+        parallelism: usize,
+    ) -> Result<SnapshotSweepResult, String> {
+        let started = std::time::Instant::now();
+        let combinations = sweep::expand_grid(parameters, topology)?;
+        let mut jobs = Vec::with_capacity(combinations.len());
+        for values in &combinations {
+            let mut variant = topology.clone();
+            sweep::apply_parameters(&mut variant, parameters, values);
+            jobs.push(KernelJob {
+                method: "power.snapshot".to_string(),
+                params: serde_json::json!({
+                    "topology_data": self.convert_topology_to_standard_format(&variant).await?,
+                    "assumptions": assumptions,
+                    "kernel_type": self.power_kernel().as_str(),
+                }),
+            });
+        }
+        let kernels_used = parallelism.clamp(1, combinations.len());
+        let results = pool.run_jobs(app, kernels_used, jobs).await?;
+        Ok(SnapshotSweepResult {
+            combinations,
+            batch: Self::summarize_snapshot_batch(results, kernels_used, started),
+        })
+    }
+
+    /// 设置运行参数，下次启动仿真时生效
+    pub fn set_run_options(&self, options: RunOptions) {
+        *self.run_options.lock().unwrap() = options;
+    }
+
+    pub fn get_run_options(&self) -> RunOptions {
+        self.run_options.lock().unwrap().clone()
+    }
+
+    /// 设置外部电网电压边界条件（pu），None 恢复拓扑中各外部电网自身的 vm_pu
+    pub async fn set_ext_grid_voltage(&self, vm_pu: Option<f64>) -> Result<(), String> {
+        if let Some(v) = vm_pu {
+            if v <= 0.0 {
+                return Err(format!("外部电网电压无效: {}", v));
+            }
+        }
+        let bridge = &self.kernel;
+        bridge
+            .call("simulation.set_ext_grid_voltage", serde_json::json!({ "vm_pu": vm_pu }))
+            .await
+            .map_err(|e| format!("设置外部电网电压失败: {}", e))?;
+        Ok(())
+    }
+
+    /// 设置孤岛频率模型：islanded=true 时激活，P(f) 曲线按 frequency_hz 生效
+    pub async fn set_grid_frequency(
+        &self,
+        frequency_hz: f64,
+        islanded: bool,
+        nominal_hz: Option<f64>,
+    ) -> Result<(), String> {
+        if frequency_hz <= 0.0 {
+            return Err(format!("频率无效: {}", frequency_hz));
+        }
+        let bridge = &self.kernel;
+        let rpc_params = serde_json::json!({
+            "frequency_hz": frequency_hz,
+            "islanded": islanded,
+            "nominal_hz": nominal_hz
+        });
+        bridge
+            .call("simulation.set_grid_frequency", rpc_params)
+            .await
+            .map_err(|e| format!("设置电网频率失败: {}", e))?;
+        Ok(())
+    }
+
+    /// 读取内核当前的电网边界条件（外部电网电压设定与频率模型状态）
+    pub async fn get_grid_boundary(&self) -> Result<GridBoundary, String> {
+        let result = self
+            .kernel
+            .call("simulation.get_grid_boundary", serde_json::json!({}))
+            .await
+            .map_err(|e| format!("读取电网边界条件失败: {}", e))?;
+        if result.get("status").and_then(|v| v.as_str()) == Some("error") {
+            let msg = result.get("message").and_then(|v| v.as_str()).unwrap_or("读取电网边界条件失败");
+            return Err(msg.to_string());
+        }
+        serde_json::from_value(result).map_err(|e| format!("解析电网边界条件失败: {}", e))
+    }
+
+    /// 恢复电网边界条件（与 get_grid_boundary 配对）
+    pub async fn restore_grid_boundary(&self, boundary: &GridBoundary) -> Result<(), String> {
+        self.set_ext_grid_voltage(boundary.vm_pu).await?;
+        self.set_grid_frequency(boundary.frequency_hz, boundary.islanded, Some(boundary.nominal_hz)).await
+    }
+
     pub async fn get_device_modes(&self) -> DeviceWorkModes {
         self.device_modes.lock().await.clone()
     }
 
+    /// 设置拓扑并重建潮流结果索引（运行中属性修改不改变设备名称，无需重建）；孤岛频率模型的孤岛划分一并失效
     pub async fn set_topology(&self, topology: Topology) {
+        *self.result_index.lock().unwrap() = Arc::new(ResultIndex::build(&topology));
+        self.frequency_model.lock().unwrap().invalidate_islands();
         *self.topology.lock().await = Some(topology);
     }
 
+    pub fn set_keep_kernel_warm(&self, enabled: bool) {
+        self.keep_kernel_warm.store(enabled, Ordering::Relaxed);
+    }
+
+    pub fn keep_kernel_warm(&self) -> bool {
+        self.keep_kernel_warm.load(Ordering::Relaxed)
+    }
+
+    /// 选择潮流求解内核，下次启动（或预加载、内核重启）时随拓扑下发
+    pub fn set_power_kernel(&self, kernel_type: KernelType) {
+        *self.power_kernel.lock().unwrap() = kernel_type;
+    }
+
+    pub fn power_kernel(&self) -> KernelType {
+        *self.power_kernel.lock().unwrap()
+    }
+
+    /// 本实例内核进程的客户端（能力查询等）
+    pub fn kernel(&self) -> &KernelClient {
+        &self.kernel
+    }
+
+    /// 预加载当前拓扑到内核（仅停止状态）：构建网络缓存并试算一次潮流，使随后的启动可立即步进
+    pub async fn preload_topology(&self, warm_up: bool) -> Result<TopologyPreloadResult, String> {
+        if self.status.lock().await.state != SimulationState::Stopped {
+            return Err("仿真运行中，无法预加载拓扑".to_string());
+        }
+        let topology = self
+            .topology
+            .lock()
+            .await
+            .clone()
+            .ok_or("拓扑数据未设置，请先加载拓扑")?;
+        let topology_data = self.convert_topology_to_standard_format(&topology).await?;
+        let bridge = &self.kernel;
+        let result = bridge
+            .call(
+                "simulation.preload_topology",
+                serde_json::json!({
+                    "topology_data": topology_data,
+                    "warm_up": warm_up,
+                    "kernel_type": self.power_kernel().as_str(),
+                }),
+            )
+            .await
+            .map_err(|e| format!("预加载拓扑失败: {}", e))?;
+        if result.get("status").and_then(|v| v.as_str()) == Some("error") {
+            let msg = result
+                .get("message")
+                .and_then(|v| v.as_str())
+                .unwrap_or("网络构建失败");
+            return Err(format!("预加载拓扑失败: {}", msg));
+        }
+        let warnings = result
+            .get("errors")
+            .and_then(|v| v.as_array())
+            .map(|errs| {
+                errs.iter()
+                    .filter_map(|e| e.get("message").and_then(|m| m.as_str()).map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok(TopologyPreloadResult {
+            already_warm: result.get("already_warm").and_then(|v| v.as_bool()).unwrap_or(false),
+            warmed_up: result.get("warmed_up").and_then(|v| v.as_bool()).unwrap_or(false),
+            elapsed_ms: result.get("elapsed_ms").and_then(|v| v.as_u64()).unwrap_or(0),
+            warnings,
+        })
+    }
+
     /// 更新开关状态（同时更新 topology 和 Python 仿真引擎）
     pub async fn update_switch_state(
         &self,
         device_id: String,
         is_closed: bool,
     ) -> Result<(), String> {
+        self.set_device_property(&device_id, "is_closed", serde_json::json!(is_closed), "switch").await;
         let rpc_params = serde_json::json!({
             "device_id": device_id,
             "is_closed": is_closed,
         });
-        let mut bridge = self.python_bridge.lock().await;
+        let bridge = &self.kernel;
         bridge
             .call("simulation.update_switch_state", rpc_params)
             .await
@@ -1384,32 +3938,47 @@ impl SimulationEngine {
         Ok(())
     }
 
-    /// 事件驱动远程控制：将设备属性增量立即写入仿真，下一拍计算即生效。先检查全局与按设备是否允许远程控制。
+    /// 事件驱动远程控制：将设备属性增量立即写入仿真，下一拍计算即生效。先检查全局与按设备是否允许远程控制（定时事件、储能计划与 EMS 为本地控制，不受限制）。
     pub async fn update_device_properties_for_simulation(
         &self,
         device_id: String,
         properties: serde_json::Value,
+        source: &str,
     ) -> Result<(), String> {
-        if !self.device_remote_control_allowed(&device_id).await {
+        if !is_local_source(source) && !self.device_remote_control_allowed(&device_id).await {
             return Ok(());
         }
-        let props_map = properties
+        let mut props_map = properties
             .as_object()
+            .cloned()
             .ok_or_else(|| "properties 必须为对象".to_string())?;
         {
+            let now = self.sim_time();
             let mut topo_guard = self.topology.lock().await;
             if let Some(topo) = topo_guard.as_mut() {
                 if let Some(device) = topo.devices.get_mut(&device_id) {
-                    for (k, v) in props_map {
+                    // 功率设定超出铭牌额定时限幅后再写入拓扑与内核
+                    self.setpoint_limiter.lock().unwrap().clamp_properties(device, source, &mut props_map, now);
+                    for (k, v) in &props_map {
                         device.properties.insert(k.clone(), v.clone());
                     }
+                    // 开关经 Modbus 线圈合/分闸：同步拓扑中的 is_closed（内核侧同样改写 is_closed 并切换网络中的开关）
+                    if device.device_type == DeviceType::Switch {
+                        if let Some(closed) = props_map.get("closed") {
+                            device.properties.insert("is_closed".to_string(), closed.clone());
+                        }
+                    }
+                    self.record_property_change(&device_id, props_map.keys().map(|k| k.as_str()), source);
+                    if source == "modbus" && props_map.contains_key("p_kw") {
+                        self.storage_schedules.lock().unwrap().note_remote_command(&device_id, now);
+                    }
                 }
             }
         }
-        let mut bridge = self.python_bridge.lock().await;
+        let bridge = &self.kernel;
         let params = serde_json::json!({
             "device_id": device_id,
-            "properties": properties
+            "properties": props_map
         });
         bridge
             .call("simulation.update_device_properties", params)
@@ -1422,8 +3991,194 @@ impl SimulationEngine {
         self.topology.lock().await.clone()
     }
 
+    /// 施加一条定时事件：is_closed 按开关操作处理，其余属性经 update_device_properties_for_simulation 写入（来源 schedule）
+    pub async fn apply_scheduled_event(&self, event: &ScheduledEvent) -> Result<(), String> {
+        if let Some(action) = &event.action {
+            return match action {
+                EventAction::GridVoltage { vm_pu } => self.set_ext_grid_voltage(Some(*vm_pu)).await,
+                EventAction::GridFrequency { frequency_hz } => self.set_grid_frequency(*frequency_hz, true, None).await,
+                EventAction::DeviceSetpoint { device_id, p_kw, q_kvar } => {
+                    self.set_device_manual_setpoint(device_id.clone(), *p_kw, *q_kvar).await
+                }
+                EventAction::DeviceMode { device_id, mode } => self.set_device_mode(device_id.clone(), mode.clone()).await,
+            };
+        }
+        let mut properties = event.properties.clone();
+        if let Some(is_closed) = properties.remove("is_closed").and_then(|v| v.as_bool()) {
+            self.update_switch_state(event.device_id.clone(), is_closed).await?;
+        }
+        if !properties.is_empty() {
+            self.update_device_properties_for_simulation(
+                event.device_id.clone(),
+                serde_json::Value::Object(properties),
+                "schedule",
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// 故障注入（需仿真运行中）：停运仅限线路/变压器，短路可施加于母线、线路或变压器；duration_s 为持续的仿真秒数，
+    /// 到期由计算循环自动恢复，None 表示持续到手动清除或仿真停止
+    pub async fn inject_fault(
+        &self,
+        app_handle: Option<&AppHandle>,
+        device_id: &str,
+        fault_type: FaultType,
+        duration_s: Option<f64>,
+        fault_resistance_ohm: Option<f64>,
+    ) -> Result<ActiveFault, String> {
+        if self.status.lock().await.state != SimulationState::Running {
+            return Err("请先启动仿真再注入故障".to_string());
+        }
+        if duration_s.is_some_and(|d| !d.is_finite() || d <= 0.0) {
+            return Err("故障持续时间必须大于 0".to_string());
+        }
+        if fault_resistance_ohm.is_some_and(|r| !r.is_finite() || r < 0.0) {
+            return Err("故障接地电阻不能为负".to_string());
+        }
+        let device_type = {
+            let topology = self.topology.lock().await;
+            topology
+                .as_ref()
+                .and_then(|t| t.devices.get(device_id))
+                .map(|d| d.device_type.clone())
+                .ok_or_else(|| format!("设备不存在: {}", device_id))?
+        };
+        let allowed = match fault_type {
+            FaultType::Outage => matches!(device_type, DeviceType::Line | DeviceType::Transformer),
+            FaultType::ShortCircuit => matches!(device_type, DeviceType::Node | DeviceType::Line | DeviceType::Transformer),
+        };
+        if !allowed {
+            return Err(match fault_type {
+                FaultType::Outage => "停运故障设备须为线路或变压器".to_string(),
+                FaultType::ShortCircuit => "短路故障位置须为母线、线路或变压器".to_string(),
+            });
+        }
+        if self.fault_injector.lock().unwrap().is_active(device_id) {
+            return Err(format!("设备 {} 已存在故障", device_id));
+        }
+        let fault_resistance_ohm = match fault_type {
+            FaultType::ShortCircuit => Some(fault_resistance_ohm.unwrap_or(5.0)),
+            FaultType::Outage => None,
+        };
+        let result = {
+            let bridge = &self.kernel;
+            let mut params = serde_json::json!({ "device_id": device_id, "fault_type": fault_type.as_str() });
+            if let Some(r) = fault_resistance_ohm {
+                params["fault_resistance_ohm"] = serde_json::json!(r);
+            }
+            bridge
+                .call("simulation.inject_fault", params)
+                .await
+                .map_err(|e| format!("故障注入失败: {}", e))?
+        };
+        if result.get("status").and_then(|v| v.as_str()) == Some("error") {
+            let msg = result.get("message").and_then(|v| v.as_str()).unwrap_or("未知错误");
+            return Err(format!("故障注入失败: {}", msg));
+        }
+        let now = self.sim_time();
+        let fault = self.fault_injector.lock().unwrap().insert(
+            device_id,
+            fault_type,
+            result.get("fault_resistance_ohm").and_then(|v| v.as_f64()).or(fault_resistance_ohm),
+            duration_s,
+            result.get("short_circuit").cloned(),
+            now,
+        );
+        if let Some(app) = self.shared_app(app_handle) {
+            window_hub::publish_typed(app, Some(device_id), FaultStateChanged::injected(fault.clone()));
+        }
+        self.request_burst("fault");
+        Ok(fault)
+    }
+
+    /// 本轮仿真已推进的仿真时间（秒）
+    pub fn sim_elapsed_s(&self) -> f64 {
+        self.sim_clock.lock().unwrap().elapsed_s
+    }
+
+    /// 当前仿真时间（Unix 秒）；尚未启动过仿真时为墙钟时间
+    pub fn sim_time(&self) -> f64 {
+        let clock = *self.sim_clock.lock().unwrap();
+        if clock.start_epoch > 0.0 {
+            clock.now()
+        } else {
+            SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs_f64()
+        }
+    }
+
+    /// 请求突发落库（由计算循环在下一步处理；未配置突发落库时无效果）
+    pub fn request_burst(&self, trigger: &str) {
+        self.burst_requests.lock().unwrap().push(trigger.to_string());
+    }
+
+    /// 手动清除故障并恢复网络
+    pub async fn clear_fault(&self, app_handle: Option<&AppHandle>, device_id: &str) -> Result<FaultRecord, String> {
+        let fault = self
+            .fault_injector
+            .lock()
+            .unwrap()
+            .remove(device_id)
+            .ok_or_else(|| format!("设备 {} 无生效中的故障", device_id))?;
+        let cleared = {
+            let bridge = &self.kernel;
+            bridge
+                .call("simulation.clear_fault", serde_json::json!({ "device_id": device_id }))
+                .await
+        };
+        let now = self.sim_time();
+        let record = self
+            .fault_injector
+            .lock()
+            .unwrap()
+            .finish(fault, if cleared.is_ok() { "command" } else { "error" }, now);
+        if let Some(app) = self.shared_app(app_handle) {
+            window_hub::publish_typed(app, Some(device_id), FaultStateChanged::cleared(record.clone()));
+        }
+        self.request_burst("fault");
+        cleared.map_err(|e| format!("恢复故障失败: {}", e))?;
+        Ok(record)
+    }
+
+    pub fn active_faults(&self) -> Vec<ActiveFault> {
+        self.fault_injector.lock().unwrap().active()
+    }
+
+    pub fn fault_history(&self) -> Vec<FaultRecord> {
+        self.fault_injector.lock().unwrap().history()
+    }
+
+    /// 载入定时事件表：运行中载入时，时间已过的事件在下一步立即施加
+    pub fn load_event_schedule(&self, schedule: EventSchedule) -> Result<(), String> {
+        self.event_scheduler.lock().unwrap().load(schedule)
+    }
+
+    pub fn clear_event_schedule(&self) {
+        self.event_scheduler.lock().unwrap().clear();
+    }
+
+    pub fn event_schedule_status(&self) -> EventScheduleStatus {
+        self.event_scheduler.lock().unwrap().status()
+    }
+
+    /// 追加定时事件（场景脚本），at_s 为本轮仿真时间
+    pub fn insert_scheduled_events(&self, events: Vec<ScheduledEvent>) -> Result<(), String> {
+        self.event_scheduler.lock().unwrap().insert(events)
+    }
+
+    /// 撤回指定归属尚未施加的定时事件
+    pub fn withdraw_scheduled_events(&self, owner: &str) {
+        self.event_scheduler.lock().unwrap().withdraw(owner);
+    }
+
+    /// 指定归属首个施加失败的定时事件
+    pub fn scheduled_event_failure(&self, owner: &str) -> Option<AppliedEventRecord> {
+        self.event_scheduler.lock().unwrap().first_failure(owner).cloned()
+    }
+
     pub async fn get_device_data(&self, device_id: &str) -> Result<serde_json::Value, String> {
-        let mut bridge = self.python_bridge.lock().await;
+        let bridge = &self.kernel;
         let params = serde_json::json!({
             "device_id": device_id
         });
@@ -0,0 +1,198 @@
+// 多仿真实例管理：主仿真（default）之外可创建若干独立仿真，各自持有拓扑、仿真库文件与 Python 内核进程，
+// 仿真命令通过 simulation_id 指定目标实例。独立实例不推送前端事件、不对接 Modbus，通过命令查询状态与数据
+use crate::domain::simulation::SimulationState;
+use crate::domain::topology::Topology;
+use crate::services::database::Database;
+use crate::services::kernel_factory::KernelFactory;
+use crate::services::python_bridge::PythonBridge;
+use crate::services::simulation_engine::SimulationEngine;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+use tokio::sync::Mutex;
+
+/// 主仿真实例 id（界面、Modbus 与其他命令使用的仿真）
+pub const DEFAULT_SIMULATION_ID: &str = "default";
+/// 独立实例上限（不含主仿真；每个实例一个 Python 内核进程）
+pub const MAX_EXTRA_SIMULATIONS: usize = 4;
+
+#[derive(Clone)]
+struct SimulationInstance {
+    engine: Arc<SimulationEngine>,
+    bridge: Arc<Mutex<PythonBridge>>,
+    label: Option<String>,
+    created_at: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulationInstanceInfo {
+    pub simulation_id: String,
+    #[serde(default)]
+    pub label: Option<String>,
+    pub is_default: bool,
+    pub state: SimulationState,
+    /// 当前（或最近一次）仿真库文件，尚未启动过时为空
+    pub db_path: String,
+    pub device_count: usize,
+    pub created_at: f64,
+}
+
+pub struct SimulationManager {
+    instances: StdMutex<HashMap<String, SimulationInstance>>,
+}
+
+impl SimulationManager {
+    /// 以应用启动时创建的主仿真引擎初始化
+    pub fn new(default_engine: Arc<SimulationEngine>, default_bridge: Arc<Mutex<PythonBridge>>) -> Self {
+        let mut instances = HashMap::new();
+        instances.insert(
+            DEFAULT_SIMULATION_ID.to_string(),
+            SimulationInstance {
+                engine: default_engine,
+                bridge: default_bridge,
+                label: None,
+                created_at: now_secs(),
+            },
+        );
+        Self { instances: StdMutex::new(instances) }
+    }
+
+    /// 按 id 获取仿真引擎；未指定时为主仿真
+    pub fn get(&self, simulation_id: Option<&str>) -> Result<Arc<SimulationEngine>, String> {
+        let id = simulation_id.unwrap_or(DEFAULT_SIMULATION_ID);
+        self.instances
+            .lock()
+            .unwrap()
+            .get(id)
+            .map(|i| i.engine.clone())
+            .ok_or_else(|| format!("仿真实例不存在: {}", id))
+    }
+
+    pub fn is_default(simulation_id: Option<&str>) -> bool {
+        simulation_id.is_none_or(|id| id == DEFAULT_SIMULATION_ID)
+    }
+
+    /// 创建独立仿真实例：拉起专用内核进程，设置拓扑快照；实例 id 用于仿真库文件名，只允许字母、数字、- 与 _
+    pub async fn create(
+        &self,
+        app: &AppHandle,
+        simulation_id: &str,
+        label: Option<String>,
+        topology: Topology,
+    ) -> Result<SimulationInstanceInfo, String> {
+        validate_simulation_id(simulation_id)?;
+        {
+            let instances = self.instances.lock().unwrap();
+            if instances.contains_key(simulation_id) {
+                return Err(format!("仿真实例已存在: {}", simulation_id));
+            }
+            if instances.len() > MAX_EXTRA_SIMULATIONS {
+                return Err(format!("独立仿真实例最多 {} 个", MAX_EXTRA_SIMULATIONS));
+            }
+        }
+        let bridge = KernelFactory::spawn_power_kernel_process(Some(app)).await?;
+        let kernel = bridge.client();
+        let bridge = Arc::new(Mutex::new(bridge));
+        let engine = Arc::new(
+            SimulationEngine::new(bridge.clone(), kernel, Arc::new(StdMutex::new(None::<Database>)), Arc::new(StdMutex::new(String::new())))
+                .with_instance_id(simulation_id),
+        );
+        engine.set_topology(topology).await;
+        let instance = SimulationInstance { engine, bridge, label, created_at: now_secs() };
+        let info = instance_info(simulation_id, &instance).await;
+        // 拉起内核期间可能有同名实例被创建
+        let duplicate = {
+            let mut instances = self.instances.lock().unwrap();
+            if instances.contains_key(simulation_id) {
+                Some(instance)
+            } else {
+                instances.insert(simulation_id.to_string(), instance);
+                None
+            }
+        };
+        if let Some(instance) = duplicate {
+            let _ = instance.bridge.lock().await.stop().await;
+            return Err(format!("仿真实例已存在: {}", simulation_id));
+        }
+        Ok(info)
+    }
+
+    /// 删除独立实例：停止仿真并关闭其内核进程；主仿真不可删除
+    pub async fn remove(&self, simulation_id: &str) -> Result<(), String> {
+        if simulation_id == DEFAULT_SIMULATION_ID {
+            return Err("主仿真实例不可删除".to_string());
+        }
+        let instance = self
+            .instances
+            .lock()
+            .unwrap()
+            .remove(simulation_id)
+            .ok_or_else(|| format!("仿真实例不存在: {}", simulation_id))?;
+        if instance.engine.get_status().await.state != SimulationState::Stopped {
+            instance.engine.stop().await?;
+        }
+        instance.engine.flush_device_data().await;
+        instance.bridge.lock().await.stop().await.map_err(|e| format!("关闭内核进程失败: {}", e))?;
+        Ok(())
+    }
+
+    /// 应用退出时关闭全部实例（含主仿真）的内核进程，各实例并行关闭
+    pub async fn shutdown_kernels(&self) {
+        let bridges: Vec<Arc<Mutex<PythonBridge>>> =
+            self.instances.lock().unwrap().values().map(|i| i.bridge.clone()).collect();
+        let mut tasks = tokio::task::JoinSet::new();
+        for bridge in bridges {
+            tasks.spawn(async move {
+                if let Err(e) = bridge.lock().await.shutdown().await {
+                    eprintln!("关闭内核进程失败: {}", e);
+                }
+            });
+        }
+        while tasks.join_next().await.is_some() {}
+    }
+
+    /// 全部实例（主仿真在前，其余按创建时间）
+    pub async fn list(&self) -> Vec<SimulationInstanceInfo> {
+        let snapshot: Vec<(String, SimulationInstance)> = self
+            .instances
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(id, i)| (id.clone(), i.clone()))
+            .collect();
+        let mut infos = Vec::with_capacity(snapshot.len());
+        for (id, instance) in &snapshot {
+            infos.push(instance_info(id, instance).await);
+        }
+        infos.sort_by(|a, b| b.is_default.cmp(&a.is_default).then(a.created_at.total_cmp(&b.created_at)));
+        infos
+    }
+}
+
+async fn instance_info(simulation_id: &str, instance: &SimulationInstance) -> SimulationInstanceInfo {
+    SimulationInstanceInfo {
+        simulation_id: simulation_id.to_string(),
+        label: instance.label.clone(),
+        is_default: simulation_id == DEFAULT_SIMULATION_ID,
+        state: instance.engine.get_status().await.state,
+        db_path: instance.engine.current_db_path(),
+        device_count: instance.engine.get_topology().await.map(|t| t.devices.len()).unwrap_or(0),
+        created_at: instance.created_at,
+    }
+}
+
+fn validate_simulation_id(simulation_id: &str) -> Result<(), String> {
+    if simulation_id.is_empty() || simulation_id.len() > 32 {
+        return Err("仿真实例 id 长度须为 1~32".to_string());
+    }
+    if !simulation_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(format!("仿真实例 id 只允许字母、数字、- 与 _: {}", simulation_id));
+    }
+    Ok(())
+}
+
+fn now_secs() -> f64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs_f64()).unwrap_or(0.0)
+}
@@ -0,0 +1,134 @@
+// 光伏晴空辐照出力模型（solar_model 工作模式）：按设备经纬度与仿真时间计算太阳位置，
+// 由晴空模型得到水平面总辐照、直射与散射，换算到组件倾斜面后按装机容量与系统效率得到有功；
+// 引擎每步计算后经 simulation.set_device_solar_value 下发内核
+use crate::domain::topology::{prop_f64, Device, Location};
+use chrono::Datelike;
+use serde::{Deserialize, Serialize};
+
+/// 太阳常数（W/m²）
+const SOLAR_CONSTANT_W_M2: f64 = 1361.0;
+/// 标准测试条件辐照（W/m²），装机容量对应的辐照
+const STC_IRRADIANCE_W_M2: f64 = 1000.0;
+
+/// 光伏组件参数（设备属性）：panel_kwp（装机容量，缺省取额定功率，见 topology::rated_power_kw）、panel_tilt_deg（倾角，缺省取纬度绝对值）、
+/// panel_azimuth_deg（方位角，正北 0° 顺时针，缺省朝向赤道）、performance_ratio（系统效率，缺省 0.85）、albedo（地面反射率，缺省 0.2）
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SolarPanelConfig {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub kwp: f64,
+    pub tilt_deg: f64,
+    pub azimuth_deg: f64,
+    pub performance_ratio: f64,
+    pub albedo: f64,
+    /// 逆变器额定功率（kW），出力不超过该值
+    pub rated_kw: Option<f64>,
+}
+
+impl SolarPanelConfig {
+    /// 从设备位置与属性解析；缺少位置或装机容量时返回原因
+    pub fn from_device(device: &Device) -> Result<Self, String> {
+        let Some(Location { latitude, longitude, .. }) = device.location.clone() else {
+            return Err(format!("光伏 {} 未设置经纬度位置", device.name));
+        };
+        if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+            return Err(format!("光伏 {} 经纬度无效: ({}, {})", device.name, latitude, longitude));
+        }
+        let p = &device.properties;
+        let rated_kw = crate::domain::topology::rated_power_kw(p).filter(|v| *v > 0.0);
+        let kwp = prop_f64(p, "panel_kwp")
+            .filter(|v| *v > 0.0)
+            .or(rated_kw)
+            .ok_or_else(|| format!("光伏 {} 未设置装机容量 panel_kwp", device.name))?;
+        Ok(Self {
+            latitude,
+            longitude,
+            kwp,
+            tilt_deg: prop_f64(p, "panel_tilt_deg").unwrap_or(latitude.abs()).clamp(0.0, 90.0),
+            azimuth_deg: prop_f64(p, "panel_azimuth_deg")
+                .unwrap_or(if latitude >= 0.0 { 180.0 } else { 0.0 })
+                .rem_euclid(360.0),
+            performance_ratio: prop_f64(p, "performance_ratio").filter(|v| *v > 0.0).unwrap_or(0.85).min(1.0),
+            albedo: prop_f64(p, "albedo").unwrap_or(0.2).clamp(0.0, 1.0),
+            rated_kw,
+        })
+    }
+}
+
+/// 太阳位置：天顶角与方位角（度，方位角正北 0° 顺时针）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SolarPosition {
+    pub zenith_deg: f64,
+    pub azimuth_deg: f64,
+}
+
+/// 按 NOAA 简化算法计算太阳位置（精度约 0.5°，足够出力仿真）；timestamp 为 Unix 秒（UTC）
+pub fn solar_position(latitude: f64, longitude: f64, timestamp: f64) -> SolarPosition {
+    let day_of_year = chrono::DateTime::from_timestamp(timestamp.floor() as i64, 0)
+        .map(|dt| dt.ordinal0() as f64)
+        .unwrap_or(0.0);
+    let hour_utc = (timestamp.rem_euclid(86400.0)) / 3600.0;
+    let gamma = 2.0 * std::f64::consts::PI / 365.0 * (day_of_year + (hour_utc - 12.0) / 24.0);
+    let eq_time_min = 229.18
+        * (0.000075 + 0.001868 * gamma.cos() - 0.032077 * gamma.sin() - 0.014615 * (2.0 * gamma).cos()
+            - 0.040849 * (2.0 * gamma).sin());
+    let declination = 0.006918 - 0.399912 * gamma.cos() + 0.070257 * gamma.sin() - 0.006758 * (2.0 * gamma).cos()
+        + 0.000907 * (2.0 * gamma).sin()
+        - 0.002697 * (3.0 * gamma).cos()
+        + 0.00148 * (3.0 * gamma).sin();
+    let true_solar_min = hour_utc * 60.0 + eq_time_min + 4.0 * longitude;
+    let hour_angle = (true_solar_min / 4.0 - 180.0).to_radians();
+    let lat = latitude.to_radians();
+    let cos_zenith = (lat.sin() * declination.sin() + lat.cos() * declination.cos() * hour_angle.cos()).clamp(-1.0, 1.0);
+    let zenith = cos_zenith.acos();
+    // 方位角：由南北分量与东西分量求得，上午在东（<180°）、下午在西
+    let azimuth = (-hour_angle.sin() * declination.cos())
+        .atan2(lat.cos() * declination.sin() - lat.sin() * declination.cos() * hour_angle.cos());
+    SolarPosition { zenith_deg: zenith.to_degrees(), azimuth_deg: azimuth.to_degrees().rem_euclid(360.0) }
+}
+
+/// 晴空辐照分量（W/m²）
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ClearSkyIrradiance {
+    /// 水平面总辐照
+    pub ghi: f64,
+    /// 法向直射
+    pub dni: f64,
+    /// 水平面散射
+    pub dhi: f64,
+}
+
+/// 晴空模型：总辐照按 Haurwitz 公式，直射按大气质量的 Meinel 衰减，散射为两者之差
+pub fn clear_sky(zenith_deg: f64) -> ClearSkyIrradiance {
+    let cos_z = zenith_deg.to_radians().cos();
+    if cos_z <= 0.01 {
+        return ClearSkyIrradiance { ghi: 0.0, dni: 0.0, dhi: 0.0 };
+    }
+    let ghi = 1098.0 * cos_z * (-0.059 / cos_z).exp();
+    let air_mass = 1.0 / (cos_z + 0.50572 * (96.07995 - zenith_deg).max(0.1).powf(-1.6364));
+    let dni = (SOLAR_CONSTANT_W_M2 * 0.7f64.powf(air_mass.powf(0.678))).min(ghi / cos_z);
+    let dhi = (ghi - dni * cos_z).max(0.0);
+    ClearSkyIrradiance { ghi, dni, dhi }
+}
+
+/// 倾斜面辐照（W/m²）：直射按入射角余弦，散射按各向同性天空，地面反射按反射率
+pub fn plane_of_array(config: &SolarPanelConfig, position: SolarPosition, sky: ClearSkyIrradiance) -> f64 {
+    let (tilt, zenith) = (config.tilt_deg.to_radians(), position.zenith_deg.to_radians());
+    let cos_aoi = zenith.cos() * tilt.cos()
+        + zenith.sin() * tilt.sin() * (position.azimuth_deg - config.azimuth_deg).to_radians().cos();
+    let beam = sky.dni * cos_aoi.max(0.0);
+    let diffuse = sky.dhi * (1.0 + tilt.cos()) / 2.0;
+    let reflected = sky.ghi * config.albedo * (1.0 - tilt.cos()) / 2.0;
+    beam + diffuse + reflected
+}
+
+/// 仿真时刻的光伏有功（kW，发电为正），不超过逆变器额定功率
+pub fn pv_output_kw(config: &SolarPanelConfig, timestamp: f64) -> f64 {
+    let position = solar_position(config.latitude, config.longitude, timestamp);
+    let poa = plane_of_array(config, position, clear_sky(position.zenith_deg));
+    let p_kw = config.kwp * poa / STC_IRRADIANCE_W_M2 * config.performance_ratio;
+    match config.rated_kw {
+        Some(rated) => p_kw.clamp(0.0, rated),
+        None => p_kw.max(0.0),
+    }
+}
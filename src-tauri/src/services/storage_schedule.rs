@@ -0,0 +1,150 @@
+// 储能计划执行：每步决定需下发的计划设定，处理与远程指令的冲突，并累计计划执行偏差
+use crate::domain::storage_schedule::{ScheduleAdherence, ScheduleConflictRule, StorageSchedule};
+use std::collections::HashMap;
+
+#[derive(Default)]
+struct ScheduleRuntime {
+    /// 最近一次下发的时段序号
+    applied_slot: Option<usize>,
+    /// 远程指令接管：Some(slot) 表示在该时段内接管（RemoteUntilNextSlot），Some(usize::MAX) 表示本次仿真内持续接管
+    overridden_in_slot: Option<usize>,
+    /// 收到远程指令后需在下一步重新下发计划值（SchedulePriority）
+    reapply: bool,
+    adherence: ScheduleAdherence,
+}
+
+#[derive(Default)]
+pub struct StorageScheduleExecutor {
+    schedules: HashMap<String, StorageSchedule>,
+    runtimes: HashMap<String, ScheduleRuntime>,
+}
+
+impl StorageScheduleExecutor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_schedule(&mut self, schedule: StorageSchedule) {
+        self.runtimes.remove(&schedule.device_id);
+        self.schedules.insert(schedule.device_id.clone(), schedule);
+    }
+
+    pub fn remove_schedule(&mut self, device_id: &str) -> bool {
+        self.runtimes.remove(device_id);
+        self.schedules.remove(device_id).is_some()
+    }
+
+    pub fn get_schedule(&self, device_id: &str) -> Option<StorageSchedule> {
+        self.schedules.get(device_id).cloned()
+    }
+
+    pub fn has_schedule(&self, device_id: &str) -> bool {
+        self.schedules.contains_key(device_id)
+    }
+
+    /// 新一轮仿真：清空执行状态与偏差统计，计划本身保留
+    pub fn reset(&mut self) {
+        self.runtimes.clear();
+    }
+
+    fn runtime(&mut self, device_id: &str) -> &mut ScheduleRuntime {
+        self.runtimes.entry(device_id.to_string()).or_insert_with(|| ScheduleRuntime {
+            adherence: ScheduleAdherence {
+                device_id: device_id.to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+    }
+
+    /// 收到该储能的远程功率指令时按冲突规则处理
+    pub fn note_remote_command(&mut self, device_id: &str, timestamp: f64) {
+        let Some(schedule) = self.schedules.get(device_id) else {
+            return;
+        };
+        let rule = schedule.conflict_rule;
+        let slot = schedule.slot_index_at(timestamp);
+        let runtime = self.runtime(device_id);
+        match rule {
+            ScheduleConflictRule::SchedulePriority => runtime.reapply = true,
+            ScheduleConflictRule::RemoteUntilNextSlot => runtime.overridden_in_slot = slot,
+            ScheduleConflictRule::RemotePriority => runtime.overridden_in_slot = Some(usize::MAX),
+        }
+    }
+
+    /// 本步需下发的设定：(device_id, p_kw, q_kvar)；仅在进入新时段或需重新下发时返回
+    pub fn due_setpoints(&mut self, timestamp: f64) -> Vec<(String, f64, f64)> {
+        let mut due = Vec::new();
+        let slots: Vec<(String, Option<usize>)> = self
+            .schedules
+            .iter()
+            .map(|(id, s)| (id.clone(), s.slot_index_at(timestamp)))
+            .collect();
+        for (device_id, slot) in slots {
+            let Some(slot) = slot else {
+                continue;
+            };
+            let point = self.schedules[&device_id].points[slot].clone();
+            let runtime = self.runtime(&device_id);
+            match runtime.overridden_in_slot {
+                Some(usize::MAX) => continue,
+                Some(s) if s == slot => continue,
+                Some(_) => runtime.overridden_in_slot = None,
+                None => {}
+            }
+            if runtime.applied_slot != Some(slot) || runtime.reapply {
+                runtime.applied_slot = Some(slot);
+                runtime.reapply = false;
+                due.push((device_id, point.p_kw, point.q_kvar));
+            }
+        }
+        due
+    }
+
+    /// 记录本步实际功率，累计计划偏差
+    pub fn record_step(&mut self, device_id: &str, timestamp: f64, actual_p_kw: f64, dt_h: f64) {
+        let Some(schedule) = self.schedules.get(device_id) else {
+            return;
+        };
+        let Some(slot) = schedule.slot_index_at(timestamp) else {
+            return;
+        };
+        let scheduled_kw = schedule.points[slot].p_kw;
+        let runtime = self.runtime(device_id);
+        let overridden = matches!(runtime.overridden_in_slot, Some(s) if s == slot || s == usize::MAX);
+        let a = &mut runtime.adherence;
+        a.steps += 1;
+        if overridden {
+            a.overridden_steps += 1;
+        }
+        let deviation_kw = (actual_p_kw - scheduled_kw).abs();
+        a.scheduled_energy_kwh += scheduled_kw.abs() * dt_h;
+        a.actual_energy_kwh += actual_p_kw.abs() * dt_h;
+        a.deviation_energy_kwh += deviation_kw * dt_h;
+        a.max_deviation_kw = a.max_deviation_kw.max(deviation_kw);
+        a.deviation_percent = if a.scheduled_energy_kwh > 0.0 {
+            a.deviation_energy_kwh / a.scheduled_energy_kwh * 100.0
+        } else {
+            0.0
+        };
+        a.current_setpoint_kw = Some(scheduled_kw);
+    }
+
+    pub fn adherence(&self) -> Vec<ScheduleAdherence> {
+        let mut out: Vec<ScheduleAdherence> = self
+            .schedules
+            .keys()
+            .map(|id| {
+                self.runtimes
+                    .get(id)
+                    .map(|r| r.adherence.clone())
+                    .unwrap_or_else(|| ScheduleAdherence {
+                        device_id: id.clone(),
+                        ..Default::default()
+                    })
+            })
+            .collect();
+        out.sort_by(|a, b| a.device_id.cmp(&b.device_id));
+        out
+    }
+}
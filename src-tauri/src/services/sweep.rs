@@ -0,0 +1,297 @@
+// 参数扫描（批量仿真）：在基准拓扑上按参数网格（如储能容量 100–1000 kWh × 光伏 50–500 kWp）逐个组合运行无界面仿真，
+// 每次运行使用独立仿真实例与内核进程，到达步数/时长上限后自动停止；各次仿真库移入输出目录，
+// 并写出 index.json（参数组合、仿真库与运行摘要）供对比
+use crate::domain::preset::RunOptions;
+use crate::domain::simulation::SimulationState;
+use crate::domain::sign_convention::SignConvention;
+use crate::domain::topology::Topology;
+use crate::services::database::Database;
+use crate::services::run_summary::{self, RunSummary};
+use crate::services::simulation_manager::SimulationManager;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter};
+
+/// 组合数上限
+const MAX_SWEEP_RUNS: usize = 500;
+/// 单次运行等待停止的轮询间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// 一个扫描维度：设备属性取 values，或按 start..=stop 以 step 递增
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SweepParameter {
+    pub device_id: String,
+    pub property: String,
+    #[serde(default)]
+    pub values: Vec<f64>,
+    #[serde(default)]
+    pub start: Option<f64>,
+    #[serde(default)]
+    pub stop: Option<f64>,
+    #[serde(default)]
+    pub step: Option<f64>,
+}
+
+impl SweepParameter {
+    pub fn key(&self) -> String {
+        format!("{}.{}", self.device_id, self.property)
+    }
+
+    /// 展开取值
+    pub fn resolve(&self) -> Result<Vec<f64>, String> {
+        let values = match (self.start, self.stop, self.step) {
+            _ if !self.values.is_empty() => self.values.clone(),
+            (Some(start), Some(stop), Some(step)) => {
+                if !(start.is_finite() && stop.is_finite() && step.is_finite()) || step <= 0.0 || stop < start {
+                    return Err(format!("参数 {} 的范围无效", self.key()));
+                }
+                let count = ((stop - start) / step + 1e-9).floor() as usize + 1;
+                if count > MAX_SWEEP_RUNS {
+                    return Err(format!("参数 {} 取值过多", self.key()));
+                }
+                (0..count).map(|i| start + step * i as f64).collect()
+            }
+            _ => return Err(format!("参数 {} 需提供 values 或 start/stop/step", self.key())),
+        };
+        if values.iter().any(|v| !v.is_finite()) {
+            return Err(format!("参数 {} 含无效取值", self.key()));
+        }
+        Ok(values)
+    }
+}
+
+fn default_interval_ms() -> u64 {
+    100
+}
+
+fn default_time_scale() -> f64 {
+    1.0
+}
+
+/// 扫描任务：每次运行须以 max_steps 或 max_duration_secs 结束
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SweepSpec {
+    pub parameters: Vec<SweepParameter>,
+    /// 基准拓扑文件；为空时使用当前界面拓扑
+    #[serde(default)]
+    pub topology_path: Option<String>,
+    /// 输出目录；为空时在工作目录下新建 sweep_<unix_ts>
+    #[serde(default)]
+    pub output_dir: Option<String>,
+    #[serde(default = "default_interval_ms")]
+    pub calculation_interval_ms: u64,
+    #[serde(default = "default_time_scale")]
+    pub time_scale: f64,
+    #[serde(default)]
+    pub max_steps: Option<u64>,
+    #[serde(default)]
+    pub max_duration_secs: Option<f64>,
+    /// 仿真时钟起点（各次运行一致，便于对比）；为空时取扫描开始时刻
+    #[serde(default)]
+    pub sim_start_epoch: Option<f64>,
+}
+
+impl SweepSpec {
+    /// 校验并展开参数网格（笛卡尔积），返回各次运行的参数组合
+    pub fn combinations(&self, topology: &Topology) -> Result<Vec<BTreeMap<String, f64>>, String> {
+        if self.parameters.is_empty() {
+            return Err("未指定扫描参数".to_string());
+        }
+        if self.max_steps.is_none() && self.max_duration_secs.is_none() {
+            return Err("需指定每次运行的最大步数或最大仿真时长".to_string());
+        }
+        if self.max_steps == Some(0) || self.max_duration_secs.is_some_and(|d| !d.is_finite() || d <= 0.0) {
+            return Err("最大步数与最大仿真时长必须大于 0".to_string());
+        }
+        if self.calculation_interval_ms == 0 || !self.time_scale.is_finite() || self.time_scale <= 0.0 {
+            return Err("计算间隔与时间倍率必须大于 0".to_string());
+        }
+        expand_grid(&self.parameters, topology)
+    }
+}
+
+/// 展开参数网格（笛卡尔积），返回各组合："设备 ID.属性" -> 取值
+pub fn expand_grid(parameters: &[SweepParameter], topology: &Topology) -> Result<Vec<BTreeMap<String, f64>>, String> {
+    if parameters.is_empty() {
+        return Err("未指定扫描参数".to_string());
+    }
+    let mut grid: Vec<BTreeMap<String, f64>> = vec![BTreeMap::new()];
+    for parameter in parameters {
+        if !topology.devices.contains_key(&parameter.device_id) {
+            return Err(format!("设备不存在: {}", parameter.device_id));
+        }
+        let values = parameter.resolve()?;
+        if grid.len() * values.len() > MAX_SWEEP_RUNS {
+            return Err(format!("组合数超过上限 {}", MAX_SWEEP_RUNS));
+        }
+        grid = grid
+            .into_iter()
+            .flat_map(|combo| {
+                values.iter().map(move |v| {
+                    let mut next = combo.clone();
+                    next.insert(parameter.key(), *v);
+                    next
+                })
+            })
+            .collect();
+    }
+    Ok(grid)
+}
+
+/// 将一组参数取值写入拓扑中对应设备的属性
+pub fn apply_parameters(topology: &mut Topology, parameters: &[SweepParameter], values: &BTreeMap<String, f64>) {
+    for parameter in parameters {
+        if let Some(device) = topology.devices.get_mut(&parameter.device_id) {
+            device.properties.insert(parameter.property.clone(), serde_json::json!(values[&parameter.key()]));
+        }
+    }
+}
+
+/// 一次运行的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SweepRun {
+    pub index: usize,
+    /// "设备 ID.属性" -> 取值
+    pub parameters: BTreeMap<String, f64>,
+    pub db_path: Option<String>,
+    pub summary: Option<RunSummary>,
+    pub error: Option<String>,
+}
+
+/// 扫描结果索引（同时写入输出目录 index.json）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SweepIndex {
+    pub output_dir: String,
+    pub started_at: f64,
+    pub finished_at: f64,
+    pub spec: SweepSpec,
+    pub runs: Vec<SweepRun>,
+}
+
+fn now_s() -> f64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs_f64()
+}
+
+/// 仿真库移入输出目录（跨磁盘时复制后删除原文件）
+fn move_db(from: &Path, to: &Path) -> Result<(), String> {
+    if std::fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    std::fs::copy(from, to).map_err(|e| format!("移动仿真库失败: {}", e))?;
+    let _ = std::fs::remove_file(from);
+    Ok(())
+}
+
+/// 项目级设置（与主仿真一致）
+pub struct SweepSettings {
+    pub sign_convention: SignConvention,
+    pub random_seed: Option<u64>,
+}
+
+/// 运行一次组合：创建独立实例、启动并等待自动停止，删除实例后移动仿真库
+async fn run_one(
+    app: &AppHandle,
+    simulations: &SimulationManager,
+    spec: &SweepSpec,
+    settings: &SweepSettings,
+    topology: Topology,
+    simulation_id: &str,
+    target_db: &Path,
+) -> Result<PathBuf, String> {
+    simulations.create(app, simulation_id, Some("参数扫描".to_string()), topology).await?;
+    let run = async {
+        let engine = simulations.get(Some(simulation_id))?;
+        engine.set_sign_convention(settings.sign_convention.clone());
+        engine.set_random_seed(settings.random_seed);
+        engine.set_keep_kernel_warm(false);
+        engine.set_remote_control_enabled(false);
+        engine.set_run_options(RunOptions {
+            time_scale: spec.time_scale,
+            sim_start_epoch: spec.sim_start_epoch,
+            max_steps: spec.max_steps,
+            max_duration_secs: spec.max_duration_secs,
+            ..RunOptions::default()
+        });
+        engine.start(Some(app.clone()), spec.calculation_interval_ms).await?;
+        while engine.get_status().await.state != SimulationState::Stopped {
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+        Ok::<String, String>(engine.current_db_path())
+    }
+    .await;
+    // 删除实例关闭内核进程与仿真库连接后再移动文件
+    let removed = simulations.remove(simulation_id).await;
+    let db_path = run?;
+    removed?;
+    move_db(Path::new(&db_path), target_db)?;
+    Ok(target_db.to_path_buf())
+}
+
+/// 依次运行全部组合；单次失败记录错误后继续。每次运行结束推送 sweep-progress，全部结束推送 sweep-finished
+pub async fn run_sweep(
+    app: &AppHandle,
+    simulations: &SimulationManager,
+    spec: SweepSpec,
+    base_topology: Topology,
+    settings: SweepSettings,
+) -> Result<SweepIndex, String> {
+    let combinations = spec.combinations(&base_topology)?;
+    let started_at = now_s();
+    let output_dir = match &spec.output_dir {
+        Some(dir) => PathBuf::from(dir),
+        None => std::env::current_dir()
+            .map_err(|e| format!("获取工作目录失败: {}", e))?
+            .join(format!("sweep_{}", started_at as u64)),
+    };
+    std::fs::create_dir_all(&output_dir).map_err(|e| format!("创建输出目录失败: {}", e))?;
+    let mut spec = spec;
+    spec.sim_start_epoch = spec.sim_start_epoch.or(Some(started_at.floor()));
+    let total = combinations.len();
+    let mut runs = Vec::with_capacity(total);
+    for (index, parameters) in combinations.into_iter().enumerate() {
+        let mut topology = base_topology.clone();
+        apply_parameters(&mut topology, &spec.parameters, &parameters);
+        let simulation_id = format!("sweep-{}-{}", started_at as u64 % 100_000, index);
+        let target_db = output_dir.join(format!("run_{:04}.db", index));
+        let outcome = run_one(app, simulations, &spec, &settings, topology, &simulation_id, &target_db).await;
+        let (db_path, summary, error) = match outcome {
+            Ok(path) => {
+                let summary = Database::new(Some(path.as_path()))
+                    .ok()
+                    .and_then(|db| run_summary::load_summary(&db));
+                (Some(path.to_string_lossy().to_string()), summary, None)
+            }
+            Err(e) => {
+                eprintln!("参数扫描第 {} 次运行失败: {}", index + 1, e);
+                (None, None, Some(e))
+            }
+        };
+        let run = SweepRun { index, parameters, db_path, summary, error };
+        let _ = app.emit("sweep-progress", serde_json::json!({
+            "completed": index + 1,
+            "total": total,
+            "run": run,
+        }));
+        runs.push(run);
+    }
+    let index = SweepIndex {
+        output_dir: output_dir.to_string_lossy().to_string(),
+        started_at,
+        finished_at: now_s(),
+        spec,
+        runs,
+    };
+    let json = serde_json::to_string_pretty(&index).map_err(|e| format!("序列化扫描索引失败: {}", e))?;
+    std::fs::write(output_dir.join("index.json"), json).map_err(|e| format!("写入扫描索引失败: {}", e))?;
+    let _ = app.emit("sweep-finished", &index);
+    Ok(index)
+}
+
+/// 读取已有扫描的结果索引
+pub fn load_index(output_dir: &str) -> Result<SweepIndex, String> {
+    let json = std::fs::read_to_string(Path::new(output_dir).join("index.json"))
+        .map_err(|e| format!("读取扫描索引失败: {}", e))?;
+    serde_json::from_str(&json).map_err(|e| format!("解析扫描索引失败: {}", e))
+}
@@ -0,0 +1,201 @@
+// 导入数据时区：不带时区后缀的时间戳（如 2025-12-09 08:00:00）按导入时选择的时区换算为 Unix 秒；
+// 分析按小时分桶（分时电价）时同样按该时区取本地小时，避免现场数据整体偏移 8 小时
+use chrono::{FixedOffset, Local, NaiveDateTime, Offset, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 自动检测时读取的时间戳样本行数
+pub const TIMEZONE_SAMPLE_ROWS: usize = 200;
+
+/// 时区设置；默认 UTC（与旧版本解析结果一致）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ImportTimezone {
+    #[default]
+    Utc,
+    /// 本机系统时区（含夏令时）
+    Local,
+    /// 固定 UTC 偏移（分钟），如中国标准时间 480
+    FixedOffset { offset_minutes: i32 },
+}
+
+impl ImportTimezone {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            Self::FixedOffset { offset_minutes } if offset_minutes.abs() > 14 * 60 => {
+                Err(format!("时区偏移超出范围（±14 小时）: {} 分钟", offset_minutes))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// 本机当前 UTC 偏移对应的固定时区
+    pub fn system_fixed() -> Self {
+        Self::FixedOffset { offset_minutes: Local::now().offset().local_minus_utc() / 60 }
+    }
+
+    /// 展示用标签：UTC / 本机 / UTC+08:00
+    pub fn label(&self) -> String {
+        match self {
+            Self::Utc => "UTC".to_string(),
+            Self::Local => "本机时区".to_string(),
+            Self::FixedOffset { offset_minutes } => format_offset(*offset_minutes),
+        }
+    }
+
+    /// 本地时间（无时区）换算为 Unix 秒；夏令时切换造成的重复时刻取较早者，不存在的时刻返回 None
+    pub fn naive_to_unix(&self, dt: &NaiveDateTime) -> Option<f64> {
+        let millis = match self {
+            Self::Utc => Some(dt.and_utc().timestamp_millis()),
+            Self::Local => Local.from_local_datetime(dt).earliest().map(|d| d.timestamp_millis()),
+            Self::FixedOffset { offset_minutes } => FixedOffset::east_opt(offset_minutes * 60)?
+                .from_local_datetime(dt)
+                .single()
+                .map(|d| d.timestamp_millis()),
+        }?;
+        Some(millis as f64 / 1000.0)
+    }
+
+    /// 指定时刻的 UTC 偏移（秒）
+    pub fn offset_seconds_at(&self, unix: f64) -> i64 {
+        match self {
+            Self::Utc => 0,
+            Self::Local => Utc
+                .timestamp_millis_opt((unix * 1000.0) as i64)
+                .single()
+                .map(|d| Local.offset_from_utc_datetime(&d.naive_utc()).fix().local_minus_utc() as i64)
+                .unwrap_or(0),
+            Self::FixedOffset { offset_minutes } => *offset_minutes as i64 * 60,
+        }
+    }
+
+    /// 该时区下的小时（0..24），用于分时电价分桶
+    pub fn local_hour(&self, unix: f64) -> usize {
+        let local = unix + self.offset_seconds_at(unix) as f64;
+        ((local / 3600.0).floor() as i64).rem_euclid(24) as usize
+    }
+}
+
+fn format_offset(offset_minutes: i32) -> String {
+    let sign = if offset_minutes < 0 { '-' } else { '+' };
+    let m = offset_minutes.abs();
+    format!("UTC{}{:02}:{:02}", sign, m / 60, m % 60)
+}
+
+/// 时间戳字符串的格式类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimestampKind {
+    /// 数值秒 / 毫秒，与时区无关
+    Epoch,
+    /// RFC 3339，自带时区偏移（分钟）
+    WithOffset(i32),
+    /// 无时区后缀，需按导入时区换算
+    Naive,
+}
+
+/// 解析时间戳，返回 (Unix 秒, 格式类别)；无时区后缀的按 tz 换算
+pub fn parse_timestamp_with_kind(s: &str, tz: &ImportTimezone) -> Option<(f64, TimestampKind)> {
+    // 去除前后空格，以及前导单引号（宽表 CSV 中常见）
+    let s = s.trim().trim_start_matches('\'').trim_start_matches('"');
+    if s.is_empty() {
+        return None;
+    }
+    if let Ok(t) = s.parse::<f64>() {
+        let t = if t > 1e12 { t / 1000.0 } else { t };
+        return Some((t, TimestampKind::Epoch));
+    }
+    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(s) {
+        let offset_minutes = dt.offset().local_minus_utc() / 60;
+        return Some((dt.timestamp_millis() as f64 / 1000.0, TimestampKind::WithOffset(offset_minutes)));
+    }
+    ["%Y-%m-%d %H:%M:%S%.3f", "%Y-%m-%d %H:%M:%S"]
+        .iter()
+        .find_map(|fmt| NaiveDateTime::parse_from_str(s, fmt).ok())
+        .and_then(|dt| tz.naive_to_unix(&dt))
+        .map(|t| (t, TimestampKind::Naive))
+}
+
+pub fn parse_timestamp(s: &str, tz: &ImportTimezone) -> Option<f64> {
+    parse_timestamp_with_kind(s, tz).map(|(t, _)| t)
+}
+
+/// 时区自动检测提示（基于时间戳样本与列名，仅供导入界面预选，不自动生效）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimezoneHint {
+    pub timestamp_column: String,
+    pub sampled: usize,
+    pub epoch_count: usize,
+    pub offset_count: usize,
+    pub naive_count: usize,
+    /// 样本中出现的显式偏移（分钟，去重）
+    pub explicit_offsets: Vec<i32>,
+    /// 首个可解析的时间戳原文
+    #[serde(default)]
+    pub first_sample: Option<String>,
+    /// 建议的导入时区（同时用于分析分桶）
+    pub suggested: ImportTimezone,
+    pub suggested_label: String,
+    /// 时区设置是否影响解析结果（样本含无时区后缀的时间戳）
+    pub affects_parsing: bool,
+    pub reason: String,
+}
+
+/// 按时间戳样本给出时区建议：
+/// 含无时区时间戳时：列名为 local_timestamp 建议本机当前偏移，否则保持默认 UTC 并提示核对；全部自带统一偏移时建议该偏移用于分析分桶
+pub fn detect_hint(timestamp_column: &str, samples: &[String]) -> TimezoneHint {
+    let (mut epoch_count, mut offset_count, mut naive_count) = (0, 0, 0);
+    let mut explicit_offsets: Vec<i32> = Vec::new();
+    let mut first_sample = None;
+    for s in samples {
+        let Some((_, kind)) = parse_timestamp_with_kind(s, &ImportTimezone::Utc) else {
+            continue;
+        };
+        if first_sample.is_none() {
+            first_sample = Some(s.trim().to_string());
+        }
+        match kind {
+            TimestampKind::Epoch => epoch_count += 1,
+            TimestampKind::WithOffset(m) => {
+                offset_count += 1;
+                if !explicit_offsets.contains(&m) {
+                    explicit_offsets.push(m);
+                }
+            }
+            TimestampKind::Naive => naive_count += 1,
+        }
+    }
+    let local_column = timestamp_column.eq_ignore_ascii_case("local_timestamp");
+    let (suggested, reason) = if naive_count > 0 {
+        if local_column {
+            (
+                ImportTimezone::system_fixed(),
+                "时间戳无时区后缀且列名为 local_timestamp，应为现场本地时间，建议按本机时区解析".to_string(),
+            )
+        } else {
+            (ImportTimezone::Utc, "时间戳无时区后缀，默认按 UTC 解析；若为现场本地时间请选择对应时区".to_string())
+        }
+    } else if explicit_offsets.len() == 1 {
+        (
+            ImportTimezone::FixedOffset { offset_minutes: explicit_offsets[0] },
+            "时间戳自带时区偏移，解析不受影响；建议按该偏移进行分析分桶".to_string(),
+        )
+    } else if epoch_count > 0 && offset_count == 0 {
+        (ImportTimezone::system_fixed(), "时间戳为 Unix 时间，解析不受影响；建议按本机时区进行分析分桶".to_string())
+    } else if offset_count > 0 {
+        (ImportTimezone::Utc, "时间戳含多个不同时区偏移，请手动选择分析时区".to_string())
+    } else {
+        (ImportTimezone::Utc, "未找到可解析的时间戳".to_string())
+    };
+    TimezoneHint {
+        timestamp_column: timestamp_column.to_string(),
+        sampled: samples.len(),
+        epoch_count,
+        offset_count,
+        naive_count,
+        explicit_offsets,
+        first_sample,
+        suggested_label: suggested.label(),
+        suggested,
+        affects_parsing: naive_count > 0,
+        reason,
+    }
+}
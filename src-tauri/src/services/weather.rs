@@ -0,0 +1,313 @@
+// 气象数据源：绑定到仿真的温度 / 辐照 / 风速时间序列（本地 CSV 文件或 HTTP 接口返回的 JSON），
+// 按仿真时间插值，驱动光伏辐照模型（实测辐照与组件温度折减）、风机功率曲线与温度相关负荷（weather_model 模式）
+use crate::domain::topology::{prop_f64, rated_power_kw, Device, DeviceType};
+use crate::services::database::Database;
+use crate::services::solar_model::{self, ClearSkyIrradiance, SolarPanelConfig};
+use crate::services::timezone::{parse_timestamp, ImportTimezone};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// 仿真库中本次运行绑定的气象数据源的元数据键
+const META_WEATHER_SOURCE: &str = "weather_source";
+/// HTTP 气象接口请求超时（秒）
+const HTTP_TIMEOUT_SECS: u64 = 30;
+
+/// 气象数据源描述（前端传入），按 kind 区分
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum WeatherSourceSpec {
+    /// 本地 CSV：时间列 timestamp/time，数据列 temperature_c / ghi_w_m2 / wind_speed_m_s（均可缺省）
+    File {
+        path: String,
+        #[serde(default)]
+        timezone: ImportTimezone,
+    },
+    /// HTTP 接口：GET 返回 JSON 数组（或 { "points": [...] }），元素字段同 CSV 列名
+    Http {
+        url: String,
+        #[serde(default)]
+        headers: HashMap<String, String>,
+    },
+}
+
+/// 一个气象数据点；缺测项为 None
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub struct WeatherSample {
+    /// Unix 秒
+    pub at: f64,
+    #[serde(default)]
+    pub temperature_c: Option<f64>,
+    /// 水平面总辐照（W/m²）
+    #[serde(default)]
+    pub ghi_w_m2: Option<f64>,
+    /// 轮毂高度风速（m/s）
+    #[serde(default)]
+    pub wind_speed_m_s: Option<f64>,
+}
+
+/// 字段别名（CSV 表头小写 / JSON 键）
+const TIME_KEYS: &[&str] = &["timestamp", "time", "时间"];
+const TEMPERATURE_KEYS: &[&str] = &["temperature_c", "temperature", "temp_c", "温度"];
+const GHI_KEYS: &[&str] = &["ghi_w_m2", "ghi", "irradiance", "辐照"];
+const WIND_KEYS: &[&str] = &["wind_speed_m_s", "wind_speed", "wind", "风速"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeatherSeries {
+    pub source: WeatherSourceSpec,
+    pub samples: Vec<WeatherSample>,
+}
+
+impl WeatherSeries {
+    /// 加载数据源（HTTP 源在此一次性拉取）
+    pub async fn load(source: WeatherSourceSpec) -> Result<Self, String> {
+        let mut samples = match &source {
+            WeatherSourceSpec::File { path, timezone } => parse_csv(path, timezone)?,
+            WeatherSourceSpec::Http { url, headers } => fetch_http(url, headers).await?,
+        };
+        samples.sort_by(|a, b| a.at.total_cmp(&b.at));
+        samples.dedup_by(|b, a| b.at == a.at);
+        if samples.is_empty() {
+            return Err("气象数据为空".to_string());
+        }
+        Ok(Self { source, samples })
+    }
+
+    pub fn time_range(&self) -> (f64, f64) {
+        (self.samples[0].at, self.samples[self.samples.len() - 1].at)
+    }
+
+    /// 仿真时刻的气象值：相邻数据点线性插值（任一侧缺测的量取有值一侧），超出范围保持首末点
+    pub fn sample_at(&self, timestamp: f64) -> WeatherSample {
+        let i = self.samples.partition_point(|s| s.at <= timestamp);
+        let (a, b) = match i {
+            0 => return WeatherSample { at: timestamp, ..self.samples[0] },
+            i if i == self.samples.len() => return WeatherSample { at: timestamp, ..self.samples[i - 1] },
+            i => (self.samples[i - 1], self.samples[i]),
+        };
+        let ratio = (timestamp - a.at) / (b.at - a.at);
+        let lerp = |x: Option<f64>, y: Option<f64>| match (x, y) {
+            (Some(x), Some(y)) => Some(x + (y - x) * ratio),
+            (x, y) => x.or(y),
+        };
+        WeatherSample {
+            at: timestamp,
+            temperature_c: lerp(a.temperature_c, b.temperature_c),
+            ghi_w_m2: lerp(a.ghi_w_m2, b.ghi_w_m2),
+            wind_speed_m_s: lerp(a.wind_speed_m_s, b.wind_speed_m_s),
+        }
+    }
+}
+
+fn parse_csv(path: &str, timezone: &ImportTimezone) -> Result<Vec<WeatherSample>, String> {
+    timezone.validate()?;
+    let mut reader = csv::Reader::from_path(path).map_err(|e| format!("打开气象文件失败: {}", e))?;
+    let headers: Vec<String> = reader
+        .headers()
+        .map_err(|e| format!("读取表头失败: {}", e))?
+        .iter()
+        .map(|h| h.trim().to_lowercase())
+        .collect();
+    let find = |names: &[&str]| headers.iter().position(|h| names.contains(&h.as_str()));
+    let time_col = find(TIME_KEYS).ok_or("气象文件缺少时间列 timestamp/time")?;
+    let (temp_col, ghi_col, wind_col) = (find(TEMPERATURE_KEYS), find(GHI_KEYS), find(WIND_KEYS));
+    if temp_col.is_none() && ghi_col.is_none() && wind_col.is_none() {
+        return Err("气象文件缺少温度、辐照或风速列".to_string());
+    }
+    let mut samples = Vec::new();
+    for (i, record) in reader.records().enumerate() {
+        let record = record.map_err(|e| format!("第 {} 行读取失败: {}", i + 2, e))?;
+        let raw_time = record.get(time_col).unwrap_or("");
+        let at = parse_timestamp(raw_time, timezone).ok_or_else(|| format!("第 {} 行时间无法解析: {}", i + 2, raw_time))?;
+        let value = |col: Option<usize>| {
+            col.and_then(|c| record.get(c))
+                .and_then(|v| v.trim().parse::<f64>().ok())
+                .filter(|v| v.is_finite())
+        };
+        samples.push(WeatherSample {
+            at,
+            temperature_c: value(temp_col),
+            ghi_w_m2: value(ghi_col),
+            wind_speed_m_s: value(wind_col),
+        });
+    }
+    Ok(samples)
+}
+
+async fn fetch_http(url: &str, headers: &HashMap<String, String>) -> Result<Vec<WeatherSample>, String> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(HTTP_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| format!("创建 HTTP 客户端失败: {}", e))?;
+    let mut request = client.get(url);
+    for (name, value) in headers {
+        request = request.header(name, value);
+    }
+    let response = request.send().await.map_err(|e| format!("请求气象接口失败: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("气象接口返回 HTTP {}", response.status()));
+    }
+    let body = response.text().await.map_err(|e| format!("读取气象接口响应失败: {}", e))?;
+    let value: serde_json::Value = serde_json::from_str(&body).map_err(|e| format!("气象接口响应不是 JSON: {}", e))?;
+    let rows = value
+        .as_array()
+        .or_else(|| value.get("points").and_then(|p| p.as_array()))
+        .ok_or("气象接口响应须为数组或含 points 数组")?;
+    let pick = |row: &serde_json::Value, keys: &[&str]| {
+        keys.iter()
+            .find_map(|k| row.get(*k))
+            .and_then(|v| v.as_f64().or_else(|| v.as_str().and_then(|s| s.trim().parse().ok())))
+            .filter(|v: &f64| v.is_finite())
+    };
+    rows.iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let at = TIME_KEYS
+                .iter()
+                .find_map(|k| row.get(*k))
+                .and_then(|v| match v {
+                    serde_json::Value::String(s) => parse_timestamp(s, &ImportTimezone::Utc),
+                    v => v.as_f64().map(|t| if t > 1e12 { t / 1000.0 } else { t }),
+                })
+                .ok_or_else(|| format!("第 {} 个数据点缺少有效时间", i + 1))?;
+            Ok(WeatherSample {
+                at,
+                temperature_c: pick(row, TEMPERATURE_KEYS),
+                ghi_w_m2: pick(row, GHI_KEYS),
+                wind_speed_m_s: pick(row, WIND_KEYS),
+            })
+        })
+        .collect()
+}
+
+/// 本次运行绑定的气象数据源摘要
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeatherBinding {
+    pub source: WeatherSourceSpec,
+    pub sample_count: usize,
+    pub start_at: f64,
+    pub end_at: f64,
+}
+
+impl WeatherBinding {
+    pub fn of(series: &WeatherSeries) -> Self {
+        let (start_at, end_at) = series.time_range();
+        Self { source: series.source.clone(), sample_count: series.samples.len(), start_at, end_at }
+    }
+}
+
+/// 记录本次运行绑定的气象数据源（供结果分析追溯输入）
+pub fn record_binding(db: &Database, binding: &WeatherBinding) {
+    match serde_json::to_string(binding) {
+        Ok(json) => {
+            if let Err(e) = db.set_meta_text(META_WEATHER_SOURCE, &json) {
+                eprintln!("写入气象数据源记录失败: {}", e);
+            }
+        }
+        Err(e) => eprintln!("序列化气象数据源记录失败: {}", e),
+    }
+}
+
+/// 读取仿真库记录的气象数据源（未绑定或旧库为 None）
+pub fn load_binding(db: &Database) -> Option<WeatherBinding> {
+    db.get_meta_text(META_WEATHER_SOURCE)
+        .ok()
+        .flatten()
+        .and_then(|json| serde_json::from_str(&json).ok())
+}
+
+/// Erbs 分解：由水平面总辐照与太阳天顶角估算直射 / 散射分量
+fn decompose_ghi(ghi: f64, zenith_deg: f64) -> ClearSkyIrradiance {
+    let cos_z = zenith_deg.to_radians().cos();
+    if ghi <= 0.0 || cos_z <= 0.01 {
+        return ClearSkyIrradiance { ghi: ghi.max(0.0), dni: 0.0, dhi: ghi.max(0.0) };
+    }
+    let kt = (ghi / (1361.0 * cos_z)).clamp(0.0, 1.0);
+    let diffuse_fraction = if kt <= 0.22 {
+        1.0 - 0.09 * kt
+    } else if kt <= 0.8 {
+        0.9511 - 0.1604 * kt + 4.388 * kt.powi(2) - 16.638 * kt.powi(3) + 12.336 * kt.powi(4)
+    } else {
+        0.165
+    };
+    let dhi = ghi * diffuse_fraction;
+    ClearSkyIrradiance { ghi, dni: ((ghi - dhi) / cos_z).max(0.0), dhi }
+}
+
+/// 光伏功率温度系数缺省值（%/°C）
+pub const DEFAULT_PV_TEMP_COEFF_PCT_PER_C: f64 = -0.4;
+
+/// 按 NOCT（800 W/m²、环境 20°C 时电池 45°C）由环境温度与组件面辐照估算电池温度（°C）
+pub fn cell_temperature_c(ambient_c: f64, poa_w_m2: f64) -> f64 {
+    ambient_c + (45.0 - 20.0) / 800.0 * poa_w_m2
+}
+
+/// 温度折减系数：coeff_pct_per_c 为功率温度系数（%/°C），相对 25°C 电池温度
+pub fn temperature_derate(coeff_pct_per_c: f64, cell_c: f64) -> f64 {
+    (1.0 + coeff_pct_per_c / 100.0 * (cell_c - 25.0)).max(0.0)
+}
+
+/// 光伏出力（kW）：有实测辐照时按实测辐照换算到倾斜面，否则按晴空辐照；有气温时按 NOCT 估算电池温度，
+/// 以 pv_temp_coeff_pct_per_c（缺省 -0.4 %/°C）相对 25°C 折减（模型标定写回同一属性）
+pub fn pv_output_kw(device: &Device, config: &SolarPanelConfig, weather: Option<&WeatherSample>, timestamp: f64) -> f64 {
+    let Some(weather) = weather.filter(|w| w.ghi_w_m2.is_some() || w.temperature_c.is_some()) else {
+        return solar_model::pv_output_kw(config, timestamp);
+    };
+    let position = solar_model::solar_position(config.latitude, config.longitude, timestamp);
+    let sky = match weather.ghi_w_m2 {
+        Some(ghi) => decompose_ghi(ghi, position.zenith_deg),
+        None => solar_model::clear_sky(position.zenith_deg),
+    };
+    let poa = solar_model::plane_of_array(config, position, sky);
+    let mut p_kw = config.kwp * poa / 1000.0 * config.performance_ratio;
+    if let Some(ambient) = weather.temperature_c {
+        let coeff = prop_f64(&device.properties, "pv_temp_coeff_pct_per_c").unwrap_or(DEFAULT_PV_TEMP_COEFF_PCT_PER_C);
+        p_kw *= temperature_derate(coeff, cell_temperature_c(ambient, poa));
+    }
+    match config.rated_kw {
+        Some(rated) => p_kw.clamp(0.0, rated),
+        None => p_kw.max(0.0),
+    }
+}
+
+/// weather_model 模式的设备出力（kW，内核原生约定）：
+/// - 风机（静态发电机且 generator_kind = "wind"）：按功率曲线，切入 wind_cut_in_m_s（缺省 3）到额定 wind_rated_m_s（缺省 12）
+///   之间按风速三次方增长，超过切出 wind_cut_out_m_s（缺省 25）停机
+/// - 其余光伏：同辐照模型（使用实测辐照与气温）
+/// - 负荷：基准功率 load_base_kw（缺省取额定功率，见 rated_power_kw）在舒适温区 [load_comfort_low_c, load_comfort_high_c]（缺省 18–24°C）
+///   外每偏离 1°C 增加 load_temp_coeff_pct_per_c（缺省 2 %）
+///
+/// 缺少所需气象量或参数时返回 None（不下发，保持上一设定）
+pub fn weather_model_output_kw(device: &Device, weather: Option<&WeatherSample>, timestamp: f64) -> Option<f64> {
+    let p = &device.properties;
+    match device.device_type {
+        DeviceType::Pv if p.get("generator_kind").and_then(|v| v.as_str()) == Some("wind") => {
+            let rated_kw = rated_power_kw(p).filter(|v| *v > 0.0)?;
+            let speed = weather?.wind_speed_m_s?.max(0.0);
+            let cut_in = prop_f64(p, "wind_cut_in_m_s").unwrap_or(3.0);
+            let rated_speed = prop_f64(p, "wind_rated_m_s").unwrap_or(12.0).max(cut_in + 0.1);
+            let cut_out = prop_f64(p, "wind_cut_out_m_s").unwrap_or(25.0);
+            Some(if speed < cut_in || speed >= cut_out {
+                0.0
+            } else if speed >= rated_speed {
+                rated_kw
+            } else {
+                rated_kw * (speed.powi(3) - cut_in.powi(3)) / (rated_speed.powi(3) - cut_in.powi(3))
+            })
+        }
+        DeviceType::Pv => {
+            let config = SolarPanelConfig::from_device(device).ok()?;
+            Some(pv_output_kw(device, &config, weather, timestamp))
+        }
+        DeviceType::Load => {
+            let base_kw = prop_f64(p, "load_base_kw").or_else(|| rated_power_kw(p))?;
+            let temperature = weather?.temperature_c?;
+            let low = prop_f64(p, "load_comfort_low_c").unwrap_or(18.0);
+            let high = prop_f64(p, "load_comfort_high_c").unwrap_or(24.0).max(low);
+            let coeff = prop_f64(p, "load_temp_coeff_pct_per_c").unwrap_or(2.0);
+            let excursion = (low - temperature).max(0.0) + (temperature - high).max(0.0);
+            Some((base_kw * (1.0 + coeff / 100.0 * excursion)).max(0.0))
+        }
+        _ => None,
+    }
+}
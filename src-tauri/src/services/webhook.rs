@@ -0,0 +1,180 @@
+// Webhook 投递：事件发生时向订阅的地址 POST JSON，失败按指数退避重试；投递在后台任务中进行，不阻塞仿真循环
+use crate::domain::webhook::{WebhookConfig, WebhookDelivery, WebhookEvent};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// 投递记录保留上限
+const MAX_DELIVERY_LOG: usize = 200;
+/// 单次请求超时
+const REQUEST_TIMEOUT_SECS: u64 = 10;
+
+pub struct WebhookDispatcher {
+    client: reqwest::Client,
+    configs: Mutex<Vec<WebhookConfig>>,
+    deliveries: Arc<Mutex<VecDeque<WebhookDelivery>>>,
+}
+
+fn now_secs() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs_f64()
+}
+
+/// 签名：HMAC-SHA256(secret, "{timestamp}.{body}")，十六进制小写
+fn sign(secret: &str, timestamp: &str, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC 接受任意长度密钥");
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+impl WebhookDispatcher {
+    pub fn new(configs: Vec<WebhookConfig>) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .build()
+            .unwrap_or_default();
+        Self {
+            client,
+            configs: Mutex::new(configs),
+            deliveries: Arc::new(Mutex::new(VecDeque::new())),
+        }
+    }
+
+    pub fn set_configs(&self, configs: Vec<WebhookConfig>) {
+        *self.configs.lock().unwrap() = configs;
+    }
+
+    /// 向订阅该事件的全部 Webhook 投递（后台进行）
+    pub fn notify(&self, event: WebhookEvent, data: serde_json::Value) {
+        let targets: Vec<WebhookConfig> = self
+            .configs
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|c| c.subscribes(event))
+            .cloned()
+            .collect();
+        for config in targets {
+            self.spawn_delivery(config, event, data.clone());
+        }
+    }
+
+    /// 向指定 Webhook 发送一条测试消息（忽略订阅与启用状态），等待投递结果
+    pub async fn send_test(&self, config: WebhookConfig) -> WebhookDelivery {
+        let data = serde_json::json!({ "message": "PVSC 微电网模拟器 Webhook 测试" });
+        let event = config.events.first().copied().unwrap_or(WebhookEvent::ScenarioCompleted);
+        let delivery = deliver(&self.client, &config, event, &data).await;
+        push_delivery(&self.deliveries, delivery.clone());
+        delivery
+    }
+
+    pub fn deliveries(&self) -> Vec<WebhookDelivery> {
+        self.deliveries.lock().unwrap().iter().cloned().collect()
+    }
+
+    fn spawn_delivery(&self, config: WebhookConfig, event: WebhookEvent, data: serde_json::Value) {
+        let client = self.client.clone();
+        let deliveries = self.deliveries.clone();
+        tauri::async_runtime::spawn(async move {
+            let delivery = deliver(&client, &config, event, &data).await;
+            if !delivery.success {
+                eprintln!(
+                    "Webhook 投递失败 {} ({}): {}",
+                    config.name,
+                    event.as_str(),
+                    delivery.error.as_deref().unwrap_or("未知错误")
+                );
+            }
+            push_delivery(&deliveries, delivery);
+        });
+    }
+}
+
+fn push_delivery(deliveries: &Mutex<VecDeque<WebhookDelivery>>, delivery: WebhookDelivery) {
+    let mut log = deliveries.lock().unwrap();
+    log.push_back(delivery);
+    while log.len() > MAX_DELIVERY_LOG {
+        log.pop_front();
+    }
+}
+
+/// 投递一次事件：非 2xx 或网络错误时按 1s、2s、4s… 退避重试，最多 max_retries 次
+async fn deliver(
+    client: &reqwest::Client,
+    config: &WebhookConfig,
+    event: WebhookEvent,
+    data: &serde_json::Value,
+) -> WebhookDelivery {
+    let timestamp = now_secs();
+    let body = serde_json::json!({
+        "event": event,
+        "timestamp": timestamp,
+        "data": data,
+    })
+    .to_string();
+    let ts_header = format!("{}", timestamp as u64);
+    let signature = config
+        .secret
+        .as_deref()
+        .filter(|s| !s.is_empty())
+        .map(|secret| format!("sha256={}", sign(secret, &ts_header, &body)));
+
+    let mut attempts = 0;
+    let mut last_status = None;
+    let mut last_error = None;
+    while attempts <= config.max_retries {
+        if attempts > 0 {
+            tokio::time::sleep(Duration::from_secs(1 << (attempts - 1).min(6))).await;
+        }
+        attempts += 1;
+        let mut request = client
+            .post(&config.url)
+            .header("Content-Type", "application/json")
+            .header("X-PVSC-Event", event.as_str())
+            .header("X-PVSC-Timestamp", &ts_header)
+            .body(body.clone());
+        if let Some(ref sig) = signature {
+            request = request.header("X-PVSC-Signature", sig);
+        }
+        match request.send().await {
+            Ok(resp) if resp.status().is_success() => {
+                return WebhookDelivery {
+                    webhook_id: config.id.clone(),
+                    event,
+                    timestamp,
+                    attempts,
+                    success: true,
+                    status_code: Some(resp.status().as_u16()),
+                    error: None,
+                };
+            }
+            Ok(resp) => {
+                last_status = Some(resp.status().as_u16());
+                last_error = Some(format!("HTTP {}", resp.status()));
+            }
+            Err(e) => {
+                last_status = None;
+                last_error = Some(e.to_string());
+            }
+        }
+    }
+    WebhookDelivery {
+        webhook_id: config.id.clone(),
+        event,
+        timestamp,
+        attempts,
+        success: false,
+        status_code: last_status,
+        error: last_error,
+    }
+}
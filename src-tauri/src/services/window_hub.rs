@@ -0,0 +1,154 @@
+// 多窗口状态共享：各窗口按事件名与设备过滤订阅后端事件流，并可获取与某一计算步对齐的一致系统快照
+// 未登记订阅的窗口（如主窗口）照常接收全部事件；登记订阅的窗口只收到匹配的事件
+use crate::domain::events::EventPayload;
+use crate::domain::simulation::{DeviceWorkModes, SimulationStatus, StorageState};
+use crate::services::limit_monitor::LimitAlert;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, EventTarget, Manager};
+
+/// 窗口的订阅：events 为空表示全部事件；device_ids 为 None 表示全部设备（仅对带设备 ID 的事件生效）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WindowSubscription {
+    #[serde(default)]
+    pub events: Vec<String>,
+    #[serde(default)]
+    pub device_ids: Option<Vec<String>>,
+}
+
+impl WindowSubscription {
+    fn accepts(&self, event: &str, device_id: Option<&str>) -> bool {
+        if !self.events.is_empty() && !self.events.iter().any(|e| e == event) {
+            return false;
+        }
+        match (device_id, &self.device_ids) {
+            (Some(id), Some(ids)) => ids.iter().any(|d| d == id),
+            _ => true,
+        }
+    }
+}
+
+/// 一个计算步提交时的状态（与 calculation-result-update 为同一步）；提交后发出 state-step-committed，
+/// 窗口据此判断快照之后的事件（seq 大于快照 seq 的步）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CommittedStep {
+    pub seq: u64,
+    pub timestamp: f64,
+    /// 本步上报的计算结果（已应用传感器延迟）
+    pub calculation_result: serde_json::Value,
+    /// 设备 ID -> (时间戳, 有功 kW, 无功 kvar)，内核原生约定
+    pub device_power: HashMap<String, (f64, Option<f64>, Option<f64>)>,
+    pub storage_states: HashMap<String, StorageState>,
+}
+
+/// 一致系统快照
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SystemSnapshot {
+    pub status: SimulationStatus,
+    /// 最近一次提交的计算步；仿真未产生结果时为 None
+    pub step: Option<CommittedStep>,
+    pub active_alerts: Vec<LimitAlert>,
+    pub device_modes: DeviceWorkModes,
+}
+
+#[derive(Default)]
+pub struct WindowEventHub {
+    subscriptions: Mutex<HashMap<String, WindowSubscription>>,
+    last_step: Mutex<Option<CommittedStep>>,
+    next_seq: Mutex<u64>,
+}
+
+impl WindowEventHub {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn subscribe(&self, window: &str, subscription: WindowSubscription) {
+        self.subscriptions.lock().unwrap().insert(window.to_string(), subscription);
+    }
+
+    pub fn unsubscribe(&self, window: &str) -> bool {
+        self.subscriptions.lock().unwrap().remove(window).is_some()
+    }
+
+    pub fn subscriptions(&self) -> HashMap<String, WindowSubscription> {
+        self.subscriptions.lock().unwrap().clone()
+    }
+
+    /// 仅保留仍存在的窗口的订阅（窗口关闭后清理）
+    pub fn retain_windows(&self, alive: &HashSet<String>) {
+        self.subscriptions.lock().unwrap().retain(|label, _| alive.contains(label));
+    }
+
+    /// 提交一个计算步，返回其序号
+    pub fn commit_step(
+        &self,
+        timestamp: f64,
+        calculation_result: serde_json::Value,
+        device_power: HashMap<String, (f64, Option<f64>, Option<f64>)>,
+        storage_states: HashMap<String, StorageState>,
+    ) -> u64 {
+        let seq = {
+            let mut next = self.next_seq.lock().unwrap();
+            *next += 1;
+            *next
+        };
+        *self.last_step.lock().unwrap() = Some(CommittedStep {
+            seq,
+            timestamp,
+            calculation_result,
+            device_power,
+            storage_states,
+        });
+        seq
+    }
+
+    /// 新一轮仿真：清空上次的步状态，序号继续递增（窗口不会把旧步误认为新步）
+    pub fn reset_steps(&self) {
+        *self.last_step.lock().unwrap() = None;
+    }
+
+    pub fn last_step(&self) -> Option<CommittedStep> {
+        self.last_step.lock().unwrap().clone()
+    }
+
+    fn target_label(target: &EventTarget) -> Option<&str> {
+        match target {
+            EventTarget::Window { label } | EventTarget::Webview { label } | EventTarget::WebviewWindow { label } => {
+                Some(label.as_str())
+            }
+            _ => None,
+        }
+    }
+
+    /// 按订阅过滤发送事件：无订阅的窗口照常接收
+    pub fn emit<S: Serialize + Clone>(&self, app: &AppHandle, event: &str, device_id: Option<&str>, payload: S) {
+        let subscriptions = self.subscriptions.lock().unwrap().clone();
+        if subscriptions.is_empty() {
+            let _ = app.emit(event, payload);
+            return;
+        }
+        let _ = app.emit_filter(event, payload, |target| {
+            match Self::target_label(target).and_then(|label| subscriptions.get(label)) {
+                Some(sub) => sub.accepts(event, device_id),
+                None => true,
+            }
+        });
+    }
+}
+
+/// 经应用状态中的订阅中心发送事件；未注册订阅中心时直接广播
+pub fn publish<S: Serialize + Clone>(app: &AppHandle, event: &str, device_id: Option<&str>, payload: S) {
+    match app.try_state::<Arc<WindowEventHub>>() {
+        Some(hub) => hub.emit(app, event, device_id, payload),
+        None => {
+            let _ = app.emit(event, payload);
+        }
+    }
+}
+
+/// 发送类型化事件（事件名取自负载类型）
+pub fn publish_typed<T: EventPayload + Clone>(app: &AppHandle, device_id: Option<&str>, payload: T) {
+    publish(app, T::EVENT, device_id, payload);
+}
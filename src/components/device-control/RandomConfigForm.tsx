@@ -2,7 +2,17 @@
  * 随机数据源配置表单组件 - 浅色主题
  */
 import { useState, useEffect, useCallback } from 'react';
-import { RandomConfig } from '../../types/dataSource';
+import { RandomConfig, RandomProfile } from '../../types/dataSource';
+
+type ProfileKind = RandomProfile['kind'] | 'auto';
+
+const PROFILE_OPTIONS: { value: ProfileKind; label: string }[] = [
+  { value: 'auto', label: '按设备类型自动' },
+  { value: 'uniform', label: '均匀白噪声' },
+  { value: 'ou_noise', label: '负荷均值回归噪声 (OU)' },
+  { value: 'clear_sky_pv', label: '光伏晴空 + 云遮' },
+  { value: 'charging_blocks', label: '充电桩充电块' },
+];
 
 interface RandomConfigFormProps {
   deviceName: string;
@@ -16,6 +26,7 @@ export default function RandomConfigForm({ deviceName, initialValue, onSave, onC
   const [maxPower, setMaxPower] = useState(initialValue?.maxPower ?? 100);
   const [updateInterval, setUpdateInterval] = useState(initialValue?.updateInterval ?? 1);
   const [volatility, setVolatility] = useState(initialValue?.volatility ?? 0.1);
+  const [profileKind, setProfileKind] = useState<ProfileKind>(initialValue?.profile?.kind ?? 'auto');
 
   useEffect(() => {
     if (initialValue) {
@@ -23,13 +34,20 @@ export default function RandomConfigForm({ deviceName, initialValue, onSave, onC
       setMaxPower(initialValue.maxPower);
       setUpdateInterval(initialValue.updateInterval);
       setVolatility(initialValue.volatility);
+      setProfileKind(initialValue.profile?.kind ?? 'auto');
     }
   }, [initialValue]);
 
   const handleSubmit = useCallback((e: React.FormEvent) => {
     e.preventDefault();
-    onSave({ minPower, maxPower, updateInterval, volatility });
-  }, [minPower, maxPower, updateInterval, volatility, onSave]);
+    let profile: RandomProfile | undefined;
+    if (profileKind === 'ou_noise') {
+      profile = { kind: 'ou_noise', volatility };
+    } else if (profileKind !== 'auto') {
+      profile = { kind: profileKind } as RandomProfile;
+    }
+    onSave({ minPower, maxPower, updateInterval, volatility, profile });
+  }, [minPower, maxPower, updateInterval, volatility, profileKind, onSave]);
 
   const isPowerRangeValid = minPower <= maxPower;
 
@@ -51,6 +69,14 @@ export default function RandomConfigForm({ deviceName, initialValue, onSave, onC
           </div>
         </div>
         {!isPowerRangeValid && <div className="text-xs text-red-500">最小功率不能大于最大功率</div>}
+        <div>
+          <label className="block text-xs font-medium text-gray-600 mb-1">生成模型</label>
+          <select value={profileKind} onChange={(e) => setProfileKind(e.target.value as ProfileKind)} className="w-full px-2 py-1 bg-white border border-gray-300 rounded text-sm">
+            {PROFILE_OPTIONS.map((o) => (
+              <option key={o.value} value={o.value}>{o.label}</option>
+            ))}
+          </select>
+        </div>
         <div>
           <label className="block text-xs font-medium text-gray-600 mb-1">更新间隔 (秒)</label>
           <div className="flex items-center gap-2">
@@ -1,5 +1,5 @@
 /**
- * 设备级仿真参数配置表单：采集频率、响应延迟、测量误差
+ * 设备级仿真参数配置表单：采集频率、传感器延迟、响应延迟、测量误差
  */
 import { useState, useCallback } from 'react';
 import { Settings, Clock, Activity, Gauge } from 'lucide-react';
@@ -15,6 +15,7 @@ interface SimParamsFormProps {
 const DEFAULT_PARAMS: DeviceSimParams = {
   samplingIntervalMs: 0,
   responseDelayMs: 0,
+  sensorDelayMs: 0,
   measurementErrorPct: 0,
 };
 
@@ -58,6 +59,25 @@ export default function SimParamsForm({ deviceName, initialValue, onSave, onCanc
           <p className="text-xs text-gray-400 mt-0.5">0 = 每步更新。可设置大于仿真周期的值以降低更新频率。</p>
         </div>
 
+        {/* 传感器延迟 */}
+        <div>
+          <label className="flex items-center gap-1 text-xs font-medium text-gray-600 mb-1">
+            <Clock className="w-3 h-3" />传感器延迟（测量值上报）
+          </label>
+          <div className="flex items-center gap-2">
+            <input
+              type="number"
+              min="0"
+              step="100"
+              value={params.sensorDelayMs ?? 0}
+              onChange={(e) => handleChange('sensorDelayMs', Number(e.target.value))}
+              className="flex-1 px-2 py-1 bg-white border border-gray-300 rounded text-sm"
+            />
+            <span className="text-xs text-gray-500 shrink-0">毫秒</span>
+          </div>
+          <p className="text-xs text-gray-400 mt-0.5">0 = 无延迟。显示、落库与 Modbus 读数为此时间之前的测量值。</p>
+        </div>
+
         {/* 响应延迟 */}
         <div>
           <label className="flex items-center gap-1 text-xs font-medium text-gray-600 mb-1">
@@ -0,0 +1,100 @@
+/**
+ * 中断仿真恢复提示 - 启动时检测到上次未正常结束的仿真，提示继续或放弃
+ */
+import { useState, useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { X, RotateCcw } from 'lucide-react';
+
+interface InterruptedRun {
+  db_path: string;
+  started_at: number | null;
+  last_timestamp: number | null;
+  checkpoint_at: number | null;
+  topology_name: string;
+  device_count: number;
+}
+
+const formatTs = (ts: number | null) =>
+  ts ? new Date(ts * 1000).toLocaleString('zh-CN', { hour12: false }) : '-';
+
+export default function InterruptedRunPrompt() {
+  const [run, setRun] = useState<InterruptedRun | null>(null);
+  const [busy, setBusy] = useState(false);
+
+  useEffect(() => {
+    // 内核就绪事件可能早于本组件挂载，挂载时主动查询一次
+    invoke<InterruptedRun | null>('get_interrupted_run')
+      .then((r) => { if (r) setRun(r); })
+      .catch(() => {});
+    const unlisten = listen<InterruptedRun>('interrupted-run-detected', (event) => {
+      setRun(event.payload);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  if (!run) return null;
+
+  const handleResume = async () => {
+    setBusy(true);
+    try {
+      await invoke('resume_interrupted_run', { dbPath: run.db_path });
+      setRun(null);
+    } catch (err) {
+      alert('恢复仿真失败：' + err);
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  const handleDismiss = async () => {
+    setBusy(true);
+    try {
+      await invoke('dismiss_interrupted_run', { dbPath: run.db_path });
+      setRun(null);
+    } catch (err) {
+      alert('放弃恢复失败：' + err);
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  return (
+    <div className="fixed inset-0 bg-black/30 flex items-center justify-center z-50">
+      <div className="bg-white rounded-lg shadow-xl w-full max-w-md mx-4">
+        <div className="flex items-center justify-between p-3 border-b border-gray-200">
+          <h3 className="text-sm font-semibold text-gray-800">检测到未正常结束的仿真</h3>
+          <button onClick={() => setRun(null)} className="p-1 hover:bg-gray-100 rounded transition-colors">
+            <X className="w-4 h-4 text-gray-500" />
+          </button>
+        </div>
+        <div className="p-3 space-y-1 text-xs text-gray-600">
+          <div>拓扑：{run.topology_name || '-'}（{run.device_count} 个设备）</div>
+          <div>开始时间：{formatTs(run.started_at)}</div>
+          <div>最后数据：{formatTs(run.last_timestamp)}</div>
+          <div>最近检查点：{formatTs(run.checkpoint_at)}</div>
+          <div className="text-gray-400 break-all">{run.db_path}</div>
+        </div>
+        <div className="flex justify-end gap-2 p-3 border-t border-gray-200">
+          <button
+            onClick={handleDismiss}
+            disabled={busy}
+            className="px-3 py-1.5 text-xs text-gray-600 hover:bg-gray-100 rounded disabled:opacity-50"
+          >
+            放弃
+          </button>
+          <button
+            onClick={handleResume}
+            disabled={busy}
+            className="flex items-center gap-1 px-3 py-1.5 text-xs bg-blue-500 text-white rounded hover:bg-blue-600 disabled:opacity-50"
+          >
+            <RotateCcw className="w-3.5 h-3.5" />
+            继续仿真
+          </button>
+        </div>
+      </div>
+    </div>
+  );
+}
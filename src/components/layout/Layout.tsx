@@ -2,6 +2,7 @@ import { ReactNode } from "react";
 import Sidebar from "./Sidebar";
 import TopBar from "./TopBar";
 import StatusBar from "./StatusBar";
+import InterruptedRunPrompt from "./InterruptedRunPrompt";
 
 interface LayoutProps {
   children: ReactNode;
@@ -18,6 +19,7 @@ export default function Layout({ children }: LayoutProps) {
         </main>
       </div>
       <StatusBar />
+      <InterruptedRunPrompt />
     </div>
   );
 }
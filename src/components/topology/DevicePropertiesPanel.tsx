@@ -17,6 +17,17 @@ interface DevicePropertiesPanelProps {
   allNodes?: Array<{ id: string; data: { deviceType: string } }>; // 所有节点，用于计算端口
 }
 
+// 多速率仿真：设备更新周期（仿真时间，0 表示每步更新）
+const UPDATE_INTERVAL_FIELD = { key: 'update_interval_ms', label: '更新周期', type: 'number' as const, unit: 'ms', defaultValue: 0 };
+
+// 过流保护（线路/变压器）：越过电流或负载率定值并持续动作延时后断开保护开关；定值留空不投入
+const PROTECTION_FIELDS = [
+  { key: 'protection_switch', label: '保护开关', type: 'text' as const, defaultValue: '' },
+  { key: 'protection_pickup_ka', label: '电流定值', type: 'number' as const, unit: 'kA', defaultValue: '' },
+  { key: 'protection_pickup_loading_pct', label: '负载率定值', type: 'number' as const, unit: '%', defaultValue: '' },
+  { key: 'protection_delay_s', label: '保护延时', type: 'number' as const, unit: 's', defaultValue: 0 },
+];
+
 // 设备属性字段定义
 const DEVICE_PROPERTY_FIELDS: Record<string, Array<{
   key: string;
@@ -36,11 +47,25 @@ const DEVICE_PROPERTY_FIELDS: Record<string, Array<{
     { key: 'length_km', label: '长度', type: 'number', unit: 'km', defaultValue: 1 },
     { key: 'r_ohm_per_km', label: '电阻', type: 'number', unit: 'Ω/km', defaultValue: 0.1 },
     { key: 'x_ohm_per_km', label: '电抗', type: 'number', unit: 'Ω/km', defaultValue: 0.1 },
+    ...PROTECTION_FIELDS,
   ],
   transformer: [
     { key: 'sn_mva', label: '额定容量', type: 'number', unit: 'MVA', defaultValue: 1 },
     { key: 'hv_kv', label: '高压侧电压', type: 'number', unit: 'kV', defaultValue: 10 },
     { key: 'lv_kv', label: '低压侧电压', type: 'number', unit: 'kV', defaultValue: 0.4 },
+    { key: 'tap_min', label: '最低档位', type: 'number', defaultValue: -9 },
+    { key: 'tap_max', label: '最高档位', type: 'number', defaultValue: 9 },
+    { key: 'tap_pos', label: '初始档位', type: 'number', defaultValue: 0 },
+    { key: 'tap_step_percent', label: '档距', type: 'number', unit: '%', defaultValue: 1.5 },
+    { key: 'tap_side', label: '分接头位置', type: 'select', options: [
+      { value: 'hv', label: '高压侧' },
+      { value: 'lv', label: '低压侧' },
+    ], defaultValue: 'hv' },
+    { key: 'oltc_regulated_bus', label: '调压母线', type: 'text', defaultValue: '' },
+    { key: 'oltc_target_vm_pu', label: '目标电压', type: 'number', unit: 'pu', defaultValue: 1.0 },
+    { key: 'oltc_deadband_pu', label: '调压死区', type: 'number', unit: 'pu', defaultValue: 0.01 },
+    { key: 'oltc_delay_s', label: '动作延时', type: 'number', unit: 's', defaultValue: 0 },
+    ...PROTECTION_FIELDS,
   ],
   switch: [
     { key: 'is_closed', label: '开关状态', type: 'select', options: [
@@ -51,15 +76,28 @@ const DEVICE_PROPERTY_FIELDS: Record<string, Array<{
   static_generator: [
     { key: 'rated_power_kw', label: '额定功率', type: 'number', unit: 'kW', defaultValue: 100 },
     { key: 'efficiency', label: '效率', type: 'number', unit: '%', defaultValue: 95 },
+    { key: 'panel_kwp', label: '装机容量', type: 'number', unit: 'kWp', defaultValue: 100 },
+    { key: 'panel_tilt_deg', label: '组件倾角', type: 'number', unit: '°', defaultValue: 30 },
+    { key: 'panel_azimuth_deg', label: '组件方位角', type: 'number', unit: '°', defaultValue: 180 },
+    UPDATE_INTERVAL_FIELD,
   ],
   storage: [
     { key: 'capacity_kwh', label: '容量', type: 'number', unit: 'kWh', defaultValue: 100 },
     { key: 'max_power_kw', label: '最大功率', type: 'number', unit: 'kW', defaultValue: 50 },
     { key: 'initial_soc', label: '初始SOC', type: 'number', unit: '%', defaultValue: 50 },
+    { key: 'charge_efficiency', label: '充电效率', type: 'number', defaultValue: 1 },
+    { key: 'discharge_efficiency', label: '放电效率', type: 'number', defaultValue: 1 },
+    { key: 'self_discharge_pct_per_hour', label: '自放电率', type: 'number', unit: '%/h', defaultValue: 0 },
+    { key: 'cycle_fade_pct', label: '循环衰减', type: 'number', unit: '%/次', defaultValue: 0 },
+    { key: 'calendar_fade_pct_per_day', label: '日历衰减', type: 'number', unit: '%/天', defaultValue: 0 },
+    { key: 'inertia_s', label: '虚拟惯量', type: 'number', unit: 's', defaultValue: 2 },
+    { key: 'droop_pct', label: '下垂系数', type: 'number', unit: '%', defaultValue: 5 },
+    UPDATE_INTERVAL_FIELD,
   ],
   load: [
     { key: 'rated_power_kw', label: '额定功率', type: 'number', unit: 'kW', defaultValue: 50 },
     { key: 'power_factor', label: '功率因数', type: 'number', defaultValue: 0.9 },
+    UPDATE_INTERVAL_FIELD,
   ],
   charger: [
     { key: 'rated_power_kw', label: '额定功率', type: 'number', unit: 'kW', defaultValue: 60 },
@@ -67,12 +105,24 @@ const DEVICE_PROPERTY_FIELDS: Record<string, Array<{
       { value: 'dc_fast', label: '直流快充' },
       { value: 'ac_slow', label: '交流慢充' },
     ], defaultValue: 'dc_fast' },
+    { key: 'gun_count', label: '充电枪数量', type: 'number', defaultValue: 2 },
+    UPDATE_INTERVAL_FIELD,
   ],
   meter: [
     { key: 'meter_type', label: '电表类型', type: 'select', options: [
       { value: 'energy', label: '电能表' },
       { value: 'power', label: '功率表' },
     ], defaultValue: 'energy' },
+    { key: 'pt_ratio', label: 'PT变比', type: 'number', defaultValue: 1 },
+    { key: 'ct_ratio', label: 'CT变比', type: 'number', defaultValue: 1 },
+    { key: 'accuracy_class', label: '准确度等级', type: 'select', options: [
+      { value: '0', label: '理想' },
+      { value: '0.2S', label: '0.2S' },
+      { value: '0.5S', label: '0.5S' },
+      { value: '0.5', label: '0.5' },
+      { value: '1', label: '1' },
+      { value: '2', label: '2' },
+    ], defaultValue: '0' },
   ],
   external_grid: [
     { key: 'voltage_kv', label: '电压等级', type: 'number', unit: 'kV', defaultValue: 10 },
@@ -17,6 +17,12 @@ export interface RegisterEntry {
   name?: string;
   /** 语义键，参与仿真更新或 HR 命令的寄存器有值，用于可配置地址 */
   key?: string;
+  /** 数据类型：指定后功率寄存器按该类型编码工程值（32 位类型占两个寄存器），未指定沿用默认编码 */
+  data_type?: 'uint16' | 'int16' | 'uint32' | 'int32' | 'float32';
+  /** 倍率：工程值 = 寄存器值 × scale，默认 1 */
+  scale?: number;
+  /** 字节/字序，默认 ABCD（高字在前） */
+  word_order?: 'ABCD' | 'CDAB' | 'BADC' | 'DCBA';
 }
 
 const REG_TYPES: ModbusRegisterType[] = [
@@ -48,11 +54,11 @@ function getMeterDefaults(): RegisterEntry[] {
     { address: 4, value: 0, type: 'input_registers', name: 'A相电流' },
     { address: 5, value: 0, type: 'input_registers', name: 'B相电流' },
     { address: 6, value: 0, type: 'input_registers', name: 'C相电流' },
-    { address: 7, value: 0, type: 'input_registers', name: '四象限-有功导出(上网,kWh)' },
-    { address: 8, value: 0, type: 'input_registers', name: '四象限-有功导入(下网,kWh)' },
-    { address: 9, value: 0, type: 'input_registers', name: '组合有功总电能(kWh)' },
-    { address: 10, value: 0, type: 'input_registers', name: '四象限-无功导出(kVarh)' },
-    { address: 11, value: 0, type: 'input_registers', name: '四象限-无功导入(kVarh)' },
+    { address: 7, value: 0, type: 'input_registers', name: '四象限-有功导出(上网,kWh)', key: 'energy_export_kwh' },
+    { address: 8, value: 0, type: 'input_registers', name: '四象限-有功导入(下网,kWh)', key: 'energy_import_kwh' },
+    { address: 9, value: 0, type: 'input_registers', name: '组合有功总电能(kWh)', key: 'energy_total_kwh' },
+    { address: 10, value: 0, type: 'input_registers', name: '四象限-无功导出(kVarh)', key: 'energy_export_kvarh' },
+    { address: 11, value: 0, type: 'input_registers', name: '四象限-无功导入(kVarh)', key: 'energy_import_kvarh' },
     { address: 20, value: 0, type: 'input_registers', name: '无功功率(int16,0.5kW)', key: 'reactive_power' },
   ];
 }
@@ -65,9 +71,9 @@ function getStaticGeneratorDefaults(): RegisterEntry[] {
     { address: 5038, value: 0x7fff, type: 'holding_registers', name: '有功功率限制', key: 'power_limit_raw' },
     { address: 5040, value: 0, type: 'holding_registers', name: '无功补偿百分比(-1000~1000=-100%~100%)', key: 'reactive_comp_pct' },
     { address: 5041, value: 0, type: 'holding_registers', name: '功率因数([-1000,-800]/[800,1000]=(-1,-0.8]/[0.8,1])', key: 'power_factor' },
-    { address: 5001, value: 0, type: 'input_registers', name: '额定功率(仅加载拓扑/属性编辑时更新)' },
-    { address: 5003, value: 0, type: 'input_registers', name: '今日发电量' },
-    { address: 5004, value: 0, type: 'input_registers', name: '总发电量' },
+    { address: 5001, value: 0, type: 'input_registers', name: '额定功率(仅加载拓扑/属性编辑时更新)', key: 'rated_power_kw' },
+    { address: 5003, value: 0, type: 'input_registers', name: '今日发电量', key: 'daily_energy_kwh' },
+    { address: 5004, value: 0, type: 'input_registers', name: '总发电量', key: 'total_energy_kwh' },
     { address: 5030, value: 0, type: 'input_registers', name: '当前有功功率(低)', key: 'active_power_low' },
     { address: 5031, value: 0, type: 'input_registers', name: '当前有功功率(高)', key: 'active_power_high' },
     { address: 5032, value: 0, type: 'input_registers', name: '无功功率(低)', key: 'reactive_power_low' },
@@ -83,11 +89,11 @@ function getStorageDefaults(): RegisterEntry[] {
     { address: 5095, value: 0, type: 'holding_registers', name: '并离网模式(0-并网,1-离网)', key: 'grid_mode' },
     { address: 5033, value: 0, type: 'holding_registers', name: 'PCS充放电状态(1-放电,2-充电)', key: 'pcs_charge_discharge_state' },
     { address: 0, value: 3, type: 'input_registers', name: 'state1' },
-    { address: 2, value: 288, type: 'input_registers', name: 'SOC' },
+    { address: 2, value: 288, type: 'input_registers', name: 'SOC', key: 'soc' },
     { address: 8, value: 10000, type: 'input_registers', name: '最大充电功率' },
     { address: 9, value: 10000, type: 'input_registers', name: '最大放电功率' },
-    { address: 12, value: 862, type: 'input_registers', name: '剩余可放电容量' },
-    { address: 39, value: 100, type: 'input_registers', name: '额定容量(仅加载拓扑/属性编辑时更新)' },
+    { address: 12, value: 862, type: 'input_registers', name: '剩余可放电容量', key: 'remaining_energy_kwh' },
+    { address: 39, value: 100, type: 'input_registers', name: '额定容量(仅加载拓扑/属性编辑时更新)', key: 'capacity_kwh' },
     { address: 40, value: 0, type: 'input_registers', name: 'pcs_num' },
     { address: 41, value: 0, type: 'input_registers', name: 'battery_cluster_num' },
     { address: 42, value: 0, type: 'input_registers', name: 'battery_cluster_capacity' },
@@ -102,12 +108,12 @@ function getStorageDefaults(): RegisterEntry[] {
     { address: 414, value: 0, type: 'input_registers', name: 'C相电流' },
     { address: 420, value: 0, type: 'input_registers', name: '有功功率(低)', key: 'active_power_low' },
     { address: 421, value: 0, type: 'input_registers', name: '有功功率(高)', key: 'active_power_high' },
-    { address: 426, value: 0, type: 'input_registers', name: '日充电量' },
-    { address: 427, value: 0, type: 'input_registers', name: '日放电量' },
-    { address: 428, value: 0, type: 'input_registers', name: '累计充电总量(低)' },
-    { address: 429, value: 0, type: 'input_registers', name: '累计充电总量(高)' },
-    { address: 430, value: 0, type: 'input_registers', name: '累计放电总量(低)' },
-    { address: 431, value: 0, type: 'input_registers', name: '累计放电总量(高)' },
+    { address: 426, value: 0, type: 'input_registers', name: '日充电量', key: 'daily_charge_kwh' },
+    { address: 427, value: 0, type: 'input_registers', name: '日放电量', key: 'daily_discharge_kwh' },
+    { address: 428, value: 0, type: 'input_registers', name: '累计充电总量(低)', key: 'total_charge_kwh_low' },
+    { address: 429, value: 0, type: 'input_registers', name: '累计充电总量(高)', key: 'total_charge_kwh_high' },
+    { address: 430, value: 0, type: 'input_registers', name: '累计放电总量(低)', key: 'total_discharge_kwh_low' },
+    { address: 431, value: 0, type: 'input_registers', name: '累计放电总量(高)', key: 'total_discharge_kwh_high' },
     { address: 432, value: 0, type: 'input_registers', name: 'PCS工作模式(bit9-并网,bit10-离网)' },
     { address: 839, value: 240, type: 'input_registers', name: 'state3(240-停机,243/245-正常,242/246-故障)' },
     { address: 900, value: 0, type: 'input_registers', name: 'SN_900' },
@@ -122,7 +128,7 @@ function getChargerDefaults(): RegisterEntry[] {
     { address: 1, value: 1, type: 'input_registers', name: '状态' },
     { address: 2, value: 0, type: 'input_registers', name: '需求功率' },
     { address: 3, value: 0, type: 'input_registers', name: '枪数量' },
-    { address: 4, value: 0, type: 'input_registers', name: '额定功率(仅加载拓扑/属性编辑时更新)' },
+    { address: 4, value: 0, type: 'input_registers', name: '额定功率(仅加载拓扑/属性编辑时更新)', key: 'rated_power_kw' },
     { address: 100, value: 1, type: 'input_registers', name: '枪1状态' },
     { address: 101, value: 2, type: 'input_registers', name: '枪2状态' },
     { address: 102, value: 3, type: 'input_registers', name: '枪3状态' },
@@ -130,6 +136,11 @@ function getChargerDefaults(): RegisterEntry[] {
   ];
 }
 
+/** switch: 线圈 0 合/分闸（1-合闸，0-分闸） */
+function getSwitchDefaults(): RegisterEntry[] {
+  return [{ address: 0, value: 1, type: 'coils', name: '合/分闸', key: 'closed' }];
+}
+
 /** 按设备类型返回 v1.5.0 预定义寄存器列表（前端与后端 get_modbus_register_defaults 一致） */
 export function getPredefinedRegistersForDeviceType(deviceType: string): RegisterEntry[] {
   switch (deviceType) {
@@ -141,6 +152,8 @@ export function getPredefinedRegistersForDeviceType(deviceType: string): Registe
       return getStorageDefaults().map((e) => ({ ...e, value: e.value }));
     case 'charger':
       return getChargerDefaults().map((e) => ({ ...e, value: e.value }));
+    case 'switch':
+      return getSwitchDefaults().map((e) => ({ ...e, value: e.value }));
     default:
       return getMeterDefaults().map((e) => ({ ...e, value: e.value }));
   }
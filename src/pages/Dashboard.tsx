@@ -26,6 +26,7 @@ import PerformanceDataMappingDialog, {
 } from '../components/analytics/PerformanceDataMappingDialog';
 import type { DataItemDisplayConfig } from '../types/dataItemConfig';
 import { transformValue } from '../types/dataItemConfig';
+import { invokeSeriesBatch } from '../utils/seriesCodec';
 
 // ====== 类型定义 ======
 
@@ -37,6 +38,8 @@ interface ColumnMeta {
   device_sn: string;
   data_item: string;
   short_label: string;
+  /** 按设备 ID、名称与别名自动匹配到的拓扑设备 */
+  device_id?: string;
 }
 
 /** 时间序列数据点（与 Rust 端 TimeSeriesPoint 对应） */
@@ -45,10 +48,76 @@ interface TimeSeriesPoint {
   value: number;
 }
 
+/** 宽表列名解析策略（与 Rust 端 ColumnNameParser 对应） */
+type ColumnNameParser =
+  | { kind: 'uppercase_sn_prefix' }
+  | { kind: 'regex'; pattern: string };
+
+/** 内置列名解析模板（与 Rust 端 ColumnNameParser::templates 对应），custom 为自定义正则 */
+const COLUMN_PARSER_OPTIONS: { value: string; label: string; pattern?: string }[] = [
+  { value: 'uppercase_sn_prefix', label: 'SN_数据项（默认）' },
+  { value: 'sn_dot_item', label: 'sn.数据项', pattern: '^(?P<sn>[^.]+)\\.(?P<item>.+)$' },
+  { value: 'device_slash_item', label: '设备/数据项', pattern: '^(?P<sn>[^/]+)/(?P<item>.+)$' },
+  { value: 'item_bracket_sn', label: '数据项[SN]', pattern: '^(?P<item>[^\\[]+)\\[(?P<sn>[^\\]]+)\\]$' },
+  { value: 'custom', label: '自定义正则' },
+];
+
+function buildColumnParser(option: string, customPattern: string): ColumnNameParser {
+  if (option === 'custom') return { kind: 'regex', pattern: customPattern };
+  const pattern = COLUMN_PARSER_OPTIONS.find((o) => o.value === option)?.pattern;
+  return pattern ? { kind: 'regex', pattern } : { kind: 'uppercase_sn_prefix' };
+}
+
+/** 导入数据时区（与 Rust 端 ImportTimezone 对应），用于解析无时区后缀的时间戳及收益分析按小时分桶 */
+type ImportTimezone =
+  | { kind: 'utc' }
+  | { kind: 'local' }
+  | { kind: 'fixed_offset'; offset_minutes: number };
+
+const TIMEZONE_OPTIONS: { value: string; label: string; timezone: ImportTimezone }[] = [
+  { value: 'utc', label: 'UTC', timezone: { kind: 'utc' } },
+  { value: 'cst', label: 'UTC+08:00（北京时间）', timezone: { kind: 'fixed_offset', offset_minutes: 480 } },
+  { value: 'local', label: '本机时区', timezone: { kind: 'local' } },
+];
+
+/** 时区检测提示（与 Rust 端 TimezoneHint 对应） */
+interface TimezoneHint {
+  naive_count: number;
+  offset_count: number;
+  epoch_count: number;
+  suggested: ImportTimezone;
+  suggested_label: string;
+  affects_parsing: boolean;
+  reason: string;
+}
+
+function timezoneOptionOf(tz: ImportTimezone): string {
+  if (tz.kind === 'fixed_offset') {
+    return TIMEZONE_OPTIONS.find(
+      (o) => o.timezone.kind === 'fixed_offset' && o.timezone.offset_minutes === tz.offset_minutes
+    )?.value ?? 'utc';
+  }
+  return tz.kind;
+}
+
+/** 分组标签附带按 ID/名称/别名匹配到的拓扑设备（与原标识相同时不重复显示） */
+function withMatchedDevice(label: string, matchedDeviceId?: string): string {
+  return matchedDeviceId && matchedDeviceId !== label ? `${label} → ${matchedDeviceId}` : label;
+}
+
+/** 列名解析预览（与 Rust 端 ColumnParsePreview 对应） */
+interface ColumnParsePreview {
+  columns: ColumnMeta[];
+  matched: number;
+  unmatched: number;
+  device_sns: string[];
+}
+
 /** 宽表 CSV 解析结果 */
 interface WideTableData {
   columns: ColumnMeta[];
   series: Record<string, TimeSeriesPoint[]>;
+  timezone?: ImportTimezone;
 }
 
 /** 本地 DB 列元信息（与 Rust 端 DbColumnMeta 对应） */
@@ -57,6 +126,8 @@ interface DbColumnMeta {
   device_id: string;
   field_name: string;
   short_label: string;
+  /** 按设备 ID、名称与别名自动匹配到的拓扑设备 */
+  matched_device_id?: string;
 }
 
 /** 分析结果 */
@@ -95,6 +166,7 @@ interface AnalysisRequestPayload {
     rated_capacity_kwh?: number | null;
     alignment_method?: string;
   } | null;
+  timezone?: ImportTimezone | null;
 }
 
 // ====== 常量 ======
@@ -118,6 +190,17 @@ export default function Dashboard() {
   // CSV 数据
   const [csvColumns, setCsvColumns] = useState<ColumnMeta[]>([]);
   const [csvSeries, setCsvSeries] = useState<Record<string, TimeSeriesPoint[]>>({});
+  /** 列名解析策略：内置模板或自定义正则（须含命名组 sn 与 item） */
+  const [columnParserOption, setColumnParserOption] = useState('uppercase_sn_prefix');
+  const [customColumnPattern, setCustomColumnPattern] = useState('^(?P<sn>[^.]+)\\.(?P<item>.+)$');
+  const [parserPreview, setParserPreview] = useState<ColumnParsePreview | null>(null);
+  /** 导入时区（默认 UTC，与旧版本一致）及加载后检测到的建议 */
+  const [timezoneOption, setTimezoneOption] = useState('utc');
+  const [timezoneHint, setTimezoneHint] = useState<TimezoneHint | null>(null);
+  const importTimezone = useMemo(
+    () => TIMEZONE_OPTIONS.find((o) => o.value === timezoneOption)?.timezone ?? { kind: 'utc' as const },
+    [timezoneOption]
+  );
 
   // DB 数据
   const [dbColumns, setDbColumns] = useState<DbColumnMeta[]>([]);
@@ -202,7 +285,13 @@ export default function Dashboard() {
       setIsLoading(true);
       setFilePath(path);
 
-      const result = await invoke<WideTableData>('dashboard_parse_wide_csv', { filePath: path });
+      const result = await invoke<WideTableData>('dashboard_parse_wide_csv', {
+        filePath: path,
+        columnParser: buildColumnParser(columnParserOption, customColumnPattern),
+        timezone: importTimezone,
+      });
+      const hint = await invoke<TimezoneHint>('dashboard_detect_timezone', { filePath: path }).catch(() => null);
+      setTimezoneHint(hint && hint.affects_parsing && timezoneOptionOf(hint.suggested) !== timezoneOption ? hint : null);
       setCsvColumns(result.columns || []);
       setCsvSeries(result.series || {});
       setDbColumns([]);
@@ -219,15 +308,65 @@ export default function Dashboard() {
         groups[groupKey] = true;
       }
       setExpandedGroups(groups);
+      setParserPreview(null);
     } catch (e) {
       setError(String(e));
     } finally {
       setIsLoading(false);
     }
-  }, []);
+  }, [columnParserOption, customColumnPattern, importTimezone, timezoneOption]);
+
+  /** 预览列名解析策略（仅读取表头），确认后按新策略重新解析已加载的 CSV（数据列 key 不变，保留已选列） */
+  const previewColumnParser = useCallback(async () => {
+    if (!filePath || dataSource !== 'csv') return;
+    setError(null);
+    try {
+      const preview = await invoke<ColumnParsePreview>('dashboard_preview_column_parser', {
+        filePath,
+        columnParser: buildColumnParser(columnParserOption, customColumnPattern),
+      });
+      setParserPreview(preview);
+    } catch (e) {
+      setError(String(e));
+    }
+  }, [filePath, dataSource, columnParserOption, customColumnPattern]);
+
+  const applyColumnParser = useCallback(async () => {
+    if (!filePath || dataSource !== 'csv') return;
+    setError(null);
+    try {
+      const result = await invoke<WideTableData>('dashboard_parse_wide_csv', {
+        filePath,
+        columnParser: buildColumnParser(columnParserOption, customColumnPattern),
+        timezone: importTimezone,
+      });
+      setCsvColumns(result.columns || []);
+      setCsvSeries(result.series || {});
+      const groups: Record<string, boolean> = {};
+      for (const col of result.columns || []) {
+        groups[col.device_sn || '未分组'] = true;
+      }
+      setExpandedGroups(groups);
+      setParserPreview(null);
+      setTimezoneHint(null);
+    } catch (e) {
+      setError(String(e));
+    }
+  }, [filePath, dataSource, columnParserOption, customColumnPattern, importTimezone]);
 
   // ====== 列选择 ======
 
+  /** 批量读取 DB 数据列（二进制编码传输，列 key 为 device_id:field_name） */
+  const fetchDbSeries = useCallback(
+    (keys: string[]) =>
+      invokeSeriesBatch('dashboard_fetch_series_batch', {
+        dbPath: filePath,
+        keys,
+        maxPointsPerSeries: 5000,
+      }),
+    [filePath],
+  );
+
   /** 切换选中数据列 */
   const toggleColumn = useCallback(async (key: string) => {
     setSelectedKeys((prev) => {
@@ -245,19 +384,14 @@ export default function Dashboard() {
       const col = dbColumns.find((c) => c.key === key);
       if (col) {
         try {
-          const data = await invoke<TimeSeriesPoint[]>('dashboard_query_db_series', {
-            dbPath: filePath,
-            deviceId: col.device_id,
-            fieldName: col.field_name,
-            maxPoints: 5000,
-          });
-          setDbSeries((prev) => ({ ...prev, [key]: data || [] }));
+          const data = await fetchDbSeries([col.key]);
+          setDbSeries((prev) => ({ ...prev, [key]: data[col.key] || [] }));
         } catch (e) {
           setError(`加载 ${col.short_label} 失败: ${String(e)}`);
         }
       }
     }
-  }, [dataSource, filePath, dbSeries, dbColumns]);
+  }, [dataSource, filePath, dbSeries, dbColumns, fetchDbSeries]);
 
   /** 切换分组展开/折叠 */
   const toggleGroup = useCallback((groupKey: string) => {
@@ -277,28 +411,20 @@ export default function Dashboard() {
         return [...prev, ...newKeys.slice(0, available)];
       });
 
-      // 如果是 DB 数据源，加载未加载的列数据
+      // 如果是 DB 数据源，一次批量加载未加载的列数据
       if (dataSource === 'local_file' && filePath) {
-        const toLoad = groupKeys.filter((k) => !dbSeries[k]);
-        for (const key of toLoad) {
-          const col = dbColumns.find((c) => c.key === key);
-          if (col) {
-            try {
-              const data = await invoke<TimeSeriesPoint[]>('dashboard_query_db_series', {
-                dbPath: filePath,
-                deviceId: col.device_id,
-                fieldName: col.field_name,
-                maxPoints: 5000,
-              });
-              setDbSeries((prev) => ({ ...prev, [key]: data || [] }));
-            } catch {
-              // 静默失败
-            }
+        const toLoad = groupKeys.filter((k) => !dbSeries[k] && dbColumns.some((c) => c.key === k));
+        if (toLoad.length > 0) {
+          try {
+            const data = await fetchDbSeries(toLoad);
+            setDbSeries((prev) => ({ ...prev, ...Object.fromEntries(toLoad.map((k) => [k, data[k] || []])) }));
+          } catch {
+            // 静默失败
           }
         }
       }
     }
-  }, [dataSource, filePath, dbSeries, dbColumns]);
+  }, [dataSource, filePath, dbSeries, dbColumns, fetchDbSeries]);
 
   // ====== 数据分析 ======
 
@@ -422,21 +548,15 @@ export default function Dashboard() {
           const missing = allKeys.filter((k) => !dbSeries[k]);
           if (missing.length > 0) {
             const loaded: Record<string, TimeSeriesPoint[]> = { ...dbSeries };
-            for (const key of missing) {
-              const col = dbColumns.find((c) => c.key === key);
-              if (!col) continue;
-              try {
-                const data = await invoke<TimeSeriesPoint[]>('dashboard_query_db_series', {
-                  dbPath: filePath,
-                  deviceId: col.device_id,
-                  fieldName: col.field_name,
-                  maxPoints: 5000,
-                });
-                loaded[key] = data || [];
-                setDbSeries((prev) => ({ ...prev, [key]: data || [] }));
-              } catch {
-                // 加载失败则跳过，buildSeriesData 会返回 null
+            const toLoad = missing.filter((k) => dbColumns.some((c) => c.key === k));
+            try {
+              const data = await fetchDbSeries(toLoad);
+              for (const key of toLoad) {
+                loaded[key] = data[key] || [];
               }
+              setDbSeries((prev) => ({ ...prev, ...Object.fromEntries(toLoad.map((k) => [k, loaded[k]])) }));
+            } catch {
+              // 加载失败则跳过，buildSeriesData 会返回 null
             }
             seriesOverride = loaded;
           }
@@ -490,6 +610,7 @@ export default function Dashboard() {
                   alignment_method: mapping.alignment_method,
                 }
               : null,
+            timezone: importTimezone,
           };
           const result = await invoke<AnalysisResult>('analyze_performance', { request });
           results.push(result);
@@ -514,6 +635,7 @@ export default function Dashboard() {
       buildSeriesData,
       dbColumns,
       dbSeries,
+      importTimezone,
     ]
   );
 
@@ -609,6 +731,7 @@ export default function Dashboard() {
                 alignment_method: performanceDataMapping.alignment_method,
               }
             : null,
+          timezone: importTimezone,
         };
         await invoke<string>('generate_report', { request: reportRequest });
         setError(null);
@@ -629,6 +752,7 @@ export default function Dashboard() {
     performanceIndicators,
     getTimeRange,
     buildSeriesData,
+    importTimezone,
   ]);
 
   // ====== 构建图表数据 ======
@@ -686,7 +810,7 @@ export default function Dashboard() {
       }
       return Object.entries(groups).map(([groupKey, cols]) => ({
         groupKey,
-        label: groupKey || '未分组',
+        label: withMatchedDevice(groupKey || '未分组', cols[0]?.device_id),
         items: cols.map((c) => ({ key: c.key, label: c.key, dataItem: c.data_item })),
       }));
     }
@@ -698,7 +822,7 @@ export default function Dashboard() {
       }
       return Object.entries(groups).map(([deviceId, cols]) => ({
         groupKey: deviceId,
-        label: deviceId,
+        label: withMatchedDevice(deviceId, cols[0]?.matched_device_id),
         items: cols.map((c) => ({ key: c.key, label: c.key, dataItem: c.field_name })),
       }));
     }
@@ -745,6 +869,84 @@ export default function Dashboard() {
             <FileSpreadsheet className="w-3.5 h-3.5" />
             加载 CSV 文件
           </button>
+          <select
+            value={columnParserOption}
+            onChange={(e) => {
+              setColumnParserOption(e.target.value);
+              setParserPreview(null);
+            }}
+            className="px-2 py-1.5 border border-gray-200 rounded text-xs"
+            title="CSV 列名解析方式"
+          >
+            {COLUMN_PARSER_OPTIONS.map((o) => (
+              <option key={o.value} value={o.value}>
+                {o.label}
+              </option>
+            ))}
+          </select>
+          {columnParserOption === 'custom' && (
+            <input
+              value={customColumnPattern}
+              onChange={(e) => {
+                setCustomColumnPattern(e.target.value);
+                setParserPreview(null);
+              }}
+              className="px-2 py-1.5 border border-gray-200 rounded text-xs font-mono w-64"
+              placeholder="须包含 (?P<sn>...) 与 (?P<item>...)"
+            />
+          )}
+          {dataSource === 'csv' && filePath && (
+            <>
+              <button
+                onClick={previewColumnParser}
+                className="px-2 py-1.5 rounded text-xs bg-gray-100 text-gray-700 hover:bg-gray-200"
+              >
+                预览解析
+              </button>
+              <button
+                onClick={applyColumnParser}
+                className="px-2 py-1.5 rounded text-xs bg-gray-100 text-gray-700 hover:bg-gray-200"
+              >
+                应用
+              </button>
+            </>
+          )}
+          <select
+            value={timezoneOption}
+            onChange={(e) => {
+              setTimezoneOption(e.target.value);
+              setTimezoneHint(null);
+            }}
+            className="px-2 py-1.5 border border-gray-200 rounded text-xs"
+            title="CSV 时间戳时区（无时区后缀的时间按此解析，收益分析按此时区分时段）"
+          >
+            {TIMEZONE_OPTIONS.map((o) => (
+              <option key={o.value} value={o.value}>
+                {o.label}
+              </option>
+            ))}
+          </select>
+          {timezoneHint && (
+            <span className="text-xs text-amber-600" title={timezoneHint.reason}>
+              建议时区 {timezoneHint.suggested_label}
+              {timezoneOptionOf(timezoneHint.suggested) !== 'utc' && (
+                <button
+                  onClick={() => {
+                    setTimezoneOption(timezoneOptionOf(timezoneHint.suggested));
+                    setTimezoneHint(null);
+                  }}
+                  className="ml-1 underline"
+                >
+                  采用（需点「应用」重新解析）
+                </button>
+              )}
+            </span>
+          )}
+          {parserPreview && (
+            <span className="text-xs text-gray-500">
+              匹配 {parserPreview.matched} 列，未匹配 {parserPreview.unmatched} 列，设备 {parserPreview.device_sns.length} 个
+            </span>
+          )}
         </div>
         {filePath && (
           <span className="text-xs text-gray-400 truncate max-w-sm" title={filePath}>
@@ -22,6 +22,16 @@ interface DeviceInfo {
   properties?: Record<string, unknown>;
 }
 
+/** 设备属性配置了 serial_port 时，同时经串口以 Modbus RTU 从站应答 */
+interface ModbusRtuConfig {
+  port_name: string;
+  baud_rate: number;
+  parity: 'none' | 'even' | 'odd';
+  data_bits: number;
+  stop_bits: number;
+  slave_id: number;
+}
+
 // 设备控制：功率设备 + 开关，不包含外部电网（外部电网不需用户控制）
 const POWER_DEVICE_TYPES: DeviceType[] = ['static_generator', 'storage', 'load', 'charger'];
 const CONTROLLABLE_DEVICE_TYPES: DeviceType[] = [...POWER_DEVICE_TYPES, 'switch'];
@@ -40,7 +50,7 @@ function getRatedPowerKw(device: DeviceInfo): number | undefined {
 
 export default function DeviceControl() {
   const [devices, setDevices] = useState<DeviceInfo[]>([]);
-  const [modbusDevices, setModbusDevices] = useState<Array<{ id: string; name: string; device_type: string; ip: string; port: number }>>([]);
+  const [modbusDevices, setModbusDevices] = useState<Array<{ id: string; name: string; device_type: string; ip: string; port: number; unit_id: number; rtu?: ModbusRtuConfig }>>([]);
   const [runningModbusIds, setRunningModbusIds] = useState<string[]>([]);
   const [selectedDevice, setSelectedDevice] = useState<DeviceInfo | null>(null);
   const [configMode, setConfigMode] = useState<DataSourceType | 'sim_params' | 'switch' | null>(null);
@@ -77,6 +87,7 @@ export default function DeviceControl() {
           deviceId,
           minPower: config.randomConfig.minPower,
           maxPower: config.randomConfig.maxPower,
+          profile: config.randomConfig.profile ?? null,
         });
       } else if (type === 'historical' && config?.historicalConfig) {
         await invoke('set_device_historical_config', {
@@ -105,7 +116,7 @@ export default function DeviceControl() {
       // 直接使用后端当前 metadata（由拓扑设计页保存/加载时更新），不先加载 topology.json，避免覆盖用户刚保存的拓扑
       const [devicesMetadata, modbusList, runningIds] = await Promise.all([
         invoke<Array<{ id: string; name: string; device_type: string; properties?: Record<string, unknown> }>>('get_all_devices'),
-        invoke<Array<{ id: string; name: string; device_type: string; ip: string; port: number }>>('get_modbus_devices').catch(() => []),
+        invoke<Array<{ id: string; name: string; device_type: string; ip: string; port: number; unit_id: number; rtu?: ModbusRtuConfig }>>('get_modbus_devices').catch(() => []),
         invoke<string[]>('get_running_modbus_device_ids').catch(() => []),
       ]);
       const powerDevices: DeviceInfo[] = devicesMetadata
@@ -166,7 +177,9 @@ export default function DeviceControl() {
             config: {
               ip_address: modbusDevice.ip,
               port: modbusDevice.port,
+              slave_id: modbusDevice.unit_id,
               registers,
+              rtu: modbusDevice.rtu,
             },
           });
           await invoke('update_device_properties_for_simulation', {
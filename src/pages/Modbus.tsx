@@ -88,7 +88,7 @@ export default function Modbus() {
     setIsLoading(true);
     try {
       // 仅显示拓扑中配置了 ip 和 port 的设备（兼容旧版 chuzhou 与新版拓扑）
-      const modbusDevices = await invoke<Array<{ id: string; name: string; device_type: string; ip: string; port: number }>>('get_modbus_devices');
+      const modbusDevices = await invoke<Array<{ id: string; name: string; device_type: string; ip: string; port: number; unit_id: number }>>('get_modbus_devices');
       const list = modbusDevices.map((d) => ({
         id: d.id,
         name: d.name,
@@ -112,8 +112,8 @@ export default function Modbus() {
           remoteControlAllowed: true,
           ipAddress: device.ip,
           port: device.port,
-          // 当前后端仅按端口区分设备，未真正使用从站ID，这里统一默认 1，避免递增编号造成误解
-          slaveId: 1,
+          // 配置同一 ip/port 的多个设备按从站ID（unit id）区分，取自设备属性，默认 1
+          slaveId: device.unit_id ?? 1,
           registers,
         };
       }
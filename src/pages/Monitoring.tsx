@@ -4,6 +4,7 @@
 import { useState, useEffect, useCallback, useMemo } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
+import { open as openDialog } from '@tauri-apps/plugin-dialog';
 import { 
   RefreshCw, 
   Activity, 
@@ -13,7 +14,10 @@ import {
   XCircle,
   Clock,
   TrendingUp,
-  TrendingDown
+  TrendingDown,
+  History,
+  Play,
+  Square
 } from 'lucide-react';
 import { DEVICE_TYPES, DeviceType } from '../constants/deviceTypes';
 import DataChart from '../components/monitoring/DataChart';
@@ -101,6 +105,30 @@ function DeviceIcon({ type, size = 24 }: { type: DeviceType; size?: number }) {
   );
 }
 
+/** 历史回看：所选历史库及其时间范围 */
+interface HistoryView {
+  dbPath: string;
+  simulation_start: number | null;
+  first_timestamp: number | null;
+  last_timestamp: number | null;
+}
+
+/** 回放状态（get_replay_status / replay-state-changed） */
+interface ReplayStatus {
+  running: boolean;
+  db_path?: string | null;
+  speed: number;
+  current_timestamp?: number | null;
+  finished_reason?: string | null;
+}
+
+const REPLAY_SPEEDS = [1, 2, 5, 10, 60];
+
+function formatCursor(ts: number | null | undefined): string {
+  if (ts == null) return '--';
+  return new Date(ts * 1000).toLocaleString();
+}
+
 export default function Monitoring() {
   const [devices, setDevices] = useState<DeviceStatus[]>([]);
   const [selectedDevice, setSelectedDevice] = useState<string | null>(null);
@@ -110,6 +138,15 @@ export default function Monitoring() {
   const [isLoading, setIsLoading] = useState(false);
   const [currentTime, setCurrentTime] = useState(() => Date.now());
   const [simulationState, setSimulationState] = useState<'Stopped' | 'Running' | 'Paused'>('Stopped');
+  /** 仿真错误涉及的设备（来自 simulation-errors-update 的 device_ids），在设备列表中高亮 */
+  const [errorDeviceIds, setErrorDeviceIds] = useState<Set<string>>(new Set());
+  /** 历史回看模式：非空时设备状态与趋势图均取自该历史库，按游标时刻解析 */
+  const [history, setHistory] = useState<HistoryView | null>(null);
+  const [historyCursor, setHistoryCursor] = useState<number | null>(null);
+  const [historyError, setHistoryError] = useState<string | null>(null);
+  /** 历史库回放：按录制节奏重新推送实时事件并驱动 Modbus，页面以实时模式显示 */
+  const [replay, setReplay] = useState<ReplayStatus | null>(null);
+  const [replaySpeed, setReplaySpeed] = useState(1);
 
   /**
    * 从拓扑元数据加载设备列表（主数据源），再叠加运行时状态。
@@ -132,7 +169,10 @@ export default function Monitoring() {
 
       // 第2步：尝试获取运行时状态（功率、在线状态等），失败时仍保留拓扑基准列表
       try {
-        const statuses = await invoke<DeviceStatus[]>('get_all_devices_status');
+        const statuses = await invoke<DeviceStatus[]>(
+          'get_all_devices_status',
+          history ? { dbPath: history.dbPath, at: historyCursor } : {}
+        );
         if (Array.isArray(statuses) && statuses.length > 0) {
           // 以运行时状态为主，但确保拓扑中的设备不会丢失
           const statusMap = new Map(statuses.map((s) => [s.device_id, s]));
@@ -157,20 +197,23 @@ export default function Monitoring() {
     } finally {
       setIsLoading(false);
     }
-  }, []);
+  }, [history, historyCursor]);
 
   const loadDeviceData = useCallback(async (deviceId: string) => {
     try {
-      const chartStart = await invoke<number | null>('get_latest_simulation_start_time');
-      const now = Date.now() / 1000;
-      const start = chartStart ?? (now - 3600);
+      const dbPath = history?.dbPath ?? null;
+      const chartStart = await invoke<number | null>('get_latest_simulation_start_time', { dbPath });
+      // 回看模式：趋势图截止到游标时刻，与设备状态一致；实时模式不设截止（时间戳为仿真时间，加速时会超前于墙钟）
+      const end = history ? (historyCursor ?? history.last_timestamp ?? Date.now() / 1000) : null;
+      const start = chartStart ?? history?.first_timestamp ?? ((end ?? Date.now() / 1000) - 3600);
       const data = await invoke<DeviceDataPoint[]>(
         'query_device_data',
         {
           deviceId: deviceId,
           startTime: start,
-          endTime: now,
+          endTime: end,
           maxPoints: 2000,
+          dbPath,
         }
       );
       const points: DeviceDataPoint[] = (data || []).map((p) => ({
@@ -192,7 +235,48 @@ export default function Monitoring() {
       console.error('[loadDeviceData] Error:', _error);
       setChartDataPoints([]);
     }
-  }, [selectedChartSeries, devices]);
+  }, [selectedChartSeries, devices, history, historyCursor]);
+
+  const openHistory = useCallback(async () => {
+    setHistoryError(null);
+    try {
+      const selected = await openDialog({ multiple: false, filters: [{ name: '仿真数据库', extensions: ['db', 'sqlite'] }] });
+      if (!selected || Array.isArray(selected)) return;
+      const range = await invoke<Omit<HistoryView, 'dbPath'>>('get_history_time_range', { dbPath: selected });
+      setHistory({ dbPath: selected, ...range });
+      setHistoryCursor(range.last_timestamp);
+    } catch (e) {
+      setHistoryError(String(e));
+    }
+  }, []);
+
+  const exitHistory = useCallback(() => {
+    setHistory(null);
+    setHistoryCursor(null);
+    setHistoryError(null);
+  }, []);
+
+  const startReplay = useCallback(async () => {
+    if (!history) return;
+    setHistoryError(null);
+    try {
+      const status = await invoke<ReplayStatus>('start_replay', {
+        options: { db_path: history.dbPath, speed: replaySpeed, start_at: historyCursor },
+      });
+      setReplay(status);
+      exitHistory();
+    } catch (e) {
+      setHistoryError(String(e));
+    }
+  }, [history, historyCursor, replaySpeed, exitHistory]);
+
+  const stopReplay = useCallback(async () => {
+    try {
+      setReplay(await invoke<ReplayStatus>('stop_replay'));
+    } catch (e) {
+      setHistoryError(String(e));
+    }
+  }, []);
 
   const refreshSimulationStatus = useCallback(async () => {
     try {
@@ -201,6 +285,7 @@ export default function Monitoring() {
     } catch {
       setSimulationState('Stopped');
     }
+    invoke<ReplayStatus>('get_replay_status').then(setReplay).catch(() => setReplay(null));
   }, []);
 
   const overview = useMemo<SystemOverview>(() => {
@@ -223,8 +308,29 @@ export default function Monitoring() {
     return () => clearInterval(statusInterval);
   }, [refreshSimulationStatus]);
 
+  useEffect(() => {
+    // 初次进入页面时拉取当前错误，之后随错误事件更新高亮设备
+    invoke<Array<{ device_id?: string; details?: { device_ids?: string[] } }>>('get_simulation_errors')
+      .then((errors) => {
+        const ids = new Set<string>();
+        (Array.isArray(errors) ? errors : []).forEach((e) => {
+          (e.details?.device_ids ?? (e.device_id ? [e.device_id] : [])).forEach((id) => ids.add(id));
+        });
+        setErrorDeviceIds(ids);
+      })
+      .catch(() => {});
+    const unlistenPromise = listen<{ device_ids?: string[] }>('simulation-errors-update', (event) => {
+      setErrorDeviceIds(new Set(event.payload?.device_ids ?? []));
+    });
+    return () => {
+      unlistenPromise.then((unlisten) => unlisten());
+    };
+  }, []);
+
   useEffect(() => {
     loadDevices();
+    // 回看模式：数据不再变化，只在游标移动时重新解析，不订阅实时推送
+    if (history) return;
     const interval = setInterval(loadDevices, 2000);
     // 首拍完成后后端会写入 device_active，立即拉取一次设备状态，使设备树（含电表）正确显示在线
     const unsubCalcPromise = listen('calculation-result-update', () => {
@@ -262,7 +368,7 @@ export default function Monitoring() {
       unsubCalcPromise.then((unsubscribe) => unsubscribe());
       unsubscribePromise.then((unsubscribe) => unsubscribe());
     };
-  }, [loadDevices, selectedDevice]);
+  }, [loadDevices, selectedDevice, history]);
 
   useEffect(() => {
     if (selectedDevice) loadDeviceData(selectedDevice);
@@ -274,11 +380,69 @@ export default function Monitoring() {
     <div className="flex flex-col h-full bg-gray-50">
       {/* 工具栏 */}
       <div className="px-4 py-2 bg-white border-b border-gray-200 flex items-center gap-4">
-        <h1 className="text-base font-semibold text-gray-800">实时监控</h1>
+        <h1 className="text-base font-semibold text-gray-800">{history ? '历史回看' : '实时监控'}</h1>
+        {history && (
+          <div className="flex items-center gap-2 text-xs text-gray-600 min-w-0">
+            <span className="truncate max-w-[16rem]" title={history.dbPath}>{history.dbPath}</span>
+            {history.first_timestamp != null && history.last_timestamp != null && (
+              <input
+                type="range"
+                min={history.first_timestamp}
+                max={history.last_timestamp}
+                step={1}
+                value={historyCursor ?? history.last_timestamp}
+                onChange={(e) => setHistoryCursor(Number(e.target.value))}
+                className="w-64"
+              />
+            )}
+            <span className="font-mono">{formatCursor(historyCursor)}</span>
+            <select
+              value={replaySpeed}
+              onChange={(e) => setReplaySpeed(Number(e.target.value))}
+              className="px-1 py-0.5 border border-gray-200 rounded"
+              title="回放倍速"
+            >
+              {REPLAY_SPEEDS.map((s) => (
+                <option key={s} value={s}>{s}x</option>
+              ))}
+            </select>
+            <button
+              onClick={startReplay}
+              disabled={simulationState !== 'Stopped'}
+              title={simulationState !== 'Stopped' ? '仿真运行中不可回放' : '从游标时刻起按录制节奏回放（含 Modbus）'}
+              className="flex items-center gap-1 px-2 py-1 bg-gray-100 hover:bg-gray-200 rounded disabled:opacity-50"
+            >
+              <Play className="w-3 h-3" />
+              回放
+            </button>
+          </div>
+        )}
+        {historyError && <span className="text-xs text-red-600 truncate">{historyError}</span>}
         <div className="flex-1" />
-        <span className={`text-xs ${simulationState === 'Running' ? 'text-green-600' : simulationState === 'Paused' ? 'text-amber-600' : 'text-gray-400'}`}>
-          {simulationState === 'Running' ? '仿真运行中' : simulationState === 'Paused' ? '仿真已暂停' : '仿真未启动'}
-        </span>
+        {history ? (
+          <button onClick={exitHistory} className="px-2 py-1 text-xs bg-blue-50 text-blue-700 hover:bg-blue-100 rounded">
+            返回实时
+          </button>
+        ) : (
+          <>
+            <button onClick={openHistory} className="flex items-center gap-1 px-2 py-1 text-xs bg-gray-100 hover:bg-gray-200 rounded">
+              <History className="w-3 h-3" />
+              回看历史库
+            </button>
+            {replay?.running && (
+              <span className="flex items-center gap-2 text-xs text-purple-700">
+                <span title={replay.db_path ?? ''}>回放中 {replay.speed}x · {formatCursor(replay.current_timestamp)}</span>
+                <button onClick={stopReplay} className="flex items-center gap-1 px-2 py-1 bg-purple-50 hover:bg-purple-100 rounded">
+                  <Square className="w-3 h-3" />
+                  停止回放
+                </button>
+              </span>
+            )}
+            <span className={`text-xs ${simulationState === 'Running' ? 'text-green-600' : simulationState === 'Paused' ? 'text-amber-600' : 'text-gray-400'}`}>
+              {simulationState === 'Running' ? '仿真运行中' : simulationState === 'Paused' ? '仿真已暂停' : '仿真未启动'}
+            </span>
+          </>
+        )}
         <button onClick={loadDevices} disabled={isLoading} className="p-1.5 bg-gray-100 hover:bg-gray-200 rounded transition-colors">
           <RefreshCw className={`w-4 h-4 text-gray-600 ${isLoading ? 'animate-spin' : ''}`} />
         </button>
@@ -337,6 +501,7 @@ export default function Monitoring() {
           <div className="p-2 space-y-1">
             {devices.map((device) => {
               const isSelected = selectedDevice === device.device_id;
+              const hasError = errorDeviceIds.has(device.device_id);
               return (
                 <div
                   key={device.device_id}
@@ -347,7 +512,7 @@ export default function Monitoring() {
                     }
                     setSelectedDevice(device.device_id);
                   }}
-                  className={`p-2 rounded cursor-pointer transition-colors ${isSelected ? 'bg-blue-500 text-white' : 'bg-gray-50 hover:bg-gray-100'}`}
+                  className={`p-2 rounded cursor-pointer transition-colors ${isSelected ? 'bg-blue-500 text-white' : hasError ? 'bg-red-50 hover:bg-red-100' : 'bg-gray-50 hover:bg-gray-100'} ${hasError ? 'ring-1 ring-red-400' : ''}`}
                 >
                   <div className="flex items-center gap-2">
                     <div className={`w-8 h-8 rounded flex items-center justify-center ${isSelected ? 'bg-blue-400' : 'bg-white border border-gray-200'}`}>
@@ -356,6 +521,11 @@ export default function Monitoring() {
                     <div className="flex-1 min-w-0">
                       <div className="flex items-center gap-1">
                         <span className={`text-sm font-medium truncate ${isSelected ? 'text-white' : 'text-gray-800'}`}>{device.name}</span>
+                        {hasError && (
+                          <span title="该设备与仿真错误相关">
+                            <AlertTriangle className={`w-3 h-3 ${isSelected ? 'text-yellow-200' : 'text-red-500'}`} />
+                          </span>
+                        )}
                         {device.device_type === 'switch' ? (
                           <span className={`text-xs px-1 rounded ${device.is_closed !== false
                             ? (isSelected ? 'bg-green-300 text-green-800' : 'bg-green-100 text-green-700')
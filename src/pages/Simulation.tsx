@@ -5,7 +5,7 @@ import { useState, useEffect, useCallback } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
 import { useDeviceControlStore } from '../stores/deviceControl';
-import { Play, Pause, Square, RefreshCw, Settings, Radio, Clock, Activity, Zap, AlertTriangle, ChevronDown, ChevronRight } from 'lucide-react';
+import { Play, Pause, Square, RefreshCw, Settings, Radio, Clock, Activity, Zap, AlertTriangle, ChevronDown, ChevronRight, StepForward } from 'lucide-react';
 
 interface SimulationStatus {
   state: 'Stopped' | 'Running' | 'Paused';
@@ -13,6 +13,12 @@ interface SimulationStatus {
   elapsed_time: number;
   calculation_count: number;
   average_delay: number;
+  current_interval_ms?: number;
+  jitter_mean_ms?: number;
+  jitter_max_ms?: number;
+  overrun_count?: number;
+  missed_steps?: number;
+  sim_time?: number | null;
   errors?: SimulationError[];
 }
 
@@ -27,17 +33,55 @@ interface SimulationError {
 
 interface SimulationConfig {
   calculationInterval: number;
+  timeScale: number;
   remoteControlEnabled: boolean;
   autoStartModbus: boolean;
+  /** 自适应计算间隔：按实测步耗时在滑块范围（100~5000 ms）内调整 */
+  adaptiveInterval: boolean;
+  /** 自动停止：仿真时长上限（秒，按仿真时钟）与步数上限；为空时不限 */
+  maxDurationSecs: number | null;
+  maxSteps: number | null;
+}
+
+/** 潮流求解内核能力（后端 get_power_kernels） */
+interface PowerKernel {
+  id: string;
+  name: string;
+  description: string;
+  available: boolean;
+  unavailable_reason?: string | null;
+  unbalanced: boolean;
+  reactive_power: boolean;
+  solver_options: string[];
+  features: string[];
+}
+
+/** Python 环境自检结果（后端 check_python_environment） */
+interface EnvironmentCheck {
+  item: string;
+  status: 'ok' | 'warning' | 'error';
+  detail: string;
+  hint?: string;
+}
+
+interface EnvironmentReport {
+  ready: boolean;
+  mode: 'bundled' | 'script' | 'remote';
+  checks: EnvironmentCheck[];
 }
 
 export default function Simulation() {
   const [status, setStatus] = useState<SimulationStatus>({ state: 'Stopped', elapsed_time: 0, calculation_count: 0, average_delay: 0, errors: [] });
-  const [config, setConfig] = useState<SimulationConfig>({ calculationInterval: 1000, remoteControlEnabled: true, autoStartModbus: false });
+  const [config, setConfig] = useState<SimulationConfig>({ calculationInterval: 1000, timeScale: 1, remoteControlEnabled: true, autoStartModbus: false, adaptiveInterval: false, maxDurationSecs: null, maxSteps: null });
   const [isLoading, setIsLoading] = useState(false);
   const [error, setError] = useState<string | null>(null);
+  const [kernelNotice, setKernelNotice] = useState<string | null>(null);
   const [expandedErrors, setExpandedErrors] = useState<Set<number>>(new Set());
   const [errorFilter, setErrorFilter] = useState<'all' | 'error' | 'warning' | 'info'>('all');
+  const [powerKernels, setPowerKernels] = useState<PowerKernel[]>([]);
+  const [selectedKernel, setSelectedKernel] = useState('pandapower');
+  const [envReport, setEnvReport] = useState<EnvironmentReport | null>(null);
+  const [envChecking, setEnvChecking] = useState(false);
 
   const { deviceConfigs } = useDeviceControlStore();
 
@@ -61,6 +105,32 @@ export default function Simulation() {
     }
   }, []);
 
+  useEffect(() => {
+    invoke<{ kernels: PowerKernel[]; selected: string }>('get_power_kernels')
+      .then((catalog) => { setPowerKernels(catalog.kernels); setSelectedKernel(catalog.selected); })
+      .catch((err) => console.warn('查询计算内核失败:', err));
+  }, []);
+
+  const checkEnvironment = async () => {
+    setEnvChecking(true);
+    try {
+      setEnvReport(await invoke<EnvironmentReport>('check_python_environment'));
+    } catch (err) {
+      alert('环境自检失败：' + err);
+    } finally {
+      setEnvChecking(false);
+    }
+  };
+
+  const changeKernel = async (kernelType: string) => {
+    try {
+      await invoke('set_power_kernel', { kernelType });
+      setSelectedKernel(kernelType);
+    } catch (err) {
+      alert('切换计算内核失败：' + err);
+    }
+  };
+
   useEffect(() => {
     loadStatus();
     const interval = setInterval(loadStatus, 1000);
@@ -77,10 +147,35 @@ export default function Simulation() {
       loadStatus();
     });
 
+    // 运行中内核进程崩溃后自动重启
+    const kernelRestartListener = listen('python-kernel-restarted', (event: any) => {
+      const p = event.payload;
+      setKernelNotice(p?.ok
+        ? `Python 内核进程意外退出，已自动重启（第 ${p.attempt} 次，恢复 ${p.restored_modes ?? 0} 个设备模式）`
+        : `Python 内核重启失败（第 ${p?.attempt} 次）：${p?.error ?? '未知错误'}`);
+      loadStatus();
+    });
+
+    // 空闲时内核看门狗探测失败
+    const kernelUnhealthyListener = listen('python-kernel-unhealthy', (event: any) => {
+      const p = event.payload;
+      setKernelNotice(p?.restarting
+        ? `Python 内核无响应（连续 ${p.consecutive_failures} 次），正在自动重启`
+        : `Python 内核无响应（连续 ${p?.consecutive_failures} 次）：${p?.error ?? '未知错误'}`);
+    });
+
+    // Python 内核启动失败，已切换到内置潮流求解器
+    const kernelFallbackListener = listen<string>('python-kernel-fallback', (event) => {
+      setKernelNotice(event.payload);
+    });
+
     return () => {
       clearInterval(interval);
       errorListener.then(unlisten => unlisten());
       autoStoppedListener.then(unlisten => unlisten());
+      kernelRestartListener.then(unlisten => unlisten());
+      kernelUnhealthyListener.then(unlisten => unlisten());
+      kernelFallbackListener.then(unlisten => unlisten());
     };
   }, [loadStatus]);
 
@@ -96,7 +191,16 @@ export default function Simulation() {
         }
       }
       // 先启动仿真（设置拓扑并启动 Python），再同步手动设定，这样 Python 已有拓扑后再应用功率
-      await invoke('start_simulation', { config: { calculation_interval_ms: config.calculationInterval, remote_control_enabled: config.remoteControlEnabled } });
+      await invoke('start_simulation', {
+        config: {
+          calculation_interval_ms: config.calculationInterval,
+          remote_control_enabled: config.remoteControlEnabled,
+          time_scale: config.timeScale,
+          adaptive_interval: config.adaptiveInterval ? { min_interval_ms: 100, max_interval_ms: 5000 } : null,
+          max_duration_secs: config.maxDurationSecs,
+          max_steps: config.maxSteps,
+        },
+      });
       // 启动后将设备控制中的设定同步到仿真，确保下一拍计算生效
       for (const [deviceId, cfg] of Object.entries(deviceConfigs)) {
         if (cfg?.dataSourceType === 'manual' && cfg.manualSetpoint) {
@@ -111,13 +215,14 @@ export default function Simulation() {
             console.warn('同步设备手动设定失败:', deviceId, e);
           }
         } else if (cfg?.dataSourceType === 'random' && cfg.randomConfig) {
-          const { minPower, maxPower } = cfg.randomConfig;
+          const { minPower, maxPower, profile } = cfg.randomConfig;
           try {
             await invoke('set_device_mode', { deviceId, mode: 'random_data' });
             await invoke('set_device_random_config', {
               deviceId,
               minPower,
               maxPower,
+              profile: profile ?? null,
             });
           } catch (e) {
             console.warn('同步设备随机设定失败:', deviceId, e);
@@ -157,6 +262,12 @@ export default function Simulation() {
     try { await invoke('resume_simulation'); await loadStatus(); } catch (err) { alert('恢复失败：' + err); } finally { setIsLoading(false); }
   };
 
+  // 单步调试：暂停中执行一步计算，完成后保持暂停
+  const handleStep = async () => {
+    setIsLoading(true);
+    try { await invoke('step_simulation'); await loadStatus(); } catch (err) { alert('单步执行失败：' + err); } finally { setIsLoading(false); }
+  };
+
   const toggleRemoteControl = async (enabled: boolean) => {
     try {
       await invoke('set_remote_control_enabled', { enabled });
@@ -250,6 +361,13 @@ export default function Simulation() {
               <span className="text-red-700">{error}</span>
             </div>
           )}
+          {kernelNotice && (
+            <div className="p-3 bg-amber-50 border border-amber-200 rounded flex items-center gap-2 text-sm">
+              <AlertTriangle className="w-4 h-4 text-amber-500" />
+              <span className="text-amber-700 flex-1">{kernelNotice}</span>
+              <button onClick={() => setKernelNotice(null)} className="text-xs text-amber-600 hover:underline">关闭</button>
+            </div>
+          )}
           <div className="bg-white rounded-lg border border-gray-200 p-4">
             <h2 className="text-sm font-semibold text-gray-700 mb-3">仿真控制</h2>
             <div className="flex flex-wrap gap-2">
@@ -265,7 +383,35 @@ export default function Simulation() {
               <button onClick={handleResume} disabled={isLoading || status.state !== 'Paused'} className="px-4 py-2 bg-blue-500 hover:bg-blue-600 rounded text-white text-sm flex items-center gap-1.5 transition-colors disabled:opacity-50">
                 <Play className="w-4 h-4" />恢复
               </button>
+              <button onClick={handleStep} disabled={isLoading || status.state !== 'Paused'} title="暂停中执行一步计算" className="px-4 py-2 bg-gray-500 hover:bg-gray-600 rounded text-white text-sm flex items-center gap-1.5 transition-colors disabled:opacity-50">
+                <StepForward className="w-4 h-4" />单步
+              </button>
+              <button onClick={checkEnvironment} disabled={envChecking} title="定位 Python 内核并检查 pandapower/numpy 等依赖版本" className="px-4 py-2 bg-white hover:bg-gray-100 border border-gray-300 rounded text-gray-700 text-sm flex items-center gap-1.5 transition-colors disabled:opacity-50">
+                <RefreshCw className={`w-4 h-4 ${envChecking ? 'animate-spin' : ''}`} />{envChecking ? '自检中…' : '环境自检'}
+              </button>
             </div>
+            {envReport && (
+              <div className={`mt-3 p-3 rounded border text-xs ${envReport.ready ? 'bg-green-50 border-green-200' : 'bg-red-50 border-red-200'}`}>
+                <div className="flex items-center mb-2">
+                  <span className={`font-medium flex-1 ${envReport.ready ? 'text-green-700' : 'text-red-700'}`}>
+                    {envReport.ready ? 'Python 环境可用' : 'Python 环境存在问题，启动仿真可能失败'}
+                    （{envReport.mode === 'bundled' ? '打包内核' : envReport.mode === 'remote' ? '远程内核' : '脚本内核'}）
+                  </span>
+                  <button onClick={() => setEnvReport(null)} className="text-gray-500 hover:underline">关闭</button>
+                </div>
+                <ul className="space-y-1">
+                  {envReport.checks.map((c, i) => (
+                    <li key={i}>
+                      <span className={c.status === 'ok' ? 'text-green-600' : c.status === 'warning' ? 'text-amber-600' : 'text-red-600'}>
+                        {c.status === 'ok' ? '✓' : c.status === 'warning' ? '!' : '✗'} {c.item}
+                      </span>
+                      <span className="text-gray-600 ml-2 break-all">{c.detail}</span>
+                      {c.hint && <div className="text-gray-500 ml-4">建议：{c.hint}</div>}
+                    </li>
+                  ))}
+                </ul>
+              </div>
+            )}
           </div>
           <div className="bg-white rounded-lg border border-gray-200 p-4">
             <h2 className="text-sm font-semibold text-gray-700 mb-3">运行状态</h2>
@@ -281,10 +427,20 @@ export default function Simulation() {
               <div className="p-3 bg-gray-50 rounded border border-gray-200" title="完成一次仿真计算（请求 Python 潮流计算并回传、写库、发事件）的平均耗时。若大于计算间隔，说明步长跟不上设定周期。">
                 <div className="flex items-center gap-1 text-gray-500 mb-1"><Zap className="w-3 h-3" /><span className="text-xs">每步平均耗时</span></div>
                 <div className="text-xl font-bold text-gray-800">{status.average_delay.toFixed(1)} <span className="text-xs text-gray-500">ms</span></div>
+                {status.state !== 'Stopped' && (
+                  <div className="text-xs text-gray-500 mt-1" title="实际步间隔相对计算间隔的偏差（近 100 步）、超时步数与因超时跳过的节拍数；跳过较多时应增大计算间隔">
+                    间隔 {status.current_interval_ms ?? '-'} ms · 抖动 {(status.jitter_mean_ms ?? 0).toFixed(1)}/{(status.jitter_max_ms ?? 0).toFixed(0)} ms · 超时 {status.overrun_count ?? 0} · 跳过 {status.missed_steps ?? 0}
+                  </div>
+                )}
               </div>
               <div className="p-3 bg-gray-50 rounded border border-gray-200">
                 <div className="flex items-center gap-1 text-gray-500 mb-1"><Clock className="w-3 h-3" /><span className="text-xs">开始时间</span></div>
                 <div className="text-sm font-bold text-gray-800">{status.start_time ? new Date(status.start_time * 1000).toLocaleTimeString() : '-'}</div>
+                {status.sim_time != null && (
+                  <div className="text-xs text-gray-500 mt-1" title="最近一步的仿真时间（落库时间戳），暂停时不前进、加速时按倍率前进">
+                    仿真时间 {new Date(status.sim_time * 1000).toLocaleString('zh-CN', { hour12: false })}
+                  </div>
+                )}
               </div>
             </div>
           </div>
@@ -297,6 +453,40 @@ export default function Simulation() {
                   <input type="range" min="100" max="5000" step="100" value={config.calculationInterval} onChange={(e) => setConfig((prev) => ({ ...prev, calculationInterval: Number(e.target.value) }))} disabled={status.state === 'Running'} className="flex-1 h-2 bg-gray-200 rounded-lg appearance-none cursor-pointer disabled:opacity-50" />
                   <span className="text-gray-700 w-16 text-xs text-right">{config.calculationInterval} ms</span>
                 </div>
+                <label className="flex items-center gap-2 mt-1 text-xs text-gray-600">
+                  <input type="checkbox" checked={config.adaptiveInterval} onChange={(e) => setConfig((prev) => ({ ...prev, adaptiveInterval: e.target.checked }))} disabled={status.state === 'Running'} />
+                  自适应：按实测步耗时在 100~5000 ms 内自动调整（每步仿真时间随之变化）
+                </label>
+              </div>
+              <div>
+                <label className="block text-xs font-medium text-gray-600 mb-1">时间倍率</label>
+                <select value={config.timeScale} onChange={(e) => setConfig((prev) => ({ ...prev, timeScale: Number(e.target.value) }))} disabled={status.state === 'Running'} className="w-full px-2 py-1 text-xs border border-gray-300 rounded disabled:opacity-50">
+                  {[1, 10, 60, 600, 3600].map((s) => <option key={s} value={s}>{s === 1 ? '实时 (1x)' : `${s}x`}</option>)}
+                </select>
+                <div className="text-xs text-gray-500 mt-1">每步仿真时间 = 计算间隔 × 倍率（{(config.calculationInterval / 1000 * config.timeScale).toFixed(1)} s），用于 SOC 与电量积分</div>
+              </div>
+              {powerKernels.length > 0 && (
+                <div>
+                  <label className="block text-xs font-medium text-gray-600 mb-1">计算内核</label>
+                  <select value={selectedKernel} onChange={(e) => changeKernel(e.target.value)} disabled={status.state !== 'Stopped'} className="w-full px-2 py-1 text-xs border border-gray-300 rounded disabled:opacity-50">
+                    {powerKernels.map((k) => (
+                      <option key={k.id} value={k.id} disabled={!k.available} title={k.unavailable_reason ?? k.description}>
+                        {k.name}{k.available ? '' : '（不可用）'}
+                      </option>
+                    ))}
+                  </select>
+                  <div className="text-xs text-gray-500 mt-1">
+                    {powerKernels.find((k) => k.id === selectedKernel)?.description}（下次启动生效）
+                  </div>
+                </div>
+              )}
+              <div>
+                <label className="block text-xs font-medium text-gray-600 mb-1">自动停止（留空不限）</label>
+                <div className="flex items-center gap-2">
+                  <input type="number" min="1" placeholder="仿真时长 (s)" value={config.maxDurationSecs ?? ''} onChange={(e) => setConfig((prev) => ({ ...prev, maxDurationSecs: e.target.value ? Number(e.target.value) : null }))} disabled={status.state === 'Running'} className="w-1/2 px-2 py-1 text-xs border border-gray-300 rounded disabled:opacity-50" />
+                  <input type="number" min="1" step="1" placeholder="步数" value={config.maxSteps ?? ''} onChange={(e) => setConfig((prev) => ({ ...prev, maxSteps: e.target.value ? Math.floor(Number(e.target.value)) : null }))} disabled={status.state === 'Running'} className="w-1/2 px-2 py-1 text-xs border border-gray-300 rounded disabled:opacity-50" />
+                </div>
+                <div className="text-xs text-gray-500 mt-1">达到任一上限后自动停止并生成运行摘要</div>
               </div>
               <div className="p-3 bg-gray-50 rounded border border-gray-200">
                 <div className="flex items-center justify-between">
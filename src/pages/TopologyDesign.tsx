@@ -370,6 +370,8 @@ export default function TopologyDesign() {
 
       updateNodesAndEdges(result.data);
       setCurrentFilePath(filePath as string);
+      // 后台预加载到内核（构建网络并试算），使随后启动仿真可立即步进
+      invoke('preload_topology').catch((e) => console.warn('拓扑预加载失败:', e));
     } catch (error) {
       console.error('Failed to load topology:', error);
       alert('加载失败：' + error);
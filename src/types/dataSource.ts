@@ -14,12 +14,28 @@ export interface ManualSetpoint {
   reactivePower: number;    // 无功功率 (kVar)
 }
 
+// 随机数据生成模型（与 Rust 端 domain/random_profile.rs 对应，参数省略时取后端默认值）
+export type RandomProfile =
+  | { kind: 'uniform' }
+  | { kind: 'ou_noise'; theta_per_s?: number; volatility?: number }
+  | {
+      kind: 'clear_sky_pv';
+      sunrise_hour?: number;
+      sunset_hour?: number;
+      clouds_per_hour?: number;
+      cloud_depth?: number;
+      cloud_duration_s?: number;
+    }
+  | { kind: 'charging_blocks'; sessions_per_hour?: number; mean_session_minutes?: number };
+
 // 随机数据源配置
 export interface RandomConfig {
   minPower: number;         // 最小功率 (kW)
   maxPower: number;         // 最大功率 (kW)
   updateInterval: number;   // 更新间隔 (秒)
   volatility: number;       // 波动率 (0-1)
+  /** 生成模型；未设置时后端按设备类型选择默认模型 */
+  profile?: RandomProfile;
 }
 
 // 列数据源 - 定义CSV中的一列及其单位
@@ -66,7 +82,8 @@ export interface HistoricalConfig {
 // 设备级仿真参数
 export interface DeviceSimParams {
   samplingIntervalMs: number;     // 采集频率（毫秒），0 = 每步更新
-  responseDelayMs: number;        // 响应延迟（毫秒），0 = 无延迟
+  responseDelayMs: number;        // 执行器响应延迟（毫秒）：指令滞后生效，0 = 无延迟
+  sensorDelayMs?: number;         // 传感器上报延迟（毫秒）：测量值滞后显示与落库，0 = 无延迟
   measurementErrorPct: number;    // 测量误差百分比，0 = 无误差
 }
 
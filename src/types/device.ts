@@ -16,7 +16,10 @@ export type WorkMode =
   | "random_data"    // 随机数据模式
   | "manual"         // 手动模式
   | "remote"         // 远程模式
-  | "historical_data"; // 历史数据模式
+  | "historical_data" // 历史数据模式
+  | "solar_model"      // 晴空辐照光伏出力模型
+  | "weather_model"    // 气象数据驱动（风机功率曲线、温度相关负荷）
+  | "ev_session";      // 充电桩电动汽车充电会话模型
 
 export interface DeviceMetadata {
   id: string;
@@ -0,0 +1,106 @@
+/**
+ * 看板序列二进制编码解码（与 Rust 端 services/series_codec.rs 对应）
+ * 布局：小端，8 字节对齐；header "PVSB" | version u8 | 保留 3 字节 | series_count u32 | 填充 4 字节
+ * 每条序列：key_len u32 | point_count u32 | key UTF-8（填充至 8 字节）| timestamps f64×n | values f64×n
+ */
+import { invoke } from '@tauri-apps/api/core';
+
+export interface TimeSeriesPoint {
+  timestamp: number;
+  value: number;
+}
+
+/** 列式序列：直接引用响应缓冲区，避免逐点构造对象 */
+export interface ColumnarSeries {
+  timestamps: Float64Array;
+  values: Float64Array;
+}
+
+export type SeriesEncoding = 'json' | 'binary';
+
+const MAGIC = 'PVSB';
+const SUPPORTED_VERSION = 1;
+
+const align8 = (n: number) => (n + 7) & ~7;
+
+export function decodeSeriesBinary(buffer: ArrayBuffer): Record<string, ColumnarSeries> {
+  const view = new DataView(buffer);
+  const magic = String.fromCharCode(view.getUint8(0), view.getUint8(1), view.getUint8(2), view.getUint8(3));
+  if (magic !== MAGIC) {
+    throw new Error('序列数据格式无效');
+  }
+  const version = view.getUint8(4);
+  if (version > SUPPORTED_VERSION) {
+    throw new Error(`不支持的序列数据版本: ${version}`);
+  }
+  const count = view.getUint32(8, true);
+  const decoder = new TextDecoder();
+  const out: Record<string, ColumnarSeries> = {};
+  let offset = 16;
+  for (let i = 0; i < count; i++) {
+    const keyLen = view.getUint32(offset, true);
+    const n = view.getUint32(offset + 4, true);
+    offset += 8;
+    const key = decoder.decode(new Uint8Array(buffer, offset, keyLen));
+    offset = align8(offset + keyLen);
+    const timestamps = new Float64Array(buffer, offset, n);
+    offset += n * 8;
+    const values = new Float64Array(buffer, offset, n);
+    offset += n * 8;
+    out[key] = { timestamps, values };
+  }
+  return out;
+}
+
+/** 列式序列转为点数组（兼容现有图表组件） */
+export function columnarToPoints(series: ColumnarSeries): TimeSeriesPoint[] {
+  const points: TimeSeriesPoint[] = new Array(series.timestamps.length);
+  for (let i = 0; i < series.timestamps.length; i++) {
+    points[i] = { timestamp: series.timestamps[i], value: series.values[i] };
+  }
+  return points;
+}
+
+/**
+ * 调用返回序列批量的命令（dashboard_fetch_series_batch / data_source_fetch_series），
+ * 协商二进制编码并解码为点数组；encoding 为 json 时行为与原接口一致
+ */
+export async function invokeSeriesBatch(
+  command: string,
+  args: Record<string, unknown>,
+  encoding: SeriesEncoding = 'binary',
+): Promise<Record<string, TimeSeriesPoint[]>> {
+  const result = await invoke<ArrayBuffer | Record<string, TimeSeriesPoint[]>>(command, { ...args, encoding });
+  if (result instanceof ArrayBuffer) {
+    const decoded = decodeSeriesBinary(result);
+    return Object.fromEntries(Object.entries(decoded).map(([k, v]) => [k, columnarToPoints(v)]));
+  }
+  return result;
+}
+
+export interface SeriesTailUpdate {
+  subscription_id: string;
+  db_path: string;
+  series: Record<string, TimeSeriesPoint[]>;
+}
+
+/**
+ * 订阅当前仿真的增量数据：回调只收到各数据列自上次推送以来的新点；仿真停止后订阅结束，返回取消订阅函数
+ */
+export async function subscribeSeriesTail(
+  keys: string[],
+  intervalMs: number,
+  onUpdate: (series: Record<string, TimeSeriesPoint[]>) => void,
+): Promise<() => Promise<void>> {
+  const { listen } = await import('@tauri-apps/api/event');
+  const subscriptionId = await invoke<string>('subscribe_series_tail', { keys, intervalMs });
+  const unlisten = await listen<SeriesTailUpdate>('series-tail-update', (event) => {
+    if (event.payload.subscription_id === subscriptionId) {
+      onUpdate(event.payload.series);
+    }
+  });
+  return async () => {
+    unlisten();
+    await invoke('unsubscribe_series_tail', { subscriptionId });
+  };
+}